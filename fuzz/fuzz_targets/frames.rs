@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol::avro::BuilderImpl;
+
+fuzz_target!(|input: (&[u8], &[u8])| {
+    let builder = match BuilderImpl::global() {
+        Ok(builder) => builder,
+        Err(_) => return,
+    };
+    let (header, payload) = input;
+    let _ = builder.read_protocol_frames(header, payload);
+    if let Ok(joined) = builder.join_frames(header, payload) {
+        let _ = builder.read_protocol_message(&joined);
+    }
+    if let Ok((header, payload)) = builder.split_frames(header) {
+        let joined = builder.join_frames(&header, &payload).unwrap();
+        assert_eq!(
+            builder.read_protocol_message(&joined).ok(),
+            builder.read_protocol_frames(&header, &payload).ok()
+        );
+    }
+});
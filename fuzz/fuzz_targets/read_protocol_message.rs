@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol::avro::BuilderImpl;
+
+// broker input, any bytes must decode or fail without a panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(builder) = BuilderImpl::global() {
+        let _ = builder.read_protocol_message(data);
+    }
+});
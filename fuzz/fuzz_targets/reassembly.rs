@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use protocol::reassembler::Reassembler;
+use protocol::unit_assembler::UnitAssembler;
+
+fuzz_target!(|chunks: Vec<(UnitElementChunkMessage, u16)>| {
+    let mut reassembler = Reassembler::new(1_000);
+    let mut assembler = UnitAssembler::new();
+    let mut now_ms = 0u64;
+    for (chunk, elapsed_ms) in chunks {
+        now_ms += u64::from(elapsed_ms);
+        if let Ok(Some(element)) = reassembler.push(chunk, now_ms) {
+            assembler.push(element);
+        }
+        reassembler.evict_expired(now_ms);
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol::avro::Builder;
+use protocol::message::Message;
+use protocol::objects::ToProtocolMessage;
+use protocol::utils::get_avro_path;
+use std::sync::OnceLock;
+
+// every message which saves must read back as the same record, compared in the
+// Debug form as a NaN field isn't equal to itself
+fuzz_target!(|message: Message| {
+    static BUILDER: OnceLock<Builder> = OnceLock::new();
+    let mb = BUILDER.get_or_init(|| Builder::new(get_avro_path().as_str()));
+    if let Some(packed) = message.save(mb) {
+        let (schema, object) = mb
+            .builder_impl()
+            .read_protocol_message(&mb.save_from_avro(packed.clone()).unwrap())
+            .unwrap();
+        assert_eq!(schema, packed.schema);
+        assert_eq!(format!("{:?}", object), format!("{:?}", packed.object));
+    }
+});
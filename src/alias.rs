@@ -0,0 +1,141 @@
+use crate::objects::services::storage::stream_alias::{
+    ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+};
+use crate::primitives::StreamName;
+use std::collections::HashMap;
+use std::fmt;
+
+pub const MAX_ALIAS_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasError {
+    Invalid(String),
+    Taken {
+        alias: String,
+        stream_name: StreamName,
+    },
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AliasError::Invalid(m) => write!(f, "Invalid alias: {}", m),
+            AliasError::Taken { alias, .. } => {
+                write!(f, "Alias `{}` is attached to another stream", alias)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
+
+/// Aliases are 1 to 64 characters long and consist of lowercase ASCII letters,
+/// digits, `-`, `_` and `.`.
+pub fn validate_alias(alias: &str) -> Result<(), AliasError> {
+    if alias.is_empty() || alias.len() > MAX_ALIAS_LENGTH {
+        return Err(AliasError::Invalid(format!(
+            "`{}` must be 1 to {} characters long",
+            alias, MAX_ALIAS_LENGTH
+        )));
+    }
+    if !alias
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c))
+    {
+        return Err(AliasError::Invalid(format!(
+            "`{}` contains characters other than a-z, 0-9, `-`, `_` and `.`",
+            alias
+        )));
+    }
+    Ok(())
+}
+
+/// Storage of the stream aliases, implemented by the services which keep them.
+///
+/// `set` is the place to enforce the uniqueness: an alias taken by another stream
+/// must be rejected unless the message asks to replace it.
+pub trait AliasRegistry {
+    fn set(&mut self, message: &SetStreamAlias) -> Result<(), AliasError>;
+
+    fn resolve(&self, alias: &str) -> Option<StreamName>;
+
+    fn answer(&self, request: &ResolveAliasRequest) -> ResolveAliasResponse {
+        ResolveAliasResponse::new(
+            request.request_id,
+            request.alias.clone(),
+            self.resolve(&request.alias),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryAliasRegistry {
+    aliases: HashMap<String, StreamName>,
+}
+
+impl AliasRegistry for InMemoryAliasRegistry {
+    fn set(&mut self, message: &SetStreamAlias) -> Result<(), AliasError> {
+        validate_alias(&message.alias)?;
+        match self.aliases.get(&message.alias) {
+            Some(stream_name) if *stream_name != message.stream_name && !message.replace => {
+                Err(AliasError::Taken {
+                    alias: message.alias.clone(),
+                    stream_name: *stream_name,
+                })
+            }
+            _ => {
+                self.aliases
+                    .insert(message.alias.clone(), message.stream_name);
+                Ok(())
+            }
+        }
+    }
+
+    fn resolve(&self, alias: &str) -> Option<StreamName> {
+        self.aliases.get(alias).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alias::{validate_alias, AliasError, AliasRegistry, InMemoryAliasRegistry};
+    use crate::objects::services::storage::stream_alias::{ResolveAliasRequest, SetStreamAlias};
+    use crate::primitives::pack_stream_name;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_registry() {
+        let first = pack_stream_name(&Uuid::from_u128(1));
+        let second = pack_stream_name(&Uuid::from_u128(2));
+        let alias = String::from("front-door-cam");
+
+        let mut registry = InMemoryAliasRegistry::default();
+        assert_eq!(
+            registry.set(&SetStreamAlias::new(alias.clone(), first, false)),
+            Ok(())
+        );
+        assert_eq!(
+            registry.set(&SetStreamAlias::new(alias.clone(), second, false)),
+            Err(AliasError::Taken {
+                alias: alias.clone(),
+                stream_name: first
+            })
+        );
+        assert_eq!(
+            registry.set(&SetStreamAlias::new(alias.clone(), second, true)),
+            Ok(())
+        );
+
+        let rep = registry.answer(&ResolveAliasRequest::new(1, String::from("t"), alias));
+        assert_eq!(rep.stream_name, Some(second));
+        assert_eq!(registry.resolve("back-door-cam"), None);
+    }
+
+    #[test]
+    fn test_validate_alias() {
+        assert!(validate_alias("cam_01.front").is_ok());
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("Front Door").is_err());
+        assert!(validate_alias(&"a".repeat(65)).is_err());
+    }
+}
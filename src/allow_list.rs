@@ -0,0 +1,156 @@
+use crate::avro::{
+    request_address, BuilderImpl, HEARTBEAT_MESSAGE_SCHEMA, KEEPALIVE_MESSAGE_SCHEMA,
+    NOTIFY_MESSAGE_SCHEMA, STREAM_END_MESSAGE_SCHEMA, UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA,
+    UNIT_ELEMENT_MESSAGE_SCHEMA,
+};
+use crate::objects::services::error_response::ErrorResponse;
+use std::collections::{HashMap, HashSet};
+
+/// What a camera publishes: the units it records and the liveness of its streams.
+pub const CAMERA_SCHEMAS: [&str; 6] = [
+    NOTIFY_MESSAGE_SCHEMA,
+    UNIT_ELEMENT_MESSAGE_SCHEMA,
+    UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA,
+    STREAM_END_MESSAGE_SCHEMA,
+    KEEPALIVE_MESSAGE_SCHEMA,
+    HEARTBEAT_MESSAGE_SCHEMA,
+];
+
+/// Message kinds every peer role may send. Peers of unknown roles may send nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowList {
+    roles: HashMap<String, HashSet<String>>,
+}
+
+impl AllowList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow<'a>(mut self, role: &str, schemas: impl IntoIterator<Item = &'a str>) -> Self {
+        self.roles
+            .entry(String::from(role))
+            .or_default()
+            .extend(schemas.into_iter().map(String::from));
+        self
+    }
+
+    pub fn is_allowed(&self, role: &str, schema: &str) -> bool {
+        matches!(self.roles.get(role), Some(schemas) if schemas.contains(schema))
+    }
+
+    pub fn schemas(&self, role: &str) -> Option<&HashSet<String>> {
+        self.roles.get(role)
+    }
+}
+
+/// Message a peer wasn't allowed to send. Requests get an unauthorized answer on
+/// their response topic, the rest is dropped silently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejection {
+    pub role: String,
+    pub schema: Option<String>,
+    pub reply: Option<(String, ErrorResponse)>,
+}
+
+/// Enforces an allow-list on what the peers of a dispatcher or a transport send.
+#[derive(Debug, Clone)]
+pub struct Gate {
+    allow_list: AllowList,
+}
+
+impl Gate {
+    pub fn new(allow_list: AllowList) -> Self {
+        Gate { allow_list }
+    }
+
+    pub fn allow_list(&self) -> &AllowList {
+        &self.allow_list
+    }
+
+    /// Only the envelope is decoded for the admitted messages; messages which
+    /// can't be decoded are rejected too. The rejection is boxed, it's as large as
+    /// the error response it carries.
+    pub fn admit(
+        &self,
+        builder: &BuilderImpl,
+        role: &str,
+        message: &[u8],
+    ) -> Result<(), Box<Rejection>> {
+        let schema = match builder.read_routing_header(message) {
+            Ok(header) if self.allow_list.is_allowed(role, &header.schema) => return Ok(()),
+            Ok(header) => Some(header.schema),
+            Err(_) => None,
+        };
+        let reply = schema.as_ref().and_then(|schema| {
+            let (_, object) = builder.read_protocol_message(message).ok()?;
+            let (request_id, topic) = request_address(&object)?;
+            Some((
+                topic,
+                ErrorResponse::unauthorized(
+                    request_id,
+                    format!("Role `{}` may not send {}", role, schema),
+                ),
+            ))
+        });
+        Err(Box::new(Rejection {
+            role: String::from(role),
+            schema,
+            reply,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::allow_list::{AllowList, Gate, CAMERA_SCHEMAS};
+    use crate::avro::{Builder, BuilderImpl};
+    use crate::objects::services::error_response::ErrorCode;
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_admit() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        let gate = Gate::new(AllowList::new().allow("camera", CAMERA_SCHEMAS));
+
+        let keep_alive = mb
+            .save_from_avro(
+                KeepAliveMessage::new(String::from("camera-1"))
+                    .save(&mb)
+                    .unwrap(),
+            )
+            .unwrap();
+        let request = mb
+            .save_from_avro(
+                StreamTracksRequest::new(
+                    7,
+                    String::from("/camera-1/replies"),
+                    pack_stream_name(&Uuid::from_u128(1)),
+                )
+                .save(&mb)
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(gate.admit(&builder, "camera", &keep_alive), Ok(()));
+
+        let rejection = gate.admit(&builder, "camera", &request).unwrap_err();
+        let (topic, reply) = rejection.reply.unwrap();
+        assert_eq!(topic, "/camera-1/replies");
+        assert_eq!(reply.request_id, 7);
+        assert_eq!(reply.code, ErrorCode::Unauthorized);
+
+        let rejection = gate.admit(&builder, "unknown", &keep_alive).unwrap_err();
+        assert!(rejection.schema.is_some());
+        assert_eq!(rejection.reply, None);
+
+        let rejection = gate.admit(&builder, "camera", &[1, 2, 3]).unwrap_err();
+        assert_eq!(rejection.schema, None);
+    }
+}
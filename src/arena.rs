@@ -0,0 +1,139 @@
+use crate::avro::Builder;
+use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsResponse;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::FromProtocolMessage;
+use crate::primitives::{Attributes, ContinuationCursor, ElementType, Unit};
+
+/// Memory the decoded payloads are placed in, e.g. a bump arena a request handler
+/// resets at once when the request is served.
+pub trait DecodeArena {
+    fn alloc_bytes(&self, data: &[u8]) -> &[u8];
+}
+
+#[cfg(feature = "arena")]
+impl DecodeArena for bumpalo::Bump {
+    fn alloc_bytes(&self, data: &[u8]) -> &[u8] {
+        self.alloc_slice_copy(data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitElementView<'a> {
+    pub stream_unit: Unit,
+    pub element: ElementType,
+    pub value: &'a [u8],
+    pub attributes: Attributes,
+    pub last: bool,
+    pub is_keyframe: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadView<'a> {
+    pub data: &'a [u8],
+    pub attributes: Attributes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitElementsView<'a> {
+    pub request_id: i64,
+    pub stream_unit: Unit,
+    pub values: Vec<PayloadView<'a>>,
+    pub continuation_cursor: Option<ContinuationCursor>,
+    pub has_more: bool,
+}
+
+/// Decodes a unit element keeping its payload in the arena.
+///
+/// The envelope itself is decoded with the global allocator and released before
+/// the call returns, only the payload outlives it.
+pub fn load_unit_element_in<'a, A: DecodeArena>(
+    mb: &Builder,
+    arena: &'a A,
+    message: Vec<u8>,
+) -> Option<UnitElementView<'a>> {
+    let message = UnitElementMessage::load_with_limits(
+        &mb.load_to_avro(message)?,
+        mb.builder_impl().decode_limits(),
+    )?;
+    Some(UnitElementView {
+        stream_unit: message.stream_unit,
+        element: message.element,
+        value: arena.alloc_bytes(&message.value),
+        attributes: message.attributes,
+        last: message.last,
+        is_keyframe: message.is_keyframe,
+    })
+}
+
+/// Decodes a unit elements response keeping the payloads in the arena.
+pub fn load_unit_elements_in<'a, A: DecodeArena>(
+    mb: &Builder,
+    arena: &'a A,
+    message: Vec<u8>,
+) -> Option<UnitElementsView<'a>> {
+    let response = StreamTrackUnitElementsResponse::load(&mb.load_to_avro(message)?)?;
+    Some(UnitElementsView {
+        request_id: response.request_id,
+        stream_unit: response.stream_unit,
+        values: response
+            .values
+            .into_iter()
+            .map(|p| PayloadView {
+                data: arena.alloc_bytes(&p.data),
+                attributes: p.attributes,
+            })
+            .collect(),
+        continuation_cursor: response.continuation_cursor,
+        has_more: response.has_more,
+    })
+}
+
+#[cfg(all(test, feature = "arena"))]
+mod tests {
+    use crate::arena::{load_unit_element_in, load_unit_elements_in};
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsResponse;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{pack_stream_name, pack_track_name, Payload, Unit};
+    use crate::utils::get_avro_path;
+    use bumpalo::Bump;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn unit() -> Unit {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        )
+    }
+
+    #[test]
+    fn test_load_in_arena() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut arena = Bump::new();
+
+        let element = UnitElementMessage::new(unit(), 0, vec![1, 2, 3], HashMap::default(), true);
+        let element_serialized = mb.save_from_avro(element.save(&mb).unwrap()).unwrap();
+        let response = StreamTrackUnitElementsResponse::new(
+            1,
+            unit(),
+            vec![Payload::new(vec![4, 5], HashMap::default())],
+        );
+        let response_serialized = mb.save_from_avro(response.save(&mb).unwrap()).unwrap();
+
+        {
+            let view = load_unit_element_in(&mb, &arena, element_serialized).unwrap();
+            assert_eq!(view.value, &[1, 2, 3]);
+            let view = load_unit_elements_in(&mb, &arena, response_serialized).unwrap();
+            assert_eq!(view.values[0].data, &[4, 5]);
+        }
+        assert!(arena.allocated_bytes() > 0);
+        arena.reset();
+    }
+}
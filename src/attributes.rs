@@ -0,0 +1,207 @@
+use crate::primitives::{AttributeValue, Attributes};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeError {
+    Invalid { key: String, reason: String },
+    TooManyKeys { limit: usize },
+    TooLarge { size: usize, limit: usize },
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::Invalid { key, reason } => {
+                write!(f, "Attribute `{}` is invalid: {}", key, reason)
+            }
+            AttributeError::TooManyKeys { limit } => {
+                write!(f, "Attributes have more than {} keys", limit)
+            }
+            AttributeError::TooLarge { size, limit } => write!(
+                f,
+                "Attributes take {} bytes, at most {} are allowed",
+                size, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+type Validator = Box<dyn Fn(&AttributeValue) -> Result<(), String> + Send + Sync>;
+
+/// Bytes the attribute takes on the wire without the Avro framing, the same way
+/// the store accounts for it.
+pub fn attribute_size(key: &str, value: &AttributeValue) -> usize {
+    key.len()
+        + match value {
+            AttributeValue::String(v) => v.len(),
+            AttributeValue::I64(_) | AttributeValue::F64(_) => 8,
+            AttributeValue::Bool(_) => 1,
+            AttributeValue::Bytes(v) => v.len(),
+            AttributeValue::StringList(v) => v.iter().map(String::len).sum(),
+        }
+}
+
+pub fn attributes_size(attributes: &Attributes) -> usize {
+    attributes
+        .iter()
+        .map(|(key, value)| attribute_size(key, value))
+        .sum()
+}
+
+/// Collects attributes checking every value as soon as it's set, so an oversized
+/// or malformed map is reported by the producer and not rejected by the store later.
+#[derive(Default)]
+pub struct AttributesBuilder {
+    attributes: Attributes,
+    validators: HashMap<String, Validator>,
+    max_keys: Option<usize>,
+    max_bytes: Option<usize>,
+    size: usize,
+}
+
+impl AttributesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_keys(mut self, limit: usize) -> Self {
+        self.max_keys = Some(limit);
+        self
+    }
+
+    pub fn max_bytes(mut self, limit: usize) -> Self {
+        self.max_bytes = Some(limit);
+        self
+    }
+
+    /// Registers the check of the values of `key`, the error message becomes the
+    /// reason of [`AttributeError::Invalid`].
+    pub fn validator<F>(mut self, key: &str, validator: F) -> Self
+    where
+        F: Fn(&AttributeValue) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validators
+            .insert(String::from(key), Box::new(validator));
+        self
+    }
+
+    /// Sets the attribute, replacing the previous value of the key. The builder is
+    /// left as it was when the attribute is rejected.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: impl Into<AttributeValue>,
+    ) -> Result<&mut Self, AttributeError> {
+        let value = value.into();
+        if key.is_empty() {
+            return Err(AttributeError::Invalid {
+                key: String::from(key),
+                reason: String::from("key is empty"),
+            });
+        }
+        if let Some(validator) = self.validators.get(key) {
+            validator(&value).map_err(|reason| AttributeError::Invalid {
+                key: String::from(key),
+                reason,
+            })?;
+        }
+
+        let previous = self.attributes.get(key);
+        if let Some(limit) = self.max_keys {
+            if previous.is_none() && self.attributes.len() >= limit {
+                return Err(AttributeError::TooManyKeys { limit });
+            }
+        }
+        let size = self.size - previous.map(|v| attribute_size(key, v)).unwrap_or_default()
+            + attribute_size(key, &value);
+        if let Some(limit) = self.max_bytes {
+            if size > limit {
+                return Err(AttributeError::TooLarge { size, limit });
+            }
+        }
+
+        self.size = size;
+        self.attributes.insert(String::from(key), value);
+        Ok(self)
+    }
+
+    /// Sets all the attributes of the map, stopping at the first rejected one.
+    pub fn extend(&mut self, attributes: Attributes) -> Result<&mut Self, AttributeError> {
+        for (key, value) in attributes {
+            self.set(&key, value)?;
+        }
+        Ok(self)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<AttributeValue> {
+        let value = self.attributes.remove(key)?;
+        self.size -= attribute_size(key, &value);
+        Some(value)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn build(self) -> Attributes {
+        self.attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attributes::{attributes_size, AttributeError, AttributesBuilder};
+    use crate::primitives::AttributeValue;
+
+    #[test]
+    fn test_builder() {
+        let mut builder = AttributesBuilder::new()
+            .max_keys(2)
+            .max_bytes(32)
+            .validator("codec", |v| match v.as_str() {
+                Some("h264") | Some("hevc") => Ok(()),
+                _ => Err(String::from("must be h264 or hevc")),
+            });
+
+        builder
+            .set("codec", "h264")
+            .unwrap()
+            .set("fps", 25i64)
+            .unwrap();
+        assert_eq!(builder.size(), 5 + 4 + 3 + 8);
+        assert_eq!(
+            builder.set("codec", "vp9").err(),
+            Some(AttributeError::Invalid {
+                key: String::from("codec"),
+                reason: String::from("must be h264 or hevc")
+            })
+        );
+        assert_eq!(
+            builder.set("width", 1920i64).err(),
+            Some(AttributeError::TooManyKeys { limit: 2 })
+        );
+        assert_eq!(
+            builder.set("fps", "twenty five and a bit").err(),
+            Some(AttributeError::TooLarge {
+                size: 33,
+                limit: 32
+            })
+        );
+        assert!(matches!(
+            builder.set("", true),
+            Err(AttributeError::Invalid { .. })
+        ));
+
+        builder.set("fps", 30.0).unwrap();
+        assert_eq!(builder.remove("codec"), Some(AttributeValue::from("h264")));
+        builder.set("width", 1920i64).unwrap();
+
+        let attributes = builder.build();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes.get("fps"), Some(&AttributeValue::F64(30.0)));
+        assert_eq!(attributes_size(&attributes), 3 + 8 + 5 + 8);
+    }
+}
@@ -0,0 +1,77 @@
+use avro_rs::types::Value;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Credentials a request carries in its envelope, e.g. `Bearer` and a JWT. The
+/// protocol passes them as they are, checking them is up to the receiving service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct AuthToken {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub scheme: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub token: String,
+}
+
+impl AuthToken {
+    pub fn new(scheme: String, token: String) -> Self {
+        AuthToken { scheme, token }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl AuthToken {
+    #[new]
+    fn py_new(scheme: String, token: String) -> Self {
+        Self::new(scheme, token)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl AuthToken {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("scheme".into(), Value::String(self.scheme.clone())),
+            ("token".into(), Value::String(self.token.clone())),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(scheme)), (_, Value::String(token))] => {
+                    Some(AuthToken::new(scheme.clone(), token.clone()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Supplies the token attached to the envelopes of the requests.
+pub trait AuthProvider: Send + Sync {
+    /// The token for a request of the schema, `None` sends it unauthenticated.
+    fn token(&self, schema_name: &str) -> Option<AuthToken>;
+}
+
+#[derive(Debug, Clone)]
+pub struct StaticAuthProvider(pub AuthToken);
+
+impl AuthProvider for StaticAuthProvider {
+    fn token(&self, _schema_name: &str) -> Option<AuthToken> {
+        Some(self.0.clone())
+    }
+}
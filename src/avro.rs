@@ -1,42 +1,137 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
 use std::str;
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
 
-use crate::objects::services::ffprobe::{ServicesFFProbeRequest, ServicesFFProbeResponse};
-use crate::objects::services::keep_alive::KeepAliveMessage;
-use crate::objects::services::ping::PingRequestResponse;
-use crate::objects::services::storage::notify_message::NotifyMessage;
-use crate::objects::services::storage::stream_track_unit_elements::{
-    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+#[cfg(feature = "python")]
+use crate::{
+    objects::services::auth::{AuthChallenge, AuthResult},
+    objects::services::error_response::ErrorResponse,
+    objects::services::ffprobe::{ServicesFFProbeRequest, ServicesFFProbeResponse},
+    objects::services::filmstrip::{ServicesFilmstripRequest, ServicesFilmstripResponse},
+    objects::services::heartbeat::HeartbeatMessage,
+    objects::services::inference::InferenceResultMessage,
+    objects::services::keep_alive::KeepAliveMessage,
+    objects::services::maintenance::MaintenanceNotice,
+    objects::services::ping::PingRequestResponse,
+    objects::services::slo_report::SloReport,
+    objects::services::snapshot::{SnapshotRequest, SnapshotResponse},
+    objects::services::storage::backfill::{BackfillRequest, BackfillResponse},
+    objects::services::storage::encoding_profile::{EncodingProfileAck, EncodingProfileOffer},
+    objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest},
+    objects::services::storage::notify_cadence::NotifyCadenceControl,
+    objects::services::storage::notify_digest::{NotifyDigestRequest, NotifyDigestResponse},
+    objects::services::storage::notify_message::NotifyMessage,
+    objects::services::storage::retention::{
+        StoragePurgeRequest, StoragePurgeResponse, StorageRetentionSetRequest,
+        StorageRetentionSetResponse,
+    },
+    objects::services::storage::storage_stats::{StorageStatsRequest, StorageStatsResponse},
+    objects::services::storage::stream_alias::{
+        ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+    },
+    objects::services::storage::stream_deletion::{RestoreStreamRequest, SoftDeleteStreamRequest},
+    objects::services::storage::stream_end_message::StreamEndMessage,
+    objects::services::storage::stream_lifecycle_message::StreamLifecycleMessage,
+    objects::services::storage::stream_multi_track_unit_elements::{
+        StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse,
+    },
+    objects::services::storage::stream_replay::{
+        StreamReplayRequest, StreamReplayStatus, StreamReplayStop,
+    },
+    objects::services::storage::stream_track_keyframes::{
+        StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+    },
+    objects::services::storage::stream_track_unit_elements::{
+        StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+    },
+    objects::services::storage::stream_track_units::{
+        StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+    },
+    objects::services::storage::stream_track_units_by_time::{
+        StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+    },
+    objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse},
+    objects::services::storage::streams_discovery::{
+        StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+    },
+    objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse},
+    objects::services::storage::unit_element_ack_message::UnitElementAckMessage,
+    objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage,
+    objects::services::storage::unit_element_message::UnitElementMessage,
+    objects::services::telemetry::NodeTelemetry,
+    objects::services::transcode::{TranscodeJobCancel, TranscodeJobRequest, TranscodeJobStatus},
+    objects::{FromProtocolMessage, ToProtocolMessage},
+    primitives::{StreamName, TrackInfo},
 };
-use crate::objects::services::storage::stream_track_units::{
-    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
-};
-use crate::objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse};
-use crate::objects::services::storage::unit_element_message::UnitElementMessage;
-use crate::objects::{FromProtocolMessage, ToProtocolMessage};
 use avro_rs::schema::Name;
-use avro_rs::types::{Record, Value};
+use avro_rs::types::Value;
 use avro_rs::{from_avro_datum, to_avro_datum, Schema};
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::PyClass;
 
+use crate::auth::{AuthProvider, AuthToken, StaticAuthProvider};
+use crate::buffer_pool::BufferPool;
+use crate::checksum::{seal_record, verify_record, ChecksumPolicy};
+use crate::encryption::{decrypt_record, encrypt_record, KeyProvider, StaticKeyProvider};
+use crate::fingerprint::schema_sha256;
+use crate::history::{current_version, ProtocolVersion};
+use crate::hops::{Hop, MAX_HOPS};
+use crate::message_kind::MessageKind;
+use crate::primitives::{
+    get_priority_enum, optional_string_to_avro, priority_literal_to_priority, validate_labels,
+    ChecksumAlgorithm, Labels, MessagePriority,
+};
+use crate::scan;
+use crate::signing::{sign_envelope, verify_envelope, SignedEnvelope};
+use crate::trace::TraceContext;
 use crate::utils;
+use crate::writer::DatumWriter;
 
-type SchemaDirectory = HashMap<String, Schema>;
+type SchemaDirectory = HashMap<String, Arc<Schema>>;
+
+/// The envelope fields the signature of a payload is checked against.
+struct EnvelopeSignature<'a> {
+    namespace: &'a str,
+    auth: Option<&'a AuthToken>,
+    key_id: Option<&'a str>,
+    signature: Option<&'a [u8]>,
+}
 
 pub const STORAGE_SCHEMAS: &str = "storage";
 pub const TRACK_TYPE_SCHEMA: &str = "insight.storage.TrackType.avsc";
 pub const TRACK_INFO_SCHEMA: &str = "insight.storage.TrackInfo.avsc";
+pub const TRACK_DESCRIPTOR_SCHEMA: &str = "insight.storage.TrackDescriptor.avsc";
 pub const UNIT_SCHEMA: &str = "insight.storage.Unit.avsc";
+pub const UNIT_RANGE_SCHEMA: &str = "insight.storage.UnitRange.avsc";
+pub const UNIT_TIMESTAMP_SCHEMA: &str = "insight.storage.UnitTimestamp.avsc";
+pub const UNIT_SUMMARY_SCHEMA: &str = "insight.storage.UnitSummary.avsc";
+pub const COMPRESSION_SCHEMA: &str = "insight.storage.Compression.avsc";
+pub const ENCRYPTION_ALGORITHM_SCHEMA: &str = "insight.storage.EncryptionAlgorithm.avsc";
+pub const ENCRYPTION_INFO_SCHEMA: &str = "insight.storage.EncryptionInfo.avsc";
+pub const CHECKSUM_ALGORITHM_SCHEMA: &str = "insight.storage.ChecksumAlgorithm.avsc";
+pub const CHECKSUM_SCHEMA: &str = "insight.storage.Checksum.avsc";
+pub const ARTIFACT_REFERENCE_SCHEMA: &str = "insight.storage.ArtifactReference.avsc";
 pub const UNIT_ELEMENT_MESSAGE_SCHEMA: &str = "insight.storage.UnitElementMessage.avsc";
+pub const UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA: &str = "insight.storage.UnitElementChunkMessage.avsc";
+pub const UNIT_ELEMENT_ACK_MESSAGE_SCHEMA: &str = "insight.storage.UnitElementAckMessage.avsc";
 pub const UNIT_ELEMENT_VALUE_SCHEMA: &str = "insight.storage.UnitElementValue.avsc";
 
 pub const TRANSPORT_SCHEMAS: &str = "transport";
 pub const NOTIFY_MESSAGE_SCHEMA: &str = "insight.transport.NotifyMessage.avsc";
 pub const STREAM_TRACKS_REQUEST_SCHEMA: &str = "insight.transport.StreamTracksRequest.avsc";
 pub const STREAM_TRACKS_RESPONSE_SCHEMA: &str = "insight.transport.StreamTracksResponse.avsc";
+pub const STREAM_DESCRIPTOR_SCHEMA: &str = "insight.transport.StreamDescriptor.avsc";
+pub const STREAMS_DISCOVERY_REQUEST_SCHEMA: &str = "insight.transport.StreamsDiscoveryRequest.avsc";
+pub const STREAMS_DISCOVERY_RESPONSE_SCHEMA: &str =
+    "insight.transport.StreamsDiscoveryResponse.avsc";
 pub const STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA: &str =
     "insight.transport.StreamTrackUnitElementsRequest.avsc";
 pub const STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA: &str =
@@ -45,29 +140,339 @@ pub const STREAM_TRACK_UNITS_REQUEST_SCHEMA: &str =
     "insight.transport.StreamTrackUnitsRequest.avsc";
 pub const STREAM_TRACK_UNITS_RESPONSE_SCHEMA: &str =
     "insight.transport.StreamTrackUnitsResponse.avsc";
+pub const STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA: &str =
+    "insight.transport.StreamTrackUnitsByTimeRequest.avsc";
+pub const STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA: &str =
+    "insight.transport.StreamTrackUnitsByTimeResponse.avsc";
+pub const STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA: &str =
+    "insight.transport.StreamTrackKeyframesRequest.avsc";
+pub const STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA: &str =
+    "insight.transport.StreamTrackKeyframesResponse.avsc";
+pub const TRACK_DIGEST_SCHEMA: &str = "insight.transport.TrackDigest.avsc";
+pub const NOTIFY_DIGEST_REQUEST_SCHEMA: &str = "insight.transport.NotifyDigestRequest.avsc";
+pub const NOTIFY_DIGEST_RESPONSE_SCHEMA: &str = "insight.transport.NotifyDigestResponse.avsc";
+pub const FLOW_CONTROL_GRANT_SCHEMA: &str = "insight.transport.FlowControlGrant.avsc";
+pub const FLOW_CONTROL_REQUEST_SCHEMA: &str = "insight.transport.FlowControlRequest.avsc";
+pub const NOTIFY_CADENCE_CONTROL_SCHEMA: &str = "insight.transport.NotifyCadenceControl.avsc";
+pub const SET_STREAM_ALIAS_SCHEMA: &str = "insight.transport.SetStreamAlias.avsc";
+pub const RESOLVE_ALIAS_REQUEST_SCHEMA: &str = "insight.transport.ResolveAliasRequest.avsc";
+pub const RESOLVE_ALIAS_RESPONSE_SCHEMA: &str = "insight.transport.ResolveAliasResponse.avsc";
+pub const MESSAGE_PRIORITY_SCHEMA: &str = "insight.transport.MessagePriority.avsc";
 pub const MESSAGE_ENVELOPE_SCHEMA: &str = "insight.transport.MessageEnvelope.avsc";
 pub const PING_REQUEST_RESPONSE_SCHEMA: &str = "insight.transport.PingRequestResponse.avsc";
 pub const KEEPALIVE_MESSAGE_SCHEMA: &str = "insight.transport.KeepAliveMessage.avsc";
+pub const STREAM_END_MESSAGE_SCHEMA: &str = "insight.transport.StreamEndMessage.avsc";
+pub const STREAM_LIFECYCLE_MESSAGE_SCHEMA: &str = "insight.transport.StreamLifecycleMessage.avsc";
+pub const SOFT_DELETE_STREAM_REQUEST_SCHEMA: &str =
+    "insight.transport.SoftDeleteStreamRequest.avsc";
+pub const RESTORE_STREAM_REQUEST_SCHEMA: &str = "insight.transport.RestoreStreamRequest.avsc";
+pub const SLO_REPORT_SCHEMA: &str = "insight.transport.SloReport.avsc";
+pub const ERROR_RESPONSE_SCHEMA: &str = "insight.transport.ErrorResponse.avsc";
+pub const MAINTENANCE_NOTICE_SCHEMA: &str = "insight.transport.MaintenanceNotice.avsc";
+pub const UNIT_BITMAP_REQUEST_SCHEMA: &str = "insight.transport.UnitBitmapRequest.avsc";
+pub const UNIT_BITMAP_RESPONSE_SCHEMA: &str = "insight.transport.UnitBitmapResponse.avsc";
+pub const BACKFILL_REQUEST_SCHEMA: &str = "insight.transport.BackfillRequest.avsc";
+pub const BACKFILL_RESPONSE_SCHEMA: &str = "insight.transport.BackfillResponse.avsc";
+pub const ENCODING_PROFILE_SCHEMA: &str = "insight.transport.EncodingProfile.avsc";
+pub const ENCODING_PROFILE_OFFER_SCHEMA: &str = "insight.transport.EncodingProfileOffer.avsc";
+pub const ENCODING_PROFILE_ACK_SCHEMA: &str = "insight.transport.EncodingProfileAck.avsc";
+pub const RETENTION_POLICY_SCHEMA: &str = "insight.transport.RetentionPolicy.avsc";
+pub const STORAGE_RETENTION_SET_REQUEST_SCHEMA: &str =
+    "insight.transport.StorageRetentionSetRequest.avsc";
+pub const STORAGE_RETENTION_SET_RESPONSE_SCHEMA: &str =
+    "insight.transport.StorageRetentionSetResponse.avsc";
+pub const STORAGE_PURGE_REQUEST_SCHEMA: &str = "insight.transport.StoragePurgeRequest.avsc";
+pub const STORAGE_PURGE_RESPONSE_SCHEMA: &str = "insight.transport.StoragePurgeResponse.avsc";
+pub const TRACK_STATS_SCHEMA: &str = "insight.transport.TrackStats.avsc";
+pub const STREAM_STATS_SCHEMA: &str = "insight.transport.StreamStats.avsc";
+pub const STORAGE_STATS_REQUEST_SCHEMA: &str = "insight.transport.StorageStatsRequest.avsc";
+pub const STORAGE_STATS_RESPONSE_SCHEMA: &str = "insight.transport.StorageStatsResponse.avsc";
+pub const STREAM_REPLAY_REQUEST_SCHEMA: &str = "insight.transport.StreamReplayRequest.avsc";
+pub const STREAM_REPLAY_STATUS_SCHEMA: &str = "insight.transport.StreamReplayStatus.avsc";
+pub const STREAM_REPLAY_STOP_SCHEMA: &str = "insight.transport.StreamReplayStop.avsc";
+pub const TRACK_ELEMENTS_SCHEMA: &str = "insight.transport.TrackElements.avsc";
+pub const STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA: &str =
+    "insight.transport.StreamMultiTrackUnitElementsRequest.avsc";
+pub const STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA: &str =
+    "insight.transport.StreamMultiTrackUnitElementsResponse.avsc";
+pub const NODE_TELEMETRY_SCHEMA: &str = "insight.transport.NodeTelemetry.avsc";
+pub const HEARTBEAT_MESSAGE_SCHEMA: &str = "insight.transport.Heartbeat.avsc";
+pub const AUTH_TOKEN_SCHEMA: &str = "insight.transport.AuthToken.avsc";
+pub const AUTH_CHALLENGE_SCHEMA: &str = "insight.transport.AuthChallenge.avsc";
+pub const AUTH_RESULT_SCHEMA: &str = "insight.transport.AuthResult.avsc";
 
 pub const SERVICE_FFPROBE_SCHEMAS: &str = "services/ffprobe";
+pub const SERVICES_FFPROBE_STREAM_INFO_SCHEMA: &str = "insight.ffprobe.StreamInfo.avsc";
+pub const SERVICES_FFPROBE_FORMAT_INFO_SCHEMA: &str = "insight.ffprobe.FormatInfo.avsc";
 pub const SERVICES_FFPROBE_REQUEST_SCHEMA: &str = "insight.ffprobe.Request.avsc";
 pub const SERVICES_FFPROBE_RESPONSE_SCHEMA: &str = "insight.ffprobe.Response.avsc";
 
+pub const SERVICE_FILMSTRIP_SCHEMAS: &str = "services/filmstrip";
+pub const SERVICES_FILMSTRIP_REQUEST_SCHEMA: &str = "insight.filmstrip.Request.avsc";
+pub const SERVICES_FILMSTRIP_RESPONSE_SCHEMA: &str = "insight.filmstrip.Response.avsc";
+
+pub const SERVICE_TRANSCODE_SCHEMAS: &str = "services/transcode";
+pub const SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA: &str = "insight.transcode.JobRequest.avsc";
+pub const SERVICES_TRANSCODE_JOB_STATUS_SCHEMA: &str = "insight.transcode.JobStatus.avsc";
+pub const SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA: &str = "insight.transcode.JobCancel.avsc";
+
+pub const SERVICE_SNAPSHOT_SCHEMAS: &str = "services/snapshot";
+pub const SERVICES_SNAPSHOT_REQUEST_SCHEMA: &str = "insight.snapshot.Request.avsc";
+pub const SERVICES_SNAPSHOT_RESPONSE_SCHEMA: &str = "insight.snapshot.Response.avsc";
+
+pub const SERVICE_INFERENCE_SCHEMAS: &str = "services/inference";
+pub const SERVICES_INFERENCE_BOUNDING_BOX_SCHEMA: &str = "insight.inference.BoundingBox.avsc";
+pub const SERVICES_INFERENCE_DETECTION_SCHEMA: &str = "insight.inference.Detection.avsc";
+pub const SERVICES_INFERENCE_RESULT_SCHEMA: &str = "insight.inference.Result.avsc";
+
+/// Envelope fields routers and proxies look at and may rewrite.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RoutingHeader {
+    pub schema: String,
+    pub namespace: String,
+    pub labels: Labels,
+    pub trace_context: Option<TraceContext>,
+    pub priority: MessagePriority,
+    pub hops: Vec<Hop>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolError {
+    Decode(String),
+    SignatureMismatch,
+    InvalidLabels(String),
+    ChecksumMismatch(String),
+    Encode(String),
+    LimitExceeded(String),
+    /// The message was written with another version of its schema, the name is
+    /// the one of the schema.
+    SchemaMismatch(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Decode(m) => write!(f, "{}", m),
+            ProtocolError::SignatureMismatch => {
+                write!(f, "Envelope signature is missing or does not match")
+            }
+            ProtocolError::InvalidLabels(m) => write!(f, "Invalid envelope labels: {}", m),
+            ProtocolError::ChecksumMismatch(m) => write!(f, "Payload is corrupted: {}", m),
+            ProtocolError::Encode(m) => write!(f, "Unable to encode the message: {}", m),
+            ProtocolError::LimitExceeded(m) => write!(f, "Message exceeds a decode limit: {}", m),
+            ProtocolError::SchemaMismatch(schema) => write!(
+                f,
+                "Message is written with another version of the {} schema",
+                schema
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaLoadError {
+    File(String),
+    Parse(String),
+}
+
+impl fmt::Display for SchemaLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaLoadError::File(m) => write!(f, "{}", m),
+            SchemaLoadError::Parse(m) => write!(f, "Unable to parse the schemas: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for SchemaLoadError {}
+
+/// Bounds of the messages a builder decodes, so a malformed or hostile message is
+/// rejected before it's materialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    pub max_message_size: usize,
+    pub max_payload_size: usize,
+    /// Entries of a single attributes map.
+    pub max_attributes: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_message_size: 256 * 1024 * 1024,
+            max_payload_size: 256 * 1024 * 1024,
+            max_attributes: 4096,
+        }
+    }
+}
+
+impl DecodeLimits {
+    fn check_message(&self, message: &[u8]) -> Result<(), ProtocolError> {
+        if message.len() > self.max_message_size {
+            return Err(ProtocolError::LimitExceeded(format!(
+                "the message takes {} bytes, at most {} are allowed",
+                message.len(),
+                self.max_message_size
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_payload(&self, payload: &[u8]) -> Result<(), ProtocolError> {
+        if payload.len() > self.max_payload_size {
+            return Err(ProtocolError::LimitExceeded(format!(
+                "the payload takes {} bytes, at most {} are allowed",
+                payload.len(),
+                self.max_payload_size
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_attributes(&self, value: &Value) -> Result<(), ProtocolError> {
+        match value {
+            Value::Union(inner) => self.check_attributes(inner),
+            Value::Array(items) => items
+                .iter()
+                .try_for_each(|item| self.check_attributes(item)),
+            Value::Record(fields) => fields.iter().try_for_each(|(name, value)| match value {
+                Value::Map(attributes)
+                    if name == "attributes" && attributes.len() > self.max_attributes =>
+                {
+                    Err(ProtocolError::LimitExceeded(format!(
+                        "{} attributes, at most {} are allowed",
+                        attributes.len(),
+                        self.max_attributes
+                    )))
+                }
+                value => self.check_attributes(value),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks the decoded record of an extension schema and may rewrite it, e.g. to
+/// fill a field the older plugins don't send; the error fails the decoding.
+pub type ExtensionDecoder = Box<dyn Fn(Value) -> Result<Value, String> + Send + Sync>;
+
+struct Extension {
+    schema: Arc<Schema>,
+    decoder: ExtensionDecoder,
+}
+
+/// Schemas of the builder with their writers and fingerprints, replaced all at once
+/// by a reload.
+struct LoadedSchemas {
+    directory: SchemaDirectory,
+    writers: HashMap<String, DatumWriter>,
+    fingerprints: HashMap<String, [u8; 32]>,
+}
+
+impl LoadedSchemas {
+    fn new(directory: SchemaDirectory) -> Self {
+        LoadedSchemas {
+            writers: BuilderImpl::compile_writers(&directory),
+            fingerprints: BuilderImpl::fingerprint_schemas(&directory),
+            directory,
+        }
+    }
+}
+
+/// Record of a catalog schema being filled, as [`avro_rs::types::Record`] but
+/// holding its schema, so a reload doesn't pull it from under the record.
+pub struct SchemaRecord {
+    schema: Arc<Schema>,
+    fields: Vec<(String, Value)>,
+}
+
+impl SchemaRecord {
+    /// `None` when the schema isn't a record one.
+    pub fn new(schema: Arc<Schema>) -> Option<Self> {
+        let fields = match &*schema {
+            Schema::Record { fields, .. } => fields
+                .iter()
+                .map(|field| (field.name.clone(), Value::Null))
+                .collect(),
+            _ => return None,
+        };
+        Some(SchemaRecord { schema, fields })
+    }
+
+    /// Fields which the schema doesn't have are ignored.
+    pub fn put<V: Into<Value>>(&mut self, field: &str, value: V) {
+        if let Schema::Record { lookup, .. } = &*self.schema {
+            if let Some(&position) = lookup.get(field) {
+                self.fields[position].1 = value.into();
+            }
+        }
+    }
+}
+
+impl From<SchemaRecord> for Value {
+    fn from(record: SchemaRecord) -> Self {
+        Value::Record(record.fields)
+    }
+}
+
+/// Schemas of another protocol version, see [`BuilderImpl::add_version`].
+struct SchemaSet {
+    version: ProtocolVersion,
+    directory: SchemaDirectory,
+    fingerprints: HashMap<String, [u8; 32]>,
+}
+
 pub struct BuilderImpl {
-    pub directory: SchemaDirectory,
+    // a message is encoded or decoded with the schemas current when it started
+    schemas: RwLock<Arc<LoadedSchemas>>,
+    versions: Vec<SchemaSet>,
+    extensions: HashMap<String, Extension>,
+    path_prefix: String,
+    signing_key: Option<(String, Vec<u8>)>,
+    verification_keys: HashMap<String, Vec<u8>>,
+    key_provider: Option<Box<dyn KeyProvider>>,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    checksum_policy: ChecksumPolicy,
+    auth_provider: Option<Box<dyn AuthProvider>>,
+    namespace: String,
+    buffer_pool: Option<Arc<BufferPool>>,
+    decode_limits: DecodeLimits,
 }
 
+// a builder is shared by the threads of a runtime, keep it so
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BuilderImpl>();
+};
+
 impl BuilderImpl {
     pub fn schema_files() -> Vec<(&'static str, &'static str)> {
         vec![
             (STORAGE_SCHEMAS, TRACK_TYPE_SCHEMA),
             (STORAGE_SCHEMAS, TRACK_INFO_SCHEMA),
+            (STORAGE_SCHEMAS, TRACK_DESCRIPTOR_SCHEMA),
             (STORAGE_SCHEMAS, UNIT_SCHEMA),
+            (STORAGE_SCHEMAS, UNIT_RANGE_SCHEMA),
+            (STORAGE_SCHEMAS, UNIT_TIMESTAMP_SCHEMA),
+            (STORAGE_SCHEMAS, UNIT_SUMMARY_SCHEMA),
             (STORAGE_SCHEMAS, UNIT_ELEMENT_VALUE_SCHEMA),
+            (STORAGE_SCHEMAS, COMPRESSION_SCHEMA),
+            (STORAGE_SCHEMAS, ENCRYPTION_ALGORITHM_SCHEMA),
+            (STORAGE_SCHEMAS, ENCRYPTION_INFO_SCHEMA),
+            (STORAGE_SCHEMAS, CHECKSUM_ALGORITHM_SCHEMA),
+            (STORAGE_SCHEMAS, CHECKSUM_SCHEMA),
+            (STORAGE_SCHEMAS, ARTIFACT_REFERENCE_SCHEMA),
             (STORAGE_SCHEMAS, UNIT_ELEMENT_MESSAGE_SCHEMA),
+            (STORAGE_SCHEMAS, UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA),
+            (STORAGE_SCHEMAS, UNIT_ELEMENT_ACK_MESSAGE_SCHEMA),
             (TRANSPORT_SCHEMAS, NOTIFY_MESSAGE_SCHEMA),
             (TRANSPORT_SCHEMAS, STREAM_TRACKS_REQUEST_SCHEMA),
             (TRANSPORT_SCHEMAS, STREAM_TRACKS_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_DESCRIPTOR_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAMS_DISCOVERY_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAMS_DISCOVERY_RESPONSE_SCHEMA),
             (TRANSPORT_SCHEMAS, STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA),
             (
                 TRANSPORT_SCHEMAS,
@@ -75,27 +480,405 @@ impl BuilderImpl {
             ),
             (TRANSPORT_SCHEMAS, STREAM_TRACK_UNITS_REQUEST_SCHEMA),
             (TRANSPORT_SCHEMAS, STREAM_TRACK_UNITS_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA),
+            (
+                TRANSPORT_SCHEMAS,
+                STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+            ),
+            (TRANSPORT_SCHEMAS, STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, TRACK_DIGEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, NOTIFY_DIGEST_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, NOTIFY_DIGEST_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, FLOW_CONTROL_GRANT_SCHEMA),
+            (TRANSPORT_SCHEMAS, FLOW_CONTROL_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, NOTIFY_CADENCE_CONTROL_SCHEMA),
+            (TRANSPORT_SCHEMAS, SET_STREAM_ALIAS_SCHEMA),
+            (TRANSPORT_SCHEMAS, RESOLVE_ALIAS_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, RESOLVE_ALIAS_RESPONSE_SCHEMA),
             (TRANSPORT_SCHEMAS, PING_REQUEST_RESPONSE_SCHEMA),
             (TRANSPORT_SCHEMAS, KEEPALIVE_MESSAGE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_END_MESSAGE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_LIFECYCLE_MESSAGE_SCHEMA),
+            (TRANSPORT_SCHEMAS, SOFT_DELETE_STREAM_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, RESTORE_STREAM_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, SLO_REPORT_SCHEMA),
+            (TRANSPORT_SCHEMAS, ERROR_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, MAINTENANCE_NOTICE_SCHEMA),
+            (TRANSPORT_SCHEMAS, UNIT_BITMAP_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, UNIT_BITMAP_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, BACKFILL_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, BACKFILL_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, ENCODING_PROFILE_SCHEMA),
+            (TRANSPORT_SCHEMAS, ENCODING_PROFILE_OFFER_SCHEMA),
+            (TRANSPORT_SCHEMAS, ENCODING_PROFILE_ACK_SCHEMA),
+            (TRANSPORT_SCHEMAS, RETENTION_POLICY_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_RETENTION_SET_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_RETENTION_SET_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_PURGE_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_PURGE_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, TRACK_STATS_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_STATS_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_STATS_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STORAGE_STATS_RESPONSE_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_REPLAY_REQUEST_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_REPLAY_STATUS_SCHEMA),
+            (TRANSPORT_SCHEMAS, STREAM_REPLAY_STOP_SCHEMA),
+            (TRANSPORT_SCHEMAS, TRACK_ELEMENTS_SCHEMA),
+            (
+                TRANSPORT_SCHEMAS,
+                STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+            ),
+            (
+                TRANSPORT_SCHEMAS,
+                STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+            ),
+            (TRANSPORT_SCHEMAS, NODE_TELEMETRY_SCHEMA),
+            (TRANSPORT_SCHEMAS, HEARTBEAT_MESSAGE_SCHEMA),
+            (TRANSPORT_SCHEMAS, AUTH_TOKEN_SCHEMA),
+            (TRANSPORT_SCHEMAS, AUTH_CHALLENGE_SCHEMA),
+            (TRANSPORT_SCHEMAS, AUTH_RESULT_SCHEMA),
+            (TRANSPORT_SCHEMAS, MESSAGE_PRIORITY_SCHEMA),
             (TRANSPORT_SCHEMAS, MESSAGE_ENVELOPE_SCHEMA),
+            (SERVICE_FFPROBE_SCHEMAS, SERVICES_FFPROBE_STREAM_INFO_SCHEMA),
+            (SERVICE_FFPROBE_SCHEMAS, SERVICES_FFPROBE_FORMAT_INFO_SCHEMA),
             (SERVICE_FFPROBE_SCHEMAS, SERVICES_FFPROBE_REQUEST_SCHEMA),
             (SERVICE_FFPROBE_SCHEMAS, SERVICES_FFPROBE_RESPONSE_SCHEMA),
+            (SERVICE_FILMSTRIP_SCHEMAS, SERVICES_FILMSTRIP_REQUEST_SCHEMA),
+            (
+                SERVICE_FILMSTRIP_SCHEMAS,
+                SERVICES_FILMSTRIP_RESPONSE_SCHEMA,
+            ),
+            (
+                SERVICE_TRANSCODE_SCHEMAS,
+                SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA,
+            ),
+            (
+                SERVICE_TRANSCODE_SCHEMAS,
+                SERVICES_TRANSCODE_JOB_STATUS_SCHEMA,
+            ),
+            (
+                SERVICE_TRANSCODE_SCHEMAS,
+                SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA,
+            ),
+            (SERVICE_SNAPSHOT_SCHEMAS, SERVICES_SNAPSHOT_REQUEST_SCHEMA),
+            (SERVICE_SNAPSHOT_SCHEMAS, SERVICES_SNAPSHOT_RESPONSE_SCHEMA),
+            (
+                SERVICE_INFERENCE_SCHEMAS,
+                SERVICES_INFERENCE_BOUNDING_BOX_SCHEMA,
+            ),
+            (
+                SERVICE_INFERENCE_SCHEMAS,
+                SERVICES_INFERENCE_DETECTION_SCHEMA,
+            ),
+            (SERVICE_INFERENCE_SCHEMAS, SERVICES_INFERENCE_RESULT_SCHEMA),
         ]
     }
 
     pub fn new(path_prefix: &str) -> BuilderImpl {
-        let schemas_raw: Vec<String> = Self::schema_files()
+        let directory = Self::read_directory(path_prefix).unwrap_or_else(|e| panic!("{}", e));
+        Self::with_directory(path_prefix, directory)
+    }
+
+    /// Builder over the schema sources in any order, for the targets which have no
+    /// filesystem, e.g. `wasm32`. The files are the ones of [`BuilderImpl::schema_files`];
+    /// [`BuilderImpl::reload`] fails on such a builder.
+    pub fn from_schemas(sources: &[&str]) -> Result<BuilderImpl, SchemaLoadError> {
+        let directory = Self::parse_directory(sources)?;
+        Ok(Self::with_directory("", directory))
+    }
+
+    fn with_directory(path_prefix: &str, directory: SchemaDirectory) -> BuilderImpl {
+        BuilderImpl {
+            schemas: RwLock::new(Arc::new(LoadedSchemas::new(directory))),
+            versions: vec![],
+            extensions: HashMap::default(),
+            path_prefix: String::from(path_prefix),
+            signing_key: None,
+            verification_keys: HashMap::default(),
+            key_provider: None,
+            checksum_algorithm: None,
+            checksum_policy: ChecksumPolicy::default(),
+            auth_provider: None,
+            namespace: String::new(),
+            buffer_pool: None,
+            decode_limits: DecodeLimits::default(),
+        }
+    }
+
+    /// One builder per schema directory for the whole process, read from the disk
+    /// on the first call only. It's alive while a handle is held, the keys and the
+    /// other settings are the defaults; `Arc::new` a configured builder instead.
+    pub fn shared(path_prefix: &str) -> Arc<BuilderImpl> {
+        static SHARED: Mutex<Vec<(String, Weak<BuilderImpl>)>> = Mutex::new(Vec::new());
+
+        let mut shared = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+        shared.retain(|(_, builder)| builder.strong_count() > 0);
+        if let Some(builder) = shared
+            .iter()
+            .find(|(prefix, _)| prefix == path_prefix)
+            .and_then(|(_, builder)| builder.upgrade())
+        {
+            return builder;
+        }
+        let builder = Arc::new(BuilderImpl::new(path_prefix));
+        shared.push((String::from(path_prefix), Arc::downgrade(&builder)));
+        builder
+    }
+
+    /// The process-wide builder over the schemas of the `API` directory the crate is
+    /// built with, for the code which has no builder handle at hand. Loaded on the
+    /// first call; the directory is a path of the build machine, a deployed binary
+    /// gets the load error instead.
+    pub fn global() -> Result<&'static BuilderImpl, SchemaLoadError> {
+        static GLOBAL: OnceLock<Result<BuilderImpl, SchemaLoadError>> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| {
+                let path_prefix = utils::get_avro_path();
+                let directory = Self::read_directory(&path_prefix)?;
+                Ok(Self::with_directory(&path_prefix, directory))
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    pub fn path_prefix(&self) -> &str {
+        &self.path_prefix
+    }
+
+    /// Reads the schemas from the directory again, e.g. after a schema patch. The
+    /// keys and the other settings are kept; the schemas are replaced all at once, a
+    /// directory which fails to load leaves the current ones in place. Works on a
+    /// shared builder, the messages in flight finish with the schemas they started
+    /// with.
+    pub fn reload(&self) -> Result<(), SchemaLoadError> {
+        let directory = Self::read_directory(&self.path_prefix)?;
+        self.swap_schemas(directory);
+        Ok(())
+    }
+
+    /// Replaces the schemas, the ones of the extensions are kept.
+    fn swap_schemas(&self, mut directory: SchemaDirectory) {
+        for (name, extension) in &self.extensions {
+            directory.insert(name.clone(), extension.schema.clone());
+        }
+        let schemas = Arc::new(LoadedSchemas::new(directory));
+        *self.schemas.write().unwrap_or_else(|e| e.into_inner()) = schemas;
+    }
+
+    fn schemas(&self) -> Arc<LoadedSchemas> {
+        self.schemas
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Names of the schemas of the catalog, the extensions included.
+    pub fn schema_names(&self) -> Vec<String> {
+        self.schemas().directory.keys().cloned().collect()
+    }
+
+    /// Adds the record schema of a third-party message, its records travel in the
+    /// envelope as the protocol ones do and load into [`crate::message::Message::Extension`].
+    /// The schema is kept across the reloads; returns its name, e.g.
+    /// `acme.lpr.PlateRecognition.avsc`.
+    pub fn register_extension<F>(
+        &mut self,
+        schema: &str,
+        decoder: F,
+    ) -> Result<String, SchemaLoadError>
+    where
+        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        let (name, schema) = match Self::parse_directory(&[schema])?.into_iter().next() {
+            Some((name, schema)) if matches!(*schema, Schema::Record { .. }) => (name, schema),
+            _ => {
+                return Err(SchemaLoadError::Parse(String::from(
+                    "An extension schema is a record",
+                )))
+            }
+        };
+        let schemas = self.schemas();
+        if schemas.directory.contains_key(&name) && !self.extensions.contains_key(&name) {
+            return Err(SchemaLoadError::Parse(format!(
+                "{} is a protocol schema",
+                name
+            )));
+        }
+        self.extensions.insert(
+            name.clone(),
+            Extension {
+                schema,
+                decoder: Box::new(decoder),
+            },
+        );
+        self.swap_schemas(schemas.directory.clone());
+        Ok(name)
+    }
+
+    /// Schemas are resolved into the writers once per load, encoding a message
+    /// doesn't touch the schema itself.
+    fn compile_writers(directory: &SchemaDirectory) -> HashMap<String, DatumWriter> {
+        directory
+            .iter()
+            .map(|(name, schema)| (name.clone(), DatumWriter::new(schema)))
+            .collect()
+    }
+
+    fn fingerprint_schemas(directory: &SchemaDirectory) -> HashMap<String, [u8; 32]> {
+        directory
+            .iter()
+            .map(|(name, schema)| (name.clone(), schema_sha256(schema)))
+            .collect()
+    }
+
+    /// SHA-256 of the Parsing Canonical Form of the schema, the envelope carries the
+    /// one of the message schema.
+    pub fn schema_fingerprint(&self, schema_name: &str) -> Option<[u8; 32]> {
+        self.schemas().fingerprints.get(schema_name).copied()
+    }
+
+    /// Reads the messages written with the schemas of another protocol version as
+    /// well, the ones at `path_prefix`. They're told apart by the schema fingerprint
+    /// of the envelope and decoded into the schemas of the builder.
+    pub fn add_version(
+        &mut self,
+        version: ProtocolVersion,
+        path_prefix: &str,
+    ) -> Result<(), SchemaLoadError> {
+        let directory = Self::read_directory(path_prefix)?;
+        self.insert_version(version, directory);
+        Ok(())
+    }
+
+    /// [`BuilderImpl::add_version`] over the schema sources, see
+    /// [`BuilderImpl::from_schemas`].
+    pub fn add_version_schemas(
+        &mut self,
+        version: ProtocolVersion,
+        sources: &[&str],
+    ) -> Result<(), SchemaLoadError> {
+        let directory = Self::parse_directory(sources)?;
+        self.insert_version(version, directory);
+        Ok(())
+    }
+
+    fn insert_version(&mut self, version: ProtocolVersion, directory: SchemaDirectory) {
+        self.versions.retain(|set| set.version != version);
+        self.versions.push(SchemaSet {
+            version,
+            fingerprints: Self::fingerprint_schemas(&directory),
+            directory,
+        });
+    }
+
+    /// The versions the builder reads, the current one of its own schemas included.
+    pub fn versions(&self) -> Vec<ProtocolVersion> {
+        let mut versions: Vec<ProtocolVersion> =
+            self.versions.iter().map(|set| set.version).collect();
+        versions.push(current_version());
+        versions.sort();
+        versions.dedup();
+        versions
+    }
+
+    fn version_schema(&self, version: ProtocolVersion, schema_name: &str) -> Option<Arc<Schema>> {
+        match self.versions.iter().find(|set| set.version == version) {
+            Some(set) => set.directory.get(schema_name).cloned(),
+            None if version == current_version() => self.get_schema(schema_name),
+            None => None,
+        }
+    }
+
+    /// The other version whose schema matches the schema fingerprint of the writer.
+    fn writer_version(&self, schema_name: &str, fingerprint: &[u8]) -> Option<&SchemaSet> {
+        self.versions.iter().find(|set| {
+            set.fingerprints
+                .get(schema_name)
+                .is_some_and(|own| own.as_slice() == fingerprint)
+        })
+    }
+
+    /// Version the message is written with; `None` when the writer doesn't send the
+    /// schema fingerprint.
+    pub fn detect_version(&self, from: &[u8]) -> Result<Option<ProtocolVersion>, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        let schema_name = routing_header(&fields)?.schema;
+        let fingerprint = match envelope_schema_fingerprint(&fields) {
+            Some(fingerprint) => fingerprint,
+            None => return Ok(None),
+        };
+        if self
+            .schema_fingerprint(&schema_name)
+            .is_some_and(|own| own.as_slice() == fingerprint)
+        {
+            return Ok(Some(current_version()));
+        }
+        match self.writer_version(&schema_name, fingerprint) {
+            Some(set) => Ok(Some(set.version)),
+            None => Err(ProtocolError::SchemaMismatch(schema_name)),
+        }
+    }
+
+    /// The message of the schemas of the version `from` in the ones of the version
+    /// `to`, by the Avro schema resolution: the fields the writer misses take their
+    /// defaults and the ones the reader misses are dropped.
+    pub fn convert(
+        &self,
+        message: &ProtocolMessage,
+        from: ProtocolVersion,
+        to: ProtocolVersion,
+    ) -> Result<ProtocolMessage, ProtocolError> {
+        let schema = |version: ProtocolVersion| {
+            self.version_schema(version, &message.schema)
+                .ok_or_else(|| {
+                    ProtocolError::Encode(format!(
+                        "No {} schema in the {} schemas",
+                        message.schema, version
+                    ))
+                })
+        };
+        let (writer, reader) = (schema(from)?, schema(to)?);
+        let no_mapping = || {
+            ProtocolError::Encode(format!(
+                "{} of {} has no mapping to {}",
+                message.schema, from, to
+            ))
+        };
+        let datum = to_avro_datum(&writer, message.object.clone()).map_err(|_| no_mapping())?;
+        let object = resolve_datum(&writer, &reader, &datum).ok_or_else(no_mapping)?;
+        Ok(ProtocolMessage {
+            schema: message.schema.clone(),
+            object,
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_directory(_path_prefix: &str) -> Result<SchemaDirectory, SchemaLoadError> {
+        Err(SchemaLoadError::File(String::from(
+            "No filesystem on wasm32, load the schemas with BuilderImpl::from_schemas",
+        )))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_directory(path_prefix: &str) -> Result<SchemaDirectory, SchemaLoadError> {
+        let schemas_raw = Self::schema_files()
             .iter()
             .map(|schema| {
-                utils::load_file(
+                utils::try_load_file(
                     Path::new(path_prefix).join(Path::new(schema.0)).as_path(),
                     schema.1,
                 )
+                .map_err(SchemaLoadError::File)
             })
-            .collect();
+            .collect::<Result<Vec<String>, _>>()?;
         let schemas_raw_str: Vec<&str> = schemas_raw.iter().map(|s| s.as_str()).collect();
+        Self::parse_directory(&schemas_raw_str)
+    }
 
-        let schemas = Schema::parse_list(&schemas_raw_str).unwrap();
+    fn parse_directory(schemas_raw: &[&str]) -> Result<SchemaDirectory, SchemaLoadError> {
+        let schemas =
+            Schema::parse_list(schemas_raw).map_err(|e| SchemaLoadError::Parse(e.to_string()))?;
         let mut named_schemas = HashMap::default();
 
         for s in &schemas {
@@ -116,7 +899,7 @@ impl BuilderImpl {
                     full_name.push('.');
                     full_name.push_str(name);
                     full_name.push_str(".avsc");
-                    named_schemas.insert(full_name, s.clone());
+                    named_schemas.insert(full_name, Arc::new(s.clone()));
                 }
                 Schema::Record {
                     name:
@@ -135,193 +918,1981 @@ impl BuilderImpl {
                     full_name.push('.');
                     full_name.push_str(name);
                     full_name.push_str(".avsc");
-                    named_schemas.insert(full_name, s.clone());
+                    named_schemas.insert(full_name, Arc::new(s.clone()));
                 }
                 _ => {
                     dbg!(s);
                 }
             };
         }
+        Ok(named_schemas)
+    }
 
-        BuilderImpl {
-            directory: named_schemas,
-        }
+    /// Encrypts unit element and chunk payloads on save and decrypts them on read.
+    pub fn with_encryption(mut self, provider: Box<dyn KeyProvider>) -> Self {
+        self.set_encryption(provider);
+        self
     }
 
-    #[inline]
-    pub fn get_schema(&self, schema_name: &str) -> Option<&Schema> {
-        self.directory.get(&String::from(schema_name))
+    pub fn set_encryption(&mut self, provider: Box<dyn KeyProvider>) {
+        self.key_provider = Some(provider);
     }
 
-    #[inline]
-    fn get_record(&self, schema_name: &str) -> Record {
-        let record = Record::new(self.get_schema(schema_name).unwrap()).unwrap();
-        record
+    /// Adds checksums to the unit element payloads on save; received checksums are
+    /// verified according to the policy.
+    pub fn with_checksums(
+        mut self,
+        algorithm: Option<ChecksumAlgorithm>,
+        policy: ChecksumPolicy,
+    ) -> Self {
+        self.set_checksums(algorithm, policy);
+        self
     }
 
-    fn pack_message_into_envelope(&self, schema_name: &str, payload: Value) -> Vec<u8> {
-        let mut envelope = self.get_record(MESSAGE_ENVELOPE_SCHEMA);
-        let inner = to_avro_datum(self.get_schema(schema_name).unwrap(), payload).unwrap();
-        envelope.put("schema", Value::Bytes(schema_name.into()));
-        envelope.put("payload", Value::Bytes(inner));
-        to_avro_datum(self.get_schema(MESSAGE_ENVELOPE_SCHEMA).unwrap(), envelope).unwrap()
+    pub fn set_checksums(&mut self, algorithm: Option<ChecksumAlgorithm>, policy: ChecksumPolicy) {
+        self.checksum_algorithm = algorithm;
+        self.checksum_policy = policy;
     }
 
-    pub fn read_protocol_message(&self, from: &Vec<u8>) -> Result<(String, Value), String> {
-        let envelope_schema = self.get_schema(MESSAGE_ENVELOPE_SCHEMA).unwrap();
-        let envelope = from_avro_datum(envelope_schema, &mut from.as_slice(), None);
+    /// Attaches the tokens of the provider to the envelopes of the requests, i.e. the
+    /// messages addressed with a request id and a response topic.
+    pub fn with_auth_provider(mut self, provider: Box<dyn AuthProvider>) -> Self {
+        self.set_auth_provider(provider);
+        self
+    }
 
-        match envelope {
-            Ok(envelope) => match envelope {
-                Value::Record(fields) => match fields.as_slice() {
-                    [(s_field_name, Value::Bytes(schema)), (p_field_name, Value::Bytes(payload))]
-                        if s_field_name == "schema" && p_field_name == "payload" =>
-                    {
-                        let schema = str::from_utf8(schema.as_slice());
-                        match schema {
-                            Ok(schema_name) => {
-                                let inner_schema = self.get_schema(schema_name);
-
-                                match inner_schema {
-                                        Some(inner_schema) => {
-                                            let inner = from_avro_datum(inner_schema,
-                                                                        &mut payload.clone().as_slice(), None);
-
-                                            match inner {
-                                                Ok(inner) => Ok((String::from(schema_name), inner)),
-                                                _ => Err(String::from("Failed to parse inner AVRO serialized record"))
-                                            }
-                                        }
-                                        _ => Err(format!("No valid schema found in schema catalog for the schema ({}) in serialized record", schema_name))
-                                    }
-                            }
-                            _ => Err(String::from(
-                                "Failed to parse schema name, not a valid UTF-8",
-                            )),
-                        }
-                    }
-                    _ => Err(String::from(
-                        "No outer AVRO record (MessageEnvelope) matched",
-                    )),
-                },
-                _ => Err(String::from("Failed to parse/match outer AVRO Record")),
-            },
-            _ => Err(String::from("Failed to deserialize the outer message")),
-        }
+    pub fn set_auth_provider(&mut self, provider: Box<dyn AuthProvider>) {
+        self.auth_provider = Some(provider);
     }
-}
 
-#[pyclass]
-pub struct Builder {
-    builder: BuilderImpl,
-}
+    /// Tenant every produced envelope belongs to, empty by default.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.set_namespace(namespace);
+        self
+    }
 
-#[derive(Clone)]
-#[pyclass]
-pub struct ProtocolMessage {
-    pub schema: String,
-    pub object: Value,
-}
+    pub fn set_namespace(&mut self, namespace: &str) {
+        self.namespace = String::from(namespace);
+    }
 
-#[pymethods]
-impl Builder {
-    #[new]
-    pub fn new(path_prefix: &str) -> Builder {
-        Builder {
-            builder: BuilderImpl::new(path_prefix),
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Messages exceeding the limits fail to load with [`ProtocolError::LimitExceeded`],
+    /// [`DecodeLimits::default`] unless set.
+    pub fn with_decode_limits(mut self, limits: DecodeLimits) -> Self {
+        self.set_decode_limits(limits);
+        self
+    }
+
+    pub fn set_decode_limits(&mut self, limits: DecodeLimits) {
+        self.decode_limits = limits;
+    }
+
+    pub fn decode_limits(&self) -> &DecodeLimits {
+        &self.decode_limits
+    }
+
+    /// Takes the payload buffers of encoding from the pool and returns the decoded
+    /// envelope buffers to it. The pool may be shared by several builders.
+    pub fn with_buffer_pool(mut self, pool: Arc<BufferPool>) -> Self {
+        self.set_buffer_pool(pool);
+        self
+    }
+
+    pub fn set_buffer_pool(&mut self, pool: Arc<BufferPool>) {
+        self.buffer_pool = Some(pool);
+    }
+
+    fn take_buffer(&self) -> Vec<u8> {
+        match &self.buffer_pool {
+            Some(pool) => pool.take(),
+            None => Vec::new(),
         }
     }
 
-    pub fn load_to_avro(&self, obj: Vec<u8>) -> Option<ProtocolMessage> {
-        match self.builder.read_protocol_message(&obj) {
-            Ok((schema, object)) => Some(ProtocolMessage { schema, object }),
-            Err(m) => {
-                warn!(
-                    "Unable to decode the message from the envelope. Error is {}",
-                    m
-                );
-                None
+    /// Hands the payload buffer of an envelope which is done with back to the pool.
+    fn recycle(&self, fields: Vec<(String, Value)>) {
+        if let Some(pool) = &self.buffer_pool {
+            let payload = fields.into_iter().find(|(name, _)| name == "payload");
+            if let Some((_, Value::Bytes(buffer))) = payload {
+                pool.put(buffer);
             }
         }
     }
 
-    pub fn save_from_avro(&self, message: ProtocolMessage) -> Vec<u8> {
-        self.builder
-            .pack_message_into_envelope(message.schema.as_str(), message.object)
+    /// Signs every produced envelope with the key; the key is also accepted on read.
+    pub fn with_signing_key(mut self, key_id: &str, key: &[u8]) -> Self {
+        self.set_signing_key(key_id, key);
+        self
     }
 
-    pub fn save(&self, obj: &PyAny) -> Option<Vec<u8>> {
-        fn try_to<T: Clone + PyClass + ToProtocolMessage>(
-            mb: &Builder,
-            x: &PyAny,
-        ) -> Option<Vec<u8>> {
-            if x.is_instance_of::<T>().unwrap() {
-                let ro: T = x.extract().unwrap();
-                let protocol_message_res = ro.save(mb);
+    pub fn set_signing_key(&mut self, key_id: &str, key: &[u8]) {
+        self.signing_key = Some((String::from(key_id), key.to_vec()));
+        self.add_verification_key(key_id, key);
+    }
 
-                protocol_message_res.map(|m| mb.save_from_avro(m))
-            } else {
-                None
-            }
-        }
+    /// Accepts envelopes signed with the key, e.g. the previous one during rotation.
+    pub fn add_verification_key(&mut self, key_id: &str, key: &[u8]) {
+        self.verification_keys
+            .insert(String::from(key_id), key.to_vec());
+    }
 
-        try_to::<UnitElementMessage>(self, obj)
-            .or_else(|| try_to::<NotifyMessage>(self, obj))
-            .or_else(|| try_to::<PingRequestResponse>(self, obj))
-            .or_else(|| try_to::<ServicesFFProbeRequest>(self, obj))
-            .or_else(|| try_to::<ServicesFFProbeResponse>(self, obj))
-            .or_else(|| try_to::<StreamTrackUnitElementsRequest>(self, obj))
-            .or_else(|| try_to::<StreamTrackUnitElementsResponse>(self, obj))
-            .or_else(|| try_to::<StreamTracksRequest>(self, obj))
-            .or_else(|| try_to::<StreamTracksResponse>(self, obj))
-            .or_else(|| try_to::<StreamTrackUnitsRequest>(self, obj))
-            .or_else(|| try_to::<StreamTrackUnitsResponse>(self, obj))
-            .or_else(|| try_to::<KeepAliveMessage>(self, obj))
+    #[inline]
+    pub fn get_schema(&self, schema_name: &str) -> Option<Arc<Schema>> {
+        self.schemas().directory.get(schema_name).cloned()
     }
 
-    pub fn load(&self, message: Vec<u8>) -> Option<PyObject> {
-        fn try_from<T: FromProtocolMessage + PyClass + Into<PyClassInitializer<T>>>(
-            message: &ProtocolMessage,
-        ) -> Option<PyObject> {
-            let gil = Python::acquire_gil();
-            let py = gil.python();
+    #[inline]
+    fn get_record(&self, schema_name: &str) -> Result<SchemaRecord, ProtocolError> {
+        self.get_schema(schema_name)
+            .and_then(SchemaRecord::new)
+            .ok_or_else(|| ProtocolError::Encode(format!("No record schema {}", schema_name)))
+    }
 
-            T::load(message).map(|o| Py::new(py, o).unwrap().to_object(py))
-        }
+    fn encode(&self, schema_name: &str, value: &Value) -> Result<Vec<u8>, ProtocolError> {
+        let schemas = self.schemas();
+        Self::writer(&schemas, schema_name)?
+            .write(value)
+            .map_err(ProtocolError::Encode)
+    }
 
-        match self.load_to_avro(message) {
-            None => None,
-            Some(obj) => try_from::<UnitElementMessage>(&obj)
-                .or_else(|| try_from::<NotifyMessage>(&obj))
-                .or_else(|| try_from::<PingRequestResponse>(&obj))
-                .or_else(|| try_from::<ServicesFFProbeRequest>(&obj))
-                .or_else(|| try_from::<ServicesFFProbeResponse>(&obj))
-                .or_else(|| try_from::<StreamTrackUnitElementsRequest>(&obj))
-                .or_else(|| try_from::<StreamTrackUnitElementsResponse>(&obj))
-                .or_else(|| try_from::<StreamTracksRequest>(&obj))
-                .or_else(|| try_from::<StreamTracksResponse>(&obj))
-                .or_else(|| try_from::<StreamTrackUnitsRequest>(&obj))
-                .or_else(|| try_from::<StreamTrackUnitsResponse>(&obj))
-                .or_else(|| try_from::<KeepAliveMessage>(&obj)),
-        }
+    fn encode_into(
+        &self,
+        schema_name: &str,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ProtocolError> {
+        let schemas = self.schemas();
+        Self::writer(&schemas, schema_name)?
+            .write_into(value, out)
+            .map_err(ProtocolError::Encode)
     }
-}
 
-impl Builder {
-    pub fn get_record(&self, schema_name: &str) -> Record {
-        let record = Record::new(self.builder.get_schema(schema_name).unwrap()).unwrap();
-        record
+    fn writer<'a>(
+        schemas: &'a LoadedSchemas,
+        schema_name: &str,
+    ) -> Result<&'a DatumWriter, ProtocolError> {
+        schemas
+            .writers
+            .get(schema_name)
+            .ok_or_else(|| ProtocolError::Encode(format!("No schema {}", schema_name)))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::avro::{Builder, UNIT_ELEMENT_MESSAGE_SCHEMA};
-    use crate::utils::get_avro_path;
+    /// Labels are meant for brokers and routers which may rewrite them, so they
+    /// aren't covered by the envelope signature.
+    pub fn pack_message_with_labels(
+        &self,
+        schema_name: &str,
+        payload: Value,
+        labels: &Labels,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.pack_message(
+            schema_name,
+            payload,
+            labels,
+            None,
+            default_priority(schema_name),
+        )
+    }
 
-    #[test]
-    fn test_load_schemas() {
-        let mb = Builder::new(get_avro_path().as_str());
-        let _r = mb.get_record(UNIT_ELEMENT_MESSAGE_SCHEMA);
+    /// The trace context changes at every hop, so like the labels it isn't covered
+    /// by the envelope signature.
+    pub fn pack_message(
+        &self,
+        schema_name: &str,
+        payload: Value,
+        labels: &Labels,
+        trace_context: Option<&TraceContext>,
+        priority: MessagePriority,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut out = Vec::new();
+        self.pack_message_into(
+            schema_name,
+            payload,
+            labels,
+            trace_context,
+            priority,
+            &mut out,
+        )?;
+        Ok(out)
+    }
+
+    /// Appends the packed message to `out`, so a caller reusing its buffer doesn't
+    /// allocate one per message. A message which fails to pack leaves `out` as it was.
+    pub fn pack_message_into(
+        &self,
+        schema_name: &str,
+        payload: Value,
+        labels: &Labels,
+        trace_context: Option<&TraceContext>,
+        priority: MessagePriority,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ProtocolError> {
+        let (mut envelope, inner) =
+            self.build_envelope(schema_name, payload, labels, trace_context, priority)?;
+        envelope.put("payload", Value::Bytes(inner));
+        let envelope = Value::from(envelope);
+        let packed = self.encode_into(MESSAGE_ENVELOPE_SCHEMA, &envelope, out);
+        if let Value::Record(fields) = envelope {
+            self.recycle(fields);
+        }
+        packed
+    }
+
+    /// Packs the message as two frames: the envelope with an empty payload, and the
+    /// serialized payload. Brokers and proxies read and rewrite the header frame with
+    /// the usual calls and pass the payload frame on as opaque bytes; the signature
+    /// in the header covers the payload frame.
+    pub fn pack_message_frames(
+        &self,
+        schema_name: &str,
+        payload: Value,
+        labels: &Labels,
+        trace_context: Option<&TraceContext>,
+        priority: MessagePriority,
+    ) -> Result<(Vec<u8>, Vec<u8>), ProtocolError> {
+        let (mut envelope, inner) =
+            self.build_envelope(schema_name, payload, labels, trace_context, priority)?;
+        envelope.put("payload", Value::Bytes(vec![]));
+        let header = self.encode(MESSAGE_ENVELOPE_SCHEMA, &Value::from(envelope))?;
+        Ok((header, inner))
+    }
+
+    /// The envelope without the payload and the serialized payload.
+    fn build_envelope(
+        &self,
+        schema_name: &str,
+        mut payload: Value,
+        labels: &Labels,
+        trace_context: Option<&TraceContext>,
+        priority: MessagePriority,
+    ) -> Result<(SchemaRecord, Vec<u8>), ProtocolError> {
+        validate_labels(labels).map_err(ProtocolError::InvalidLabels)?;
+        let auth = match (&self.auth_provider, request_address(&payload)) {
+            (Some(provider), Some(_)) => provider.token(schema_name),
+            _ => None,
+        };
+        if let (Some(algorithm), true) = (self.checksum_algorithm, is_checksummed(schema_name)) {
+            seal_record(algorithm, &mut payload).map_err(ProtocolError::Encode)?;
+        }
+        if let (Some(provider), true) = (&self.key_provider, is_encrypted(schema_name)) {
+            encrypt_record(provider.as_ref(), &mut payload).map_err(ProtocolError::Encode)?;
+        }
+        let mut envelope = self.get_record(MESSAGE_ENVELOPE_SCHEMA)?;
+        let mut inner = self.take_buffer();
+        self.encode_into(schema_name, &payload, &mut inner)?;
+        match &self.signing_key {
+            Some((key_id, key)) => {
+                let signature = sign_envelope(
+                    key,
+                    &SignedEnvelope {
+                        key_id,
+                        schema_name,
+                        namespace: &self.namespace,
+                        auth: auth.as_ref(),
+                        payload: &inner,
+                    },
+                );
+                envelope.put(
+                    "key_id",
+                    Value::Union(Box::new(Value::String(key_id.clone()))),
+                );
+                envelope.put("signature", Value::Union(Box::new(Value::Bytes(signature))));
+            }
+            None => {
+                envelope.put("key_id", Value::Union(Box::new(Value::Null)));
+                envelope.put("signature", Value::Union(Box::new(Value::Null)));
+            }
+        }
+        envelope.put("namespace", Value::String(self.namespace.clone()));
+        envelope.put("labels", utils::gen_hash_map(labels));
+        envelope.put(
+            "traceparent",
+            optional_string_to_avro(&trace_context.map(|c| c.traceparent.clone())),
+        );
+        envelope.put(
+            "tracestate",
+            optional_string_to_avro(&trace_context.and_then(|c| c.tracestate.clone())),
+        );
+        envelope.put("priority", get_priority_enum(&priority));
+        envelope.put("hops", Value::Array(vec![]));
+        envelope.put(
+            "auth",
+            Value::Union(Box::new(match auth {
+                Some(auth) => auth.to_avro_record(),
+                None => Value::Null,
+            })),
+        );
+        envelope.put(
+            "schema_fingerprint",
+            Value::Union(Box::new(match self.schema_fingerprint(schema_name) {
+                Some(fingerprint) => Value::Bytes(fingerprint.to_vec()),
+                None => Value::Null,
+            })),
+        );
+        envelope.put("schema", Value::Bytes(schema_name.into()));
+        Ok((envelope, inner))
+    }
+
+    fn verify_payload_signature(
+        &self,
+        schema_name: &str,
+        payload: &[u8],
+        envelope: &EnvelopeSignature,
+    ) -> Result<(), ProtocolError> {
+        if self.verification_keys.is_empty() {
+            return Ok(());
+        }
+
+        match (envelope.key_id, envelope.signature) {
+            (Some(key_id), Some(signature)) => match self.verification_keys.get(key_id) {
+                Some(key) => {
+                    let signed = SignedEnvelope {
+                        key_id,
+                        schema_name,
+                        namespace: envelope.namespace,
+                        auth: envelope.auth,
+                        payload,
+                    };
+                    if verify_envelope(key, &signed, signature) {
+                        Ok(())
+                    } else {
+                        Err(ProtocolError::SignatureMismatch)
+                    }
+                }
+                None => Err(ProtocolError::SignatureMismatch),
+            },
+            _ => Err(ProtocolError::SignatureMismatch),
+        }
+    }
+
+    fn read_envelope(&self, from: &[u8]) -> Result<Vec<(String, Value)>, ProtocolError> {
+        let envelope_schema = self.get_schema(MESSAGE_ENVELOPE_SCHEMA).ok_or_else(|| {
+            ProtocolError::Decode(String::from("No MessageEnvelope schema in the catalog"))
+        })?;
+        self.decode_limits.check_message(from)?;
+        if !scan::fits(&envelope_schema, from) {
+            return Err(ProtocolError::Decode(String::from(
+                "Failed to deserialize the outer message",
+            )));
+        }
+        let envelope = decode_datum(&envelope_schema, from).ok_or_else(|| {
+            ProtocolError::Decode(String::from("Failed to deserialize the outer message"))
+        })?;
+
+        match envelope {
+            Value::Record(fields) => Ok(fields),
+            _ => Err(ProtocolError::Decode(String::from(
+                "Failed to parse/match outer AVRO Record",
+            ))),
+        }
+    }
+
+    /// Reads the envelope labels without decoding the message itself.
+    pub fn read_labels(&self, from: &[u8]) -> Result<Labels, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_labels(&fields))
+    }
+
+    /// Reads the envelope trace context without decoding the message itself. A
+    /// malformed context is dropped, the trace is restarted then.
+    pub fn read_trace_context(&self, from: &[u8]) -> Result<Option<TraceContext>, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_trace_context(&fields))
+    }
+
+    /// Reads the envelope priority without decoding the message itself, so a broker
+    /// adapter can pick the queue before the payload is touched.
+    pub fn read_priority(&self, from: &[u8]) -> Result<MessagePriority, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_priority(&fields))
+    }
+
+    /// Reads the envelope fields a router works with, the message stays encoded.
+    pub fn read_routing_header(&self, from: &[u8]) -> Result<RoutingHeader, ProtocolError> {
+        self.read_header(from).map(|header| header.routing)
+    }
+
+    /// Decodes the envelope only, the payload stays serialized until
+    /// [`MessageHeader::into_message`] is called; a message routed on is never
+    /// decoded past its envelope.
+    pub fn read_header<'a>(&'a self, from: &'a [u8]) -> Result<MessageHeader<'a>, ProtocolError> {
+        let envelope_schema = self.get_schema(MESSAGE_ENVELOPE_SCHEMA).ok_or_else(|| {
+            ProtocolError::Decode(String::from("No MessageEnvelope schema in the catalog"))
+        })?;
+        self.decode_limits.check_message(from)?;
+        let decode_error =
+            || ProtocolError::Decode(String::from("Failed to deserialize the outer message"));
+        let spans = scan::field_spans(&envelope_schema, from, &mut 0).ok_or_else(decode_error)?;
+
+        let mut payload = None;
+        let mut fields = Vec::with_capacity(spans.len());
+        for (name, schema, span) in spans {
+            if name == "payload" {
+                payload = scan::bytes_content(from, span).map(|content| &from[content]);
+            } else {
+                let value = decode_datum(schema, &from[span]).ok_or_else(decode_error)?;
+                fields.push((String::from(name), value));
+            }
+        }
+        let payload = payload.ok_or_else(|| {
+            ProtocolError::Decode(String::from(
+                "No outer AVRO record (MessageEnvelope) matched",
+            ))
+        })?;
+
+        Ok(MessageHeader {
+            routing: routing_header(&fields)?,
+            auth: envelope_auth(&fields),
+            fingerprint: envelope_schema_fingerprint(&fields).map(Vec::from),
+            key_id: envelope_key_id(&fields).map(String::from),
+            signature: envelope_signature(&fields).map(Vec::from),
+            payload,
+            builder: self,
+        })
+    }
+
+    /// Reads the envelope namespace without decoding the message itself.
+    pub fn read_namespace(&self, from: &[u8]) -> Result<String, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_namespace(&fields))
+    }
+
+    /// Reads the auth token of a request without decoding the message itself, so a
+    /// service can turn the request down before the payload is touched.
+    pub fn read_auth(&self, from: &[u8]) -> Result<Option<AuthToken>, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_auth(&fields))
+    }
+
+    /// Reads the hops the message passed so far without decoding the message itself.
+    pub fn read_hops(&self, from: &[u8]) -> Result<Vec<Hop>, ProtocolError> {
+        let fields = self.read_envelope(from)?;
+        Ok(envelope_hops(&fields))
+    }
+
+    /// Replaces the namespace, the labels, the trace context, the priority and the
+    /// hops of the envelope. The schema and the payload are copied as they are, so the signature
+    /// stays valid. Only the last [`MAX_HOPS`] hops are kept.
+    pub fn rewrite_routing_header(
+        &self,
+        from: &[u8],
+        header: &RoutingHeader,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        validate_labels(&header.labels).map_err(ProtocolError::InvalidLabels)?;
+        let mut fields = self.read_envelope(from)?;
+        let trace_context = header.trace_context.as_ref();
+        for (name, value) in fields.iter_mut() {
+            match name.as_str() {
+                "namespace" => *value = Value::String(header.namespace.clone()),
+                "labels" => *value = utils::gen_hash_map(&header.labels),
+                "traceparent" => {
+                    *value = optional_string_to_avro(&trace_context.map(|c| c.traceparent.clone()))
+                }
+                "tracestate" => {
+                    *value =
+                        optional_string_to_avro(&trace_context.and_then(|c| c.tracestate.clone()))
+                }
+                "priority" => *value = get_priority_enum(&header.priority),
+                "hops" => {
+                    let skip = header.hops.len().saturating_sub(MAX_HOPS);
+                    *value = Value::Array(
+                        header.hops[skip..]
+                            .iter()
+                            .map(Hop::to_avro_record)
+                            .collect(),
+                    )
+                }
+                _ => {}
+            }
+        }
+        self.encode(MESSAGE_ENVELOPE_SCHEMA, &Value::Record(fields))
+    }
+
+    pub fn read_protocol_message(&self, from: &[u8]) -> Result<(String, Value), ProtocolError> {
+        let fields = self.read_envelope(from)?;
+
+        let (schema, payload) = match (
+            envelope_field(&fields, "schema"),
+            envelope_field(&fields, "payload"),
+        ) {
+            (Some(Value::Bytes(schema)), Some(Value::Bytes(payload))) => (schema, payload),
+            _ => {
+                return Err(ProtocolError::Decode(String::from(
+                    "No outer AVRO record (MessageEnvelope) matched",
+                )))
+            }
+        };
+
+        let schema_name = str::from_utf8(schema.as_slice()).map_err(|_| {
+            ProtocolError::Decode(String::from(
+                "Failed to parse schema name, not a valid UTF-8",
+            ))
+        })?;
+
+        let namespace = envelope_namespace(&fields);
+        let auth = envelope_auth(&fields);
+        let inner = self.decode_payload(
+            schema_name,
+            payload,
+            envelope_schema_fingerprint(&fields),
+            &EnvelopeSignature {
+                namespace: &namespace,
+                auth: auth.as_ref(),
+                key_id: envelope_key_id(&fields),
+                signature: envelope_signature(&fields),
+            },
+        )?;
+        let schema_name = String::from(schema_name);
+        self.recycle(fields);
+        Ok((schema_name, inner))
+    }
+
+    /// Reads a message packed by [`BuilderImpl::pack_message_frames`].
+    pub fn read_protocol_frames(
+        &self,
+        header: &[u8],
+        payload: &[u8],
+    ) -> Result<(String, Value), ProtocolError> {
+        let message = self
+            .read_header(header)?
+            .into_message_with_payload(payload)?;
+        Ok((message.schema, message.object))
+    }
+
+    /// Joins the frames into a message for the consumers which read single frames.
+    pub fn join_frames(&self, header: &[u8], payload: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let mut fields = self.read_envelope(header)?;
+        match fields.iter_mut().find(|(name, _)| name == "payload") {
+            Some((_, Value::Bytes(bytes))) if bytes.is_empty() => bytes.extend_from_slice(payload),
+            _ => {
+                return Err(ProtocolError::Decode(String::from(
+                    "The header frame carries a payload",
+                )))
+            }
+        }
+        self.encode(MESSAGE_ENVELOPE_SCHEMA, &Value::Record(fields))
+    }
+
+    /// Splits a message into the header and the payload frames.
+    pub fn split_frames(&self, from: &[u8]) -> Result<(Vec<u8>, Vec<u8>), ProtocolError> {
+        let mut fields = self.read_envelope(from)?;
+        let payload = match fields.iter_mut().find(|(name, _)| name == "payload") {
+            Some((_, Value::Bytes(bytes))) => std::mem::take(bytes),
+            _ => {
+                return Err(ProtocolError::Decode(String::from(
+                    "No outer AVRO record (MessageEnvelope) matched",
+                )))
+            }
+        };
+        let header = self.encode(MESSAGE_ENVELOPE_SCHEMA, &Value::Record(fields))?;
+        Ok((header, payload))
+    }
+
+    /// Verifies, decodes, decrypts and checks the serialized payload of an envelope.
+    /// The envelopes of the writers which don't send the schema fingerprint are
+    /// decoded as is.
+    fn decode_payload(
+        &self,
+        schema_name: &str,
+        payload: &[u8],
+        fingerprint: Option<&[u8]>,
+        envelope: &EnvelopeSignature,
+    ) -> Result<Value, ProtocolError> {
+        self.decode_limits.check_payload(payload)?;
+        self.verify_payload_signature(schema_name, payload, envelope)?;
+
+        let reader_schema = self.get_schema(schema_name).ok_or_else(|| {
+            ProtocolError::Decode(format!(
+                "No valid schema found in schema catalog for the schema ({}) in serialized record",
+                schema_name
+            ))
+        })?;
+        // the messages of the other versions are decoded with the schema of the writer
+        // and resolved into the one of the builder once checked
+        let mut writer_schema = None;
+        if let (Some(writer), Some(reader)) = (fingerprint, self.schema_fingerprint(schema_name)) {
+            if writer != reader {
+                writer_schema = Some(
+                    self.writer_version(schema_name, writer)
+                        .and_then(|set| set.directory.get(schema_name))
+                        .ok_or_else(|| ProtocolError::SchemaMismatch(String::from(schema_name)))?,
+                );
+            }
+        }
+        let inner_schema = writer_schema.unwrap_or(&reader_schema);
+
+        let parse_error =
+            || ProtocolError::Decode(String::from("Failed to parse inner AVRO serialized record"));
+        if !scan::fits(inner_schema, payload) {
+            return Err(parse_error());
+        }
+        let mut inner = decode_datum(inner_schema, payload).ok_or_else(parse_error)?;
+        self.decode_limits.check_attributes(&inner)?;
+
+        if let (Some(provider), true) = (&self.key_provider, is_encrypted(schema_name)) {
+            // the payload stays encrypted and the consumer decides what to do with it
+            if let Err(e) = decrypt_record(provider.as_ref(), &mut inner) {
+                warn!("Unable to decrypt the message. Error is {}", e);
+            }
+        }
+
+        if is_checksummed(schema_name) {
+            match (verify_record(&inner), self.checksum_policy) {
+                (Ok(()), _) | (_, ChecksumPolicy::Skip) => {}
+                (Err(e), ChecksumPolicy::Warn) => warn!("{}", e),
+                (Err(e), ChecksumPolicy::Fail) => return Err(ProtocolError::ChecksumMismatch(e)),
+            }
+        }
+
+        if writer_schema.is_some() {
+            inner = resolve_value(inner, &reader_schema)
+                .ok_or_else(|| ProtocolError::SchemaMismatch(String::from(schema_name)))?;
+        }
+        match self.extensions.get(schema_name) {
+            Some(extension) => (extension.decoder)(inner).map_err(ProtocolError::Decode),
+            None => Ok(inner),
+        }
+    }
+}
+
+/// Envelope of a message whose payload isn't decoded yet, see
+/// [`BuilderImpl::read_header`].
+pub struct MessageHeader<'a> {
+    pub routing: RoutingHeader,
+    pub auth: Option<AuthToken>,
+    fingerprint: Option<Vec<u8>>,
+    key_id: Option<String>,
+    signature: Option<Vec<u8>>,
+    payload: &'a [u8],
+    builder: &'a BuilderImpl,
+}
+
+impl<'a> MessageHeader<'a> {
+    pub fn schema(&self) -> &str {
+        &self.routing.schema
+    }
+
+    /// Size of the serialized payload.
+    pub fn payload_len(&self) -> usize {
+        self.payload.len()
+    }
+
+    /// Decodes the payload with the same checks as [`BuilderImpl::read_protocol_message`].
+    pub fn into_message(self) -> Result<ProtocolMessage, ProtocolError> {
+        let payload = self.payload;
+        self.decode(payload)
+    }
+
+    /// Decodes the payload frame of a header frame, see
+    /// [`BuilderImpl::pack_message_frames`].
+    pub fn into_message_with_payload(
+        self,
+        payload: &[u8],
+    ) -> Result<ProtocolMessage, ProtocolError> {
+        if !self.payload.is_empty() {
+            return Err(ProtocolError::Decode(String::from(
+                "The header frame carries a payload",
+            )));
+        }
+        self.decode(payload)
+    }
+
+    /// The payload, once its signature is verified.
+    pub fn verified_payload(&self) -> Result<&'a [u8], ProtocolError> {
+        self.builder.verify_payload_signature(
+            &self.routing.schema,
+            self.payload,
+            &self.signature(),
+        )?;
+        Ok(self.payload)
+    }
+
+    fn signature(&self) -> EnvelopeSignature<'_> {
+        EnvelopeSignature {
+            namespace: &self.routing.namespace,
+            auth: self.auth.as_ref(),
+            key_id: self.key_id.as_deref(),
+            signature: self.signature.as_deref(),
+        }
+    }
+
+    fn decode(self, payload: &[u8]) -> Result<ProtocolMessage, ProtocolError> {
+        let object = self.builder.decode_payload(
+            &self.routing.schema,
+            payload,
+            self.fingerprint.as_deref(),
+            &self.signature(),
+        )?;
+        Ok(ProtocolMessage {
+            schema: self.routing.schema,
+            object,
+        })
+    }
+}
+
+/// Schemas which carry an encryptable `value`.
+fn is_encrypted(schema_name: &str) -> bool {
+    matches!(
+        schema_name,
+        UNIT_ELEMENT_MESSAGE_SCHEMA | UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA
+    )
+}
+
+/// Priority of the messages saved without an explicit one: liveness and flow control
+/// messages must not wait behind the payloads they are about.
+pub fn default_priority(schema_name: &str) -> MessagePriority {
+    match schema_name {
+        PING_REQUEST_RESPONSE_SCHEMA
+        | KEEPALIVE_MESSAGE_SCHEMA
+        | HEARTBEAT_MESSAGE_SCHEMA
+        | FLOW_CONTROL_GRANT_SCHEMA
+        | FLOW_CONTROL_REQUEST_SCHEMA
+        | NOTIFY_CADENCE_CONTROL_SCHEMA => MessagePriority::Control,
+        MAINTENANCE_NOTICE_SCHEMA | ERROR_RESPONSE_SCHEMA => MessagePriority::High,
+        _ => MessagePriority::Normal,
+    }
+}
+
+/// Schemas which carry payloads covered by checksums.
+fn is_checksummed(schema_name: &str) -> bool {
+    matches!(
+        schema_name,
+        UNIT_ELEMENT_MESSAGE_SCHEMA | STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA
+    )
+}
+
+fn envelope_namespace(fields: &[(String, Value)]) -> String {
+    match envelope_field(fields, "namespace") {
+        Some(Value::String(namespace)) => namespace.clone(),
+        _ => String::new(),
+    }
+}
+
+/// The decoder of the AVRO library isn't hardened against every malformed input;
+/// a panic inside of it is turned into a decode error, so a bad message can't take
+/// down the process which reads it.
+fn decode_datum(schema: &Schema, from: &[u8]) -> Option<Value> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        from_avro_datum(schema, &mut &from[..], None).ok()
+    }))
+    .ok()
+    .flatten()
+}
+
+fn resolve_datum(writer: &Schema, reader: &Schema, from: &[u8]) -> Option<Value> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        from_avro_datum(writer, &mut &from[..], Some(reader)).ok()
+    }))
+    .ok()
+    .flatten()
+}
+
+fn resolve_value(value: Value, reader: &Schema) -> Option<Value> {
+    panic::catch_unwind(AssertUnwindSafe(|| value.resolve(reader).ok()))
+        .ok()
+        .flatten()
+}
+
+fn envelope_labels(fields: &[(String, Value)]) -> Labels {
+    match envelope_field(fields, "labels") {
+        Some(Value::Map(labels)) => utils::parse_hash_map(labels),
+        _ => Labels::default(),
+    }
+}
+
+fn envelope_trace_context(fields: &[(String, Value)]) -> Option<TraceContext> {
+    match (
+        unwrap_union(envelope_field(fields, "traceparent")),
+        unwrap_union(envelope_field(fields, "tracestate")),
+    ) {
+        (Some(Value::String(traceparent)), tracestate) => {
+            let tracestate = match tracestate {
+                Some(Value::String(tracestate)) => Some(tracestate.as_str()),
+                _ => None,
+            };
+            match TraceContext::parse(traceparent, tracestate) {
+                Ok(context) => Some(context),
+                Err(e) => {
+                    warn!("Trace context is dropped. Error is {}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn envelope_priority(fields: &[(String, Value)]) -> MessagePriority {
+    match envelope_field(fields, "priority") {
+        Some(Value::Enum(_, priority)) => priority_literal_to_priority(priority),
+        _ => MessagePriority::default(),
+    }
+}
+
+/// Hops which fail to parse are dropped, they only serve the latency accounting.
+fn envelope_hops(fields: &[(String, Value)]) -> Vec<Hop> {
+    match envelope_field(fields, "hops") {
+        Some(Value::Array(hops)) => hops.iter().filter_map(Hop::from_avro).collect(),
+        _ => vec![],
+    }
+}
+
+fn envelope_key_id(fields: &[(String, Value)]) -> Option<&str> {
+    match unwrap_union(envelope_field(fields, "key_id")) {
+        Some(Value::String(key_id)) => Some(key_id.as_str()),
+        _ => None,
+    }
+}
+
+fn envelope_schema_fingerprint(fields: &[(String, Value)]) -> Option<&[u8]> {
+    match unwrap_union(envelope_field(fields, "schema_fingerprint")) {
+        Some(Value::Bytes(fingerprint)) => Some(fingerprint.as_slice()),
+        _ => None,
+    }
+}
+
+fn envelope_signature(fields: &[(String, Value)]) -> Option<&[u8]> {
+    match unwrap_union(envelope_field(fields, "signature")) {
+        Some(Value::Bytes(signature)) => Some(signature.as_slice()),
+        _ => None,
+    }
+}
+
+fn routing_header(fields: &[(String, Value)]) -> Result<RoutingHeader, ProtocolError> {
+    let schema = match envelope_field(fields, "schema") {
+        Some(Value::Bytes(schema)) => String::from_utf8(schema.clone()).map_err(|_| {
+            ProtocolError::Decode(String::from(
+                "Failed to parse schema name, not a valid UTF-8",
+            ))
+        })?,
+        _ => {
+            return Err(ProtocolError::Decode(String::from(
+                "No outer AVRO record (MessageEnvelope) matched",
+            )))
+        }
+    };
+    Ok(RoutingHeader {
+        schema,
+        namespace: envelope_namespace(fields),
+        labels: envelope_labels(fields),
+        trace_context: envelope_trace_context(fields),
+        priority: envelope_priority(fields),
+        hops: envelope_hops(fields),
+    })
+}
+
+fn envelope_auth(fields: &[(String, Value)]) -> Option<AuthToken> {
+    unwrap_union(envelope_field(fields, "auth")).and_then(AuthToken::from_avro)
+}
+
+/// Request id and response topic of a decoded request.
+pub fn request_address(object: &Value) -> Option<(i64, String)> {
+    let fields = match object {
+        Value::Record(fields) => fields,
+        _ => return None,
+    };
+    match (
+        envelope_field(fields, "request_id")?,
+        envelope_field(fields, "topic")?,
+    ) {
+        (Value::Long(request_id), Value::String(topic)) => Some((*request_id, topic.clone())),
+        _ => None,
+    }
+}
+
+fn envelope_field<'a>(fields: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+    fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn unwrap_union(value: Option<&Value>) -> Option<&Value> {
+    match value {
+        Some(Value::Union(inner)) => Some(inner.as_ref()),
+        other => other,
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass)]
+pub struct Builder {
+    builder: BuilderImpl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ProtocolMessage {
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub schema: String,
+    pub object: Value,
+}
+
+/// The common accessors for the Python tooling which routes messages without
+/// loading them into their classes.
+#[cfg(feature = "python")]
+#[pymethods]
+impl ProtocolMessage {
+    #[pyo3(name = "kind")]
+    fn py_kind(&self) -> Option<String> {
+        self.kind().map(|kind| kind.to_string())
+    }
+
+    #[pyo3(name = "stream_name")]
+    fn py_stream_name(&self) -> Option<StreamName> {
+        self.stream_name()
+    }
+
+    #[pyo3(name = "track")]
+    fn py_track(&self) -> Option<TrackInfo> {
+        self.track()
+    }
+
+    #[pyo3(name = "request_id")]
+    fn py_request_id(&self) -> Option<i64> {
+        self.request_id()
+    }
+
+    #[pyo3(name = "timestamp")]
+    fn py_timestamp(&self) -> Option<u64> {
+        self.timestamp()
+    }
+
+    /// Violated invariants, empty for a valid message.
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> Vec<String> {
+        match self.validate() {
+            Ok(()) => vec![],
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ProtocolMessage({})", self.schema)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl Builder {
+    pub fn new(path_prefix: &str) -> Builder {
+        Builder {
+            builder: BuilderImpl::new(path_prefix),
+        }
+    }
+
+    pub fn set_signing_key(&mut self, key_id: &str, key: Vec<u8>) {
+        self.builder.set_signing_key(key_id, &key);
+    }
+
+    pub fn add_verification_key(&mut self, key_id: &str, key: Vec<u8>) {
+        self.builder.add_verification_key(key_id, &key);
+    }
+
+    pub fn set_encryption_keys(&mut self, key_id: &str, keys: HashMap<String, Vec<u8>>) {
+        self.builder
+            .set_encryption(Box::new(StaticKeyProvider::new(key_id, keys)));
+    }
+
+    pub fn set_checksums(&mut self, algorithm: Option<ChecksumAlgorithm>, policy: ChecksumPolicy) {
+        self.builder.set_checksums(algorithm, policy);
+    }
+
+    pub fn load_to_avro(&self, obj: Vec<u8>) -> Option<ProtocolMessage> {
+        match self.builder.read_protocol_message(&obj) {
+            Ok((schema, object)) => Some(ProtocolMessage { schema, object }),
+            Err(m) => {
+                warn!(
+                    "Unable to decode the message from the envelope. Error is {}",
+                    m
+                );
+                None
+            }
+        }
+    }
+
+    pub fn read_priority(&self, message: Vec<u8>) -> Option<MessagePriority> {
+        match self.builder.read_priority(&message) {
+            Ok(priority) => Some(priority),
+            Err(m) => {
+                warn!("Unable to read the envelope priority. Error is {}", m);
+                None
+            }
+        }
+    }
+
+    pub fn read_trace_context(&self, message: Vec<u8>) -> Option<TraceContext> {
+        match self.builder.read_trace_context(&message) {
+            Ok(context) => context,
+            Err(m) => {
+                warn!("Unable to read the envelope trace context. Error is {}", m);
+                None
+            }
+        }
+    }
+
+    /// Picks up the changed schemas, the current ones stay when the directory fails
+    /// to load.
+    pub fn reload(&mut self) -> bool {
+        match self.builder.reload() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Unable to reload the schemas. Error is {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn set_namespace(&mut self, namespace: &str) {
+        self.builder.set_namespace(namespace);
+    }
+
+    /// The limits of the messages loaded, see [`DecodeLimits`].
+    pub fn set_decode_limits(
+        &mut self,
+        max_message_size: usize,
+        max_payload_size: usize,
+        max_attributes: usize,
+    ) {
+        self.builder.set_decode_limits(DecodeLimits {
+            max_message_size,
+            max_payload_size,
+            max_attributes,
+        });
+    }
+
+    pub fn read_namespace(&self, message: Vec<u8>) -> Option<String> {
+        match self.builder.read_namespace(&message) {
+            Ok(namespace) => Some(namespace),
+            Err(m) => {
+                warn!("Unable to read the envelope namespace. Error is {}", m);
+                None
+            }
+        }
+    }
+
+    pub fn set_auth_token(&mut self, scheme: String, token: String) {
+        self.builder
+            .set_auth_provider(Box::new(StaticAuthProvider(AuthToken::new(scheme, token))));
+    }
+
+    pub fn read_auth(&self, message: Vec<u8>) -> Option<AuthToken> {
+        match self.builder.read_auth(&message) {
+            Ok(auth) => auth,
+            Err(m) => {
+                warn!("Unable to read the envelope auth token. Error is {}", m);
+                None
+            }
+        }
+    }
+
+    pub fn read_hops(&self, message: Vec<u8>) -> Option<Vec<Hop>> {
+        match self.builder.read_hops(&message) {
+            Ok(hops) => Some(hops),
+            Err(m) => {
+                warn!("Unable to read the envelope hops. Error is {}", m);
+                None
+            }
+        }
+    }
+
+    pub fn read_labels(&self, message: Vec<u8>) -> Option<Labels> {
+        match self.builder.read_labels(&message) {
+            Ok(labels) => Some(labels),
+            Err(m) => {
+                warn!("Unable to read the envelope labels. Error is {}", m);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Builder {
+    #[new]
+    fn py_new(path_prefix: &str) -> Self {
+        Self::new(path_prefix)
+    }
+
+    #[pyo3(name = "set_signing_key")]
+    fn py_set_signing_key(&mut self, key_id: &str, key: Vec<u8>) {
+        self.set_signing_key(key_id, key)
+    }
+
+    #[pyo3(name = "add_verification_key")]
+    fn py_add_verification_key(&mut self, key_id: &str, key: Vec<u8>) {
+        self.add_verification_key(key_id, key)
+    }
+
+    #[pyo3(name = "set_encryption_keys")]
+    fn py_set_encryption_keys(&mut self, key_id: &str, keys: HashMap<String, Vec<u8>>) {
+        self.set_encryption_keys(key_id, keys)
+    }
+
+    #[pyo3(name = "set_checksums")]
+    fn py_set_checksums(&mut self, algorithm: Option<ChecksumAlgorithm>, policy: ChecksumPolicy) {
+        self.set_checksums(algorithm, policy)
+    }
+
+    #[pyo3(name = "load_to_avro")]
+    fn py_load_to_avro(&self, obj: Vec<u8>) -> Option<ProtocolMessage> {
+        self.load_to_avro(obj)
+    }
+
+    #[pyo3(name = "save_from_avro")]
+    fn py_save_from_avro(&self, message: ProtocolMessage) -> PyResult<Vec<u8>> {
+        self.save_from_avro(message)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    pub fn save(&self, obj: &PyAny) -> Option<Vec<u8>> {
+        match self.save_from_avro(self.to_protocol_message(obj)?) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                warn!("Unable to save the message. Error is {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save_with_labels(&self, obj: &PyAny, labels: Labels) -> PyResult<Option<Vec<u8>>> {
+        self.to_protocol_message(obj)
+            .map(|m| self.save_from_avro_with_labels(m, &labels))
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    pub fn save_with_trace_context(
+        &self,
+        obj: &PyAny,
+        trace_context: TraceContext,
+    ) -> Option<Vec<u8>> {
+        self.to_protocol_message(obj).and_then(|m| {
+            self.save_from_avro_with_trace_context(m, &trace_context)
+                .ok()
+        })
+    }
+
+    pub fn save_with_priority(&self, obj: &PyAny, priority: MessagePriority) -> Option<Vec<u8>> {
+        self.to_protocol_message(obj)
+            .and_then(|m| self.save_from_avro_with_priority(m, priority).ok())
+    }
+
+    #[pyo3(name = "read_priority")]
+    fn py_read_priority(&self, message: Vec<u8>) -> Option<MessagePriority> {
+        self.read_priority(message)
+    }
+
+    #[pyo3(name = "read_trace_context")]
+    fn py_read_trace_context(&self, message: Vec<u8>) -> Option<TraceContext> {
+        self.read_trace_context(message)
+    }
+
+    #[pyo3(name = "reload")]
+    fn py_reload(&mut self) -> bool {
+        self.reload()
+    }
+
+    #[pyo3(name = "set_namespace")]
+    fn py_set_namespace(&mut self, namespace: &str) {
+        self.set_namespace(namespace)
+    }
+
+    #[pyo3(name = "set_decode_limits")]
+    fn py_set_decode_limits(
+        &mut self,
+        max_message_size: usize,
+        max_payload_size: usize,
+        max_attributes: usize,
+    ) {
+        self.set_decode_limits(max_message_size, max_payload_size, max_attributes)
+    }
+
+    #[pyo3(name = "read_namespace")]
+    fn py_read_namespace(&self, message: Vec<u8>) -> Option<String> {
+        self.read_namespace(message)
+    }
+
+    #[pyo3(name = "set_auth_token")]
+    fn py_set_auth_token(&mut self, scheme: String, token: String) {
+        self.set_auth_token(scheme, token)
+    }
+
+    #[pyo3(name = "read_auth")]
+    fn py_read_auth(&self, message: Vec<u8>) -> Option<AuthToken> {
+        self.read_auth(message)
+    }
+
+    #[pyo3(name = "read_hops")]
+    fn py_read_hops(&self, message: Vec<u8>) -> Option<Vec<Hop>> {
+        self.read_hops(message)
+    }
+
+    #[pyo3(name = "read_labels")]
+    fn py_read_labels(&self, message: Vec<u8>) -> Option<Labels> {
+        self.read_labels(message)
+    }
+
+    pub fn load(&self, message: Vec<u8>) -> Option<PyObject> {
+        self.load_to_avro(message)
+            .and_then(|obj| self.load_protocol_message(&obj))
+    }
+
+    /// Saves the message as the header and the payload frames, see
+    /// [`BuilderImpl::pack_message_frames`].
+    pub fn save_frames(&self, obj: &PyAny) -> Option<(Vec<u8>, Vec<u8>)> {
+        let message = self.to_protocol_message(obj)?;
+        let priority = default_priority(message.schema.as_str());
+        match self.builder.pack_message_frames(
+            message.schema.as_str(),
+            message.object,
+            &Labels::default(),
+            None,
+            priority,
+        ) {
+            Ok(frames) => Some(frames),
+            Err(e) => {
+                warn!("Unable to save the message frames. Error is {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn load_frames(&self, header: Vec<u8>, payload: Vec<u8>) -> Option<PyObject> {
+        match self.builder.read_protocol_frames(&header, &payload) {
+            Ok((schema, object)) => self.load_protocol_message(&ProtocolMessage { schema, object }),
+            Err(m) => {
+                warn!(
+                    "Unable to decode the message from the frames. Error is {}",
+                    m
+                );
+                None
+            }
+        }
+    }
+}
+
+impl From<BuilderImpl> for Builder {
+    fn from(builder: BuilderImpl) -> Self {
+        Builder { builder }
+    }
+}
+
+impl Builder {
+    pub fn builder_impl(&self) -> &BuilderImpl {
+        &self.builder
+    }
+
+    /// See [`BuilderImpl::register_extension`].
+    pub fn register_extension<F>(
+        &mut self,
+        schema: &str,
+        decoder: F,
+    ) -> Result<String, SchemaLoadError>
+    where
+        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.builder.register_extension(schema, decoder)
+    }
+
+    /// See [`BuilderImpl::schema_fingerprint`], `None` when the builder has no schema
+    /// of the kind, e.g. one made of a part of the schemas.
+    pub fn schema_fingerprint(&self, kind: MessageKind) -> Option<[u8; 32]> {
+        self.builder.schema_fingerprint(kind.schema())
+    }
+
+    /// Fails with [`ProtocolError::Encode`] when the message doesn't match its schema.
+    pub fn save_from_avro(&self, message: ProtocolMessage) -> Result<Vec<u8>, ProtocolError> {
+        self.save_from_avro_with_labels(message, &Labels::default())
+    }
+
+    /// Appends the serialized message to `out`, see [`BuilderImpl::pack_message_into`].
+    pub fn save_into(
+        &self,
+        message: ProtocolMessage,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ProtocolError> {
+        let priority = default_priority(message.schema.as_str());
+        self.builder.pack_message_into(
+            message.schema.as_str(),
+            message.object,
+            &Labels::default(),
+            None,
+            priority,
+            out,
+        )
+    }
+
+    pub fn set_buffer_pool(&mut self, pool: Arc<BufferPool>) {
+        self.builder.set_buffer_pool(pool);
+    }
+
+    pub fn save_from_avro_with_labels(
+        &self,
+        message: ProtocolMessage,
+        labels: &Labels,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.builder
+            .pack_message_with_labels(message.schema.as_str(), message.object, labels)
+    }
+
+    pub fn save_from_avro_with_trace_context(
+        &self,
+        message: ProtocolMessage,
+        trace_context: &TraceContext,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.builder.pack_message(
+            message.schema.as_str(),
+            message.object,
+            &Labels::default(),
+            Some(trace_context),
+            default_priority(message.schema.as_str()),
+        )
+    }
+
+    /// Overrides the default priority of the message schema, e.g. to send a bulk
+    /// backfill response as `Low`.
+    pub fn save_from_avro_with_priority(
+        &self,
+        message: ProtocolMessage,
+        priority: MessagePriority,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.builder.pack_message(
+            message.schema.as_str(),
+            message.object,
+            &Labels::default(),
+            None,
+            priority,
+        )
+    }
+
+    #[cfg(feature = "python")]
+    fn load_protocol_message(&self, obj: &ProtocolMessage) -> Option<PyObject> {
+        fn try_from<T: FromProtocolMessage + PyClass + Into<PyClassInitializer<T>>>(
+            message: &ProtocolMessage,
+        ) -> Option<PyObject> {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+
+            T::load(message).and_then(|o| Py::new(py, o).ok().map(|o| o.to_object(py)))
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        UnitElementMessage::load_with_limits(obj, self.builder.decode_limits())
+            .and_then(|o| Py::new(py, o).ok().map(|o| o.to_object(py)))
+            .or_else(|| try_from::<NotifyMessage>(obj))
+            .or_else(|| try_from::<PingRequestResponse>(obj))
+            .or_else(|| try_from::<ServicesFFProbeRequest>(obj))
+            .or_else(|| try_from::<ServicesFFProbeResponse>(obj))
+            .or_else(|| try_from::<StreamTrackUnitElementsRequest>(obj))
+            .or_else(|| try_from::<StreamTrackUnitElementsResponse>(obj))
+            .or_else(|| try_from::<StreamTracksRequest>(obj))
+            .or_else(|| try_from::<StreamTracksResponse>(obj))
+            .or_else(|| try_from::<StreamTrackUnitsRequest>(obj))
+            .or_else(|| try_from::<StreamTrackUnitsResponse>(obj))
+            .or_else(|| try_from::<KeepAliveMessage>(obj))
+            .or_else(|| try_from::<ServicesFilmstripRequest>(obj))
+            .or_else(|| try_from::<ServicesFilmstripResponse>(obj))
+            .or_else(|| try_from::<StreamEndMessage>(obj))
+            .or_else(|| try_from::<StreamLifecycleMessage>(obj))
+            .or_else(|| try_from::<SloReport>(obj))
+            .or_else(|| try_from::<StreamsDiscoveryRequest>(obj))
+            .or_else(|| try_from::<StreamsDiscoveryResponse>(obj))
+            .or_else(|| try_from::<StreamTrackUnitsByTimeRequest>(obj))
+            .or_else(|| try_from::<StreamTrackUnitsByTimeResponse>(obj))
+            .or_else(|| try_from::<StreamTrackKeyframesRequest>(obj))
+            .or_else(|| try_from::<StreamTrackKeyframesResponse>(obj))
+            .or_else(|| try_from::<UnitElementChunkMessage>(obj))
+            .or_else(|| try_from::<UnitElementAckMessage>(obj))
+            .or_else(|| try_from::<NotifyDigestRequest>(obj))
+            .or_else(|| try_from::<NotifyDigestResponse>(obj))
+            .or_else(|| try_from::<FlowControlGrant>(obj))
+            .or_else(|| try_from::<FlowControlRequest>(obj))
+            .or_else(|| try_from::<SetStreamAlias>(obj))
+            .or_else(|| try_from::<ResolveAliasRequest>(obj))
+            .or_else(|| try_from::<ResolveAliasResponse>(obj))
+            .or_else(|| try_from::<ErrorResponse>(obj))
+            .or_else(|| try_from::<SoftDeleteStreamRequest>(obj))
+            .or_else(|| try_from::<RestoreStreamRequest>(obj))
+            .or_else(|| try_from::<MaintenanceNotice>(obj))
+            .or_else(|| try_from::<UnitBitmapRequest>(obj))
+            .or_else(|| try_from::<UnitBitmapResponse>(obj))
+            .or_else(|| try_from::<BackfillRequest>(obj))
+            .or_else(|| try_from::<BackfillResponse>(obj))
+            .or_else(|| try_from::<TranscodeJobRequest>(obj))
+            .or_else(|| try_from::<TranscodeJobStatus>(obj))
+            .or_else(|| try_from::<TranscodeJobCancel>(obj))
+            .or_else(|| try_from::<SnapshotRequest>(obj))
+            .or_else(|| try_from::<SnapshotResponse>(obj))
+            .or_else(|| try_from::<EncodingProfileOffer>(obj))
+            .or_else(|| try_from::<EncodingProfileAck>(obj))
+            .or_else(|| try_from::<StorageRetentionSetRequest>(obj))
+            .or_else(|| try_from::<StorageRetentionSetResponse>(obj))
+            .or_else(|| try_from::<StoragePurgeRequest>(obj))
+            .or_else(|| try_from::<StoragePurgeResponse>(obj))
+            .or_else(|| try_from::<NotifyCadenceControl>(obj))
+            .or_else(|| try_from::<StorageStatsRequest>(obj))
+            .or_else(|| try_from::<StorageStatsResponse>(obj))
+            .or_else(|| try_from::<StreamReplayRequest>(obj))
+            .or_else(|| try_from::<StreamReplayStatus>(obj))
+            .or_else(|| try_from::<StreamReplayStop>(obj))
+            .or_else(|| try_from::<StreamMultiTrackUnitElementsRequest>(obj))
+            .or_else(|| try_from::<StreamMultiTrackUnitElementsResponse>(obj))
+            .or_else(|| try_from::<InferenceResultMessage>(obj))
+            .or_else(|| try_from::<NodeTelemetry>(obj))
+            .or_else(|| try_from::<HeartbeatMessage>(obj))
+            .or_else(|| try_from::<AuthChallenge>(obj))
+            .or_else(|| try_from::<AuthResult>(obj))
+    }
+
+    #[cfg(feature = "python")]
+    fn to_protocol_message(&self, obj: &PyAny) -> Option<ProtocolMessage> {
+        fn try_to<T: Clone + PyClass + ToProtocolMessage>(
+            mb: &Builder,
+            x: &PyAny,
+        ) -> Option<ProtocolMessage> {
+            match x.is_instance_of::<T>() {
+                Ok(true) => x.extract::<T>().ok()?.save(mb),
+                _ => None,
+            }
+        }
+
+        try_to::<UnitElementMessage>(self, obj)
+            .or_else(|| try_to::<NotifyMessage>(self, obj))
+            .or_else(|| try_to::<PingRequestResponse>(self, obj))
+            .or_else(|| try_to::<ServicesFFProbeRequest>(self, obj))
+            .or_else(|| try_to::<ServicesFFProbeResponse>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitElementsRequest>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitElementsResponse>(self, obj))
+            .or_else(|| try_to::<StreamTracksRequest>(self, obj))
+            .or_else(|| try_to::<StreamTracksResponse>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitsRequest>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitsResponse>(self, obj))
+            .or_else(|| try_to::<KeepAliveMessage>(self, obj))
+            .or_else(|| try_to::<ServicesFilmstripRequest>(self, obj))
+            .or_else(|| try_to::<ServicesFilmstripResponse>(self, obj))
+            .or_else(|| try_to::<StreamEndMessage>(self, obj))
+            .or_else(|| try_to::<StreamLifecycleMessage>(self, obj))
+            .or_else(|| try_to::<SloReport>(self, obj))
+            .or_else(|| try_to::<StreamsDiscoveryRequest>(self, obj))
+            .or_else(|| try_to::<StreamsDiscoveryResponse>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitsByTimeRequest>(self, obj))
+            .or_else(|| try_to::<StreamTrackUnitsByTimeResponse>(self, obj))
+            .or_else(|| try_to::<StreamTrackKeyframesRequest>(self, obj))
+            .or_else(|| try_to::<StreamTrackKeyframesResponse>(self, obj))
+            .or_else(|| try_to::<UnitElementChunkMessage>(self, obj))
+            .or_else(|| try_to::<UnitElementAckMessage>(self, obj))
+            .or_else(|| try_to::<NotifyDigestRequest>(self, obj))
+            .or_else(|| try_to::<NotifyDigestResponse>(self, obj))
+            .or_else(|| try_to::<FlowControlGrant>(self, obj))
+            .or_else(|| try_to::<FlowControlRequest>(self, obj))
+            .or_else(|| try_to::<SetStreamAlias>(self, obj))
+            .or_else(|| try_to::<ResolveAliasRequest>(self, obj))
+            .or_else(|| try_to::<ResolveAliasResponse>(self, obj))
+            .or_else(|| try_to::<ErrorResponse>(self, obj))
+            .or_else(|| try_to::<SoftDeleteStreamRequest>(self, obj))
+            .or_else(|| try_to::<RestoreStreamRequest>(self, obj))
+            .or_else(|| try_to::<MaintenanceNotice>(self, obj))
+            .or_else(|| try_to::<UnitBitmapRequest>(self, obj))
+            .or_else(|| try_to::<UnitBitmapResponse>(self, obj))
+            .or_else(|| try_to::<BackfillRequest>(self, obj))
+            .or_else(|| try_to::<BackfillResponse>(self, obj))
+            .or_else(|| try_to::<TranscodeJobRequest>(self, obj))
+            .or_else(|| try_to::<TranscodeJobStatus>(self, obj))
+            .or_else(|| try_to::<TranscodeJobCancel>(self, obj))
+            .or_else(|| try_to::<SnapshotRequest>(self, obj))
+            .or_else(|| try_to::<SnapshotResponse>(self, obj))
+            .or_else(|| try_to::<EncodingProfileOffer>(self, obj))
+            .or_else(|| try_to::<EncodingProfileAck>(self, obj))
+            .or_else(|| try_to::<StorageRetentionSetRequest>(self, obj))
+            .or_else(|| try_to::<StorageRetentionSetResponse>(self, obj))
+            .or_else(|| try_to::<StoragePurgeRequest>(self, obj))
+            .or_else(|| try_to::<StoragePurgeResponse>(self, obj))
+            .or_else(|| try_to::<NotifyCadenceControl>(self, obj))
+            .or_else(|| try_to::<StorageStatsRequest>(self, obj))
+            .or_else(|| try_to::<StorageStatsResponse>(self, obj))
+            .or_else(|| try_to::<StreamReplayRequest>(self, obj))
+            .or_else(|| try_to::<StreamReplayStatus>(self, obj))
+            .or_else(|| try_to::<StreamReplayStop>(self, obj))
+            .or_else(|| try_to::<StreamMultiTrackUnitElementsRequest>(self, obj))
+            .or_else(|| try_to::<StreamMultiTrackUnitElementsResponse>(self, obj))
+            .or_else(|| try_to::<InferenceResultMessage>(self, obj))
+            .or_else(|| try_to::<NodeTelemetry>(self, obj))
+            .or_else(|| try_to::<HeartbeatMessage>(self, obj))
+            .or_else(|| try_to::<AuthChallenge>(self, obj))
+            .or_else(|| try_to::<AuthResult>(self, obj))
+    }
+
+    pub fn get_schema(&self, schema_name: &str) -> Option<Arc<Schema>> {
+        self.builder.get_schema(schema_name)
+    }
+
+    /// `None` for the names which aren't record schemas of the catalog.
+    pub fn get_record(&self, schema_name: &str) -> Option<SchemaRecord> {
+        self.builder.get_record(schema_name).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{
+        envelope_schema_fingerprint, Builder, BuilderImpl, DecodeLimits, ProtocolError,
+        ProtocolMessage, SchemaLoadError, KEEPALIVE_MESSAGE_SCHEMA, MESSAGE_ENVELOPE_SCHEMA,
+        TRANSPORT_SCHEMAS, UNIT_ELEMENT_MESSAGE_SCHEMA,
+    };
+    use crate::buffer_pool::BufferPool;
+    use crate::fingerprint::schema_sha256;
+    use crate::history::{current_version, ProtocolVersion};
+    use crate::message::Message;
+    use crate::message_kind::MessageKind;
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{AttributeValue, Labels, MessagePriority, Unit};
+    use crate::trace::TraceContext;
+    use crate::utils::get_avro_path;
+    use avro_rs::to_avro_datum;
+    use avro_rs::types::Value;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_load_schemas() {
+        let mb = Builder::new(get_avro_path().as_str());
+        assert!(mb.get_record(UNIT_ELEMENT_MESSAGE_SCHEMA).is_some());
+        assert!(mb.get_record("insight.transport.Unknown.avsc").is_none());
+    }
+
+    #[test]
+    fn test_reload() {
+        let dir = std::env::temp_dir().join(format!("protocol-reload-{}", std::process::id()));
+        for (sub_dir, file) in BuilderImpl::schema_files() {
+            fs::create_dir_all(dir.join(sub_dir)).unwrap();
+            fs::copy(
+                Path::new(&get_avro_path()).join(sub_dir).join(file),
+                dir.join(sub_dir).join(file),
+            )
+            .unwrap();
+        }
+        let builder = BuilderImpl::new(dir.to_str().unwrap());
+        assert_eq!(builder.reload(), Ok(()));
+
+        let keep_alive = dir.join(TRANSPORT_SCHEMAS).join(KEEPALIVE_MESSAGE_SCHEMA);
+        let original = fs::read_to_string(&keep_alive).unwrap();
+        fs::write(&keep_alive, "{").unwrap();
+        assert!(matches!(builder.reload(), Err(SchemaLoadError::Parse(_))));
+        assert!(builder.get_schema(KEEPALIVE_MESSAGE_SCHEMA).is_some());
+
+        fs::remove_file(&keep_alive).unwrap();
+        assert!(matches!(builder.reload(), Err(SchemaLoadError::File(_))));
+
+        fs::write(&keep_alive, original).unwrap();
+        assert_eq!(builder.reload(), Ok(()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_signed_envelope() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        mb.set_signing_key("k1", b"secret".to_vec());
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let mut serialized = mb.save_from_avro(message).unwrap();
+        assert!(mb.load_to_avro(serialized.clone()).is_some());
+
+        let verifier = BuilderImpl::new(get_avro_path().as_str()).with_signing_key("k2", b"other");
+        assert_eq!(
+            verifier.read_protocol_message(&serialized),
+            Err(ProtocolError::SignatureMismatch)
+        );
+
+        let last = serialized.len() - 1;
+        serialized[last] ^= 0xff;
+        assert!(mb.load_to_avro(serialized).is_none());
+    }
+
+    #[test]
+    fn test_schema_fingerprint() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let fingerprint = schema_sha256(&mb.get_schema(KEEPALIVE_MESSAGE_SCHEMA).unwrap());
+        assert_eq!(
+            mb.schema_fingerprint(MessageKind::KeepAliveMessage),
+            Some(fingerprint)
+        );
+
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let serialized = mb.save_from_avro(message).unwrap();
+        let builder = mb.builder_impl();
+        let mut fields = builder.read_envelope(&serialized).unwrap();
+        assert_eq!(
+            envelope_schema_fingerprint(&fields),
+            Some(fingerprint.as_slice())
+        );
+
+        for (name, value) in fields.iter_mut() {
+            if name == "schema_fingerprint" {
+                *value = Value::Union(Box::new(Value::Bytes(vec![0; 32])));
+            }
+        }
+        let tampered = builder
+            .encode(MESSAGE_ENVELOPE_SCHEMA, &Value::Record(fields.clone()))
+            .unwrap();
+        assert_eq!(
+            builder.read_protocol_message(&tampered),
+            Err(ProtocolError::SchemaMismatch(String::from(
+                KEEPALIVE_MESSAGE_SCHEMA
+            )))
+        );
+
+        for (name, value) in fields.iter_mut() {
+            if name == "schema_fingerprint" {
+                *value = Value::Union(Box::new(Value::Null));
+            }
+        }
+        let unversioned = builder
+            .encode(MESSAGE_ENVELOPE_SCHEMA, &Value::Record(fields))
+            .unwrap();
+        assert!(builder.read_protocol_message(&unversioned).is_ok());
+    }
+
+    #[test]
+    fn test_versions() {
+        let sources: Vec<String> = BuilderImpl::schema_files()
+            .iter()
+            .map(|(sub_dir, file)| {
+                let source =
+                    fs::read_to_string(Path::new(&get_avro_path()).join(sub_dir).join(file))
+                        .unwrap();
+                if *file != KEEPALIVE_MESSAGE_SCHEMA {
+                    return source;
+                }
+                let fields = source.find("\"fields\"").unwrap();
+                let at = fields + source[fields..].find('[').unwrap() + 1;
+                format!(
+                    "{}{{\"name\": \"legacy\", \"type\": \"int\", \"default\": 7}},{}",
+                    &source[..at],
+                    &source[at..]
+                )
+            })
+            .collect();
+        let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+        let old = ProtocolVersion::parse("0.2.1").unwrap();
+        let old_builder = BuilderImpl::from_schemas(&sources).unwrap();
+        let mut builder = BuilderImpl::new(get_avro_path().as_str());
+        builder.add_version_schemas(old, &sources).unwrap();
+        assert_eq!(builder.versions(), vec![old, current_version()]);
+        let mb = Builder::from(builder);
+
+        let message = Message::KeepAliveMessage(KeepAliveMessage::new("module".into()));
+        let old_message = message.upgrade_to(&mb, old).unwrap();
+        let serialized = old_builder
+            .pack_message_with_labels(
+                KEEPALIVE_MESSAGE_SCHEMA,
+                old_message.object,
+                &Labels::default(),
+            )
+            .unwrap();
+
+        let builder = mb.builder_impl();
+        assert_eq!(builder.detect_version(&serialized), Ok(Some(old)));
+        let (schema, object) = builder.read_protocol_message(&serialized).unwrap();
+        assert_eq!(
+            Message::load(&ProtocolMessage { schema, object }),
+            Some(message.clone())
+        );
+
+        let current = mb.save_from_avro(message.save(&mb).unwrap()).unwrap();
+        assert_eq!(
+            builder.detect_version(&current),
+            Ok(Some(current_version()))
+        );
+        let unknown = BuilderImpl::new(get_avro_path().as_str());
+        assert_eq!(
+            unknown.read_protocol_message(&serialized),
+            Err(ProtocolError::SchemaMismatch(String::from(
+                KEEPALIVE_MESSAGE_SCHEMA
+            )))
+        );
+    }
+
+    #[test]
+    fn test_envelope_labels() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let labels = Labels::from([
+            (String::from("region"), "eu-west".into()),
+            (String::from("priority"), 1i64.into()),
+        ]);
+        let serialized = mb
+            .save_from_avro_with_labels(message.clone(), &labels)
+            .unwrap();
+        assert_eq!(mb.read_labels(serialized.clone()), Some(labels));
+        assert!(mb.load_to_avro(serialized).is_some());
+
+        let too_long = Labels::from([(String::from("tenant"), "x".repeat(1024).into())]);
+        assert!(matches!(
+            mb.save_from_avro_with_labels(message, &too_long),
+            Err(ProtocolError::InvalidLabels(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_mismatched_message() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        message.object = Value::Record(vec![(String::from("module"), Value::Long(0))]);
+        assert!(matches!(
+            mb.save_from_avro(message),
+            Err(ProtocolError::Encode(_))
+        ));
+    }
+
+    #[test]
+    fn test_envelope_trace_context() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let context = TraceContext::parse(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            Some("vendor=value"),
+        )
+        .unwrap();
+        let serialized = mb
+            .save_from_avro_with_trace_context(message.clone(), &context)
+            .unwrap();
+        assert_eq!(mb.read_trace_context(serialized.clone()), Some(context));
+        assert!(mb.load_to_avro(serialized).is_some());
+
+        let serialized = mb.save_from_avro(message).unwrap();
+        assert_eq!(mb.read_trace_context(serialized), None);
+    }
+
+    #[test]
+    fn test_shared() {
+        let builder = BuilderImpl::shared(get_avro_path().as_str());
+        assert!(Arc::ptr_eq(
+            &builder,
+            &BuilderImpl::shared(get_avro_path().as_str())
+        ));
+
+        let message = KeepAliveMessage::new("module".into())
+            .save(&Builder::new(get_avro_path().as_str()))
+            .unwrap();
+        let workers = (0..4)
+            .map(|_| {
+                let builder = Arc::clone(&builder);
+                let message = message.clone();
+                std::thread::spawn(move || {
+                    let packed = builder
+                        .pack_message_with_labels(
+                            &message.schema,
+                            message.object.clone(),
+                            &Labels::default(),
+                        )
+                        .unwrap();
+                    builder.read_protocol_message(&packed).unwrap().1 == message.object
+                })
+            })
+            .collect::<Vec<_>>();
+        assert!(workers.into_iter().all(|w| w.join().unwrap()));
+    }
+
+    #[test]
+    fn test_global() {
+        let builder = BuilderImpl::global().unwrap();
+        assert!(std::ptr::eq(builder, BuilderImpl::global().unwrap()));
+        assert_eq!(builder.path_prefix(), get_avro_path());
+
+        let message = KeepAliveMessage::new("module".into())
+            .save(&Builder::new(get_avro_path().as_str()))
+            .unwrap();
+        let packed = builder
+            .pack_message_with_labels(&message.schema, message.object.clone(), &Labels::default())
+            .unwrap();
+        assert_eq!(
+            builder.read_protocol_message(&packed).unwrap().1,
+            message.object
+        );
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let unit = Unit::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), 7);
+        let attributes = (0..8)
+            .map(|i| (format!("k{}", i), AttributeValue::I64(i)))
+            .collect();
+        let message = UnitElementMessage::new(unit, 0, vec![0; 1024], attributes, true)
+            .save(&mb)
+            .unwrap();
+        let packed = mb.save_from_avro(message).unwrap();
+
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        assert!(builder.read_protocol_message(&packed).is_ok());
+        let limits = DecodeLimits::default();
+        for limits in [
+            DecodeLimits {
+                max_message_size: 1024,
+                ..limits
+            },
+            DecodeLimits {
+                max_payload_size: 1000,
+                ..limits
+            },
+            DecodeLimits {
+                max_attributes: 7,
+                ..limits
+            },
+        ] {
+            let builder = BuilderImpl::new(get_avro_path().as_str()).with_decode_limits(limits);
+            assert!(matches!(
+                builder.read_protocol_message(&packed),
+                Err(ProtocolError::LimitExceeded(_))
+            ));
+        }
+
+        // a bytes field declaring more than the message holds
+        let mut oversized = packed.clone();
+        let at = oversized
+            .windows(4)
+            .position(|window| window == [0x80, 0x10, 0, 0])
+            .unwrap();
+        oversized[at..at + 2].copy_from_slice(&[0xfe, 0x7f]);
+        assert!(matches!(
+            builder.read_protocol_message(&oversized),
+            Err(ProtocolError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_into() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        let pool = Arc::new(BufferPool::new(4, 1024));
+        mb.set_buffer_pool(pool.clone());
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let serialized = mb.save_from_avro(message.clone()).unwrap();
+
+        let mut out = vec![0xff];
+        mb.save_into(message.clone(), &mut out).unwrap();
+        assert_eq!(out[0], 0xff);
+        assert_eq!(&out[1..], serialized.as_slice());
+        assert_eq!(pool.len(), 1);
+
+        assert!(mb.load_to_avro(serialized).is_some());
+        assert_eq!(pool.len(), 2);
+        mb.save_into(message, &mut out).unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_read_header() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str()).with_signing_key("k1", b"secret");
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let labels = Labels::from([(String::from("camera"), "1".into())]);
+        let packed = builder
+            .pack_message_with_labels(&message.schema, message.object.clone(), &labels)
+            .unwrap();
+
+        let header = builder.read_header(&packed).unwrap();
+        assert_eq!(header.schema(), KEEPALIVE_MESSAGE_SCHEMA);
+        assert_eq!(header.routing.labels, labels);
+        assert_eq!(header.routing.priority, MessagePriority::Control);
+        assert!(header.payload_len() > 0);
+        assert_eq!(
+            header.routing,
+            builder.read_routing_header(&packed).unwrap()
+        );
+        assert_eq!(header.into_message().unwrap().object, message.object);
+
+        let inner = to_avro_datum(
+            &builder.get_schema(KEEPALIVE_MESSAGE_SCHEMA).unwrap(),
+            message.object,
+        )
+        .unwrap();
+        let at = packed
+            .windows(inner.len())
+            .position(|window| window == inner.as_slice())
+            .unwrap();
+        let mut tampered = packed.clone();
+        tampered[at] ^= 1;
+        let header = builder.read_header(&tampered).unwrap();
+        assert_eq!(
+            header.into_message().err(),
+            Some(ProtocolError::SignatureMismatch)
+        );
+        assert!(builder.read_header(&packed[..packed.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_frames() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str()).with_signing_key("k1", b"secret");
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let (header, payload) = builder
+            .pack_message_frames(
+                &message.schema,
+                message.object.clone(),
+                &Labels::default(),
+                None,
+                MessagePriority::Control,
+            )
+            .unwrap();
+        let (schema, object) = builder.read_protocol_frames(&header, &payload).unwrap();
+        assert_eq!(schema, KEEPALIVE_MESSAGE_SCHEMA);
+        assert_eq!(object, message.object);
+        assert_eq!(builder.read_header(&header).unwrap().payload_len(), 0);
+
+        let mut routing = builder.read_routing_header(&header).unwrap();
+        routing.labels = Labels::from([(String::from("camera"), "1".into())]);
+        let header = builder.rewrite_routing_header(&header, &routing).unwrap();
+        assert_eq!(builder.read_labels(&header).unwrap(), routing.labels);
+        assert!(builder.read_protocol_frames(&header, &payload).is_ok());
+
+        let joined = builder.join_frames(&header, &payload).unwrap();
+        assert_eq!(
+            builder.read_protocol_message(&joined).unwrap().1,
+            message.object
+        );
+        assert_eq!(
+            builder.split_frames(&joined).unwrap(),
+            (header.clone(), payload.clone())
+        );
+        assert!(builder.join_frames(&joined, &payload).is_err());
+        assert!(builder.read_protocol_frames(&joined, &payload).is_err());
+
+        let mut tampered = payload;
+        tampered[0] ^= 1;
+        assert_eq!(
+            builder.read_protocol_frames(&header, &tampered).err(),
+            Some(ProtocolError::SignatureMismatch)
+        );
+    }
+
+    #[test]
+    fn test_envelope_priority() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = KeepAliveMessage::new("module".into()).save(&mb).unwrap();
+        let serialized = mb.save_from_avro(message.clone()).unwrap();
+        assert_eq!(mb.read_priority(serialized), Some(MessagePriority::Control));
+
+        let serialized = mb
+            .save_from_avro_with_priority(message, MessagePriority::Low)
+            .unwrap();
+        assert_eq!(
+            mb.read_priority(serialized.clone()),
+            Some(MessagePriority::Low)
+        );
+        assert!(mb.load_to_avro(serialized).is_some());
+
+        assert!(MessagePriority::Control > MessagePriority::High);
+        assert_eq!(MessagePriority::Low.queue_priority(9), 0);
+        assert_eq!(MessagePriority::Normal.queue_priority(9), 3);
+        assert_eq!(MessagePriority::Control.queue_priority(9), 9);
     }
 }
@@ -0,0 +1,160 @@
+use crate::avro::Builder;
+use crate::testkit::corpus::boundary_messages;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator which counts the allocations. The host binary installs it with
+/// `#[global_allocator]`, otherwise the benchmarks report no allocation counts.
+///
+/// The counter is process wide, so the counts are exact only when nothing else
+/// allocates while a benchmark runs.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+pub fn allocations() -> u64 {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// Encode and decode costs of a message kind, per message.
+///
+/// Encoding covers the conversion to the AVRO record and packing it into the
+/// envelope, decoding covers unpacking the envelope into a [`ProtocolMessage`].
+/// `encode_into` is the same encoding into one reused output buffer; with a
+/// [`BufferPool`] set on the builder the payload buffers are reused as well.
+///
+/// [`ProtocolMessage`]: crate::avro::ProtocolMessage
+/// [`BufferPool`]: crate::buffer_pool::BufferPool
+#[derive(Debug, Clone, PartialEq)]
+pub struct KindBenchmark {
+    pub name: String,
+    pub schema: String,
+    pub message_bytes: usize,
+    pub iterations: u32,
+    pub encode_ns: f64,
+    pub encode_into_ns: f64,
+    pub decode_ns: f64,
+    pub encode_allocations: Option<f64>,
+    pub encode_into_allocations: Option<f64>,
+    pub decode_allocations: Option<f64>,
+}
+
+impl KindBenchmark {
+    pub fn encode_bytes_per_second(&self) -> f64 {
+        self.message_bytes as f64 * 1e9 / self.encode_ns
+    }
+
+    pub fn decode_bytes_per_second(&self) -> f64 {
+        self.message_bytes as f64 * 1e9 / self.decode_ns
+    }
+}
+
+/// Measures every boundary case of the golden corpus, e.g. `unit_element_message_max`,
+/// `iterations` times.
+///
+/// The builder should be configured as in production, signing, encryption and
+/// checksums are part of the measured costs.
+pub fn bench_kinds(mb: &Builder, iterations: u32) -> Vec<KindBenchmark> {
+    assert!(iterations > 0, "At least one iteration is required");
+    boundary_messages()
+        .into_iter()
+        .filter_map(|(name, message)| {
+            let envelope = message.save(mb)?;
+            let schema = envelope.schema.clone();
+            let serialized = mb.save_from_avro(envelope).ok()?;
+
+            let allocations_before = allocations();
+            let started = Instant::now();
+            for _ in 0..iterations {
+                black_box(mb.save_from_avro(message.save(mb)?).ok()?);
+            }
+            let encode_ns = started.elapsed().as_nanos() as f64 / iterations as f64;
+            let encode_allocations = allocations() - allocations_before;
+
+            let mut out = Vec::with_capacity(serialized.len());
+            let allocations_before = allocations();
+            let started = Instant::now();
+            for _ in 0..iterations {
+                out.clear();
+                mb.save_into(message.save(mb)?, &mut out).ok()?;
+                black_box(&out);
+            }
+            let encode_into_ns = started.elapsed().as_nanos() as f64 / iterations as f64;
+            let encode_into_allocations = allocations() - allocations_before;
+
+            let allocations_before = allocations();
+            let started = Instant::now();
+            for _ in 0..iterations {
+                black_box(mb.load_to_avro(serialized.clone())?);
+            }
+            let decode_ns = started.elapsed().as_nanos() as f64 / iterations as f64;
+            let decode_allocations = allocations() - allocations_before;
+
+            // encoding always allocates, no allocations mean the counter isn't installed
+            let per_message =
+                |count: u64| (encode_allocations > 0).then(|| count as f64 / iterations as f64);
+            Some(KindBenchmark {
+                name,
+                schema,
+                message_bytes: serialized.len(),
+                iterations,
+                encode_ns,
+                encode_into_ns,
+                decode_ns,
+                encode_allocations: per_message(encode_allocations),
+                encode_into_allocations: per_message(encode_into_allocations),
+                decode_allocations: per_message(decode_allocations),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::bench::bench_kinds;
+    use crate::buffer_pool::BufferPool;
+    use crate::utils::get_avro_path;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_bench_kinds() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let results = bench_kinds(&mb, 2);
+        assert!(results.iter().any(|r| r.name == "unit_element_message_max"));
+        for r in &results {
+            assert!(r.message_bytes > 0, "{}", r.name);
+            assert!(r.encode_bytes_per_second() > 0.0, "{}", r.name);
+            assert_eq!(r.encode_allocations, None);
+            assert_eq!(r.encode_into_allocations, None);
+        }
+    }
+
+    #[test]
+    fn test_bench_with_pool() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        let pool = Arc::new(BufferPool::default());
+        mb.set_buffer_pool(pool.clone());
+        let results = bench_kinds(&mb, 2);
+        assert!(results.iter().all(|r| r.encode_into_ns > 0.0));
+        assert!(!pool.is_empty());
+    }
+}
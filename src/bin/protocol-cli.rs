@@ -0,0 +1,59 @@
+use protocol::avro::BuilderImpl;
+use protocol::cli::{self, CliError, Command};
+use protocol::utils::get_avro_path;
+use std::io::{self, Read, Write};
+use std::{env, fs, process};
+
+fn read_input(file: Option<&str>) -> Result<Vec<u8>, CliError> {
+    match file {
+        Some(file) => fs::read(file).map_err(|e| CliError::Io(format!("{}: {}", file, e))),
+        None => {
+            let mut input = vec![];
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| CliError::Io(format!("stdin: {}", e)))?;
+            Ok(input)
+        }
+    }
+}
+
+fn run() -> Result<(), CliError> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = cli::parse_args(&args)?;
+    let avro_path = options.schemas.clone().unwrap_or_else(get_avro_path);
+    let mut builder = BuilderImpl::new(&avro_path);
+    if let Some((key_id, key)) = &options.sign {
+        builder.set_signing_key(key_id, key);
+    }
+    for (key_id, key) in &options.verify {
+        builder.add_verification_key(key_id, key);
+    }
+
+    match &options.command {
+        Command::Decode(file) => {
+            println!("{}", cli::decode(&builder, &read_input(file.as_deref())?)?)
+        }
+        Command::Encode(file) => {
+            let json = String::from_utf8(read_input(file.as_deref())?)
+                .map_err(|e| CliError::Json(e.to_string()))?;
+            io::stdout()
+                .write_all(&cli::encode(&builder, &json)?)
+                .map_err(|e| CliError::Io(format!("stdout: {}", e)))?;
+        }
+        Command::Schemas => println!("{}", cli::schemas(&builder).join("\n")),
+        Command::Fingerprint(schema) => {
+            println!("{}", cli::fingerprints(&builder, schema.as_deref())?)
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(match e {
+            CliError::Usage(_) => 2,
+            _ => 1,
+        });
+    }
+}
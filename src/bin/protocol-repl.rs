@@ -0,0 +1,104 @@
+use protocol::avro::BuilderImpl;
+use protocol::repl::{read_frame, write_frame, Output, Session};
+use protocol::utils::get_avro_path;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::time::Duration;
+use std::{env, process};
+
+const USAGE: &str = "Usage: protocol-repl <host:port> [avro schema dir] [response timeout ms]";
+
+struct ReplHelper {
+    session: Session,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.session.complete(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Prints what arrives until the peer keeps silent for the timeout.
+fn print_responses(session: &Session, stream: &mut TcpStream) {
+    loop {
+        match read_frame(stream) {
+            Ok((topic, message)) => match session.describe(&message) {
+                Ok(text) => println!("<- {}\n{}", topic, text),
+                Err(e) => println!("<- {}: {}", topic, e),
+            },
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return,
+            Err(e) => {
+                eprintln!("Connection failed: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn main() -> rustyline::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let address = match args.first() {
+        Some(address) => address,
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let avro_path = args.get(1).cloned().unwrap_or_else(get_avro_path);
+    let timeout_ms = args.get(2).and_then(|t| t.parse().ok()).unwrap_or(2_000);
+
+    let mut stream = TcpStream::connect(address).unwrap_or_else(|e| {
+        eprintln!("Unable to connect to {}: {}", address, e);
+        process::exit(1);
+    });
+    stream.set_read_timeout(Some(Duration::from_millis(timeout_ms)))?;
+
+    let mut editor = Editor::<ReplHelper>::new()?;
+    editor.set_helper(Some(ReplHelper {
+        session: Session::new(BuilderImpl::new(avro_path.as_str())),
+    }));
+
+    loop {
+        let line = match editor.readline("protocol> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        editor.add_history_entry(line.as_str());
+        let session = &mut editor.helper_mut().unwrap().session;
+        match session.execute(&line) {
+            Ok(Output::Text(text)) if text.is_empty() => {}
+            Ok(Output::Text(text)) => println!("{}", text.trim_end()),
+            Ok(Output::Send { topic, message }) => {
+                write_frame(&mut stream, &topic, &message)?;
+                println!("-> {} ({} bytes)", topic, message.len());
+                print_responses(session, &mut stream);
+            }
+            Ok(Output::Quit) => return Ok(()),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
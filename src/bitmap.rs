@@ -0,0 +1,401 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+
+/// Containers with more values than this keep them as a bitmap.
+const ARRAY_MAX_LENGTH: usize = 4096;
+const BITMAP_WORDS: usize = 1024;
+
+const ARRAY_CONTAINER: u8 = 0;
+const BITMAP_CONTAINER: u8 = 1;
+const RUNS_CONTAINER: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Container {
+    Array(Vec<u16>),
+    Bitmap(Box<[u64; BITMAP_WORDS]>),
+}
+
+impl Container {
+    fn contains(&self, low: u16) -> bool {
+        match self {
+            Container::Array(values) => values.binary_search(&low).is_ok(),
+            Container::Bitmap(words) => words[low as usize / 64] & (1 << (low % 64)) != 0,
+        }
+    }
+
+    fn insert(&mut self, low: u16) -> bool {
+        match self {
+            Container::Array(values) => match values.binary_search(&low) {
+                Ok(_) => false,
+                Err(pos) => {
+                    values.insert(pos, low);
+                    if values.len() > ARRAY_MAX_LENGTH {
+                        let promoted = Container::from_sorted(values.iter().copied());
+                        *self = promoted;
+                    }
+                    true
+                }
+            },
+            Container::Bitmap(words) => {
+                let word = &mut words[low as usize / 64];
+                let inserted = *word & (1 << (low % 64)) == 0;
+                *word |= 1 << (low % 64);
+                inserted
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Container::Array(values) => values.len(),
+            Container::Bitmap(words) => words.iter().map(|w| w.count_ones() as usize).sum(),
+        }
+    }
+
+    fn values(&self) -> Vec<u16> {
+        match self {
+            Container::Array(values) => values.clone(),
+            Container::Bitmap(words) => (0..=u16::MAX)
+                .filter(|low| words[*low as usize / 64] & (1 << (low % 64)) != 0)
+                .collect(),
+        }
+    }
+
+    /// Keeps the invariant: arrays up to [`ARRAY_MAX_LENGTH`] values, bitmaps above.
+    fn from_sorted(values: impl Iterator<Item = u16>) -> Self {
+        let values: Vec<_> = values.collect();
+        if values.len() <= ARRAY_MAX_LENGTH {
+            return Container::Array(values);
+        }
+        let mut words = Box::new([0u64; BITMAP_WORDS]);
+        for low in values {
+            words[low as usize / 64] |= 1 << (low % 64);
+        }
+        Container::Bitmap(words)
+    }
+
+    fn runs(values: &[u16]) -> Vec<(u16, u16)> {
+        let mut runs: Vec<(u16, u16)> = Vec::new();
+        for &low in values {
+            match runs.last_mut() {
+                Some((_, last)) if u32::from(*last) + 1 == u32::from(low) => *last = low,
+                _ => runs.push((low, low)),
+            }
+        }
+        runs
+    }
+
+    /// Writes the smallest of the array, bitmap and run encodings.
+    fn encode(&self, out: &mut Vec<u8>) {
+        let values = self.values();
+        let runs = Container::runs(&values);
+        let array_size = 2 + 2 * values.len();
+        let runs_size = 2 + 4 * runs.len();
+        if runs_size < array_size && runs_size < 8 * BITMAP_WORDS {
+            out.push(RUNS_CONTAINER);
+            out.extend_from_slice(&((runs.len() - 1) as u16).to_le_bytes());
+            for (first, last) in runs {
+                out.extend_from_slice(&first.to_le_bytes());
+                out.extend_from_slice(&(last - first).to_le_bytes());
+            }
+        } else if array_size <= 8 * BITMAP_WORDS {
+            out.push(ARRAY_CONTAINER);
+            out.extend_from_slice(&((values.len() - 1) as u16).to_le_bytes());
+            for low in values {
+                out.extend_from_slice(&low.to_le_bytes());
+            }
+        } else {
+            out.push(BITMAP_CONTAINER);
+            let words = match Container::from_sorted(values.into_iter()) {
+                Container::Bitmap(words) => words,
+                Container::Array(_) => unreachable!(),
+            };
+            for word in words.iter() {
+                out.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < n {
+            return Err(String::from("Unit bitmap is truncated"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Compressed set of unit offsets, roaring-style: the offsets are grouped by their
+/// high 16 bits and each group is kept as a sorted array, a bitmap or runs, whichever
+/// is the smallest.
+///
+/// Recorded footage is mostly long runs of units, so a day of a track with a few
+/// gaps takes a few bytes per gap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass)]
+pub struct UnitBitmap {
+    containers: BTreeMap<u16, Container>,
+}
+
+impl UnitBitmap {
+    pub fn new() -> Self {
+        UnitBitmap::default()
+    }
+
+    pub fn insert(&mut self, offset: u32) -> bool {
+        let (key, low) = ((offset >> 16) as u16, offset as u16);
+        self.containers
+            .entry(key)
+            .or_insert_with(|| Container::Array(vec![]))
+            .insert(low)
+    }
+
+    pub fn contains(&self, offset: u32) -> bool {
+        let (key, low) = ((offset >> 16) as u16, offset as u16);
+        self.containers
+            .get(&key)
+            .map(|c| c.contains(low))
+            .unwrap_or(false)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.containers.values().map(|c| c.len() as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Ranges of consecutive offsets, both ends included.
+    pub fn runs(&self) -> Vec<(u32, u32)> {
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for offset in self.offsets() {
+            match runs.last_mut() {
+                Some((_, last)) if *last + 1 == offset => *last = offset,
+                _ => runs.push((offset, offset)),
+            }
+        }
+        runs
+    }
+
+    pub fn offsets(&self) -> Vec<u32> {
+        self.containers
+            .iter()
+            .flat_map(|(key, c)| {
+                c.values()
+                    .into_iter()
+                    .map(move |low| u32::from(*key) << 16 | u32::from(low))
+            })
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.containers.len() as u32).to_le_bytes());
+        for (key, container) in &self.containers {
+            out.extend_from_slice(&key.to_le_bytes());
+            container.encode(&mut out);
+        }
+        out
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitBitmap {
+    #[new]
+    fn py_new() -> Self {
+        Self::new()
+    }
+
+    #[pyo3(name = "insert")]
+    fn py_insert(&mut self, offset: u32) -> bool {
+        self.insert(offset)
+    }
+
+    #[pyo3(name = "contains")]
+    fn py_contains(&self, offset: u32) -> bool {
+        self.contains(offset)
+    }
+
+    #[pyo3(name = "len")]
+    fn py_len(&self) -> u64 {
+        self.len()
+    }
+
+    #[pyo3(name = "is_empty")]
+    fn py_is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    #[pyo3(name = "runs")]
+    fn py_runs(&self) -> Vec<(u32, u32)> {
+        self.runs()
+    }
+
+    #[pyo3(name = "offsets")]
+    fn py_offsets(&self) -> Vec<u32> {
+        self.offsets()
+    }
+
+    #[pyo3(name = "to_bytes")]
+    fn py_to_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_bytes")]
+    fn py_from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        UnitBitmap::from_bytes(&data).map_err(PyValueError::new_err)
+    }
+
+    fn __len__(&self) -> usize {
+        self.len() as usize
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UnitBitmap {{ runs: {:?} }}", self.runs())
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl UnitBitmap {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { data };
+        let mut containers = BTreeMap::new();
+        let mut previous_key: Option<u16> = None;
+        for _ in 0..reader.u32()? {
+            let key = reader.u16()?;
+            if matches!(previous_key, Some(previous) if previous >= key) {
+                return Err(String::from("Unit bitmap containers aren't sorted"));
+            }
+            previous_key = Some(key);
+            let values: Vec<u16> = match reader.u8()? {
+                ARRAY_CONTAINER => {
+                    let count = reader.u16()? as usize + 1;
+                    let values = (0..count)
+                        .map(|_| reader.u16())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if values.windows(2).any(|w| w[0] >= w[1]) {
+                        return Err(String::from("Unit bitmap array isn't sorted"));
+                    }
+                    values
+                }
+                BITMAP_CONTAINER => {
+                    let mut words = Box::new([0u64; BITMAP_WORDS]);
+                    for word in words.iter_mut() {
+                        *word = reader.u64()?;
+                    }
+                    Container::Bitmap(words).values()
+                }
+                RUNS_CONTAINER => {
+                    let count = reader.u16()? as usize + 1;
+                    let mut values = Vec::new();
+                    for _ in 0..count {
+                        let (first, length) = (reader.u16()?, reader.u16()?);
+                        let last = first
+                            .checked_add(length)
+                            .ok_or_else(|| String::from("Unit bitmap run overflows"))?;
+                        if matches!(values.last(), Some(previous) if *previous >= first) {
+                            return Err(String::from("Unit bitmap runs aren't sorted"));
+                        }
+                        values.extend(first..=last);
+                    }
+                    values
+                }
+                kind => return Err(format!("Unknown unit bitmap container {}", kind)),
+            };
+            if values.is_empty() {
+                return Err(String::from("Unit bitmap container is empty"));
+            }
+            containers.insert(key, Container::from_sorted(values.into_iter()));
+        }
+        if !reader.data.is_empty() {
+            return Err(String::from("Unit bitmap has trailing bytes"));
+        }
+        Ok(UnitBitmap { containers })
+    }
+}
+
+impl FromIterator<u32> for UnitBitmap {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        let mut bitmap = UnitBitmap::new();
+        for offset in iter {
+            bitmap.insert(offset);
+        }
+        bitmap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmap::UnitBitmap;
+
+    #[test]
+    fn test_runs() {
+        // a day of one unit per second with two gaps
+        let bitmap: UnitBitmap = (0..86_400u32)
+            .filter(|u| !(1000..1060).contains(u) && !(50_000..70_000).contains(u))
+            .collect();
+        assert_eq!(bitmap.len(), 86_400 - 60 - 20_000);
+        assert_eq!(
+            bitmap.runs(),
+            vec![(0, 999), (1060, 49_999), (70_000, 86_399)]
+        );
+        assert!(bitmap.contains(999));
+        assert!(!bitmap.contains(1000));
+
+        let data = bitmap.to_bytes();
+        assert!(data.len() < 64);
+        assert_eq!(UnitBitmap::from_bytes(&data).unwrap(), bitmap);
+    }
+
+    #[test]
+    fn test_sparse_and_dense() {
+        let sparse: UnitBitmap = (0..100u32).map(|u| u * 1000).collect();
+        assert_eq!(UnitBitmap::from_bytes(&sparse.to_bytes()).unwrap(), sparse);
+
+        let dense: UnitBitmap = (0..65_536u32).filter(|u| u % 3 != 0).collect();
+        let data = dense.to_bytes();
+        assert_eq!(data.len(), 4 + 2 + 1 + 8192);
+        assert_eq!(UnitBitmap::from_bytes(&data).unwrap(), dense);
+
+        assert_eq!(
+            UnitBitmap::from_bytes(&UnitBitmap::new().to_bytes()).unwrap(),
+            UnitBitmap::new()
+        );
+        assert!(UnitBitmap::from_bytes(&data[..100]).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Mutex;
+
+/// Recycles the byte buffers of encoding and decoding, so a builder handling a
+/// steady flow of messages stops hitting the allocator for them.
+///
+/// A buffer grown over `max_capacity` isn't kept, one huge message doesn't pin
+/// its memory for the rest of the process.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_buffers: usize,
+    max_capacity: usize,
+}
+
+impl BufferPool {
+    pub fn new(max_buffers: usize, max_capacity: usize) -> Self {
+        BufferPool {
+            buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+            max_buffers,
+            max_capacity,
+        }
+    }
+
+    /// An empty buffer, allocated only when the pool has none.
+    pub fn take(&self) -> Vec<u8> {
+        self.lock().pop().unwrap_or_default()
+    }
+
+    pub fn put(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() == 0 || buffer.capacity() > self.max_capacity {
+            return;
+        }
+        let mut buffers = self.lock();
+        if buffers.len() < self.max_buffers {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+
+    /// Buffers waiting to be taken.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        // the buffers are plain bytes, a panic elsewhere can't leave them inconsistent
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        BufferPool::new(64, 1024 * 1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer_pool::BufferPool;
+
+    #[test]
+    fn test_recycles() {
+        let pool = BufferPool::new(1, 1024);
+        let mut buffer = pool.take();
+        assert_eq!(buffer.capacity(), 0);
+        buffer.extend_from_slice(&[1, 2, 3]);
+        pool.put(buffer);
+        assert_eq!(pool.len(), 1);
+
+        let buffer = pool.take();
+        assert!(buffer.is_empty() && buffer.capacity() >= 3);
+        assert!(pool.is_empty());
+
+        pool.put(Vec::with_capacity(2048));
+        assert!(pool.is_empty());
+        pool.put(buffer);
+        pool.put(Vec::with_capacity(16));
+        assert_eq!(pool.len(), 1);
+    }
+}
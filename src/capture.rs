@@ -0,0 +1,384 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// Leads a capture file, the last byte is the format version.
+pub const CAPTURE_MAGIC: [u8; 8] = *b"PRCAPT\x00\x01";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    NotACapture,
+    /// The file ends in the middle of the record at the offset, e.g. the writer
+    /// crashed while appending it.
+    Truncated {
+        offset: u64,
+    },
+    Corrupt {
+        offset: u64,
+        reason: String,
+    },
+    Io(String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::NotACapture => write!(f, "Not a capture file"),
+            CaptureError::Truncated { offset } => {
+                write!(f, "Capture is truncated in the record at {}", offset)
+            }
+            CaptureError::Corrupt { offset, reason } => {
+                write!(f, "Capture record at {} is corrupted: {}", offset, reason)
+            }
+            CaptureError::Io(m) => write!(f, "Capture I/O failed: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl From<io::Error> for CaptureError {
+    fn from(e: io::Error) -> Self {
+        CaptureError::Io(e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedMessage {
+    pub captured_ms: u64,
+    pub topic: String,
+    pub message: Vec<u8>,
+}
+
+/// Appends the encoded messages to a capture: the magic, then for every message the
+/// capture time, the topic and the message, each length-delimited.
+pub struct CaptureWriter<W: Write> {
+    out: W,
+    count: u64,
+}
+
+impl CaptureWriter<BufWriter<File>> {
+    /// Opens the capture at `path` for appending, a new or empty file is started.
+    pub fn append(path: &Path) -> Result<Self, CaptureError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        if file.metadata()?.len() == 0 {
+            return CaptureWriter::new(BufWriter::new(file));
+        }
+        let mut magic = [0u8; 8];
+        match file.read_exact(&mut magic) {
+            Ok(()) if magic == CAPTURE_MAGIC => Ok(CaptureWriter {
+                out: BufWriter::new(file),
+                count: 0,
+            }),
+            _ => Err(CaptureError::NotACapture),
+        }
+    }
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Starts a capture, writes the magic.
+    pub fn new(mut out: W) -> Result<Self, CaptureError> {
+        out.write_all(&CAPTURE_MAGIC)?;
+        Ok(CaptureWriter { out, count: 0 })
+    }
+
+    pub fn record(
+        &mut self,
+        captured_ms: u64,
+        topic: &str,
+        message: &[u8],
+    ) -> Result<(), CaptureError> {
+        let mut record = Vec::with_capacity(16 + topic.len() + message.len());
+        record.extend_from_slice(&captured_ms.to_le_bytes());
+        record.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+        record.extend_from_slice(topic.as_bytes());
+        record.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        record.extend_from_slice(message);
+        self.out.write_all(&record)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Messages recorded by this writer.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn flush(&mut self) -> Result<(), CaptureError> {
+        Ok(self.out.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Reads the messages of a capture in order; stops after the first error.
+pub struct CaptureReader<R: Read> {
+    input: R,
+    offset: u64,
+    failed: bool,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self, CaptureError> {
+        CaptureReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(mut input: R) -> Result<Self, CaptureError> {
+        let mut magic = [0u8; 8];
+        match input.read_exact(&mut magic) {
+            Ok(()) if magic == CAPTURE_MAGIC => Ok(CaptureReader {
+                input,
+                offset: CAPTURE_MAGIC.len() as u64,
+                failed: false,
+            }),
+            Ok(()) => Err(CaptureError::NotACapture),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Err(CaptureError::NotACapture),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fills `buf`; `false` when the input ends before the first byte.
+    fn read_part(&mut self, buf: &mut [u8], start: u64) -> Result<bool, CaptureError> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.input.read(&mut buf[read..]) {
+                Ok(0) if read == 0 && self.offset == start => return Ok(false),
+                Ok(0) => return Err(CaptureError::Truncated { offset: start }),
+                Ok(n) => {
+                    read += n;
+                    self.offset += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads a part of `len` bytes. The buffer grows with the input, so a corrupted
+    /// length can't allocate more than the capture holds.
+    fn read_sized(&mut self, len: u32, start: u64) -> Result<Vec<u8>, CaptureError> {
+        let mut buf = Vec::new();
+        let read = (&mut self.input)
+            .take(u64::from(len))
+            .read_to_end(&mut buf)?;
+        self.offset += read as u64;
+        if read < len as usize {
+            return Err(CaptureError::Truncated { offset: start });
+        }
+        Ok(buf)
+    }
+
+    fn read_record(&mut self) -> Result<Option<CapturedMessage>, CaptureError> {
+        let start = self.offset;
+        let mut head = [0u8; 12];
+        if !self.read_part(&mut head, start)? {
+            return Ok(None);
+        }
+        let captured_ms = u64::from_le_bytes(head[..8].try_into().unwrap());
+        let topic = self.read_sized(u32::from_le_bytes(head[8..].try_into().unwrap()), start)?;
+        let topic = String::from_utf8(topic).map_err(|_| CaptureError::Corrupt {
+            offset: start,
+            reason: String::from("the topic isn't a valid UTF-8"),
+        })?;
+        let mut len = [0u8; 4];
+        self.read_part(&mut len, start)?;
+        let message = self.read_sized(u32::from_le_bytes(len), start)?;
+        Ok(Some(CapturedMessage {
+            captured_ms,
+            topic,
+            message,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CapturedMessage, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.read_record().transpose();
+        self.failed = matches!(record, Some(Err(_)));
+        record
+    }
+}
+
+/// How fast a capture is replayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    /// Back to back.
+    AsFast,
+    /// The gaps between the messages as they were captured.
+    Original,
+    /// The captured gaps divided by the factor, e.g. `4.0` replays four times faster.
+    Accelerated(f64),
+}
+
+impl Pace {
+    fn gap(&self, captured_gap_ms: u64) -> Duration {
+        match self {
+            Pace::AsFast => Duration::ZERO,
+            Pace::Original => Duration::from_millis(captured_gap_ms),
+            Pace::Accelerated(factor) if *factor > 0.0 => {
+                Duration::from_secs_f64(captured_gap_ms as f64 / 1000.0 / factor)
+            }
+            Pace::Accelerated(_) => Duration::ZERO,
+        }
+    }
+}
+
+/// Passes the messages of the capture to `callback` at the pace; returns how many
+/// were replayed. The gaps are slept between the callbacks, so the time they take
+/// adds up.
+pub fn replay<R, F>(reader: CaptureReader<R>, pace: Pace, callback: F) -> Result<u64, CaptureError>
+where
+    R: Read,
+    F: FnMut(&CapturedMessage),
+{
+    replay_with(reader, pace, thread::sleep, callback)
+}
+
+/// [`replay`] which waits with `sleep`, e.g. the one of an async runtime or a test.
+pub fn replay_with<R, S, F>(
+    reader: CaptureReader<R>,
+    pace: Pace,
+    mut sleep: S,
+    mut callback: F,
+) -> Result<u64, CaptureError>
+where
+    R: Read,
+    S: FnMut(Duration),
+    F: FnMut(&CapturedMessage),
+{
+    let mut replayed = 0;
+    let mut last_ms = None;
+    for message in reader {
+        let message = message?;
+        if let Some(last_ms) = last_ms {
+            let gap = pace.gap(message.captured_ms.saturating_sub(last_ms));
+            if !gap.is_zero() {
+                sleep(gap);
+            }
+        }
+        last_ms = Some(message.captured_ms);
+        callback(&message);
+        replayed += 1;
+    }
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capture::{
+        replay_with, CaptureError, CaptureReader, CaptureWriter, CapturedMessage, Pace,
+        CAPTURE_MAGIC,
+    };
+    use std::fs;
+    use std::time::Duration;
+
+    fn capture() -> Vec<u8> {
+        let mut writer = CaptureWriter::new(vec![]).unwrap();
+        writer.record(1_000, "/a", b"first").unwrap();
+        writer.record(1_400, "/b", b"").unwrap();
+        writer.record(1_300, "/a", b"third").unwrap();
+        assert_eq!(writer.count(), 3);
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut sleeps = vec![];
+        let mut replayed = vec![];
+        let capture = capture();
+        let reader = CaptureReader::new(capture.as_slice()).unwrap();
+        let count = replay_with(
+            reader,
+            Pace::Accelerated(4.0),
+            |gap| sleeps.push(gap),
+            |message| replayed.push(message.clone()),
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(sleeps, vec![Duration::from_millis(100)]);
+        assert_eq!(
+            replayed[1],
+            CapturedMessage {
+                captured_ms: 1_400,
+                topic: String::from("/b"),
+                message: vec![],
+            }
+        );
+
+        let reader = CaptureReader::new(capture.as_slice()).unwrap();
+        sleeps.clear();
+        replay_with(reader, Pace::Original, |gap| sleeps.push(gap), |_| {}).unwrap();
+        assert_eq!(sleeps, vec![Duration::from_millis(400)]);
+    }
+
+    #[test]
+    fn test_damaged_capture() {
+        let capture = capture();
+        let truncated = &capture[..capture.len() - 2];
+        let read: Vec<_> = CaptureReader::new(truncated).unwrap().collect();
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[2], Err(CaptureError::Truncated { offset: 49 }));
+
+        let mut oversized = CAPTURE_MAGIC.to_vec();
+        oversized.extend_from_slice(&1_000u64.to_le_bytes());
+        oversized.extend_from_slice(&u32::MAX.to_le_bytes());
+        oversized.extend_from_slice(b"/a");
+        let read: Vec<_> = CaptureReader::new(oversized.as_slice()).unwrap().collect();
+        assert_eq!(read, vec![Err(CaptureError::Truncated { offset: 8 })]);
+
+        assert!(matches!(
+            CaptureReader::new(&b"PRCAPT"[..]),
+            Err(CaptureError::NotACapture)
+        ));
+        assert!(matches!(
+            CaptureReader::new(&capture[1..]),
+            Err(CaptureError::NotACapture)
+        ));
+    }
+
+    #[test]
+    fn test_append() {
+        let path = std::env::temp_dir().join(format!("protocol-capture-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut writer = CaptureWriter::append(&path).unwrap();
+        writer.record(1, "/a", b"first").unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        let mut writer = CaptureWriter::append(&path).unwrap();
+        writer.record(2, "/a", b"second").unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let read: Vec<_> = CaptureReader::open(&path)
+            .unwrap()
+            .map(|m| m.unwrap().message)
+            .collect();
+        assert_eq!(read, vec![b"first".to_vec(), b"second".to_vec()]);
+        assert_eq!(&fs::read(&path).unwrap()[..8], &CAPTURE_MAGIC);
+
+        fs::write(&path, b"not a capture").unwrap();
+        assert!(matches!(
+            CaptureWriter::append(&path),
+            Err(CaptureError::NotACapture)
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
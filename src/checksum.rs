@@ -0,0 +1,236 @@
+use crate::primitives::{Checksum, ChecksumAlgorithm};
+use avro_rs::types::Value;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/// Reflected CRC-32 of the data with the reversed polynomial.
+fn crc32_reflected(data: &[u8], polynomial: u32) -> u32 {
+    let mut crc = !0u32;
+    for b in data {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// CRC-32C (Castagnoli) of the data.
+pub fn crc32c(data: &[u8]) -> u32 {
+    crc32_reflected(data, 0x82F6_3B78)
+}
+
+/// CRC-32 (IEEE 802.3) of the data.
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_reflected(data, 0xEDB8_8320)
+}
+
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_le_bytes(data[..4].try_into().unwrap())
+}
+
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn xxh64_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ xxh64_round(0, value))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+/// XXH64 of the data.
+pub fn xxhash64(data: &[u8], seed: u64) -> u64 {
+    let mut rest = data;
+    let mut hash = if data.len() >= 32 {
+        let mut v = [
+            seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+            seed.wrapping_add(PRIME64_2),
+            seed,
+            seed.wrapping_sub(PRIME64_1),
+        ];
+        while rest.len() >= 32 {
+            for (i, acc) in v.iter_mut().enumerate() {
+                *acc = xxh64_round(*acc, read_u64(&rest[i * 8..]));
+            }
+            rest = &rest[32..];
+        }
+        let hash = v[0]
+            .rotate_left(1)
+            .wrapping_add(v[1].rotate_left(7))
+            .wrapping_add(v[2].rotate_left(12))
+            .wrapping_add(v[3].rotate_left(18));
+        v.iter().fold(hash, |hash, acc| xxh64_merge(hash, *acc))
+    } else {
+        seed.wrapping_add(PRIME64_5)
+    };
+    hash = hash.wrapping_add(data.len() as u64);
+
+    while rest.len() >= 8 {
+        hash ^= xxh64_round(0, read_u64(rest));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        hash ^= (read_u32(rest) as u64).wrapping_mul(PRIME64_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(PRIME64_2)
+            .wrapping_add(PRIME64_3);
+        rest = &rest[4..];
+    }
+    for b in rest {
+        hash ^= (*b as u64).wrapping_mul(PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^= hash >> 32;
+    hash
+}
+
+/// What the builder does when a decoded payload doesn't match its checksum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum ChecksumPolicy {
+    Fail,
+    #[default]
+    Warn,
+    Skip,
+}
+
+pub fn compute(algo: ChecksumAlgorithm, data: &[u8]) -> Result<Checksum, String> {
+    match algo {
+        ChecksumAlgorithm::Crc32c => Ok(Checksum::new(algo, crc32c(data).into())),
+        ChecksumAlgorithm::XxHash64 => Ok(Checksum::new(algo, xxhash64(data, 0))),
+        ChecksumAlgorithm::NotImplemented => Err(String::from("Not supported checksum algorithm")),
+    }
+}
+
+pub fn verify(checksum: &Checksum, data: &[u8]) -> Result<(), String> {
+    let actual = compute(checksum.algo, data)?;
+    if actual.value != checksum.value {
+        return Err(format!(
+            "{:?} checksum mismatch: expected {:x}, got {:x}",
+            checksum.algo, checksum.value, actual.value
+        ));
+    }
+    Ok(())
+}
+
+fn record_field<'a>(fields: &'a mut [(String, Value)], name: &str) -> Option<&'a mut Value> {
+    fields.iter_mut().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn payload_data(fields: &[(String, Value)]) -> Option<&Vec<u8>> {
+    fields
+        .iter()
+        .find(|(n, _)| n == "value" || n == "data")
+        .and_then(|(_, v)| match v {
+            Value::Bytes(data) => Some(data),
+            _ => None,
+        })
+}
+
+fn is_encrypted(fields: &[(String, Value)]) -> bool {
+    fields.iter().any(|(n, v)| match (n.as_str(), v) {
+        ("encryption", Value::Union(inner)) => **inner != Value::Null,
+        _ => false,
+    })
+}
+
+/// Sets the missing checksums of a unit element record or of every payload of an
+/// element response record.
+pub fn seal_record(algo: ChecksumAlgorithm, record: &mut Value) -> Result<(), String> {
+    let fields = match record {
+        Value::Record(fields) => fields,
+        _ => return Err(String::from("Unable to match AVRO Record.")),
+    };
+    if let Some(Value::Array(values)) = record_field(fields, "values") {
+        return values.iter_mut().try_for_each(|v| seal_record(algo, v));
+    }
+    let checksum = match payload_data(fields) {
+        Some(data) => compute(algo, data)?,
+        None => return Err(String::from("Record has no payload bytes to checksum")),
+    };
+    match record_field(fields, "checksum") {
+        Some(Value::Union(inner)) if **inner == Value::Null => {
+            **inner = checksum.to_avro_record()?;
+            Ok(())
+        }
+        Some(_) => Ok(()),
+        None => Err(String::from("Record has no `checksum` field")),
+    }
+}
+
+/// Verifies the checksums of a record sealed with [`seal_record`]. Payloads without a
+/// checksum and the ones still encrypted are accepted as is.
+pub fn verify_record(record: &Value) -> Result<(), String> {
+    let fields = match record {
+        Value::Record(fields) => fields,
+        _ => return Err(String::from("Unable to match AVRO Record.")),
+    };
+    if let Some((_, Value::Array(values))) = fields.iter().find(|(n, _)| n == "values") {
+        return values.iter().try_for_each(verify_record);
+    }
+    if is_encrypted(fields) {
+        return Ok(());
+    }
+    let checksum = fields
+        .iter()
+        .find(|(n, _)| n == "checksum")
+        .and_then(|(_, v)| Checksum::from_avro_union(v));
+    match (checksum, payload_data(fields)) {
+        (Some(checksum), Some(data)) => verify(&checksum, data),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checksum::{compute, crc32, crc32c, verify, xxhash64};
+    use crate::primitives::ChecksumAlgorithm;
+
+    #[test]
+    fn test_known_values() {
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(xxhash64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxhash64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        assert_eq!(
+            xxhash64(b"Nobody inspects the spammish repetition", 0),
+            0xFBCE_A83C_8A37_8BF1
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        for algo in [ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::XxHash64] {
+            let checksum = compute(algo, &[1, 2, 3]).unwrap();
+            assert!(verify(&checksum, &[1, 2, 3]).is_ok());
+            assert!(verify(&checksum, &[1, 2, 4]).is_err());
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::avro::{BuilderImpl, ProtocolMessage};
+use crate::fingerprint::{schema_rabin, schema_sha256};
+use crate::json::from_json;
+use serde_json::Value as Json;
+use std::fmt;
+
+pub const USAGE: &str = "\
+Usage: protocol-cli [options] <command>
+
+Commands:
+  decode [file]             prints the message of the file or stdin as JSON
+  encode [file]             writes the message the JSON describes to stdout
+  schemas                   lists the schemas
+  fingerprint [schema]      prints the CRC-64-AVRO and SHA-256 fingerprints
+
+Options:
+  --schemas <dir>           avro schema directory
+  --sign <key id>=<key>     signs the encoded message
+  --verify <key id>=<key>   verifies the decoded message, may be repeated
+
+The JSON is {\"schema\": ..., \"object\": ...}, bytes are base64 strings.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    Usage(String),
+    Io(String),
+    Json(String),
+    UnknownSchema(String),
+    Protocol(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(m) => write!(f, "{}\n\n{}", m, USAGE),
+            CliError::Io(m) => write!(f, "{}", m),
+            CliError::Json(m) => write!(f, "Invalid JSON: {}", m),
+            CliError::UnknownSchema(schema) => write!(f, "Unknown schema {}", schema),
+            CliError::Protocol(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Reads stdin without a file.
+    Decode(Option<String>),
+    Encode(Option<String>),
+    Schemas,
+    /// All the schemas without a name.
+    Fingerprint(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub schemas: Option<String>,
+    pub sign: Option<(String, Vec<u8>)>,
+    pub verify: Vec<(String, Vec<u8>)>,
+    pub command: Command,
+}
+
+fn parse_key(text: Option<&String>, option: &str) -> Result<(String, Vec<u8>), CliError> {
+    text.and_then(|text| text.split_once('='))
+        .filter(|(key_id, _)| !key_id.is_empty())
+        .map(|(key_id, key)| (String::from(key_id), key.as_bytes().to_vec()))
+        .ok_or_else(|| CliError::Usage(format!("{} takes <key id>=<key>", option)))
+}
+
+/// Options and the command of the arguments without the program name.
+pub fn parse_args(args: &[String]) -> Result<Options, CliError> {
+    let mut schemas = None;
+    let mut sign = None;
+    let mut verify = vec![];
+    let mut rest = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schemas" => {
+                schemas = Some(
+                    args.next()
+                        .cloned()
+                        .ok_or_else(|| CliError::Usage(String::from("--schemas takes a dir")))?,
+                )
+            }
+            "--sign" => sign = Some(parse_key(args.next(), "--sign")?),
+            "--verify" => verify.push(parse_key(args.next(), "--verify")?),
+            option if option.starts_with("--") => {
+                return Err(CliError::Usage(format!("Unknown option {}", option)))
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    let mut rest = rest.into_iter();
+    let command = match rest.next().as_deref() {
+        Some("decode") => Command::Decode(rest.next()),
+        Some("encode") => Command::Encode(rest.next()),
+        Some("schemas") => Command::Schemas,
+        Some("fingerprint") => Command::Fingerprint(rest.next()),
+        Some(command) => return Err(CliError::Usage(format!("Unknown command {}", command))),
+        None => return Err(CliError::Usage(String::from("No command"))),
+    };
+    if let Some(arg) = rest.next() {
+        return Err(CliError::Usage(format!("Unexpected argument {}", arg)));
+    }
+    Ok(Options {
+        schemas,
+        sign,
+        verify,
+        command,
+    })
+}
+
+/// The message as pretty printed `{"schema": ..., "object": ...}`.
+pub fn decode(builder: &BuilderImpl, message: &[u8]) -> Result<String, CliError> {
+    let (schema, object) = builder
+        .read_protocol_message(message)
+        .map_err(|e| CliError::Protocol(e.to_string()))?;
+    serde_json::to_string_pretty(&ProtocolMessage { schema, object })
+        .map_err(|e| CliError::Json(e.to_string()))
+}
+
+/// Encodes the message of the [`decode`] form, the record fields missing from
+/// the object are `null`.
+pub fn encode(builder: &BuilderImpl, json: &str) -> Result<Vec<u8>, CliError> {
+    let json: Json = serde_json::from_str(json).map_err(|e| CliError::Json(e.to_string()))?;
+    let schema = json
+        .get("schema")
+        .and_then(Json::as_str)
+        .ok_or_else(|| CliError::Json(String::from("no schema")))?;
+    let avro_schema = builder
+        .get_schema(schema)
+        .ok_or_else(|| CliError::UnknownSchema(String::from(schema)))?;
+    let object = from_json(&avro_schema, json.get("object").unwrap_or(&Json::Null))
+        .ok_or_else(|| CliError::Json(format!("the object doesn't match {}", schema)))?;
+    builder
+        .pack_message_with_labels(schema, object, &Default::default())
+        .map_err(|e| CliError::Protocol(e.to_string()))
+}
+
+pub fn schemas(builder: &BuilderImpl) -> Vec<String> {
+    let mut schemas = builder.schema_names();
+    schemas.sort();
+    schemas
+}
+
+/// `<schema> <CRC-64-AVRO> <SHA-256>` lines in hex, of all the schemas without a name.
+pub fn fingerprints(builder: &BuilderImpl, schema: Option<&str>) -> Result<String, CliError> {
+    let names = match schema {
+        Some(schema) => vec![String::from(schema)],
+        None => schemas(builder),
+    };
+    let lines = names
+        .iter()
+        .map(|name| {
+            let schema = builder
+                .get_schema(name)
+                .ok_or_else(|| CliError::UnknownSchema(name.clone()))?;
+            let sha256: String = schema_sha256(&schema)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            Ok(format!(
+                "{} {:016x} {}",
+                name,
+                schema_rabin(&schema),
+                sha256
+            ))
+        })
+        .collect::<Result<Vec<_>, CliError>>()?;
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{BuilderImpl, KEEPALIVE_MESSAGE_SCHEMA};
+    use crate::cli::{decode, encode, fingerprints, parse_args, schemas, CliError, Command};
+    use crate::utils::get_avro_path;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(&args("--schemas api --sign k1=secret encode msg.json")).unwrap();
+        assert_eq!(options.schemas.as_deref(), Some("api"));
+        assert_eq!(options.sign, Some((String::from("k1"), b"secret".to_vec())));
+        assert_eq!(
+            options.command,
+            Command::Encode(Some(String::from("msg.json")))
+        );
+        assert_eq!(
+            parse_args(&args("decode")).unwrap().command,
+            Command::Decode(None)
+        );
+
+        for line in [
+            "",
+            "send",
+            "decode a b",
+            "--sign secret decode",
+            "--quiet schemas",
+        ] {
+            assert!(matches!(parse_args(&args(line)), Err(CliError::Usage(_))));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        let json = format!(
+            r#"{{"schema": "{}", "object": {{"module_id": "camera-1"}}}}"#,
+            KEEPALIVE_MESSAGE_SCHEMA
+        );
+        let encoded = encode(&builder, &json).unwrap();
+        let decoded = decode(&builder, &encoded).unwrap();
+        assert!(decoded.contains("\"camera-1\""));
+        assert_eq!(encode(&builder, &decoded).unwrap(), encoded);
+
+        assert_eq!(
+            encode(&builder, r#"{"schema": "Unknown.avsc", "object": {}}"#),
+            Err(CliError::UnknownSchema(String::from("Unknown.avsc")))
+        );
+        assert!(matches!(encode(&builder, "{"), Err(CliError::Json(_))));
+        assert!(matches!(
+            decode(&builder, &encoded[..3]),
+            Err(CliError::Protocol(_))
+        ));
+
+        assert!(schemas(&builder).contains(&String::from(KEEPALIVE_MESSAGE_SCHEMA)));
+        let line = fingerprints(&builder, Some(KEEPALIVE_MESSAGE_SCHEMA)).unwrap();
+        assert_eq!(
+            line.split(' ').map(str::len).collect::<Vec<_>>()[1..],
+            [16, 64]
+        );
+        assert_eq!(
+            fingerprints(&builder, None).unwrap().lines().count(),
+            schemas(&builder).len()
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use crate::client::{Transport, TransportFuture};
+use std::io;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+
+/// One end of an in-process link, for the tests and the services embedded into
+/// their clients.
+pub struct MemoryTransport {
+    outgoing: UnboundedSender<Vec<u8>>,
+    incoming: Mutex<UnboundedReceiver<Vec<u8>>>,
+}
+
+impl MemoryTransport {
+    /// The two ends of a link, what one sends the other receives.
+    pub fn pair() -> (MemoryTransport, MemoryTransport) {
+        let (left_tx, left_rx) = unbounded_channel();
+        let (right_tx, right_rx) = unbounded_channel();
+        (
+            MemoryTransport {
+                outgoing: left_tx,
+                incoming: Mutex::new(right_rx),
+            },
+            MemoryTransport {
+                outgoing: right_tx,
+                incoming: Mutex::new(left_rx),
+            },
+        )
+    }
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the other end is dropped")
+}
+
+impl Transport for MemoryTransport {
+    fn send<'a>(&'a self, message: &'a [u8]) -> TransportFuture<'a, ()> {
+        let sent = self.outgoing.send(message.to_vec()).map_err(|_| closed());
+        Box::pin(async move { sent })
+    }
+
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>> {
+        Box::pin(async move { self.incoming.lock().await.recv().await.ok_or_else(closed) })
+    }
+}
@@ -0,0 +1,365 @@
+pub mod memory;
+pub mod tcp;
+
+use crate::avro::{Builder, ProtocolError, ProtocolMessage};
+use crate::correlation::{CorrelationTable, ResponseError};
+use crate::objects::services::error_response::ErrorResponse;
+use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+use crate::objects::services::storage::stream_track_unit_elements::{
+    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{ElementType, StreamName, Unit};
+use log::warn;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// Carries the encoded messages between a client and the services, e.g. a socket or
+/// a broker subscription. A transport delivers whole messages; the routing of them
+/// to the services is its business.
+pub trait Transport: Send + Sync + 'static {
+    fn send<'a>(&'a self, message: &'a [u8]) -> TransportFuture<'a, ()>;
+
+    /// The next message for the client; an error ends the client.
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientError {
+    Transport(String),
+    Protocol(ProtocolError),
+    /// The request failed to save, e.g. a field is out of its range.
+    Invalid(String),
+    Timeout,
+    /// The service answered with an error.
+    Failed(ErrorResponse),
+    /// The response isn't of the kind the request is answered with.
+    UnexpectedResponse(String),
+    /// The transport ended, before the response came or before the request.
+    Closed,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(m) => write!(f, "Transport failed: {}", m),
+            ClientError::Protocol(e) => write!(f, "{}", e),
+            ClientError::Invalid(m) => write!(f, "Invalid request: {}", m),
+            ClientError::Timeout => write!(f, "Request timed out"),
+            ClientError::Failed(e) => write!(f, "Request failed with {:?}: {}", e.code, e.message),
+            ClientError::UnexpectedResponse(schema) => {
+                write!(f, "Unexpected response {}", schema)
+            }
+            ClientError::Closed => write!(f, "Transport is closed"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        ClientError::Transport(e.to_string())
+    }
+}
+
+impl From<ResponseError> for ClientError {
+    fn from(e: ResponseError) -> Self {
+        match e {
+            ResponseError::Failed(error) => ClientError::Failed(error),
+            ResponseError::Decode(e) => ClientError::Protocol(e),
+        }
+    }
+}
+
+impl From<ProtocolError> for ClientError {
+    fn from(e: ProtocolError) -> Self {
+        ClientError::Protocol(e)
+    }
+}
+
+type Responder = oneshot::Sender<Result<ProtocolMessage, ResponseError>>;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Typed requests over a transport: assigns the request ids, waits for the
+/// responses up to the timeout and decodes them.
+///
+/// Receives in a task of the tokio runtime it's created in; dropping the client
+/// stops it.
+pub struct Client<T: Transport> {
+    transport: Arc<T>,
+    mb: Arc<Builder>,
+    pending: Arc<Mutex<CorrelationTable<Responder>>>,
+    closed: Arc<AtomicBool>,
+    reply_topic: String,
+    timeout: Duration,
+    receiver: JoinHandle<()>,
+}
+
+impl<T: Transport> Client<T> {
+    /// The services answer the requests on `reply_topic`, which the transport
+    /// delivers to the client.
+    pub fn new(transport: T, mb: Arc<Builder>, reply_topic: &str) -> Self {
+        let transport = Arc::new(transport);
+        let pending = Arc::new(Mutex::new(CorrelationTable::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let receiver = tokio::spawn(receive(
+            transport.clone(),
+            mb.clone(),
+            pending.clone(),
+            closed.clone(),
+        ));
+        Client {
+            transport,
+            mb,
+            pending,
+            closed,
+            reply_topic: String::from(reply_topic),
+            timeout: Duration::from_secs(10),
+            receiver,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the request `build` makes of the request id and the reply topic, the
+    /// response is any message answering the request id.
+    pub async fn request<R, F>(&self, build: F) -> Result<ProtocolMessage, ClientError>
+    where
+        R: ToProtocolMessage,
+        F: FnOnce(i64, String) -> R,
+    {
+        let (responder, response) = oneshot::channel();
+        let deadline_ms = now_ms() + self.timeout.as_millis() as u64;
+        let request_id = {
+            // checked under the lock, so the request isn't left behind by the expiry
+            // of the closing transport
+            let mut pending = self.pending.lock().unwrap();
+            if self.closed.load(Ordering::Acquire) {
+                return Err(ClientError::Closed);
+            }
+            pending.register(responder, deadline_ms)
+        };
+        let result = self
+            .exchange(build(request_id, self.reply_topic.clone()), response)
+            .await;
+        if result.is_err() {
+            self.pending.lock().unwrap().cancel(request_id);
+        }
+        result
+    }
+
+    async fn exchange<R: ToProtocolMessage>(
+        &self,
+        request: R,
+        response: oneshot::Receiver<Result<ProtocolMessage, ResponseError>>,
+    ) -> Result<ProtocolMessage, ClientError> {
+        let message = request
+            .save(&self.mb)
+            .ok_or_else(|| ClientError::Invalid(String::from("the message doesn't save")))?;
+        let encoded = self.mb.save_from_avro(message)?;
+        self.transport.send(&encoded).await?;
+        match tokio::time::timeout(self.timeout, response).await {
+            Ok(Ok(Ok(response))) => Ok(response),
+            Ok(Ok(Err(error))) => Err(error.into()),
+            Ok(Err(_)) => Err(ClientError::Closed),
+            Err(_) => Err(ClientError::Timeout),
+        }
+    }
+
+    async fn typed_request<R, S, F>(&self, build: F) -> Result<S, ClientError>
+    where
+        R: ToProtocolMessage,
+        S: FromProtocolMessage,
+        F: FnOnce(i64, String) -> R,
+    {
+        let response = self.request(build).await?;
+        S::load(&response).ok_or(ClientError::UnexpectedResponse(response.schema))
+    }
+
+    pub async fn list_tracks(
+        &self,
+        stream_name: StreamName,
+    ) -> Result<StreamTracksResponse, ClientError> {
+        self.typed_request(|request_id, topic| {
+            StreamTracksRequest::new(request_id, topic, stream_name)
+        })
+        .await
+    }
+
+    /// The first page of the elements of the unit, see
+    /// [`StreamTrackUnitElementsResponse::continuation_cursor`].
+    pub async fn get_unit_elements(
+        &self,
+        stream_unit: Unit,
+        max_element: ElementType,
+    ) -> Result<StreamTrackUnitElementsResponse, ClientError> {
+        self.typed_request(|request_id, topic| {
+            StreamTrackUnitElementsRequest::new(request_id, topic, stream_unit, max_element)
+        })
+        .await
+    }
+
+    /// Round trip of a ping answered on the reply topic.
+    pub async fn ping(&self) -> Result<Duration, ClientError> {
+        let started = Instant::now();
+        let pong: PingRequestResponse = self
+            .typed_request(|request_id, reply_topic| {
+                PingRequestResponse::new(request_id, reply_topic, PingRequestResponseType::Request)
+            })
+            .await?;
+        match pong.mtype {
+            PingRequestResponseType::Response => Ok(started.elapsed()),
+            PingRequestResponseType::Request => Err(ClientError::UnexpectedResponse(String::from(
+                "ping request",
+            ))),
+        }
+    }
+
+    /// The transport ended, the requests fail as [`ClientError::Closed`].
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+impl<T: Transport> Drop for Client<T> {
+    fn drop(&mut self) {
+        self.receiver.abort();
+    }
+}
+
+async fn receive<T: Transport>(
+    transport: Arc<T>,
+    mb: Arc<Builder>,
+    pending: Arc<Mutex<CorrelationTable<Responder>>>,
+    closed: Arc<AtomicBool>,
+) {
+    while let Ok(message) = transport.recv().await {
+        let (schema, object) = match mb.builder_impl().read_protocol_message(&message) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Client dropped an undecodable message. Error is {}", e);
+                continue;
+            }
+        };
+        let answered = pending
+            .lock()
+            .unwrap()
+            .on_response(ProtocolMessage { schema, object });
+        if let Some((responder, outcome)) = answered {
+            // the requester may have timed out meanwhile
+            let _ = responder.send(outcome);
+        }
+    }
+    // the responders are dropped, so the requests fail as closed
+    let mut pending = pending.lock().unwrap();
+    closed.store(true, Ordering::Release);
+    pending.expire(u64::MAX);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::client::memory::MemoryTransport;
+    use crate::client::{Client, ClientError, Transport};
+    use crate::message_kind::MessageKind;
+    use crate::objects::services::error_response::{ErrorCode, ErrorResponse};
+    use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+    use crate::objects::services::storage::stream_tracks::StreamTracksResponse;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::Attributes;
+    use crate::utils::get_avro_path;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Answers the tracks of the stream `[1; 16]` and the pings, fails the rest.
+    async fn serve(transport: MemoryTransport, mb: Arc<Builder>) {
+        while let Ok(message) = transport.recv().await {
+            let request = mb.load_to_avro(message).unwrap();
+            let request_id = request.request_id().unwrap();
+            let response = match (request.kind(), request.stream_name()) {
+                (Some(MessageKind::StreamTracksRequest), Some([1, ..])) => {
+                    StreamTracksResponse::new(request_id, [1; 16], vec![]).save(&mb)
+                }
+                (Some(MessageKind::PingRequestResponse), _) => {
+                    let ping = PingRequestResponse::load(&request).unwrap();
+                    assert_eq!(ping.topic, "/services/client");
+                    PingRequestResponse::new(
+                        request_id,
+                        ping.topic,
+                        PingRequestResponseType::Response,
+                    )
+                    .save(&mb)
+                }
+                _ => ErrorResponse::new(
+                    request_id,
+                    ErrorCode::NotFound,
+                    String::from("no stream"),
+                    Attributes::default(),
+                )
+                .save(&mb),
+            };
+            let response = mb.save_from_avro(response.unwrap()).unwrap();
+            transport.send(&response).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client() {
+        let mb = Arc::new(Builder::new(get_avro_path().as_str()));
+        let (client_side, service_side) = MemoryTransport::pair();
+        let service = tokio::spawn(serve(service_side, mb.clone()));
+        let client =
+            Client::new(client_side, mb, "/services/client").with_timeout(Duration::from_secs(5));
+
+        let tracks = client.list_tracks([1; 16]).await.unwrap();
+        assert_eq!(tracks.stream_name, [1; 16]);
+        assert!(client.ping().await.is_ok());
+        match client.list_tracks([2; 16]).await {
+            Err(ClientError::Failed(error)) => assert_eq!(error.code, ErrorCode::NotFound),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(client.pending(), 0);
+
+        service.abort();
+        let _ = service.await;
+        while !client.is_closed() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(client.list_tracks([1; 16]).await, Err(ClientError::Closed));
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let mb = Arc::new(Builder::new(get_avro_path().as_str()));
+        let (client_side, _service_side) = MemoryTransport::pair();
+        let client = Client::new(client_side, mb, "/services/client")
+            .with_timeout(Duration::from_millis(10));
+        assert_eq!(client.ping().await, Err(ClientError::Timeout));
+        assert_eq!(client.pending(), 0);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::client::{Transport, TransportFuture};
+use std::io;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::Mutex;
+
+/// Frames longer than this are refused, the peer is broken or hostile.
+pub const MAX_FRAME_LENGTH: usize = 256 * 1024 * 1024;
+
+/// Messages over a TCP connection, each one prefixed by its length as a big-endian
+/// `u32`.
+pub struct TcpTransport {
+    reader: Mutex<OwnedReadHalf>,
+    writer: Mutex<OwnedWriteHalf>,
+}
+
+impl TcpTransport {
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::from_stream(TcpStream::connect(addr).await?))
+    }
+
+    /// E.g. the stream a listener accepted.
+    pub fn from_stream(stream: TcpStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        TcpTransport {
+            reader: Mutex::new(reader),
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send<'a>(&'a self, message: &'a [u8]) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            if message.len() > MAX_FRAME_LENGTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("message of {} bytes is too long", message.len()),
+                ));
+            }
+            let mut frame = Vec::with_capacity(4 + message.len());
+            frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+            frame.extend_from_slice(message);
+            let mut writer = self.writer.lock().await;
+            writer.write_all(&frame).await?;
+            writer.flush().await
+        })
+    }
+
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>> {
+        Box::pin(async move {
+            let mut reader = self.reader.lock().await;
+            let len = reader.read_u32().await? as usize;
+            if len > MAX_FRAME_LENGTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("frame of {} bytes is too long", len),
+                ));
+            }
+            let mut message = vec![0u8; len];
+            reader.read_exact(&mut message).await?;
+            Ok(message)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tcp::TcpTransport;
+    use crate::client::Transport;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let echo = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let transport = TcpTransport::from_stream(stream);
+            while let Ok(message) = transport.recv().await {
+                transport.send(&message).await.unwrap();
+            }
+        });
+
+        let transport = TcpTransport::connect(addr).await.unwrap();
+        for message in [b"first".to_vec(), vec![], vec![7; 100_000]] {
+            transport.send(&message).await.unwrap();
+            assert_eq!(transport.recv().await.unwrap(), message);
+        }
+        drop(transport);
+        echo.await.unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use crate::avro::{Builder, ProtocolError, ProtocolMessage};
+use avro_rs::types::Value;
+use avro_rs::{from_avro_datum, Schema};
+use std::collections::HashMap;
+use std::str;
+
+/// Envelope of the messages written by protocol-python, before the signature and
+/// label fields were added.
+const LEGACY_ENVELOPE_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "MessageEnvelope",
+    "namespace": "insight.transport",
+    "fields": [
+        {"name": "schema", "type": "bytes"},
+        {"name": "payload", "type": "bytes"}
+    ]
+}"#;
+
+/// Decodes the messages produced by protocol-python and normalizes them into the
+/// current messages.
+///
+/// The Python implementation names the schemas without the `.avsc` suffix and
+/// may write the fields in its own order, so every legacy message kind is
+/// registered with the writer schema it was produced with. The payload is then
+/// resolved against the current schema by the field names: reordered fields are
+/// matched, fields the legacy writer didn't know get their schema defaults.
+pub struct LegacyDecoder {
+    envelope: Schema,
+    writers: HashMap<String, (Schema, String)>,
+}
+
+impl Default for LegacyDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LegacyDecoder {
+    pub fn new() -> Self {
+        LegacyDecoder {
+            envelope: Schema::parse_str(LEGACY_ENVELOPE_SCHEMA).unwrap(),
+            writers: HashMap::default(),
+        }
+    }
+
+    /// Registers the writer schema of a legacy message kind. `current_schema` is the
+    /// schema file the message maps to, e.g. `insight.transport.NotifyMessage.avsc`.
+    pub fn register(
+        &mut self,
+        legacy_name: &str,
+        writer_schema: &str,
+        current_schema: &str,
+    ) -> Result<(), ProtocolError> {
+        let writer = Schema::parse_str(writer_schema).map_err(|e| {
+            ProtocolError::Decode(format!("Invalid legacy schema for {}: {}", legacy_name, e))
+        })?;
+        self.writers.insert(
+            String::from(legacy_name),
+            (writer, String::from(current_schema)),
+        );
+        Ok(())
+    }
+
+    /// Legacy names are registered explicitly, otherwise the `.avsc` suffix is the
+    /// only difference from the current name.
+    pub fn current_schema_name(&self, legacy_name: &str) -> String {
+        match self.writers.get(legacy_name) {
+            Some((_, current)) => current.clone(),
+            None if legacy_name.ends_with(".avsc") => String::from(legacy_name),
+            None => format!("{}.avsc", legacy_name),
+        }
+    }
+
+    pub fn decode(&self, mb: &Builder, from: &[u8]) -> Result<ProtocolMessage, ProtocolError> {
+        let fields = match from_avro_datum(&self.envelope, &mut &from[..], None) {
+            Ok(Value::Record(fields)) => fields,
+            _ => {
+                return Err(ProtocolError::Decode(String::from(
+                    "Failed to deserialize the legacy envelope",
+                )))
+            }
+        };
+        let (legacy_name, payload) = match fields.as_slice() {
+            [(_, Value::Bytes(schema)), (_, Value::Bytes(payload))] => (schema, payload),
+            _ => {
+                return Err(ProtocolError::Decode(String::from(
+                    "No legacy envelope matched",
+                )))
+            }
+        };
+        let legacy_name = str::from_utf8(legacy_name).map_err(|_| {
+            ProtocolError::Decode(String::from(
+                "Failed to parse schema name, not a valid UTF-8",
+            ))
+        })?;
+
+        let schema_name = self.current_schema_name(legacy_name);
+        let reader = mb.get_schema(&schema_name).ok_or_else(|| {
+            ProtocolError::Decode(format!(
+                "Legacy schema {} has no current counterpart {}",
+                legacy_name, schema_name
+            ))
+        })?;
+        let writer = self
+            .writers
+            .get(legacy_name)
+            .map(|(writer, _)| writer)
+            .unwrap_or(&reader);
+
+        let object =
+            from_avro_datum(writer, &mut payload.as_slice(), Some(&reader)).map_err(|e| {
+                ProtocolError::Decode(format!(
+                    "Failed to resolve legacy {} record: {}",
+                    legacy_name, e
+                ))
+            })?;
+        Ok(ProtocolMessage {
+            schema: schema_name,
+            object,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, PING_REQUEST_RESPONSE_SCHEMA};
+    use crate::compat::legacy::{LegacyDecoder, LEGACY_ENVELOPE_SCHEMA};
+    use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+    use crate::objects::FromProtocolMessage;
+    use crate::utils::get_avro_path;
+    use avro_rs::types::{Record, Value};
+    use avro_rs::{to_avro_datum, Schema};
+
+    const LEGACY_PING: &str = r#"{
+        "type": "record",
+        "name": "PingRequestResponse",
+        "namespace": "insight.transport",
+        "fields": [
+            {"name": "topic", "type": "string"},
+            {"name": "type", "type": {
+                "type": "enum",
+                "name": "PingRequestResponseType",
+                "symbols": ["REQUEST", "RESPONSE"]
+            }},
+            {"name": "request_id", "type": "long"}
+        ]
+    }"#;
+
+    fn legacy_message(legacy_name: &str) -> Vec<u8> {
+        let writer = Schema::parse_str(LEGACY_PING).unwrap();
+        let mut ping = Record::new(&writer).unwrap();
+        ping.put("topic", Value::String(String::from("replies")));
+        ping.put("type", Value::Enum(1, String::from("RESPONSE")));
+        ping.put("request_id", Value::Long(7));
+        let payload = to_avro_datum(&writer, ping).unwrap();
+
+        let envelope_schema = Schema::parse_str(LEGACY_ENVELOPE_SCHEMA).unwrap();
+        let mut envelope = Record::new(&envelope_schema).unwrap();
+        envelope.put("schema", Value::Bytes(legacy_name.into()));
+        envelope.put("payload", Value::Bytes(payload));
+        to_avro_datum(&envelope_schema, envelope).unwrap()
+    }
+
+    #[test]
+    fn test_decode_reordered() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut decoder = LegacyDecoder::new();
+        decoder
+            .register(
+                "insight.transport.PingRequestResponse",
+                LEGACY_PING,
+                PING_REQUEST_RESPONSE_SCHEMA,
+            )
+            .unwrap();
+
+        let message = decoder
+            .decode(
+                &mb,
+                &legacy_message("insight.transport.PingRequestResponse"),
+            )
+            .unwrap();
+        assert_eq!(message.schema, PING_REQUEST_RESPONSE_SCHEMA);
+        assert_eq!(
+            PingRequestResponse::load(&message),
+            Some(PingRequestResponse::new(
+                7,
+                String::from("replies"),
+                PingRequestResponseType::Response
+            ))
+        );
+
+        assert!(decoder
+            .decode(&mb, &legacy_message("insight.transport.Unknown"))
+            .is_err());
+    }
+}
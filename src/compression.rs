@@ -0,0 +1,152 @@
+use crate::avro::ProtocolError;
+use crate::primitives::Compression;
+#[cfg(feature = "compression")]
+use std::io::Read;
+
+#[cfg(feature = "compression")]
+const ZSTD_LEVEL: i32 = 3;
+
+#[cfg(feature = "compression")]
+pub fn compress(compression: &Compression, data: &[u8]) -> Result<Vec<u8>, String> {
+    match compression {
+        Compression::Uncompressed => Ok(data.to_vec()),
+        Compression::Zstd => zstd::encode_all(data, ZSTD_LEVEL)
+            .map_err(|e| format!("Unable to compress the payload with ZSTD. Error is {:?}", e)),
+        Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        Compression::NotImplemented => Err(String::from("Not supported compression")),
+    }
+}
+
+fn limit_exceeded(limit: usize) -> ProtocolError {
+    ProtocolError::LimitExceeded(format!(
+        "the decompressed payload takes more than {} bytes",
+        limit
+    ))
+}
+
+fn check_limit(data: Vec<u8>, limit: usize) -> Result<Vec<u8>, ProtocolError> {
+    if data.len() > limit {
+        return Err(limit_exceeded(limit));
+    }
+    Ok(data)
+}
+
+/// Fails with [`ProtocolError::LimitExceeded`] as soon as the payload grows past
+/// `limit` bytes, the size a payload declares isn't trusted.
+#[cfg(feature = "compression")]
+pub fn decompress(
+    compression: &Compression,
+    data: &[u8],
+    limit: usize,
+) -> Result<Vec<u8>, ProtocolError> {
+    match compression {
+        Compression::Uncompressed => check_limit(data.to_vec(), limit),
+        Compression::Zstd => {
+            let mut decoded = vec![];
+            zstd::stream::read::Decoder::new(data)
+                .and_then(|decoder| decoder.take(limit as u64 + 1).read_to_end(&mut decoded))
+                .map_err(|e| {
+                    ProtocolError::Decode(format!(
+                        "Unable to decompress the ZSTD payload. Error is {:?}",
+                        e
+                    ))
+                })?;
+            check_limit(decoded, limit)
+        }
+        Compression::Lz4 => {
+            let size = match data {
+                [a, b, c, d, ..] => u32::from_le_bytes([*a, *b, *c, *d]) as usize,
+                _ => {
+                    return Err(ProtocolError::Decode(String::from(
+                        "LZ4 payload has no size",
+                    )))
+                }
+            };
+            if size > limit {
+                return Err(limit_exceeded(limit));
+            }
+            lz4_flex::decompress_size_prepended(data).map_err(|e| {
+                ProtocolError::Decode(format!(
+                    "Unable to decompress the LZ4 payload. Error is {:?}",
+                    e
+                ))
+            })
+        }
+        Compression::NotImplemented => Err(ProtocolError::Decode(String::from(
+            "Not supported compression",
+        ))),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+pub fn compress(compression: &Compression, data: &[u8]) -> Result<Vec<u8>, String> {
+    match compression {
+        Compression::Uncompressed => Ok(data.to_vec()),
+        _ => Err(format!(
+            "Compression {:?} requires the `compression` feature",
+            compression
+        )),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+pub fn decompress(
+    compression: &Compression,
+    data: &[u8],
+    limit: usize,
+) -> Result<Vec<u8>, ProtocolError> {
+    match compression {
+        Compression::Uncompressed => check_limit(data.to_vec(), limit),
+        _ => Err(ProtocolError::Decode(format!(
+            "Decompression {:?} requires the `compression` feature",
+            compression
+        ))),
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use crate::avro::ProtocolError;
+    use crate::compression::{compress, decompress};
+    use crate::primitives::Compression;
+
+    #[test]
+    fn test_compress_decompress() {
+        let data = vec![7u8; 4096];
+        for c in [
+            Compression::Uncompressed,
+            Compression::Zstd,
+            Compression::Lz4,
+        ] {
+            let packed = compress(&c, &data).unwrap();
+            assert_eq!(decompress(&c, &packed, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decompression_bomb() {
+        let data = vec![0u8; 64 * 1024 * 1024];
+        for c in [
+            Compression::Uncompressed,
+            Compression::Zstd,
+            Compression::Lz4,
+        ] {
+            let packed = compress(&c, &data).unwrap();
+            assert!(matches!(
+                decompress(&c, &packed, 1024 * 1024),
+                Err(ProtocolError::LimitExceeded(_))
+            ));
+            assert!(matches!(
+                decompress(&c, &packed, data.len() - 1),
+                Err(ProtocolError::LimitExceeded(_))
+            ));
+        }
+        // the size an LZ4 payload declares is checked before anything is allocated
+        let mut forged = lz4_flex::compress_prepend_size(&[0; 16]);
+        forged[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            decompress(&Compression::Lz4, &forged, 1024),
+            Err(ProtocolError::LimitExceeded(_))
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::avro::{ProtocolError, ProtocolMessage};
+use crate::id::{IdGenerator, UuidV7Generator};
+use crate::message_kind::MessageKind;
+use crate::objects::services::error_response::ErrorResponse;
+use crate::objects::FromProtocolMessage;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a pending request isn't answered with a message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseError {
+    /// The service answered with an error.
+    Failed(ErrorResponse),
+    /// The answer is an [`ErrorResponse`] which doesn't load.
+    Decode(ProtocolError),
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseError::Failed(e) => {
+                write!(f, "Request failed with {:?}: {}", e.code, e.message)
+            }
+            ResponseError::Decode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+struct Pending<T> {
+    request: T,
+    deadline_ms: u64,
+}
+
+/// Pending requests of a client by their request id, e.g. the callbacks or the
+/// channels the responses are passed to.
+///
+/// The table doesn't keep the time, the caller passes it in, so the expiry is
+/// driven by its own timer.
+pub struct CorrelationTable<T> {
+    ids: Box<dyn IdGenerator>,
+    pending: HashMap<i64, Pending<T>>,
+}
+
+impl<T> Default for CorrelationTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> CorrelationTable<T> {
+    pub fn new() -> Self {
+        Self::with_generator(Box::new(UuidV7Generator))
+    }
+
+    pub fn with_generator(ids: Box<dyn IdGenerator>) -> Self {
+        CorrelationTable {
+            ids,
+            pending: HashMap::default(),
+        }
+    }
+
+    /// Keeps the request until its response or `deadline_ms`, returns the request id
+    /// to send it with.
+    pub fn register(&mut self, request: T, deadline_ms: u64) -> i64 {
+        let mut request_id = self.ids.next_request_id();
+        while self.pending.contains_key(&request_id) {
+            request_id = self.ids.next_request_id();
+        }
+        self.pending.insert(
+            request_id,
+            Pending {
+                request,
+                deadline_ms,
+            },
+        );
+        request_id
+    }
+
+    /// The pending request the response answers, `Err` when it's an
+    /// [`ErrorResponse`], even one which doesn't load. `None` for the responses of
+    /// unknown, expired or already answered requests.
+    pub fn on_response(
+        &mut self,
+        response: ProtocolMessage,
+    ) -> Option<(T, Result<ProtocolMessage, ResponseError>)> {
+        let pending = self.pending.remove(&response.request_id()?)?;
+        let outcome = match response.kind() {
+            Some(MessageKind::ErrorResponse) => match ErrorResponse::load(&response) {
+                Some(error) => Err(ResponseError::Failed(error)),
+                None => Err(ResponseError::Decode(ProtocolError::Decode(String::from(
+                    "Unable to load the error response",
+                )))),
+            },
+            _ => Ok(response),
+        };
+        Some((pending.request, outcome))
+    }
+
+    pub fn cancel(&mut self, request_id: i64) -> Option<T> {
+        self.pending
+            .remove(&request_id)
+            .map(|pending| pending.request)
+    }
+
+    /// Removes the requests whose deadline has passed, in the deadline order.
+    pub fn expire(&mut self, now_ms: u64) -> Vec<(i64, T)> {
+        let mut expired: Vec<i64> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now_ms >= pending.deadline_ms)
+            .map(|(request_id, _)| *request_id)
+            .collect();
+        expired.sort_by_key(|request_id| (self.pending[request_id].deadline_ms, *request_id));
+        expired
+            .into_iter()
+            .filter_map(|request_id| {
+                self.pending
+                    .remove(&request_id)
+                    .map(|pending| (request_id, pending.request))
+            })
+            .collect()
+    }
+
+    /// When the next [`CorrelationTable::expire`] is due.
+    pub fn next_deadline_ms(&self) -> Option<u64> {
+        self.pending
+            .values()
+            .map(|pending| pending.deadline_ms)
+            .min()
+    }
+
+    pub fn is_pending(&self, request_id: i64) -> bool {
+        self.pending.contains_key(&request_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, ProtocolError, ProtocolMessage, ERROR_RESPONSE_SCHEMA};
+    use crate::correlation::{CorrelationTable, ResponseError};
+    use crate::objects::services::error_response::{ErrorCode, ErrorResponse};
+    use crate::objects::services::storage::stream_tracks::StreamTracksResponse;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::Attributes;
+    use crate::utils::get_avro_path;
+    use avro_rs::types::Value;
+
+    #[test]
+    fn test_correlation() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut table = CorrelationTable::new();
+        let tracks = table.register("tracks", 1_000);
+        let purge = table.register("purge", 2_000);
+        let stale = table.register("stale", 500);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.next_deadline_ms(), Some(500));
+
+        let response = StreamTracksResponse::new(tracks, [1; 16], vec![])
+            .save(&mb)
+            .unwrap();
+        let (request, outcome) = table.on_response(response.clone()).unwrap();
+        assert_eq!(request, "tracks");
+        assert_eq!(outcome, Ok(response.clone()));
+        assert!(table.on_response(response).is_none());
+
+        let error = ErrorResponse::new(
+            purge,
+            ErrorCode::NotFound,
+            String::from("no stream"),
+            Attributes::default(),
+        );
+        let (request, outcome) = table.on_response(error.save(&mb).unwrap()).unwrap();
+        assert_eq!(request, "purge");
+        assert_eq!(outcome, Err(ResponseError::Failed(error)));
+
+        let broken = table.register("broken", 3_000);
+        let response = ProtocolMessage {
+            schema: String::from(ERROR_RESPONSE_SCHEMA),
+            object: Value::Record(vec![(String::from("request_id"), Value::Long(broken))]),
+        };
+        let (request, outcome) = table.on_response(response).unwrap();
+        assert_eq!(request, "broken");
+        assert!(matches!(
+            outcome,
+            Err(ResponseError::Decode(ProtocolError::Decode(_)))
+        ));
+        assert!(!table.is_pending(broken));
+
+        assert_eq!(table.expire(499), vec![]);
+        assert_eq!(table.expire(500), vec![(stale, "stale")]);
+        assert!(table.is_empty());
+
+        let cancelled = table.register("cancelled", 100);
+        assert!(table.is_pending(cancelled));
+        assert_eq!(table.cancel(cancelled), Some("cancelled"));
+        assert_eq!(table.next_deadline_ms(), None);
+    }
+}
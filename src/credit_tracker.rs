@@ -0,0 +1,104 @@
+use crate::objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest};
+use crate::primitives::{StreamName, TrackInfo};
+use std::collections::HashMap;
+
+/// Producer side accounting of the flow control credits.
+///
+/// Every unit element sent spends a credit of its track, grants from the receiver
+/// replenish them. Tracks which never received a grant have no credits.
+#[derive(Debug, Clone, Default)]
+pub struct CreditTracker {
+    credits: HashMap<(StreamName, TrackInfo), i64>,
+}
+
+impl CreditTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_grant(&mut self, grant: &FlowControlGrant) {
+        let credits = self
+            .credits
+            .entry((grant.stream_name, grant.track_info))
+            .or_default();
+        *credits = credits.saturating_add(grant.credits.max(0));
+    }
+
+    pub fn available(&self, stream_name: &StreamName, track_info: &TrackInfo) -> i64 {
+        self.credits
+            .get(&(*stream_name, *track_info))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Spends `count` credits, nothing is spent when not enough credits are left.
+    pub fn try_consume(
+        &mut self,
+        stream_name: &StreamName,
+        track_info: &TrackInfo,
+        count: i64,
+    ) -> bool {
+        match self.credits.get_mut(&(*stream_name, *track_info)) {
+            Some(credits) if *credits >= count => {
+                *credits -= count;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Builds the request for more credits when `pending` elements don't fit into
+    /// the credits left.
+    pub fn request(
+        &self,
+        stream_name: &StreamName,
+        track_info: &TrackInfo,
+        pending: i64,
+    ) -> Option<FlowControlRequest> {
+        let available = self.available(stream_name, track_info);
+        if pending > available {
+            Some(FlowControlRequest::new(
+                *stream_name,
+                *track_info,
+                pending - available,
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn forget(&mut self, stream_name: &StreamName, track_info: &TrackInfo) {
+        self.credits.remove(&(*stream_name, *track_info));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::credit_tracker::CreditTracker;
+    use crate::objects::services::storage::flow_control::FlowControlGrant;
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_credits() {
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let track_info = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+
+        let mut tracker = CreditTracker::new();
+        assert!(!tracker.try_consume(&stream_name, &track_info, 1));
+
+        tracker.on_grant(&FlowControlGrant::new(stream_name, track_info, 3));
+        tracker.on_grant(&FlowControlGrant::new(stream_name, track_info, 2));
+        assert!(tracker.try_consume(&stream_name, &track_info, 4));
+        assert!(!tracker.try_consume(&stream_name, &track_info, 2));
+        assert_eq!(tracker.available(&stream_name, &track_info), 1);
+
+        let req = tracker.request(&stream_name, &track_info, 5).unwrap();
+        assert_eq!(req.pending, 4);
+        assert!(tracker.request(&stream_name, &track_info, 1).is_none());
+    }
+}
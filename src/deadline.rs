@@ -0,0 +1,226 @@
+use crate::avro::ProtocolMessage;
+use crate::objects::services::ffprobe::ServicesFFProbeRequest;
+use crate::objects::services::filmstrip::ServicesFilmstripRequest;
+use crate::objects::services::snapshot::SnapshotRequest;
+use crate::objects::services::storage::backfill::BackfillRequest;
+use crate::objects::services::storage::encoding_profile::EncodingProfileOffer;
+use crate::objects::services::storage::notify_digest::NotifyDigestRequest;
+use crate::objects::services::storage::retention::{
+    StoragePurgeRequest, StorageRetentionSetRequest,
+};
+use crate::objects::services::storage::storage_stats::StorageStatsRequest;
+use crate::objects::services::storage::stream_alias::ResolveAliasRequest;
+use crate::objects::services::storage::stream_deletion::{
+    RestoreStreamRequest, SoftDeleteStreamRequest,
+};
+use crate::objects::services::storage::stream_multi_track_unit_elements::StreamMultiTrackUnitElementsRequest;
+use crate::objects::services::storage::stream_replay::{StreamReplayRequest, StreamReplayStop};
+use crate::objects::services::storage::stream_track_keyframes::StreamTrackKeyframesRequest;
+use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsRequest;
+use crate::objects::services::storage::stream_track_units::StreamTrackUnitsRequest;
+use crate::objects::services::storage::stream_track_units_by_time::StreamTrackUnitsByTimeRequest;
+use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+use crate::objects::services::storage::streams_discovery::StreamsDiscoveryRequest;
+use crate::objects::services::storage::unit_bitmap::UnitBitmapRequest;
+use crate::objects::services::transcode::{TranscodeJobCancel, TranscodeJobRequest};
+use crate::primitives::optional_u64_from_avro;
+use avro_rs::types::Value;
+
+/// Requests which carry an absolute epoch deadline. A server may drop an expired
+/// request without answering it, the client has given up on it anyway.
+pub trait Deadline {
+    fn deadline_ms(&self) -> Option<u64>;
+
+    /// Requests without a deadline never expire.
+    fn is_expired(&self, now_ms: u64) -> bool {
+        matches!(self.deadline_ms(), Some(deadline_ms) if now_ms >= deadline_ms)
+    }
+}
+
+/// Lets a router check the deadline without decoding the message into its type.
+impl Deadline for ProtocolMessage {
+    fn deadline_ms(&self) -> Option<u64> {
+        match &self.object {
+            Value::Record(fields) => fields
+                .iter()
+                .find(|(name, _)| name == "deadline_ms")
+                .and_then(|(_, value)| optional_u64_from_avro(value))
+                .flatten(),
+            _ => None,
+        }
+    }
+}
+
+impl Deadline for ServicesFFProbeRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for ServicesFilmstripRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamTracksRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamTrackUnitElementsRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamTrackUnitsRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamTrackUnitsByTimeRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamTrackKeyframesRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamsDiscoveryRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for NotifyDigestRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for ResolveAliasRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for SoftDeleteStreamRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for RestoreStreamRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for UnitBitmapRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for BackfillRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for TranscodeJobRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for TranscodeJobCancel {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for SnapshotRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for EncodingProfileOffer {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StorageRetentionSetRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StoragePurgeRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StorageStatsRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamReplayRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamReplayStop {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+impl Deadline for StreamMultiTrackUnitElementsRequest {
+    fn deadline_ms(&self) -> Option<u64> {
+        self.deadline_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::deadline::Deadline;
+    use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_is_expired() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let mut req =
+            StreamTracksRequest::new(1, String::from("response"), pack_stream_name(&stream_uuid));
+        assert!(!req.is_expired(u64::MAX));
+
+        req.deadline_ms = Some(1000);
+        assert!(!req.is_expired(999));
+        assert!(req.is_expired(1000));
+
+        let envelope = mb
+            .load_to_avro(mb.save_from_avro(req.save(&mb).unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(envelope.deadline_ms(), Some(1000));
+        assert!(envelope.is_expired(1500));
+    }
+}
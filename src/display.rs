@@ -0,0 +1,248 @@
+use crate::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::primitives::{AttributeValue, Attributes, Payload, StreamName, TrackName, Unit};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::Uuid;
+
+/// Attributes shown before the rest of the map is summarized.
+pub const MAX_SHOWN_ATTRIBUTES: usize = 8;
+
+/// Characters of a string attribute shown before it's cut.
+pub const MAX_SHOWN_CHARS: usize = 32;
+
+static REDACT_ATTRIBUTES: AtomicBool = AtomicBool::new(false);
+
+/// Hides the attribute values in the printed messages, only the keys are left.
+/// Meant for the logs which must not carry user data.
+pub fn set_redact_attributes(redact: bool) {
+    REDACT_ATTRIBUTES.store(redact, Ordering::Relaxed);
+}
+
+pub fn redact_attributes() -> bool {
+    REDACT_ATTRIBUTES.load(Ordering::Relaxed)
+}
+
+pub fn format_stream_name(stream_name: &StreamName) -> String {
+    Uuid::from_bytes(*stream_name).to_string()
+}
+
+/// The track name without the zero padding.
+pub fn format_track_name(track_name: &TrackName) -> String {
+    let len = track_name
+        .iter()
+        .rposition(|b| *b != 0)
+        .map_or(0, |last| last + 1);
+    String::from_utf8_lossy(&track_name[..len]).into_owned()
+}
+
+/// Size of a byte buffer printed in place of its content.
+struct Size(usize);
+
+impl fmt::Debug for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0)
+    }
+}
+
+struct ShownAttributeValue<'a>(&'a AttributeValue);
+
+impl fmt::Display for ShownAttributeValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            AttributeValue::String(s) if s.chars().count() > MAX_SHOWN_CHARS => {
+                let cut: String = s.chars().take(MAX_SHOWN_CHARS).collect();
+                write!(f, "{:?}…", cut)
+            }
+            AttributeValue::String(s) => write!(f, "{:?}", s),
+            AttributeValue::I64(v) => write!(f, "{}", v),
+            AttributeValue::F64(v) => write!(f, "{}", v),
+            AttributeValue::Bool(v) => write!(f, "{}", v),
+            AttributeValue::Bytes(v) => write!(f, "{:?}", Size(v.len())),
+            AttributeValue::StringList(v) => write!(f, "<{} strings>", v.len()),
+        }
+    }
+}
+
+/// Attributes in the key order, at most [`MAX_SHOWN_ATTRIBUTES`] of them.
+pub struct ShownAttributes<'a>(pub &'a Attributes);
+
+impl fmt::Display for ShownAttributes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&String> = self.0.keys().collect();
+        keys.sort();
+        let redact = redact_attributes();
+        write!(f, "{{")?;
+        for (i, key) in keys.iter().take(MAX_SHOWN_ATTRIBUTES).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if redact {
+                write!(f, "{}: <redacted>", key)?;
+            } else {
+                write!(f, "{}: {}", key, ShownAttributeValue(&self.0[*key]))?;
+            }
+        }
+        if keys.len() > MAX_SHOWN_ATTRIBUTES {
+            write!(f, ", … {} more", keys.len() - MAX_SHOWN_ATTRIBUTES)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Debug for ShownAttributes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} {:?} #{}",
+            format_stream_name(&self.stream_name),
+            format_track_name(&self.track_name),
+            self.track_type,
+            self.unit
+        )
+    }
+}
+
+impl fmt::Debug for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Unit")
+            .field("stream_name", &format_stream_name(&self.stream_name))
+            .field("track_name", &format_track_name(&self.track_name))
+            .field("track_type", &self.track_type)
+            .field("unit", &self.unit)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payload")
+            .field("data", &Size(self.data.len()))
+            .field("attributes", &ShownAttributes(&self.attributes))
+            .field("checksum", &self.checksum)
+            .finish()
+    }
+}
+
+impl fmt::Display for UnitElementMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "UnitElementMessage {} element {}, {} bytes",
+            self.stream_unit,
+            self.element,
+            self.value.len()
+        )?;
+        if self.is_keyframe {
+            write!(f, ", keyframe")?;
+        }
+        if self.last {
+            write!(f, ", last")?;
+        }
+        write!(f, ", attributes {}", ShownAttributes(&self.attributes))
+    }
+}
+
+impl fmt::Debug for UnitElementMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnitElementMessage")
+            .field("stream_unit", &self.stream_unit)
+            .field("element", &self.element)
+            .field("value", &Size(self.value.len()))
+            .field("attributes", &ShownAttributes(&self.attributes))
+            .field("last", &self.last)
+            .field("compression", &self.compression)
+            .field("encryption", &self.encryption)
+            .field("is_keyframe", &self.is_keyframe)
+            .field("checksum", &self.checksum)
+            .finish()
+    }
+}
+
+impl fmt::Display for UnitElementChunkMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "UnitElementChunkMessage {} element {}, chunk {}/{}, {} bytes",
+            self.stream_unit,
+            self.element,
+            self.chunk_index + 1,
+            self.total_chunks,
+            self.value.len()
+        )?;
+        if self.is_keyframe {
+            write!(f, ", keyframe")?;
+        }
+        if self.last {
+            write!(f, ", last")?;
+        }
+        write!(f, ", attributes {}", ShownAttributes(&self.attributes))
+    }
+}
+
+impl fmt::Debug for UnitElementChunkMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnitElementChunkMessage")
+            .field("stream_unit", &self.stream_unit)
+            .field("element", &self.element)
+            .field("chunk_index", &self.chunk_index)
+            .field("total_chunks", &self.total_chunks)
+            .field("checksum", &self.checksum)
+            .field("value", &Size(self.value.len()))
+            .field("attributes", &ShownAttributes(&self.attributes))
+            .field("last", &self.last)
+            .field("compression", &self.compression)
+            .field("encryption", &self.encryption)
+            .field("is_keyframe", &self.is_keyframe)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::display::{format_track_name, set_redact_attributes, MAX_SHOWN_ATTRIBUTES};
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::primitives::{pack_track_name, AttributeValue, Attributes, Unit};
+
+    #[test]
+    fn test_message_is_summarized() {
+        let stream_name = uuid::Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let unit = Unit::new(
+            stream_name.as_bytes().to_vec(),
+            b"test".to_vec(),
+            String::from("VIDEO"),
+            7,
+        );
+        assert_eq!(format_track_name(&pack_track_name("test").unwrap()), "test");
+        assert_eq!(
+            unit.to_string(),
+            "fa807469-fbb3-4f63-b1a9-f63fbbf90f41/test Video #7"
+        );
+
+        let mut attributes: Attributes = (0..MAX_SHOWN_ATTRIBUTES + 2)
+            .map(|i| (format!("k{}", i), AttributeValue::I64(i as i64)))
+            .collect();
+        attributes.insert(String::from("a"), AttributeValue::String("x".repeat(100)));
+        let message = UnitElementMessage::new(unit, 3, vec![0xab; 4096], attributes, true);
+
+        let shown = message.to_string();
+        assert!(shown.contains("4096 bytes"));
+        assert!(shown.contains(&format!("\"{}\"…", "x".repeat(32))));
+        assert!(shown.contains("… 3 more"));
+        let debug = format!("{:?}", message);
+        assert!(debug.contains("<4096 bytes>"));
+        assert!(!debug.contains("171"));
+
+        set_redact_attributes(true);
+        let redacted = message.to_string();
+        set_redact_attributes(false);
+        assert!(redacted.contains("a: <redacted>"));
+        assert!(!redacted.contains("xxx"));
+    }
+}
@@ -0,0 +1,316 @@
+use crate::primitives::{EncryptionAlgorithm, EncryptionInfo};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use avro_rs::types::Value;
+use std::collections::HashMap;
+
+pub const AES_256_GCM_KEY_LENGTH: usize = 32;
+
+/// Supplies the keys used to encrypt and decrypt unit element payloads.
+pub trait KeyProvider: Send + Sync {
+    /// The key id and the key new payloads are encrypted with.
+    fn encryption_key(&self) -> Option<(String, Vec<u8>)>;
+
+    /// The key to decrypt payloads encrypted with `key_id`.
+    fn decryption_key(&self, key_id: &str) -> Option<Vec<u8>>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StaticKeyProvider {
+    pub current_key_id: String,
+    pub keys: HashMap<String, Vec<u8>>,
+}
+
+impl StaticKeyProvider {
+    pub fn new(current_key_id: &str, keys: HashMap<String, Vec<u8>>) -> Self {
+        StaticKeyProvider {
+            current_key_id: String::from(current_key_id),
+            keys,
+        }
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn encryption_key(&self) -> Option<(String, Vec<u8>)> {
+        self.keys
+            .get(&self.current_key_id)
+            .map(|k| (self.current_key_id.clone(), k.clone()))
+    }
+
+    fn decryption_key(&self, key_id: &str) -> Option<Vec<u8>> {
+        self.keys.get(key_id).cloned()
+    }
+}
+
+/// `aad` is authenticated along with the data, the payload decrypts only with the same.
+pub fn encrypt(
+    key_id: &str,
+    key: &[u8],
+    data: &[u8],
+    aad: &[u8],
+) -> Result<(Vec<u8>, EncryptionInfo), String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| {
+        format!(
+            "Invalid AES-256-GCM key length. Must be {} bytes.",
+            AES_256_GCM_KEY_LENGTH
+        )
+    })?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let encrypted = cipher
+        .encrypt(&nonce, Payload { msg: data, aad })
+        .map_err(|_| String::from("Unable to encrypt the payload"))?;
+    Ok((
+        encrypted,
+        EncryptionInfo::new(
+            String::from(key_id),
+            nonce.to_vec(),
+            EncryptionAlgorithm::Aes256Gcm,
+        ),
+    ))
+}
+
+pub fn decrypt(
+    info: &EncryptionInfo,
+    key: &[u8],
+    data: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    if info.algo != EncryptionAlgorithm::Aes256Gcm {
+        return Err(format!(
+            "Not supported encryption algorithm {:?}",
+            info.algo
+        ));
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| {
+        format!(
+            "Invalid AES-256-GCM key length. Must be {} bytes.",
+            AES_256_GCM_KEY_LENGTH
+        )
+    })?;
+    if info.nonce.len() != 12 {
+        return Err(String::from("Invalid AES-256-GCM nonce length"));
+    }
+    cipher
+        .decrypt(Nonce::from_slice(&info.nonce), Payload { msg: data, aad })
+        .map_err(|_| String::from("Unable to decrypt the payload"))
+}
+
+fn record_field<'a>(fields: &'a mut [(String, Value)], name: &str) -> Option<&'a mut Value> {
+    fields.iter_mut().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn field<'a>(fields: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+    fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn push_bytes(aad: &mut Vec<u8>, bytes: &[u8]) {
+    aad.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    aad.extend_from_slice(bytes);
+}
+
+/// The stream name, the track name, the unit and the element of the record, and the
+/// position of a chunk in its element, so a payload doesn't decrypt when it's moved
+/// to another track, element or chunk.
+fn record_aad(fields: &[(String, Value)]) -> Result<Vec<u8>, String> {
+    let stream_unit = match field(fields, "stream_unit") {
+        Some(Value::Record(stream_unit)) => stream_unit,
+        _ => return Err(String::from("Record has no `stream_unit` to authenticate")),
+    };
+    let mut aad = match (
+        field(stream_unit, "stream_name"),
+        field(stream_unit, "track_name"),
+        field(stream_unit, "unit"),
+        field(fields, "element"),
+    ) {
+        (
+            Some(Value::Bytes(stream_name)),
+            Some(Value::Bytes(track_name)),
+            Some(Value::Long(unit)),
+            Some(Value::Long(element)),
+        ) => {
+            let mut aad = vec![];
+            push_bytes(&mut aad, stream_name);
+            push_bytes(&mut aad, track_name);
+            aad.extend_from_slice(&unit.to_be_bytes());
+            aad.extend_from_slice(&element.to_be_bytes());
+            aad
+        }
+        _ => {
+            return Err(String::from(
+                "Record has no stream name, track name, unit and element to authenticate",
+            ))
+        }
+    };
+    match (field(fields, "chunk_index"), field(fields, "total_chunks")) {
+        (Some(Value::Int(chunk_index)), Some(Value::Int(total_chunks))) => {
+            aad.extend_from_slice(&chunk_index.to_be_bytes());
+            aad.extend_from_slice(&total_chunks.to_be_bytes());
+        }
+        (None, None) => {}
+        _ => {
+            return Err(String::from(
+                "Record has no chunk index and count to authenticate",
+            ))
+        }
+    }
+    Ok(aad)
+}
+
+/// Encrypts `value` of a record carrying the `encryption` field unless it's already encrypted.
+pub fn encrypt_record(provider: &dyn KeyProvider, record: &mut Value) -> Result<(), String> {
+    let fields = match record {
+        Value::Record(fields) => fields,
+        _ => return Err(String::from("Unable to match AVRO Record.")),
+    };
+    if let Some(Value::Union(inner)) = record_field(fields, "encryption") {
+        if **inner != Value::Null {
+            return Ok(());
+        }
+    }
+    let (key_id, key) = provider
+        .encryption_key()
+        .ok_or_else(|| String::from("No encryption key is available"))?;
+    let aad = record_aad(fields)?;
+
+    let info = match record_field(fields, "value") {
+        Some(Value::Bytes(data)) => {
+            let (encrypted, info) = encrypt(&key_id, &key, data, &aad)?;
+            *data = encrypted;
+            info
+        }
+        _ => return Err(String::from("Record has no `value` bytes to encrypt")),
+    };
+    if let Some(encryption) = record_field(fields, "encryption") {
+        *encryption = Value::Union(Box::new(info.to_avro_record()?));
+    }
+    Ok(())
+}
+
+/// Decrypts `value` of a record in place and resets its `encryption` field.
+pub fn decrypt_record(provider: &dyn KeyProvider, record: &mut Value) -> Result<(), String> {
+    let fields = match record {
+        Value::Record(fields) => fields,
+        _ => return Err(String::from("Unable to match AVRO Record.")),
+    };
+    let info = match record_field(fields, "encryption") {
+        Some(Value::Union(inner)) => match inner.as_ref() {
+            Value::Null => return Ok(()),
+            v => EncryptionInfo::from_avro(v)
+                .ok_or_else(|| String::from("Unable to match AVRO Record to EncryptionInfo"))?,
+        },
+        _ => return Ok(()),
+    };
+    let key = provider
+        .decryption_key(&info.key_id)
+        .ok_or_else(|| format!("No decryption key with id {} is available", info.key_id))?;
+    let aad = record_aad(fields)?;
+
+    match record_field(fields, "value") {
+        Some(Value::Bytes(data)) => *data = decrypt(&info, &key, data, &aad)?,
+        _ => return Err(String::from("Record has no `value` bytes to decrypt")),
+    }
+    if let Some(encryption) = record_field(fields, "encryption") {
+        *encryption = Value::Union(Box::new(Value::Null));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encryption::{decrypt, decrypt_record, encrypt, encrypt_record, StaticKeyProvider};
+    use avro_rs::types::Value;
+    use std::collections::HashMap;
+
+    fn chunk(track_name: &str, chunk_index: i32) -> Value {
+        let stream_unit = Value::Record(vec![
+            (String::from("stream_name"), Value::Bytes(vec![7; 16])),
+            (
+                String::from("track_name"),
+                Value::Bytes(track_name.as_bytes().to_vec()),
+            ),
+            (
+                String::from("track_type"),
+                Value::Enum(0, String::from("VIDEO")),
+            ),
+            (String::from("unit"), Value::Long(3)),
+        ]);
+        Value::Record(vec![
+            (String::from("stream_unit"), stream_unit),
+            (String::from("element"), Value::Long(0)),
+            (String::from("chunk_index"), Value::Int(chunk_index)),
+            (String::from("total_chunks"), Value::Int(2)),
+            (
+                String::from("value"),
+                Value::Bytes(vec![chunk_index as u8; 8]),
+            ),
+            (
+                String::from("encryption"),
+                Value::Union(Box::new(Value::Null)),
+            ),
+        ])
+    }
+
+    fn encrypted_part(record: &Value) -> Vec<(String, Value)> {
+        match record {
+            Value::Record(fields) => fields
+                .iter()
+                .filter(|(name, _)| name == "value" || name == "encryption")
+                .cloned()
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn with_part(record: &Value, part: &[(String, Value)]) -> Value {
+        match record {
+            Value::Record(fields) => Value::Record(
+                fields
+                    .iter()
+                    .map(|(name, value)| {
+                        part.iter()
+                            .find(|(n, _)| n == name)
+                            .cloned()
+                            .unwrap_or_else(|| (name.clone(), value.clone()))
+                    })
+                    .collect(),
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = vec![1u8; 32];
+        let (encrypted, info) = encrypt("k1", &key, &[1, 2, 3], b"unit 1").unwrap();
+        assert_ne!(encrypted, vec![1, 2, 3]);
+        assert_eq!(
+            decrypt(&info, &key, &encrypted, b"unit 1").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(decrypt(&info, &[2u8; 32], &encrypted, b"unit 1").is_err());
+        assert!(decrypt(&info, &key, &encrypted, b"unit 2").is_err());
+    }
+
+    #[test]
+    fn test_moved_payload() {
+        let provider = StaticKeyProvider::new("k1", HashMap::from([("k1".into(), vec![1; 32])]));
+        let mut first = chunk("video", 0);
+        let mut second = chunk("video", 1);
+        encrypt_record(&provider, &mut first).unwrap();
+        encrypt_record(&provider, &mut second).unwrap();
+
+        let mut decrypted = first.clone();
+        decrypt_record(&provider, &mut decrypted).unwrap();
+        assert_eq!(decrypted, chunk("video", 0));
+
+        // the chunks of an element swapped
+        let mut swapped = with_part(&chunk("video", 1), &encrypted_part(&first));
+        assert!(decrypt_record(&provider, &mut swapped).is_err());
+        let mut swapped = with_part(&chunk("video", 0), &encrypted_part(&second));
+        assert!(decrypt_record(&provider, &mut swapped).is_err());
+
+        // the payload moved to another track of the stream
+        let mut moved = with_part(&chunk("audio", 0), &encrypted_part(&first));
+        assert!(decrypt_record(&provider, &mut moved).is_err());
+    }
+}
@@ -0,0 +1,468 @@
+use crate::avro::{Builder, ProtocolError, ProtocolMessage};
+use crate::json::from_json;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::ToProtocolMessage;
+use crate::primitives::{StreamName, TrackName, TrackType, Unit};
+use std::ffi::{CStr, CString};
+use std::mem::ManuallyDrop;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolStatus {
+    Ok,
+    InvalidArgument,
+    Encode,
+    Decode,
+    SignatureMismatch,
+    ChecksumMismatch,
+    LimitExceeded,
+    /// The library panicked; the builder may be used further.
+    Panic,
+    SchemaMismatch,
+}
+
+impl From<ProtocolError> for ProtocolStatus {
+    fn from(e: ProtocolError) -> Self {
+        match e {
+            ProtocolError::Decode(_) => ProtocolStatus::Decode,
+            ProtocolError::SignatureMismatch => ProtocolStatus::SignatureMismatch,
+            ProtocolError::InvalidLabels(_) => ProtocolStatus::InvalidArgument,
+            ProtocolError::ChecksumMismatch(_) => ProtocolStatus::ChecksumMismatch,
+            ProtocolError::Encode(_) => ProtocolStatus::Encode,
+            ProtocolError::LimitExceeded(_) => ProtocolStatus::LimitExceeded,
+            ProtocolError::SchemaMismatch(_) => ProtocolStatus::SchemaMismatch,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolTrackType {
+    Video,
+    Meta,
+    Audio,
+    Subtitle,
+    NotImplemented,
+}
+
+impl From<ProtocolTrackType> for TrackType {
+    fn from(track_type: ProtocolTrackType) -> Self {
+        match track_type {
+            ProtocolTrackType::Video => TrackType::Video,
+            ProtocolTrackType::Meta => TrackType::Meta,
+            ProtocolTrackType::Audio => TrackType::Audio,
+            ProtocolTrackType::Subtitle => TrackType::Subtitle,
+            ProtocolTrackType::NotImplemented => TrackType::NotImplemented,
+        }
+    }
+}
+
+impl From<TrackType> for ProtocolTrackType {
+    fn from(track_type: TrackType) -> Self {
+        match track_type {
+            TrackType::Video => ProtocolTrackType::Video,
+            TrackType::Meta => ProtocolTrackType::Meta,
+            TrackType::Audio => ProtocolTrackType::Audio,
+            TrackType::Subtitle => ProtocolTrackType::Subtitle,
+            TrackType::NotImplemented => ProtocolTrackType::NotImplemented,
+        }
+    }
+}
+
+/// Bytes allocated by the library, released with `protocol_buffer_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl ProtocolBuffer {
+    fn empty() -> Self {
+        ProtocolBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    fn from_vec(data: Vec<u8>) -> Self {
+        let mut data = ManuallyDrop::new(data);
+        ProtocolBuffer {
+            data: data.as_mut_ptr(),
+            len: data.len(),
+            capacity: data.capacity(),
+        }
+    }
+
+    unsafe fn release(&mut self) {
+        if !self.data.is_null() {
+            drop(Vec::from_raw_parts(self.data, self.len, self.capacity));
+        }
+        *self = ProtocolBuffer::empty();
+    }
+}
+
+/// A unit element without attributes. On decode `value` points into the
+/// `ProtocolDecoded` it belongs to.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolUnitElement {
+    pub stream_name: StreamName,
+    pub track_name: TrackName,
+    pub track_type: ProtocolTrackType,
+    pub unit: i64,
+    pub element: i16,
+    pub value: *const u8,
+    pub value_len: usize,
+    pub last: bool,
+    pub is_keyframe: bool,
+}
+
+/// A decoded message, released with `protocol_decoded_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProtocolDecoded {
+    pub schema: *mut c_char,
+    /// The message as `{"schema": ..., "object": ...}`, null when it has no JSON form.
+    pub json: *mut c_char,
+    pub is_unit_element: bool,
+    pub unit_element: ProtocolUnitElement,
+    /// Holds the unit element value.
+    pub value: ProtocolBuffer,
+}
+
+fn guard<F: FnOnce() -> ProtocolStatus>(f: F) -> ProtocolStatus {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(ProtocolStatus::Panic)
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, len) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+unsafe fn string<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+unsafe fn encode(
+    builder: *const Builder,
+    out: *mut ProtocolBuffer,
+    message: impl FnOnce(&Builder) -> Option<ProtocolMessage>,
+) -> ProtocolStatus {
+    let builder = match (builder.as_ref(), out.is_null()) {
+        (Some(builder), false) => builder,
+        _ => return ProtocolStatus::InvalidArgument,
+    };
+    let message = match message(builder) {
+        Some(message) => message,
+        None => return ProtocolStatus::InvalidArgument,
+    };
+    match builder.save_from_avro(message) {
+        Ok(packed) => {
+            ptr::write(out, ProtocolBuffer::from_vec(packed));
+            ProtocolStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Loads the schemas of the `API/avro/protocol` directory at `schema_path`; null
+/// when they fail to load.
+///
+/// # Safety
+/// `schema_path` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_builder_new(schema_path: *const c_char) -> *mut Builder {
+    let path = match string(schema_path) {
+        Some(path) => path,
+        None => return ptr::null_mut(),
+    };
+    panic::catch_unwind(|| Builder::new(path))
+        .map(|builder| Box::into_raw(Box::new(builder)))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+/// `builder` is null or comes from `protocol_builder_new` and isn't used after.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_builder_free(builder: *mut Builder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+/// # Safety
+/// `key_id` is a NUL-terminated string, `key` holds `key_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_builder_set_signing_key(
+    builder: *mut Builder,
+    key_id: *const c_char,
+    key: *const u8,
+    key_len: usize,
+) -> ProtocolStatus {
+    guard(
+        || match (builder.as_mut(), string(key_id), bytes(key, key_len)) {
+            (Some(builder), Some(key_id), Some(key)) => {
+                builder.set_signing_key(key_id, key.to_vec());
+                ProtocolStatus::Ok
+            }
+            _ => ProtocolStatus::InvalidArgument,
+        },
+    )
+}
+
+/// Encodes the unit element into `out`.
+///
+/// # Safety
+/// `element.value` holds `element.value_len` bytes; `out` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_unit_element_encode(
+    builder: *const Builder,
+    element: *const ProtocolUnitElement,
+    out: *mut ProtocolBuffer,
+) -> ProtocolStatus {
+    guard(|| {
+        encode(builder, out, |builder| {
+            let element = element.as_ref()?;
+            let value = bytes(element.value, element.value_len)?;
+            let mut message = UnitElementMessage::new(
+                Unit {
+                    stream_name: element.stream_name,
+                    track_name: element.track_name,
+                    track_type: element.track_type.into(),
+                    unit: element.unit,
+                },
+                element.element,
+                value.to_vec(),
+                Default::default(),
+                element.last,
+            );
+            message.is_keyframe = element.is_keyframe;
+            message.save(builder)
+        })
+    })
+}
+
+/// Encodes a message of any kind given as `{"schema": ..., "object": ...}`, the
+/// form `protocol_message_decode` gives back.
+///
+/// # Safety
+/// `json` is a NUL-terminated string; `out` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_message_encode_json(
+    builder: *const Builder,
+    json: *const c_char,
+    out: *mut ProtocolBuffer,
+) -> ProtocolStatus {
+    guard(|| {
+        encode(builder, out, |builder| {
+            let json: serde_json::Value = serde_json::from_str(string(json)?).ok()?;
+            let schema = json.get("schema")?.as_str()?;
+            let avro_schema = builder.get_schema(schema)?;
+            let object = from_json(&avro_schema, json.get("object")?)?;
+            Some(ProtocolMessage {
+                schema: String::from(schema),
+                object,
+            })
+        })
+    })
+}
+
+/// Decodes the message into `out`, the unit elements into their C form as well.
+///
+/// # Safety
+/// `data` holds `len` bytes; `out` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_message_decode(
+    builder: *const Builder,
+    data: *const u8,
+    len: usize,
+    out: *mut ProtocolDecoded,
+) -> ProtocolStatus {
+    guard(|| {
+        let (builder, data) = match (builder.as_ref(), bytes(data, len), out.is_null()) {
+            (Some(builder), Some(data), false) => (builder, data),
+            _ => return ProtocolStatus::InvalidArgument,
+        };
+        let (schema, object) = match builder.builder_impl().read_protocol_message(data) {
+            Ok(message) => message,
+            Err(e) => return e.into(),
+        };
+        let message = ProtocolMessage { schema, object };
+
+        let mut decoded = ProtocolDecoded {
+            schema: CString::new(message.schema.as_str())
+                .map(CString::into_raw)
+                .unwrap_or(ptr::null_mut()),
+            json: serde_json::to_string(&message)
+                .ok()
+                .and_then(|json| CString::new(json).ok())
+                .map(CString::into_raw)
+                .unwrap_or(ptr::null_mut()),
+            is_unit_element: false,
+            unit_element: ProtocolUnitElement {
+                stream_name: StreamName::default(),
+                track_name: TrackName::default(),
+                track_type: ProtocolTrackType::NotImplemented,
+                unit: 0,
+                element: 0,
+                value: ptr::null(),
+                value_len: 0,
+                last: false,
+                is_keyframe: false,
+            },
+            value: ProtocolBuffer::empty(),
+        };
+        if let Some(element) =
+            UnitElementMessage::load_with_limits(&message, builder.builder_impl().decode_limits())
+        {
+            decoded.value = ProtocolBuffer::from_vec(element.value);
+            decoded.is_unit_element = true;
+            decoded.unit_element = ProtocolUnitElement {
+                stream_name: element.stream_unit.stream_name,
+                track_name: element.stream_unit.track_name,
+                track_type: element.stream_unit.track_type.into(),
+                unit: element.stream_unit.unit,
+                element: element.element,
+                value: decoded.value.data,
+                value_len: decoded.value.len,
+                last: element.last,
+                is_keyframe: element.is_keyframe,
+            };
+        }
+        ptr::write(out, decoded);
+        ProtocolStatus::Ok
+    })
+}
+
+/// # Safety
+/// `buffer` is filled by the library and isn't freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_buffer_free(buffer: *mut ProtocolBuffer) {
+    if let Some(buffer) = buffer.as_mut() {
+        buffer.release();
+    }
+}
+
+/// # Safety
+/// `decoded` is filled by `protocol_message_decode` and isn't freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn protocol_decoded_free(decoded: *mut ProtocolDecoded) {
+    if let Some(decoded) = decoded.as_mut() {
+        for s in [&mut decoded.schema, &mut decoded.json] {
+            if !s.is_null() {
+                drop(CString::from_raw(*s));
+                *s = ptr::null_mut();
+            }
+        }
+        decoded.value.release();
+        decoded.unit_element.value = ptr::null();
+        decoded.unit_element.value_len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use crate::utils::get_avro_path;
+    use std::ffi::{CStr, CString};
+    use std::mem::MaybeUninit;
+
+    #[test]
+    fn test_round_trip() {
+        unsafe {
+            let path = CString::new(get_avro_path()).unwrap();
+            let builder = protocol_builder_new(path.as_ptr());
+            assert!(!builder.is_null());
+            let key_id = CString::new("k1").unwrap();
+            assert_eq!(
+                protocol_builder_set_signing_key(builder, key_id.as_ptr(), b"secret".as_ptr(), 6),
+                ProtocolStatus::Ok
+            );
+
+            let value = vec![0xab; 100];
+            let element = ProtocolUnitElement {
+                stream_name: [1; 16],
+                track_name: *b"video\0\0\0\0\0\0\0\0\0\0\0",
+                track_type: ProtocolTrackType::Video,
+                unit: 7,
+                element: 3,
+                value: value.as_ptr(),
+                value_len: value.len(),
+                last: true,
+                is_keyframe: true,
+            };
+            let mut packed = MaybeUninit::uninit();
+            assert_eq!(
+                protocol_unit_element_encode(builder, &element, packed.as_mut_ptr()),
+                ProtocolStatus::Ok
+            );
+            let mut packed = packed.assume_init();
+
+            let mut decoded = MaybeUninit::uninit();
+            assert_eq!(
+                protocol_message_decode(builder, packed.data, packed.len, decoded.as_mut_ptr()),
+                ProtocolStatus::Ok
+            );
+            let mut decoded = decoded.assume_init();
+            assert!(decoded.is_unit_element);
+            let read = &decoded.unit_element;
+            assert_eq!(
+                (read.stream_name, read.track_name, read.unit, read.element),
+                (element.stream_name, element.track_name, 7, 3)
+            );
+            assert!(read.last && read.is_keyframe);
+            assert_eq!(
+                slice::from_raw_parts(read.value, read.value_len),
+                &value[..]
+            );
+
+            let mut json_packed = MaybeUninit::uninit();
+            assert_eq!(
+                protocol_message_encode_json(builder, decoded.json, json_packed.as_mut_ptr()),
+                ProtocolStatus::Ok
+            );
+            let mut json_packed = json_packed.assume_init();
+            let mut redecoded = MaybeUninit::uninit();
+            assert_eq!(
+                protocol_message_decode(
+                    builder,
+                    json_packed.data,
+                    json_packed.len,
+                    redecoded.as_mut_ptr()
+                ),
+                ProtocolStatus::Ok
+            );
+            let mut redecoded = redecoded.assume_init();
+            assert_eq!(CStr::from_ptr(redecoded.json), CStr::from_ptr(decoded.json));
+
+            *packed.data.add(packed.len - 1) ^= 1;
+            let mut tampered = MaybeUninit::uninit();
+            assert_ne!(
+                protocol_message_decode(builder, packed.data, packed.len, tampered.as_mut_ptr()),
+                ProtocolStatus::Ok
+            );
+            assert_eq!(
+                protocol_message_decode(builder, ptr::null(), 1, tampered.as_mut_ptr()),
+                ProtocolStatus::InvalidArgument
+            );
+
+            protocol_decoded_free(&mut decoded);
+            protocol_decoded_free(&mut redecoded);
+            protocol_buffer_free(&mut packed);
+            protocol_buffer_free(&mut json_packed);
+            protocol_builder_free(builder);
+        }
+    }
+}
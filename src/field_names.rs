@@ -0,0 +1,149 @@
+use crate::avro::{
+    BuilderImpl, FLOW_CONTROL_GRANT_SCHEMA, FLOW_CONTROL_REQUEST_SCHEMA,
+    NOTIFY_CADENCE_CONTROL_SCHEMA, STORAGE_PURGE_REQUEST_SCHEMA, STREAM_END_MESSAGE_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA, STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+    TRACK_DESCRIPTOR_SCHEMA, TRACK_DIGEST_SCHEMA, TRACK_ELEMENTS_SCHEMA, TRACK_INFO_SCHEMA,
+    TRACK_STATS_SCHEMA, UNIT_BITMAP_REQUEST_SCHEMA, UNIT_BITMAP_RESPONSE_SCHEMA,
+};
+use avro_rs::Schema;
+
+/// Names of a single field of a message: in the Rust struct, in the Avro record
+/// and as an attribute of the Python class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMapping {
+    pub schema: String,
+    pub avro: String,
+    pub rust: String,
+    pub python: String,
+}
+
+/// Avro fields whose Rust field is named differently; `(schema, avro, rust)`.
+/// The Python classes expose the fields under their Rust names.
+pub const RENAMED_FIELDS: &[(&str, &str, &str)] = &[
+    (TRACK_INFO_SCHEMA, "name", "track_name"),
+    (TRACK_INFO_SCHEMA, "type", "track_type"),
+    (TRACK_DESCRIPTOR_SCHEMA, "track", "track_info"),
+    (TRACK_DIGEST_SCHEMA, "track", "track_info"),
+    (TRACK_STATS_SCHEMA, "track", "track_info"),
+    (TRACK_ELEMENTS_SCHEMA, "track", "track_info"),
+    (FLOW_CONTROL_GRANT_SCHEMA, "track", "track_info"),
+    (FLOW_CONTROL_REQUEST_SCHEMA, "track", "track_info"),
+    (NOTIFY_CADENCE_CONTROL_SCHEMA, "track", "track_info"),
+    (STORAGE_PURGE_REQUEST_SCHEMA, "track", "track_info"),
+    (STREAM_END_MESSAGE_SCHEMA, "track", "track_info"),
+    (
+        STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+        "track",
+        "track_info",
+    ),
+    (
+        STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+        "track",
+        "track_info",
+    ),
+    (UNIT_BITMAP_REQUEST_SCHEMA, "track", "track_info"),
+    (UNIT_BITMAP_RESPONSE_SCHEMA, "track", "track_info"),
+];
+
+pub fn rust_field_name<'a>(schema: &str, avro: &'a str) -> &'a str {
+    RENAMED_FIELDS
+        .iter()
+        .find(|(s, a, _)| *s == schema && *a == avro)
+        .map_or(avro, |(_, _, rust)| *rust)
+}
+
+pub fn avro_field_name<'a>(schema: &str, rust: &'a str) -> &'a str {
+    RENAMED_FIELDS
+        .iter()
+        .find(|(s, _, r)| *s == schema && *r == rust)
+        .map_or(rust, |(_, avro, _)| *avro)
+}
+
+/// Fields of a record schema in the Avro order, `None` for unknown schemas and enums.
+pub fn schema_fields(builder: &BuilderImpl, schema: &str) -> Option<Vec<FieldMapping>> {
+    match &*builder.get_schema(schema)? {
+        Schema::Record { fields, .. } => Some(
+            fields
+                .iter()
+                .map(|f| {
+                    let rust = rust_field_name(schema, &f.name);
+                    FieldMapping {
+                        schema: String::from(schema),
+                        avro: f.name.clone(),
+                        rust: String::from(rust),
+                        python: String::from(rust),
+                    }
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// The mapping of every record the builder knows, sorted by schema, for code
+/// generators and debugging tools of other languages.
+pub fn field_name_table(builder: &BuilderImpl) -> Vec<FieldMapping> {
+    let mut schemas = builder.schema_names();
+    schemas.sort();
+    schemas
+        .into_iter()
+        .filter_map(|schema| schema_fields(builder, &schema))
+        .flatten()
+        .collect()
+}
+
+/// Tab-separated `schema avro rust python` lines, one per field.
+pub fn field_name_table_tsv(builder: &BuilderImpl) -> String {
+    field_name_table(builder)
+        .iter()
+        .map(|f| format!("{}\t{}\t{}\t{}\n", f.schema, f.avro, f.rust, f.python))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{BuilderImpl, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA, TRACK_INFO_SCHEMA};
+    use crate::field_names::{
+        avro_field_name, field_name_table, rust_field_name, schema_fields, FieldMapping,
+        RENAMED_FIELDS,
+    };
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_mapping() {
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+
+        assert_eq!(rust_field_name(TRACK_INFO_SCHEMA, "type"), "track_type");
+        assert_eq!(avro_field_name(TRACK_INFO_SCHEMA, "track_type"), "type");
+        assert_eq!(rust_field_name(TRACK_INFO_SCHEMA, "other"), "other");
+
+        let fields = schema_fields(&builder, TRACK_INFO_SCHEMA).unwrap();
+        assert_eq!(
+            fields[0],
+            FieldMapping {
+                schema: String::from(TRACK_INFO_SCHEMA),
+                avro: String::from("name"),
+                rust: String::from("track_name"),
+                python: String::from("track_name"),
+            }
+        );
+
+        let fields = schema_fields(&builder, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA).unwrap();
+        assert!(fields
+            .iter()
+            .any(|f| f.avro == "track" && f.rust == "track_info"));
+
+        // every rename must point at a field the schema really has
+        let table = field_name_table(&builder);
+        for (schema, avro, rust) in RENAMED_FIELDS {
+            assert!(
+                table
+                    .iter()
+                    .any(|f| f.schema == *schema && f.avro == *avro && f.rust == *rust),
+                "{} has no field {}",
+                schema,
+                avro
+            );
+        }
+    }
+}
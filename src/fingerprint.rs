@@ -0,0 +1,60 @@
+use avro_rs::Schema;
+use sha2::{Digest, Sha256};
+
+const CRC64_AVRO_EMPTY: u64 = 0xc15d_213a_a4d7_a795;
+const CRC64_AVRO_TABLE: [u64; 256] = crc64_avro_table();
+
+const fn crc64_avro_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut fp = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            fp = (fp >> 1) ^ (CRC64_AVRO_EMPTY & (fp & 1).wrapping_neg());
+            j += 1;
+        }
+        table[i] = fp;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-64-AVRO, the Rabin fingerprint of the Avro specification.
+pub fn crc64_avro(data: &[u8]) -> u64 {
+    data.iter().fold(CRC64_AVRO_EMPTY, |fp, b| {
+        (fp >> 8) ^ CRC64_AVRO_TABLE[((fp ^ u64::from(*b)) & 0xff) as usize]
+    })
+}
+
+/// Fingerprint of the Parsing Canonical Form, the one the Avro single object
+/// encoding carries.
+pub fn schema_rabin(schema: &Schema) -> u64 {
+    crc64_avro(schema.canonical_form().as_bytes())
+}
+
+pub fn schema_sha256(schema: &Schema) -> [u8; 32] {
+    Sha256::digest(schema.canonical_form().as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{BuilderImpl, KEEPALIVE_MESSAGE_SCHEMA, UNIT_ELEMENT_MESSAGE_SCHEMA};
+    use crate::fingerprint::{crc64_avro, schema_rabin, schema_sha256};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_fingerprints() {
+        assert_eq!(crc64_avro(b"\"null\""), 0x63dd_24e7_cc25_8f8a);
+
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        let keep_alive = builder.get_schema(KEEPALIVE_MESSAGE_SCHEMA).unwrap();
+        let unit_element = builder.get_schema(UNIT_ELEMENT_MESSAGE_SCHEMA).unwrap();
+        assert_eq!(
+            schema_rabin(&keep_alive),
+            schema_rabin(&keep_alive.as_ref().clone())
+        );
+        assert_ne!(schema_rabin(&keep_alive), schema_rabin(&unit_element));
+        assert_ne!(schema_sha256(&keep_alive), schema_sha256(&unit_element));
+    }
+}
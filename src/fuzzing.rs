@@ -0,0 +1,70 @@
+use crate::bitmap::UnitBitmap;
+use crate::trace::{TraceContext, MAX_TRACESTATE_LENGTH};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+impl<'a> Arbitrary<'a> for TraceContext {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut trace_id: [u8; 16] = u.arbitrary()?;
+        let mut parent_id: [u8; 8] = u.arbitrary()?;
+        trace_id[15] |= 1;
+        parent_id[7] |= 1;
+        let hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+        let tracestate: Option<String> = u.arbitrary()?;
+        Ok(TraceContext {
+            traceparent: format!(
+                "00-{}-{}-{:02x}",
+                hex(&trace_id),
+                hex(&parent_id),
+                u8::arbitrary(u)?
+            ),
+            tracestate: tracestate.map(|s| s.chars().take(MAX_TRACESTATE_LENGTH / 4).collect()),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for UnitBitmap {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut bitmap = UnitBitmap::new();
+        for offset in u.arbitrary_iter::<u32>()? {
+            bitmap.insert(offset?);
+        }
+        Ok(bitmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::message::Message;
+    use crate::message_kind::MessageKind;
+    use crate::objects::ToProtocolMessage;
+    use crate::trace::TraceContext;
+    use crate::utils::get_avro_path;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[test]
+    fn test_arbitrary_messages() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let data: Vec<u8> = (0..64 * 1024u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+        let mut saved = 0;
+        for _ in 0..256 {
+            let message = Message::arbitrary(&mut u).unwrap();
+            if let Some(packed) = message.save(&mb) {
+                assert_eq!(MessageKind::of_schema(&packed.schema), message.kind());
+                let read = mb
+                    .builder_impl()
+                    .read_protocol_message(&mb.save_from_avro(packed).unwrap())
+                    .unwrap();
+                assert_eq!(read.0, message.schema());
+                saved += 1;
+            }
+        }
+        assert!(saved > 0);
+
+        let context = TraceContext::arbitrary(&mut Unstructured::new(&[0; 64])).unwrap();
+        assert!(TraceContext::parse(&context.traceparent, context.tracestate.as_deref()).is_ok());
+    }
+}
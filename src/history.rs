@@ -0,0 +1,326 @@
+use crate::avro::{
+    AUTH_CHALLENGE_SCHEMA, AUTH_RESULT_SCHEMA, BACKFILL_REQUEST_SCHEMA, BACKFILL_RESPONSE_SCHEMA,
+    ENCODING_PROFILE_ACK_SCHEMA, ENCODING_PROFILE_OFFER_SCHEMA, ENCRYPTION_INFO_SCHEMA,
+    ERROR_RESPONSE_SCHEMA, FLOW_CONTROL_GRANT_SCHEMA, FLOW_CONTROL_REQUEST_SCHEMA,
+    HEARTBEAT_MESSAGE_SCHEMA, KEEPALIVE_MESSAGE_SCHEMA, MAINTENANCE_NOTICE_SCHEMA,
+    MESSAGE_ENVELOPE_SCHEMA, NODE_TELEMETRY_SCHEMA, NOTIFY_CADENCE_CONTROL_SCHEMA,
+    NOTIFY_DIGEST_REQUEST_SCHEMA, NOTIFY_DIGEST_RESPONSE_SCHEMA, NOTIFY_MESSAGE_SCHEMA,
+    PING_REQUEST_RESPONSE_SCHEMA, RESOLVE_ALIAS_REQUEST_SCHEMA, RESOLVE_ALIAS_RESPONSE_SCHEMA,
+    RESTORE_STREAM_REQUEST_SCHEMA, SERVICES_FFPROBE_REQUEST_SCHEMA,
+    SERVICES_FFPROBE_RESPONSE_SCHEMA, SERVICES_FILMSTRIP_REQUEST_SCHEMA,
+    SERVICES_FILMSTRIP_RESPONSE_SCHEMA, SERVICES_INFERENCE_RESULT_SCHEMA,
+    SERVICES_SNAPSHOT_REQUEST_SCHEMA, SERVICES_SNAPSHOT_RESPONSE_SCHEMA,
+    SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA, SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA,
+    SERVICES_TRANSCODE_JOB_STATUS_SCHEMA, SET_STREAM_ALIAS_SCHEMA, SLO_REPORT_SCHEMA,
+    SOFT_DELETE_STREAM_REQUEST_SCHEMA, STORAGE_PURGE_REQUEST_SCHEMA, STORAGE_PURGE_RESPONSE_SCHEMA,
+    STORAGE_RETENTION_SET_REQUEST_SCHEMA, STORAGE_RETENTION_SET_RESPONSE_SCHEMA,
+    STORAGE_STATS_REQUEST_SCHEMA, STORAGE_STATS_RESPONSE_SCHEMA, STREAMS_DISCOVERY_REQUEST_SCHEMA,
+    STREAMS_DISCOVERY_RESPONSE_SCHEMA, STREAM_END_MESSAGE_SCHEMA, STREAM_LIFECYCLE_MESSAGE_SCHEMA,
+    STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, STREAM_REPLAY_REQUEST_SCHEMA,
+    STREAM_REPLAY_STATUS_SCHEMA, STREAM_REPLAY_STOP_SCHEMA, STREAM_TRACKS_REQUEST_SCHEMA,
+    STREAM_TRACKS_RESPONSE_SCHEMA, STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_RESPONSE_SCHEMA, STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, TRACK_TYPE_SCHEMA, UNIT_BITMAP_REQUEST_SCHEMA,
+    UNIT_BITMAP_RESPONSE_SCHEMA, UNIT_ELEMENT_ACK_MESSAGE_SCHEMA,
+    UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA, UNIT_ELEMENT_MESSAGE_SCHEMA, UNIT_ELEMENT_VALUE_SCHEMA,
+};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl ProtocolVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        ProtocolVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim_start_matches('v').split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+        let patch = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(ProtocolVersion::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Field of a schema which was added or changed in a revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldChange {
+    pub schema: &'static str,
+    pub field: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revision {
+    pub version: ProtocolVersion,
+    pub added_messages: &'static [&'static str],
+    pub added_fields: &'static [FieldChange],
+    pub changed_fields: &'static [FieldChange],
+}
+
+const fn field(schema: &'static str, field: &'static str) -> FieldChange {
+    FieldChange { schema, field }
+}
+
+/// Revisions of the protocol schemas, oldest first.
+pub const REVISIONS: &[Revision] = &[
+    Revision {
+        version: ProtocolVersion::new(0, 2, 1),
+        added_messages: &[
+            MESSAGE_ENVELOPE_SCHEMA,
+            NOTIFY_MESSAGE_SCHEMA,
+            UNIT_ELEMENT_MESSAGE_SCHEMA,
+            STREAM_TRACKS_REQUEST_SCHEMA,
+            STREAM_TRACKS_RESPONSE_SCHEMA,
+            STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+            STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+            STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+            STREAM_TRACK_UNITS_RESPONSE_SCHEMA,
+            PING_REQUEST_RESPONSE_SCHEMA,
+            KEEPALIVE_MESSAGE_SCHEMA,
+            SERVICES_FFPROBE_REQUEST_SCHEMA,
+            SERVICES_FFPROBE_RESPONSE_SCHEMA,
+        ],
+        added_fields: &[],
+        changed_fields: &[],
+    },
+    Revision {
+        version: ProtocolVersion::new(0, 3, 0),
+        added_messages: &[
+            SERVICES_FILMSTRIP_REQUEST_SCHEMA,
+            SERVICES_FILMSTRIP_RESPONSE_SCHEMA,
+            STREAM_END_MESSAGE_SCHEMA,
+            STREAM_LIFECYCLE_MESSAGE_SCHEMA,
+            SLO_REPORT_SCHEMA,
+            STREAMS_DISCOVERY_REQUEST_SCHEMA,
+            STREAMS_DISCOVERY_RESPONSE_SCHEMA,
+            STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+            STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+            STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+            STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA,
+            UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA,
+            UNIT_ELEMENT_ACK_MESSAGE_SCHEMA,
+            NOTIFY_DIGEST_REQUEST_SCHEMA,
+            NOTIFY_DIGEST_RESPONSE_SCHEMA,
+            FLOW_CONTROL_GRANT_SCHEMA,
+            FLOW_CONTROL_REQUEST_SCHEMA,
+            SET_STREAM_ALIAS_SCHEMA,
+            RESOLVE_ALIAS_REQUEST_SCHEMA,
+            RESOLVE_ALIAS_RESPONSE_SCHEMA,
+            ERROR_RESPONSE_SCHEMA,
+            SOFT_DELETE_STREAM_REQUEST_SCHEMA,
+            RESTORE_STREAM_REQUEST_SCHEMA,
+            MAINTENANCE_NOTICE_SCHEMA,
+            UNIT_BITMAP_REQUEST_SCHEMA,
+            UNIT_BITMAP_RESPONSE_SCHEMA,
+            BACKFILL_REQUEST_SCHEMA,
+            BACKFILL_RESPONSE_SCHEMA,
+            SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA,
+            SERVICES_TRANSCODE_JOB_STATUS_SCHEMA,
+            SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA,
+            SERVICES_SNAPSHOT_REQUEST_SCHEMA,
+            SERVICES_SNAPSHOT_RESPONSE_SCHEMA,
+            ENCODING_PROFILE_OFFER_SCHEMA,
+            ENCODING_PROFILE_ACK_SCHEMA,
+            STORAGE_RETENTION_SET_REQUEST_SCHEMA,
+            STORAGE_RETENTION_SET_RESPONSE_SCHEMA,
+            STORAGE_PURGE_REQUEST_SCHEMA,
+            STORAGE_PURGE_RESPONSE_SCHEMA,
+            NOTIFY_CADENCE_CONTROL_SCHEMA,
+            STORAGE_STATS_REQUEST_SCHEMA,
+            STORAGE_STATS_RESPONSE_SCHEMA,
+            STREAM_REPLAY_REQUEST_SCHEMA,
+            STREAM_REPLAY_STATUS_SCHEMA,
+            STREAM_REPLAY_STOP_SCHEMA,
+            STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+            STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+            SERVICES_INFERENCE_RESULT_SCHEMA,
+            NODE_TELEMETRY_SCHEMA,
+            HEARTBEAT_MESSAGE_SCHEMA,
+            AUTH_CHALLENGE_SCHEMA,
+            AUTH_RESULT_SCHEMA,
+        ],
+        added_fields: &[
+            field(UNIT_ELEMENT_MESSAGE_SCHEMA, "compression"),
+            field(UNIT_ELEMENT_MESSAGE_SCHEMA, "encryption"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "key_id"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "signature"),
+            field(SERVICES_FFPROBE_REQUEST_SCHEMA, "source_range"),
+            field(SERVICES_FILMSTRIP_RESPONSE_SCHEMA, "artifacts"),
+            field(STREAM_TRACKS_RESPONSE_SCHEMA, "descriptors"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "labels"),
+            field(UNIT_ELEMENT_MESSAGE_SCHEMA, "is_keyframe"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA, "max_results"),
+            field(
+                STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+                "continuation_cursor",
+            ),
+            field(
+                STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+                "continuation_cursor",
+            ),
+            field(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, "has_more"),
+            field(STREAM_TRACK_UNITS_REQUEST_SCHEMA, "max_results"),
+            field(STREAM_TRACK_UNITS_REQUEST_SCHEMA, "continuation_cursor"),
+            field(STREAM_TRACK_UNITS_RESPONSE_SCHEMA, "continuation_cursor"),
+            field(STREAM_TRACK_UNITS_RESPONSE_SCHEMA, "has_more"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA, "snapshot_token"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, "snapshot_token"),
+            field(STREAM_TRACK_UNITS_REQUEST_SCHEMA, "snapshot_token"),
+            field(STREAM_TRACK_UNITS_RESPONSE_SCHEMA, "snapshot_token"),
+            field(UNIT_ELEMENT_MESSAGE_SCHEMA, "checksum"),
+            field(UNIT_ELEMENT_VALUE_SCHEMA, "checksum"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "traceparent"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "tracestate"),
+            field(STREAM_TRACKS_REQUEST_SCHEMA, "deadline_ms"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA, "deadline_ms"),
+            field(STREAM_TRACK_UNITS_REQUEST_SCHEMA, "deadline_ms"),
+            field(SERVICES_FFPROBE_REQUEST_SCHEMA, "deadline_ms"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "priority"),
+            field(SERVICES_FFPROBE_RESPONSE_SCHEMA, "format"),
+            field(SERVICES_FFPROBE_RESPONSE_SCHEMA, "error_detail"),
+            field(STREAM_TRACK_UNITS_REQUEST_SCHEMA, "track_pattern"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA, "track_pattern"),
+            field(STREAM_TRACK_UNITS_RESPONSE_SCHEMA, "track_names"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, "track_names"),
+            field(STREAM_TRACK_UNITS_RESPONSE_SCHEMA, "unit_summaries"),
+            field(NOTIFY_MESSAGE_SCHEMA, "attributes"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "hops"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "auth"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "namespace"),
+            field(MESSAGE_ENVELOPE_SCHEMA, "schema_fingerprint"),
+        ],
+        changed_fields: &[
+            field(UNIT_ELEMENT_MESSAGE_SCHEMA, "attributes"),
+            field(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, "values"),
+            field(SERVICES_FFPROBE_REQUEST_SCHEMA, "attributes"),
+            field(SERVICES_FFPROBE_RESPONSE_SCHEMA, "streams"),
+            field(ENCRYPTION_INFO_SCHEMA, "algo"),
+            field(TRACK_TYPE_SCHEMA, "symbols"),
+            field(SERVICES_FFPROBE_RESPONSE_SCHEMA, "response_type"),
+        ],
+    },
+];
+
+/// The current version, the one of the last revision.
+pub const PROTOCOL_VERSION: &str = "0.3.0";
+
+pub fn protocol_version() -> &'static str {
+    PROTOCOL_VERSION
+}
+
+pub fn current_version() -> ProtocolVersion {
+    REVISIONS.last().unwrap().version
+}
+
+pub fn revisions() -> &'static [Revision] {
+    REVISIONS
+}
+
+/// Revisions newer than `version` up to the current one.
+pub fn revisions_since(version: ProtocolVersion) -> impl Iterator<Item = &'static Revision> {
+    REVISIONS.iter().filter(move |r| r.version > version)
+}
+
+pub fn message_added_in(schema: &str) -> Option<ProtocolVersion> {
+    REVISIONS
+        .iter()
+        .find(|r| r.added_messages.contains(&schema))
+        .map(|r| r.version)
+}
+
+pub fn field_added_in(schema: &str, field: &str) -> Option<ProtocolVersion> {
+    REVISIONS
+        .iter()
+        .find(|r| {
+            r.added_fields
+                .iter()
+                .any(|f| f.schema == schema && f.field == field)
+        })
+        .map(|r| r.version)
+}
+
+/// Explains why a peer running `peer_version` may fail to decode `schema`, e.g.
+/// "field `artifacts` of insight.filmstrip.Response.avsc added in v0.3.0, peer is v0.2.1".
+pub fn explain(schema: &str, peer_version: ProtocolVersion) -> Vec<String> {
+    let mut reasons = vec![];
+    if let Some(version) = message_added_in(schema) {
+        if version > peer_version {
+            reasons.push(format!(
+                "message {} added in {}, peer is {}",
+                schema, version, peer_version
+            ));
+            return reasons;
+        }
+    }
+    for revision in revisions_since(peer_version) {
+        let changes = [
+            ("added", revision.added_fields),
+            ("changed", revision.changed_fields),
+        ];
+        for (kind, fields) in changes {
+            for f in fields.iter().filter(|f| f.schema == schema) {
+                reasons.push(format!(
+                    "field `{}` of {} {} in {}, peer is {}",
+                    f.field, schema, kind, revision.version, peer_version
+                ));
+            }
+        }
+    }
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{SERVICES_FILMSTRIP_RESPONSE_SCHEMA, STREAM_END_MESSAGE_SCHEMA};
+    use crate::history::{
+        current_version, explain, field_added_in, protocol_version, ProtocolVersion, REVISIONS,
+    };
+
+    #[test]
+    fn test_history() {
+        assert!(REVISIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(
+            ProtocolVersion::parse(protocol_version()),
+            Some(current_version())
+        );
+        assert_eq!(
+            ProtocolVersion::parse("v0.2"),
+            Some(ProtocolVersion::new(0, 2, 0))
+        );
+        assert_eq!(
+            field_added_in(SERVICES_FILMSTRIP_RESPONSE_SCHEMA, "artifacts"),
+            Some(ProtocolVersion::new(0, 3, 0))
+        );
+
+        let peer = ProtocolVersion::new(0, 2, 1);
+        assert_eq!(
+            explain(STREAM_END_MESSAGE_SCHEMA, peer),
+            vec![format!(
+                "message {} added in v0.3.0, peer is v0.2.1",
+                STREAM_END_MESSAGE_SCHEMA
+            )]
+        );
+        assert!(explain(STREAM_END_MESSAGE_SCHEMA, ProtocolVersion::new(0, 3, 0)).is_empty());
+    }
+}
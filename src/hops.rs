@@ -0,0 +1,210 @@
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro};
+use avro_rs::types::Value;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Hops kept by the envelope; the oldest ones are dropped past it, so a routing
+/// loop can't grow the message without bound.
+pub const MAX_HOPS: usize = 16;
+
+/// Passage of a message through a proxy or a relay, as the clock of the node saw
+/// it. `forwarded_ms` stays empty while the node still holds the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct Hop {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub node_id: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub received_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub forwarded_ms: Option<u64>,
+}
+
+impl Hop {
+    pub fn new(node_id: String, received_ms: u64, forwarded_ms: Option<u64>) -> Self {
+        Hop {
+            node_id,
+            received_ms,
+            forwarded_ms,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Hop {
+    #[new]
+    fn py_new(node_id: String, received_ms: u64, forwarded_ms: Option<u64>) -> Self {
+        Self::new(node_id, received_ms, forwarded_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl Hop {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("node_id".into(), Value::String(self.node_id.clone())),
+            ("received_ms".into(), Value::Long(self.received_ms as i64)),
+            (
+                "forwarded_ms".into(),
+                optional_u64_to_avro(&self.forwarded_ms),
+            ),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(node_id)), (_, Value::Long(received_ms)), (_, forwarded_ms)] => {
+                    Some(Hop::new(
+                        node_id.clone(),
+                        *received_ms as u64,
+                        optional_u64_from_avro(forwarded_ms)?,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Appends the hop, dropping the oldest ones past [`MAX_HOPS`].
+pub fn push_hop(hops: &mut Vec<Hop>, hop: Hop) {
+    hops.push(hop);
+    if hops.len() > MAX_HOPS {
+        hops.drain(..hops.len() - MAX_HOPS);
+    }
+}
+
+/// Delay a message picked up at a hop: `link_ms` is spent on the way from the
+/// previous hop, `residence_ms` inside the node itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct HopLatency {
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub node_id: String,
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub link_ms: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub residence_ms: Option<u64>,
+}
+
+impl HopLatency {
+    pub fn of_hops(hops: Vec<Hop>, delivered_ms: u64) -> (Vec<HopLatency>, Option<u64>) {
+        hop_latencies(&hops, delivered_ms)
+    }
+
+    /// Both parts together, the unknown ones count as zero.
+    pub fn total_ms(&self) -> u64 {
+        self.link_ms.unwrap_or_default() + self.residence_ms.unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl HopLatency {
+    #[staticmethod]
+    #[pyo3(name = "of_hops")]
+    fn py_of_hops(hops: Vec<Hop>, delivered_ms: u64) -> (Vec<HopLatency>, Option<u64>) {
+        Self::of_hops(hops, delivered_ms)
+    }
+
+    #[pyo3(name = "total_ms")]
+    fn py_total_ms(&self) -> u64 {
+        self.total_ms()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Per-hop latency of a delivered message, the last link is the one from the last
+/// hop to the receiver.
+///
+/// The timestamps come from the clocks of different nodes, a link which looks
+/// negative because of the clock skew counts as zero. The link to the first hop
+/// is unknown, the sender doesn't stamp the envelope.
+pub fn hop_latencies(hops: &[Hop], delivered_ms: u64) -> (Vec<HopLatency>, Option<u64>) {
+    let latencies = hops
+        .iter()
+        .enumerate()
+        .map(|(i, hop)| HopLatency {
+            node_id: hop.node_id.clone(),
+            link_ms: i
+                .checked_sub(1)
+                .and_then(|previous| hops[previous].forwarded_ms)
+                .map(|forwarded_ms| hop.received_ms.saturating_sub(forwarded_ms)),
+            residence_ms: hop
+                .forwarded_ms
+                .map(|forwarded_ms| forwarded_ms.saturating_sub(hop.received_ms)),
+        })
+        .collect();
+    let last_link_ms = hops
+        .last()
+        .and_then(|hop| hop.forwarded_ms)
+        .map(|forwarded_ms| delivered_ms.saturating_sub(forwarded_ms));
+    (latencies, last_link_ms)
+}
+
+/// The hop which added the most delay, `None` without hops.
+pub fn slowest_hop(latencies: &[HopLatency]) -> Option<&HopLatency> {
+    latencies.iter().max_by_key(|latency| latency.total_ms())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hops::{hop_latencies, push_hop, slowest_hop, Hop, MAX_HOPS};
+
+    #[test]
+    fn test_latencies() {
+        let hops = vec![
+            Hop::new(String::from("gw-a"), 100, Some(104)),
+            Hop::new(String::from("gw-b"), 130, Some(190)),
+            Hop::new(String::from("gw-c"), 189, Some(195)),
+        ];
+        let (latencies, last_link_ms) = hop_latencies(&hops, 200);
+        assert_eq!(latencies[0].link_ms, None);
+        assert_eq!(latencies[0].residence_ms, Some(4));
+        assert_eq!(latencies[1].link_ms, Some(26));
+        assert_eq!(latencies[2].link_ms, Some(0));
+        assert_eq!(last_link_ms, Some(5));
+        assert_eq!(slowest_hop(&latencies).unwrap().node_id, "gw-b");
+
+        assert_eq!(hop_latencies(&[], 200), (vec![], None));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut hops = vec![];
+        for i in 0..MAX_HOPS as u64 + 3 {
+            push_hop(&mut hops, Hop::new(format!("gw-{}", i), i, Some(i)));
+        }
+        assert_eq!(hops.len(), MAX_HOPS);
+        assert_eq!(hops[0].node_id, "gw-3");
+
+        for hop in hops {
+            assert_eq!(Hop::from_avro(&hop.to_avro_record()), Some(hop));
+        }
+    }
+}
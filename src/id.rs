@@ -0,0 +1,165 @@
+use crate::primitives::{pack_stream_name, StreamName};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use std::sync::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Mints the identifiers of requests and streams.
+///
+/// Deployments with ordering or locality requirements supply their own scheme,
+/// e.g. time-ordered ids keep a store index append-only.
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> Uuid;
+
+    /// Request ids are positive `i64`, by default the 63 high bits of [`next_id`], so
+    /// time-ordered UUIDs give ordered request ids.
+    ///
+    /// [`next_id`]: IdGenerator::next_id
+    fn next_request_id(&self) -> i64 {
+        (self.next_id().as_u128() >> 65) as i64
+    }
+
+    fn next_stream_name(&self) -> StreamName {
+        pack_stream_name(&self.next_id())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn with_version(mut bytes: [u8; 16], version: u8) -> Uuid {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    Uuid::from_bytes(bytes)
+}
+
+/// Random UUIDs, the ids don't tell anything about the time or the node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV4Generator;
+
+impl IdGenerator for UuidV4Generator {
+    fn next_id(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        with_version(bytes, 4)
+    }
+}
+
+/// UUIDs starting with the epoch milliseconds, ordered by the time they are minted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7Generator;
+
+impl UuidV7Generator {
+    pub fn id_at(&self, now_ms: u64) -> Uuid {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes[6..]);
+        bytes[..6].copy_from_slice(&now_ms.to_be_bytes()[2..]);
+        with_version(bytes, 7)
+    }
+}
+
+impl IdGenerator for UuidV7Generator {
+    fn next_id(&self) -> Uuid {
+        self.id_at(now_ms())
+    }
+}
+
+pub const SNOWFLAKE_NODE_BITS: u32 = 10;
+pub const SNOWFLAKE_SEQUENCE_BITS: u32 = 12;
+
+/// Snowflake-style 63 bit ids: milliseconds since `epoch_ms`, the node id and a
+/// per-millisecond sequence. The ids of a node are strictly increasing, when the
+/// sequence is exhausted the milliseconds are borrowed from the future.
+#[derive(Debug)]
+pub struct SnowflakeGenerator {
+    epoch_ms: u64,
+    node: u16,
+    last: Mutex<(u64, u64)>,
+}
+
+impl SnowflakeGenerator {
+    pub fn new(epoch_ms: u64, node: u16) -> Self {
+        assert!(
+            u32::from(node) < 1 << SNOWFLAKE_NODE_BITS,
+            "Node id must fit in {} bits",
+            SNOWFLAKE_NODE_BITS
+        );
+        SnowflakeGenerator {
+            epoch_ms,
+            node,
+            last: Mutex::new((0, 0)),
+        }
+    }
+
+    pub fn id_at(&self, now_ms: u64) -> i64 {
+        let mut last = self.last.lock().unwrap();
+        let (last_ms, sequence) = *last;
+        let elapsed = now_ms.saturating_sub(self.epoch_ms);
+        *last = if elapsed > last_ms {
+            (elapsed, 0)
+        } else if sequence + 1 < 1 << SNOWFLAKE_SEQUENCE_BITS {
+            (last_ms, sequence + 1)
+        } else {
+            (last_ms + 1, 0)
+        };
+        let (ms, sequence) = *last;
+        ((ms << (SNOWFLAKE_NODE_BITS + SNOWFLAKE_SEQUENCE_BITS)
+            | u64::from(self.node) << SNOWFLAKE_SEQUENCE_BITS
+            | sequence)
+            & i64::MAX as u64) as i64
+    }
+}
+
+impl IdGenerator for SnowflakeGenerator {
+    /// The snowflake id is kept in the high bits, so the UUIDs are ordered as well.
+    fn next_id(&self) -> Uuid {
+        Uuid::from_u128((self.next_request_id() as u128) << 65)
+    }
+
+    fn next_request_id(&self) -> i64 {
+        self.id_at(now_ms())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::id::{IdGenerator, SnowflakeGenerator, UuidV4Generator, UuidV7Generator};
+
+    #[test]
+    fn test_uuid_versions() {
+        assert_eq!(UuidV4Generator.next_id().get_version_num(), 4);
+        assert!(UuidV4Generator.next_request_id() >= 0);
+
+        let generator = UuidV7Generator;
+        let first = generator.id_at(1_700_000_000_000);
+        let second = generator.id_at(1_700_000_000_001);
+        assert_eq!(first.get_version_num(), 7);
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_snowflake() {
+        let generator = SnowflakeGenerator::new(1_600_000_000_000, 5);
+        let ids: Vec<_> = (0..5000)
+            .map(|_| generator.id_at(1_600_000_001_000))
+            .collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!((ids[0] >> 12) & 0x3ff, 5);
+        assert_eq!(ids[0] >> 22, 1000);
+        // the sequence is exhausted after 4096 ids, the next millisecond is borrowed
+        assert_eq!(ids[4096] >> 22, 1001);
+        assert!(generator.id_at(1_600_000_000_500) > ids[4999]);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::avro::{BuilderImpl, ProtocolMessage};
+use crate::deadline::Deadline;
+use crate::relay::{DiskQueue, QueuedMessage, RelayError};
+use std::collections::{HashMap, HashSet};
+
+/// What the janitor drops from a queue. Messages the janitor can't decode are
+/// kept, it's up to the upstream to reject them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JanitorPolicy {
+    /// Messages queued for longer are dropped, `None` keeps them until forwarded.
+    pub ttl_ms: Option<u64>,
+    /// Drops the requests whose deadline passed, nobody waits for their answers.
+    pub drop_expired_requests: bool,
+    /// Schemas of the state-like messages, e.g. keep-alives, of which only the
+    /// latest one per topic is worth forwarding.
+    pub superseded_schemas: HashSet<String>,
+}
+
+/// What a sweep dropped, for the metrics of the relay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JanitorReport {
+    pub scanned: usize,
+    pub expired: usize,
+    pub deadline_passed: usize,
+    pub superseded: usize,
+    pub corrupted: usize,
+    pub dropped_bytes: u64,
+}
+
+impl JanitorReport {
+    pub fn dropped(&self) -> usize {
+        self.expired + self.deadline_passed + self.superseded + self.corrupted
+    }
+}
+
+/// Keeps disk-backed queues of the edge relays within their disks during long
+/// outages by dropping the messages which aren't worth forwarding anymore.
+#[derive(Debug, Clone, Default)]
+pub struct Janitor {
+    policy: JanitorPolicy,
+}
+
+impl Janitor {
+    pub fn new(policy: JanitorPolicy) -> Self {
+        Janitor { policy }
+    }
+
+    pub fn policy(&self) -> &JanitorPolicy {
+        &self.policy
+    }
+
+    fn schema(&self, builder: &BuilderImpl, message: &QueuedMessage) -> Option<String> {
+        builder
+            .read_routing_header(&message.message)
+            .ok()
+            .map(|header| header.schema)
+    }
+
+    pub fn sweep(
+        &self,
+        queue: &mut DiskQueue,
+        builder: &BuilderImpl,
+        now_ms: u64,
+    ) -> Result<JanitorReport, RelayError> {
+        let mut latest = HashMap::new();
+        if !self.policy.superseded_schemas.is_empty() {
+            queue.for_each(|message| {
+                if let Some(schema) = self.schema(builder, message) {
+                    if self.policy.superseded_schemas.contains(&schema) {
+                        latest.insert((message.topic.clone(), schema), message.seq);
+                    }
+                }
+            })?;
+        }
+
+        let mut report = JanitorReport::default();
+        let (dropped, dropped_bytes) = queue.retain(|message| {
+            report.scanned += 1;
+            if let Some(ttl_ms) = self.policy.ttl_ms {
+                if now_ms.saturating_sub(message.accepted_ms) >= ttl_ms {
+                    report.expired += 1;
+                    return false;
+                }
+            }
+            if !latest.is_empty() {
+                if let Some(schema) = self.schema(builder, message) {
+                    match latest.get(&(message.topic.clone(), schema)) {
+                        Some(seq) if *seq != message.seq => {
+                            report.superseded += 1;
+                            return false;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if self.policy.drop_expired_requests {
+                if let Ok((schema, object)) = builder.read_protocol_message(&message.message) {
+                    if (ProtocolMessage { schema, object }).is_expired(now_ms) {
+                        report.deadline_passed += 1;
+                        return false;
+                    }
+                }
+            }
+            true
+        })?;
+
+        report.corrupted = dropped - (report.expired + report.deadline_passed + report.superseded);
+        report.scanned += report.corrupted;
+        report.dropped_bytes = dropped_bytes;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl, KEEPALIVE_MESSAGE_SCHEMA};
+    use crate::janitor::{Janitor, JanitorPolicy, JanitorReport};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::pack_stream_name;
+    use crate::relay::DiskQueue;
+    use crate::utils::get_avro_path;
+    use std::collections::HashSet;
+    use std::fs;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_sweep() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        let dir = std::env::temp_dir().join(format!("protocol-janitor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut queue = DiskQueue::open(&dir, u64::MAX).unwrap();
+
+        let keep_alive = mb
+            .save_from_avro(
+                KeepAliveMessage::new(String::from("relay"))
+                    .save(&mb)
+                    .unwrap(),
+            )
+            .unwrap();
+        let request = |deadline_ms| {
+            mb.save_from_avro(
+                StreamTracksRequest {
+                    deadline_ms: Some(deadline_ms),
+                    ..StreamTracksRequest::new(
+                        1,
+                        String::from("response"),
+                        pack_stream_name(&Uuid::from_u128(1)),
+                    )
+                }
+                .save(&mb)
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        queue.push("/old", &request(u64::MAX), 0).unwrap();
+        queue.push("/control", &keep_alive, 100).unwrap();
+        queue.push("/storage", &request(500), 100).unwrap();
+        queue.push("/storage", &request(5_000), 100).unwrap();
+        queue.push("/control", &keep_alive, 200).unwrap();
+        queue.push("/other", &keep_alive, 200).unwrap();
+
+        let janitor = Janitor::new(JanitorPolicy {
+            ttl_ms: Some(1_000),
+            drop_expired_requests: true,
+            superseded_schemas: HashSet::from([String::from(KEEPALIVE_MESSAGE_SCHEMA)]),
+        });
+        let report = janitor.sweep(&mut queue, &builder, 1_000).unwrap();
+        assert_eq!(
+            JanitorReport {
+                dropped_bytes: 0,
+                ..report
+            },
+            JanitorReport {
+                scanned: 6,
+                expired: 1,
+                deadline_passed: 1,
+                superseded: 1,
+                corrupted: 0,
+                dropped_bytes: 0,
+            }
+        );
+        assert_eq!(report.dropped(), 3);
+        assert_eq!(queue.len(), 3);
+        assert!(report.dropped_bytes > 0);
+
+        let mut topics = vec![];
+        queue
+            .for_each(|message| topics.push(message.topic.clone()))
+            .unwrap();
+        assert_eq!(topics, vec!["/storage", "/control", "/other"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
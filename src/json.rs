@@ -0,0 +1,234 @@
+use crate::avro::{BuilderImpl, ProtocolMessage};
+use avro_rs::types::Value;
+use avro_rs::Schema;
+use serde::de::{self, Deserializer};
+use serde::ser::{self, SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value as Json};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    let mut data = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let sextet = BASE64.iter().position(|b| b == c)? as u32;
+            n |= sextet << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            data.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(data)
+}
+
+/// JSON form of the value: bytes and fixed are base64 strings, enums are their
+/// symbols and unions are their branch values.
+pub fn to_json(value: &Value) -> Result<Json, String> {
+    Ok(match value {
+        Value::Null => Json::Null,
+        Value::Boolean(b) => Json::Bool(*b),
+        Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => Json::from(*i),
+        Value::Long(l)
+        | Value::TimeMicros(l)
+        | Value::TimestampMillis(l)
+        | Value::TimestampMicros(l) => Json::from(*l),
+        Value::Float(f) => Number::from_f64(f64::from(*f))
+            .map(Json::Number)
+            .ok_or_else(|| format!("{} has no JSON form", f))?,
+        Value::Double(d) => Number::from_f64(*d)
+            .map(Json::Number)
+            .ok_or_else(|| format!("{} has no JSON form", d))?,
+        Value::Bytes(bytes) | Value::Fixed(_, bytes) => Json::String(base64_encode(bytes)),
+        Value::String(s) => Json::String(s.clone()),
+        Value::Enum(_, symbol) => Json::String(symbol.clone()),
+        Value::Union(inner) => to_json(inner)?,
+        Value::Array(items) => Json::Array(items.iter().map(to_json).collect::<Result<_, _>>()?),
+        Value::Map(values) => Json::Object(
+            values
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), to_json(v)?)))
+                .collect::<Result<Map<_, _>, String>>()?,
+        ),
+        Value::Record(fields) => Json::Object(
+            fields
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), to_json(v)?)))
+                .collect::<Result<Map<_, _>, String>>()?,
+        ),
+        Value::Uuid(uuid) => Json::String(uuid.to_string()),
+        other => return Err(format!("{:?} has no JSON form", other)),
+    })
+}
+
+/// Value of the schema read back from its [`to_json`] form; missing record fields
+/// are read as `null`.
+pub fn from_json(schema: &Schema, json: &Json) -> Option<Value> {
+    Some(match (schema, json) {
+        (Schema::Null, Json::Null) => Value::Null,
+        (Schema::Boolean, Json::Bool(b)) => Value::Boolean(*b),
+        (Schema::Int, Json::Number(n)) => Value::Int(i32::try_from(n.as_i64()?).ok()?),
+        (Schema::Date, Json::Number(n)) => Value::Date(i32::try_from(n.as_i64()?).ok()?),
+        (Schema::TimeMillis, Json::Number(n)) => {
+            Value::TimeMillis(i32::try_from(n.as_i64()?).ok()?)
+        }
+        (Schema::Long, Json::Number(n)) => Value::Long(n.as_i64()?),
+        (Schema::TimeMicros, Json::Number(n)) => Value::TimeMicros(n.as_i64()?),
+        (Schema::TimestampMillis, Json::Number(n)) => Value::TimestampMillis(n.as_i64()?),
+        (Schema::TimestampMicros, Json::Number(n)) => Value::TimestampMicros(n.as_i64()?),
+        (Schema::Float, Json::Number(n)) => Value::Float(n.as_f64()? as f32),
+        (Schema::Double, Json::Number(n)) => Value::Double(n.as_f64()?),
+        (Schema::Bytes, Json::String(s)) => Value::Bytes(base64_decode(s)?),
+        (Schema::Fixed { size, .. }, Json::String(s)) => {
+            let bytes = base64_decode(s)?;
+            if bytes.len() != *size {
+                return None;
+            }
+            Value::Fixed(*size, bytes)
+        }
+        (Schema::String, Json::String(s)) => Value::String(s.clone()),
+        (Schema::Uuid, Json::String(s)) => Value::Uuid(Uuid::parse_str(s).ok()?),
+        (Schema::Enum { symbols, .. }, Json::String(s)) => {
+            let index = symbols.iter().position(|symbol| symbol == s)?;
+            Value::Enum(i32::try_from(index).ok()?, s.clone())
+        }
+        (Schema::Array(items), Json::Array(values)) => Value::Array(
+            values
+                .iter()
+                .map(|v| from_json(items, v))
+                .collect::<Option<_>>()?,
+        ),
+        (Schema::Map(values), Json::Object(object)) => Value::Map(
+            object
+                .iter()
+                .map(|(k, v)| Some((k.clone(), from_json(values, v)?)))
+                .collect::<Option<HashMap<_, _>>>()?,
+        ),
+        (Schema::Union(union), json) => Value::Union(Box::new(
+            union
+                .variants()
+                .iter()
+                .find_map(|variant| from_json(variant, json))?,
+        )),
+        (Schema::Record { fields, .. }, Json::Object(object)) => Value::Record(
+            fields
+                .iter()
+                .map(|field| {
+                    let value = object.get(&field.name).unwrap_or(&Json::Null);
+                    Some((field.name.clone(), from_json(&field.schema, value)?))
+                })
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    })
+}
+
+impl Serialize for ProtocolMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut message = serializer.serialize_struct("ProtocolMessage", 2)?;
+        message.serialize_field("schema", &self.schema)?;
+        message.serialize_field(
+            "object",
+            &to_json(&self.object).map_err(ser::Error::custom)?,
+        )?;
+        message.end()
+    }
+}
+
+/// The object is read with the schemas of [`BuilderImpl::global`].
+impl<'de> Deserialize<'de> for ProtocolMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Message {
+            schema: String,
+            object: Json,
+        }
+
+        let message = Message::deserialize(deserializer)?;
+        let schema = BuilderImpl::global()
+            .map_err(de::Error::custom)?
+            .get_schema(&message.schema)
+            .ok_or_else(|| de::Error::custom(format!("Unknown schema {}", message.schema)))?;
+        let object = from_json(&schema, &message.object).ok_or_else(|| {
+            de::Error::custom(format!("The object doesn't match {}", message.schema))
+        })?;
+        Ok(ProtocolMessage {
+            schema: message.schema,
+            object,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, ProtocolMessage};
+    use crate::json::{base64_decode, base64_encode};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{AttributeValue, Attributes, Unit};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_base64() {
+        for (data, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+        ] {
+            assert_eq!(base64_encode(data), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), data);
+        }
+        assert!(base64_decode("Z").is_none());
+        assert!(base64_decode("Zm9*").is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let unit = Unit::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), 7);
+        let attributes = Attributes::from([(String::from("a"), AttributeValue::I64(-1))]);
+        let messages = [
+            KeepAliveMessage::new(String::from("camera-1")).save(&mb),
+            UnitElementMessage::new(unit, 0, b"foo".to_vec(), attributes, true).save(&mb),
+        ];
+
+        for message in messages.iter().map(|m| m.as_ref().unwrap()) {
+            let json = serde_json::to_string(message).unwrap();
+            let read: ProtocolMessage = serde_json::from_str(&json).unwrap();
+            assert_eq!(&read, message);
+        }
+
+        let json = serde_json::to_string(messages[1].as_ref().unwrap()).unwrap();
+        assert!(json.contains("\"value\":\"Zm9v\""));
+        let unknown = json.replacen("insight", "unknown", 1);
+        assert!(serde_json::from_str::<ProtocolMessage>(&unknown).is_err());
+    }
+}
@@ -1,47 +1,71 @@
-use crate::avro::Builder;
-use crate::objects::services::keep_alive::KeepAliveMessage;
-use crate::primitives::{NotifyType, Payload, TrackInfo, TrackType, Unit};
-use objects::services::ffprobe::{
-    ServicesFFProbeRequest, ServicesFFProbeResponse, ServicesFFProbeResponseType,
-};
-use objects::services::ping::{PingRequestResponse, PingRequestResponseType};
-use objects::services::storage::notify_message::NotifyMessage;
-use objects::services::storage::stream_track_unit_elements::{
-    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
-};
-use objects::services::storage::stream_track_units::{
-    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
-};
-use objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse};
-use objects::services::storage::unit_element_message::UnitElementMessage;
-use pyo3::prelude::*;
-
+pub mod alias;
+pub mod allow_list;
+pub mod arena;
+pub mod attributes;
+pub mod auth;
 pub mod avro;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod bitmap;
+pub mod buffer_pool;
+pub mod capture;
+pub mod checksum;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod compat;
+pub mod compression;
+pub mod correlation;
+pub mod credit_tracker;
+pub mod deadline;
+pub mod display;
+pub mod encryption;
+#[cfg(feature = "capi")]
+pub mod ffi;
+pub mod field_names;
+pub mod fingerprint;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+pub mod history;
+pub mod hops;
+pub mod id;
+pub mod janitor;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod liveness;
+pub mod maintenance;
+pub mod message;
+pub mod message_kind;
+pub mod namespace;
 pub mod objects;
+pub mod pager;
 pub mod primitives;
+pub mod proxy;
+#[cfg(feature = "python")]
+mod python;
+pub mod readahead;
+pub mod reassembler;
+pub mod relay;
+#[cfg(feature = "repl")]
+pub mod repl;
+pub mod routing;
+pub mod savant;
+pub mod scan;
+#[cfg(feature = "watch")]
+pub mod schema_watch;
+pub mod signing;
+pub mod slo;
+pub mod stream_state;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+pub mod trace;
+pub mod track_pattern;
+pub mod unit_assembler;
 pub mod utils;
-
-#[pymodule]
-fn protocol(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<Builder>()?;
-    m.add_class::<UnitElementMessage>()?;
-    m.add_class::<NotifyMessage>()?;
-    m.add_class::<PingRequestResponse>()?;
-    m.add_class::<ServicesFFProbeRequest>()?;
-    m.add_class::<ServicesFFProbeResponse>()?;
-    m.add_class::<StreamTrackUnitElementsRequest>()?;
-    m.add_class::<StreamTrackUnitElementsResponse>()?;
-    m.add_class::<StreamTracksRequest>()?;
-    m.add_class::<StreamTracksResponse>()?;
-    m.add_class::<StreamTrackUnitsRequest>()?;
-    m.add_class::<StreamTrackUnitsResponse>()?;
-    m.add_class::<PingRequestResponseType>()?;
-    m.add_class::<ServicesFFProbeResponseType>()?;
-    m.add_class::<Unit>()?;
-    m.add_class::<TrackInfo>()?;
-    m.add_class::<Payload>()?;
-    m.add_class::<TrackType>()?;
-    m.add_class::<NotifyType>()?;
-    m.add_class::<KeepAliveMessage>()?;
-    Ok(())
-}
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
+#[cfg(feature = "zero-copy")]
+pub mod zero_copy;
@@ -0,0 +1,123 @@
+use crate::objects::services::heartbeat::HeartbeatMessage;
+use std::collections::HashMap;
+
+/// What the monitor knows about a peer from its last heartbeat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerState {
+    pub role: String,
+    pub last_seen_ms: u64,
+    pub uptime_ms: u64,
+    pub load: f64,
+    pub restarts: u64,
+}
+
+/// Tracks the heartbeats of the peers, a peer which kept silent for longer than
+/// `stale_after_ms` is stale.
+#[derive(Debug, Clone)]
+pub struct HeartbeatMonitor {
+    stale_after_ms: u64,
+    peers: HashMap<String, PeerState>,
+}
+
+impl HeartbeatMonitor {
+    pub fn new(stale_after_ms: u64) -> Self {
+        HeartbeatMonitor {
+            stale_after_ms,
+            peers: HashMap::default(),
+        }
+    }
+
+    /// Records the heartbeat received at `now_ms`. The uptime going down means the
+    /// peer restarted since its previous heartbeat.
+    pub fn observe(&mut self, heartbeat: &HeartbeatMessage, now_ms: u64) {
+        let restarts = match self.peers.get(&heartbeat.node_id) {
+            Some(peer) if heartbeat.uptime_ms < peer.uptime_ms => peer.restarts + 1,
+            Some(peer) => peer.restarts,
+            None => 0,
+        };
+        self.peers.insert(
+            heartbeat.node_id.clone(),
+            PeerState {
+                role: heartbeat.role.clone(),
+                last_seen_ms: now_ms,
+                uptime_ms: heartbeat.uptime_ms,
+                load: heartbeat.load,
+                restarts,
+            },
+        );
+    }
+
+    pub fn peer(&self, node_id: &str) -> Option<&PeerState> {
+        self.peers.get(node_id)
+    }
+
+    pub fn last_seen(&self, node_id: &str) -> Option<u64> {
+        self.peers.get(node_id).map(|peer| peer.last_seen_ms)
+    }
+
+    pub fn is_alive(&self, node_id: &str, now_ms: u64) -> bool {
+        matches!(self.last_seen(node_id), Some(last_seen_ms) if now_ms.saturating_sub(last_seen_ms) <= self.stale_after_ms)
+    }
+
+    /// Stale peers, sorted by the node id.
+    pub fn stale(&self, now_ms: u64) -> Vec<String> {
+        let mut stale: Vec<_> = self
+            .peers
+            .keys()
+            .filter(|node_id| !self.is_alive(node_id, now_ms))
+            .cloned()
+            .collect();
+        stale.sort();
+        stale
+    }
+
+    /// Live peers of the role, sorted by the node id.
+    pub fn alive_with_role(&self, role: &str, now_ms: u64) -> Vec<String> {
+        let mut alive: Vec<_> = self
+            .peers
+            .iter()
+            .filter(|(node_id, peer)| peer.role == role && self.is_alive(node_id, now_ms))
+            .map(|(node_id, _)| node_id.clone())
+            .collect();
+        alive.sort();
+        alive
+    }
+
+    pub fn forget(&mut self, node_id: &str) -> Option<PeerState> {
+        self.peers.remove(node_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::liveness::HeartbeatMonitor;
+    use crate::objects::services::heartbeat::HeartbeatMessage;
+
+    fn heartbeat(node_id: &str, role: &str, uptime_ms: u64) -> HeartbeatMessage {
+        HeartbeatMessage::new(String::from(node_id), String::from(role), uptime_ms, 0.5)
+    }
+
+    #[test]
+    fn test_stale_peers() {
+        let mut monitor = HeartbeatMonitor::new(3_000);
+        monitor.observe(&heartbeat("store-1", "store", 10_000), 1_000);
+        monitor.observe(&heartbeat("camera-1", "camera", 5_000), 3_000);
+        monitor.observe(&heartbeat("camera-2", "camera", 5_000), 3_500);
+
+        assert!(monitor.stale(4_000).is_empty());
+        assert_eq!(monitor.stale(5_500), vec![String::from("store-1")]);
+        assert_eq!(
+            monitor.alive_with_role("camera", 5_500),
+            vec![String::from("camera-1"), String::from("camera-2")]
+        );
+
+        monitor.observe(&heartbeat("store-1", "store", 200), 6_500);
+        assert_eq!(monitor.stale(6_500), vec![String::from("camera-1")]);
+        assert_eq!(monitor.peer("store-1").unwrap().restarts, 1);
+        assert_eq!(monitor.last_seen("store-1"), Some(6_500));
+
+        monitor.forget("camera-1");
+        assert!(monitor.stale(6_500).is_empty());
+        assert_eq!(monitor.last_seen("unknown"), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity};
+use crate::primitives::StreamName;
+use std::collections::HashMap;
+
+/// Client side view of the announced maintenance windows.
+///
+/// Non-critical traffic to the streams under maintenance of `Degraded` or higher
+/// severity is paused, so a planned window doesn't end up as a storm of failed
+/// requests and alerts.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceGate {
+    notices: HashMap<String, MaintenanceNotice>,
+}
+
+impl MaintenanceGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The latest notice of a node replaces the previous one.
+    pub fn on_notice(&mut self, notice: &MaintenanceNotice) {
+        if notice.duration_ms == 0 {
+            self.notices.remove(&notice.node);
+        } else {
+            self.notices.insert(notice.node.clone(), notice.clone());
+        }
+    }
+
+    pub fn active_notices(&self, now_ms: u64) -> Vec<&MaintenanceNotice> {
+        self.notices
+            .values()
+            .filter(|n| n.is_active(now_ms))
+            .collect()
+    }
+
+    pub fn severity(&self, stream_name: StreamName, now_ms: u64) -> Option<MaintenanceSeverity> {
+        self.active_notices(now_ms)
+            .into_iter()
+            .filter(|n| n.affects(stream_name))
+            .map(|n| n.severity)
+            .max()
+    }
+
+    /// Critical traffic is never paused, the rest waits for the window to end.
+    pub fn should_pause(&self, stream_name: StreamName, critical: bool, now_ms: u64) -> bool {
+        !critical
+            && matches!(
+                self.severity(stream_name, now_ms),
+                Some(MaintenanceSeverity::Degraded | MaintenanceSeverity::Outage)
+            )
+    }
+
+    /// Drops the notices whose windows are over.
+    pub fn expire(&mut self, now_ms: u64) {
+        self.notices
+            .retain(|_, n| now_ms < n.start_ms.saturating_add(n.duration_ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maintenance::MaintenanceGate;
+    use crate::objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity};
+    use crate::primitives::pack_stream_name;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_pause() {
+        let stream_name = pack_stream_name(&Uuid::from_u128(1));
+        let mut gate = MaintenanceGate::new();
+
+        gate.on_notice(&MaintenanceNotice::new(
+            String::from("store-1"),
+            vec![],
+            1000,
+            500,
+            MaintenanceSeverity::Degraded,
+        ));
+        assert!(!gate.should_pause(stream_name, false, 900));
+        assert!(gate.should_pause(stream_name, false, 1200));
+        assert!(!gate.should_pause(stream_name, true, 1200));
+
+        gate.on_notice(&MaintenanceNotice::new(
+            String::from("store-1"),
+            vec![],
+            0,
+            0,
+            MaintenanceSeverity::Info,
+        ));
+        assert!(!gate.should_pause(stream_name, false, 1200));
+
+        gate.on_notice(&MaintenanceNotice::new(
+            String::from("store-2"),
+            vec![],
+            1000,
+            500,
+            MaintenanceSeverity::Info,
+        ));
+        assert!(!gate.should_pause(stream_name, false, 1200));
+        gate.expire(2000);
+        assert!(gate.active_notices(1200).is_empty());
+    }
+}
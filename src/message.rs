@@ -0,0 +1,282 @@
+use crate::avro::{Builder, ProtocolError, ProtocolMessage};
+use crate::history::{current_version, ProtocolVersion};
+use crate::message_kind::MessageKind;
+use crate::objects::services::auth::{AuthChallenge, AuthResult};
+use crate::objects::services::error_response::ErrorResponse;
+use crate::objects::services::ffprobe::{ServicesFFProbeRequest, ServicesFFProbeResponse};
+use crate::objects::services::filmstrip::{ServicesFilmstripRequest, ServicesFilmstripResponse};
+use crate::objects::services::heartbeat::HeartbeatMessage;
+use crate::objects::services::inference::InferenceResultMessage;
+use crate::objects::services::keep_alive::KeepAliveMessage;
+use crate::objects::services::maintenance::MaintenanceNotice;
+use crate::objects::services::ping::PingRequestResponse;
+use crate::objects::services::slo_report::SloReport;
+use crate::objects::services::snapshot::{SnapshotRequest, SnapshotResponse};
+use crate::objects::services::storage::backfill::{BackfillRequest, BackfillResponse};
+use crate::objects::services::storage::encoding_profile::{
+    EncodingProfileAck, EncodingProfileOffer,
+};
+use crate::objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest};
+use crate::objects::services::storage::notify_cadence::NotifyCadenceControl;
+use crate::objects::services::storage::notify_digest::{NotifyDigestRequest, NotifyDigestResponse};
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::services::storage::retention::{
+    StoragePurgeRequest, StoragePurgeResponse, StorageRetentionSetRequest,
+    StorageRetentionSetResponse,
+};
+use crate::objects::services::storage::storage_stats::{StorageStatsRequest, StorageStatsResponse};
+use crate::objects::services::storage::stream_alias::{
+    ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+};
+use crate::objects::services::storage::stream_deletion::{
+    RestoreStreamRequest, SoftDeleteStreamRequest,
+};
+use crate::objects::services::storage::stream_end_message::StreamEndMessage;
+use crate::objects::services::storage::stream_lifecycle_message::StreamLifecycleMessage;
+use crate::objects::services::storage::stream_multi_track_unit_elements::{
+    StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_replay::{
+    StreamReplayRequest, StreamReplayStatus, StreamReplayStop,
+};
+use crate::objects::services::storage::stream_track_keyframes::{
+    StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+};
+use crate::objects::services::storage::stream_track_unit_elements::{
+    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_track_units::{
+    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+};
+use crate::objects::services::storage::stream_track_units_by_time::{
+    StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+};
+use crate::objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse};
+use crate::objects::services::storage::streams_discovery::{
+    StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+};
+use crate::objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse};
+use crate::objects::services::storage::unit_element_ack_message::UnitElementAckMessage;
+use crate::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::services::telemetry::NodeTelemetry;
+use crate::objects::services::transcode::{
+    TranscodeJobCancel, TranscodeJobRequest, TranscodeJobStatus,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use avro_rs::types::Value;
+
+macro_rules! messages {
+    ($($kind:ident,)*) => {
+        /// Any protocol message, one variant per [`MessageKind`] and one for the
+        /// records of the extension schemas, see
+        /// [`crate::avro::BuilderImpl::register_extension`].
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Message {
+            $($kind($kind),)*
+            Extension { name: String, value: Value },
+        }
+
+        impl Message {
+            /// `None` for an extension.
+            pub fn kind(&self) -> Option<MessageKind> {
+                match self {
+                    $(Message::$kind(_) => Some(MessageKind::$kind),)*
+                    Message::Extension { .. } => None,
+                }
+            }
+
+            pub fn schema(&self) -> &str {
+                match self {
+                    Message::Extension { name, .. } => name,
+                    _ => self.kind().map_or("", |kind| kind.schema()),
+                }
+            }
+
+            /// See [`ProtocolMessage::partition_key`], the message is saved with the
+            /// builder first.
+            pub fn partition_key(&self, mb: &Builder) -> Option<Vec<u8>> {
+                self.save(mb)?.partition_key()
+            }
+
+            /// The message in the schemas of the version the builder reads, see
+            /// [`crate::avro::BuilderImpl::convert`].
+            pub fn upgrade_to(
+                &self,
+                mb: &Builder,
+                version: ProtocolVersion,
+            ) -> Result<ProtocolMessage, ProtocolError> {
+                let message = self
+                    .save(mb)
+                    .ok_or_else(|| ProtocolError::Encode(format!("Invalid {}", self.schema())))?;
+                mb.builder_impl()
+                    .convert(&message, current_version(), version)
+            }
+        }
+
+        impl FromProtocolMessage for Message {
+            /// The records of the schemas which aren't the protocol ones are
+            /// extensions.
+            fn load(message: &ProtocolMessage) -> Option<Self> {
+                match MessageKind::of_schema(&message.schema) {
+                    $(Some(MessageKind::$kind) => $kind::load(message).map(Message::$kind),)*
+                    None => Some(Message::Extension {
+                        name: message.schema.clone(),
+                        value: message.object.clone(),
+                    }),
+                }
+            }
+        }
+
+        impl ToProtocolMessage for Message {
+            fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+                match self {
+                    $(Message::$kind(m) => m.save(mb),)*
+                    Message::Extension { name, value } => {
+                        mb.get_schema(name).map(|_| ProtocolMessage {
+                            schema: name.clone(),
+                            object: value.clone(),
+                        })
+                    }
+                }
+            }
+        }
+
+        // the extensions carry any record, the fuzzing sticks to the protocol ones
+        #[cfg(feature = "fuzzing")]
+        impl<'a> arbitrary::Arbitrary<'a> for Message {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(match u.choose(MessageKind::ALL)? {
+                    $(MessageKind::$kind => Message::$kind(u.arbitrary()?),)*
+                })
+            }
+        }
+    };
+}
+
+messages! {
+    AuthChallenge,
+    AuthResult,
+    BackfillRequest,
+    BackfillResponse,
+    EncodingProfileAck,
+    EncodingProfileOffer,
+    ErrorResponse,
+    FlowControlGrant,
+    FlowControlRequest,
+    HeartbeatMessage,
+    InferenceResultMessage,
+    KeepAliveMessage,
+    MaintenanceNotice,
+    NodeTelemetry,
+    NotifyCadenceControl,
+    NotifyDigestRequest,
+    NotifyDigestResponse,
+    NotifyMessage,
+    PingRequestResponse,
+    ResolveAliasRequest,
+    ResolveAliasResponse,
+    RestoreStreamRequest,
+    ServicesFFProbeRequest,
+    ServicesFFProbeResponse,
+    ServicesFilmstripRequest,
+    ServicesFilmstripResponse,
+    SetStreamAlias,
+    SloReport,
+    SnapshotRequest,
+    SnapshotResponse,
+    SoftDeleteStreamRequest,
+    StoragePurgeRequest,
+    StoragePurgeResponse,
+    StorageRetentionSetRequest,
+    StorageRetentionSetResponse,
+    StorageStatsRequest,
+    StorageStatsResponse,
+    StreamEndMessage,
+    StreamLifecycleMessage,
+    StreamMultiTrackUnitElementsRequest,
+    StreamMultiTrackUnitElementsResponse,
+    StreamReplayRequest,
+    StreamReplayStatus,
+    StreamReplayStop,
+    StreamTrackKeyframesRequest,
+    StreamTrackKeyframesResponse,
+    StreamTrackUnitElementsRequest,
+    StreamTrackUnitElementsResponse,
+    StreamTrackUnitsByTimeRequest,
+    StreamTrackUnitsByTimeResponse,
+    StreamTrackUnitsRequest,
+    StreamTrackUnitsResponse,
+    StreamTracksRequest,
+    StreamTracksResponse,
+    StreamsDiscoveryRequest,
+    StreamsDiscoveryResponse,
+    TranscodeJobCancel,
+    TranscodeJobRequest,
+    TranscodeJobStatus,
+    UnitBitmapRequest,
+    UnitBitmapResponse,
+    UnitElementAckMessage,
+    UnitElementChunkMessage,
+    UnitElementMessage,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, ProtocolError};
+    use crate::message::Message;
+    use crate::message_kind::MessageKind;
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+    use avro_rs::types::Value;
+
+    #[test]
+    fn test_message() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = Message::KeepAliveMessage(KeepAliveMessage::new(String::from("camera-1")));
+        assert_eq!(message.kind(), Some(MessageKind::KeepAliveMessage));
+        let saved = message.save(&mb).unwrap();
+        assert_eq!(saved.schema, MessageKind::KeepAliveMessage.schema());
+        assert_eq!(Message::load(&saved), Some(message.clone()));
+        assert_eq!(message.partition_key(&mb), None);
+    }
+
+    #[test]
+    fn test_extension() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        let name = mb
+            .register_extension(
+                r#"{"type": "record", "name": "PlateRecognition", "namespace": "acme.lpr",
+                    "fields": [{"name": "plate", "type": "string"}]}"#,
+                |value| match &value {
+                    Value::Record(fields) if fields[0].1 == Value::String(String::new()) => {
+                        Err(String::from("no plate"))
+                    }
+                    _ => Ok(value),
+                },
+            )
+            .unwrap();
+        assert_eq!(name, "acme.lpr.PlateRecognition.avsc");
+
+        let plate = |plate: &str| Message::Extension {
+            name: name.clone(),
+            value: Value::Record(vec![(String::from("plate"), Value::String(plate.into()))]),
+        };
+        let message = plate("AB123");
+        assert_eq!(message.kind(), None);
+        let packed = mb.save_from_avro(message.save(&mb).unwrap()).unwrap();
+        let read = mb.load_to_avro(packed).unwrap();
+        assert_eq!(read.schema, name);
+        assert_eq!(Message::load(&read), Some(message));
+
+        let packed = mb.save_from_avro(plate("").save(&mb).unwrap()).unwrap();
+        assert_eq!(
+            mb.builder_impl().read_protocol_message(&packed),
+            Err(ProtocolError::Decode(String::from("no plate")))
+        );
+        assert!(mb.register_extension(r#"{"type": "string"}"#, Ok).is_err());
+        assert!(mb.reload());
+        assert!(mb.get_schema(&name).is_some());
+    }
+}
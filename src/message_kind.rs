@@ -0,0 +1,304 @@
+use crate::avro::{
+    ProtocolMessage, AUTH_CHALLENGE_SCHEMA, AUTH_RESULT_SCHEMA, BACKFILL_REQUEST_SCHEMA,
+    BACKFILL_RESPONSE_SCHEMA, ENCODING_PROFILE_ACK_SCHEMA, ENCODING_PROFILE_OFFER_SCHEMA,
+    ERROR_RESPONSE_SCHEMA, FLOW_CONTROL_GRANT_SCHEMA, FLOW_CONTROL_REQUEST_SCHEMA,
+    HEARTBEAT_MESSAGE_SCHEMA, KEEPALIVE_MESSAGE_SCHEMA, MAINTENANCE_NOTICE_SCHEMA,
+    NODE_TELEMETRY_SCHEMA, NOTIFY_CADENCE_CONTROL_SCHEMA, NOTIFY_DIGEST_REQUEST_SCHEMA,
+    NOTIFY_DIGEST_RESPONSE_SCHEMA, NOTIFY_MESSAGE_SCHEMA, PING_REQUEST_RESPONSE_SCHEMA,
+    RESOLVE_ALIAS_REQUEST_SCHEMA, RESOLVE_ALIAS_RESPONSE_SCHEMA, RESTORE_STREAM_REQUEST_SCHEMA,
+    SERVICES_FFPROBE_REQUEST_SCHEMA, SERVICES_FFPROBE_RESPONSE_SCHEMA,
+    SERVICES_FILMSTRIP_REQUEST_SCHEMA, SERVICES_FILMSTRIP_RESPONSE_SCHEMA,
+    SERVICES_INFERENCE_RESULT_SCHEMA, SERVICES_SNAPSHOT_REQUEST_SCHEMA,
+    SERVICES_SNAPSHOT_RESPONSE_SCHEMA, SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA,
+    SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA, SERVICES_TRANSCODE_JOB_STATUS_SCHEMA,
+    SET_STREAM_ALIAS_SCHEMA, SLO_REPORT_SCHEMA, SOFT_DELETE_STREAM_REQUEST_SCHEMA,
+    STORAGE_PURGE_REQUEST_SCHEMA, STORAGE_PURGE_RESPONSE_SCHEMA,
+    STORAGE_RETENTION_SET_REQUEST_SCHEMA, STORAGE_RETENTION_SET_RESPONSE_SCHEMA,
+    STORAGE_STATS_REQUEST_SCHEMA, STORAGE_STATS_RESPONSE_SCHEMA, STREAMS_DISCOVERY_REQUEST_SCHEMA,
+    STREAMS_DISCOVERY_RESPONSE_SCHEMA, STREAM_END_MESSAGE_SCHEMA, STREAM_LIFECYCLE_MESSAGE_SCHEMA,
+    STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, STREAM_REPLAY_REQUEST_SCHEMA,
+    STREAM_REPLAY_STATUS_SCHEMA, STREAM_REPLAY_STOP_SCHEMA, STREAM_TRACKS_REQUEST_SCHEMA,
+    STREAM_TRACKS_RESPONSE_SCHEMA, STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_RESPONSE_SCHEMA, STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, UNIT_BITMAP_REQUEST_SCHEMA,
+    UNIT_BITMAP_RESPONSE_SCHEMA, UNIT_ELEMENT_ACK_MESSAGE_SCHEMA,
+    UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA, UNIT_ELEMENT_MESSAGE_SCHEMA,
+};
+use crate::primitives::{StreamName, TrackInfo, TrackName, Unit};
+use crate::routing::partition_key;
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! message_kinds {
+    ($($kind:ident => $schema:ident,)*) => {
+        /// Kind of a protocol message, one per message schema.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum MessageKind {
+            $($kind,)*
+        }
+
+        impl MessageKind {
+            pub const ALL: &'static [MessageKind] = &[$(MessageKind::$kind,)*];
+
+            pub fn schema(&self) -> &'static str {
+                match self {
+                    $(MessageKind::$kind => $schema,)*
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(MessageKind::$kind => stringify!($kind),)*
+                }
+            }
+
+            pub fn of_schema(schema_name: &str) -> Option<Self> {
+                match schema_name {
+                    $($schema => Some(MessageKind::$kind),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+message_kinds! {
+    AuthChallenge => AUTH_CHALLENGE_SCHEMA,
+    AuthResult => AUTH_RESULT_SCHEMA,
+    BackfillRequest => BACKFILL_REQUEST_SCHEMA,
+    BackfillResponse => BACKFILL_RESPONSE_SCHEMA,
+    EncodingProfileAck => ENCODING_PROFILE_ACK_SCHEMA,
+    EncodingProfileOffer => ENCODING_PROFILE_OFFER_SCHEMA,
+    ErrorResponse => ERROR_RESPONSE_SCHEMA,
+    FlowControlGrant => FLOW_CONTROL_GRANT_SCHEMA,
+    FlowControlRequest => FLOW_CONTROL_REQUEST_SCHEMA,
+    HeartbeatMessage => HEARTBEAT_MESSAGE_SCHEMA,
+    InferenceResultMessage => SERVICES_INFERENCE_RESULT_SCHEMA,
+    KeepAliveMessage => KEEPALIVE_MESSAGE_SCHEMA,
+    MaintenanceNotice => MAINTENANCE_NOTICE_SCHEMA,
+    NodeTelemetry => NODE_TELEMETRY_SCHEMA,
+    NotifyCadenceControl => NOTIFY_CADENCE_CONTROL_SCHEMA,
+    NotifyDigestRequest => NOTIFY_DIGEST_REQUEST_SCHEMA,
+    NotifyDigestResponse => NOTIFY_DIGEST_RESPONSE_SCHEMA,
+    NotifyMessage => NOTIFY_MESSAGE_SCHEMA,
+    PingRequestResponse => PING_REQUEST_RESPONSE_SCHEMA,
+    ResolveAliasRequest => RESOLVE_ALIAS_REQUEST_SCHEMA,
+    ResolveAliasResponse => RESOLVE_ALIAS_RESPONSE_SCHEMA,
+    RestoreStreamRequest => RESTORE_STREAM_REQUEST_SCHEMA,
+    ServicesFFProbeRequest => SERVICES_FFPROBE_REQUEST_SCHEMA,
+    ServicesFFProbeResponse => SERVICES_FFPROBE_RESPONSE_SCHEMA,
+    ServicesFilmstripRequest => SERVICES_FILMSTRIP_REQUEST_SCHEMA,
+    ServicesFilmstripResponse => SERVICES_FILMSTRIP_RESPONSE_SCHEMA,
+    SetStreamAlias => SET_STREAM_ALIAS_SCHEMA,
+    SloReport => SLO_REPORT_SCHEMA,
+    SnapshotRequest => SERVICES_SNAPSHOT_REQUEST_SCHEMA,
+    SnapshotResponse => SERVICES_SNAPSHOT_RESPONSE_SCHEMA,
+    SoftDeleteStreamRequest => SOFT_DELETE_STREAM_REQUEST_SCHEMA,
+    StoragePurgeRequest => STORAGE_PURGE_REQUEST_SCHEMA,
+    StoragePurgeResponse => STORAGE_PURGE_RESPONSE_SCHEMA,
+    StorageRetentionSetRequest => STORAGE_RETENTION_SET_REQUEST_SCHEMA,
+    StorageRetentionSetResponse => STORAGE_RETENTION_SET_RESPONSE_SCHEMA,
+    StorageStatsRequest => STORAGE_STATS_REQUEST_SCHEMA,
+    StorageStatsResponse => STORAGE_STATS_RESPONSE_SCHEMA,
+    StreamEndMessage => STREAM_END_MESSAGE_SCHEMA,
+    StreamLifecycleMessage => STREAM_LIFECYCLE_MESSAGE_SCHEMA,
+    StreamMultiTrackUnitElementsRequest => STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    StreamMultiTrackUnitElementsResponse => STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+    StreamReplayRequest => STREAM_REPLAY_REQUEST_SCHEMA,
+    StreamReplayStatus => STREAM_REPLAY_STATUS_SCHEMA,
+    StreamReplayStop => STREAM_REPLAY_STOP_SCHEMA,
+    StreamTrackKeyframesRequest => STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    StreamTrackKeyframesResponse => STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA,
+    StreamTrackUnitElementsRequest => STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    StreamTrackUnitElementsResponse => STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+    StreamTrackUnitsByTimeRequest => STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    StreamTrackUnitsByTimeResponse => STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+    StreamTrackUnitsRequest => STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+    StreamTrackUnitsResponse => STREAM_TRACK_UNITS_RESPONSE_SCHEMA,
+    StreamTracksRequest => STREAM_TRACKS_REQUEST_SCHEMA,
+    StreamTracksResponse => STREAM_TRACKS_RESPONSE_SCHEMA,
+    StreamsDiscoveryRequest => STREAMS_DISCOVERY_REQUEST_SCHEMA,
+    StreamsDiscoveryResponse => STREAMS_DISCOVERY_RESPONSE_SCHEMA,
+    TranscodeJobCancel => SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA,
+    TranscodeJobRequest => SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA,
+    TranscodeJobStatus => SERVICES_TRANSCODE_JOB_STATUS_SCHEMA,
+    UnitBitmapRequest => UNIT_BITMAP_REQUEST_SCHEMA,
+    UnitBitmapResponse => UNIT_BITMAP_RESPONSE_SCHEMA,
+    UnitElementAckMessage => UNIT_ELEMENT_ACK_MESSAGE_SCHEMA,
+    UnitElementChunkMessage => UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA,
+    UnitElementMessage => UNIT_ELEMENT_MESSAGE_SCHEMA,
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownMessageKind(pub String);
+
+impl fmt::Display for UnknownMessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown message kind {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownMessageKind {}
+
+/// Accepts the kind name, e.g. `UnitElementMessage`, and the schema name.
+impl FromStr for MessageKind {
+    type Err = UnknownMessageKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MessageKind::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .or_else(|| MessageKind::of_schema(s))
+            .ok_or_else(|| UnknownMessageKind(String::from(s)))
+    }
+}
+
+/// Fields which tell when the message was produced, in milliseconds since the epoch.
+const TIMESTAMP_FIELDS: [&str; 4] = ["timestamp_ms", "produced_ms", "saved_ms", "collected_ms"];
+
+fn field<'a>(object: &'a Value, name: &str) -> Option<&'a Value> {
+    match object {
+        Value::Record(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+/// Accessors common to the message kinds, without decoding the message into its
+/// class. `None` when the kind has no such field.
+impl ProtocolMessage {
+    pub fn kind(&self) -> Option<MessageKind> {
+        MessageKind::of_schema(&self.schema)
+    }
+
+    fn stream_unit(&self) -> Option<Unit> {
+        field(&self.object, "stream_unit").and_then(Unit::from_avro)
+    }
+
+    pub fn stream_name(&self) -> Option<StreamName> {
+        match field(&self.object, "stream_name") {
+            Some(Value::Bytes(bytes)) => {
+                let mut stream_name = StreamName::default();
+                fill_byte_array(&mut stream_name, bytes);
+                Some(stream_name)
+            }
+            _ => self.stream_unit().map(|unit| unit.stream_name),
+        }
+    }
+
+    pub fn track(&self) -> Option<TrackInfo> {
+        match field(&self.object, "track") {
+            Some(track) => TrackInfo::from_avro(track),
+            None => self
+                .stream_unit()
+                .map(|unit| TrackInfo::new(unit.track_type, unit.track_name)),
+        }
+    }
+
+    pub fn track_name(&self) -> Option<TrackName> {
+        self.track().map(|track| track.track_name)
+    }
+
+    /// See [`crate::routing::partition_key`], `None` for the messages of no stream.
+    pub fn partition_key(&self) -> Option<Vec<u8>> {
+        let stream_name = self.stream_name()?;
+        Some(partition_key(&stream_name, self.track_name().as_ref()))
+    }
+
+    pub fn request_id(&self) -> Option<i64> {
+        match field(&self.object, "request_id") {
+            Some(Value::Long(request_id)) => Some(*request_id),
+            _ => None,
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<u64> {
+        TIMESTAMP_FIELDS
+            .iter()
+            .find_map(|name| match field(&self.object, name) {
+                Some(Value::Long(ms)) => u64::try_from(*ms).ok(),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, UNIT_ELEMENT_MESSAGE_SCHEMA};
+    use crate::message_kind::MessageKind;
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::stream_lifecycle_message::{
+        StreamLifecycleEvent, StreamLifecycleMessage,
+    };
+    use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{Attributes, TrackType, Unit};
+    use crate::utils::get_avro_path;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_kinds() {
+        let schemas: HashSet<&str> = MessageKind::ALL.iter().map(|k| k.schema()).collect();
+        assert_eq!(schemas.len(), MessageKind::ALL.len());
+        for kind in MessageKind::ALL {
+            assert_eq!(kind.to_string().parse::<MessageKind>(), Ok(*kind));
+            assert_eq!(kind.schema().parse::<MessageKind>(), Ok(*kind));
+        }
+        assert_eq!(
+            MessageKind::of_schema(UNIT_ELEMENT_MESSAGE_SCHEMA),
+            Some(MessageKind::UnitElementMessage)
+        );
+        assert!("Nope".parse::<MessageKind>().is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let unit = Unit::new(vec![1; 16], b"test".to_vec(), String::from("VIDEO"), 7);
+        let message = UnitElementMessage::new(unit, 0, vec![], Attributes::default(), true)
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.kind(), Some(MessageKind::UnitElementMessage));
+        assert_eq!(message.stream_name(), Some([1; 16]));
+        let track = message.track().unwrap();
+        assert_eq!(track.track_type, TrackType::Video);
+        assert_eq!(&track.track_name[..4], b"test");
+        assert_eq!(message.request_id(), None);
+        let mut key = vec![1; 16];
+        key.extend_from_slice(b"test");
+        assert_eq!(message.partition_key(), Some(key));
+
+        let message = StreamTracksRequest::new(42, String::from("topic"), [2; 16])
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.kind(), Some(MessageKind::StreamTracksRequest));
+        assert_eq!(message.request_id(), Some(42));
+        assert_eq!(message.stream_name(), Some([2; 16]));
+        assert_eq!(message.track(), None);
+        assert_eq!(message.partition_key(), Some(vec![2; 16]));
+
+        let message = KeepAliveMessage::new(String::from("camera-1"))
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.stream_name(), None);
+        assert_eq!(message.timestamp(), None);
+
+        let message = StreamLifecycleMessage::new([3; 16], StreamLifecycleEvent::Paused, 1000)
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.timestamp(), Some(1000));
+        assert_eq!(message.stream_name(), Some([3; 16]));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::avro::BuilderImpl;
+use crate::proxy::{Forwarded, Interceptor, Verdict};
+use std::collections::HashSet;
+
+/// Tenants a service or a gateway serves. The empty namespace is the one of the
+/// messages produced before the namespaces existed, it has to be allowed explicitly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceFilter {
+    namespaces: HashSet<String>,
+}
+
+impl NamespaceFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow(mut self, namespace: &str) -> Self {
+        self.namespaces.insert(String::from(namespace));
+        self
+    }
+
+    pub fn allows(&self, namespace: &str) -> bool {
+        self.namespaces.contains(namespace)
+    }
+
+    /// Only the envelope is decoded; messages which can't be decoded aren't admitted.
+    pub fn admits(&self, builder: &BuilderImpl, message: &[u8]) -> bool {
+        matches!(builder.read_namespace(message), Ok(namespace) if self.allows(&namespace))
+    }
+
+    /// Messages of the allowed namespaces, in the order they came.
+    pub fn retain<'a>(
+        &self,
+        builder: &BuilderImpl,
+        messages: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Vec<&'a [u8]> {
+        messages
+            .into_iter()
+            .filter(|message| self.admits(builder, message))
+            .collect()
+    }
+}
+
+/// Drops the messages of the namespaces the proxy doesn't carry.
+impl Interceptor for NamespaceFilter {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        if self.allows(&message.header.namespace) {
+            Verdict::Forward
+        } else {
+            Verdict::Drop
+        }
+    }
+}
+
+/// Moves the messages into another namespace, e.g. at the gateway of a tenant
+/// which doesn't stamp its messages itself.
+pub struct SetNamespace(pub String);
+
+impl Interceptor for SetNamespace {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        message.header.namespace = self.0.clone();
+        Verdict::Forward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl};
+    use crate::namespace::{NamespaceFilter, SetNamespace};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::proxy::Proxy;
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_filter() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = KeepAliveMessage::new(String::from("camera-1"))
+            .save(&mb)
+            .unwrap();
+        let acme = BuilderImpl::new(get_avro_path().as_str()).with_namespace("acme");
+        let plain = BuilderImpl::new(get_avro_path().as_str());
+        let from_acme = acme
+            .pack_message_with_labels(&message.schema, message.object.clone(), &Default::default())
+            .unwrap();
+        let unstamped = plain
+            .pack_message_with_labels(&message.schema, message.object, &Default::default())
+            .unwrap();
+        assert_eq!(plain.read_namespace(&from_acme), Ok(String::from("acme")));
+        assert_eq!(plain.read_namespace(&unstamped), Ok(String::new()));
+
+        let filter = NamespaceFilter::new().allow("acme");
+        assert_eq!(
+            filter.retain(
+                &plain,
+                [from_acme.as_slice(), unstamped.as_slice(), &[1u8, 2][..]]
+            ),
+            vec![from_acme.as_slice()]
+        );
+
+        let proxy = Proxy::new(BuilderImpl::new(get_avro_path().as_str()))
+            .with(SetNamespace(String::from("acme")))
+            .with(filter);
+        let (_, forwarded) = proxy.forward("/keepalive", &unstamped).unwrap().unwrap();
+        assert_eq!(plain.read_namespace(&forwarded), Ok(String::from("acme")));
+
+        let proxy =
+            Proxy::new(BuilderImpl::new(get_avro_path().as_str())).with(NamespaceFilter::new());
+        assert_eq!(proxy.forward("/keepalive", &from_acme), Ok(None));
+    }
+}
@@ -1,6 +1,69 @@
-pub mod services;
-
 use crate::avro::{Builder, ProtocolMessage};
+use std::fmt;
+
+/// Fluent constructor of a message kind: the fields without a natural default must
+/// be set, the options, the collections and the flags start empty.
+macro_rules! message_builder {
+    ($message:ident, $builder:ident {
+        required { $($required:ident: $required_ty:ty,)* }
+        optional { $($optional:ident: $optional_ty:ty,)* }
+        defaulted { $($defaulted:ident: $defaulted_ty:ty,)* }
+    }) => {
+        #[derive(Default)]
+        pub struct $builder {
+            $($required: Option<$required_ty>,)*
+            $($optional: Option<$optional_ty>,)*
+            $($defaulted: $defaulted_ty,)*
+        }
+
+        impl $message {
+            pub fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+
+        impl $builder {
+            $(
+                pub fn $required(mut self, $required: $required_ty) -> Self {
+                    self.$required = Some($required);
+                    self
+                }
+            )*
+            $(
+                pub fn $optional(mut self, $optional: $optional_ty) -> Self {
+                    self.$optional = Some($optional);
+                    self
+                }
+            )*
+            $(
+                pub fn $defaulted(mut self, $defaulted: $defaulted_ty) -> Self {
+                    self.$defaulted = $defaulted;
+                    self
+                }
+            )*
+
+            pub fn try_into_message(self) -> Result<$message, $crate::objects::MessageBuildError> {
+                Ok($message {
+                    $($required: self.$required.ok_or(
+                        $crate::objects::MessageBuildError::Missing(stringify!($required)),
+                    )?,)*
+                    $($optional: self.$optional,)*
+                    $($defaulted: self.$defaulted,)*
+                })
+            }
+
+            pub fn build(
+                self,
+                mb: &$crate::avro::Builder,
+            ) -> Result<$crate::avro::ProtocolMessage, $crate::objects::MessageBuildError> {
+                $crate::objects::ToProtocolMessage::save(&self.try_into_message()?, mb)
+                    .ok_or($crate::objects::MessageBuildError::Save)
+            }
+        }
+    };
+}
+
+pub mod services;
 
 pub trait FromProtocolMessage {
     fn load(message: &ProtocolMessage) -> Option<Self>
@@ -11,3 +74,23 @@ pub trait FromProtocolMessage {
 pub trait ToProtocolMessage {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage>;
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageBuildError {
+    Missing(&'static str),
+    /// An argument the message is made from is out of its range.
+    Invalid(&'static str),
+    Save,
+}
+
+impl fmt::Display for MessageBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageBuildError::Missing(field) => write!(f, "Field {} is not set", field),
+            MessageBuildError::Invalid(reason) => write!(f, "{}", reason),
+            MessageBuildError::Save => write!(f, "The message doesn't match its schema"),
+        }
+    }
+}
+
+impl std::error::Error for MessageBuildError {}
@@ -0,0 +1,352 @@
+use crate::avro::{Builder, ProtocolMessage, AUTH_CHALLENGE_SCHEMA, AUTH_RESULT_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_string_from_avro, optional_string_to_avro, optional_u64_from_avro,
+    optional_u64_to_avro,
+};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Answer to a request which came without an acceptable auth token, sent to the
+/// response topic of the request. The peer repeats the request with a token of
+/// one of `schemes`; schemes which sign the request bind the signature to `nonce`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct AuthChallenge {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub schemes: Vec<String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub nonce: Vec<u8>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub reason: Option<String>,
+}
+
+message_builder!(AuthChallenge, AuthChallengeBuilder {
+    required {
+        request_id: i64,
+    }
+    optional {
+        reason: String,
+    }
+    defaulted {
+        schemes: Vec<String>,
+        nonce: Vec<u8>,
+    }
+});
+
+impl AuthChallenge {
+    pub fn new(request_id: i64, schemes: Vec<String>, nonce: Vec<u8>) -> Self {
+        AuthChallenge {
+            request_id,
+            schemes,
+            nonce,
+            reason: None,
+        }
+    }
+
+    pub fn accepts(&self, scheme: &str) -> bool {
+        self.schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl AuthChallenge {
+    #[new]
+    fn py_new(request_id: i64, schemes: Vec<String>, nonce: Vec<u8>) -> Self {
+        Self::new(request_id, schemes, nonce)
+    }
+
+    #[pyo3(name = "accepts")]
+    fn py_accepts(&self, scheme: &str) -> bool {
+        self.accepts(scheme)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl AuthChallenge {
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(String::from(reason));
+        self
+    }
+}
+
+impl ToProtocolMessage for AuthChallenge {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(AUTH_CHALLENGE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put(
+            "schemes",
+            Value::Array(self.schemes.iter().cloned().map(Value::String).collect()),
+        );
+        obj.put("nonce", Value::Bytes(self.nonce.clone()));
+        obj.put("reason", optional_string_to_avro(&self.reason));
+        Some(ProtocolMessage {
+            schema: String::from(AUTH_CHALLENGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for AuthChallenge {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != AUTH_CHALLENGE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Array(schemes)), (_, Value::Bytes(nonce)), (_, reason)] =>
+                {
+                    let schemes_parsed: Vec<_> = schemes
+                        .iter()
+                        .filter_map(|s| match s {
+                            Value::String(s) => Some(s.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    if schemes_parsed.len() < schemes.len() {
+                        warn!("Not all auth schemes are parsed well.");
+                        return None;
+                    }
+                    Some(AuthChallenge {
+                        request_id: *request_id,
+                        schemes: schemes_parsed,
+                        nonce: nonce.clone(),
+                        reason: optional_string_from_avro(reason)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to AuthChallenge");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+/// Outcome of checking the token of the request; `principal` is who the token
+/// belongs to, the token isn't accepted after `expires_ms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct AuthResult {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub accepted: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub principal: Option<String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub expires_ms: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub reason: Option<String>,
+}
+
+message_builder!(AuthResult, AuthResultBuilder {
+    required {
+        request_id: i64,
+    }
+    optional {
+        principal: String,
+        expires_ms: u64,
+        reason: String,
+    }
+    defaulted {
+        accepted: bool,
+    }
+});
+
+impl AuthResult {
+    pub fn new(request_id: i64, accepted: bool) -> Self {
+        AuthResult {
+            request_id,
+            accepted,
+            principal: None,
+            expires_ms: None,
+            reason: None,
+        }
+    }
+
+    pub fn accept(request_id: i64, principal: String, expires_ms: Option<u64>) -> Self {
+        AuthResult {
+            principal: Some(principal),
+            expires_ms,
+            ..Self::new(request_id, true)
+        }
+    }
+
+    pub fn reject(request_id: i64, reason: String) -> Self {
+        AuthResult {
+            reason: Some(reason),
+            ..Self::new(request_id, false)
+        }
+    }
+
+    /// Whether the token is still accepted at `now_ms`.
+    pub fn is_valid_at(&self, now_ms: u64) -> bool {
+        self.accepted && !matches!(self.expires_ms, Some(expires_ms) if now_ms >= expires_ms)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl AuthResult {
+    #[new]
+    fn py_new(request_id: i64, accepted: bool) -> Self {
+        Self::new(request_id, accepted)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "accept")]
+    fn py_accept(request_id: i64, principal: String, expires_ms: Option<u64>) -> Self {
+        Self::accept(request_id, principal, expires_ms)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "reject")]
+    fn py_reject(request_id: i64, reason: String) -> Self {
+        Self::reject(request_id, reason)
+    }
+
+    #[pyo3(name = "is_valid_at")]
+    fn py_is_valid_at(&self, now_ms: u64) -> bool {
+        self.is_valid_at(now_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for AuthResult {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(AUTH_RESULT_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("accepted", Value::Boolean(self.accepted));
+        obj.put("principal", optional_string_to_avro(&self.principal));
+        obj.put("expires_ms", optional_u64_to_avro(&self.expires_ms));
+        obj.put("reason", optional_string_to_avro(&self.reason));
+        Some(ProtocolMessage {
+            schema: String::from(AUTH_RESULT_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for AuthResult {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != AUTH_RESULT_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Boolean(accepted)), (_, principal), (_, expires_ms), (_, reason)] => {
+                    Some(AuthResult {
+                        request_id: *request_id,
+                        accepted: *accepted,
+                        principal: optional_string_from_avro(principal)?,
+                        expires_ms: optional_u64_from_avro(expires_ms)?,
+                        reason: optional_string_from_avro(reason)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to AuthResult");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::{AuthToken, StaticAuthProvider};
+    use crate::avro::{Builder, BuilderImpl};
+    use crate::objects::services::auth::{AuthChallenge, AuthResult};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::storage_stats::StorageStatsRequest;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let challenge = AuthChallenge::new(7, vec![String::from("Bearer")], vec![1, 2, 3])
+            .with_reason("token expired");
+        assert!(challenge.accepts("bearer"));
+        let envelope = mb.load_to_avro(mb.save_from_avro(challenge.save(&mb).unwrap()).unwrap());
+        assert_eq!(AuthChallenge::load(&envelope.unwrap()).unwrap(), challenge);
+
+        for result in [
+            AuthResult::accept(7, String::from("camera-1"), Some(10_000)),
+            AuthResult::reject(7, String::from("unknown key")),
+        ] {
+            let envelope = mb.load_to_avro(mb.save_from_avro(result.save(&mb).unwrap()).unwrap());
+            assert_eq!(AuthResult::load(&envelope.unwrap()).unwrap(), result);
+        }
+        assert!(AuthResult::accept(7, String::from("camera-1"), Some(10_000)).is_valid_at(9_999));
+        assert!(!AuthResult::accept(7, String::from("camera-1"), Some(10_000)).is_valid_at(10_000));
+    }
+
+    #[test]
+    fn test_token_attached() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let token = AuthToken::new(String::from("Bearer"), String::from("abc"));
+        let builder = BuilderImpl::new(get_avro_path().as_str())
+            .with_auth_provider(Box::new(StaticAuthProvider(token.clone())));
+
+        let request = StorageStatsRequest::new(1, String::from("/replies"), None);
+        let message = request.save(&mb).unwrap();
+        let packed = builder
+            .pack_message_with_labels(&message.schema, message.object, &Default::default())
+            .unwrap();
+        assert_eq!(builder.read_auth(&packed), Ok(Some(token)));
+        let envelope = mb.load_to_avro(packed).unwrap();
+        assert_eq!(StorageStatsRequest::load(&envelope), Some(request));
+
+        let message = KeepAliveMessage::new(String::from("camera-1"))
+            .save(&mb)
+            .unwrap();
+        let packed = builder
+            .pack_message_with_labels(&message.schema, message.object, &Default::default())
+            .unwrap();
+        assert_eq!(builder.read_auth(&packed), Ok(None));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::avro::{Builder, ProtocolMessage, ERROR_RESPONSE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::Attributes;
+use crate::utils::{gen_hash_map, parse_hash_map};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum ErrorCode {
+    NotFound,
+    InvalidRange,
+    Unauthorized,
+    Overloaded,
+    Internal,
+    NotImplemented,
+}
+
+fn get_error_code_avro(code: &ErrorCode) -> Result<Value, String> {
+    Ok(match code {
+        ErrorCode::NotFound => Value::Enum(0, "NOT_FOUND".into()),
+        ErrorCode::InvalidRange => Value::Enum(1, "INVALID_RANGE".into()),
+        ErrorCode::Unauthorized => Value::Enum(2, "UNAUTHORIZED".into()),
+        ErrorCode::Overloaded => Value::Enum(3, "OVERLOADED".into()),
+        ErrorCode::Internal => Value::Enum(4, "INTERNAL".into()),
+        ErrorCode::NotImplemented => return Err(String::from("Not supported error code")),
+    })
+}
+
+fn get_error_code_enum(code: &str) -> ErrorCode {
+    match code {
+        "NOT_FOUND" => ErrorCode::NotFound,
+        "INVALID_RANGE" => ErrorCode::InvalidRange,
+        "UNAUTHORIZED" => ErrorCode::Unauthorized,
+        "OVERLOADED" => ErrorCode::Overloaded,
+        "INTERNAL" => ErrorCode::Internal,
+        _ => ErrorCode::NotImplemented,
+    }
+}
+
+/// Failure answer to any request, matched to it by `request_id`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ErrorResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub code: ErrorCode,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub message: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub details: Attributes,
+}
+
+message_builder!(ErrorResponse, ErrorResponseBuilder {
+    required {
+        request_id: i64,
+        code: ErrorCode,
+        message: String,
+    }
+    optional {}
+    defaulted {
+        details: Attributes,
+    }
+});
+
+impl ErrorResponse {
+    pub fn new(request_id: i64, code: ErrorCode, message: String, details: Attributes) -> Self {
+        ErrorResponse {
+            request_id,
+            code,
+            message,
+            details,
+        }
+    }
+
+    pub fn not_found(request_id: i64, message: String) -> Self {
+        Self::new(request_id, ErrorCode::NotFound, message, HashMap::default())
+    }
+
+    pub fn invalid_range(request_id: i64, message: String) -> Self {
+        Self::new(
+            request_id,
+            ErrorCode::InvalidRange,
+            message,
+            HashMap::default(),
+        )
+    }
+
+    pub fn unauthorized(request_id: i64, message: String) -> Self {
+        Self::new(
+            request_id,
+            ErrorCode::Unauthorized,
+            message,
+            HashMap::default(),
+        )
+    }
+
+    pub fn overloaded(request_id: i64, message: String) -> Self {
+        Self::new(
+            request_id,
+            ErrorCode::Overloaded,
+            message,
+            HashMap::default(),
+        )
+    }
+
+    pub fn internal(request_id: i64, message: String) -> Self {
+        Self::new(request_id, ErrorCode::Internal, message, HashMap::default())
+    }
+
+    /// Overloaded stores may serve the request later, other failures are final.
+    pub fn is_retryable(&self) -> bool {
+        self.code == ErrorCode::Overloaded
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ErrorResponse {
+    #[new]
+    fn py_new(request_id: i64, code: ErrorCode, message: String, details: Attributes) -> Self {
+        Self::new(request_id, code, message, details)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "not_found")]
+    fn py_not_found(request_id: i64, message: String) -> Self {
+        Self::not_found(request_id, message)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "invalid_range")]
+    fn py_invalid_range(request_id: i64, message: String) -> Self {
+        Self::invalid_range(request_id, message)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "unauthorized")]
+    fn py_unauthorized(request_id: i64, message: String) -> Self {
+        Self::unauthorized(request_id, message)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "overloaded")]
+    fn py_overloaded(request_id: i64, message: String) -> Self {
+        Self::overloaded(request_id, message)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "internal")]
+    fn py_internal(request_id: i64, message: String) -> Self {
+        Self::internal(request_id, message)
+    }
+
+    #[pyo3(name = "is_retryable")]
+    fn py_is_retryable(&self) -> bool {
+        self.is_retryable()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for ErrorResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != ERROR_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Enum(_, code)), (_, Value::String(message)), (_, Value::Map(details))] => {
+                    Some(ErrorResponse {
+                        request_id: *request_id,
+                        code: get_error_code_enum(code.as_str()),
+                        message: message.clone(),
+                        details: parse_hash_map(details),
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to ErrorResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for ErrorResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(ERROR_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("code", get_error_code_avro(&self.code).ok()?);
+        obj.put("message", Value::String(self.message.clone()));
+        obj.put("details", gen_hash_map(&self.details));
+        Some(ProtocolMessage {
+            schema: String::from(ERROR_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::error_response::{ErrorCode, ErrorResponse};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let mut rep = ErrorResponse::invalid_range(1, String::from("to_unit < from_unit"));
+        rep.details.insert("from_unit".into(), "10".into());
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = ErrorResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert_eq!(new_rep.code, ErrorCode::InvalidRange);
+        assert!(!new_rep.is_retryable());
+    }
+}
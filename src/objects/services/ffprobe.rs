@@ -2,32 +2,82 @@ use crate::avro::{
     Builder, ProtocolMessage, SERVICES_FFPROBE_REQUEST_SCHEMA, SERVICES_FFPROBE_RESPONSE_SCHEMA,
 };
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-use crate::utils::{gen_hash_map, value_to_string};
+use crate::primitives::{
+    optional_f64_from_avro, optional_f64_to_avro, optional_i64_from_avro, optional_i64_to_avro,
+    optional_string_from_avro, optional_string_to_avro, optional_u64_from_avro,
+    optional_u64_to_avro, AttributeValue, Attributes, UnitRange,
+};
+use crate::utils::{gen_hash_map, parse_hash_map};
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
 pub enum ServicesFFProbeResponseType {
     Accepted,
     Complete,
     Error,
+    Timeout,
+    SourceUnreachable,
+    UnsupportedProtocol,
+    AuthFailed,
     NotImplemented,
 }
 
+impl ServicesFFProbeResponseType {
+    pub fn is_failure(&self) -> bool {
+        !matches!(
+            self,
+            ServicesFFProbeResponseType::Accepted | ServicesFFProbeResponseType::Complete
+        )
+    }
+
+    /// Failures which may pass on their own, the others need someone to fix the
+    /// source URL, the credentials or the service.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ServicesFFProbeResponseType::Timeout | ServicesFFProbeResponseType::SourceUnreachable
+        )
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ServicesFFProbeResponseType {
+    #[pyo3(name = "is_failure")]
+    fn py_is_failure(&self) -> bool {
+        self.is_failure()
+    }
+
+    #[pyo3(name = "is_retryable")]
+    fn py_is_retryable(&self) -> bool {
+        self.is_retryable()
+    }
+}
+
 pub fn get_services_ffprobe_response_type_avro(
     response_type: &ServicesFFProbeResponseType,
-) -> Value {
-    match response_type {
+) -> Result<Value, String> {
+    Ok(match response_type {
         ServicesFFProbeResponseType::Accepted => Value::Enum(0, "ACCEPTED".into()),
         ServicesFFProbeResponseType::Complete => Value::Enum(1, "COMPLETE".into()),
         ServicesFFProbeResponseType::Error => Value::Enum(2, "ERROR".into()),
+        ServicesFFProbeResponseType::Timeout => Value::Enum(3, "TIMEOUT".into()),
+        ServicesFFProbeResponseType::SourceUnreachable => {
+            Value::Enum(4, "SOURCE_UNREACHABLE".into())
+        }
+        ServicesFFProbeResponseType::UnsupportedProtocol => {
+            Value::Enum(5, "UNSUPPORTED_PROTOCOL".into())
+        }
+        ServicesFFProbeResponseType::AuthFailed => Value::Enum(6, "AUTH_FAILED".into()),
         ServicesFFProbeResponseType::NotImplemented => {
-            panic!("Not supported ffprobe response type")
+            return Err(String::from("Not supported ffprobe response type"))
         }
-    }
+    })
 }
 
 fn get_services_ffprobe_response_type_enum(response_type: &str) -> ServicesFFProbeResponseType {
@@ -35,39 +85,398 @@ fn get_services_ffprobe_response_type_enum(response_type: &str) -> ServicesFFPro
         "ACCEPTED" => ServicesFFProbeResponseType::Accepted,
         "COMPLETE" => ServicesFFProbeResponseType::Complete,
         "ERROR" => ServicesFFProbeResponseType::Error,
+        "TIMEOUT" => ServicesFFProbeResponseType::Timeout,
+        "SOURCE_UNREACHABLE" => ServicesFFProbeResponseType::SourceUnreachable,
+        "UNSUPPORTED_PROTOCOL" => ServicesFFProbeResponseType::UnsupportedProtocol,
+        "AUTH_FAILED" => ServicesFFProbeResponseType::AuthFailed,
         _ => ServicesFFProbeResponseType::NotImplemented,
     }
 }
 
+fn attribute_i64(value: Option<&AttributeValue>) -> Option<i64> {
+    match value? {
+        AttributeValue::I64(v) => Some(*v),
+        AttributeValue::String(v) => v.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// ffprobe reports rates as fractions, e.g. `30000/1001`.
+fn attribute_f64(value: Option<&AttributeValue>) -> Option<f64> {
+    match value? {
+        AttributeValue::F64(v) => Some(*v),
+        AttributeValue::I64(v) => Some(*v as f64),
+        AttributeValue::String(v) => match v.trim().split_once('/') {
+            Some((num, den)) => {
+                let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+                (den != 0.0).then(|| num / den)
+            }
+            None => v.trim().parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn attribute_string(value: Option<&AttributeValue>) -> Option<String> {
+    match value? {
+        AttributeValue::String(v) => Some(v.clone()),
+        _ => None,
+    }
+}
+
+fn insert_some(attributes: &mut Attributes, key: &str, value: Option<AttributeValue>) {
+    if let Some(value) = value {
+        attributes.insert(String::from(key), value);
+    }
+}
+
+/// A stream of the probed media. The keys ffprobe reports which have no field here
+/// are kept in `extra` as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct FFProbeStreamInfo {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub index: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub codec_type: Option<String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub codec_name: Option<String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub width: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub height: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub fps: Option<f64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub bit_rate: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub duration_s: Option<f64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub extra: Attributes,
+}
+
+const STREAM_INFO_KEYS: [&str; 8] = [
+    "index",
+    "codec_type",
+    "codec_name",
+    "width",
+    "height",
+    "r_frame_rate",
+    "bit_rate",
+    "duration",
+];
+
+impl FFProbeStreamInfo {
+    pub fn from_attributes(attributes: Attributes) -> Self {
+        FFProbeStreamInfo {
+            index: attribute_i64(attributes.get("index")),
+            codec_type: attribute_string(attributes.get("codec_type")),
+            codec_name: attribute_string(attributes.get("codec_name")),
+            width: attribute_i64(attributes.get("width")),
+            height: attribute_i64(attributes.get("height")),
+            fps: attribute_f64(attributes.get("r_frame_rate")),
+            bit_rate: attribute_i64(attributes.get("bit_rate")),
+            duration_s: attribute_f64(attributes.get("duration")),
+            extra: attributes
+                .into_iter()
+                .filter(|(k, _)| !STREAM_INFO_KEYS.contains(&k.as_str()))
+                .collect(),
+        }
+    }
+
+    /// The raw map with the typed fields merged back under the ffprobe keys.
+    pub fn to_attributes(&self) -> Attributes {
+        let mut attributes = self.extra.clone();
+        insert_some(&mut attributes, "index", self.index.map(Into::into));
+        insert_some(
+            &mut attributes,
+            "codec_type",
+            self.codec_type.clone().map(Into::into),
+        );
+        insert_some(
+            &mut attributes,
+            "codec_name",
+            self.codec_name.clone().map(Into::into),
+        );
+        insert_some(&mut attributes, "width", self.width.map(Into::into));
+        insert_some(&mut attributes, "height", self.height.map(Into::into));
+        insert_some(&mut attributes, "r_frame_rate", self.fps.map(Into::into));
+        insert_some(&mut attributes, "bit_rate", self.bit_rate.map(Into::into));
+        insert_some(&mut attributes, "duration", self.duration_s.map(Into::into));
+        attributes
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FFProbeStreamInfo {
+    #[staticmethod]
+    #[pyo3(name = "from_attributes")]
+    fn py_from_attributes(attributes: Attributes) -> Self {
+        Self::from_attributes(attributes)
+    }
+
+    #[pyo3(name = "to_attributes")]
+    fn py_to_attributes(&self) -> Attributes {
+        self.to_attributes()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FFProbeStreamInfo {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("index".into(), optional_i64_to_avro(&self.index)),
+            (
+                "codec_type".into(),
+                optional_string_to_avro(&self.codec_type),
+            ),
+            (
+                "codec_name".into(),
+                optional_string_to_avro(&self.codec_name),
+            ),
+            ("width".into(), optional_i64_to_avro(&self.width)),
+            ("height".into(), optional_i64_to_avro(&self.height)),
+            ("fps".into(), optional_f64_to_avro(&self.fps)),
+            ("bit_rate".into(), optional_i64_to_avro(&self.bit_rate)),
+            ("duration_s".into(), optional_f64_to_avro(&self.duration_s)),
+            ("extra".into(), gen_hash_map(&self.extra)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, index), (_, codec_type), (_, codec_name), (_, width), (_, height), (_, fps), (_, bit_rate), (_, duration_s), (_, Value::Map(extra))] => {
+                    Some(FFProbeStreamInfo {
+                        index: optional_i64_from_avro(index)?,
+                        codec_type: optional_string_from_avro(codec_type)?,
+                        codec_name: optional_string_from_avro(codec_name)?,
+                        width: optional_i64_from_avro(width)?,
+                        height: optional_i64_from_avro(height)?,
+                        fps: optional_f64_from_avro(fps)?,
+                        bit_rate: optional_i64_from_avro(bit_rate)?,
+                        duration_s: optional_f64_from_avro(duration_s)?,
+                        extra: parse_hash_map(extra),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The container of the probed media, unrecognized keys are kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct FFProbeFormatInfo {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub format_name: Option<String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub duration_s: Option<f64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub size_bytes: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub bit_rate: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub nb_streams: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub extra: Attributes,
+}
+
+const FORMAT_INFO_KEYS: [&str; 5] = ["format_name", "duration", "size", "bit_rate", "nb_streams"];
+
+impl FFProbeFormatInfo {
+    pub fn from_attributes(attributes: Attributes) -> Self {
+        FFProbeFormatInfo {
+            format_name: attribute_string(attributes.get("format_name")),
+            duration_s: attribute_f64(attributes.get("duration")),
+            size_bytes: attribute_i64(attributes.get("size")),
+            bit_rate: attribute_i64(attributes.get("bit_rate")),
+            nb_streams: attribute_i64(attributes.get("nb_streams")),
+            extra: attributes
+                .into_iter()
+                .filter(|(k, _)| !FORMAT_INFO_KEYS.contains(&k.as_str()))
+                .collect(),
+        }
+    }
+
+    /// The raw map with the typed fields merged back under the ffprobe keys.
+    pub fn to_attributes(&self) -> Attributes {
+        let mut attributes = self.extra.clone();
+        insert_some(
+            &mut attributes,
+            "format_name",
+            self.format_name.clone().map(Into::into),
+        );
+        insert_some(&mut attributes, "duration", self.duration_s.map(Into::into));
+        insert_some(&mut attributes, "size", self.size_bytes.map(Into::into));
+        insert_some(&mut attributes, "bit_rate", self.bit_rate.map(Into::into));
+        insert_some(
+            &mut attributes,
+            "nb_streams",
+            self.nb_streams.map(Into::into),
+        );
+        attributes
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FFProbeFormatInfo {
+    #[staticmethod]
+    #[pyo3(name = "from_attributes")]
+    fn py_from_attributes(attributes: Attributes) -> Self {
+        Self::from_attributes(attributes)
+    }
+
+    #[pyo3(name = "to_attributes")]
+    fn py_to_attributes(&self) -> Attributes {
+        self.to_attributes()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FFProbeFormatInfo {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            (
+                "format_name".into(),
+                optional_string_to_avro(&self.format_name),
+            ),
+            ("duration_s".into(), optional_f64_to_avro(&self.duration_s)),
+            ("size_bytes".into(), optional_i64_to_avro(&self.size_bytes)),
+            ("bit_rate".into(), optional_i64_to_avro(&self.bit_rate)),
+            ("nb_streams".into(), optional_i64_to_avro(&self.nb_streams)),
+            ("extra".into(), gen_hash_map(&self.extra)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, format_name), (_, duration_s), (_, size_bytes), (_, bit_rate), (_, nb_streams), (_, Value::Map(extra))] => {
+                    Some(FFProbeFormatInfo {
+                        format_name: optional_string_from_avro(format_name)?,
+                        duration_s: optional_f64_from_avro(duration_s)?,
+                        size_bytes: optional_i64_from_avro(size_bytes)?,
+                        bit_rate: optional_i64_from_avro(bit_rate)?,
+                        nb_streams: optional_i64_from_avro(nb_streams)?,
+                        extra: parse_hash_map(extra),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct ServicesFFProbeRequest {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub topic: String,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub url: String,
-    #[pyo3(get, set)]
-    pub attributes: HashMap<String, String>,
+    /// Stored footage to probe; when set, `url` is ignored by the service.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub source_range: Option<UnitRange>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
 }
 
-#[pymethods]
-impl ServicesFFProbeRequest {
-    #[new]
-    pub fn new(
+message_builder!(ServicesFFProbeRequest, ServicesFFProbeRequestBuilder {
+    required {
         request_id: i64,
         topic: String,
         url: String,
-        attributes: HashMap<String, String>,
-    ) -> Self {
+    }
+    optional {
+        source_range: UnitRange,
+        deadline_ms: u64,
+    }
+    defaulted {
+        attributes: Attributes,
+    }
+});
+
+impl ServicesFFProbeRequest {
+    pub fn new(request_id: i64, topic: String, url: String, attributes: Attributes) -> Self {
         ServicesFFProbeRequest {
             request_id,
             topic,
             url,
+            source_range: None,
+            attributes,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn from_range(
+        request_id: i64,
+        topic: String,
+        source_range: UnitRange,
+        attributes: Attributes,
+    ) -> Self {
+        ServicesFFProbeRequest {
+            request_id,
+            topic,
+            url: String::default(),
+            source_range: Some(source_range),
             attributes,
+            deadline_ms: None,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ServicesFFProbeRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, url: String, attributes: Attributes) -> Self {
+        Self::new(request_id, topic, url, attributes)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_range")]
+    fn py_from_range(
+        request_id: i64,
+        topic: String,
+        source_range: UnitRange,
+        attributes: Attributes,
+    ) -> Self {
+        Self::from_range(request_id, topic, source_range, attributes)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -82,35 +491,135 @@ impl ServicesFFProbeRequest {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct ServicesFFProbeResponse {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub response_type: ServicesFFProbeResponseType,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub time_spent: i64,
-    #[pyo3(get, set)]
-    pub streams: Vec<HashMap<String, String>>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub streams: Vec<FFProbeStreamInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub format: Option<FFProbeFormatInfo>,
+    /// Human-readable cause of a failed probe, e.g. the ffprobe stderr.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error_detail: Option<String>,
 }
 
-#[pymethods]
+message_builder!(ServicesFFProbeResponse, ServicesFFProbeResponseBuilder {
+    required {
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+    }
+    optional {
+        format: FFProbeFormatInfo,
+        error_detail: String,
+    }
+    defaulted {
+        streams: Vec<FFProbeStreamInfo>,
+    }
+});
+
 impl ServicesFFProbeResponse {
-    #[new]
     pub fn new(
         request_id: i64,
         response_type: ServicesFFProbeResponseType,
         time_spent: i64,
-        streams: Vec<HashMap<String, String>>,
+        streams: Vec<Attributes>,
+    ) -> Self {
+        ServicesFFProbeResponse {
+            request_id,
+            response_type,
+            time_spent,
+            streams: streams
+                .into_iter()
+                .map(FFProbeStreamInfo::from_attributes)
+                .collect(),
+            format: None,
+            error_detail: None,
+        }
+    }
+
+    pub fn typed(
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+        streams: Vec<FFProbeStreamInfo>,
+        format: Option<FFProbeFormatInfo>,
     ) -> Self {
         ServicesFFProbeResponse {
             request_id,
             response_type,
             time_spent,
             streams,
+            format,
+            error_detail: None,
         }
     }
 
+    pub fn failed(
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+        error_detail: String,
+    ) -> Self {
+        ServicesFFProbeResponse {
+            error_detail: Some(error_detail),
+            ..Self::typed(request_id, response_type, time_spent, vec![], None)
+        }
+    }
+
+    /// The streams as ffprobe reports them, for the keys the typed fields don't cover.
+    pub fn raw_streams(&self) -> Vec<Attributes> {
+        self.streams.iter().map(|s| s.to_attributes()).collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ServicesFFProbeResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+        streams: Vec<Attributes>,
+    ) -> Self {
+        Self::new(request_id, response_type, time_spent, streams)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "typed")]
+    fn py_typed(
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+        streams: Vec<FFProbeStreamInfo>,
+        format: Option<FFProbeFormatInfo>,
+    ) -> Self {
+        Self::typed(request_id, response_type, time_spent, streams, format)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "failed")]
+    fn py_failed(
+        request_id: i64,
+        response_type: ServicesFFProbeResponseType,
+        time_spent: i64,
+        error_detail: String,
+    ) -> Self {
+        Self::failed(request_id, response_type, time_spent, error_detail)
+    }
+
+    #[pyo3(name = "raw_streams")]
+    fn py_raw_streams(&self) -> Vec<Attributes> {
+        self.raw_streams()
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -131,22 +640,14 @@ impl FromProtocolMessage for ServicesFFProbeRequest {
 
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(url)), (_, Value::Map(attributes))] => {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(url)), (_, Value::Union(source_range)), (_, Value::Map(attributes)), (_, deadline_ms)] => {
                     Some(ServicesFFProbeRequest {
                         request_id: *request_id,
                         topic: topic.clone(),
                         url: url.clone(),
-                        attributes: attributes
-                            .iter()
-                            .map(|x| {
-                                (
-                                    x.0.clone(),
-                                    value_to_string(x.1)
-                                        .or_else(|| Some(String::from("")))
-                                        .unwrap(),
-                                )
-                            })
-                            .collect(),
+                        source_range: UnitRange::from_avro(source_range),
+                        attributes: parse_hash_map(attributes),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
                     })
                 }
                 _ => {
@@ -170,38 +671,30 @@ impl FromProtocolMessage for ServicesFFProbeResponse {
 
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::Enum(_, response_type)), (_, Value::Long(time_spent)), (_, Value::Array(streams))] =>
+                [(_, Value::Long(request_id)), (_, Value::Enum(_, response_type)), (_, Value::Long(time_spent)), (_, Value::Array(streams)), (_, Value::Union(format)), (_, error_detail)] =>
                 {
-                    let mut response_streams: Vec<HashMap<String, String>> = Default::default();
-                    for s in streams {
-                        match s {
-                            Value::Map(attributes) => {
-                                let attributes: HashMap<String, String> = attributes
-                                    .iter()
-                                    .map(|kv| {
-                                        (
-                                            kv.0.clone(),
-                                            value_to_string(kv.1)
-                                                .unwrap_or_else(|| String::from("")),
-                                        )
-                                    })
-                                    .collect();
-                                response_streams.push(attributes);
-                            }
-                            _ => panic!(
-                                "Unexpected structure found, stream attributes must be a `map`"
+                    let streams: Option<Vec<_>> =
+                        streams.iter().map(FFProbeStreamInfo::from_avro).collect();
+                    let format = match format.as_ref() {
+                        Value::Null => None,
+                        format => Some(FFProbeFormatInfo::from_avro(format)?),
+                    };
+                    match streams {
+                        Some(streams) => Some(ServicesFFProbeResponse {
+                            request_id: *request_id,
+                            response_type: get_services_ffprobe_response_type_enum(
+                                response_type.as_str(),
                             ),
+                            time_spent: *time_spent,
+                            streams,
+                            format,
+                            error_detail: optional_string_from_avro(error_detail)?,
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to FFProbeStreamInfo");
+                            None
                         }
                     }
-
-                    Some(ServicesFFProbeResponse {
-                        request_id: *request_id,
-                        response_type: get_services_ffprobe_response_type_enum(
-                            response_type.as_str(),
-                        ),
-                        time_spent: *time_spent,
-                        streams: response_streams,
-                    })
                 }
                 _ => {
                     warn!("Unable to match AVRO Record to to FFprobe Response");
@@ -218,11 +711,19 @@ impl FromProtocolMessage for ServicesFFProbeResponse {
 
 impl ToProtocolMessage for ServicesFFProbeRequest {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut object = mb.get_record(SERVICES_FFPROBE_REQUEST_SCHEMA);
+        let mut object = mb.get_record(SERVICES_FFPROBE_REQUEST_SCHEMA)?;
         object.put("request_id", Value::Long(self.request_id));
         object.put("topic", Value::String(self.topic.clone()));
         object.put("url", Value::String(self.url.clone()));
+        object.put(
+            "source_range",
+            Value::Union(Box::new(match &self.source_range {
+                Some(source_range) => source_range.to_avro_record().ok()?,
+                None => Value::Null,
+            })),
+        );
         object.put("attributes", gen_hash_map(&self.attributes));
+        object.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
         Some(ProtocolMessage {
             schema: String::from(SERVICES_FFPROBE_REQUEST_SCHEMA),
             object: Value::from(object),
@@ -232,15 +733,23 @@ impl ToProtocolMessage for ServicesFFProbeRequest {
 
 impl ToProtocolMessage for ServicesFFProbeResponse {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut object = mb.get_record(SERVICES_FFPROBE_RESPONSE_SCHEMA);
+        let mut object = mb.get_record(SERVICES_FFPROBE_RESPONSE_SCHEMA)?;
         object.put("request_id", Value::Long(self.request_id));
         object.put(
             "response_type",
-            get_services_ffprobe_response_type_avro(&self.response_type),
+            get_services_ffprobe_response_type_avro(&self.response_type).ok()?,
         );
         object.put("time_spent", Value::Long(self.time_spent));
-        let streams_array: Vec<Value> = self.streams.iter().map(gen_hash_map).collect();
+        let streams_array: Vec<Value> = self.streams.iter().map(|s| s.to_avro_record()).collect();
         object.put("streams", Value::Array(streams_array));
+        object.put(
+            "format",
+            Value::Union(Box::new(match &self.format {
+                Some(format) => format.to_avro_record(),
+                None => Value::Null,
+            })),
+        );
+        object.put("error_detail", optional_string_to_avro(&self.error_detail));
         Some(ProtocolMessage {
             schema: String::from(SERVICES_FFPROBE_RESPONSE_SCHEMA),
             object: Value::from(object),
@@ -252,11 +761,14 @@ impl ToProtocolMessage for ServicesFFProbeResponse {
 mod tests {
     use crate::avro::Builder;
     use crate::objects::services::ffprobe::{
-        ServicesFFProbeRequest, ServicesFFProbeResponse, ServicesFFProbeResponseType,
+        FFProbeFormatInfo, FFProbeStreamInfo, ServicesFFProbeRequest, ServicesFFProbeResponse,
+        ServicesFFProbeResponseType,
     };
     use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, UnitRange};
     use crate::utils::get_avro_path;
     use std::collections::HashMap;
+    use uuid::Uuid;
 
     #[test]
     fn test_load_save_req() {
@@ -271,7 +783,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -287,6 +799,32 @@ mod tests {
         assert_eq!(req, new_req);
     }
 
+    #[test]
+    fn test_load_save_range_req() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let req = ServicesFFProbeRequest::from_range(
+            0,
+            String::from("test"),
+            UnitRange::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                10,
+                20,
+            ),
+            HashMap::default(),
+        );
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        let new_req = ServicesFFProbeRequest::load(&req_envelope).unwrap();
+
+        assert_eq!(req, new_req);
+    }
+
     #[test]
     fn test_load_save_resp() {
         let mb = Builder::new(get_avro_path().as_str());
@@ -295,15 +833,15 @@ mod tests {
             ServicesFFProbeResponseType::Accepted,
             100,
             vec![
-                HashMap::from([("a".to_string(), "b".to_string())]),
-                HashMap::from([("x".to_string(), "y".to_string())]),
+                HashMap::from([("a".to_string(), "b".into())]),
+                HashMap::from([("width".to_string(), 1920i64.into())]),
             ],
         );
         let res_envelope_opt = res.save(&mb);
         assert!(res_envelope_opt.is_some());
 
         let res_envelope = res_envelope_opt.unwrap();
-        let res_serialized = mb.save_from_avro(res_envelope);
+        let res_serialized = mb.save_from_avro(res_envelope).unwrap();
 
         let res_envelope_opt = mb.load_to_avro(res_serialized);
         assert!(res_envelope_opt.is_some());
@@ -318,4 +856,61 @@ mod tests {
 
         assert_eq!(res, new_res);
     }
+
+    #[test]
+    fn test_typed_stream_info() {
+        let info = FFProbeStreamInfo::from_attributes(HashMap::from([
+            ("codec_name".to_string(), "h264".into()),
+            ("width".to_string(), "1920".into()),
+            ("height".to_string(), 1080i64.into()),
+            ("r_frame_rate".to_string(), "30000/1001".into()),
+            ("profile".to_string(), "High".into()),
+        ]));
+        assert_eq!(info.codec_name.as_deref(), Some("h264"));
+        assert_eq!(info.width, Some(1920));
+        assert_eq!(info.height, Some(1080));
+        assert!((info.fps.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(info.bit_rate, None);
+        assert_eq!(
+            info.extra,
+            HashMap::from([("profile".to_string(), "High".into())])
+        );
+        assert_eq!(info.to_attributes().get("width"), Some(&1920i64.into()));
+
+        let mb = Builder::new(get_avro_path().as_str());
+        let res = ServicesFFProbeResponse::typed(
+            1,
+            ServicesFFProbeResponseType::Complete,
+            100,
+            vec![info],
+            Some(FFProbeFormatInfo::from_attributes(HashMap::from([
+                ("format_name".to_string(), "mov,mp4".into()),
+                ("duration".to_string(), "12.5".into()),
+            ]))),
+        );
+        let res_serialized = mb.save_from_avro(res.save(&mb).unwrap()).unwrap();
+        let new_res =
+            ServicesFFProbeResponse::load(&mb.load_to_avro(res_serialized).unwrap()).unwrap();
+        assert_eq!(res, new_res);
+        assert_eq!(new_res.format.unwrap().duration_s, Some(12.5));
+    }
+
+    #[test]
+    fn test_load_save_failed_resp() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let res = ServicesFFProbeResponse::failed(
+            1,
+            ServicesFFProbeResponseType::SourceUnreachable,
+            5000,
+            String::from("Connection refused"),
+        );
+        assert!(res.response_type.is_failure());
+        assert!(res.response_type.is_retryable());
+        assert!(!ServicesFFProbeResponseType::AuthFailed.is_retryable());
+
+        let res_serialized = mb.save_from_avro(res.save(&mb).unwrap()).unwrap();
+        let new_res =
+            ServicesFFProbeResponse::load(&mb.load_to_avro(res_serialized).unwrap()).unwrap();
+        assert_eq!(res, new_res);
+    }
 }
@@ -0,0 +1,428 @@
+use crate::avro::{
+    Builder, ProtocolMessage, SERVICES_FILMSTRIP_REQUEST_SCHEMA, SERVICES_FILMSTRIP_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_u64_from_avro, optional_u64_to_avro, ArtifactReference, Payload, Unit,
+};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ServicesFilmstripRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tile_count: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tile_width: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tile_height: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub composite: bool,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(ServicesFilmstripRequest, ServicesFilmstripRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u64,
+        to_ms: u64,
+        tile_count: i32,
+        tile_width: i32,
+        tile_height: i32,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {
+        composite: bool,
+    }
+});
+
+impl ServicesFilmstripRequest {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u64,
+        to_ms: u64,
+        tile_count: i32,
+        tile_width: i32,
+        tile_height: i32,
+        composite: bool,
+    ) -> Self {
+        ServicesFilmstripRequest {
+            request_id,
+            topic,
+            stream_unit,
+            from_ms,
+            to_ms,
+            tile_count,
+            tile_width,
+            tile_height,
+            composite,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ServicesFilmstripRequest {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u64,
+        to_ms: u64,
+        tile_count: i32,
+        tile_width: i32,
+        tile_height: i32,
+        composite: bool,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_unit,
+            from_ms,
+            to_ms,
+            tile_count,
+            tile_width,
+            tile_height,
+            composite,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ServicesFilmstripResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub composite: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tiles: Vec<Payload>,
+    /// Filled instead of `tiles` when the service uploads the result and hands out
+    /// download links.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub artifacts: Vec<ArtifactReference>,
+}
+
+message_builder!(ServicesFilmstripResponse, ServicesFilmstripResponseBuilder {
+    required {
+        request_id: i64,
+        stream_unit: Unit,
+    }
+    optional {}
+    defaulted {
+        composite: bool,
+        tiles: Vec<Payload>,
+        artifacts: Vec<ArtifactReference>,
+    }
+});
+
+impl ServicesFilmstripResponse {
+    pub fn new(
+        request_id: i64,
+        stream_unit: Unit,
+        composite: bool,
+        tiles: Vec<Payload>,
+        artifacts: Vec<ArtifactReference>,
+    ) -> Self {
+        ServicesFilmstripResponse {
+            request_id,
+            stream_unit,
+            composite,
+            tiles,
+            artifacts,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ServicesFilmstripResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_unit: Unit,
+        composite: bool,
+        tiles: Vec<Payload>,
+        artifacts: Vec<ArtifactReference>,
+    ) -> Self {
+        Self::new(request_id, stream_unit, composite, tiles, artifacts)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for ServicesFilmstripRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_FILMSTRIP_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, stream_unit), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, Value::Int(tile_count)), (_, Value::Int(tile_width)), (_, Value::Int(tile_height)), (_, Value::Boolean(composite)), (_, deadline_ms)] => {
+                    match Unit::from_avro(stream_unit) {
+                        Some(stream_unit) => Some(ServicesFilmstripRequest {
+                            request_id: *request_id,
+                            topic: topic.clone(),
+                            stream_unit,
+                            from_ms: *from_ms as u64,
+                            to_ms: *to_ms as u64,
+                            tile_count: *tile_count,
+                            tile_width: *tile_width,
+                            tile_height: *tile_height,
+                            composite: *composite,
+                            deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to Unit");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to Filmstrip Request");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for ServicesFilmstripRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_FILMSTRIP_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("from_ms", Value::Long(self.from_ms as i64));
+        obj.put("to_ms", Value::Long(self.to_ms as i64));
+        obj.put("tile_count", Value::Int(self.tile_count));
+        obj.put("tile_width", Value::Int(self.tile_width));
+        obj.put("tile_height", Value::Int(self.tile_height));
+        obj.put("composite", Value::Boolean(self.composite));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_FILMSTRIP_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for ServicesFilmstripResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_FILMSTRIP_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, stream_unit), (_, Value::Boolean(composite)), (_, Value::Array(tiles)), (_, Value::Array(artifacts))] =>
+                {
+                    let tiles_parsed: Vec<_> =
+                        tiles.iter().filter_map(Payload::from_avro).collect();
+                    let artifacts_parsed: Vec<_> = artifacts
+                        .iter()
+                        .filter_map(ArtifactReference::from_avro)
+                        .collect();
+                    match Unit::from_avro(stream_unit) {
+                        Some(stream_unit)
+                            if tiles_parsed.len() == tiles.len()
+                                && artifacts_parsed.len() == artifacts.len() =>
+                        {
+                            Some(ServicesFilmstripResponse {
+                                request_id: *request_id,
+                                stream_unit,
+                                composite: *composite,
+                                tiles: tiles_parsed,
+                                artifacts: artifacts_parsed,
+                            })
+                        }
+                        _ => {
+                            warn!("Unable to match AVRO Record to Unit, tiles or artifacts");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to Filmstrip Response");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for ServicesFilmstripResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_FILMSTRIP_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("composite", Value::Boolean(self.composite));
+        let tiles: Vec<Value> = self
+            .tiles
+            .iter()
+            .map(Payload::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("tiles", Value::Array(tiles));
+        let artifacts: Vec<Value> = self
+            .artifacts
+            .iter()
+            .map(ArtifactReference::to_avro_record)
+            .collect();
+        obj.put("artifacts", Value::Array(artifacts));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_FILMSTRIP_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::filmstrip::{
+        ServicesFilmstripRequest, ServicesFilmstripResponse,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, ArtifactReference, Payload, Unit};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn stream_unit() -> Unit {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_load_save_req() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let req = ServicesFilmstripRequest::new(
+            1,
+            String::from("response"),
+            stream_unit(),
+            100,
+            500,
+            10,
+            160,
+            90,
+            true,
+        );
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        let new_req = ServicesFilmstripRequest::load(&req_envelope).unwrap();
+
+        assert_eq!(req, new_req);
+    }
+
+    #[test]
+    fn test_load_save_rep() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let rep = ServicesFilmstripResponse::new(
+            1,
+            stream_unit(),
+            false,
+            vec![
+                Payload::new(vec![0, 1], HashMap::from([("ts".into(), "100".into())])),
+                Payload::new(vec![2, 3], HashMap::from([("ts".into(), "200".into())])),
+            ],
+            vec![],
+        );
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = ServicesFilmstripResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+    }
+
+    #[test]
+    fn test_load_save_rep_artifacts() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let artifact = ArtifactReference::new(
+            String::from("https://storage.local/filmstrip/1.jpg?X-Amz-Signature=abc"),
+            1_000_000,
+            String::from("image/jpeg"),
+            4096,
+        );
+        let rep = ServicesFilmstripResponse::new(1, stream_unit(), true, vec![], vec![artifact]);
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = ServicesFilmstripResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert!(!new_rep.artifacts[0].is_expired(999_999));
+        assert!(new_rep.artifacts[0].is_expired(1_000_000));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::avro::{Builder, ProtocolMessage, HEARTBEAT_MESSAGE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Liveness a node publishes unsolicited and periodically, unlike the ping it
+/// expects no answer. `load` is the share of the node capacity in use.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct HeartbeatMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub node_id: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub role: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub uptime_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub load: f64,
+}
+
+message_builder!(HeartbeatMessage, HeartbeatMessageBuilder {
+    required {
+        node_id: String,
+        role: String,
+        uptime_ms: u64,
+        load: f64,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl HeartbeatMessage {
+    pub fn new(node_id: String, role: String, uptime_ms: u64, load: f64) -> Self {
+        HeartbeatMessage {
+            node_id,
+            role,
+            uptime_ms,
+            load,
+        }
+    }
+
+    /// Heartbeat of a node started at `started_ms`.
+    pub fn since(node_id: String, role: String, started_ms: u64, now_ms: u64, load: f64) -> Self {
+        Self::new(node_id, role, now_ms.saturating_sub(started_ms), load)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl HeartbeatMessage {
+    #[new]
+    fn py_new(node_id: String, role: String, uptime_ms: u64, load: f64) -> Self {
+        Self::new(node_id, role, uptime_ms, load)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "since")]
+    fn py_since(node_id: String, role: String, started_ms: u64, now_ms: u64, load: f64) -> Self {
+        Self::since(node_id, role, started_ms, now_ms, load)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for HeartbeatMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(HEARTBEAT_MESSAGE_SCHEMA)?;
+        obj.put("node_id", Value::String(self.node_id.clone()));
+        obj.put("role", Value::String(self.role.clone()));
+        obj.put("uptime_ms", Value::Long(self.uptime_ms as i64));
+        obj.put("load", Value::Double(self.load));
+        Some(ProtocolMessage {
+            schema: String::from(HEARTBEAT_MESSAGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for HeartbeatMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != HEARTBEAT_MESSAGE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(node_id)), (_, Value::String(role)), (_, Value::Long(uptime_ms)), (_, Value::Double(load))] => {
+                    Some(HeartbeatMessage {
+                        node_id: node_id.clone(),
+                        role: role.clone(),
+                        uptime_ms: *uptime_ms as u64,
+                        load: *load,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to HeartbeatMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::heartbeat::HeartbeatMessage;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let msg = HeartbeatMessage::since(
+            String::from("store-1"),
+            String::from("store"),
+            1_000,
+            61_000,
+            0.25,
+        );
+        assert_eq!(msg.uptime_ms, 60_000);
+
+        let envelope = msg.save(&mb).unwrap();
+        let serialized = mb.save_from_avro(envelope).unwrap();
+        let envelope = mb.load_to_avro(serialized).unwrap();
+        let new_msg = HeartbeatMessage::load(&envelope).unwrap();
+        assert_eq!(msg, new_msg);
+    }
+}
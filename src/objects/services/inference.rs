@@ -0,0 +1,471 @@
+use crate::avro::{Builder, ProtocolMessage, SERVICES_INFERENCE_RESULT_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_i64_from_avro, optional_i64_to_avro, Attributes, ElementType, Unit,
+};
+use crate::utils::{gen_hash_map, parse_hash_map};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Box in the frame coordinates, the origin is the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct BoundingBox {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub left: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub top: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub width: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub height: f64,
+}
+
+impl BoundingBox {
+    pub fn new(left: f64, top: f64, width: f64, height: f64) -> Self {
+        BoundingBox {
+            left,
+            top,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.left + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.top + self.height
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+
+    /// Intersection over union with the other box, `0.0` for disjoint boxes.
+    pub fn iou(&self, other: BoundingBox) -> f64 {
+        let width = self.right().min(other.right()) - self.left.max(other.left);
+        let height = self.bottom().min(other.bottom()) - self.top.max(other.top);
+        if width <= 0.0 || height <= 0.0 {
+            return 0.0;
+        }
+        let intersection = width * height;
+        intersection / (self.area() + other.area() - intersection)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BoundingBox {
+    #[new]
+    fn py_new(left: f64, top: f64, width: f64, height: f64) -> Self {
+        Self::new(left, top, width, height)
+    }
+
+    #[pyo3(name = "right")]
+    fn py_right(&self) -> f64 {
+        self.right()
+    }
+
+    #[pyo3(name = "bottom")]
+    fn py_bottom(&self) -> f64 {
+        self.bottom()
+    }
+
+    #[pyo3(name = "area")]
+    fn py_area(&self) -> f64 {
+        self.area()
+    }
+
+    #[pyo3(name = "iou")]
+    fn py_iou(&self, other: BoundingBox) -> f64 {
+        self.iou(other)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl BoundingBox {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("left".into(), Value::Double(self.left)),
+            ("top".into(), Value::Double(self.top)),
+            ("width".into(), Value::Double(self.width)),
+            ("height".into(), Value::Double(self.height)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Double(left)), (_, Value::Double(top)), (_, Value::Double(width)), (_, Value::Double(height))] => {
+                    Some(BoundingBox::new(*left, *top, *width, *height))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Object a model found in the frame. `object_id` tells the detections of a frame
+/// apart, `track_id` is kept by the tracker for the object across the frames.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct Detection {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub label: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub confidence: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub bbox: BoundingBox,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub object_id: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_id: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
+}
+
+impl Detection {
+    pub fn new(label: String, confidence: f64, bbox: BoundingBox) -> Self {
+        Detection {
+            label,
+            confidence,
+            bbox,
+            object_id: None,
+            track_id: None,
+            attributes: Attributes::default(),
+        }
+    }
+
+    pub fn tracked(
+        label: String,
+        confidence: f64,
+        bbox: BoundingBox,
+        object_id: i64,
+        track_id: i64,
+    ) -> Self {
+        Detection {
+            object_id: Some(object_id),
+            track_id: Some(track_id),
+            ..Self::new(label, confidence, bbox)
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Detection {
+    #[new]
+    fn py_new(label: String, confidence: f64, bbox: BoundingBox) -> Self {
+        Self::new(label, confidence, bbox)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "tracked")]
+    fn py_tracked(
+        label: String,
+        confidence: f64,
+        bbox: BoundingBox,
+        object_id: i64,
+        track_id: i64,
+    ) -> Self {
+        Self::tracked(label, confidence, bbox, object_id, track_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl Detection {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("label".into(), Value::String(self.label.clone())),
+            ("confidence".into(), Value::Double(self.confidence)),
+            ("bbox".into(), self.bbox.to_avro_record()),
+            ("object_id".into(), optional_i64_to_avro(&self.object_id)),
+            ("track_id".into(), optional_i64_to_avro(&self.track_id)),
+            ("attributes".into(), gen_hash_map(&self.attributes)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(label)), (_, Value::Double(confidence)), (_, bbox), (_, object_id), (_, track_id), (_, Value::Map(attributes))] => {
+                    Some(Detection {
+                        label: label.clone(),
+                        confidence: *confidence,
+                        bbox: BoundingBox::from_avro(bbox)?,
+                        object_id: optional_i64_from_avro(object_id)?,
+                        track_id: optional_i64_from_avro(track_id)?,
+                        attributes: parse_hash_map(attributes),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// What a model found in `element` of the unit, e.g. a video frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct InferenceResultMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub element: ElementType,
+    /// Name and version of the model, e.g. `yolov5s:2`.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub model: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub detections: Vec<Detection>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub produced_ms: u64,
+}
+
+message_builder!(InferenceResultMessage, InferenceResultMessageBuilder {
+    required {
+        stream_unit: Unit,
+        element: ElementType,
+        model: String,
+        produced_ms: u64,
+    }
+    optional {}
+    defaulted {
+        detections: Vec<Detection>,
+    }
+});
+
+impl InferenceResultMessage {
+    pub fn new(stream_unit: Unit, element: ElementType, model: String, produced_ms: u64) -> Self {
+        InferenceResultMessage {
+            stream_unit,
+            element,
+            model,
+            detections: vec![],
+            produced_ms,
+        }
+    }
+
+    pub fn add_detection(&mut self, detection: Detection) {
+        self.detections.push(detection);
+    }
+
+    /// Detections the model is at least `min_confidence` sure about.
+    pub fn confident(&self, min_confidence: f64) -> Vec<Detection> {
+        self.detections
+            .iter()
+            .filter(|d| d.confidence >= min_confidence)
+            .cloned()
+            .collect()
+    }
+
+    pub fn with_label(&self, label: &str) -> Vec<Detection> {
+        self.detections
+            .iter()
+            .filter(|d| d.label == label)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl InferenceResultMessage {
+    #[new]
+    fn py_new(stream_unit: Unit, element: ElementType, model: String, produced_ms: u64) -> Self {
+        Self::new(stream_unit, element, model, produced_ms)
+    }
+
+    #[pyo3(name = "add_detection")]
+    fn py_add_detection(&mut self, detection: Detection) {
+        self.add_detection(detection)
+    }
+
+    #[pyo3(name = "confident")]
+    fn py_confident(&self, min_confidence: f64) -> Vec<Detection> {
+        self.confident(min_confidence)
+    }
+
+    #[pyo3(name = "with_label")]
+    fn py_with_label(&self, label: &str) -> Vec<Detection> {
+        self.with_label(label)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl InferenceResultMessage {
+    pub fn with_detections(mut self, detections: impl IntoIterator<Item = Detection>) -> Self {
+        self.detections.extend(detections);
+        self
+    }
+}
+
+impl ToProtocolMessage for InferenceResultMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_INFERENCE_RESULT_SCHEMA)?;
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("element", Value::Int(self.element.into()));
+        obj.put("model", Value::String(self.model.clone()));
+        let detections: Vec<Value> = self
+            .detections
+            .iter()
+            .map(Detection::to_avro_record)
+            .collect();
+        obj.put("detections", Value::Array(detections));
+        obj.put("produced_ms", Value::Long(self.produced_ms as i64));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_INFERENCE_RESULT_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for InferenceResultMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_INFERENCE_RESULT_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, stream_unit), (_, Value::Int(element)), (_, Value::String(model)), (_, Value::Array(detections)), (_, Value::Long(produced_ms))] =>
+                {
+                    let stream_unit = match Unit::from_avro(stream_unit) {
+                        Some(stream_unit) => stream_unit,
+                        None => {
+                            warn!("Unable to match AVRO Record to Unit");
+                            return None;
+                        }
+                    };
+                    let detections_parsed: Vec<_> =
+                        detections.iter().filter_map(Detection::from_avro).collect();
+                    if detections_parsed.len() < detections.len() {
+                        warn!("Not all Detection records are parsed well.");
+                        return None;
+                    }
+                    Some(InferenceResultMessage {
+                        stream_unit,
+                        element: *element as ElementType,
+                        model: model.clone(),
+                        detections: detections_parsed,
+                        produced_ms: *produced_ms as u64,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to InferenceResultMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::inference::{BoundingBox, Detection, InferenceResultMessage};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, AttributeValue, Unit};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let mut person = Detection::tracked(
+            String::from("person"),
+            0.92,
+            BoundingBox::new(10.0, 20.0, 50.0, 120.0),
+            1,
+            77,
+        );
+        person
+            .attributes
+            .insert(String::from("pose"), AttributeValue::from("standing"));
+        let msg = InferenceResultMessage::new(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            2,
+            String::from("yolov5s:2"),
+            1_650_000_000_000,
+        )
+        .with_detections([
+            person,
+            Detection::new(
+                String::from("car"),
+                0.41,
+                BoundingBox::new(200.0, 80.0, 160.0, 90.0),
+            ),
+        ]);
+
+        let envelope = msg.save(&mb).unwrap();
+        let serialized = mb.save_from_avro(envelope).unwrap();
+        let envelope = mb.load_to_avro(serialized).unwrap();
+        let new_msg = InferenceResultMessage::load(&envelope).unwrap();
+
+        assert_eq!(msg, new_msg);
+        assert_eq!(new_msg.confident(0.5).len(), 1);
+        assert_eq!(new_msg.with_label("car")[0].track_id, None);
+    }
+
+    #[test]
+    fn test_iou() {
+        let a = BoundingBox::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(a.iou(a), 1.0);
+        assert_eq!(a.iou(BoundingBox::new(10.0, 0.0, 10.0, 10.0)), 0.0);
+        assert_eq!(a.iou(BoundingBox::new(5.0, 0.0, 10.0, 10.0)), 50.0 / 150.0);
+    }
+}
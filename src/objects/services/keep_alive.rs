@@ -2,21 +2,38 @@ use crate::avro::{Builder, ProtocolMessage, KEEPALIVE_MESSAGE_SCHEMA};
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct KeepAliveMessage {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub module_id: String,
 }
 
-#[pymethods]
+message_builder!(KeepAliveMessage, KeepAliveMessageBuilder {
+    required {
+        module_id: String,
+    }
+    optional {}
+    defaulted {}
+});
+
 impl KeepAliveMessage {
-    #[new]
     pub fn new(module_id: String) -> KeepAliveMessage {
         KeepAliveMessage { module_id }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl KeepAliveMessage {
+    #[new]
+    fn py_new(module_id: String) -> Self {
+        Self::new(module_id)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -58,7 +75,7 @@ impl FromProtocolMessage for KeepAliveMessage {
 
 impl ToProtocolMessage for KeepAliveMessage {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut object = mb.get_record(KEEPALIVE_MESSAGE_SCHEMA);
+        let mut object = mb.get_record(KEEPALIVE_MESSAGE_SCHEMA)?;
         object.put("module_id", Value::String(self.module_id.clone()));
 
         Some(ProtocolMessage {
@@ -84,7 +101,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
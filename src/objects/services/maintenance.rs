@@ -0,0 +1,241 @@
+use crate::avro::{Builder, ProtocolMessage, MAINTENANCE_NOTICE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::StreamName;
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum MaintenanceSeverity {
+    Info,
+    Degraded,
+    Outage,
+    NotImplemented,
+}
+
+fn get_maintenance_severity_avro(severity: &MaintenanceSeverity) -> Result<Value, String> {
+    Ok(match severity {
+        MaintenanceSeverity::Info => Value::Enum(0, "INFO".into()),
+        MaintenanceSeverity::Degraded => Value::Enum(1, "DEGRADED".into()),
+        MaintenanceSeverity::Outage => Value::Enum(2, "OUTAGE".into()),
+        MaintenanceSeverity::NotImplemented => {
+            return Err(String::from("Not supported maintenance severity"))
+        }
+    })
+}
+
+fn get_maintenance_severity_enum(severity: &str) -> MaintenanceSeverity {
+    match severity {
+        "INFO" => MaintenanceSeverity::Info,
+        "DEGRADED" => MaintenanceSeverity::Degraded,
+        "OUTAGE" => MaintenanceSeverity::Outage,
+        _ => MaintenanceSeverity::NotImplemented,
+    }
+}
+
+/// Announces planned maintenance of a node. An empty `streams` list means all the
+/// streams served by the node are affected, a zero `duration_ms` cancels the
+/// previous notice of the node.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct MaintenanceNotice {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub node: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub streams: Vec<StreamName>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub start_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub duration_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub severity: MaintenanceSeverity,
+}
+
+message_builder!(MaintenanceNotice, MaintenanceNoticeBuilder {
+    required {
+        node: String,
+        start_ms: u64,
+        duration_ms: u64,
+        severity: MaintenanceSeverity,
+    }
+    optional {}
+    defaulted {
+        streams: Vec<StreamName>,
+    }
+});
+
+impl MaintenanceNotice {
+    pub fn new(
+        node: String,
+        streams: Vec<StreamName>,
+        start_ms: u64,
+        duration_ms: u64,
+        severity: MaintenanceSeverity,
+    ) -> Self {
+        MaintenanceNotice {
+            node,
+            streams,
+            start_ms,
+            duration_ms,
+            severity,
+        }
+    }
+
+    pub fn is_active(&self, now_ms: u64) -> bool {
+        now_ms >= self.start_ms && now_ms - self.start_ms < self.duration_ms
+    }
+
+    pub fn affects(&self, stream_name: StreamName) -> bool {
+        self.streams.is_empty() || self.streams.contains(&stream_name)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MaintenanceNotice {
+    #[new]
+    fn py_new(
+        node: String,
+        streams: Vec<StreamName>,
+        start_ms: u64,
+        duration_ms: u64,
+        severity: MaintenanceSeverity,
+    ) -> Self {
+        Self::new(node, streams, start_ms, duration_ms, severity)
+    }
+
+    #[pyo3(name = "is_active")]
+    fn py_is_active(&self, now_ms: u64) -> bool {
+        self.is_active(now_ms)
+    }
+
+    #[pyo3(name = "affects")]
+    fn py_affects(&self, stream_name: StreamName) -> bool {
+        self.affects(stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for MaintenanceNotice {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != MAINTENANCE_NOTICE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(node)), (_, Value::Array(streams)), (_, Value::Long(start_ms)), (_, Value::Long(duration_ms)), (_, Value::Enum(_, severity))] =>
+                {
+                    let stream_names: Vec<_> = streams
+                        .iter()
+                        .filter_map(|s| match s {
+                            Value::Bytes(s) => {
+                                let mut sn = StreamName::default();
+                                fill_byte_array(&mut sn, s);
+                                Some(sn)
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    if stream_names.len() < streams.len() {
+                        warn!("Not all stream names are parsed well.");
+                        return None;
+                    }
+                    Some(MaintenanceNotice {
+                        node: node.clone(),
+                        streams: stream_names,
+                        start_ms: *start_ms as u64,
+                        duration_ms: *duration_ms as u64,
+                        severity: get_maintenance_severity_enum(severity.as_str()),
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to MaintenanceNotice");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for MaintenanceNotice {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(MAINTENANCE_NOTICE_SCHEMA)?;
+        obj.put("node", Value::String(self.node.clone()));
+        obj.put(
+            "streams",
+            Value::Array(
+                self.streams
+                    .iter()
+                    .map(|s| Value::Bytes(s.to_vec()))
+                    .collect(),
+            ),
+        );
+        obj.put("start_ms", Value::Long(self.start_ms as i64));
+        obj.put("duration_ms", Value::Long(self.duration_ms as i64));
+        obj.put(
+            "severity",
+            get_maintenance_severity_avro(&self.severity).ok()?,
+        );
+        Some(ProtocolMessage {
+            schema: String::from(MAINTENANCE_NOTICE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let notice = MaintenanceNotice::new(
+            String::from("store-1"),
+            vec![stream_name],
+            1000,
+            500,
+            MaintenanceSeverity::Outage,
+        );
+        let notice_serialized = mb.save_from_avro(notice.save(&mb).unwrap()).unwrap();
+        let notice_envelope = mb.load_to_avro(notice_serialized).unwrap();
+        let new_notice = MaintenanceNotice::load(&notice_envelope).unwrap();
+        assert_eq!(notice, new_notice);
+
+        assert!(!notice.is_active(999));
+        assert!(notice.is_active(1499));
+        assert!(!notice.is_active(1500));
+        assert!(notice.affects(stream_name));
+        assert!(!notice.affects(pack_stream_name(&Uuid::from_u128(1))));
+    }
+}
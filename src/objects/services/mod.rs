@@ -1,4 +1,14 @@
+pub mod auth;
+pub mod error_response;
 pub mod ffprobe;
+pub mod filmstrip;
+pub mod heartbeat;
+pub mod inference;
 pub mod keep_alive;
+pub mod maintenance;
 pub mod ping;
+pub mod slo_report;
+pub mod snapshot;
 pub mod storage;
+pub mod telemetry;
+pub mod transcode;
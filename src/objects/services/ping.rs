@@ -2,29 +2,40 @@ use crate::avro::{Builder, ProtocolMessage, PING_REQUEST_RESPONSE_SCHEMA};
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
 pub enum PingRequestResponseType {
     Request,
     Response,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct PingRequestResponse {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub topic: String,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub mtype: PingRequestResponseType,
 }
 
-#[pymethods]
+message_builder!(PingRequestResponse, PingRequestResponseBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        mtype: PingRequestResponseType,
+    }
+    optional {}
+    defaulted {}
+});
+
 impl PingRequestResponse {
-    #[new]
     pub fn new(
         request_id: i64,
         topic: String,
@@ -36,6 +47,15 @@ impl PingRequestResponse {
             mtype,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PingRequestResponse {
+    #[new]
+    fn py_new(request_id: i64, topic: String, mtype: PingRequestResponseType) -> Self {
+        Self::new(request_id, topic, mtype)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -85,7 +105,7 @@ impl FromProtocolMessage for PingRequestResponse {
 
 impl ToProtocolMessage for PingRequestResponse {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut object = mb.get_record(PING_REQUEST_RESPONSE_SCHEMA);
+        let mut object = mb.get_record(PING_REQUEST_RESPONSE_SCHEMA)?;
         object.put("request_id", Value::Long(self.request_id));
         object.put("topic", Value::String(self.topic.clone()));
         match self.mtype {
@@ -119,7 +139,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
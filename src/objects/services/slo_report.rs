@@ -0,0 +1,223 @@
+use crate::avro::{Builder, ProtocolMessage, SLO_REPORT_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct SloReport {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub service: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub window_start_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub window_end_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub availability: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub availability_target: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub latency_p95_ms: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub latency_target_ms: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub latency_samples: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub compliant: bool,
+}
+
+message_builder!(SloReport, SloReportBuilder {
+    required {
+        service: String,
+        window_start_ms: u64,
+        window_end_ms: u64,
+        availability: f64,
+        availability_target: f64,
+        latency_p95_ms: f64,
+        latency_target_ms: f64,
+        latency_samples: i64,
+    }
+    optional {}
+    defaulted {
+        compliant: bool,
+    }
+});
+
+impl SloReport {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        service: String,
+        window_start_ms: u64,
+        window_end_ms: u64,
+        availability: f64,
+        availability_target: f64,
+        latency_p95_ms: f64,
+        latency_target_ms: f64,
+        latency_samples: i64,
+        compliant: bool,
+    ) -> Self {
+        SloReport {
+            service,
+            window_start_ms,
+            window_end_ms,
+            availability,
+            availability_target,
+            latency_p95_ms,
+            latency_target_ms,
+            latency_samples,
+            compliant,
+        }
+    }
+
+    /// Share of the error budget left in the window, negative when it is exceeded.
+    pub fn error_budget_remaining(&self) -> f64 {
+        let budget = 1.0 - self.availability_target;
+        if budget <= 0.0 {
+            return if self.availability >= self.availability_target {
+                1.0
+            } else {
+                -1.0
+            };
+        }
+        1.0 - (1.0 - self.availability) / budget
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SloReport {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        service: String,
+        window_start_ms: u64,
+        window_end_ms: u64,
+        availability: f64,
+        availability_target: f64,
+        latency_p95_ms: f64,
+        latency_target_ms: f64,
+        latency_samples: i64,
+        compliant: bool,
+    ) -> Self {
+        Self::new(
+            service,
+            window_start_ms,
+            window_end_ms,
+            availability,
+            availability_target,
+            latency_p95_ms,
+            latency_target_ms,
+            latency_samples,
+            compliant,
+        )
+    }
+
+    #[pyo3(name = "error_budget_remaining")]
+    fn py_error_budget_remaining(&self) -> f64 {
+        self.error_budget_remaining()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for SloReport {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SLO_REPORT_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(service)), (_, Value::Long(window_start_ms)), (_, Value::Long(window_end_ms)), (_, Value::Double(availability)), (_, Value::Double(availability_target)), (_, Value::Double(latency_p95_ms)), (_, Value::Double(latency_target_ms)), (_, Value::Long(latency_samples)), (_, Value::Boolean(compliant))] => {
+                    Some(SloReport {
+                        service: service.clone(),
+                        window_start_ms: *window_start_ms as u64,
+                        window_end_ms: *window_end_ms as u64,
+                        availability: *availability,
+                        availability_target: *availability_target,
+                        latency_p95_ms: *latency_p95_ms,
+                        latency_target_ms: *latency_target_ms,
+                        latency_samples: *latency_samples,
+                        compliant: *compliant,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to SloReport");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for SloReport {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut object = mb.get_record(SLO_REPORT_SCHEMA)?;
+        object.put("service", Value::String(self.service.clone()));
+        object.put("window_start_ms", Value::Long(self.window_start_ms as i64));
+        object.put("window_end_ms", Value::Long(self.window_end_ms as i64));
+        object.put("availability", Value::Double(self.availability));
+        object.put(
+            "availability_target",
+            Value::Double(self.availability_target),
+        );
+        object.put("latency_p95_ms", Value::Double(self.latency_p95_ms));
+        object.put("latency_target_ms", Value::Double(self.latency_target_ms));
+        object.put("latency_samples", Value::Long(self.latency_samples));
+        object.put("compliant", Value::Boolean(self.compliant));
+
+        Some(ProtocolMessage {
+            schema: String::from(SLO_REPORT_SCHEMA),
+            object: Value::from(object),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::slo_report::SloReport;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let report = SloReport::new(
+            String::from("storage"),
+            0,
+            60_000,
+            0.995,
+            0.99,
+            12.5,
+            50.0,
+            120,
+            true,
+        );
+
+        let serialized = mb.save_from_avro(report.save(&mb).unwrap()).unwrap();
+        let envelope = mb.load_to_avro(serialized).unwrap();
+        let new_report = SloReport::load(&envelope).unwrap();
+
+        assert_eq!(report, new_report);
+        assert!((new_report.error_budget_remaining() - 0.5).abs() < 1e-9);
+    }
+}
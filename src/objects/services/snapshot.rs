@@ -0,0 +1,340 @@
+use crate::avro::{
+    Builder, ProtocolMessage, SERVICES_SNAPSHOT_REQUEST_SCHEMA, SERVICES_SNAPSHOT_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, ElementType, Payload, Unit};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum SnapshotFormat {
+    Jpeg,
+    Png,
+    Webp,
+    NotImplemented,
+}
+
+fn get_snapshot_format_avro(format: &SnapshotFormat) -> Result<Value, String> {
+    Ok(match format {
+        SnapshotFormat::Jpeg => Value::Enum(0, "JPEG".into()),
+        SnapshotFormat::Png => Value::Enum(1, "PNG".into()),
+        SnapshotFormat::Webp => Value::Enum(2, "WEBP".into()),
+        SnapshotFormat::NotImplemented => {
+            return Err(String::from("Not supported snapshot format"))
+        }
+    })
+}
+
+fn get_snapshot_format_enum(format: &str) -> SnapshotFormat {
+    match format {
+        "JPEG" => SnapshotFormat::Jpeg,
+        "PNG" => SnapshotFormat::Png,
+        "WEBP" => SnapshotFormat::Webp,
+        _ => SnapshotFormat::NotImplemented,
+    }
+}
+
+/// Asks for a still of the frame stored in `element` of the unit, e.g. for a stream
+/// tile. The image is scaled down to fit `max_dimension` on its longer side, `0`
+/// keeps the original size.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct SnapshotRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub element: ElementType,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub format: SnapshotFormat,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_dimension: i32,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(SnapshotRequest, SnapshotRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        element: ElementType,
+        format: SnapshotFormat,
+        max_dimension: i32,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl SnapshotRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        element: ElementType,
+        format: SnapshotFormat,
+        max_dimension: i32,
+    ) -> Self {
+        SnapshotRequest {
+            request_id,
+            topic,
+            stream_unit,
+            element,
+            format,
+            max_dimension,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SnapshotRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        element: ElementType,
+        format: SnapshotFormat,
+        max_dimension: i32,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_unit,
+            element,
+            format,
+            max_dimension,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// The encoded still, its attributes carry the image properties, e.g. the size.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct SnapshotResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub image: Payload,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub produced_ms: u64,
+}
+
+message_builder!(SnapshotResponse, SnapshotResponseBuilder {
+    required {
+        request_id: i64,
+        image: Payload,
+        produced_ms: u64,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl SnapshotResponse {
+    pub fn new(request_id: i64, image: Payload, produced_ms: u64) -> Self {
+        SnapshotResponse {
+            request_id,
+            image,
+            produced_ms,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SnapshotResponse {
+    #[new]
+    fn py_new(request_id: i64, image: Payload, produced_ms: u64) -> Self {
+        Self::new(request_id, image, produced_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for SnapshotRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_SNAPSHOT_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("element", Value::Int(self.element.into()));
+        obj.put("format", get_snapshot_format_avro(&self.format).ok()?);
+        obj.put("max_dimension", Value::Int(self.max_dimension));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_SNAPSHOT_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for SnapshotRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_SNAPSHOT_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, stream_unit), (_, Value::Int(element)), (_, Value::Enum(_, format)), (_, Value::Int(max_dimension)), (_, deadline_ms)] => {
+                    match Unit::from_avro(stream_unit) {
+                        Some(stream_unit) => Some(SnapshotRequest {
+                            request_id: *request_id,
+                            topic: topic.clone(),
+                            stream_unit,
+                            element: *element as ElementType,
+                            format: get_snapshot_format_enum(format),
+                            max_dimension: *max_dimension,
+                            deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to Unit");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to SnapshotRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for SnapshotResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_SNAPSHOT_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("image", self.image.to_avro_record().ok()?);
+        obj.put("produced_ms", Value::Long(self.produced_ms as i64));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_SNAPSHOT_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for SnapshotResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_SNAPSHOT_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, image), (_, Value::Long(produced_ms))] => {
+                    match Payload::from_avro(image) {
+                        Some(image) => Some(SnapshotResponse {
+                            request_id: *request_id,
+                            image,
+                            produced_ms: *produced_ms as u64,
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to Payload");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to SnapshotResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::snapshot::{SnapshotFormat, SnapshotRequest, SnapshotResponse};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, Payload, Unit};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_unit = Unit::new(
+            pack_stream_name(&stream_uuid).to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            100,
+        );
+
+        let req = SnapshotRequest::new(
+            1,
+            String::from("response"),
+            stream_unit,
+            0,
+            SnapshotFormat::Webp,
+            320,
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(SnapshotRequest::load(&req_envelope).unwrap(), req);
+
+        let rep = SnapshotResponse::new(
+            1,
+            Payload::new(
+                vec![0x52, 0x49, 0x46, 0x46],
+                HashMap::from([("width".into(), "320".into())]),
+            ),
+            1_700_000_000_000,
+        );
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(SnapshotResponse::load(&rep_envelope).unwrap(), rep);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::avro::{Builder, ProtocolMessage, BACKFILL_REQUEST_SCHEMA, BACKFILL_RESPONSE_SCHEMA};
+use crate::objects::services::storage::unit_bitmap::UnitBitmapResponse;
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, UnitRange};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum BackfillStatus {
+    Accepted,
+    Partial,
+    Unavailable,
+    NotImplemented,
+}
+
+fn get_backfill_status_avro(status: &BackfillStatus) -> Result<Value, String> {
+    Ok(match status {
+        BackfillStatus::Accepted => Value::Enum(0, "ACCEPTED".into()),
+        BackfillStatus::Partial => Value::Enum(1, "PARTIAL".into()),
+        BackfillStatus::Unavailable => Value::Enum(2, "UNAVAILABLE".into()),
+        BackfillStatus::NotImplemented => {
+            return Err(String::from("Not supported backfill status"))
+        }
+    })
+}
+
+fn get_backfill_status_enum(status: &str) -> BackfillStatus {
+    match status {
+        "ACCEPTED" => BackfillStatus::Accepted,
+        "PARTIAL" => BackfillStatus::Partial,
+        "UNAVAILABLE" => BackfillStatus::Unavailable,
+        _ => BackfillStatus::NotImplemented,
+    }
+}
+
+fn ranges_from_avro(ranges: &[Value]) -> Option<Vec<UnitRange>> {
+    let parsed: Vec<_> = ranges.iter().filter_map(UnitRange::from_avro).collect();
+    if parsed.len() < ranges.len() {
+        warn!("Not all unit range records are parsed well.");
+        None
+    } else {
+        Some(parsed)
+    }
+}
+
+fn ranges_to_avro(ranges: &[UnitRange]) -> Result<Value, String> {
+    Ok(Value::Array(
+        ranges
+            .iter()
+            .map(UnitRange::to_avro_record)
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+/// Asks an edge recorder to upload the units of `ranges` again, e.g. the gaps found
+/// with a unit bitmap request.
+///
+/// The recorder answers with a [`BackfillResponse`] and re-sends the units it still
+/// keeps as regular unit element messages.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct BackfillRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub ranges: Vec<UnitRange>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(BackfillRequest, BackfillRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {
+        ranges: Vec<UnitRange>,
+    }
+});
+
+impl BackfillRequest {
+    pub fn new(request_id: i64, topic: String, ranges: Vec<UnitRange>) -> Self {
+        BackfillRequest {
+            request_id,
+            topic,
+            ranges,
+            deadline_ms: None,
+        }
+    }
+
+    /// Number of units the ranges cover.
+    pub fn missing_units(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|r| (i128::from(r.to_unit) - i128::from(r.from_unit) + 1).max(0) as u64)
+            .sum()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BackfillRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, ranges: Vec<UnitRange>) -> Self {
+        Self::new(request_id, topic, ranges)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_bitmap")]
+    fn py_from_bitmap(request_id: i64, topic: String, bitmap: UnitBitmapResponse) -> Self {
+        Self::from_bitmap(request_id, topic, &bitmap)
+    }
+
+    #[pyo3(name = "missing_units")]
+    fn py_missing_units(&self) -> u64 {
+        self.missing_units()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl BackfillRequest {
+    /// Requests the units missing from the range of the bitmap response.
+    pub fn from_bitmap(request_id: i64, topic: String, bitmap: &UnitBitmapResponse) -> Self {
+        let range = |from_unit, to_unit| UnitRange {
+            stream_name: bitmap.stream_name,
+            track_name: bitmap.track_info.track_name,
+            track_type: bitmap.track_info.track_type,
+            from_unit,
+            to_unit,
+        };
+        let mut ranges = Vec::new();
+        let mut next = bitmap.from_unit;
+        for (first, last) in bitmap.segments() {
+            if first > next {
+                ranges.push(range(next, first - 1));
+            }
+            next = last.saturating_add(1);
+        }
+        if next <= bitmap.to_unit {
+            ranges.push(range(next, bitmap.to_unit));
+        }
+        Self::new(request_id, topic, ranges)
+    }
+}
+
+/// `ranges` are the ones the recorder is going to re-send: all of the requested
+/// for `Accepted`, the ones it still keeps for `Partial` and none for `Unavailable`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct BackfillResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub status: BackfillStatus,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub ranges: Vec<UnitRange>,
+}
+
+message_builder!(BackfillResponse, BackfillResponseBuilder {
+    required {
+        request_id: i64,
+        status: BackfillStatus,
+    }
+    optional {}
+    defaulted {
+        ranges: Vec<UnitRange>,
+    }
+});
+
+impl BackfillResponse {
+    pub fn new(request_id: i64, status: BackfillStatus, ranges: Vec<UnitRange>) -> Self {
+        BackfillResponse {
+            request_id,
+            status,
+            ranges,
+        }
+    }
+
+    pub fn unavailable(request_id: i64) -> Self {
+        Self::new(request_id, BackfillStatus::Unavailable, vec![])
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BackfillResponse {
+    #[new]
+    fn py_new(request_id: i64, status: BackfillStatus, ranges: Vec<UnitRange>) -> Self {
+        Self::new(request_id, status, ranges)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "unavailable")]
+    fn py_unavailable(request_id: i64) -> Self {
+        Self::unavailable(request_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for BackfillRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(BACKFILL_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("ranges", ranges_to_avro(&self.ranges).ok()?);
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(BACKFILL_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for BackfillRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != BACKFILL_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Array(ranges)), (_, deadline_ms)] => {
+                    Some(BackfillRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        ranges: ranges_from_avro(ranges)?,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to BackfillRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for BackfillResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(BACKFILL_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("status", get_backfill_status_avro(&self.status).ok()?);
+        obj.put("ranges", ranges_to_avro(&self.ranges).ok()?);
+        Some(ProtocolMessage {
+            schema: String::from(BACKFILL_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for BackfillResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != BACKFILL_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Enum(_, status)), (_, Value::Array(ranges))] => {
+                    Some(BackfillResponse {
+                        request_id: *request_id,
+                        status: get_backfill_status_enum(status),
+                        ranges: ranges_from_avro(ranges)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to BackfillResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::backfill::{
+        BackfillRequest, BackfillResponse, BackfillStatus,
+    };
+    use crate::objects::services::storage::unit_bitmap::UnitBitmapResponse;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let track_info = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+        let bitmap = UnitBitmapResponse::from_units(
+            1,
+            pack_stream_name(&stream_uuid),
+            track_info,
+            1000,
+            1999,
+            (1010..1500).chain(1600..1990).collect(),
+        );
+
+        let req = BackfillRequest::from_bitmap(2, String::from("response"), &bitmap);
+        let gaps: Vec<_> = req
+            .ranges
+            .iter()
+            .map(|r| (r.from_unit, r.to_unit))
+            .collect();
+        assert_eq!(gaps, vec![(1000, 1009), (1500, 1599), (1990, 1999)]);
+        assert_eq!(req.missing_units(), 120);
+        assert!(req
+            .ranges
+            .iter()
+            .all(|r| r.track_name == track_info.track_name));
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(BackfillRequest::load(&req_envelope).unwrap(), req);
+
+        let rep = BackfillResponse::new(2, BackfillStatus::Partial, req.ranges[1..].to_vec());
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(BackfillResponse::load(&rep_envelope).unwrap(), rep);
+    }
+}
@@ -0,0 +1,495 @@
+use crate::avro::{
+    Builder, ProtocolMessage, ENCODING_PROFILE_ACK_SCHEMA, ENCODING_PROFILE_OFFER_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    get_track_type_enum, optional_u64_from_avro, optional_u64_to_avro,
+    track_type_literal_to_track_type, StreamName, TrackInfo, TrackType,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// A codec the platform accepts for the tracks of a type, limited to `profiles`
+/// when they are given, e.g. `h264` with `["baseline"]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct EncodingProfile {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_type: TrackType,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub codec: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub profiles: Vec<String>,
+}
+
+impl EncodingProfile {
+    pub fn new(track_type: TrackType, codec: String, profiles: Vec<String>) -> Self {
+        EncodingProfile {
+            track_type,
+            codec,
+            profiles,
+        }
+    }
+
+    /// Codec and profile names are compared ignoring the case.
+    pub fn accepts(&self, track_type: TrackType, codec: &str, profile: Option<&str>) -> bool {
+        self.track_type == track_type
+            && self.codec.eq_ignore_ascii_case(codec)
+            && (self.profiles.is_empty()
+                || profile
+                    .map(|profile| {
+                        self.profiles
+                            .iter()
+                            .any(|p| p.eq_ignore_ascii_case(profile))
+                    })
+                    .unwrap_or(false))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl EncodingProfile {
+    #[new]
+    fn py_new(track_type: TrackType, codec: String, profiles: Vec<String>) -> Self {
+        Self::new(track_type, codec, profiles)
+    }
+
+    #[pyo3(name = "accepts")]
+    fn py_accepts(&self, track_type: TrackType, codec: &str, profile: Option<&str>) -> bool {
+        self.accepts(track_type, codec, profile)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl EncodingProfile {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("track_type".into(), get_track_type_enum(&self.track_type)?),
+            ("codec".into(), Value::String(self.codec.clone())),
+            (
+                "profiles".into(),
+                Value::Array(
+                    self.profiles
+                        .iter()
+                        .map(|p| Value::String(p.clone()))
+                        .collect(),
+                ),
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Enum(_, track_type)), (_, Value::String(codec)), (_, Value::Array(profiles))] =>
+                {
+                    let profiles: Option<Vec<_>> = profiles
+                        .iter()
+                        .map(|p| match p {
+                            Value::String(p) => Some(p.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    Some(EncodingProfile {
+                        track_type: track_type_literal_to_track_type(track_type),
+                        codec: codec.clone(),
+                        profiles: profiles?,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Sent by an ingest gateway before a producer uploads a stream: the codecs and
+/// profiles the platform will accept, track types which have none listed are not
+/// accepted at all.
+///
+/// The producer answers with an [`EncodingProfileAck`] to `topic`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct EncodingProfileOffer {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub profiles: Vec<EncodingProfile>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(EncodingProfileOffer, EncodingProfileOfferBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {
+        profiles: Vec<EncodingProfile>,
+    }
+});
+
+impl EncodingProfileOffer {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        profiles: Vec<EncodingProfile>,
+    ) -> Self {
+        EncodingProfileOffer {
+            request_id,
+            topic,
+            stream_name,
+            profiles,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn accepts(&self, track_type: TrackType, codec: &str, profile: Option<&str>) -> bool {
+        self.profiles
+            .iter()
+            .any(|p| p.accepts(track_type, codec, profile))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl EncodingProfileOffer {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        profiles: Vec<EncodingProfile>,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, profiles)
+    }
+
+    #[pyo3(name = "accepts")]
+    fn py_accepts(&self, track_type: TrackType, codec: &str, profile: Option<&str>) -> bool {
+        self.accepts(track_type, codec, profile)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum EncodingAckStatus {
+    Accepted,
+    Partial,
+    Rejected,
+    NotImplemented,
+}
+
+fn get_encoding_ack_status_avro(status: &EncodingAckStatus) -> Result<Value, String> {
+    Ok(match status {
+        EncodingAckStatus::Accepted => Value::Enum(0, "ACCEPTED".into()),
+        EncodingAckStatus::Partial => Value::Enum(1, "PARTIAL".into()),
+        EncodingAckStatus::Rejected => Value::Enum(2, "REJECTED".into()),
+        EncodingAckStatus::NotImplemented => {
+            return Err(String::from("Not supported encoding ack status"))
+        }
+    })
+}
+
+fn get_encoding_ack_status_enum(status: &str) -> EncodingAckStatus {
+    match status {
+        "ACCEPTED" => EncodingAckStatus::Accepted,
+        "PARTIAL" => EncodingAckStatus::Partial,
+        "REJECTED" => EncodingAckStatus::Rejected,
+        _ => EncodingAckStatus::NotImplemented,
+    }
+}
+
+/// The producer's answer to an offer: `Accepted` when all of its tracks are encoded
+/// in an accepted profile, otherwise the tracks it can't re-encode are listed in
+/// `unsupported_tracks` and won't be uploaded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct EncodingProfileAck {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub status: EncodingAckStatus,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unsupported_tracks: Vec<TrackInfo>,
+}
+
+message_builder!(EncodingProfileAck, EncodingProfileAckBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        status: EncodingAckStatus,
+    }
+    optional {}
+    defaulted {
+        unsupported_tracks: Vec<TrackInfo>,
+    }
+});
+
+impl EncodingProfileAck {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: EncodingAckStatus,
+        unsupported_tracks: Vec<TrackInfo>,
+    ) -> Self {
+        EncodingProfileAck {
+            request_id,
+            stream_name,
+            status,
+            unsupported_tracks,
+        }
+    }
+
+    pub fn accepted(request_id: i64, stream_name: StreamName) -> Self {
+        Self::new(request_id, stream_name, EncodingAckStatus::Accepted, vec![])
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl EncodingProfileAck {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: EncodingAckStatus,
+        unsupported_tracks: Vec<TrackInfo>,
+    ) -> Self {
+        Self::new(request_id, stream_name, status, unsupported_tracks)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "accepted")]
+    fn py_accepted(request_id: i64, stream_name: StreamName) -> Self {
+        Self::accepted(request_id, stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for EncodingProfileOffer {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(ENCODING_PROFILE_OFFER_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        let profiles: Vec<Value> = self
+            .profiles
+            .iter()
+            .map(EncodingProfile::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("profiles", Value::Array(profiles));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(ENCODING_PROFILE_OFFER_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for EncodingProfileOffer {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != ENCODING_PROFILE_OFFER_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Array(profiles)), (_, deadline_ms)] =>
+                {
+                    let parsed: Vec<_> = profiles
+                        .iter()
+                        .filter_map(EncodingProfile::from_avro)
+                        .collect();
+                    if parsed.len() < profiles.len() {
+                        warn!("Not all encoding profile records are parsed well.");
+                        return None;
+                    }
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(EncodingProfileOffer {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        profiles: parsed,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to EncodingProfileOffer");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for EncodingProfileAck {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(ENCODING_PROFILE_ACK_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("status", get_encoding_ack_status_avro(&self.status).ok()?);
+        let tracks: Vec<Value> = self
+            .unsupported_tracks
+            .iter()
+            .map(TrackInfo::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("unsupported_tracks", Value::Array(tracks));
+        Some(ProtocolMessage {
+            schema: String::from(ENCODING_PROFILE_ACK_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for EncodingProfileAck {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != ENCODING_PROFILE_ACK_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Enum(_, status)), (_, Value::Array(tracks))] =>
+                {
+                    let parsed: Vec<_> = tracks.iter().filter_map(TrackInfo::from_avro).collect();
+                    if parsed.len() < tracks.len() {
+                        warn!("Not all track info records are parsed well.");
+                        return None;
+                    }
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(EncodingProfileAck {
+                        request_id: *request_id,
+                        stream_name: sn,
+                        status: get_encoding_ack_status_enum(status),
+                        unsupported_tracks: parsed,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to EncodingProfileAck");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::encoding_profile::{
+        EncodingAckStatus, EncodingProfile, EncodingProfileAck, EncodingProfileOffer,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let offer = EncodingProfileOffer::new(
+            1,
+            String::from("response"),
+            stream_name,
+            vec![
+                EncodingProfile::new(
+                    TrackType::Video,
+                    String::from("h264"),
+                    vec![String::from("baseline")],
+                ),
+                EncodingProfile::new(TrackType::Audio, String::from("aac"), vec![]),
+            ],
+        );
+        assert!(offer.accepts(TrackType::Video, "H264", Some("Baseline")));
+        assert!(!offer.accepts(TrackType::Video, "h264", Some("high")));
+        assert!(!offer.accepts(TrackType::Video, "h264", None));
+        assert!(offer.accepts(TrackType::Audio, "aac", None));
+        assert!(!offer.accepts(TrackType::Meta, "json", None));
+
+        let offer_serialized = mb.save_from_avro(offer.save(&mb).unwrap()).unwrap();
+        let offer_envelope = mb.load_to_avro(offer_serialized).unwrap();
+        assert_eq!(EncodingProfileOffer::load(&offer_envelope).unwrap(), offer);
+
+        let ack = EncodingProfileAck::new(
+            1,
+            stream_name,
+            EncodingAckStatus::Partial,
+            vec![TrackInfo::new(
+                TrackType::Video,
+                pack_track_name(&String::from("4k")).unwrap(),
+            )],
+        );
+        let ack_serialized = mb.save_from_avro(ack.save(&mb).unwrap()).unwrap();
+        let ack_envelope = mb.load_to_avro(ack_serialized).unwrap();
+        assert_eq!(EncodingProfileAck::load(&ack_envelope).unwrap(), ack);
+    }
+}
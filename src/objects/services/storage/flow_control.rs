@@ -0,0 +1,242 @@
+use crate::avro::{
+    Builder, ProtocolMessage, FLOW_CONTROL_GRANT_SCHEMA, FLOW_CONTROL_REQUEST_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{StreamName, TrackInfo};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Allows the producer to send `credits` more unit elements of the track.
+///
+/// Grants are issued by the receiver and add up, the producer stops sending once
+/// the credits are spent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct FlowControlGrant {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub credits: i64,
+}
+
+message_builder!(FlowControlGrant, FlowControlGrantBuilder {
+    required {
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        credits: i64,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl FlowControlGrant {
+    pub fn new(stream_name: StreamName, track_info: TrackInfo, credits: i64) -> Self {
+        FlowControlGrant {
+            stream_name,
+            track_info,
+            credits,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FlowControlGrant {
+    #[new]
+    fn py_new(stream_name: StreamName, track_info: TrackInfo, credits: i64) -> Self {
+        Self::new(stream_name, track_info, credits)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Sent by a producer which ran out of credits, `pending` is the number of unit
+/// elements it has to send.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct FlowControlRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub pending: i64,
+}
+
+message_builder!(FlowControlRequest, FlowControlRequestBuilder {
+    required {
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        pending: i64,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl FlowControlRequest {
+    pub fn new(stream_name: StreamName, track_info: TrackInfo, pending: i64) -> Self {
+        FlowControlRequest {
+            stream_name,
+            track_info,
+            pending,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FlowControlRequest {
+    #[new]
+    fn py_new(stream_name: StreamName, track_info: TrackInfo, pending: i64) -> Self {
+        Self::new(stream_name, track_info, pending)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+fn from_avro_fields(fields: &[(String, Value)]) -> Option<(StreamName, TrackInfo, i64)> {
+    match fields {
+        [(_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(count))] => {
+            let mut sn = StreamName::default();
+            fill_byte_array(&mut sn, stream_name);
+            Some((sn, TrackInfo::from_avro(track_info)?, *count))
+        }
+        _ => None,
+    }
+}
+
+impl ToProtocolMessage for FlowControlGrant {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(FLOW_CONTROL_GRANT_SCHEMA)?;
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("credits", Value::Long(self.credits));
+        Some(ProtocolMessage {
+            schema: String::from(FLOW_CONTROL_GRANT_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for FlowControlGrant {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != FLOW_CONTROL_GRANT_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match from_avro_fields(fields) {
+                Some((stream_name, track_info, credits)) => {
+                    Some(FlowControlGrant::new(stream_name, track_info, credits))
+                }
+                None => {
+                    warn!("Unable to match AVRO Record to to FlowControlGrant");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for FlowControlRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(FLOW_CONTROL_REQUEST_SCHEMA)?;
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("pending", Value::Long(self.pending));
+        Some(ProtocolMessage {
+            schema: String::from(FLOW_CONTROL_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for FlowControlRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != FLOW_CONTROL_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match from_avro_fields(fields) {
+                Some((stream_name, track_info, pending)) => {
+                    Some(FlowControlRequest::new(stream_name, track_info, pending))
+                }
+                None => {
+                    warn!("Unable to match AVRO Record to to FlowControlRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let track_info = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+
+        let grant = FlowControlGrant::new(stream_name, track_info, 64);
+        let grant_serialized = mb.save_from_avro(grant.save(&mb).unwrap()).unwrap();
+        let grant_envelope = mb.load_to_avro(grant_serialized).unwrap();
+        assert_eq!(FlowControlGrant::load(&grant_envelope).unwrap(), grant);
+        assert!(FlowControlRequest::load(&grant_envelope).is_none());
+
+        let req = FlowControlRequest::new(stream_name, track_info, 10);
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(FlowControlRequest::load(&req_envelope).unwrap(), req);
+    }
+}
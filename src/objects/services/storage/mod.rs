@@ -1,5 +1,24 @@
+pub mod backfill;
+pub mod encoding_profile;
+pub mod flow_control;
+pub mod notify_cadence;
+pub mod notify_digest;
 pub mod notify_message;
+pub mod retention;
+pub mod storage_stats;
+pub mod stream_alias;
+pub mod stream_deletion;
+pub mod stream_end_message;
+pub mod stream_lifecycle_message;
+pub mod stream_multi_track_unit_elements;
+pub mod stream_replay;
+pub mod stream_track_keyframes;
 pub mod stream_track_unit_elements;
 pub mod stream_track_units;
+pub mod stream_track_units_by_time;
 pub mod stream_tracks;
+pub mod streams_discovery;
+pub mod unit_bitmap;
+pub mod unit_element_ack_message;
+pub mod unit_element_chunk_message;
 pub mod unit_element_message;
@@ -0,0 +1,207 @@
+use crate::avro::{Builder, ProtocolMessage, NOTIFY_CADENCE_CONTROL_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Asks the producer of a stream to change how often it sends notify messages and
+/// how long the units it cuts are, e.g. 10 second units for an archive-only stream.
+///
+/// Applies to one track or to all of them when `track_info` isn't set; settings
+/// which aren't set are kept as they are. The producer picks the change up from
+/// the next unit and confirms it implicitly with the notifications that follow.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct NotifyCadenceControl {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: Option<TrackInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub notify_interval_ms: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit_duration_ms: Option<u64>,
+}
+
+message_builder!(NotifyCadenceControl, NotifyCadenceControlBuilder {
+    required {
+        stream_name: StreamName,
+    }
+    optional {
+        track_info: TrackInfo,
+        notify_interval_ms: u64,
+        unit_duration_ms: u64,
+    }
+    defaulted {}
+});
+
+impl NotifyCadenceControl {
+    pub fn new(
+        stream_name: StreamName,
+        track_info: Option<TrackInfo>,
+        notify_interval_ms: Option<u64>,
+        unit_duration_ms: Option<u64>,
+    ) -> Self {
+        NotifyCadenceControl {
+            stream_name,
+            track_info,
+            notify_interval_ms,
+            unit_duration_ms,
+        }
+    }
+
+    pub fn applies_to(&self, stream_name: StreamName, track_info: TrackInfo) -> bool {
+        self.stream_name == stream_name && self.track_info.map(|t| t == track_info).unwrap_or(true)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NotifyCadenceControl {
+    #[new]
+    fn py_new(
+        stream_name: StreamName,
+        track_info: Option<TrackInfo>,
+        notify_interval_ms: Option<u64>,
+        unit_duration_ms: Option<u64>,
+    ) -> Self {
+        Self::new(
+            stream_name,
+            track_info,
+            notify_interval_ms,
+            unit_duration_ms,
+        )
+    }
+
+    #[pyo3(name = "applies_to")]
+    fn py_applies_to(&self, stream_name: StreamName, track_info: TrackInfo) -> bool {
+        self.applies_to(stream_name, track_info)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for NotifyCadenceControl {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(NOTIFY_CADENCE_CONTROL_SCHEMA)?;
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "track",
+            Value::Union(Box::new(match &self.track_info {
+                Some(track_info) => track_info.to_avro_record().ok()?,
+                None => Value::Null,
+            })),
+        );
+        obj.put(
+            "notify_interval_ms",
+            optional_u64_to_avro(&self.notify_interval_ms),
+        );
+        obj.put(
+            "unit_duration_ms",
+            optional_u64_to_avro(&self.unit_duration_ms),
+        );
+        Some(ProtocolMessage {
+            schema: String::from(NOTIFY_CADENCE_CONTROL_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for NotifyCadenceControl {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != NOTIFY_CADENCE_CONTROL_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Union(track_info)), (_, notify_interval_ms), (_, unit_duration_ms)] =>
+                {
+                    let track_info = match track_info.as_ref() {
+                        Value::Null => None,
+                        track_info => Some(TrackInfo::from_avro(track_info)?),
+                    };
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(NotifyCadenceControl {
+                        stream_name: sn,
+                        track_info,
+                        notify_interval_ms: optional_u64_from_avro(notify_interval_ms)?,
+                        unit_duration_ms: optional_u64_from_avro(unit_duration_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to NotifyCadenceControl");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{default_priority, Builder, NOTIFY_CADENCE_CONTROL_SCHEMA};
+    use crate::objects::services::storage::notify_cadence::NotifyCadenceControl;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{
+        pack_stream_name, pack_track_name, MessagePriority, TrackInfo, TrackType,
+    };
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let video = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+        let meta = TrackInfo::new(TrackType::Meta, video.track_name);
+
+        let control = NotifyCadenceControl::new(stream_name, Some(video), None, Some(10_000));
+        assert!(control.applies_to(stream_name, video));
+        assert!(!control.applies_to(stream_name, meta));
+        let control_serialized = mb.save_from_avro(control.save(&mb).unwrap()).unwrap();
+        let control_envelope = mb.load_to_avro(control_serialized).unwrap();
+        assert_eq!(
+            NotifyCadenceControl::load(&control_envelope).unwrap(),
+            control
+        );
+
+        let control = NotifyCadenceControl::new(stream_name, None, Some(5_000), None);
+        assert!(control.applies_to(stream_name, meta));
+        let control_serialized = mb.save_from_avro(control.save(&mb).unwrap()).unwrap();
+        let control_envelope = mb.load_to_avro(control_serialized).unwrap();
+        assert_eq!(
+            NotifyCadenceControl::load(&control_envelope).unwrap(),
+            control
+        );
+
+        assert_eq!(
+            default_priority(NOTIFY_CADENCE_CONTROL_SCHEMA),
+            MessagePriority::Control
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use crate::avro::{
+    Builder, ProtocolMessage, NOTIFY_DIGEST_REQUEST_SCHEMA, NOTIFY_DIGEST_RESPONSE_SCHEMA,
+};
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_u64_from_avro, optional_u64_to_avro, NotifyTypeImpl, StreamName, TrackInfo,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// What was produced on a track since the requested position.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TrackDigest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub first_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub units_added: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub notifications: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub first_saved_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_saved_ms: u64,
+}
+
+impl TrackDigest {
+    pub fn new(
+        track_info: TrackInfo,
+        first_unit: i64,
+        last_unit: i64,
+        units_added: i64,
+        notifications: i64,
+        first_saved_ms: u64,
+        last_saved_ms: u64,
+    ) -> Self {
+        TrackDigest {
+            track_info,
+            first_unit,
+            last_unit,
+            units_added,
+            notifications,
+            first_saved_ms,
+            last_saved_ms,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TrackDigest {
+    #[new]
+    fn py_new(
+        track_info: TrackInfo,
+        first_unit: i64,
+        last_unit: i64,
+        units_added: i64,
+        notifications: i64,
+        first_saved_ms: u64,
+        last_saved_ms: u64,
+    ) -> Self {
+        Self::new(
+            track_info,
+            first_unit,
+            last_unit,
+            units_added,
+            notifications,
+            first_saved_ms,
+            last_saved_ms,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl TrackDigest {
+    /// Folds the notifications of a stream saved after `since_ms` into per-track
+    /// digests, ordered by the track.
+    pub fn summarize(
+        stream_name: &StreamName,
+        since_ms: u64,
+        messages: &[NotifyMessage],
+    ) -> Vec<TrackDigest> {
+        let mut digests: HashMap<TrackInfo, TrackDigest> = HashMap::default();
+        for m in messages
+            .iter()
+            .filter(|m| m.stream_unit.stream_name == *stream_name && m.saved_ms > since_ms)
+        {
+            let unit = m.stream_unit.unit;
+            let track_info = TrackInfo::new(m.stream_unit.track_type, m.stream_unit.track_name);
+            let digest = digests
+                .entry(track_info)
+                .or_insert_with(|| TrackDigest::new(track_info, unit, unit, 0, 0, m.saved_ms, 0));
+            digest.first_unit = digest.first_unit.min(unit);
+            digest.last_unit = digest.last_unit.max(unit);
+            if m.notify_type.obj == NotifyTypeImpl::New {
+                digest.units_added += 1;
+            }
+            digest.notifications += 1;
+            digest.first_saved_ms = digest.first_saved_ms.min(m.saved_ms);
+            digest.last_saved_ms = digest.last_saved_ms.max(m.saved_ms);
+        }
+        let mut digests: Vec<_> = digests.into_values().collect();
+        digests.sort_by_key(|d| (d.track_info.track_type as i32, d.track_info.track_name));
+        digests
+    }
+
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("track".into(), self.track_info.to_avro_record()?),
+            ("first_unit".into(), Value::Long(self.first_unit)),
+            ("last_unit".into(), Value::Long(self.last_unit)),
+            ("units_added".into(), Value::Long(self.units_added)),
+            ("notifications".into(), Value::Long(self.notifications)),
+            (
+                "first_saved_ms".into(),
+                Value::Long(self.first_saved_ms as i64),
+            ),
+            (
+                "last_saved_ms".into(),
+                Value::Long(self.last_saved_ms as i64),
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, track_info), (_, Value::Long(first_unit)), (_, Value::Long(last_unit)), (_, Value::Long(units_added)), (_, Value::Long(notifications)), (_, Value::Long(first_saved_ms)), (_, Value::Long(last_saved_ms))] => {
+                    Some(TrackDigest {
+                        track_info: TrackInfo::from_avro(track_info)?,
+                        first_unit: *first_unit,
+                        last_unit: *last_unit,
+                        units_added: *units_added,
+                        notifications: *notifications,
+                        first_saved_ms: *first_saved_ms as u64,
+                        last_saved_ms: *last_saved_ms as u64,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Asks the store for everything produced on a stream after `since_ms`, so a
+/// reconnecting consumer catches up without replaying the notifications.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct NotifyDigestRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub since_ms: u64,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(NotifyDigestRequest, NotifyDigestRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        since_ms: u64,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl NotifyDigestRequest {
+    pub fn new(request_id: i64, topic: String, stream_name: StreamName, since_ms: u64) -> Self {
+        NotifyDigestRequest {
+            request_id,
+            topic,
+            stream_name,
+            since_ms,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NotifyDigestRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, stream_name: StreamName, since_ms: u64) -> Self {
+        Self::new(request_id, topic, stream_name, since_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct NotifyDigestResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tracks: Vec<TrackDigest>,
+}
+
+message_builder!(NotifyDigestResponse, NotifyDigestResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+    }
+    optional {}
+    defaulted {
+        tracks: Vec<TrackDigest>,
+    }
+});
+
+impl NotifyDigestResponse {
+    pub fn new(request_id: i64, stream_name: StreamName, tracks: Vec<TrackDigest>) -> Self {
+        NotifyDigestResponse {
+            request_id,
+            stream_name,
+            tracks,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NotifyDigestResponse {
+    #[new]
+    fn py_new(request_id: i64, stream_name: StreamName, tracks: Vec<TrackDigest>) -> Self {
+        Self::new(request_id, stream_name, tracks)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for NotifyDigestRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(NOTIFY_DIGEST_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("since_ms", Value::Long(self.since_ms as i64));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(NOTIFY_DIGEST_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for NotifyDigestRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != NOTIFY_DIGEST_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Long(since_ms)), (_, deadline_ms)] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(NotifyDigestRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        since_ms: *since_ms as u64,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to NotifyDigestRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for NotifyDigestResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(NOTIFY_DIGEST_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        let tracks: Vec<Value> = self
+            .tracks
+            .iter()
+            .map(TrackDigest::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("tracks", Value::Array(tracks));
+        Some(ProtocolMessage {
+            schema: String::from(NOTIFY_DIGEST_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for NotifyDigestResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != NOTIFY_DIGEST_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Array(tracks))] =>
+                {
+                    let digests: Vec<_> =
+                        tracks.iter().filter_map(TrackDigest::from_avro).collect();
+                    if digests.len() < tracks.len() {
+                        warn!("Not all track digest records are parsed well.");
+                        None
+                    } else {
+                        let mut sn = StreamName::default();
+                        fill_byte_array(&mut sn, stream_name);
+                        Some(NotifyDigestResponse {
+                            request_id: *request_id,
+                            stream_name: sn,
+                            tracks: digests,
+                        })
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to NotifyDigestResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::notify_digest::{
+        NotifyDigestRequest, NotifyDigestResponse, TrackDigest,
+    };
+    use crate::objects::services::storage::notify_message::NotifyMessage;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, NotifyType, TrackInfo, Unit};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    fn unit(unit: i64) -> Unit {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            unit,
+        )
+    }
+
+    #[test]
+    fn test_summarize() {
+        let messages = vec![
+            NotifyMessage::new(unit(1), 10, NotifyType::new()),
+            NotifyMessage::new(unit(2), 20, NotifyType::new()),
+            NotifyMessage::new(unit(2), 25, NotifyType::ready(3)),
+            NotifyMessage::new(unit(3), 30, NotifyType::new()),
+        ];
+        let u = unit(0);
+        let digests = TrackDigest::summarize(&u.stream_name, 10, &messages);
+        assert_eq!(
+            digests,
+            vec![TrackDigest::new(
+                TrackInfo::new(u.track_type, u.track_name),
+                2,
+                3,
+                2,
+                3,
+                20,
+                30
+            )]
+        );
+        assert!(TrackDigest::summarize(&u.stream_name, 30, &messages).is_empty());
+    }
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let u = unit(0);
+
+        let req = NotifyDigestRequest::new(1, String::from("response"), u.stream_name, 100);
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(NotifyDigestRequest::load(&req_envelope).unwrap(), req);
+
+        let rep = NotifyDigestResponse::new(
+            1,
+            u.stream_name,
+            vec![TrackDigest::new(
+                TrackInfo::new(u.track_type, u.track_name),
+                2,
+                3,
+                2,
+                3,
+                120,
+                130,
+            )],
+        );
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(NotifyDigestResponse::load(&rep_envelope).unwrap(), rep);
+    }
+}
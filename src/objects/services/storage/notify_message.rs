@@ -1,31 +1,57 @@
 use crate::avro::{Builder, ProtocolMessage, NOTIFY_MESSAGE_SCHEMA};
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-use crate::primitives::{NotifyType, NotifyTypeImpl, Unit};
+use crate::primitives::{Attributes, NotifyType, NotifyTypeImpl, Unit};
+use crate::utils::{gen_hash_map, parse_hash_map};
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct NotifyMessage {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub saved_ms: u64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub notify_type: NotifyType,
+    /// Annotations of the source, e.g. `keyframe` or `scene-change`.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
 }
 
-#[pymethods]
+message_builder!(NotifyMessage, NotifyMessageBuilder {
+    required {
+        stream_unit: Unit,
+        saved_ms: u64,
+        notify_type: NotifyType,
+    }
+    optional {}
+    defaulted {
+        attributes: Attributes,
+    }
+});
+
 impl NotifyMessage {
-    #[new]
     pub fn new(stream_unit: Unit, saved_ms: u64, notify_type: NotifyType) -> Self {
         NotifyMessage {
             stream_unit,
             saved_ms,
             notify_type,
+            attributes: Attributes::default(),
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NotifyMessage {
+    #[new]
+    fn py_new(stream_unit: Unit, saved_ms: u64, notify_type: NotifyType) -> Self {
+        Self::new(stream_unit, saved_ms, notify_type)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -49,7 +75,7 @@ impl FromProtocolMessage for NotifyMessage {
         }
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Record(stream_unit_fields)), (_, Value::Int(last_element)), (_, Value::Long(saved_ms)), (_, Value::Enum(_index, notify_type))] => {
+                [(_, Value::Record(stream_unit_fields)), (_, Value::Int(last_element)), (_, Value::Long(saved_ms)), (_, Value::Enum(_index, notify_type)), (_, Value::Map(attributes))] => {
                     match stream_unit_fields.as_slice() {
                         [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] => {
                             Some(NotifyMessage {
@@ -67,6 +93,7 @@ impl FromProtocolMessage for NotifyMessage {
                                         obj: NotifyTypeImpl::NotImplemented,
                                     },
                                 },
+                                attributes: parse_hash_map(attributes),
                             })
                         }
                         _ => {
@@ -90,9 +117,10 @@ impl FromProtocolMessage for NotifyMessage {
 
 impl ToProtocolMessage for NotifyMessage {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(NOTIFY_MESSAGE_SCHEMA);
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
+        let mut obj = mb.get_record(NOTIFY_MESSAGE_SCHEMA)?;
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
         obj.put("saved_ms", Value::Long(self.saved_ms as i64));
+        obj.put("attributes", gen_hash_map(&self.attributes));
         match &self.notify_type.obj {
             NotifyTypeImpl::Ready(elt) => {
                 obj.put("notify_type", Value::Enum(0, "READY".into()));
@@ -103,7 +131,8 @@ impl ToProtocolMessage for NotifyMessage {
                 obj.put("last_element", Value::Int(-1));
             }
             NotifyTypeImpl::NotImplemented => {
-                panic!("Unable to handle unsupported NotifyType");
+                warn!("Unable to handle unsupported NotifyType");
+                return None;
             }
         }
 
@@ -119,7 +148,7 @@ mod tests {
     use crate::avro::Builder;
     use crate::objects::services::storage::notify_message::NotifyMessage;
     use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-    use crate::primitives::{pack_stream_name, pack_track_name, NotifyType, Unit};
+    use crate::primitives::{pack_stream_name, pack_track_name, AttributeValue, NotifyType, Unit};
     use crate::utils::get_avro_path;
     use uuid::Uuid;
 
@@ -130,7 +159,7 @@ mod tests {
         let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
         let stream_name = pack_stream_name(&stream_uuid);
 
-        let req = NotifyMessage::new(
+        let mut req = NotifyMessage::new(
             Unit::new(
                 stream_name.to_vec(),
                 track_name.to_vec(),
@@ -140,12 +169,14 @@ mod tests {
             0,
             notify_type,
         );
+        req.attributes
+            .insert(String::from("scene-change"), AttributeValue::from(true));
 
         let req_envelope_opt = req.save(&mb);
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -0,0 +1,816 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STORAGE_PURGE_REQUEST_SCHEMA, STORAGE_PURGE_RESPONSE_SCHEMA,
+    STORAGE_RETENTION_SET_REQUEST_SCHEMA, STORAGE_RETENTION_SET_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_i64_from_avro, optional_i64_to_avro, optional_string_from_avro,
+    optional_string_to_avro, optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Limits of what the store keeps for a stream, the oldest units are collected
+/// once any of them is exceeded. Limits which aren't set don't apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct RetentionPolicy {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub ttl_ms: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_bytes: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_units: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn new(ttl_ms: Option<u64>, max_bytes: Option<u64>, max_units: Option<u64>) -> Self {
+        RetentionPolicy {
+            ttl_ms,
+            max_bytes,
+            max_units,
+        }
+    }
+
+    /// Keeps everything.
+    pub fn is_unlimited(&self) -> bool {
+        self.ttl_ms.is_none() && self.max_bytes.is_none() && self.max_units.is_none()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RetentionPolicy {
+    #[new]
+    fn py_new(ttl_ms: Option<u64>, max_bytes: Option<u64>, max_units: Option<u64>) -> Self {
+        Self::new(ttl_ms, max_bytes, max_units)
+    }
+
+    #[pyo3(name = "is_unlimited")]
+    fn py_is_unlimited(&self) -> bool {
+        self.is_unlimited()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl RetentionPolicy {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("ttl_ms".into(), optional_u64_to_avro(&self.ttl_ms)),
+            ("max_bytes".into(), optional_u64_to_avro(&self.max_bytes)),
+            ("max_units".into(), optional_u64_to_avro(&self.max_units)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, ttl_ms), (_, max_bytes), (_, max_units)] => Some(RetentionPolicy {
+                    ttl_ms: optional_u64_from_avro(ttl_ms)?,
+                    max_bytes: optional_u64_from_avro(max_bytes)?,
+                    max_units: optional_u64_from_avro(max_units)?,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum StorageControlStatus {
+    Applied,
+    Rejected,
+    NotFound,
+    NotImplemented,
+}
+
+fn get_storage_control_status_avro(status: &StorageControlStatus) -> Result<Value, String> {
+    Ok(match status {
+        StorageControlStatus::Applied => Value::Enum(0, "APPLIED".into()),
+        StorageControlStatus::Rejected => Value::Enum(1, "REJECTED".into()),
+        StorageControlStatus::NotFound => Value::Enum(2, "NOT_FOUND".into()),
+        StorageControlStatus::NotImplemented => {
+            return Err(String::from("Not supported storage control status"))
+        }
+    })
+}
+
+fn get_storage_control_status_enum(status: &str) -> StorageControlStatus {
+    match status {
+        "APPLIED" => StorageControlStatus::Applied,
+        "REJECTED" => StorageControlStatus::Rejected,
+        "NOT_FOUND" => StorageControlStatus::NotFound,
+        _ => StorageControlStatus::NotImplemented,
+    }
+}
+
+/// Replaces the retention policy of the stream.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StorageRetentionSetRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub policy: RetentionPolicy,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StorageRetentionSetRequest, StorageRetentionSetRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        policy: RetentionPolicy,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StorageRetentionSetRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        policy: RetentionPolicy,
+    ) -> Self {
+        StorageRetentionSetRequest {
+            request_id,
+            topic,
+            stream_name,
+            policy,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StorageRetentionSetRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        policy: RetentionPolicy,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, policy)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// `previous` is the policy which was replaced, so an operator can roll back.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StorageRetentionSetResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub status: StorageControlStatus,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub previous: Option<RetentionPolicy>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error: Option<String>,
+}
+
+message_builder!(StorageRetentionSetResponse, StorageRetentionSetResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+    }
+    optional {
+        previous: RetentionPolicy,
+        error: String,
+    }
+    defaulted {}
+});
+
+impl StorageRetentionSetResponse {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+        previous: Option<RetentionPolicy>,
+        error: Option<String>,
+    ) -> Self {
+        StorageRetentionSetResponse {
+            request_id,
+            stream_name,
+            status,
+            previous,
+            error,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StorageRetentionSetResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+        previous: Option<RetentionPolicy>,
+        error: Option<String>,
+    ) -> Self {
+        Self::new(request_id, stream_name, status, previous, error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Deletes the units of the stream older than `before_unit` or saved before
+/// `before_ms`, of one track or of all of them when `track_info` isn't set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StoragePurgeRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: Option<TrackInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub before_unit: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub before_ms: Option<u64>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StoragePurgeRequest, StoragePurgeRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+    }
+    optional {
+        track_info: TrackInfo,
+        before_unit: i64,
+        before_ms: u64,
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StoragePurgeRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: Option<TrackInfo>,
+        before_unit: Option<i64>,
+        before_ms: Option<u64>,
+    ) -> Self {
+        StoragePurgeRequest {
+            request_id,
+            topic,
+            stream_name,
+            track_info,
+            before_unit,
+            before_ms,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn older_than_unit(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        before_unit: i64,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_name,
+            Some(track_info),
+            Some(before_unit),
+            None,
+        )
+    }
+
+    pub fn older_than_ms(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        before_ms: u64,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, None, None, Some(before_ms))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StoragePurgeRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: Option<TrackInfo>,
+        before_unit: Option<i64>,
+        before_ms: Option<u64>,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_name,
+            track_info,
+            before_unit,
+            before_ms,
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "older_than_unit")]
+    fn py_older_than_unit(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        before_unit: i64,
+    ) -> Self {
+        Self::older_than_unit(request_id, topic, stream_name, track_info, before_unit)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "older_than_ms")]
+    fn py_older_than_ms(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        before_ms: u64,
+    ) -> Self {
+        Self::older_than_ms(request_id, topic, stream_name, before_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StoragePurgeResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub status: StorageControlStatus,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub purged_units: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub freed_bytes: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error: Option<String>,
+}
+
+message_builder!(StoragePurgeResponse, StoragePurgeResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+        purged_units: u64,
+        freed_bytes: u64,
+    }
+    optional {
+        error: String,
+    }
+    defaulted {}
+});
+
+impl StoragePurgeResponse {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+        purged_units: u64,
+        freed_bytes: u64,
+        error: Option<String>,
+    ) -> Self {
+        StoragePurgeResponse {
+            request_id,
+            stream_name,
+            status,
+            purged_units,
+            freed_bytes,
+            error,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StoragePurgeResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        status: StorageControlStatus,
+        purged_units: u64,
+        freed_bytes: u64,
+        error: Option<String>,
+    ) -> Self {
+        Self::new(
+            request_id,
+            stream_name,
+            status,
+            purged_units,
+            freed_bytes,
+            error,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for StorageRetentionSetRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_RETENTION_SET_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("policy", self.policy.to_avro_record());
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_RETENTION_SET_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StorageRetentionSetRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_RETENTION_SET_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, policy), (_, deadline_ms)] => {
+                    match RetentionPolicy::from_avro(policy) {
+                        Some(policy) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(StorageRetentionSetRequest {
+                                request_id: *request_id,
+                                topic: topic.clone(),
+                                stream_name: sn,
+                                policy,
+                                deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                            })
+                        }
+                        None => {
+                            warn!("Unable to match AVRO Record to RetentionPolicy");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StorageRetentionSetRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StorageRetentionSetResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_RETENTION_SET_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "status",
+            get_storage_control_status_avro(&self.status).ok()?,
+        );
+        obj.put(
+            "previous",
+            Value::Union(Box::new(match &self.previous {
+                Some(previous) => previous.to_avro_record(),
+                None => Value::Null,
+            })),
+        );
+        obj.put("error", optional_string_to_avro(&self.error));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_RETENTION_SET_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StorageRetentionSetResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_RETENTION_SET_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Enum(_, status)), (_, Value::Union(previous)), (_, error)] =>
+                {
+                    let previous = match previous.as_ref() {
+                        Value::Null => None,
+                        previous => Some(RetentionPolicy::from_avro(previous)?),
+                    };
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StorageRetentionSetResponse {
+                        request_id: *request_id,
+                        stream_name: sn,
+                        status: get_storage_control_status_enum(status),
+                        previous,
+                        error: optional_string_from_avro(error)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StorageRetentionSetResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StoragePurgeRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_PURGE_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "track",
+            Value::Union(Box::new(match &self.track_info {
+                Some(track_info) => track_info.to_avro_record().ok()?,
+                None => Value::Null,
+            })),
+        );
+        obj.put("before_unit", optional_i64_to_avro(&self.before_unit));
+        obj.put("before_ms", optional_u64_to_avro(&self.before_ms));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_PURGE_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StoragePurgeRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_PURGE_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Union(track_info)), (_, before_unit), (_, before_ms), (_, deadline_ms)] =>
+                {
+                    let track_info = match track_info.as_ref() {
+                        Value::Null => None,
+                        track_info => Some(TrackInfo::from_avro(track_info)?),
+                    };
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StoragePurgeRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        track_info,
+                        before_unit: optional_i64_from_avro(before_unit)?,
+                        before_ms: optional_u64_from_avro(before_ms)?,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StoragePurgeRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StoragePurgeResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_PURGE_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "status",
+            get_storage_control_status_avro(&self.status).ok()?,
+        );
+        obj.put("purged_units", Value::Long(self.purged_units as i64));
+        obj.put("freed_bytes", Value::Long(self.freed_bytes as i64));
+        obj.put("error", optional_string_to_avro(&self.error));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_PURGE_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StoragePurgeResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_PURGE_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Enum(_, status)), (_, Value::Long(purged_units)), (_, Value::Long(freed_bytes)), (_, error)] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StoragePurgeResponse {
+                        request_id: *request_id,
+                        stream_name: sn,
+                        status: get_storage_control_status_enum(status),
+                        purged_units: *purged_units as u64,
+                        freed_bytes: *freed_bytes as u64,
+                        error: optional_string_from_avro(error)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StoragePurgeResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::retention::{
+        RetentionPolicy, StorageControlStatus, StoragePurgeRequest, StoragePurgeResponse,
+        StorageRetentionSetRequest, StorageRetentionSetResponse,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let policy = RetentionPolicy::new(Some(7 * 86_400_000), Some(1 << 40), None);
+        let req = StorageRetentionSetRequest::new(1, String::from("response"), stream_name, policy);
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(
+            StorageRetentionSetRequest::load(&req_envelope).unwrap(),
+            req
+        );
+
+        let rep = StorageRetentionSetResponse::new(
+            1,
+            stream_name,
+            StorageControlStatus::Applied,
+            Some(RetentionPolicy::default()),
+            None,
+        );
+        assert!(rep.previous.unwrap().is_unlimited());
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(
+            StorageRetentionSetResponse::load(&rep_envelope).unwrap(),
+            rep
+        );
+
+        let track_info = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+        for req in [
+            StoragePurgeRequest::older_than_unit(
+                2,
+                String::from("response"),
+                stream_name,
+                track_info,
+                1000,
+            ),
+            StoragePurgeRequest::older_than_ms(
+                3,
+                String::from("response"),
+                stream_name,
+                1_700_000_000_000,
+            ),
+        ] {
+            let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+            let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+            assert_eq!(StoragePurgeRequest::load(&req_envelope).unwrap(), req);
+        }
+
+        let rep = StoragePurgeResponse::new(
+            2,
+            stream_name,
+            StorageControlStatus::NotFound,
+            0,
+            0,
+            Some(String::from("Unknown stream")),
+        );
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(StoragePurgeResponse::load(&rep_envelope).unwrap(), rep);
+    }
+}
@@ -0,0 +1,515 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STORAGE_STATS_REQUEST_SCHEMA, STORAGE_STATS_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// What the store keeps for a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TrackStats {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit_count: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub byte_size: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub first_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_write_ms: u64,
+}
+
+impl TrackStats {
+    pub fn new(
+        track_info: TrackInfo,
+        unit_count: u64,
+        byte_size: u64,
+        first_unit: i64,
+        last_unit: i64,
+        last_write_ms: u64,
+    ) -> Self {
+        TrackStats {
+            track_info,
+            unit_count,
+            byte_size,
+            first_unit,
+            last_unit,
+            last_write_ms,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TrackStats {
+    #[new]
+    fn py_new(
+        track_info: TrackInfo,
+        unit_count: u64,
+        byte_size: u64,
+        first_unit: i64,
+        last_unit: i64,
+        last_write_ms: u64,
+    ) -> Self {
+        Self::new(
+            track_info,
+            unit_count,
+            byte_size,
+            first_unit,
+            last_unit,
+            last_write_ms,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl TrackStats {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("track".into(), self.track_info.to_avro_record()?),
+            ("unit_count".into(), Value::Long(self.unit_count as i64)),
+            ("byte_size".into(), Value::Long(self.byte_size as i64)),
+            ("first_unit".into(), Value::Long(self.first_unit)),
+            ("last_unit".into(), Value::Long(self.last_unit)),
+            (
+                "last_write_ms".into(),
+                Value::Long(self.last_write_ms as i64),
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, track_info), (_, Value::Long(unit_count)), (_, Value::Long(byte_size)), (_, Value::Long(first_unit)), (_, Value::Long(last_unit)), (_, Value::Long(last_write_ms))] => {
+                    Some(TrackStats {
+                        track_info: TrackInfo::from_avro(track_info)?,
+                        unit_count: *unit_count as u64,
+                        byte_size: *byte_size as u64,
+                        first_unit: *first_unit,
+                        last_unit: *last_unit,
+                        last_write_ms: *last_write_ms as u64,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Per-track statistics of a stream, the stream totals are derived from them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamStats {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tracks: Vec<TrackStats>,
+}
+
+impl StreamStats {
+    pub fn new(stream_name: StreamName, tracks: Vec<TrackStats>) -> Self {
+        StreamStats {
+            stream_name,
+            tracks,
+        }
+    }
+
+    pub fn unit_count(&self) -> u64 {
+        self.tracks.iter().map(|t| t.unit_count).sum()
+    }
+
+    pub fn byte_size(&self) -> u64 {
+        self.tracks.iter().map(|t| t.byte_size).sum()
+    }
+
+    pub fn last_write_ms(&self) -> Option<u64> {
+        self.tracks.iter().map(|t| t.last_write_ms).max()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamStats {
+    #[new]
+    fn py_new(stream_name: StreamName, tracks: Vec<TrackStats>) -> Self {
+        Self::new(stream_name, tracks)
+    }
+
+    #[pyo3(name = "unit_count")]
+    fn py_unit_count(&self) -> u64 {
+        self.unit_count()
+    }
+
+    #[pyo3(name = "byte_size")]
+    fn py_byte_size(&self) -> u64 {
+        self.byte_size()
+    }
+
+    #[pyo3(name = "last_write_ms")]
+    fn py_last_write_ms(&self) -> Option<u64> {
+        self.last_write_ms()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl StreamStats {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            (
+                "stream_name".into(),
+                Value::Bytes(self.stream_name.to_vec()),
+            ),
+            (
+                "tracks".into(),
+                Value::Array(
+                    self.tracks
+                        .iter()
+                        .map(TrackStats::to_avro_record)
+                        .collect::<Result<_, _>>()?,
+                ),
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Array(tracks))] => {
+                    let tracks: Option<Vec<_>> = tracks.iter().map(TrackStats::from_avro).collect();
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamStats {
+                        stream_name: sn,
+                        tracks: tracks?,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Asks the store for the statistics of a stream, or of all the streams it keeps
+/// when `stream_name` isn't set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StorageStatsRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: Option<StreamName>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StorageStatsRequest, StorageStatsRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+    }
+    optional {
+        stream_name: StreamName,
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StorageStatsRequest {
+    pub fn new(request_id: i64, topic: String, stream_name: Option<StreamName>) -> Self {
+        StorageStatsRequest {
+            request_id,
+            topic,
+            stream_name,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StorageStatsRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, stream_name: Option<StreamName>) -> Self {
+        Self::new(request_id, topic, stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StorageStatsResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub streams: Vec<StreamStats>,
+}
+
+message_builder!(StorageStatsResponse, StorageStatsResponseBuilder {
+    required {
+        request_id: i64,
+    }
+    optional {}
+    defaulted {
+        streams: Vec<StreamStats>,
+    }
+});
+
+impl StorageStatsResponse {
+    pub fn new(request_id: i64, streams: Vec<StreamStats>) -> Self {
+        StorageStatsResponse {
+            request_id,
+            streams,
+        }
+    }
+
+    pub fn byte_size(&self) -> u64 {
+        self.streams.iter().map(StreamStats::byte_size).sum()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StorageStatsResponse {
+    #[new]
+    fn py_new(request_id: i64, streams: Vec<StreamStats>) -> Self {
+        Self::new(request_id, streams)
+    }
+
+    #[pyo3(name = "byte_size")]
+    fn py_byte_size(&self) -> u64 {
+        self.byte_size()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for StorageStatsRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_STATS_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put(
+            "stream_name",
+            Value::Union(Box::new(match &self.stream_name {
+                Some(stream_name) => Value::Bytes(stream_name.to_vec()),
+                None => Value::Null,
+            })),
+        );
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_STATS_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StorageStatsRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_STATS_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Union(stream_name)), (_, deadline_ms)] =>
+                {
+                    let stream_name = match stream_name.as_ref() {
+                        Value::Null => None,
+                        Value::Bytes(stream_name) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(sn)
+                        }
+                        _ => return None,
+                    };
+                    Some(StorageStatsRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StorageStatsRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StorageStatsResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STORAGE_STATS_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        let streams: Vec<Value> = self
+            .streams
+            .iter()
+            .map(StreamStats::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("streams", Value::Array(streams));
+        Some(ProtocolMessage {
+            schema: String::from(STORAGE_STATS_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StorageStatsResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STORAGE_STATS_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Array(streams))] => {
+                    let parsed: Vec<_> =
+                        streams.iter().filter_map(StreamStats::from_avro).collect();
+                    if parsed.len() < streams.len() {
+                        warn!("Not all stream stats records are parsed well.");
+                        None
+                    } else {
+                        Some(StorageStatsResponse {
+                            request_id: *request_id,
+                            streams: parsed,
+                        })
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StorageStatsResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::storage_stats::{
+        StorageStatsRequest, StorageStatsResponse, StreamStats, TrackStats,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+
+        for req in [
+            StorageStatsRequest::new(1, String::from("response"), Some(stream_name)),
+            StorageStatsRequest::new(2, String::from("response"), None),
+        ] {
+            let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+            let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+            assert_eq!(StorageStatsRequest::load(&req_envelope).unwrap(), req);
+        }
+
+        let stats = StreamStats::new(
+            stream_name,
+            vec![
+                TrackStats::new(
+                    TrackInfo::new(TrackType::Video, track_name),
+                    86_400,
+                    10 << 30,
+                    0,
+                    86_399,
+                    1_700_000_000_000,
+                ),
+                TrackStats::new(
+                    TrackInfo::new(TrackType::Meta, track_name),
+                    86_000,
+                    1 << 20,
+                    400,
+                    86_399,
+                    1_700_000_000_500,
+                ),
+            ],
+        );
+        assert_eq!(stats.unit_count(), 172_400);
+        assert_eq!(stats.byte_size(), (10 << 30) + (1 << 20));
+        assert_eq!(stats.last_write_ms(), Some(1_700_000_000_500));
+
+        let rep = StorageStatsResponse::new(1, vec![stats]);
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(StorageStatsResponse::load(&rep_envelope).unwrap(), rep);
+    }
+}
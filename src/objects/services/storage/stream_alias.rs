@@ -0,0 +1,366 @@
+use crate::avro::{
+    Builder, ProtocolMessage, RESOLVE_ALIAS_REQUEST_SCHEMA, RESOLVE_ALIAS_RESPONSE_SCHEMA,
+    SET_STREAM_ALIAS_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_bytes_from_avro, optional_bytes_to_avro, optional_u64_from_avro, optional_u64_to_avro,
+    StreamName,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Attaches a human-readable alias to a stream.
+///
+/// An alias names a single stream, the alias which is already taken is moved to
+/// the new stream only when `replace` is set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct SetStreamAlias {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub alias: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub replace: bool,
+}
+
+message_builder!(SetStreamAlias, SetStreamAliasBuilder {
+    required {
+        alias: String,
+        stream_name: StreamName,
+    }
+    optional {}
+    defaulted {
+        replace: bool,
+    }
+});
+
+impl SetStreamAlias {
+    pub fn new(alias: String, stream_name: StreamName, replace: bool) -> Self {
+        SetStreamAlias {
+            alias,
+            stream_name,
+            replace,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SetStreamAlias {
+    #[new]
+    fn py_new(alias: String, stream_name: StreamName, replace: bool) -> Self {
+        Self::new(alias, stream_name, replace)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ResolveAliasRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub alias: String,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(ResolveAliasRequest, ResolveAliasRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        alias: String,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl ResolveAliasRequest {
+    pub fn new(request_id: i64, topic: String, alias: String) -> Self {
+        ResolveAliasRequest {
+            request_id,
+            topic,
+            alias,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ResolveAliasRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, alias: String) -> Self {
+        Self::new(request_id, topic, alias)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// `stream_name` is unset when the alias is unknown.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ResolveAliasResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub alias: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: Option<StreamName>,
+}
+
+message_builder!(ResolveAliasResponse, ResolveAliasResponseBuilder {
+    required {
+        request_id: i64,
+        alias: String,
+    }
+    optional {
+        stream_name: StreamName,
+    }
+    defaulted {}
+});
+
+impl ResolveAliasResponse {
+    pub fn new(request_id: i64, alias: String, stream_name: Option<StreamName>) -> Self {
+        ResolveAliasResponse {
+            request_id,
+            alias,
+            stream_name,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ResolveAliasResponse {
+    #[new]
+    fn py_new(request_id: i64, alias: String, stream_name: Option<StreamName>) -> Self {
+        Self::new(request_id, alias, stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for SetStreamAlias {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SET_STREAM_ALIAS_SCHEMA)?;
+        obj.put("alias", Value::String(self.alias.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("replace", Value::Boolean(self.replace));
+        Some(ProtocolMessage {
+            schema: String::from(SET_STREAM_ALIAS_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for SetStreamAlias {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SET_STREAM_ALIAS_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(alias)), (_, Value::Bytes(stream_name)), (_, Value::Boolean(replace))] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(SetStreamAlias {
+                        alias: alias.clone(),
+                        stream_name: sn,
+                        replace: *replace,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to SetStreamAlias");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for ResolveAliasRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(RESOLVE_ALIAS_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("alias", Value::String(self.alias.clone()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(RESOLVE_ALIAS_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for ResolveAliasRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != RESOLVE_ALIAS_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(alias)), (_, deadline_ms)] => {
+                    Some(ResolveAliasRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        alias: alias.clone(),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to ResolveAliasRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for ResolveAliasResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(RESOLVE_ALIAS_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("alias", Value::String(self.alias.clone()));
+        obj.put(
+            "stream_name",
+            optional_bytes_to_avro(&self.stream_name.map(|sn| sn.to_vec())),
+        );
+        Some(ProtocolMessage {
+            schema: String::from(RESOLVE_ALIAS_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for ResolveAliasResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != RESOLVE_ALIAS_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(alias)), (_, stream_name)] => {
+                    match optional_bytes_from_avro(stream_name) {
+                        Some(stream_name) => Some(ResolveAliasResponse {
+                            request_id: *request_id,
+                            alias: alias.clone(),
+                            stream_name: stream_name.map(|stream_name| {
+                                let mut sn = StreamName::default();
+                                fill_byte_array(&mut sn, &stream_name);
+                                sn
+                            }),
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to to ResolveAliasResponse");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to ResolveAliasResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_alias::{
+        ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let set = SetStreamAlias::new(String::from("front-door-cam"), stream_name, false);
+        let set_serialized = mb.save_from_avro(set.save(&mb).unwrap()).unwrap();
+        let set_envelope = mb.load_to_avro(set_serialized).unwrap();
+        assert_eq!(SetStreamAlias::load(&set_envelope).unwrap(), set);
+
+        let req = ResolveAliasRequest::new(1, String::from("response"), set.alias.clone());
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(ResolveAliasRequest::load(&req_envelope).unwrap(), req);
+
+        for stream_name in [Some(stream_name), None] {
+            let rep = ResolveAliasResponse::new(1, set.alias.clone(), stream_name);
+            let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+            let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+            assert_eq!(ResolveAliasResponse::load(&rep_envelope).unwrap(), rep);
+        }
+    }
+}
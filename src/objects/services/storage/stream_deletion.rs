@@ -0,0 +1,274 @@
+use crate::avro::{
+    Builder, ProtocolMessage, RESTORE_STREAM_REQUEST_SCHEMA, SOFT_DELETE_STREAM_REQUEST_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, StreamName};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Hides the stream from the listings, the store purges it once `grace_period_ms`
+/// passes without a restore.
+///
+/// The store confirms with a `SOFT_DELETED` lifecycle message or answers with an
+/// error response.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct SoftDeleteStreamRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub grace_period_ms: u64,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(SoftDeleteStreamRequest, SoftDeleteStreamRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        grace_period_ms: u64,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl SoftDeleteStreamRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        grace_period_ms: u64,
+    ) -> Self {
+        SoftDeleteStreamRequest {
+            request_id,
+            topic,
+            stream_name,
+            grace_period_ms,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SoftDeleteStreamRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        grace_period_ms: u64,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, grace_period_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Brings back a soft-deleted stream, confirmed with a `RESTORED` lifecycle message.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct RestoreStreamRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(RestoreStreamRequest, RestoreStreamRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl RestoreStreamRequest {
+    pub fn new(request_id: i64, topic: String, stream_name: StreamName) -> Self {
+        RestoreStreamRequest {
+            request_id,
+            topic,
+            stream_name,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RestoreStreamRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, stream_name: StreamName) -> Self {
+        Self::new(request_id, topic, stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for SoftDeleteStreamRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SOFT_DELETE_STREAM_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("grace_period_ms", Value::Long(self.grace_period_ms as i64));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(SOFT_DELETE_STREAM_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for SoftDeleteStreamRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SOFT_DELETE_STREAM_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Long(grace_period_ms)), (_, deadline_ms)] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(SoftDeleteStreamRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        grace_period_ms: *grace_period_ms as u64,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to SoftDeleteStreamRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for RestoreStreamRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(RESTORE_STREAM_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(RESTORE_STREAM_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for RestoreStreamRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != RESTORE_STREAM_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, deadline_ms)] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(RestoreStreamRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to RestoreStreamRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_deletion::{
+        RestoreStreamRequest, SoftDeleteStreamRequest,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let req =
+            SoftDeleteStreamRequest::new(1, String::from("response"), stream_name, 86_400_000);
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(SoftDeleteStreamRequest::load(&req_envelope).unwrap(), req);
+
+        let req = RestoreStreamRequest::new(2, String::from("response"), stream_name);
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(RestoreStreamRequest::load(&req_envelope).unwrap(), req);
+    }
+}
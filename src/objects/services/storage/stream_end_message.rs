@@ -0,0 +1,209 @@
+use crate::avro::{Builder, ProtocolMessage, STREAM_END_MESSAGE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{StreamName, TrackInfo};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum StreamEndReason {
+    Finished,
+    SourceLost,
+    Deleted,
+    Error,
+    NotImplemented,
+}
+
+fn get_stream_end_reason_avro(reason: &StreamEndReason) -> Result<Value, String> {
+    Ok(match reason {
+        StreamEndReason::Finished => Value::Enum(0, "FINISHED".into()),
+        StreamEndReason::SourceLost => Value::Enum(1, "SOURCE_LOST".into()),
+        StreamEndReason::Deleted => Value::Enum(2, "DELETED".into()),
+        StreamEndReason::Error => Value::Enum(3, "ERROR".into()),
+        StreamEndReason::NotImplemented => {
+            return Err(String::from("Not supported stream end reason"))
+        }
+    })
+}
+
+fn get_stream_end_reason_enum(reason: &str) -> StreamEndReason {
+    match reason {
+        "FINISHED" => StreamEndReason::Finished,
+        "SOURCE_LOST" => StreamEndReason::SourceLost,
+        "DELETED" => StreamEndReason::Deleted,
+        "ERROR" => StreamEndReason::Error,
+        _ => StreamEndReason::NotImplemented,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamEndMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub reason: StreamEndReason,
+}
+
+message_builder!(StreamEndMessage, StreamEndMessageBuilder {
+    required {
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        last_unit: i64,
+        reason: StreamEndReason,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl StreamEndMessage {
+    pub fn new(
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        last_unit: i64,
+        reason: StreamEndReason,
+    ) -> Self {
+        StreamEndMessage {
+            stream_name,
+            track_info,
+            last_unit,
+            reason,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamEndMessage {
+    #[new]
+    fn py_new(
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        last_unit: i64,
+        reason: StreamEndReason,
+    ) -> Self {
+        Self::new(stream_name, track_info, last_unit, reason)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for StreamEndMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_END_MESSAGE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(last_unit)), (_, Value::Enum(_, reason))] => {
+                    match TrackInfo::from_avro(track_info) {
+                        Some(track_info) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(StreamEndMessage {
+                                stream_name: sn,
+                                track_info,
+                                last_unit: *last_unit,
+                                reason: get_stream_end_reason_enum(reason.as_str()),
+                            })
+                        }
+                        None => {
+                            warn!("Unable to match AVRO Record to TrackInfo");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamEndMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamEndMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_END_MESSAGE_SCHEMA)?;
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("last_unit", Value::Long(self.last_unit));
+        obj.put("reason", get_stream_end_reason_avro(&self.reason).ok()?);
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_END_MESSAGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_end_message::{
+        StreamEndMessage, StreamEndReason,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+
+        let req = StreamEndMessage::new(
+            stream_name,
+            TrackInfo::new(TrackType::Video, track_name),
+            42,
+            StreamEndReason::SourceLost,
+        );
+
+        let req_envelope_opt = req.save(&mb);
+        assert!(req_envelope_opt.is_some());
+
+        let req_envelope = req_envelope_opt.unwrap();
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
+
+        let req_envelope_opt = mb.load_to_avro(req_serialized);
+        assert!(req_envelope_opt.is_some());
+
+        let req_envelope = req_envelope_opt.unwrap();
+
+        let new_req_opt = StreamEndMessage::load(&req_envelope);
+
+        assert!(new_req_opt.is_some());
+
+        let new_req = new_req_opt.unwrap();
+
+        assert_eq!(req, new_req);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::avro::{Builder, ProtocolMessage, STREAM_LIFECYCLE_MESSAGE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::StreamName;
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum StreamLifecycleEvent {
+    Created,
+    Paused,
+    Resumed,
+    Deleted,
+    SoftDeleted,
+    Restored,
+    NotImplemented,
+}
+
+fn get_stream_lifecycle_event_avro(event: &StreamLifecycleEvent) -> Result<Value, String> {
+    Ok(match event {
+        StreamLifecycleEvent::Created => Value::Enum(0, "CREATED".into()),
+        StreamLifecycleEvent::Paused => Value::Enum(1, "PAUSED".into()),
+        StreamLifecycleEvent::Resumed => Value::Enum(2, "RESUMED".into()),
+        StreamLifecycleEvent::Deleted => Value::Enum(3, "DELETED".into()),
+        StreamLifecycleEvent::SoftDeleted => Value::Enum(4, "SOFT_DELETED".into()),
+        StreamLifecycleEvent::Restored => Value::Enum(5, "RESTORED".into()),
+        StreamLifecycleEvent::NotImplemented => {
+            return Err(String::from("Not supported stream lifecycle event"))
+        }
+    })
+}
+
+fn get_stream_lifecycle_event_enum(event: &str) -> StreamLifecycleEvent {
+    match event {
+        "CREATED" => StreamLifecycleEvent::Created,
+        "PAUSED" => StreamLifecycleEvent::Paused,
+        "RESUMED" => StreamLifecycleEvent::Resumed,
+        "DELETED" => StreamLifecycleEvent::Deleted,
+        "SOFT_DELETED" => StreamLifecycleEvent::SoftDeleted,
+        "RESTORED" => StreamLifecycleEvent::Restored,
+        _ => StreamLifecycleEvent::NotImplemented,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamLifecycleMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub event: StreamLifecycleEvent,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub timestamp_ms: u64,
+}
+
+message_builder!(StreamLifecycleMessage, StreamLifecycleMessageBuilder {
+    required {
+        stream_name: StreamName,
+        event: StreamLifecycleEvent,
+        timestamp_ms: u64,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl StreamLifecycleMessage {
+    pub fn new(stream_name: StreamName, event: StreamLifecycleEvent, timestamp_ms: u64) -> Self {
+        StreamLifecycleMessage {
+            stream_name,
+            event,
+            timestamp_ms,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamLifecycleMessage {
+    #[new]
+    fn py_new(stream_name: StreamName, event: StreamLifecycleEvent, timestamp_ms: u64) -> Self {
+        Self::new(stream_name, event, timestamp_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for StreamLifecycleMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_LIFECYCLE_MESSAGE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Enum(_, event)), (_, Value::Long(timestamp_ms))] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamLifecycleMessage {
+                        stream_name: sn,
+                        event: get_stream_lifecycle_event_enum(event.as_str()),
+                        timestamp_ms: *timestamp_ms as u64,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamLifecycleMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamLifecycleMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_LIFECYCLE_MESSAGE_SCHEMA)?;
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("event", get_stream_lifecycle_event_avro(&self.event).ok()?);
+        obj.put("timestamp_ms", Value::Long(self.timestamp_ms as i64));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_LIFECYCLE_MESSAGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_lifecycle_message::{
+        StreamLifecycleEvent, StreamLifecycleMessage,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        for event in [
+            StreamLifecycleEvent::Created,
+            StreamLifecycleEvent::Paused,
+            StreamLifecycleEvent::Resumed,
+            StreamLifecycleEvent::Deleted,
+        ] {
+            let msg = StreamLifecycleMessage::new(stream_name, event, 1_650_000_000_000);
+
+            let msg_serialized = mb.save_from_avro(msg.save(&mb).unwrap()).unwrap();
+            let msg_envelope = mb.load_to_avro(msg_serialized).unwrap();
+            let new_msg = StreamLifecycleMessage::load(&msg_envelope).unwrap();
+
+            assert_eq!(msg, new_msg);
+        }
+    }
+}
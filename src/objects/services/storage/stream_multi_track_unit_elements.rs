@@ -0,0 +1,474 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+};
+use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsResponse;
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_u64_from_avro, optional_u64_to_avro, ElementType, Payload, StreamName, TrackInfo, Unit,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Elements of a unit stored for a single track.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TrackElements {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub values: Vec<Payload>,
+}
+
+impl TrackElements {
+    pub fn new(track_info: TrackInfo, values: Vec<Payload>) -> Self {
+        TrackElements { track_info, values }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TrackElements {
+    #[new]
+    fn py_new(track_info: TrackInfo, values: Vec<Payload>) -> Self {
+        Self::new(track_info, values)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl TrackElements {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("track".into(), self.track_info.to_avro_record()?),
+            (
+                "values".into(),
+                Value::Array(
+                    self.values
+                        .iter()
+                        .map(Payload::to_avro_record)
+                        .collect::<Result<_, _>>()?,
+                ),
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, track_info), (_, Value::Array(values))] => {
+                    let values: Option<Vec<_>> = values.iter().map(Payload::from_avro).collect();
+                    Some(TrackElements {
+                        track_info: TrackInfo::from_avro(track_info)?,
+                        values: values?,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Fetches the elements `0..=max_element` of the same unit of several tracks in one
+/// round trip, e.g. the video and the meta elements a player shows together.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamMultiTrackUnitElementsRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tracks: Vec<TrackInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_element: ElementType,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        unit: i64,
+        max_element: ElementType,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {
+        tracks: Vec<TrackInfo>,
+    }
+});
+
+impl StreamMultiTrackUnitElementsRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        unit: i64,
+        max_element: ElementType,
+    ) -> Self {
+        StreamMultiTrackUnitElementsRequest {
+            request_id,
+            topic,
+            stream_name,
+            tracks,
+            unit,
+            max_element,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamMultiTrackUnitElementsRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        unit: i64,
+        max_element: ElementType,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, tracks, unit, max_element)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Elements grouped by track in the order of the requested tracks. Tracks which
+/// have no such unit are answered with no values.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamMultiTrackUnitElementsResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tracks: Vec<TrackElements>,
+}
+
+message_builder!(StreamMultiTrackUnitElementsResponse, StreamMultiTrackUnitElementsResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        unit: i64,
+    }
+    optional {}
+    defaulted {
+        tracks: Vec<TrackElements>,
+    }
+});
+
+impl StreamMultiTrackUnitElementsResponse {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        unit: i64,
+        tracks: Vec<TrackElements>,
+    ) -> Self {
+        StreamMultiTrackUnitElementsResponse {
+            request_id,
+            stream_name,
+            unit,
+            tracks,
+        }
+    }
+
+    pub fn values(&self, track_info: TrackInfo) -> Option<Vec<Payload>> {
+        self.tracks
+            .iter()
+            .find(|t| t.track_info == track_info)
+            .map(|t| t.values.clone())
+    }
+
+    /// Splits the response into the per-track responses, for the consumers written
+    /// against the single track request.
+    pub fn unit_responses(&self) -> Vec<StreamTrackUnitElementsResponse> {
+        self.tracks
+            .iter()
+            .map(|t| {
+                StreamTrackUnitElementsResponse::new(
+                    self.request_id,
+                    Unit {
+                        stream_name: self.stream_name,
+                        track_name: t.track_info.track_name,
+                        track_type: t.track_info.track_type,
+                        unit: self.unit,
+                    },
+                    t.values.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamMultiTrackUnitElementsResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        unit: i64,
+        tracks: Vec<TrackElements>,
+    ) -> Self {
+        Self::new(request_id, stream_name, unit, tracks)
+    }
+
+    #[pyo3(name = "values")]
+    fn py_values(&self, track_info: TrackInfo) -> Option<Vec<Payload>> {
+        self.values(track_info)
+    }
+
+    #[pyo3(name = "unit_responses")]
+    fn py_unit_responses(&self) -> Vec<StreamTrackUnitElementsResponse> {
+        self.unit_responses()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for StreamMultiTrackUnitElementsRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "tracks",
+            Value::Array(
+                self.tracks
+                    .iter()
+                    .map(TrackInfo::to_avro_record)
+                    .collect::<Result<_, _>>()
+                    .ok()?,
+            ),
+        );
+        obj.put("unit", Value::Long(self.unit));
+        obj.put("max_element", Value::Int(self.max_element.into()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamMultiTrackUnitElementsRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_MULTI_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Array(tracks)), (_, Value::Long(unit)), (_, Value::Int(max_element)), (_, deadline_ms)] =>
+                {
+                    let parsed: Vec<_> = tracks.iter().filter_map(TrackInfo::from_avro).collect();
+                    if parsed.len() < tracks.len() {
+                        warn!("Not all track info records are parsed well.");
+                        return None;
+                    }
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamMultiTrackUnitElementsRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        tracks: parsed,
+                        unit: *unit,
+                        max_element: *max_element as ElementType,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamMultiTrackUnitElementsRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamMultiTrackUnitElementsResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("unit", Value::Long(self.unit));
+        let tracks: Vec<Value> = self
+            .tracks
+            .iter()
+            .map(TrackElements::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        obj.put("tracks", Value::Array(tracks));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamMultiTrackUnitElementsResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_MULTI_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Long(unit)), (_, Value::Array(tracks))] =>
+                {
+                    let parsed: Vec<_> =
+                        tracks.iter().filter_map(TrackElements::from_avro).collect();
+                    if parsed.len() < tracks.len() {
+                        warn!("Not all track elements records are parsed well.");
+                        return None;
+                    }
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamMultiTrackUnitElementsResponse {
+                        request_id: *request_id,
+                        stream_name: sn,
+                        unit: *unit,
+                        tracks: parsed,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamMultiTrackUnitElementsResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_multi_track_unit_elements::{
+        StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse, TrackElements,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, Payload, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let video = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+        let meta = TrackInfo::new(TrackType::Meta, video.track_name);
+
+        let req = StreamMultiTrackUnitElementsRequest::new(
+            1,
+            String::from("response"),
+            stream_name,
+            vec![video, meta],
+            3,
+            100,
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(
+            StreamMultiTrackUnitElementsRequest::load(&req_envelope).unwrap(),
+            req
+        );
+
+        let rep = StreamMultiTrackUnitElementsResponse::new(
+            1,
+            stream_name,
+            3,
+            vec![
+                TrackElements::new(
+                    video,
+                    vec![
+                        Payload::new(vec![0, 1, 2], HashMap::default()),
+                        Payload::new(vec![1, 2, 3], HashMap::default()),
+                    ],
+                ),
+                TrackElements::new(meta, vec![]),
+            ],
+        );
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        assert_eq!(
+            StreamMultiTrackUnitElementsResponse::load(&rep_envelope).unwrap(),
+            rep
+        );
+
+        assert_eq!(rep.values(meta), Some(vec![]));
+        let responses = rep.unit_responses();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].stream_unit.track_type, TrackType::Video);
+        assert_eq!(responses[0].stream_unit.unit, 3);
+        assert_eq!(responses[0].values.len(), 2);
+    }
+}
@@ -0,0 +1,623 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STREAM_REPLAY_REQUEST_SCHEMA, STREAM_REPLAY_STATUS_SCHEMA,
+    STREAM_REPLAY_STOP_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_i64_from_avro, optional_i64_to_avro, optional_string_from_avro,
+    optional_string_to_avro, optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum StreamReplayState {
+    Started,
+    Running,
+    Completed,
+    Stopped,
+    Failed,
+    NotImplemented,
+}
+
+impl StreamReplayState {
+    /// The replay won't report again once it is in one of these states.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            StreamReplayState::Completed | StreamReplayState::Stopped | StreamReplayState::Failed
+        )
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamReplayState {
+    #[pyo3(name = "is_terminal")]
+    fn py_is_terminal(&self) -> bool {
+        self.is_terminal()
+    }
+}
+
+fn get_stream_replay_state_avro(state: &StreamReplayState) -> Result<Value, String> {
+    Ok(match state {
+        StreamReplayState::Started => Value::Enum(0, "STARTED".into()),
+        StreamReplayState::Running => Value::Enum(1, "RUNNING".into()),
+        StreamReplayState::Completed => Value::Enum(2, "COMPLETED".into()),
+        StreamReplayState::Stopped => Value::Enum(3, "STOPPED".into()),
+        StreamReplayState::Failed => Value::Enum(4, "FAILED".into()),
+        StreamReplayState::NotImplemented => {
+            return Err(String::from("Not supported stream replay state"))
+        }
+    })
+}
+
+fn get_stream_replay_state_enum(state: &str) -> StreamReplayState {
+    match state {
+        "STARTED" => StreamReplayState::Started,
+        "RUNNING" => StreamReplayState::Running,
+        "COMPLETED" => StreamReplayState::Completed,
+        "STOPPED" => StreamReplayState::Stopped,
+        "FAILED" => StreamReplayState::Failed,
+        _ => StreamReplayState::NotImplemented,
+    }
+}
+
+/// Asks the store to re-emit the archived units of a stream onto
+/// `destination_topic`, starting at `from_unit` or at the first unit written after
+/// `from_ms`.
+///
+/// `speed` is relative to the recording pace, `1.0` replays in real time and `0.0`
+/// as fast as the store can read. Empty `tracks` replay all the tracks of the
+/// stream. The store answers with [`StreamReplayStatus`] messages carrying the
+/// `replay_id` it assigned.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamReplayRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub tracks: Vec<TrackInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_unit: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_ms: Option<u64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub speed: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub destination_topic: String,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamReplayRequest, StreamReplayRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        speed: f64,
+        destination_topic: String,
+    }
+    optional {
+        from_unit: i64,
+        from_ms: u64,
+        deadline_ms: u64,
+    }
+    defaulted {
+        tracks: Vec<TrackInfo>,
+    }
+});
+
+impl StreamReplayRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        from_unit: i64,
+        speed: f64,
+        destination_topic: String,
+    ) -> Self {
+        StreamReplayRequest {
+            request_id,
+            topic,
+            stream_name,
+            tracks,
+            from_unit: Some(from_unit),
+            from_ms: None,
+            speed,
+            destination_topic,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn from_time(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        from_ms: u64,
+        speed: f64,
+        destination_topic: String,
+    ) -> Self {
+        StreamReplayRequest {
+            from_unit: None,
+            from_ms: Some(from_ms),
+            ..Self::new(
+                request_id,
+                topic,
+                stream_name,
+                tracks,
+                0,
+                speed,
+                destination_topic,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamReplayRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        from_unit: i64,
+        speed: f64,
+        destination_topic: String,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_name,
+            tracks,
+            from_unit,
+            speed,
+            destination_topic,
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_time")]
+    fn py_from_time(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        tracks: Vec<TrackInfo>,
+        from_ms: u64,
+        speed: f64,
+        destination_topic: String,
+    ) -> Self {
+        Self::from_time(
+            request_id,
+            topic,
+            stream_name,
+            tracks,
+            from_ms,
+            speed,
+            destination_topic,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// State of a replay, sent when it starts, periodically while it runs and once it
+/// is over. `last_unit` is the last unit re-emitted so far.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamReplayStatus {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub replay_id: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub state: StreamReplayState,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_unit: Option<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error: Option<String>,
+}
+
+message_builder!(StreamReplayStatus, StreamReplayStatusBuilder {
+    required {
+        request_id: i64,
+        replay_id: String,
+        state: StreamReplayState,
+    }
+    optional {
+        last_unit: i64,
+        error: String,
+    }
+    defaulted {}
+});
+
+impl StreamReplayStatus {
+    pub fn new(
+        request_id: i64,
+        replay_id: String,
+        state: StreamReplayState,
+        last_unit: Option<i64>,
+    ) -> Self {
+        StreamReplayStatus {
+            request_id,
+            replay_id,
+            state,
+            last_unit,
+            error: None,
+        }
+    }
+
+    pub fn failed(
+        request_id: i64,
+        replay_id: String,
+        last_unit: Option<i64>,
+        error: String,
+    ) -> Self {
+        StreamReplayStatus {
+            error: Some(error),
+            ..Self::new(request_id, replay_id, StreamReplayState::Failed, last_unit)
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamReplayStatus {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        replay_id: String,
+        state: StreamReplayState,
+        last_unit: Option<i64>,
+    ) -> Self {
+        Self::new(request_id, replay_id, state, last_unit)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "failed")]
+    fn py_failed(
+        request_id: i64,
+        replay_id: String,
+        last_unit: Option<i64>,
+        error: String,
+    ) -> Self {
+        Self::failed(request_id, replay_id, last_unit, error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Stops a running replay, confirmed with a `STOPPED` replay status.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamReplayStop {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub replay_id: String,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamReplayStop, StreamReplayStopBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        replay_id: String,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StreamReplayStop {
+    pub fn new(request_id: i64, topic: String, replay_id: String) -> Self {
+        StreamReplayStop {
+            request_id,
+            topic,
+            replay_id,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamReplayStop {
+    #[new]
+    fn py_new(request_id: i64, topic: String, replay_id: String) -> Self {
+        Self::new(request_id, topic, replay_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for StreamReplayRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_REPLAY_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put(
+            "tracks",
+            Value::Array(
+                self.tracks
+                    .iter()
+                    .map(TrackInfo::to_avro_record)
+                    .collect::<Result<_, _>>()
+                    .ok()?,
+            ),
+        );
+        obj.put("from_unit", optional_i64_to_avro(&self.from_unit));
+        obj.put("from_ms", optional_u64_to_avro(&self.from_ms));
+        obj.put("speed", Value::Double(self.speed));
+        obj.put(
+            "destination_topic",
+            Value::String(self.destination_topic.clone()),
+        );
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_REPLAY_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamReplayRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_REPLAY_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, Value::Array(tracks)), (_, from_unit), (_, from_ms), (_, Value::Double(speed)), (_, Value::String(destination_topic)), (_, deadline_ms)] =>
+                {
+                    let parsed: Vec<_> = tracks.iter().filter_map(TrackInfo::from_avro).collect();
+                    if parsed.len() < tracks.len() {
+                        warn!("Not all track info records are parsed well.");
+                        return None;
+                    }
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamReplayRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        stream_name: sn,
+                        tracks: parsed,
+                        from_unit: optional_i64_from_avro(from_unit)?,
+                        from_ms: optional_u64_from_avro(from_ms)?,
+                        speed: *speed,
+                        destination_topic: destination_topic.clone(),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamReplayRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamReplayStatus {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_REPLAY_STATUS_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("replay_id", Value::String(self.replay_id.clone()));
+        obj.put("state", get_stream_replay_state_avro(&self.state).ok()?);
+        obj.put("last_unit", optional_i64_to_avro(&self.last_unit));
+        obj.put("error", optional_string_to_avro(&self.error));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_REPLAY_STATUS_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamReplayStatus {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_REPLAY_STATUS_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(replay_id)), (_, Value::Enum(_, state)), (_, last_unit), (_, error)] => {
+                    Some(StreamReplayStatus {
+                        request_id: *request_id,
+                        replay_id: replay_id.clone(),
+                        state: get_stream_replay_state_enum(state),
+                        last_unit: optional_i64_from_avro(last_unit)?,
+                        error: optional_string_from_avro(error)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamReplayStatus");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamReplayStop {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_REPLAY_STOP_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("replay_id", Value::String(self.replay_id.clone()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_REPLAY_STOP_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamReplayStop {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_REPLAY_STOP_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(replay_id)), (_, deadline_ms)] => {
+                    Some(StreamReplayStop {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        replay_id: replay_id.clone(),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamReplayStop");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_replay::{
+        StreamReplayRequest, StreamReplayState, StreamReplayStatus, StreamReplayStop,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let video = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+
+        for req in [
+            StreamReplayRequest::new(
+                1,
+                String::from("response"),
+                stream_name,
+                vec![video],
+                100,
+                2.0,
+                String::from("/replay/1"),
+            ),
+            StreamReplayRequest::from_time(
+                2,
+                String::from("response"),
+                stream_name,
+                vec![],
+                1_700_000_000_000,
+                0.0,
+                String::from("/replay/2"),
+            ),
+        ] {
+            let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+            let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+            assert_eq!(StreamReplayRequest::load(&req_envelope).unwrap(), req);
+        }
+
+        let status = StreamReplayStatus::new(
+            1,
+            String::from("replay-1"),
+            StreamReplayState::Running,
+            Some(150),
+        );
+        assert!(!status.state.is_terminal());
+        let status_serialized = mb.save_from_avro(status.save(&mb).unwrap()).unwrap();
+        let status_envelope = mb.load_to_avro(status_serialized).unwrap();
+        assert_eq!(StreamReplayStatus::load(&status_envelope).unwrap(), status);
+
+        let status = StreamReplayStatus::failed(
+            2,
+            String::from("replay-2"),
+            None,
+            String::from("No units after the requested time"),
+        );
+        assert!(status.state.is_terminal());
+        let status_serialized = mb.save_from_avro(status.save(&mb).unwrap()).unwrap();
+        let status_envelope = mb.load_to_avro(status_serialized).unwrap();
+        assert_eq!(StreamReplayStatus::load(&status_envelope).unwrap(), status);
+
+        let stop = StreamReplayStop::new(3, String::from("response"), String::from("replay-1"));
+        let stop_serialized = mb.save_from_avro(stop.save(&mb).unwrap()).unwrap();
+        let stop_envelope = mb.load_to_avro(stop_serialized).unwrap();
+        assert_eq!(StreamReplayStop::load(&stop_envelope).unwrap(), stop);
+    }
+}
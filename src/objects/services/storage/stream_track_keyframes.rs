@@ -0,0 +1,317 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, ElementType, UnitRange};
+use crate::readahead::Keyframe;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamTrackKeyframesRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub range: UnitRange,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamTrackKeyframesRequest, StreamTrackKeyframesRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        range: UnitRange,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StreamTrackKeyframesRequest {
+    pub fn new(request_id: i64, topic: String, range: UnitRange) -> Self {
+        StreamTrackKeyframesRequest {
+            request_id,
+            topic,
+            range,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackKeyframesRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, range: UnitRange) -> Self {
+        Self::new(request_id, topic, range)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamTrackKeyframesResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub range: UnitRange,
+    /// `(unit, element)` pairs of the elements flagged as keyframes.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub keyframes: Vec<(i64, ElementType)>,
+}
+
+message_builder!(StreamTrackKeyframesResponse, StreamTrackKeyframesResponseBuilder {
+    required {
+        request_id: i64,
+        range: UnitRange,
+    }
+    optional {}
+    defaulted {
+        keyframes: Vec<(i64, ElementType)>,
+    }
+});
+
+impl StreamTrackKeyframesResponse {
+    pub fn new(request_id: i64, range: UnitRange, keyframes: Vec<(i64, ElementType)>) -> Self {
+        StreamTrackKeyframesResponse {
+            request_id,
+            range,
+            keyframes,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackKeyframesResponse {
+    #[new]
+    fn py_new(request_id: i64, range: UnitRange, keyframes: Vec<(i64, ElementType)>) -> Self {
+        Self::new(request_id, range, keyframes)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl StreamTrackKeyframesResponse {
+    /// Keyframes in the form the readahead planner accepts.
+    pub fn to_keyframes(&self) -> Vec<Keyframe> {
+        self.keyframes
+            .iter()
+            .map(|(unit, element)| Keyframe {
+                unit: *unit,
+                element: *element,
+            })
+            .collect()
+    }
+}
+
+impl FromProtocolMessage for StreamTrackKeyframesRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, range), (_, deadline_ms)] => {
+                    match UnitRange::from_avro(range) {
+                        Some(range) => Some(StreamTrackKeyframesRequest {
+                            request_id: *request_id,
+                            topic: topic.clone(),
+                            range,
+                            deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                        }),
+                        None => {
+                            warn!("Unable to match AVRO Record to UnitRange");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamTrackKeyframesRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamTrackKeyframesRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("range", self.range.to_avro_record().ok()?);
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamTrackKeyframesResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, range), (_, Value::Array(keyframes))] => {
+                    let keyframes_parsed: Vec<_> = keyframes
+                        .iter()
+                        .filter_map(|k| match k {
+                            Value::Record(fields) => match fields.as_slice() {
+                                [(_, Value::Long(unit)), (_, Value::Int(element))] => {
+                                    Some((*unit, *element as ElementType))
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        })
+                        .collect();
+                    match UnitRange::from_avro(range) {
+                        Some(range) if keyframes_parsed.len() == keyframes.len() => {
+                            Some(StreamTrackKeyframesResponse {
+                                request_id: *request_id,
+                                range,
+                                keyframes: keyframes_parsed,
+                            })
+                        }
+                        _ => {
+                            warn!("Unable to match AVRO Record to UnitRange or keyframes");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamTrackKeyframesResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamTrackKeyframesResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("range", self.range.to_avro_record().ok()?);
+        let keyframes: Vec<Value> = self
+            .keyframes
+            .iter()
+            .map(|(unit, element)| {
+                Value::Record(vec![
+                    ("unit".into(), Value::Long(*unit)),
+                    ("element".into(), Value::Int((*element).into())),
+                ])
+            })
+            .collect();
+        obj.put("keyframes", Value::Array(keyframes));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_track_keyframes::{
+        StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, UnitRange};
+    use crate::readahead::Keyframe;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    fn range() -> UnitRange {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        UnitRange::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            1,
+            10,
+        )
+    }
+
+    #[test]
+    fn test_load_save_req() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let req = StreamTrackKeyframesRequest::new(1, String::from("response"), range());
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        let new_req = StreamTrackKeyframesRequest::load(&req_envelope).unwrap();
+
+        assert_eq!(req, new_req);
+    }
+
+    #[test]
+    fn test_load_save_rep() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let rep = StreamTrackKeyframesResponse::new(1, range(), vec![(1, 0), (5, 0), (5, 30)]);
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = StreamTrackKeyframesResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert_eq!(
+            new_rep.to_keyframes()[2],
+            Keyframe {
+                unit: 5,
+                element: 30
+            }
+        );
+    }
+}
@@ -4,28 +4,64 @@ use crate::avro::{
 };
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-use crate::primitives::{ElementType, Payload, Unit};
-use crate::utils::value_to_string;
+use crate::pager::PagedRequest;
+use crate::primitives::{
+    optional_bytes_from_avro, optional_bytes_to_avro, optional_string_from_avro,
+    optional_string_to_avro, optional_u64_from_avro, optional_u64_to_avro, ContinuationCursor,
+    ElementType, Payload, SnapshotToken, TrackName, Unit,
+};
+use crate::utils::fill_byte_array;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTrackUnitElementsRequest {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub topic: String,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub max_element: ElementType,
+    /// Upper bound of payloads in a single response, `0` lets the store decide.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_results: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub continuation_cursor: Option<ContinuationCursor>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub snapshot_token: Option<SnapshotToken>,
+    /// Selects the tracks of the unit type whose names match the pattern, e.g.
+    /// `meta-*`; the track name of the unit is ignored then.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_pattern: Option<String>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
 }
 
-#[pymethods]
+message_builder!(StreamTrackUnitElementsRequest, StreamTrackUnitElementsRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        max_element: ElementType,
+        max_results: i32,
+    }
+    optional {
+        continuation_cursor: ContinuationCursor,
+        snapshot_token: SnapshotToken,
+        track_pattern: String,
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
 impl StreamTrackUnitElementsRequest {
-    #[new]
     pub fn new(
         request_id: i64,
         topic: String,
@@ -37,9 +73,48 @@ impl StreamTrackUnitElementsRequest {
             topic,
             stream_unit,
             max_element,
+            max_results: 0,
+            continuation_cursor: None,
+            snapshot_token: None,
+            track_pattern: None,
+            deadline_ms: None,
         }
     }
 
+    pub fn paged(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        max_element: ElementType,
+        max_results: i32,
+    ) -> Self {
+        StreamTrackUnitElementsRequest {
+            max_results,
+            ..Self::new(request_id, topic, stream_unit, max_element)
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitElementsRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, stream_unit: Unit, max_element: ElementType) -> Self {
+        Self::new(request_id, topic, stream_unit, max_element)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "paged")]
+    fn py_paged(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        max_element: ElementType,
+        max_results: i32,
+    ) -> Self {
+        Self::paged(request_id, topic, stream_unit, max_element, max_results)
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -62,23 +137,34 @@ impl FromProtocolMessage for StreamTrackUnitElementsRequest {
         }
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(max_element))] => {
-                    match stream_unit_fields.as_slice() {
-                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] => {
-                            Some(StreamTrackUnitElementsRequest {
-                                request_id: *request_id,
-                                topic: topic.clone(),
-                                stream_unit: Unit::new(
-                                    stream_name.clone(),
-                                    track_name.clone(),
-                                    track_type.clone(),
-                                    *unit,
-                                ),
-                                max_element: *max_element as i16,
-                            })
-                        }
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(max_element)), (_, Value::Int(max_results)), (_, continuation_cursor), (_, snapshot_token), (_, track_pattern), (_, deadline_ms)] => {
+                    match (
+                        stream_unit_fields.as_slice(),
+                        optional_bytes_from_avro(continuation_cursor),
+                        optional_bytes_from_avro(snapshot_token),
+                    ) {
+                        (
+                            [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))],
+                            Some(continuation_cursor),
+                            Some(snapshot_token),
+                        ) => Some(StreamTrackUnitElementsRequest {
+                            request_id: *request_id,
+                            topic: topic.clone(),
+                            stream_unit: Unit::new(
+                                stream_name.clone(),
+                                track_name.clone(),
+                                track_type.clone(),
+                                *unit,
+                            ),
+                            max_element: *max_element as i16,
+                            max_results: *max_results,
+                            continuation_cursor,
+                            snapshot_token,
+                            track_pattern: optional_string_from_avro(track_pattern)?,
+                            deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                        }),
                         _ => {
-                            warn!("Unable to match AVRO Record to Unit");
+                            warn!("Unable to match AVRO Record to Unit, continuation cursor or snapshot token");
                             None
                         }
                     }
@@ -98,11 +184,25 @@ impl FromProtocolMessage for StreamTrackUnitElementsRequest {
 
 impl ToProtocolMessage for StreamTrackUnitElementsRequest {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
         obj.put("topic", Value::String(self.topic.clone()));
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
         obj.put("max_element", Value::Long(self.max_element.into()));
+        obj.put("max_results", Value::Int(self.max_results));
+        obj.put(
+            "continuation_cursor",
+            optional_bytes_to_avro(&self.continuation_cursor),
+        );
+        obj.put(
+            "snapshot_token",
+            optional_bytes_to_avro(&self.snapshot_token),
+        );
+        obj.put(
+            "track_pattern",
+            optional_string_to_avro(&self.track_pattern),
+        );
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
 
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA),
@@ -112,27 +212,112 @@ impl ToProtocolMessage for StreamTrackUnitElementsRequest {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTrackUnitElementsResponse {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub values: Vec<Payload>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub continuation_cursor: Option<ContinuationCursor>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub has_more: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub snapshot_token: Option<SnapshotToken>,
+    /// Track of every entry of `values` when the request selected the tracks by a
+    /// pattern, empty otherwise.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_names: Vec<TrackName>,
 }
 
-#[pymethods]
+message_builder!(StreamTrackUnitElementsResponse, StreamTrackUnitElementsResponseBuilder {
+    required {
+        request_id: i64,
+        stream_unit: Unit,
+    }
+    optional {
+        continuation_cursor: ContinuationCursor,
+        snapshot_token: SnapshotToken,
+    }
+    defaulted {
+        values: Vec<Payload>,
+        has_more: bool,
+        track_names: Vec<TrackName>,
+    }
+});
+
 impl StreamTrackUnitElementsResponse {
-    #[new]
     pub fn new(request_id: i64, stream_unit: Unit, values: Vec<Payload>) -> Self {
         StreamTrackUnitElementsResponse {
             request_id,
             stream_unit,
             values,
+            continuation_cursor: None,
+            has_more: false,
+            snapshot_token: None,
+            track_names: vec![],
+        }
+    }
+
+    /// A page which is followed by the one the cursor points to.
+    pub fn partial(
+        request_id: i64,
+        stream_unit: Unit,
+        values: Vec<Payload>,
+        continuation_cursor: ContinuationCursor,
+    ) -> Self {
+        StreamTrackUnitElementsResponse {
+            continuation_cursor: Some(continuation_cursor),
+            has_more: true,
+            ..Self::new(request_id, stream_unit, values)
         }
     }
 
+    /// Pairs every entry of `values` with the track it belongs to.
+    pub fn resolved_values(&self) -> Vec<(TrackName, Payload)> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                (
+                    self.track_names
+                        .get(i)
+                        .copied()
+                        .unwrap_or(self.stream_unit.track_name),
+                    x.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitElementsResponse {
+    #[new]
+    fn py_new(request_id: i64, stream_unit: Unit, values: Vec<Payload>) -> Self {
+        Self::new(request_id, stream_unit, values)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "partial")]
+    fn py_partial(
+        request_id: i64,
+        stream_unit: Unit,
+        values: Vec<Payload>,
+        continuation_cursor: ContinuationCursor,
+    ) -> Self {
+        Self::partial(request_id, stream_unit, values, continuation_cursor)
+    }
+
+    #[pyo3(name = "resolved_values")]
+    fn py_resolved_values(&self) -> Vec<(TrackName, Payload)> {
+        self.resolved_values()
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -155,39 +340,35 @@ impl FromProtocolMessage for StreamTrackUnitElementsResponse {
         }
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::Record(stream_unit_fields)), (_, Value::Array(values))] => {
-                    match stream_unit_fields.as_slice() {
-                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] =>
-                        {
-                            fn to_payload(v: &Value) -> Option<Payload> {
-                                match v {
-                                    Value::Record(fields) => match fields.as_slice() {
-                                        [(_, Value::Bytes(data)), (_, Value::Map(attributes))] => {
-                                            Some(Payload {
-                                                data: data.clone(),
-                                                attributes: attributes
-                                                    .iter()
-                                                    .map(|x| {
-                                                        (
-                                                            x.0.clone(),
-                                                            value_to_string(x.1)
-                                                                .or_else(|| Some(String::from("")))
-                                                                .unwrap(),
-                                                        )
-                                                    })
-                                                    .collect(),
-                                            })
-                                        }
-                                        _ => None,
-                                    },
-                                    _ => None,
-                                }
-                            }
-
+                [(_, Value::Long(request_id)), (_, Value::Record(stream_unit_fields)), (_, Value::Array(values)), (_, continuation_cursor), (_, Value::Boolean(has_more)), (_, snapshot_token), (_, Value::Array(track_names))] => {
+                    match (
+                        stream_unit_fields.as_slice(),
+                        optional_bytes_from_avro(continuation_cursor),
+                        optional_bytes_from_avro(snapshot_token),
+                    ) {
+                        (
+                            [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))],
+                            Some(continuation_cursor),
+                            Some(snapshot_token),
+                        ) => {
                             let values_parsed: Vec<_> =
-                                values.iter().filter_map(to_payload).collect();
+                                values.iter().filter_map(Payload::from_avro).collect();
+
+                            let track_names_parsed: Vec<TrackName> = track_names
+                                .iter()
+                                .filter_map(|x| match x {
+                                    Value::Bytes(name) => {
+                                        let mut tn = TrackName::default();
+                                        fill_byte_array(&mut tn, name);
+                                        Some(tn)
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
 
-                            if values_parsed.len() < values.len() {
+                            if values_parsed.len() < values.len()
+                                || track_names_parsed.len() < track_names.len()
+                            {
                                 warn!("Not all payload values were parsed correctly");
                                 None
                             } else {
@@ -200,11 +381,15 @@ impl FromProtocolMessage for StreamTrackUnitElementsResponse {
                                         *unit,
                                     ),
                                     values: values_parsed,
+                                    continuation_cursor,
+                                    has_more: *has_more,
+                                    snapshot_token,
+                                    track_names: track_names_parsed,
                                 })
                             }
                         }
                         _ => {
-                            warn!("Unable to match AVRO Record to Unit");
+                            warn!("Unable to match AVRO Record to Unit, continuation cursor or snapshot token");
                             None
                         }
                     }
@@ -222,29 +407,34 @@ impl FromProtocolMessage for StreamTrackUnitElementsResponse {
     }
 }
 
-fn payload_to_avro(p: &Payload) -> Value {
-    Value::Record(vec![
-        ("data".into(), Value::Bytes(p.data.clone())),
-        (
-            "attributes".into(),
-            Value::Map(
-                p.attributes
-                    .iter()
-                    .map(|x| (x.0.clone(), Value::String(x.1.clone())))
-                    .collect(),
-            ),
-        ),
-    ])
-}
-
 impl ToProtocolMessage for StreamTrackUnitElementsResponse {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
-
-        let values: Vec<Value> = self.values.iter().map(payload_to_avro).collect();
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+
+        let values: Vec<Value> = self
+            .values
+            .iter()
+            .map(Payload::to_avro_record)
+            .collect::<Result<_, _>>()
+            .ok()?;
         obj.put("values", Value::Array(values));
+        obj.put(
+            "continuation_cursor",
+            optional_bytes_to_avro(&self.continuation_cursor),
+        );
+        obj.put("has_more", Value::Boolean(self.has_more));
+        obj.put(
+            "snapshot_token",
+            optional_bytes_to_avro(&self.snapshot_token),
+        );
+        let track_names: Vec<Value> = self
+            .track_names
+            .iter()
+            .map(|x| Value::Bytes(x.to_vec()))
+            .collect();
+        obj.put("track_names", Value::Array(track_names));
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA),
             object: Value::from(obj),
@@ -252,6 +442,24 @@ impl ToProtocolMessage for StreamTrackUnitElementsResponse {
     }
 }
 
+impl PagedRequest for StreamTrackUnitElementsRequest {
+    type Response = StreamTrackUnitElementsResponse;
+
+    fn next_page(&self, response: &Self::Response) -> Option<Self> {
+        match (&response.continuation_cursor, response.has_more) {
+            (Some(cursor), true) => Some(StreamTrackUnitElementsRequest {
+                continuation_cursor: Some(cursor.clone()),
+                snapshot_token: response
+                    .snapshot_token
+                    .clone()
+                    .or_else(|| self.snapshot_token.clone()),
+                ..self.clone()
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::avro::Builder;
@@ -272,7 +480,7 @@ mod tests {
         let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
         let stream_name = pack_stream_name(&stream_uuid);
 
-        let req = StreamTrackUnitElementsRequest::new(
+        let mut req = StreamTrackUnitElementsRequest::paged(
             1,
             String::from("response"),
             Unit::new(
@@ -282,13 +490,16 @@ mod tests {
                 3,
             ),
             100,
+            10,
         );
+        req.continuation_cursor = Some(vec![0, 0, 1]);
+        req.snapshot_token = Some(vec![7]);
 
         let req_envelope_opt = req.save(&mb);
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -312,7 +523,7 @@ mod tests {
         let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
         let stream_name = pack_stream_name(&stream_uuid);
 
-        let req = StreamTrackUnitElementsResponse::new(
+        let mut req = StreamTrackUnitElementsResponse::partial(
             1,
             Unit::new(
                 stream_name.to_vec(),
@@ -321,22 +532,18 @@ mod tests {
                 3,
             ),
             vec![
-                Payload {
-                    data: vec![0, 1, 2],
-                    attributes: HashMap::default(),
-                },
-                Payload {
-                    data: vec![1, 2, 3],
-                    attributes: HashMap::default(),
-                },
+                Payload::new(vec![0, 1, 2], HashMap::default()),
+                Payload::new(vec![1, 2, 3], HashMap::default()),
             ],
+            vec![0, 0, 2],
         );
+        req.snapshot_token = Some(vec![7]);
 
         let req_envelope_opt = req.save(&mb);
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
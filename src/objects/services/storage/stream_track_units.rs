@@ -2,29 +2,67 @@ use crate::avro::{
     Builder, ProtocolMessage, STREAM_TRACK_UNITS_REQUEST_SCHEMA, STREAM_TRACK_UNITS_RESPONSE_SCHEMA,
 };
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-use crate::primitives::Unit;
+use crate::pager::PagedRequest;
+use crate::primitives::{
+    optional_bytes_from_avro, optional_bytes_to_avro, optional_string_from_avro,
+    optional_string_to_avro, optional_u64_from_avro, optional_u64_to_avro, ContinuationCursor,
+    SnapshotToken, TrackName, Unit, UnitSummary,
+};
+use crate::utils::fill_byte_array;
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTrackUnitsRequest {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub topic: String,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub from_ms: u128,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub to_ms: u128,
+    /// Upper bound of units in a single response, `0` lets the store decide.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub max_results: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub continuation_cursor: Option<ContinuationCursor>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub snapshot_token: Option<SnapshotToken>,
+    /// Selects the tracks of the unit type whose names match the pattern, e.g.
+    /// `meta-*`; the track name of the unit is ignored then.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_pattern: Option<String>,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
 }
 
-#[pymethods]
+message_builder!(StreamTrackUnitsRequest, StreamTrackUnitsRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        max_results: i32,
+    }
+    optional {
+        continuation_cursor: ContinuationCursor,
+        snapshot_token: SnapshotToken,
+        track_pattern: String,
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
 impl StreamTrackUnitsRequest {
-    #[new]
     pub fn new(
         request_id: i64,
         topic: String,
@@ -38,9 +76,56 @@ impl StreamTrackUnitsRequest {
             stream_unit,
             from_ms,
             to_ms,
+            max_results: 0,
+            continuation_cursor: None,
+            snapshot_token: None,
+            track_pattern: None,
+            deadline_ms: None,
         }
     }
 
+    pub fn paged(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        max_results: i32,
+    ) -> Self {
+        StreamTrackUnitsRequest {
+            max_results,
+            ..Self::new(request_id, topic, stream_unit, from_ms, to_ms)
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitsRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+    ) -> Self {
+        Self::new(request_id, topic, stream_unit, from_ms, to_ms)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "paged")]
+    fn py_paged(
+        request_id: i64,
+        topic: String,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        max_results: i32,
+    ) -> Self {
+        Self::paged(request_id, topic, stream_unit, from_ms, to_ms, max_results)
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -60,24 +145,35 @@ impl FromProtocolMessage for StreamTrackUnitsRequest {
     {
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(from_ms)), (_, Value::Long(to_ms))] => {
-                    match stream_unit_fields.as_slice() {
-                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] => {
-                            Some(StreamTrackUnitsRequest {
-                                request_id: *request_id,
-                                topic: topic.clone(),
-                                stream_unit: Unit::new(
-                                    stream_name.clone(),
-                                    track_name.clone(),
-                                    track_type.clone(),
-                                    *unit,
-                                ),
-                                from_ms: *from_ms as u128,
-                                to_ms: *to_ms as u128,
-                            })
-                        }
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, Value::Int(max_results)), (_, continuation_cursor), (_, snapshot_token), (_, track_pattern), (_, deadline_ms)] => {
+                    match (
+                        stream_unit_fields.as_slice(),
+                        optional_bytes_from_avro(continuation_cursor),
+                        optional_bytes_from_avro(snapshot_token),
+                    ) {
+                        (
+                            [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))],
+                            Some(continuation_cursor),
+                            Some(snapshot_token),
+                        ) => Some(StreamTrackUnitsRequest {
+                            request_id: *request_id,
+                            topic: topic.clone(),
+                            stream_unit: Unit::new(
+                                stream_name.clone(),
+                                track_name.clone(),
+                                track_type.clone(),
+                                *unit,
+                            ),
+                            from_ms: *from_ms as u128,
+                            to_ms: *to_ms as u128,
+                            max_results: *max_results,
+                            continuation_cursor,
+                            snapshot_token,
+                            track_pattern: optional_string_from_avro(track_pattern)?,
+                            deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                        }),
                         _ => {
-                            warn!("Unable to match AVRO Record to Unit");
+                            warn!("Unable to match AVRO Record to Unit, continuation cursor or snapshot token");
                             None
                         }
                     }
@@ -97,12 +193,26 @@ impl FromProtocolMessage for StreamTrackUnitsRequest {
 
 impl ToProtocolMessage for StreamTrackUnitsRequest {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACK_UNITS_REQUEST_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACK_UNITS_REQUEST_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
         obj.put("topic", Value::String(self.topic.clone()));
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
-        obj.put("from_ms", Value::Long(i64::try_from(self.from_ms).unwrap()));
-        obj.put("to_ms", Value::Long(i64::try_from(self.to_ms).unwrap()));
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("from_ms", Value::Long(i64::try_from(self.from_ms).ok()?));
+        obj.put("to_ms", Value::Long(i64::try_from(self.to_ms).ok()?));
+        obj.put("max_results", Value::Int(self.max_results));
+        obj.put(
+            "continuation_cursor",
+            optional_bytes_to_avro(&self.continuation_cursor),
+        );
+        obj.put(
+            "snapshot_token",
+            optional_bytes_to_avro(&self.snapshot_token),
+        );
+        obj.put(
+            "track_pattern",
+            optional_string_to_avro(&self.track_pattern),
+        );
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACK_UNITS_REQUEST_SCHEMA),
             object: Value::from(obj),
@@ -111,23 +221,55 @@ impl ToProtocolMessage for StreamTrackUnitsRequest {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTrackUnitsResponse {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub from_ms: u128,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub to_ms: u128,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub units: Vec<i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub continuation_cursor: Option<ContinuationCursor>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub has_more: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub snapshot_token: Option<SnapshotToken>,
+    /// Track of every entry of `units` when the request selected the tracks by a
+    /// pattern, empty otherwise.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_names: Vec<TrackName>,
+    /// Save times and element counts of `units`, entry by entry, when the store
+    /// provides them, empty otherwise.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit_summaries: Vec<UnitSummary>,
 }
 
-#[pymethods]
+message_builder!(StreamTrackUnitsResponse, StreamTrackUnitsResponseBuilder {
+    required {
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+    }
+    optional {
+        continuation_cursor: ContinuationCursor,
+        snapshot_token: SnapshotToken,
+    }
+    defaulted {
+        units: Vec<i64>,
+        has_more: bool,
+        track_names: Vec<TrackName>,
+        unit_summaries: Vec<UnitSummary>,
+    }
+});
+
 impl StreamTrackUnitsResponse {
-    #[new]
     pub fn new(
         request_id: i64,
         stream_unit: Unit,
@@ -141,9 +283,114 @@ impl StreamTrackUnitsResponse {
             from_ms,
             to_ms,
             units,
+            continuation_cursor: None,
+            has_more: false,
+            snapshot_token: None,
+            track_names: vec![],
+            unit_summaries: vec![],
         }
     }
 
+    /// A response carrying the summary of every unit, `units` are filled from it.
+    pub fn with_summaries(
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        unit_summaries: Vec<UnitSummary>,
+    ) -> Self {
+        StreamTrackUnitsResponse {
+            units: unit_summaries.iter().map(|x| x.unit).collect(),
+            unit_summaries,
+            ..Self::new(request_id, stream_unit, from_ms, to_ms, vec![])
+        }
+    }
+
+    /// A page which is followed by the one the cursor points to.
+    pub fn partial(
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        units: Vec<i64>,
+        continuation_cursor: ContinuationCursor,
+    ) -> Self {
+        StreamTrackUnitsResponse {
+            continuation_cursor: Some(continuation_cursor),
+            has_more: true,
+            ..Self::new(request_id, stream_unit, from_ms, to_ms, units)
+        }
+    }
+
+    /// Pairs every entry of `units` with the track it belongs to.
+    pub fn resolved_units(&self) -> Vec<(TrackName, i64)> {
+        self.units
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                (
+                    self.track_names
+                        .get(i)
+                        .copied()
+                        .unwrap_or(self.stream_unit.track_name),
+                    *x,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitsResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        units: Vec<i64>,
+    ) -> Self {
+        Self::new(request_id, stream_unit, from_ms, to_ms, units)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "with_summaries")]
+    fn py_with_summaries(
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        unit_summaries: Vec<UnitSummary>,
+    ) -> Self {
+        Self::with_summaries(request_id, stream_unit, from_ms, to_ms, unit_summaries)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "partial")]
+    fn py_partial(
+        request_id: i64,
+        stream_unit: Unit,
+        from_ms: u128,
+        to_ms: u128,
+        units: Vec<i64>,
+        continuation_cursor: ContinuationCursor,
+    ) -> Self {
+        Self::partial(
+            request_id,
+            stream_unit,
+            from_ms,
+            to_ms,
+            units,
+            continuation_cursor,
+        )
+    }
+
+    #[pyo3(name = "resolved_units")]
+    fn py_resolved_units(&self) -> Vec<(TrackName, i64)> {
+        self.resolved_units()
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -163,10 +410,17 @@ impl FromProtocolMessage for StreamTrackUnitsResponse {
     {
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, Value::Array(units))] => {
-                    match stream_unit_fields.as_slice() {
-                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] =>
-                        {
+                [(_, Value::Long(request_id)), (_, Value::Record(stream_unit_fields)), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, Value::Array(units)), (_, continuation_cursor), (_, Value::Boolean(has_more)), (_, snapshot_token), (_, Value::Array(track_names)), (_, Value::Array(unit_summaries))] => {
+                    match (
+                        stream_unit_fields.as_slice(),
+                        optional_bytes_from_avro(continuation_cursor),
+                        optional_bytes_from_avro(snapshot_token),
+                    ) {
+                        (
+                            [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))],
+                            Some(continuation_cursor),
+                            Some(snapshot_token),
+                        ) => {
                             let units_parsed: Vec<_> = units
                                 .iter()
                                 .filter_map(|x| match x {
@@ -175,7 +429,27 @@ impl FromProtocolMessage for StreamTrackUnitsResponse {
                                 })
                                 .collect();
 
-                            if units_parsed.len() < units.len() {
+                            let track_names_parsed: Vec<TrackName> = track_names
+                                .iter()
+                                .filter_map(|x| match x {
+                                    Value::Bytes(name) => {
+                                        let mut tn = TrackName::default();
+                                        fill_byte_array(&mut tn, name);
+                                        Some(tn)
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+
+                            let unit_summaries_parsed: Vec<_> = unit_summaries
+                                .iter()
+                                .filter_map(UnitSummary::from_avro)
+                                .collect();
+
+                            if units_parsed.len() < units.len()
+                                || track_names_parsed.len() < track_names.len()
+                                || unit_summaries_parsed.len() < unit_summaries.len()
+                            {
                                 warn!("Not all payload units were parsed correctly");
                                 None
                             } else {
@@ -190,11 +464,16 @@ impl FromProtocolMessage for StreamTrackUnitsResponse {
                                     from_ms: *from_ms as u128,
                                     to_ms: *to_ms as u128,
                                     units: units_parsed,
+                                    continuation_cursor,
+                                    has_more: *has_more,
+                                    snapshot_token,
+                                    track_names: track_names_parsed,
+                                    unit_summaries: unit_summaries_parsed,
                                 })
                             }
                         }
                         _ => {
-                            warn!("Unable to match AVRO Record to Unit");
+                            warn!("Unable to match AVRO Record to Unit, continuation cursor or snapshot token");
                             None
                         }
                     }
@@ -214,13 +493,34 @@ impl FromProtocolMessage for StreamTrackUnitsResponse {
 
 impl ToProtocolMessage for StreamTrackUnitsResponse {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACK_UNITS_RESPONSE_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACK_UNITS_RESPONSE_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
-        obj.put("from_ms", Value::Long(i64::try_from(self.from_ms).unwrap()));
-        obj.put("to_ms", Value::Long(i64::try_from(self.to_ms).unwrap()));
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("from_ms", Value::Long(i64::try_from(self.from_ms).ok()?));
+        obj.put("to_ms", Value::Long(i64::try_from(self.to_ms).ok()?));
         let values: Vec<Value> = self.units.iter().map(|x| Value::Long(*x)).collect();
         obj.put("units", Value::Array(values));
+        obj.put(
+            "continuation_cursor",
+            optional_bytes_to_avro(&self.continuation_cursor),
+        );
+        obj.put("has_more", Value::Boolean(self.has_more));
+        obj.put(
+            "snapshot_token",
+            optional_bytes_to_avro(&self.snapshot_token),
+        );
+        let track_names: Vec<Value> = self
+            .track_names
+            .iter()
+            .map(|x| Value::Bytes(x.to_vec()))
+            .collect();
+        obj.put("track_names", Value::Array(track_names));
+        let unit_summaries: Vec<Value> = self
+            .unit_summaries
+            .iter()
+            .map(UnitSummary::to_avro_record)
+            .collect();
+        obj.put("unit_summaries", Value::Array(unit_summaries));
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACK_UNITS_RESPONSE_SCHEMA),
             object: Value::from(obj),
@@ -228,6 +528,24 @@ impl ToProtocolMessage for StreamTrackUnitsResponse {
     }
 }
 
+impl PagedRequest for StreamTrackUnitsRequest {
+    type Response = StreamTrackUnitsResponse;
+
+    fn next_page(&self, response: &Self::Response) -> Option<Self> {
+        match (&response.continuation_cursor, response.has_more) {
+            (Some(cursor), true) => Some(StreamTrackUnitsRequest {
+                continuation_cursor: Some(cursor.clone()),
+                snapshot_token: response
+                    .snapshot_token
+                    .clone()
+                    .or_else(|| self.snapshot_token.clone()),
+                ..self.clone()
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::avro::Builder;
@@ -235,7 +553,7 @@ mod tests {
         StreamTrackUnitsRequest, StreamTrackUnitsResponse,
     };
     use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit, UnitSummary};
     use crate::utils::get_avro_path;
     use uuid::Uuid;
 
@@ -264,7 +582,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -305,7 +623,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -320,4 +638,38 @@ mod tests {
 
         assert_eq!(req, new_req);
     }
+
+    #[test]
+    fn test_load_save_rep_with_summaries() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let rep = StreamTrackUnitsResponse::with_summaries(
+            1,
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            100,
+            500,
+            vec![
+                UnitSummary::new(1, 110, 190, 5),
+                UnitSummary::new(2, 200, 260, 3),
+            ],
+        );
+        assert_eq!(rep.units, vec![1, 2]);
+
+        let rep_envelope = rep.save(&mb).unwrap();
+        let rep_serialized = mb.save_from_avro(rep_envelope).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = StreamTrackUnitsResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert_eq!(new_rep.unit_summaries[0].duration_ms(), 80);
+    }
 }
@@ -0,0 +1,394 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo, UnitTimestamp,
+};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamTrackUnitsByTimeRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_ms: u64,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl StreamTrackUnitsByTimeRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+    ) -> Self {
+        StreamTrackUnitsByTimeRequest {
+            request_id,
+            topic,
+            stream_name,
+            track_info,
+            from_ms,
+            to_ms,
+            deadline_ms: None,
+        }
+    }
+
+    /// The range is half-open: `from_ms` is included, `to_ms` is not.
+    pub fn covers(&self, saved_ms: u64) -> bool {
+        saved_ms >= self.from_ms && saved_ms < self.to_ms
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitsByTimeRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+    ) -> Self {
+        Self::new(request_id, topic, stream_name, track_info, from_ms, to_ms)
+    }
+
+    #[pyo3(name = "covers")]
+    fn py_covers(&self, saved_ms: u64) -> bool {
+        self.covers(saved_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamTrackUnitsByTimeResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub units: Vec<UnitTimestamp>,
+}
+
+message_builder!(StreamTrackUnitsByTimeResponse, StreamTrackUnitsByTimeResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+    }
+    optional {}
+    defaulted {
+        units: Vec<UnitTimestamp>,
+    }
+});
+
+impl StreamTrackUnitsByTimeResponse {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+        units: Vec<UnitTimestamp>,
+    ) -> Self {
+        StreamTrackUnitsByTimeResponse {
+            request_id,
+            stream_name,
+            track_info,
+            from_ms,
+            to_ms,
+            units,
+        }
+    }
+
+    pub fn unit_ids(&self) -> Vec<i64> {
+        self.units.iter().map(|u| u.unit).collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTrackUnitsByTimeResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_ms: u64,
+        to_ms: u64,
+        units: Vec<UnitTimestamp>,
+    ) -> Self {
+        Self::new(request_id, stream_name, track_info, from_ms, to_ms, units)
+    }
+
+    #[pyo3(name = "unit_ids")]
+    fn py_unit_ids(&self) -> Vec<i64> {
+        self.unit_ids()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for StreamTrackUnitsByTimeRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, deadline_ms)] => {
+                    match TrackInfo::from_avro(track_info) {
+                        Some(track_info) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(StreamTrackUnitsByTimeRequest {
+                                request_id: *request_id,
+                                topic: topic.clone(),
+                                stream_name: sn,
+                                track_info,
+                                from_ms: *from_ms as u64,
+                                to_ms: *to_ms as u64,
+                                deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                            })
+                        }
+                        None => {
+                            warn!("Unable to match AVRO Record to TrackInfo");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamTrackUnitsByTimeRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamTrackUnitsByTimeRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("from_ms", Value::Long(self.from_ms as i64));
+        obj.put("to_ms", Value::Long(self.to_ms as i64));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamTrackUnitsByTimeResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(from_ms)), (_, Value::Long(to_ms)), (_, Value::Array(units))] =>
+                {
+                    let units_parsed: Vec<_> =
+                        units.iter().filter_map(UnitTimestamp::from_avro).collect();
+                    match TrackInfo::from_avro(track_info) {
+                        Some(track_info) if units_parsed.len() == units.len() => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(StreamTrackUnitsByTimeResponse {
+                                request_id: *request_id,
+                                stream_name: sn,
+                                track_info,
+                                from_ms: *from_ms as u64,
+                                to_ms: *to_ms as u64,
+                                units: units_parsed,
+                            })
+                        }
+                        _ => {
+                            warn!("Unable to match AVRO Record to TrackInfo or units");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamTrackUnitsByTimeResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamTrackUnitsByTimeResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("from_ms", Value::Long(self.from_ms as i64));
+        obj.put("to_ms", Value::Long(self.to_ms as i64));
+        let units: Vec<Value> = self
+            .units
+            .iter()
+            .map(UnitTimestamp::to_avro_record)
+            .collect();
+        obj.put("units", Value::Array(units));
+        Some(ProtocolMessage {
+            schema: String::from(STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::stream_track_units_by_time::{
+        StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{
+        pack_stream_name, pack_track_name, StreamName, TrackInfo, TrackType, UnitTimestamp,
+    };
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    fn stream() -> (StreamName, TrackInfo) {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        (
+            pack_stream_name(&stream_uuid),
+            TrackInfo::new(TrackType::Video, track_name),
+        )
+    }
+
+    #[test]
+    fn test_load_save_req() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let (stream_name, track_info) = stream();
+
+        let req = StreamTrackUnitsByTimeRequest::new(
+            1,
+            String::from("response"),
+            stream_name,
+            track_info,
+            1_650_000_000_000,
+            1_650_000_300_000,
+        );
+        assert!(req.covers(1_650_000_000_000));
+        assert!(!req.covers(1_650_000_300_000));
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        let new_req = StreamTrackUnitsByTimeRequest::load(&req_envelope).unwrap();
+
+        assert_eq!(req, new_req);
+    }
+
+    #[test]
+    fn test_load_save_rep() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let (stream_name, track_info) = stream();
+
+        let rep = StreamTrackUnitsByTimeResponse::new(
+            1,
+            stream_name,
+            track_info,
+            1_650_000_000_000,
+            1_650_000_300_000,
+            vec![
+                UnitTimestamp::new(10, 1_650_000_000_500),
+                UnitTimestamp::new(11, 1_650_000_100_500),
+            ],
+        );
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = StreamTrackUnitsByTimeResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert_eq!(new_rep.unit_ids(), vec![10, 11]);
+    }
+}
@@ -1,46 +1,214 @@
 use crate::avro::{
     Builder, ProtocolMessage, STREAM_TRACKS_REQUEST_SCHEMA, STREAM_TRACKS_RESPONSE_SCHEMA,
-    TRACK_INFO_SCHEMA,
+    TRACK_DESCRIPTOR_SCHEMA,
 };
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
 use crate::primitives::{
-    track_type_literal_to_track_type, StreamName, TrackInfo, TrackName, TrackType,
+    optional_u64_from_avro, optional_u64_to_avro, track_type_literal_to_track_type, StreamName,
+    TrackInfo, TrackName,
 };
 use crate::utils::fill_byte_array;
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-fn get_track_type_enum(track_type: &TrackType) -> Value {
-    match track_type {
-        TrackType::Video => Value::Enum(0, "VIDEO".into()),
-        TrackType::Meta => Value::Enum(1, "META".into()),
-        TrackType::NotImplemented => panic!("Not supported track type"),
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TrackDescriptor {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub codec: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub width: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub height: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub fps: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub first_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub byte_size: i64,
+}
+
+impl TrackDescriptor {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        track_info: TrackInfo,
+        codec: String,
+        width: i32,
+        height: i32,
+        fps: f64,
+        first_unit: i64,
+        last_unit: i64,
+        byte_size: i64,
+    ) -> Self {
+        TrackDescriptor {
+            track_info,
+            codec,
+            width,
+            height,
+            fps,
+            first_unit,
+            last_unit,
+            byte_size,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TrackDescriptor {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        track_info: TrackInfo,
+        codec: String,
+        width: i32,
+        height: i32,
+        fps: f64,
+        first_unit: i64,
+        last_unit: i64,
+        byte_size: i64,
+    ) -> Self {
+        Self::new(
+            track_info, codec, width, height, fps, first_unit, last_unit, byte_size,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl TrackDescriptor {
+    fn to_avro_record(&self, mb: &Builder) -> Option<Value> {
+        let mut r = mb.get_record(TRACK_DESCRIPTOR_SCHEMA)?;
+        r.put("track", self.track_info.to_avro_record().ok()?);
+        r.put("codec", Value::String(self.codec.clone()));
+        r.put("width", Value::Int(self.width));
+        r.put("height", Value::Int(self.height));
+        r.put("fps", Value::Double(self.fps));
+        r.put("first_unit", Value::Long(self.first_unit));
+        r.put("last_unit", Value::Long(self.last_unit));
+        r.put("byte_size", Value::Long(self.byte_size));
+        Some(r.into())
+    }
+
+    fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, track_info), (_, Value::String(codec)), (_, Value::Int(width)), (_, Value::Int(height)), (_, Value::Double(fps)), (_, Value::Long(first_unit)), (_, Value::Long(last_unit)), (_, Value::Long(byte_size))] => {
+                    Some(TrackDescriptor {
+                        track_info: TrackInfo::from_avro(track_info)?,
+                        codec: codec.clone(),
+                        width: *width,
+                        height: *height,
+                        fps: *fps,
+                        first_unit: *first_unit,
+                        last_unit: *last_unit,
+                        byte_size: *byte_size,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTracksResponse {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_name: StreamName,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub tracks: Vec<TrackInfo>,
+    /// Optional metadata of the tracks, empty when the store doesn't provide it.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub descriptors: Vec<TrackDescriptor>,
 }
 
-#[pymethods]
+message_builder!(StreamTracksResponse, StreamTracksResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+    }
+    optional {}
+    defaulted {
+        tracks: Vec<TrackInfo>,
+        descriptors: Vec<TrackDescriptor>,
+    }
+});
+
 impl StreamTracksResponse {
-    #[new]
     pub fn new(request_id: i64, stream_name: StreamName, tracks: Vec<TrackInfo>) -> Self {
         StreamTracksResponse {
             request_id,
             stream_name,
             tracks,
+            descriptors: vec![],
         }
     }
 
+    pub fn with_descriptors(
+        request_id: i64,
+        stream_name: StreamName,
+        descriptors: Vec<TrackDescriptor>,
+    ) -> Self {
+        StreamTracksResponse {
+            request_id,
+            stream_name,
+            tracks: descriptors.iter().map(|d| d.track_info).collect(),
+            descriptors,
+        }
+    }
+
+    pub fn descriptor(&self, track_info: TrackInfo) -> Option<TrackDescriptor> {
+        self.descriptors
+            .iter()
+            .find(|d| d.track_info == track_info)
+            .cloned()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTracksResponse {
+    #[new]
+    fn py_new(request_id: i64, stream_name: StreamName, tracks: Vec<TrackInfo>) -> Self {
+        Self::new(request_id, stream_name, tracks)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "with_descriptors")]
+    fn py_with_descriptors(
+        request_id: i64,
+        stream_name: StreamName,
+        descriptors: Vec<TrackDescriptor>,
+    ) -> Self {
+        Self::with_descriptors(request_id, stream_name, descriptors)
+    }
+
+    #[pyo3(name = "descriptor")]
+    fn py_descriptor(&self, track_info: TrackInfo) -> Option<TrackDescriptor> {
+        self.descriptor(track_info)
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
@@ -55,20 +223,22 @@ impl StreamTracksResponse {
 
 impl ToProtocolMessage for StreamTracksResponse {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACKS_RESPONSE_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACKS_RESPONSE_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
         obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
         let tracks: Vec<Value> = self
             .tracks
             .iter()
-            .map(|track_info| {
-                let mut r = mb.get_record(TRACK_INFO_SCHEMA);
-                r.put("name", Value::Bytes(track_info.track_name.to_vec()));
-                r.put("type", get_track_type_enum(&track_info.track_type));
-                r.into()
-            })
-            .collect();
+            .map(|track_info| track_info.to_avro_record())
+            .collect::<Result<_, _>>()
+            .ok()?;
         obj.put("tracks", Value::Array(tracks));
+        let descriptors: Vec<Value> = self
+            .descriptors
+            .iter()
+            .map(|d| d.to_avro_record(mb))
+            .collect::<Option<_>>()?;
+        obj.put("descriptors", Value::Array(descriptors));
 
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACKS_RESPONSE_SCHEMA),
@@ -87,7 +257,7 @@ impl FromProtocolMessage for StreamTracksResponse {
         }
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Array(tracks))] =>
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, Value::Array(tracks)), (_, Value::Array(descriptors))] =>
                 {
                     let mut sn = StreamName::default();
                     fill_byte_array(&mut sn, stream_name);
@@ -114,14 +284,23 @@ impl FromProtocolMessage for StreamTracksResponse {
                     let valid_track_records: Vec<_> =
                         track_records.iter().flatten().copied().collect();
 
+                    let valid_descriptors: Vec<_> = descriptors
+                        .iter()
+                        .filter_map(TrackDescriptor::from_avro)
+                        .collect();
+
                     if valid_track_records.len() < track_records.len() {
                         warn!("Not all track info records are parsed well.");
                         None
+                    } else if valid_descriptors.len() < descriptors.len() {
+                        warn!("Not all track descriptor records are parsed well.");
+                        None
                     } else {
                         Some(StreamTracksResponse {
                             request_id: *request_id,
                             stream_name: sn,
                             tracks: valid_track_records,
+                            descriptors: valid_descriptors,
                         })
                     }
                 }
@@ -139,26 +318,50 @@ impl FromProtocolMessage for StreamTracksResponse {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct StreamTracksRequest {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub request_id: i64,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub topic: String,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_name: StreamName,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
 }
 
-#[pymethods]
+message_builder!(StreamTracksRequest, StreamTracksRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
 impl StreamTracksRequest {
-    #[new]
     pub fn new(request_id: i64, topic: String, stream_name: StreamName) -> Self {
         StreamTracksRequest {
             request_id,
             topic,
             stream_name,
+            deadline_ms: None,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamTracksRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, stream_name: StreamName) -> Self {
+        Self::new(request_id, topic, stream_name)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -174,10 +377,11 @@ impl StreamTracksRequest {
 
 impl ToProtocolMessage for StreamTracksRequest {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(STREAM_TRACKS_REQUEST_SCHEMA);
+        let mut obj = mb.get_record(STREAM_TRACKS_REQUEST_SCHEMA)?;
         obj.put("request_id", Value::Long(self.request_id));
         obj.put("topic", Value::String(self.topic.clone()));
         obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
         Some(ProtocolMessage {
             schema: String::from(STREAM_TRACKS_REQUEST_SCHEMA),
             object: Value::from(obj),
@@ -195,7 +399,7 @@ impl FromProtocolMessage for StreamTracksRequest {
         }
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name))] =>
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, deadline_ms)] =>
                 {
                     let mut sn = StreamName::default();
                     fill_byte_array(&mut sn, stream_name);
@@ -203,6 +407,7 @@ impl FromProtocolMessage for StreamTracksRequest {
                         request_id: *request_id,
                         topic: topic.clone(),
                         stream_name: sn,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
                     })
                 }
                 _ => {
@@ -222,7 +427,7 @@ impl FromProtocolMessage for StreamTracksRequest {
 mod tests {
     use crate::avro::Builder;
     use crate::objects::services::storage::stream_tracks::{
-        StreamTracksRequest, StreamTracksResponse,
+        StreamTracksRequest, StreamTracksResponse, TrackDescriptor,
     };
     use crate::objects::{FromProtocolMessage, ToProtocolMessage};
     use crate::primitives::{pack_stream_name, pack_track_name, TrackInfo, TrackType};
@@ -242,7 +447,7 @@ mod tests {
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -269,14 +474,18 @@ mod tests {
         let rep = StreamTracksResponse::new(
             0,
             stream_name,
-            vec![TrackInfo::new(TrackType::Video, track_name)],
+            vec![
+                TrackInfo::new(TrackType::Video, track_name),
+                TrackInfo::new(TrackType::Audio, track_name),
+                TrackInfo::new(TrackType::Subtitle, track_name),
+            ],
         );
 
         let rep_envelope_opt = rep.save(&mb);
         assert!(rep_envelope_opt.is_some());
 
         let rep_envelope = rep_envelope_opt.unwrap();
-        let rep_serialized = mb.save_from_avro(rep_envelope);
+        let rep_serialized = mb.save_from_avro(rep_envelope).unwrap();
 
         let rep_envelope_opt = mb.load_to_avro(rep_serialized);
         assert!(rep_envelope_opt.is_some());
@@ -291,4 +500,52 @@ mod tests {
 
         assert_eq!(rep, new_rep);
     }
+
+    #[test]
+    fn test_load_save_rep_descriptors() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let track_info = TrackInfo::new(
+            TrackType::Video,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+
+        let rep = StreamTracksResponse::with_descriptors(
+            0,
+            stream_name,
+            vec![TrackDescriptor::new(
+                track_info,
+                String::from("h264"),
+                1920,
+                1080,
+                25.0,
+                1,
+                100,
+                1 << 30,
+            )],
+        );
+        assert_eq!(rep.tracks, vec![track_info]);
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = StreamTracksResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+        assert_eq!(new_rep.descriptor(track_info).unwrap().codec, "h264");
+    }
+
+    #[test]
+    fn test_save_not_implemented_track() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let track_info = TrackInfo::new(
+            TrackType::NotImplemented,
+            pack_track_name(&String::from("test")).unwrap(),
+        );
+        assert!(track_info.to_avro_record().is_err());
+        assert!(StreamTracksResponse::new(0, [0; 16], vec![track_info])
+            .save(&mb)
+            .is_none());
+    }
 }
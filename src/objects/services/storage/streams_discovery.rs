@@ -0,0 +1,385 @@
+use crate::avro::{
+    Builder, ProtocolMessage, STREAMS_DISCOVERY_REQUEST_SCHEMA, STREAMS_DISCOVERY_RESPONSE_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, StreamName};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamDescriptor {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_count: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_activity_ms: u64,
+    /// Soft-deleted streams are listed only on request and may still be restored.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deleted: bool,
+}
+
+impl StreamDescriptor {
+    pub fn new(stream_name: StreamName, track_count: i32, last_activity_ms: u64) -> Self {
+        StreamDescriptor {
+            stream_name,
+            track_count,
+            last_activity_ms,
+            deleted: false,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamDescriptor {
+    #[new]
+    fn py_new(stream_name: StreamName, track_count: i32, last_activity_ms: u64) -> Self {
+        Self::new(stream_name, track_count, last_activity_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl StreamDescriptor {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            (
+                "stream_name".into(),
+                Value::Bytes(self.stream_name.to_vec()),
+            ),
+            ("track_count".into(), Value::Int(self.track_count)),
+            (
+                "last_activity_ms".into(),
+                Value::Long(self.last_activity_ms as i64),
+            ),
+            ("deleted".into(), Value::Boolean(self.deleted)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Int(track_count)), (_, Value::Long(last_activity_ms)), (_, Value::Boolean(deleted))] =>
+                {
+                    let mut sn = StreamName::default();
+                    fill_byte_array(&mut sn, stream_name);
+                    Some(StreamDescriptor {
+                        stream_name: sn,
+                        track_count: *track_count,
+                        last_activity_ms: *last_activity_ms as u64,
+                        deleted: *deleted,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamsDiscoveryRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    /// Leading bytes of the stream names to list, empty to list all streams.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub prefix_filter: Vec<u8>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub include_deleted: bool,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(StreamsDiscoveryRequest, StreamsDiscoveryRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {
+        prefix_filter: Vec<u8>,
+        include_deleted: bool,
+    }
+});
+
+impl StreamsDiscoveryRequest {
+    pub fn new(request_id: i64, topic: String, prefix_filter: Vec<u8>) -> Self {
+        StreamsDiscoveryRequest {
+            request_id,
+            topic,
+            prefix_filter,
+            include_deleted: false,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn with_deleted(request_id: i64, topic: String, prefix_filter: Vec<u8>) -> Self {
+        StreamsDiscoveryRequest {
+            include_deleted: true,
+            ..Self::new(request_id, topic, prefix_filter)
+        }
+    }
+
+    pub fn matches(&self, stream_name: StreamName) -> bool {
+        stream_name.starts_with(&self.prefix_filter)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamsDiscoveryRequest {
+    #[new]
+    fn py_new(request_id: i64, topic: String, prefix_filter: Vec<u8>) -> Self {
+        Self::new(request_id, topic, prefix_filter)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "with_deleted")]
+    fn py_with_deleted(request_id: i64, topic: String, prefix_filter: Vec<u8>) -> Self {
+        Self::with_deleted(request_id, topic, prefix_filter)
+    }
+
+    #[pyo3(name = "matches")]
+    fn py_matches(&self, stream_name: StreamName) -> bool {
+        self.matches(stream_name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct StreamsDiscoveryResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub streams: Vec<StreamDescriptor>,
+}
+
+message_builder!(StreamsDiscoveryResponse, StreamsDiscoveryResponseBuilder {
+    required {
+        request_id: i64,
+    }
+    optional {}
+    defaulted {
+        streams: Vec<StreamDescriptor>,
+    }
+});
+
+impl StreamsDiscoveryResponse {
+    pub fn new(request_id: i64, streams: Vec<StreamDescriptor>) -> Self {
+        StreamsDiscoveryResponse {
+            request_id,
+            streams,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StreamsDiscoveryResponse {
+    #[new]
+    fn py_new(request_id: i64, streams: Vec<StreamDescriptor>) -> Self {
+        Self::new(request_id, streams)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for StreamsDiscoveryRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAMS_DISCOVERY_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("prefix_filter", Value::Bytes(self.prefix_filter.clone()));
+        obj.put("include_deleted", Value::Boolean(self.include_deleted));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(STREAMS_DISCOVERY_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamsDiscoveryRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAMS_DISCOVERY_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(prefix_filter)), (_, Value::Boolean(include_deleted)), (_, deadline_ms)] => {
+                    Some(StreamsDiscoveryRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        prefix_filter: prefix_filter.clone(),
+                        include_deleted: *include_deleted,
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamsDiscoveryRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for StreamsDiscoveryResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(STREAMS_DISCOVERY_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        let streams: Vec<Value> = self
+            .streams
+            .iter()
+            .map(StreamDescriptor::to_avro_record)
+            .collect();
+        obj.put("streams", Value::Array(streams));
+        Some(ProtocolMessage {
+            schema: String::from(STREAMS_DISCOVERY_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for StreamsDiscoveryResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != STREAMS_DISCOVERY_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Array(streams))] => {
+                    let descriptors: Vec<_> = streams
+                        .iter()
+                        .filter_map(StreamDescriptor::from_avro)
+                        .collect();
+                    if descriptors.len() < streams.len() {
+                        warn!("Not all stream descriptor records are parsed well.");
+                        None
+                    } else {
+                        Some(StreamsDiscoveryResponse {
+                            request_id: *request_id,
+                            streams: descriptors,
+                        })
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to StreamsDiscoveryResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::streams_discovery::{
+        StreamDescriptor, StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::pack_stream_name;
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save_req() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let req = StreamsDiscoveryRequest::with_deleted(0, String::from("test"), vec![0xfa, 0x80]);
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        let new_req = StreamsDiscoveryRequest::load(&req_envelope).unwrap();
+
+        assert_eq!(req, new_req);
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        assert!(new_req.matches(pack_stream_name(&stream_uuid)));
+        let stream_uuid = Uuid::parse_str("0a807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        assert!(!new_req.matches(pack_stream_name(&stream_uuid)));
+    }
+
+    #[test]
+    fn test_load_save_rep() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let rep = StreamsDiscoveryResponse::new(
+            0,
+            vec![
+                StreamDescriptor::new(stream_name, 2, 1_650_000_000_000),
+                StreamDescriptor {
+                    deleted: true,
+                    ..StreamDescriptor::new(stream_name, 1, 1_650_000_000_000)
+                },
+            ],
+        );
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let rep_envelope = mb.load_to_avro(rep_serialized).unwrap();
+        let new_rep = StreamsDiscoveryResponse::load(&rep_envelope).unwrap();
+
+        assert_eq!(rep, new_rep);
+    }
+}
@@ -0,0 +1,449 @@
+use crate::avro::{
+    Builder, ProtocolMessage, UNIT_BITMAP_REQUEST_SCHEMA, UNIT_BITMAP_RESPONSE_SCHEMA,
+};
+use crate::bitmap::UnitBitmap;
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_u64_from_avro, optional_u64_to_avro, StreamName, TrackInfo};
+use crate::utils::fill_byte_array;
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Asks which units of `[from_unit, to_unit]` the store has for the track, e.g. to
+/// draw the recorded and missing segments of a timeline.
+///
+/// The range may span at most 2^32 units.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitBitmapRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_unit: i64,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(UnitBitmapRequest, UnitBitmapRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl UnitBitmapRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+    ) -> Self {
+        UnitBitmapRequest {
+            request_id,
+            topic,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitBitmapRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+    ) -> Self {
+        Self::new(
+            request_id,
+            topic,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// The units which exist in the requested range, as offsets from `from_unit`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitBitmapResponse {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_info: TrackInfo,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub bitmap: UnitBitmap,
+}
+
+message_builder!(UnitBitmapResponse, UnitBitmapResponseBuilder {
+    required {
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+        bitmap: UnitBitmap,
+    }
+    optional {}
+    defaulted {}
+});
+
+impl UnitBitmapResponse {
+    pub fn new(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+        bitmap: UnitBitmap,
+    ) -> Self {
+        UnitBitmapResponse {
+            request_id,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+            bitmap,
+        }
+    }
+
+    /// Builds the bitmap from the existing units, the ones out of the range are skipped.
+    pub fn from_units(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+        units: Vec<i64>,
+    ) -> Self {
+        let bitmap = units
+            .into_iter()
+            .filter(|u| *u >= from_unit && *u <= to_unit)
+            .filter_map(|u| u32::try_from(u - from_unit).ok())
+            .collect();
+        Self::new(
+            request_id,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+            bitmap,
+        )
+    }
+
+    pub fn contains(&self, unit: i64) -> bool {
+        unit >= self.from_unit
+            && u32::try_from(unit - self.from_unit)
+                .map(|offset| self.bitmap.contains(offset))
+                .unwrap_or(false)
+    }
+
+    /// Recorded segments as unit ranges, both ends included; the gaps are between them.
+    pub fn segments(&self) -> Vec<(i64, i64)> {
+        self.bitmap
+            .runs()
+            .into_iter()
+            .map(|(first, last)| {
+                (
+                    self.from_unit + i64::from(first),
+                    self.from_unit + i64::from(last),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitBitmapResponse {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+        bitmap: UnitBitmap,
+    ) -> Self {
+        Self::new(
+            request_id,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+            bitmap,
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_units")]
+    fn py_from_units(
+        request_id: i64,
+        stream_name: StreamName,
+        track_info: TrackInfo,
+        from_unit: i64,
+        to_unit: i64,
+        units: Vec<i64>,
+    ) -> Self {
+        Self::from_units(
+            request_id,
+            stream_name,
+            track_info,
+            from_unit,
+            to_unit,
+            units,
+        )
+    }
+
+    #[pyo3(name = "contains")]
+    fn py_contains(&self, unit: i64) -> bool {
+        self.contains(unit)
+    }
+
+    #[pyo3(name = "segments")]
+    fn py_segments(&self) -> Vec<(i64, i64)> {
+        self.segments()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for UnitBitmapRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != UNIT_BITMAP_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(from_unit)), (_, Value::Long(to_unit)), (_, deadline_ms)] => {
+                    match TrackInfo::from_avro(track_info) {
+                        Some(track_info) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(UnitBitmapRequest {
+                                request_id: *request_id,
+                                topic: topic.clone(),
+                                stream_name: sn,
+                                track_info,
+                                from_unit: *from_unit,
+                                to_unit: *to_unit,
+                                deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                            })
+                        }
+                        None => {
+                            warn!("Unable to match AVRO Record to TrackInfo");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to UnitBitmapRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for UnitBitmapRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(UNIT_BITMAP_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("from_unit", Value::Long(self.from_unit));
+        obj.put("to_unit", Value::Long(self.to_unit));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(UNIT_BITMAP_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for UnitBitmapResponse {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != UNIT_BITMAP_RESPONSE_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::Bytes(stream_name)), (_, track_info), (_, Value::Long(from_unit)), (_, Value::Long(to_unit)), (_, Value::Bytes(bitmap))] => {
+                    match (
+                        TrackInfo::from_avro(track_info),
+                        UnitBitmap::from_bytes(bitmap),
+                    ) {
+                        (Some(track_info), Ok(bitmap)) => {
+                            let mut sn = StreamName::default();
+                            fill_byte_array(&mut sn, stream_name);
+                            Some(UnitBitmapResponse {
+                                request_id: *request_id,
+                                stream_name: sn,
+                                track_info,
+                                from_unit: *from_unit,
+                                to_unit: *to_unit,
+                                bitmap,
+                            })
+                        }
+                        (_, Err(e)) => {
+                            warn!("Unable to decode the unit bitmap. Error is {}", e);
+                            None
+                        }
+                        (None, _) => {
+                            warn!("Unable to match AVRO Record to TrackInfo");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to UnitBitmapResponse");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for UnitBitmapResponse {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(UNIT_BITMAP_RESPONSE_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("stream_name", Value::Bytes(self.stream_name.to_vec()));
+        obj.put("track", self.track_info.to_avro_record().ok()?);
+        obj.put("from_unit", Value::Long(self.from_unit));
+        obj.put("to_unit", Value::Long(self.to_unit));
+        obj.put("bitmap", Value::Bytes(self.bitmap.to_bytes()));
+        Some(ProtocolMessage {
+            schema: String::from(UNIT_BITMAP_RESPONSE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, StreamName, TrackInfo, TrackType};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    fn stream() -> (StreamName, TrackInfo) {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        (
+            pack_stream_name(&stream_uuid),
+            TrackInfo::new(TrackType::Video, track_name),
+        )
+    }
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let (stream_name, track_info) = stream();
+
+        let req = UnitBitmapRequest::new(
+            1,
+            String::from("response"),
+            stream_name,
+            track_info,
+            1000,
+            87_399,
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let new_req = UnitBitmapRequest::load(&mb.load_to_avro(req_serialized).unwrap()).unwrap();
+        assert_eq!(req, new_req);
+
+        let units = (1000..2000).chain(5000..87_400).chain(vec![10, 90_000]);
+        let rep = UnitBitmapResponse::from_units(
+            1,
+            stream_name,
+            track_info,
+            1000,
+            87_399,
+            units.collect(),
+        );
+        assert_eq!(rep.segments(), vec![(1000, 1999), (5000, 87_399)]);
+        assert!(rep.contains(1999));
+        assert!(!rep.contains(2000));
+        assert!(!rep.contains(10));
+
+        let rep_serialized = mb.save_from_avro(rep.save(&mb).unwrap()).unwrap();
+        let new_rep = UnitBitmapResponse::load(&mb.load_to_avro(rep_serialized).unwrap()).unwrap();
+        assert_eq!(rep, new_rep);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::avro::{Builder, ProtocolMessage, UNIT_ELEMENT_ACK_MESSAGE_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{optional_string_from_avro, optional_string_to_avro, ElementType, Unit};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum AckStatus {
+    Persisted,
+    Rejected,
+    RetryLater,
+    NotImplemented,
+}
+
+fn get_ack_status_avro(status: &AckStatus) -> Result<Value, String> {
+    Ok(match status {
+        AckStatus::Persisted => Value::Enum(0, "PERSISTED".into()),
+        AckStatus::Rejected => Value::Enum(1, "REJECTED".into()),
+        AckStatus::RetryLater => Value::Enum(2, "RETRY_LATER".into()),
+        AckStatus::NotImplemented => return Err(String::from("Not supported ack status")),
+    })
+}
+
+fn get_ack_status_enum(status: &str) -> AckStatus {
+    match status {
+        "PERSISTED" => AckStatus::Persisted,
+        "REJECTED" => AckStatus::Rejected,
+        "RETRY_LATER" => AckStatus::RetryLater,
+        _ => AckStatus::NotImplemented,
+    }
+}
+
+/// Confirms to the source that the media store has handled a unit element.
+///
+/// Elements which are `Rejected` must not be sent again, `RetryLater` ones may be
+/// retransmitted by the source.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitElementAckMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub element: ElementType,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub status: AckStatus,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error: Option<String>,
+}
+
+message_builder!(UnitElementAckMessage, UnitElementAckMessageBuilder {
+    required {
+        stream_unit: Unit,
+        element: ElementType,
+        status: AckStatus,
+    }
+    optional {
+        error: String,
+    }
+    defaulted {}
+});
+
+impl UnitElementAckMessage {
+    pub fn new(
+        stream_unit: Unit,
+        element: ElementType,
+        status: AckStatus,
+        error: Option<String>,
+    ) -> Self {
+        UnitElementAckMessage {
+            stream_unit,
+            element,
+            status,
+            error,
+        }
+    }
+
+    pub fn ack(stream_unit: Unit, element: ElementType) -> Self {
+        Self::new(stream_unit, element, AckStatus::Persisted, None)
+    }
+
+    pub fn nack(stream_unit: Unit, element: ElementType, error: String, retryable: bool) -> Self {
+        let status = if retryable {
+            AckStatus::RetryLater
+        } else {
+            AckStatus::Rejected
+        };
+        Self::new(stream_unit, element, status, Some(error))
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.status == AckStatus::RetryLater
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitElementAckMessage {
+    #[new]
+    fn py_new(
+        stream_unit: Unit,
+        element: ElementType,
+        status: AckStatus,
+        error: Option<String>,
+    ) -> Self {
+        Self::new(stream_unit, element, status, error)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "ack")]
+    fn py_ack(stream_unit: Unit, element: ElementType) -> Self {
+        Self::ack(stream_unit, element)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "nack")]
+    fn py_nack(stream_unit: Unit, element: ElementType, error: String, retryable: bool) -> Self {
+        Self::nack(stream_unit, element, error, retryable)
+    }
+
+    #[pyo3(name = "is_retryable")]
+    fn py_is_retryable(&self) -> bool {
+        self.is_retryable()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl FromProtocolMessage for UnitElementAckMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != UNIT_ELEMENT_ACK_MESSAGE_SCHEMA {
+            return None;
+        }
+
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Record(stream_unit_fields)), (_, Value::Long(element)), (_, Value::Enum(_, status)), (_, error)] => {
+                    match (
+                        stream_unit_fields.as_slice(),
+                        optional_string_from_avro(error),
+                    ) {
+                        (
+                            [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))],
+                            Some(error),
+                        ) => Some(UnitElementAckMessage {
+                            stream_unit: Unit::new(
+                                stream_name.clone(),
+                                track_name.clone(),
+                                track_type.clone(),
+                                *unit,
+                            ),
+                            element: *element as i16,
+                            status: get_ack_status_enum(status.as_str()),
+                            error,
+                        }),
+                        _ => {
+                            warn!("Unable to match AVRO Record to Unit");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to UnitElementAckMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for UnitElementAckMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(UNIT_ELEMENT_ACK_MESSAGE_SCHEMA)?;
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("element", Value::Long(self.element.into()));
+        obj.put("status", get_ack_status_avro(&self.status).ok()?);
+        obj.put("error", optional_string_to_avro(&self.error));
+
+        Some(ProtocolMessage {
+            schema: String::from(UNIT_ELEMENT_ACK_MESSAGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::unit_element_ack_message::{
+        AckStatus, UnitElementAckMessage,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let unit = Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        );
+
+        for msg in [
+            UnitElementAckMessage::ack(unit.clone(), 2),
+            UnitElementAckMessage::nack(unit.clone(), 2, String::from("disk is full"), true),
+        ] {
+            let msg_serialized = mb.save_from_avro(msg.save(&mb).unwrap()).unwrap();
+            let msg_envelope = mb.load_to_avro(msg_serialized).unwrap();
+            let new_msg = UnitElementAckMessage::load(&msg_envelope).unwrap();
+            assert_eq!(msg, new_msg);
+        }
+
+        let nack = UnitElementAckMessage::nack(unit, 2, String::from("bad element"), false);
+        assert_eq!(nack.status, AckStatus::Rejected);
+        assert!(!nack.is_retryable());
+    }
+}
@@ -0,0 +1,279 @@
+use crate::avro::{Builder, ProtocolMessage, UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA};
+use crate::checksum::crc32;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::{FromProtocolMessage, MessageBuildError, ToProtocolMessage};
+use crate::primitives::{
+    compression_literal_to_compression, get_compression_enum, Attributes, Compression, ElementType,
+    EncryptionInfo, Unit,
+};
+use crate::utils::{gen_hash_map, parse_hash_map};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// A part of a unit element which is too large to be sent as a single message.
+///
+/// `checksum` is the CRC-32 of the whole element value, the attributes are sent with
+/// the first chunk only.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitElementChunkMessage {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_unit: Unit,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub element: ElementType,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub chunk_index: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub total_chunks: i32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub checksum: u32,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub value: Vec<u8>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub compression: Compression,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub encryption: Option<EncryptionInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub is_keyframe: bool,
+}
+
+message_builder!(UnitElementChunkMessage, UnitElementChunkMessageBuilder {
+    required {
+        stream_unit: Unit,
+        element: ElementType,
+        chunk_index: i32,
+        total_chunks: i32,
+        checksum: u32,
+    }
+    optional {
+        encryption: EncryptionInfo,
+    }
+    defaulted {
+        value: Vec<u8>,
+        attributes: Attributes,
+        last: bool,
+        compression: Compression,
+        is_keyframe: bool,
+    }
+});
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitElementChunkMessage {
+    #[staticmethod]
+    pub fn fragment(
+        message: UnitElementMessage,
+        max_chunk: usize,
+    ) -> PyResult<Vec<UnitElementChunkMessage>> {
+        fragment_unit_element(&message, max_chunk).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Splits the element value into chunks of at most `max_chunk` bytes. An empty value
+/// still produces a single chunk.
+pub fn fragment_unit_element(
+    message: &UnitElementMessage,
+    max_chunk: usize,
+) -> Result<Vec<UnitElementChunkMessage>, MessageBuildError> {
+    if max_chunk == 0 {
+        return Err(MessageBuildError::Invalid("Chunk size must be positive"));
+    }
+    let total_chunks = message.value.len().div_ceil(max_chunk).max(1);
+    if i32::try_from(total_chunks).is_err() {
+        return Err(MessageBuildError::Invalid(
+            "Too many chunks for the chunk size",
+        ));
+    }
+    let checksum = crc32(&message.value);
+    Ok((0..total_chunks)
+        .map(|chunk_index| {
+            let from = chunk_index * max_chunk;
+            let to = (from + max_chunk).min(message.value.len());
+            UnitElementChunkMessage {
+                stream_unit: message.stream_unit.clone(),
+                element: message.element,
+                chunk_index: chunk_index as i32,
+                total_chunks: total_chunks as i32,
+                checksum,
+                value: message.value[from..to].to_vec(),
+                attributes: if chunk_index == 0 {
+                    message.attributes.clone()
+                } else {
+                    HashMap::default()
+                },
+                last: message.last,
+                compression: message.compression,
+                encryption: message.encryption.clone(),
+                is_keyframe: message.is_keyframe,
+            }
+        })
+        .collect())
+}
+
+impl FromProtocolMessage for UnitElementChunkMessage {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA {
+            return None;
+        }
+
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Record(stream_unit_fields)), (_, Value::Long(element)), (_, Value::Int(chunk_index)), (_, Value::Int(total_chunks)), (_, Value::Long(checksum)), (_, Value::Bytes(value)), (_, Value::Map(attributes)), (_, Value::Boolean(last)), (_, Value::Enum(_, compression)), (_, encryption), (_, Value::Boolean(is_keyframe))] =>
+                {
+                    match stream_unit_fields.as_slice() {
+                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] =>
+                        {
+                            // chunks are decompressed by the reassembler once the whole
+                            // value is collected
+                            Some(UnitElementChunkMessage {
+                                stream_unit: Unit::new(
+                                    stream_name.clone(),
+                                    track_name.clone(),
+                                    track_type.clone(),
+                                    *unit,
+                                ),
+                                element: *element as i16,
+                                chunk_index: *chunk_index,
+                                total_chunks: *total_chunks,
+                                checksum: *checksum as u32,
+                                value: value.clone(),
+                                attributes: parse_hash_map(attributes),
+                                last: *last,
+                                compression: compression_literal_to_compression(
+                                    compression.as_str(),
+                                ),
+                                encryption: EncryptionInfo::from_avro_union(encryption),
+                                is_keyframe: *is_keyframe,
+                            })
+                        }
+                        _ => {
+                            warn!("Unable to match AVRO Record to Unit");
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to UnitElementChunkMessage");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for UnitElementChunkMessage {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA)?;
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
+        obj.put("element", Value::Long(self.element.into()));
+        obj.put("chunk_index", Value::Int(self.chunk_index));
+        obj.put("total_chunks", Value::Int(self.total_chunks));
+        obj.put("checksum", Value::Long(self.checksum.into()));
+        obj.put("value", Value::Bytes(self.value.clone()));
+        obj.put("attributes", gen_hash_map(&self.attributes));
+        obj.put("last", Value::Boolean(self.last));
+        obj.put("compression", get_compression_enum(&self.compression).ok()?);
+        obj.put(
+            "encryption",
+            EncryptionInfo::to_avro_union(&self.encryption).ok()?,
+        );
+        obj.put("is_keyframe", Value::Boolean(self.is_keyframe));
+
+        Some(ProtocolMessage {
+            schema: String::from(UNIT_ELEMENT_CHUNK_MESSAGE_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::storage::unit_element_chunk_message::{
+        fragment_unit_element, UnitElementChunkMessage,
+    };
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::{FromProtocolMessage, MessageBuildError, ToProtocolMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn message(value: Vec<u8>) -> UnitElementMessage {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        UnitElementMessage::new(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            2,
+            value,
+            HashMap::from([("a".into(), "b".into())]),
+            true,
+        )
+    }
+
+    #[test]
+    fn test_fragment() {
+        let chunks = fragment_unit_element(&message(vec![1; 10]), 4).unwrap();
+        assert_eq!(
+            chunks.iter().map(|c| c.value.len()).collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        assert!(chunks.iter().all(|c| c.total_chunks == 3));
+        assert!(!chunks[0].attributes.is_empty());
+        assert!(chunks[1].attributes.is_empty());
+
+        assert_eq!(fragment_unit_element(&message(vec![]), 4).unwrap().len(), 1);
+        assert_eq!(
+            fragment_unit_element(&message(vec![1]), 0),
+            Err(MessageBuildError::Invalid("Chunk size must be positive"))
+        );
+    }
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let chunk = fragment_unit_element(&message(vec![1, 2, 3]), 2)
+            .unwrap()
+            .remove(0);
+
+        let chunk_serialized = mb.save_from_avro(chunk.save(&mb).unwrap()).unwrap();
+        let chunk_envelope = mb.load_to_avro(chunk_serialized).unwrap();
+        let new_chunk = UnitElementChunkMessage::load(&chunk_envelope).unwrap();
+
+        assert_eq!(chunk, new_chunk);
+    }
+}
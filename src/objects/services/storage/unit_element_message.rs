@@ -1,35 +1,68 @@
-use crate::avro::{Builder, ProtocolMessage, UNIT_ELEMENT_MESSAGE_SCHEMA};
+use crate::avro::{
+    Builder, DecodeLimits, ProtocolError, ProtocolMessage, UNIT_ELEMENT_MESSAGE_SCHEMA,
+};
+use crate::compression::{compress, decompress};
 use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-use crate::primitives::{ElementType, Unit};
-use crate::utils::value_to_string;
+use crate::primitives::{
+    compression_literal_to_compression, get_compression_enum, Attributes, Checksum, Compression,
+    ElementType, EncryptionInfo, Unit,
+};
+use crate::utils::{gen_hash_map, parse_hash_map};
 use avro_rs::types::Value;
 use log::warn;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use std::collections::HashMap;
 
-#[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct UnitElementMessage {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_unit: Unit,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub element: ElementType,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub value: Vec<u8>,
-    #[pyo3(get, set)]
-    pub attributes: HashMap<String, String>,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub last: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub compression: Compression,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub encryption: Option<EncryptionInfo>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub is_keyframe: bool,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub checksum: Option<Checksum>,
 }
 
-#[pymethods]
+message_builder!(UnitElementMessage, UnitElementMessageBuilder {
+    required {
+        stream_unit: Unit,
+        element: ElementType,
+    }
+    optional {
+        encryption: EncryptionInfo,
+        checksum: Checksum,
+    }
+    defaulted {
+        value: Vec<u8>,
+        attributes: Attributes,
+        last: bool,
+        compression: Compression,
+        is_keyframe: bool,
+    }
+});
+
 impl UnitElementMessage {
-    #[new]
     pub fn new(
         stream_unit: Unit,
         element: ElementType,
         value: Vec<u8>,
-        attributes: HashMap<String, String>,
+        attributes: Attributes,
         last: bool,
     ) -> Self {
         UnitElementMessage {
@@ -38,15 +71,70 @@ impl UnitElementMessage {
             value,
             attributes,
             last,
+            compression: Compression::Uncompressed,
+            encryption: None,
+            is_keyframe: false,
+            checksum: None,
         }
     }
 
+    pub fn compressed(
+        stream_unit: Unit,
+        element: ElementType,
+        value: Vec<u8>,
+        attributes: Attributes,
+        last: bool,
+        compression: Compression,
+    ) -> Result<Self, String> {
+        let value = compress(&compression, &value)?;
+        Ok(UnitElementMessage {
+            stream_unit,
+            element,
+            value,
+            attributes,
+            last,
+            compression,
+            encryption: None,
+            is_keyframe: false,
+            checksum: None,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitElementMessage {
+    #[new]
+    fn py_new(
+        stream_unit: Unit,
+        element: ElementType,
+        value: Vec<u8>,
+        attributes: Attributes,
+        last: bool,
+    ) -> Self {
+        Self::new(stream_unit, element, value, attributes, last)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "compressed")]
+    fn py_compressed(
+        stream_unit: Unit,
+        element: ElementType,
+        value: Vec<u8>,
+        attributes: Attributes,
+        last: bool,
+        compression: Compression,
+    ) -> PyResult<Self> {
+        Self::compressed(stream_unit, element, value, attributes, last, compression)
+            .map_err(PyValueError::new_err)
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
 
     fn __str__(&self) -> String {
-        self.__repr__()
+        self.to_string()
     }
 
     #[classattr]
@@ -54,19 +142,51 @@ impl UnitElementMessage {
 }
 
 impl FromProtocolMessage for UnitElementMessage {
+    /// See [`UnitElementMessage::load_with_limits`], with the default limits.
     fn load(message: &ProtocolMessage) -> Option<Self>
     where
         Self: Sized,
     {
+        Self::load_with_limits(message, &DecodeLimits::default())
+    }
+}
+
+impl UnitElementMessage {
+    /// The value is decompressed up to the `max_payload_size` of the limits, a larger
+    /// one doesn't load.
+    pub fn load_with_limits(message: &ProtocolMessage, limits: &DecodeLimits) -> Option<Self> {
         if message.schema != UNIT_ELEMENT_MESSAGE_SCHEMA {
             return None;
         }
 
         match &message.object {
             Value::Record(fields) => match fields.as_slice() {
-                [(_, Value::Record(stream_unit_fields)), (_, Value::Long(element)), (_, Value::Bytes(value)), (_, Value::Map(attributes)), (_, Value::Boolean(last))] => {
+                [(_, Value::Record(stream_unit_fields)), (_, Value::Long(element)), (_, Value::Bytes(value)), (_, Value::Map(attributes)), (_, Value::Boolean(last)), (_, Value::Enum(_, compression)), (_, encryption), (_, Value::Boolean(is_keyframe)), (_, checksum)] =>
+                {
                     match stream_unit_fields.as_slice() {
-                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] => {
+                        [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] =>
+                        {
+                            let compression =
+                                compression_literal_to_compression(compression.as_str());
+                            let encryption = EncryptionInfo::from_avro_union(encryption);
+                            // peers without codec support still get the raw bytes and
+                            // the compression marker to decide on their own
+                            let (value, compression) = if encryption.is_some() {
+                                warn!("Payload is kept encrypted, no decryption key is available");
+                                (value.clone(), compression)
+                            } else {
+                                match decompress(&compression, value, limits.max_payload_size) {
+                                    Ok(value) => (value, Compression::Uncompressed),
+                                    Err(e @ ProtocolError::LimitExceeded(_)) => {
+                                        warn!("Unable to load the unit element. Error is {}", e);
+                                        return None;
+                                    }
+                                    Err(e) => {
+                                        warn!("Payload is kept compressed. Error is {}", e);
+                                        (value.clone(), compression)
+                                    }
+                                }
+                            };
                             Some(UnitElementMessage {
                                 stream_unit: Unit::new(
                                     stream_name.clone(),
@@ -75,19 +195,13 @@ impl FromProtocolMessage for UnitElementMessage {
                                     *unit,
                                 ),
                                 element: *element as i16,
-                                value: value.clone(),
-                                attributes: attributes
-                                    .iter()
-                                    .map(|x| {
-                                        (
-                                            x.0.clone(),
-                                            value_to_string(x.1)
-                                                .or_else(|| Some(String::from("")))
-                                                .unwrap(),
-                                        )
-                                    })
-                                    .collect(),
+                                value,
+                                attributes: parse_hash_map(attributes),
                                 last: *last,
+                                compression,
+                                encryption,
+                                is_keyframe: *is_keyframe,
+                                checksum: Checksum::from_avro_union(checksum),
                             })
                         }
                         _ => {
@@ -111,20 +225,19 @@ impl FromProtocolMessage for UnitElementMessage {
 
 impl ToProtocolMessage for UnitElementMessage {
     fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
-        let mut obj = mb.get_record(UNIT_ELEMENT_MESSAGE_SCHEMA);
-        obj.put("stream_unit", self.stream_unit.to_avro_record());
+        let mut obj = mb.get_record(UNIT_ELEMENT_MESSAGE_SCHEMA)?;
+        obj.put("stream_unit", self.stream_unit.to_avro_record().ok()?);
         obj.put("element", Value::Long(self.element.into()));
         obj.put("value", Value::Bytes(self.value.clone()));
+        obj.put("attributes", gen_hash_map(&self.attributes));
+        obj.put("last", Value::Boolean(self.last));
+        obj.put("compression", get_compression_enum(&self.compression).ok()?);
         obj.put(
-            "attributes",
-            Value::Map(
-                self.attributes
-                    .iter()
-                    .map(|x| (x.0.clone(), Value::String(x.1.clone())))
-                    .collect(),
-            ),
+            "encryption",
+            EncryptionInfo::to_avro_union(&self.encryption).ok()?,
         );
-        obj.put("last", Value::Boolean(self.last));
+        obj.put("is_keyframe", Value::Boolean(self.is_keyframe));
+        obj.put("checksum", Checksum::to_avro_union(&self.checksum).ok()?);
 
         Some(ProtocolMessage {
             schema: String::from(UNIT_ELEMENT_MESSAGE_SCHEMA),
@@ -136,9 +249,12 @@ impl ToProtocolMessage for UnitElementMessage {
 #[cfg(test)]
 mod tests {
     use crate::avro::Builder;
+    use crate::checksum::{compute, ChecksumPolicy};
     use crate::objects::services::storage::unit_element_message::UnitElementMessage;
-    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
-    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::objects::{FromProtocolMessage, MessageBuildError, ToProtocolMessage};
+    use crate::primitives::{
+        pack_stream_name, pack_track_name, Checksum, ChecksumAlgorithm, Compression, Unit,
+    };
     use crate::utils::get_avro_path;
     use std::collections::HashMap;
     use uuid::Uuid;
@@ -151,7 +267,7 @@ mod tests {
         let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
         let stream_name = pack_stream_name(&stream_uuid);
 
-        let req = UnitElementMessage::new(
+        let mut req = UnitElementMessage::new(
             Unit::new(
                 stream_name.to_vec(),
                 track_name.to_vec(),
@@ -163,12 +279,13 @@ mod tests {
             HashMap::from([("a".into(), "b".into()), ("c".into(), "d".into())]),
             true,
         );
+        req.is_keyframe = true;
 
         let req_envelope_opt = req.save(&mb);
         assert!(req_envelope_opt.is_some());
 
         let req_envelope = req_envelope_opt.unwrap();
-        let req_serialized = mb.save_from_avro(req_envelope);
+        let req_serialized = mb.save_from_avro(req_envelope).unwrap();
 
         let req_envelope_opt = mb.load_to_avro(req_serialized);
         assert!(req_envelope_opt.is_some());
@@ -183,4 +300,148 @@ mod tests {
 
         assert_eq!(req, new_req);
     }
+
+    #[test]
+    fn test_builder() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let unit = Unit::new(vec![1; 16], b"test".to_vec(), String::from("VIDEO"), 3);
+        let attributes = HashMap::from([("a".into(), "b".into())]);
+
+        let built = UnitElementMessage::builder()
+            .stream_unit(unit.clone())
+            .element(2)
+            .value(vec![0, 1])
+            .attributes(attributes.clone())
+            .last(true)
+            .try_into_message()
+            .unwrap();
+        assert_eq!(
+            built,
+            UnitElementMessage::new(unit.clone(), 2, vec![0, 1], attributes, true)
+        );
+        assert_eq!(
+            UnitElementMessage::builder()
+                .stream_unit(unit.clone())
+                .element(2)
+                .build(&mb),
+            UnitElementMessage::new(unit.clone(), 2, vec![], HashMap::default(), false)
+                .save(&mb)
+                .ok_or(MessageBuildError::Save)
+        );
+        assert_eq!(
+            UnitElementMessage::builder().element(2).build(&mb).err(),
+            Some(MessageBuildError::Missing("stream_unit"))
+        );
+        assert_eq!(
+            UnitElementMessage::builder()
+                .stream_unit(unit)
+                .element(2)
+                .compression(Compression::NotImplemented)
+                .build(&mb)
+                .err(),
+            Some(MessageBuildError::Save)
+        );
+    }
+
+    #[test]
+    fn test_load_save_encrypted() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        mb.set_encryption_keys("k1", HashMap::from([("k1".into(), vec![7; 32])]));
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let req = UnitElementMessage::new(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            2,
+            vec![0, 1],
+            HashMap::default(),
+            true,
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+
+        let new_req = UnitElementMessage::load(&mb.load_to_avro(req_serialized.clone()).unwrap());
+        assert_eq!(new_req.unwrap(), req);
+
+        let plain_mb = Builder::new(get_avro_path().as_str());
+        let new_req =
+            UnitElementMessage::load(&plain_mb.load_to_avro(req_serialized).unwrap()).unwrap();
+        assert_ne!(new_req.value, req.value);
+        assert_eq!(new_req.encryption.unwrap().key_id, "k1");
+    }
+
+    #[test]
+    fn test_load_save_checksum() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        mb.set_checksums(Some(ChecksumAlgorithm::Crc32c), ChecksumPolicy::Fail);
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+
+        let mut req = UnitElementMessage::new(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            2,
+            vec![0, 1],
+            HashMap::default(),
+            true,
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let new_req = UnitElementMessage::load(&mb.load_to_avro(req_serialized).unwrap()).unwrap();
+        assert_eq!(
+            new_req.checksum,
+            Some(compute(ChecksumAlgorithm::Crc32c, &[0, 1]).unwrap())
+        );
+
+        req.checksum = Some(Checksum::new(ChecksumAlgorithm::Crc32c, 1));
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        assert!(mb.load_to_avro(req_serialized.clone()).is_none());
+
+        mb.set_checksums(None, ChecksumPolicy::Warn);
+        assert!(mb.load_to_avro(req_serialized).is_some());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_load_save_compressed() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let value = vec![1; 1024];
+
+        let req = UnitElementMessage::compressed(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            2,
+            value.clone(),
+            HashMap::default(),
+            true,
+            Compression::Zstd,
+        )
+        .unwrap();
+        assert!(req.value.len() < value.len());
+
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let new_req = UnitElementMessage::load(&mb.load_to_avro(req_serialized).unwrap()).unwrap();
+
+        assert_eq!(new_req.value, value);
+        assert_eq!(new_req.compression, Compression::Uncompressed);
+    }
 }
@@ -0,0 +1,243 @@
+use crate::avro::{Builder, ProtocolMessage, NODE_TELEMETRY_SCHEMA};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Metrics a node publishes periodically: counters only grow between the restarts
+/// of the node, gauges are the current values, e.g. the queue lengths.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct NodeTelemetry {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub node_id: String,
+    /// What the node does, e.g. `camera`, `store` or `transcoder`.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub role: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub counters: HashMap<String, i64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub gauges: HashMap<String, f64>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub collected_ms: u64,
+}
+
+message_builder!(NodeTelemetry, NodeTelemetryBuilder {
+    required {
+        node_id: String,
+        role: String,
+        collected_ms: u64,
+    }
+    optional {}
+    defaulted {
+        counters: HashMap<String, i64>,
+        gauges: HashMap<String, f64>,
+    }
+});
+
+impl NodeTelemetry {
+    pub fn new(node_id: String, role: String, collected_ms: u64) -> Self {
+        NodeTelemetry {
+            node_id,
+            role,
+            counters: HashMap::default(),
+            gauges: HashMap::default(),
+            collected_ms,
+        }
+    }
+
+    pub fn set_counter(&mut self, name: String, value: i64) {
+        self.counters.insert(name, value);
+    }
+
+    pub fn set_gauge(&mut self, name: String, value: f64) {
+        self.gauges.insert(name, value);
+    }
+
+    /// Growth of the counters since the previous report of the node; a counter
+    /// which went down means a restart, its whole value is the growth then.
+    pub fn counter_deltas(&self, previous: NodeTelemetry) -> HashMap<String, i64> {
+        self.counters
+            .iter()
+            .map(|(name, value)| {
+                let delta = match previous.counters.get(name) {
+                    Some(before) if before <= value => value - before,
+                    _ => *value,
+                };
+                (name.clone(), delta)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NodeTelemetry {
+    #[new]
+    fn py_new(node_id: String, role: String, collected_ms: u64) -> Self {
+        Self::new(node_id, role, collected_ms)
+    }
+
+    #[pyo3(name = "set_counter")]
+    fn py_set_counter(&mut self, name: String, value: i64) {
+        self.set_counter(name, value)
+    }
+
+    #[pyo3(name = "set_gauge")]
+    fn py_set_gauge(&mut self, name: String, value: f64) {
+        self.set_gauge(name, value)
+    }
+
+    #[pyo3(name = "counter_deltas")]
+    fn py_counter_deltas(&self, previous: NodeTelemetry) -> HashMap<String, i64> {
+        self.counter_deltas(previous)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl NodeTelemetry {
+    pub fn with_counter(mut self, name: &str, value: i64) -> Self {
+        self.set_counter(String::from(name), value);
+        self
+    }
+
+    pub fn with_gauge(mut self, name: &str, value: f64) -> Self {
+        self.set_gauge(String::from(name), value);
+        self
+    }
+}
+
+impl ToProtocolMessage for NodeTelemetry {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(NODE_TELEMETRY_SCHEMA)?;
+        obj.put("node_id", Value::String(self.node_id.clone()));
+        obj.put("role", Value::String(self.role.clone()));
+        obj.put(
+            "counters",
+            Value::Map(
+                self.counters
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Value::Long(*v)))
+                    .collect(),
+            ),
+        );
+        obj.put(
+            "gauges",
+            Value::Map(
+                self.gauges
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Value::Double(*v)))
+                    .collect(),
+            ),
+        );
+        obj.put("collected_ms", Value::Long(self.collected_ms as i64));
+        Some(ProtocolMessage {
+            schema: String::from(NODE_TELEMETRY_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for NodeTelemetry {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != NODE_TELEMETRY_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(node_id)), (_, Value::String(role)), (_, Value::Map(counters)), (_, Value::Map(gauges)), (_, Value::Long(collected_ms))] =>
+                {
+                    let counters_parsed: HashMap<String, i64> = counters
+                        .iter()
+                        .filter_map(|(k, v)| match v {
+                            Value::Long(v) => Some((k.clone(), *v)),
+                            _ => None,
+                        })
+                        .collect();
+                    let gauges_parsed: HashMap<String, f64> = gauges
+                        .iter()
+                        .filter_map(|(k, v)| match v {
+                            Value::Double(v) => Some((k.clone(), *v)),
+                            _ => None,
+                        })
+                        .collect();
+                    if counters_parsed.len() < counters.len() || gauges_parsed.len() < gauges.len()
+                    {
+                        warn!("Not all metric values are parsed well.");
+                        return None;
+                    }
+                    Some(NodeTelemetry {
+                        node_id: node_id.clone(),
+                        role: role.clone(),
+                        counters: counters_parsed,
+                        gauges: gauges_parsed,
+                        collected_ms: *collected_ms as u64,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to NodeTelemetry");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::telemetry::NodeTelemetry;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let msg = NodeTelemetry::new(String::from("store-1"), String::from("store"), 1_000)
+            .with_counter("units_saved", 120)
+            .with_counter("requests", 7)
+            .with_gauge("disk_used_ratio", 0.42);
+
+        let envelope = msg.save(&mb).unwrap();
+        let serialized = mb.save_from_avro(envelope).unwrap();
+        let envelope = mb.load_to_avro(serialized).unwrap();
+        let new_msg = NodeTelemetry::load(&envelope).unwrap();
+        assert_eq!(msg, new_msg);
+
+        let next = NodeTelemetry {
+            collected_ms: 2_000,
+            ..new_msg.clone()
+        }
+        .with_counter("units_saved", 150)
+        .with_counter("requests", 2);
+        assert_eq!(
+            next.counter_deltas(new_msg),
+            HashMap::from([
+                (String::from("units_saved"), 30),
+                (String::from("requests"), 2)
+            ])
+        );
+    }
+}
@@ -0,0 +1,553 @@
+use crate::avro::{
+    Builder, ProtocolMessage, SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA,
+    SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA, SERVICES_TRANSCODE_JOB_STATUS_SCHEMA,
+};
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{
+    optional_string_from_avro, optional_string_to_avro, optional_u64_from_avro,
+    optional_u64_to_avro, Attributes, UnitRange,
+};
+use crate::utils::{gen_hash_map, parse_hash_map};
+use avro_rs::types::Value;
+use log::warn;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum TranscodeJobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    NotImplemented,
+}
+
+impl TranscodeJobState {
+    /// The job won't report again once it is in one of these states.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TranscodeJobState::Completed | TranscodeJobState::Failed | TranscodeJobState::Cancelled
+        )
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TranscodeJobState {
+    #[pyo3(name = "is_terminal")]
+    fn py_is_terminal(&self) -> bool {
+        self.is_terminal()
+    }
+}
+
+fn get_transcode_job_state_avro(state: &TranscodeJobState) -> Result<Value, String> {
+    Ok(match state {
+        TranscodeJobState::Queued => Value::Enum(0, "QUEUED".into()),
+        TranscodeJobState::Running => Value::Enum(1, "RUNNING".into()),
+        TranscodeJobState::Completed => Value::Enum(2, "COMPLETED".into()),
+        TranscodeJobState::Failed => Value::Enum(3, "FAILED".into()),
+        TranscodeJobState::Cancelled => Value::Enum(4, "CANCELLED".into()),
+        TranscodeJobState::NotImplemented => {
+            return Err(String::from("Not supported transcode job state"))
+        }
+    })
+}
+
+fn get_transcode_job_state_enum(state: &str) -> TranscodeJobState {
+    match state {
+        "QUEUED" => TranscodeJobState::Queued,
+        "RUNNING" => TranscodeJobState::Running,
+        "COMPLETED" => TranscodeJobState::Completed,
+        "FAILED" => TranscodeJobState::Failed,
+        "CANCELLED" => TranscodeJobState::Cancelled,
+        _ => TranscodeJobState::NotImplemented,
+    }
+}
+
+/// Submits a job to the transcoding service: `source` is transcoded with the named
+/// `profile` and written to `output`.
+///
+/// The service answers with [`TranscodeJobStatus`] messages carrying the `job_id` it
+/// assigned, until the job reaches a terminal state.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TranscodeJobRequest {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub source: String,
+    /// Stored footage to transcode; when set, `source` is ignored by the service.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub source_range: Option<UnitRange>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub profile: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub output: String,
+    /// Options overriding the ones of the profile.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub options: Attributes,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(TranscodeJobRequest, TranscodeJobRequestBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        source: String,
+        profile: String,
+        output: String,
+    }
+    optional {
+        source_range: UnitRange,
+        deadline_ms: u64,
+    }
+    defaulted {
+        options: Attributes,
+    }
+});
+
+impl TranscodeJobRequest {
+    pub fn new(
+        request_id: i64,
+        topic: String,
+        source: String,
+        profile: String,
+        output: String,
+        options: Attributes,
+    ) -> Self {
+        TranscodeJobRequest {
+            request_id,
+            topic,
+            source,
+            source_range: None,
+            profile,
+            output,
+            options,
+            deadline_ms: None,
+        }
+    }
+
+    pub fn from_range(
+        request_id: i64,
+        topic: String,
+        source_range: UnitRange,
+        profile: String,
+        output: String,
+        options: Attributes,
+    ) -> Self {
+        TranscodeJobRequest {
+            source_range: Some(source_range),
+            ..Self::new(
+                request_id,
+                topic,
+                String::default(),
+                profile,
+                output,
+                options,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TranscodeJobRequest {
+    #[new]
+    fn py_new(
+        request_id: i64,
+        topic: String,
+        source: String,
+        profile: String,
+        output: String,
+        options: Attributes,
+    ) -> Self {
+        Self::new(request_id, topic, source, profile, output, options)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_range")]
+    fn py_from_range(
+        request_id: i64,
+        topic: String,
+        source_range: UnitRange,
+        profile: String,
+        output: String,
+        options: Attributes,
+    ) -> Self {
+        Self::from_range(request_id, topic, source_range, profile, output, options)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// State of a transcoding job, sent when it is accepted, while it runs and once it
+/// is over. `progress` goes from `0.0` to `1.0`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TranscodeJobStatus {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub job_id: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub state: TranscodeJobState,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub progress: f64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub error: Option<String>,
+}
+
+message_builder!(TranscodeJobStatus, TranscodeJobStatusBuilder {
+    required {
+        request_id: i64,
+        job_id: String,
+        state: TranscodeJobState,
+        progress: f64,
+    }
+    optional {
+        error: String,
+    }
+    defaulted {}
+});
+
+impl TranscodeJobStatus {
+    pub fn new(request_id: i64, job_id: String, state: TranscodeJobState, progress: f64) -> Self {
+        TranscodeJobStatus {
+            request_id,
+            job_id,
+            state,
+            progress,
+            error: None,
+        }
+    }
+
+    pub fn failed(request_id: i64, job_id: String, progress: f64, error: String) -> Self {
+        TranscodeJobStatus {
+            error: Some(error),
+            ..Self::new(request_id, job_id, TranscodeJobState::Failed, progress)
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TranscodeJobStatus {
+    #[new]
+    fn py_new(request_id: i64, job_id: String, state: TranscodeJobState, progress: f64) -> Self {
+        Self::new(request_id, job_id, state, progress)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "failed")]
+    fn py_failed(request_id: i64, job_id: String, progress: f64, error: String) -> Self {
+        Self::failed(request_id, job_id, progress, error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+/// Stops a queued or running job, confirmed with a `CANCELLED` job status.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TranscodeJobCancel {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub request_id: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub topic: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub job_id: String,
+    /// Absolute epoch time after which the request is stale and may be dropped.
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub deadline_ms: Option<u64>,
+}
+
+message_builder!(TranscodeJobCancel, TranscodeJobCancelBuilder {
+    required {
+        request_id: i64,
+        topic: String,
+        job_id: String,
+    }
+    optional {
+        deadline_ms: u64,
+    }
+    defaulted {}
+});
+
+impl TranscodeJobCancel {
+    pub fn new(request_id: i64, topic: String, job_id: String) -> Self {
+        TranscodeJobCancel {
+            request_id,
+            topic,
+            job_id,
+            deadline_ms: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TranscodeJobCancel {
+    #[new]
+    fn py_new(request_id: i64, topic: String, job_id: String) -> Self {
+        Self::new(request_id, topic, job_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ToProtocolMessage for TranscodeJobRequest {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("source", Value::String(self.source.clone()));
+        obj.put(
+            "source_range",
+            Value::Union(Box::new(match &self.source_range {
+                Some(source_range) => source_range.to_avro_record().ok()?,
+                None => Value::Null,
+            })),
+        );
+        obj.put("profile", Value::String(self.profile.clone()));
+        obj.put("output", Value::String(self.output.clone()));
+        obj.put("options", gen_hash_map(&self.options));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for TranscodeJobRequest {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_TRANSCODE_JOB_REQUEST_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(source)), (_, Value::Union(source_range)), (_, Value::String(profile)), (_, Value::String(output)), (_, Value::Map(options)), (_, deadline_ms)] =>
+                {
+                    let source_range = match source_range.as_ref() {
+                        Value::Null => None,
+                        source_range => Some(UnitRange::from_avro(source_range)?),
+                    };
+                    Some(TranscodeJobRequest {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        source: source.clone(),
+                        source_range,
+                        profile: profile.clone(),
+                        output: output.clone(),
+                        options: parse_hash_map(options),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to TranscodeJobRequest");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for TranscodeJobStatus {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_TRANSCODE_JOB_STATUS_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("job_id", Value::String(self.job_id.clone()));
+        obj.put("state", get_transcode_job_state_avro(&self.state).ok()?);
+        obj.put("progress", Value::Double(self.progress));
+        obj.put("error", optional_string_to_avro(&self.error));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_TRANSCODE_JOB_STATUS_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for TranscodeJobStatus {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_TRANSCODE_JOB_STATUS_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(job_id)), (_, Value::Enum(_, state)), (_, Value::Double(progress)), (_, error)] => {
+                    Some(TranscodeJobStatus {
+                        request_id: *request_id,
+                        job_id: job_id.clone(),
+                        state: get_transcode_job_state_enum(state),
+                        progress: *progress,
+                        error: optional_string_from_avro(error)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to TranscodeJobStatus");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+impl ToProtocolMessage for TranscodeJobCancel {
+    fn save(&self, mb: &Builder) -> Option<ProtocolMessage> {
+        let mut obj = mb.get_record(SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA)?;
+        obj.put("request_id", Value::Long(self.request_id));
+        obj.put("topic", Value::String(self.topic.clone()));
+        obj.put("job_id", Value::String(self.job_id.clone()));
+        obj.put("deadline_ms", optional_u64_to_avro(&self.deadline_ms));
+        Some(ProtocolMessage {
+            schema: String::from(SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA),
+            object: Value::from(obj),
+        })
+    }
+}
+
+impl FromProtocolMessage for TranscodeJobCancel {
+    fn load(message: &ProtocolMessage) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if message.schema != SERVICES_TRANSCODE_JOB_CANCEL_SCHEMA {
+            return None;
+        }
+        match &message.object {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(request_id)), (_, Value::String(topic)), (_, Value::String(job_id)), (_, deadline_ms)] => {
+                    Some(TranscodeJobCancel {
+                        request_id: *request_id,
+                        topic: topic.clone(),
+                        job_id: job_id.clone(),
+                        deadline_ms: optional_u64_from_avro(deadline_ms)?,
+                    })
+                }
+                _ => {
+                    warn!("Unable to match AVRO Record to to TranscodeJobCancel");
+                    None
+                }
+            },
+            _ => {
+                warn!("Unable to match AVRO Record.");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::transcode::{
+        TranscodeJobCancel, TranscodeJobRequest, TranscodeJobState, TranscodeJobStatus,
+    };
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{AttributeValue, UnitRange};
+    use crate::utils::get_avro_path;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_load_save() {
+        let mb = Builder::new(get_avro_path().as_str());
+
+        let req = TranscodeJobRequest::new(
+            1,
+            String::from("response"),
+            String::from("rtsp://camera/stream"),
+            String::from("h264-720p"),
+            String::from("s3://bucket/out.mp4"),
+            HashMap::from([(String::from("crf"), AttributeValue::I64(23))]),
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(TranscodeJobRequest::load(&req_envelope).unwrap(), req);
+
+        let req = TranscodeJobRequest::from_range(
+            2,
+            String::from("response"),
+            UnitRange::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), 100, 200),
+            String::from("h264-720p"),
+            String::from("s3://bucket/out.mp4"),
+            HashMap::default(),
+        );
+        let req_serialized = mb.save_from_avro(req.save(&mb).unwrap()).unwrap();
+        let req_envelope = mb.load_to_avro(req_serialized).unwrap();
+        assert_eq!(TranscodeJobRequest::load(&req_envelope).unwrap(), req);
+
+        let status =
+            TranscodeJobStatus::new(1, String::from("job-1"), TranscodeJobState::Running, 0.5);
+        let status_serialized = mb.save_from_avro(status.save(&mb).unwrap()).unwrap();
+        let status_envelope = mb.load_to_avro(status_serialized).unwrap();
+        assert_eq!(TranscodeJobStatus::load(&status_envelope).unwrap(), status);
+
+        let status = TranscodeJobStatus::failed(
+            1,
+            String::from("job-1"),
+            0.5,
+            String::from("Encoder h264_nvenc not found"),
+        );
+        assert!(status.state.is_terminal());
+        let status_serialized = mb.save_from_avro(status.save(&mb).unwrap()).unwrap();
+        let status_envelope = mb.load_to_avro(status_serialized).unwrap();
+        assert_eq!(TranscodeJobStatus::load(&status_envelope).unwrap(), status);
+
+        let cancel = TranscodeJobCancel::new(3, String::from("response"), String::from("job-1"));
+        let cancel_serialized = mb.save_from_avro(cancel.save(&mb).unwrap()).unwrap();
+        let cancel_envelope = mb.load_to_avro(cancel_serialized).unwrap();
+        assert_eq!(TranscodeJobCancel::load(&cancel_envelope).unwrap(), cancel);
+    }
+}
@@ -0,0 +1,110 @@
+/// Requests which the store may answer with several pages.
+pub trait PagedRequest: Sized {
+    type Response;
+
+    /// The follow-up request for the page after `response`, `None` when it was the last one.
+    fn next_page(&self, response: &Self::Response) -> Option<Self>;
+}
+
+/// Iterates over all pages of a response, issuing the follow-up requests with `fetch`.
+///
+/// Iteration stops after the last page or when `fetch` returns `None`, e.g. because
+/// the response did not arrive in time.
+pub struct ResponsePager<R, F> {
+    next: Option<R>,
+    fetch: F,
+}
+
+impl<R, F> ResponsePager<R, F>
+where
+    R: PagedRequest,
+    F: FnMut(&R) -> Option<R::Response>,
+{
+    pub fn new(request: R, fetch: F) -> Self {
+        ResponsePager {
+            next: Some(request),
+            fetch,
+        }
+    }
+
+    /// The request the next page is fetched with.
+    pub fn pending(&self) -> Option<&R> {
+        self.next.as_ref()
+    }
+}
+
+impl<R, F> Iterator for ResponsePager<R, F>
+where
+    R: PagedRequest,
+    F: FnMut(&R) -> Option<R::Response>,
+{
+    type Item = R::Response;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let request = self.next.take()?;
+        let response = (self.fetch)(&request)?;
+        self.next = request.next_page(&response);
+        Some(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::services::storage::stream_track_unit_elements::{
+        StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+    };
+    use crate::pager::ResponsePager;
+    use crate::primitives::{pack_stream_name, pack_track_name, Payload, Unit};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_pages() {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let unit = Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        );
+        let payloads: Vec<Payload> = (0..5u8)
+            .map(|i| Payload::new(vec![i], HashMap::default()))
+            .collect();
+
+        let req = StreamTrackUnitElementsRequest::paged(1, String::from("response"), unit, 10, 2);
+        let pager = ResponsePager::new(req, |req: &StreamTrackUnitElementsRequest| {
+            let from = req
+                .continuation_cursor
+                .as_ref()
+                .map(|c| c[0] as usize)
+                .unwrap_or_default();
+            let to = (from + req.max_results as usize).min(payloads.len());
+            let values = payloads[from..to].to_vec();
+            // follow-up pages must read the view the first page was served from
+            assert_eq!(req.snapshot_token.is_some(), from > 0);
+            let mut rep = if to < payloads.len() {
+                StreamTrackUnitElementsResponse::partial(
+                    req.request_id,
+                    req.stream_unit.clone(),
+                    values,
+                    vec![to as u8],
+                )
+            } else {
+                StreamTrackUnitElementsResponse::new(
+                    req.request_id,
+                    req.stream_unit.clone(),
+                    values,
+                )
+            };
+            rep.snapshot_token = Some(req.snapshot_token.clone().unwrap_or_else(|| vec![7]));
+            Some(rep)
+        });
+
+        let pages: Vec<_> = pager.collect();
+        assert_eq!(pages.len(), 3);
+        let values: Vec<_> = pages.into_iter().flat_map(|p| p.values).collect();
+        assert_eq!(values, payloads);
+    }
+}
@@ -1,6 +1,11 @@
-use crate::utils::fill_byte_array;
+use crate::utils::{fill_byte_array, gen_hash_map, parse_hash_map, value_to_string};
 use avro_rs::types::Value;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyTypeError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBool, PyBytes, PyFloat, PyList, PyLong, PyString};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use uuid::Uuid;
@@ -13,10 +18,13 @@ pub type TrackName = [u8; TRACK_NAME_MAX_LENGTH];
 pub type ElementType = i16;
 
 #[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
 pub enum TrackType {
     Video,
     Meta,
+    Audio,
+    Subtitle,
     NotImplemented,
 }
 
@@ -26,20 +34,685 @@ impl Default for TrackType {
     }
 }
 
+/// Typed value of payload and service attributes, mapped to native Python values.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum AttributeValue {
+    String(String),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    StringList(Vec<String>),
+}
+
+impl AttributeValue {
+    pub fn to_avro(&self) -> Value {
+        Value::Union(Box::new(match self {
+            AttributeValue::String(v) => Value::String(v.clone()),
+            AttributeValue::I64(v) => Value::Long(*v),
+            AttributeValue::F64(v) => Value::Double(*v),
+            AttributeValue::Bool(v) => Value::Boolean(*v),
+            AttributeValue::Bytes(v) => Value::Bytes(v.clone()),
+            AttributeValue::StringList(v) => {
+                Value::Array(v.iter().map(|s| Value::String(s.clone())).collect())
+            }
+        }))
+    }
+
+    /// Plain strings are accepted as well to read maps produced by older peers.
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Union(inner) => Self::from_avro(inner),
+            Value::String(v) => Some(AttributeValue::String(v.clone())),
+            Value::Long(v) => Some(AttributeValue::I64(*v)),
+            Value::Double(v) => Some(AttributeValue::F64(*v)),
+            Value::Boolean(v) => Some(AttributeValue::Bool(*v)),
+            Value::Bytes(v) => Some(AttributeValue::Bytes(v.clone())),
+            Value::Array(values) => {
+                let strings: Vec<String> = values.iter().filter_map(value_to_string).collect();
+                if strings.len() == values.len() {
+                    Some(AttributeValue::StringList(strings))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::String(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            AttributeValue::I64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttributeValue::F64(v) => Some(*v),
+            AttributeValue::I64(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(v: &str) -> Self {
+        AttributeValue::String(String::from(v))
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(v: String) -> Self {
+        AttributeValue::String(v)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(v: i64) -> Self {
+        AttributeValue::I64(v)
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(v: f64) -> Self {
+        AttributeValue::F64(v)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(v: bool) -> Self {
+        AttributeValue::Bool(v)
+    }
+}
+
+impl From<Vec<u8>> for AttributeValue {
+    fn from(v: Vec<u8>) -> Self {
+        AttributeValue::Bytes(v)
+    }
+}
+
+impl From<Vec<String>> for AttributeValue {
+    fn from(v: Vec<String>) -> Self {
+        AttributeValue::StringList(v)
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'source> FromPyObject<'source> for AttributeValue {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if ob.is_instance_of::<PyBool>()? {
+            Ok(AttributeValue::Bool(ob.extract()?))
+        } else if ob.is_instance_of::<PyLong>()? {
+            Ok(AttributeValue::I64(ob.extract()?))
+        } else if ob.is_instance_of::<PyFloat>()? {
+            Ok(AttributeValue::F64(ob.extract()?))
+        } else if ob.is_instance_of::<PyString>()? {
+            Ok(AttributeValue::String(ob.extract()?))
+        } else if ob.is_instance_of::<PyBytes>()? {
+            Ok(AttributeValue::Bytes(ob.extract()?))
+        } else if ob.is_instance_of::<PyList>()? {
+            Ok(AttributeValue::StringList(ob.extract()?))
+        } else {
+            Err(PyTypeError::new_err(
+                "Attribute value must be one of str, int, float, bool, bytes or list[str]",
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for AttributeValue {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            AttributeValue::String(v) => v.into_py(py),
+            AttributeValue::I64(v) => v.into_py(py),
+            AttributeValue::F64(v) => v.into_py(py),
+            AttributeValue::Bool(v) => v.into_py(py),
+            AttributeValue::Bytes(v) => PyBytes::new(py, &v).into(),
+            AttributeValue::StringList(v) => v.into_py(py),
+        }
+    }
+}
+
+pub type Attributes = HashMap<String, AttributeValue>;
+
+/// Envelope labels used by brokers and routers, e.g. region or tenant.
+pub type Labels = HashMap<String, AttributeValue>;
+
+pub const MAX_LABELS: usize = 16;
+pub const MAX_LABEL_KEY_LENGTH: usize = 64;
+pub const MAX_LABEL_VALUE_LENGTH: usize = 256;
+
+pub fn validate_labels(labels: &Labels) -> Result<(), String> {
+    if labels.len() > MAX_LABELS {
+        return Err(format!(
+            "Too many labels: {}, at most {} are allowed",
+            labels.len(),
+            MAX_LABELS
+        ));
+    }
+    for (key, value) in labels {
+        if key.is_empty() || key.len() > MAX_LABEL_KEY_LENGTH {
+            return Err(format!(
+                "Label key `{}` must be 1 to {} bytes long",
+                key, MAX_LABEL_KEY_LENGTH
+            ));
+        }
+        let value_len = match value {
+            AttributeValue::String(v) => v.len(),
+            AttributeValue::Bytes(v) => v.len(),
+            AttributeValue::StringList(v) => v.iter().map(|s| s.len()).sum(),
+            _ => 0,
+        };
+        if value_len > MAX_LABEL_VALUE_LENGTH {
+            return Err(format!(
+                "Value of label `{}` is {} bytes long, at most {} are allowed",
+                key, value_len, MAX_LABEL_VALUE_LENGTH
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Opaque position in a paged result set, produced by the store and echoed back
+/// in the follow-up request.
+pub type ContinuationCursor = Vec<u8>;
+
+/// Opaque view of the store a query was served from. Paged requests which carry
+/// the token read the same view, so units written meanwhile neither shift nor
+/// duplicate the pages.
+pub type SnapshotToken = Vec<u8>;
+
+pub fn optional_bytes_to_avro(value: &Option<Vec<u8>>) -> Value {
+    Value::Union(Box::new(match value {
+        Some(value) => Value::Bytes(value.clone()),
+        None => Value::Null,
+    }))
+}
+
+pub fn optional_bytes_from_avro(value: &Value) -> Option<Option<Vec<u8>>> {
+    match value {
+        Value::Union(inner) => optional_bytes_from_avro(inner),
+        Value::Null => Some(None),
+        Value::Bytes(value) => Some(Some(value.clone())),
+        _ => None,
+    }
+}
+
+pub fn optional_string_to_avro(value: &Option<String>) -> Value {
+    Value::Union(Box::new(match value {
+        Some(value) => Value::String(value.clone()),
+        None => Value::Null,
+    }))
+}
+
+pub fn optional_string_from_avro(value: &Value) -> Option<Option<String>> {
+    match value {
+        Value::Union(inner) => optional_string_from_avro(inner),
+        Value::Null => Some(None),
+        Value::String(value) => Some(Some(value.clone())),
+        _ => None,
+    }
+}
+
+pub fn optional_u64_to_avro(value: &Option<u64>) -> Value {
+    Value::Union(Box::new(match value {
+        Some(value) => Value::Long(*value as i64),
+        None => Value::Null,
+    }))
+}
+
+pub fn optional_u64_from_avro(value: &Value) -> Option<Option<u64>> {
+    match value {
+        Value::Union(inner) => optional_u64_from_avro(inner),
+        Value::Null => Some(None),
+        Value::Long(value) => Some(Some(*value as u64)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash, Default)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum Compression {
+    #[default]
+    Uncompressed,
+    Zstd,
+    Lz4,
+    NotImplemented,
+}
+
+pub fn compression_literal_to_compression(literal: &str) -> Compression {
+    match literal {
+        "NONE" => Compression::Uncompressed,
+        "ZSTD" => Compression::Zstd,
+        "LZ4" => Compression::Lz4,
+        _ => Compression::NotImplemented,
+    }
+}
+
+pub fn get_compression_enum(compression: &Compression) -> Result<Value, String> {
+    match compression {
+        Compression::Uncompressed => Ok(Value::Enum(0, "NONE".into())),
+        Compression::Zstd => Ok(Value::Enum(1, "ZSTD".into())),
+        Compression::Lz4 => Ok(Value::Enum(2, "LZ4".into())),
+        Compression::NotImplemented => Err(String::from("Not supported compression")),
+    }
+}
+
+pub fn optional_i64_to_avro(value: &Option<i64>) -> Value {
+    Value::Union(Box::new(match value {
+        Some(value) => Value::Long(*value),
+        None => Value::Null,
+    }))
+}
+
+pub fn optional_i64_from_avro(value: &Value) -> Option<Option<i64>> {
+    match value {
+        Value::Union(inner) => optional_i64_from_avro(inner),
+        Value::Null => Some(None),
+        Value::Long(value) => Some(Some(*value)),
+        _ => None,
+    }
+}
+
+pub fn optional_f64_to_avro(value: &Option<f64>) -> Value {
+    Value::Union(Box::new(match value {
+        Some(value) => Value::Double(*value),
+        None => Value::Null,
+    }))
+}
+
+pub fn optional_f64_from_avro(value: &Value) -> Option<Option<f64>> {
+    match value {
+        Value::Union(inner) => optional_f64_from_avro(inner),
+        Value::Null => Some(None),
+        Value::Double(value) => Some(Some(*value)),
+        _ => None,
+    }
+}
+
+/// Delivery priority of a message. Priorities are ordered, `Control` is the highest,
+/// so congestion control traffic overtakes the payloads queued before it.
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum MessagePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Control,
+}
+
+impl MessagePriority {
+    /// Maps the priority to a broker queue priority in `[0, max_priority]`, e.g.
+    /// `x-max-priority` of a RabbitMQ queue.
+    pub fn queue_priority(&self, max_priority: u8) -> u8 {
+        let level = *self as u16;
+        let top = MessagePriority::Control as u16;
+        (level * u16::from(max_priority) / top) as u8
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MessagePriority {
+    #[pyo3(name = "queue_priority")]
+    fn py_queue_priority(&self, max_priority: u8) -> u8 {
+        self.queue_priority(max_priority)
+    }
+}
+
+/// An unknown priority is read as `Normal`: the priority is a delivery hint only and
+/// must not make a message from a newer peer unreadable.
+pub fn priority_literal_to_priority(literal: &str) -> MessagePriority {
+    match literal {
+        "LOW" => MessagePriority::Low,
+        "HIGH" => MessagePriority::High,
+        "CONTROL" => MessagePriority::Control,
+        _ => MessagePriority::Normal,
+    }
+}
+
+pub fn get_priority_enum(priority: &MessagePriority) -> Value {
+    match priority {
+        MessagePriority::Low => Value::Enum(0, "LOW".into()),
+        MessagePriority::Normal => Value::Enum(1, "NORMAL".into()),
+        MessagePriority::High => Value::Enum(2, "HIGH".into()),
+        MessagePriority::Control => Value::Enum(3, "CONTROL".into()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum EncryptionAlgorithm {
+    Aes256Gcm,
+    NotImplemented,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct EncryptionInfo {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub key_id: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub nonce: Vec<u8>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub algo: EncryptionAlgorithm,
+}
+
+impl EncryptionInfo {
+    pub fn new(key_id: String, nonce: Vec<u8>, algo: EncryptionAlgorithm) -> Self {
+        EncryptionInfo {
+            key_id,
+            nonce,
+            algo,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl EncryptionInfo {
+    #[new]
+    fn py_new(key_id: String, nonce: Vec<u8>, algo: EncryptionAlgorithm) -> Self {
+        Self::new(key_id, nonce, algo)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl EncryptionInfo {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("key_id".into(), Value::String(self.key_id.clone())),
+            ("nonce".into(), Value::Bytes(self.nonce.clone())),
+            (
+                "algo".into(),
+                match self.algo {
+                    EncryptionAlgorithm::Aes256Gcm => Value::Enum(0, "AES_256_GCM".into()),
+                    EncryptionAlgorithm::NotImplemented => {
+                        return Err(String::from("Not supported encryption algorithm"))
+                    }
+                },
+            ),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(key_id)), (_, Value::Bytes(nonce)), (_, Value::Enum(_, algo))] => {
+                    Some(EncryptionInfo {
+                        key_id: key_id.clone(),
+                        nonce: nonce.clone(),
+                        algo: match algo.as_str() {
+                            "AES_256_GCM" => EncryptionAlgorithm::Aes256Gcm,
+                            _ => EncryptionAlgorithm::NotImplemented,
+                        },
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn to_avro_union(info: &Option<EncryptionInfo>) -> Result<Value, String> {
+        Ok(Value::Union(Box::new(match info {
+            Some(info) => info.to_avro_record()?,
+            None => Value::Null,
+        })))
+    }
+
+    pub fn from_avro_union(value: &Value) -> Option<Self> {
+        match value {
+            Value::Union(inner) => Self::from_avro(inner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
+pub enum ChecksumAlgorithm {
+    Crc32c,
+    XxHash64,
+    NotImplemented,
+}
+
+/// Checksum of a payload as it's sent, i.e. after compression and before encryption.
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct Checksum {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub algo: ChecksumAlgorithm,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub value: u64,
+}
+
+impl Checksum {
+    pub fn new(algo: ChecksumAlgorithm, value: u64) -> Self {
+        Checksum { algo, value }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Checksum {
+    #[new]
+    fn py_new(algo: ChecksumAlgorithm, value: u64) -> Self {
+        Self::new(algo, value)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl Checksum {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            (
+                "algo".into(),
+                match self.algo {
+                    ChecksumAlgorithm::Crc32c => Value::Enum(0, "CRC32C".into()),
+                    ChecksumAlgorithm::XxHash64 => Value::Enum(1, "XXHASH64".into()),
+                    ChecksumAlgorithm::NotImplemented => {
+                        return Err(String::from("Not supported checksum algorithm"))
+                    }
+                },
+            ),
+            ("value".into(), Value::Long(self.value as i64)),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Enum(_, algo)), (_, Value::Long(value))] => Some(Checksum {
+                    algo: match algo.as_str() {
+                        "CRC32C" => ChecksumAlgorithm::Crc32c,
+                        "XXHASH64" => ChecksumAlgorithm::XxHash64,
+                        _ => ChecksumAlgorithm::NotImplemented,
+                    },
+                    value: *value as u64,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn to_avro_union(checksum: &Option<Checksum>) -> Result<Value, String> {
+        Ok(Value::Union(Box::new(match checksum {
+            Some(checksum) => checksum.to_avro_record()?,
+            None => Value::Null,
+        })))
+    }
+
+    pub fn from_avro_union(value: &Value) -> Option<Self> {
+        match value {
+            Value::Union(inner) => Self::from_avro(inner),
+            _ => None,
+        }
+    }
+}
+
+/// Reference to an artifact produced by a service job, e.g. a presigned HTTP URL
+/// the client downloads the result from until `expires_ms`.
 #[derive(Debug, Default, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct ArtifactReference {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub url: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub expires_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub content_type: String,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub size_bytes: i64,
+}
+
+impl ArtifactReference {
+    pub fn new(url: String, expires_ms: u64, content_type: String, size_bytes: i64) -> Self {
+        ArtifactReference {
+            url,
+            expires_ms,
+            content_type,
+            size_bytes,
+        }
+    }
+
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        now_ms >= self.expires_ms
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ArtifactReference {
+    #[new]
+    fn py_new(url: String, expires_ms: u64, content_type: String, size_bytes: i64) -> Self {
+        Self::new(url, expires_ms, content_type, size_bytes)
+    }
+
+    #[pyo3(name = "is_expired")]
+    fn py_is_expired(&self, now_ms: u64) -> bool {
+        self.is_expired(now_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl ArtifactReference {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("url".into(), Value::String(self.url.clone())),
+            ("expires_ms".into(), Value::Long(self.expires_ms as i64)),
+            (
+                "content_type".into(),
+                Value::String(self.content_type.clone()),
+            ),
+            ("size_bytes".into(), Value::Long(self.size_bytes)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::String(url)), (_, Value::Long(expires_ms)), (_, Value::String(content_type)), (_, Value::Long(size_bytes))] => {
+                    Some(ArtifactReference {
+                        url: url.clone(),
+                        expires_ms: *expires_ms as u64,
+                        content_type: content_type.clone(),
+                        size_bytes: *size_bytes,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct Payload {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub data: Vec<u8>,
-    #[pyo3(get, set)]
-    pub attributes: HashMap<String, String>,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub attributes: Attributes,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub checksum: Option<Checksum>,
 }
 
+impl Payload {
+    pub fn new(data: Vec<u8>, attributes: Attributes) -> Self {
+        Payload {
+            data,
+            attributes,
+            checksum: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
 #[pymethods]
 impl Payload {
     #[new]
-    pub fn new(data: Vec<u8>, attributes: HashMap<String, String>) -> Self {
-        Payload { data, attributes }
+    fn py_new(data: Vec<u8>, attributes: Attributes) -> Self {
+        Self::new(data, attributes)
     }
 
     fn __repr__(&self) -> String {
@@ -54,24 +727,58 @@ impl Payload {
     const __hash__: Option<Py<PyAny>> = None;
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Copy)]
-#[pyclass]
+impl Payload {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("data".into(), Value::Bytes(self.data.clone())),
+            ("attributes".into(), gen_hash_map(&self.attributes)),
+            ("checksum".into(), Checksum::to_avro_union(&self.checksum)?),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(data)), (_, Value::Map(attributes)), (_, checksum)] => {
+                    Some(Payload {
+                        data: data.clone(),
+                        attributes: parse_hash_map(attributes),
+                        checksum: Checksum::from_avro_union(checksum),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct TrackInfo {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub track_type: TrackType,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub track_name: TrackName,
 }
 
-#[pymethods]
 impl TrackInfo {
-    #[new]
     pub fn new(track_type: TrackType, track_name: TrackName) -> Self {
         TrackInfo {
             track_type,
             track_name,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TrackInfo {
+    #[new]
+    fn py_new(track_type: TrackType, track_name: TrackName) -> Self {
+        Self::new(track_type, track_name)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -85,6 +792,32 @@ impl TrackInfo {
     const __hash__: Option<Py<PyAny>> = None;
 }
 
+impl TrackInfo {
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            ("name".into(), Value::Bytes(self.track_name.to_vec())),
+            ("type".into(), get_track_type_enum(&self.track_type)?),
+        ]))
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(track_name)), (_, Value::Enum(_, track_type))] => {
+                    let mut tn = TrackName::default();
+                    fill_byte_array(&mut tn, track_name);
+                    Some(TrackInfo {
+                        track_name: tn,
+                        track_type: track_type_literal_to_track_type(track_type.as_str()),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 pub fn get_empty_track_name() -> TrackName {
     [0; TRACK_NAME_MAX_LENGTH]
 }
@@ -108,16 +841,17 @@ pub fn pack_track_name(track_name: &str) -> std::result::Result<TrackName, Strin
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
 pub struct Unit {
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub stream_name: StreamName,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub track_name: TrackName,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub track_type: TrackType,
-    #[pyo3(get, set)]
+    #[cfg_attr(feature = "python", pyo3(get, set))]
     pub unit: i64,
 }
 
@@ -125,13 +859,13 @@ pub fn track_type_literal_to_track_type(literal: &str) -> TrackType {
     match literal {
         "VIDEO" => TrackType::Video,
         "META" => TrackType::Meta,
+        "AUDIO" => TrackType::Audio,
+        "SUBTITLE" => TrackType::Subtitle,
         _ => TrackType::NotImplemented,
     }
 }
 
-#[pymethods]
 impl Unit {
-    #[new]
     pub fn new(stream_name: Vec<u8>, track_name: Vec<u8>, track_type: String, unit: i64) -> Unit {
         let mut b_stream_name: StreamName = StreamName::default();
         let mut b_track_name: TrackName = TrackName::default();
@@ -146,42 +880,334 @@ impl Unit {
             unit,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Unit {
+    #[new]
+    fn py_new(stream_name: Vec<u8>, track_name: Vec<u8>, track_type: String, unit: i64) -> Self {
+        Self::new(stream_name, track_name, track_type, unit)
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
 
     fn __str__(&self) -> String {
-        self.__repr__()
+        self.to_string()
     }
 
     #[classattr]
     const __hash__: Option<Py<PyAny>> = None;
 }
 
-fn get_track_type_enum(track_type: &TrackType) -> Value {
+/// New symbols are appended to the Avro enum, the indices of the existing ones
+/// don't change.
+pub fn get_track_type_enum(track_type: &TrackType) -> Result<Value, String> {
     match track_type {
-        TrackType::Video => Value::Enum(0, "VIDEO".into()),
-        TrackType::Meta => Value::Enum(1, "META".into()),
-        TrackType::NotImplemented => panic!("Not supported track type"),
+        TrackType::Video => Ok(Value::Enum(0, "VIDEO".into())),
+        TrackType::Meta => Ok(Value::Enum(1, "META".into())),
+        TrackType::Audio => Ok(Value::Enum(2, "AUDIO".into())),
+        TrackType::Subtitle => Ok(Value::Enum(3, "SUBTITLE".into())),
+        TrackType::NotImplemented => Err(String::from("Not supported track type")),
     }
 }
 
 impl Unit {
-    pub fn to_avro_record(&self) -> Value {
-        Value::Record(vec![
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_index, track_type)), (_, Value::Long(unit))] => {
+                    Some(Unit::new(
+                        stream_name.clone(),
+                        track_name.clone(),
+                        track_type.clone(),
+                        *unit,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
             (
                 "stream_name".into(),
                 Value::Bytes(self.stream_name.to_vec()),
             ),
             ("track_name".into(), Value::Bytes(self.track_name.to_vec())),
-            ("track_type".into(), get_track_type_enum(&self.track_type)),
+            ("track_type".into(), get_track_type_enum(&self.track_type)?),
             ("unit".into(), Value::Long(self.unit)),
+        ]))
+    }
+}
+
+/// Inclusive range of units of a single stream track.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitRange {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub stream_name: StreamName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_name: TrackName,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub track_type: TrackType,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub from_unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub to_unit: i64,
+}
+
+impl UnitRange {
+    pub fn new(
+        stream_name: Vec<u8>,
+        track_name: Vec<u8>,
+        track_type: String,
+        from_unit: i64,
+        to_unit: i64,
+    ) -> UnitRange {
+        let mut b_stream_name: StreamName = StreamName::default();
+        let mut b_track_name: TrackName = TrackName::default();
+        fill_byte_array(&mut b_stream_name, &stream_name);
+        fill_byte_array(&mut b_track_name, &track_name);
+
+        UnitRange {
+            stream_name: b_stream_name,
+            track_name: b_track_name,
+            track_type: track_type_literal_to_track_type(track_type.as_str()),
+            from_unit,
+            to_unit,
+        }
+    }
+
+    pub fn contains(&self, unit: &Unit) -> bool {
+        unit.stream_name == self.stream_name
+            && unit.track_name == self.track_name
+            && unit.track_type == self.track_type
+            && unit.unit >= self.from_unit
+            && unit.unit <= self.to_unit
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitRange {
+    #[new]
+    fn py_new(
+        stream_name: Vec<u8>,
+        track_name: Vec<u8>,
+        track_type: String,
+        from_unit: i64,
+        to_unit: i64,
+    ) -> Self {
+        Self::new(stream_name, track_name, track_type, from_unit, to_unit)
+    }
+
+    #[pyo3(name = "contains")]
+    fn py_contains(&self, unit: &Unit) -> bool {
+        self.contains(unit)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl UnitRange {
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Bytes(stream_name)), (_, Value::Bytes(track_name)), (_, Value::Enum(_, track_type)), (_, Value::Long(from_unit)), (_, Value::Long(to_unit))] => {
+                    Some(UnitRange::new(
+                        stream_name.clone(),
+                        track_name.clone(),
+                        track_type.clone(),
+                        *from_unit,
+                        *to_unit,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn to_avro_record(&self) -> Result<Value, String> {
+        Ok(Value::Record(vec![
+            (
+                "stream_name".into(),
+                Value::Bytes(self.stream_name.to_vec()),
+            ),
+            ("track_name".into(), Value::Bytes(self.track_name.to_vec())),
+            ("track_type".into(), get_track_type_enum(&self.track_type)?),
+            ("from_unit".into(), Value::Long(self.from_unit)),
+            ("to_unit".into(), Value::Long(self.to_unit)),
+        ]))
+    }
+}
+
+/// Unit together with the wall-clock time the store saved it at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitTimestamp {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub saved_ms: u64,
+}
+
+impl UnitTimestamp {
+    pub fn new(unit: i64, saved_ms: u64) -> Self {
+        UnitTimestamp { unit, saved_ms }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitTimestamp {
+    #[new]
+    fn py_new(unit: i64, saved_ms: u64) -> Self {
+        Self::new(unit, saved_ms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl UnitTimestamp {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("unit".into(), Value::Long(self.unit)),
+            ("saved_ms".into(), Value::Long(self.saved_ms as i64)),
+        ])
+    }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(unit)), (_, Value::Long(saved_ms))] => Some(UnitTimestamp {
+                    unit: *unit,
+                    saved_ms: *saved_ms as u64,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Unit with the times its first and last elements were saved at and the number
+/// of its elements, enough to draw a timeline without requesting the elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct UnitSummary {
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub unit: i64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub first_saved_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub last_saved_ms: u64,
+    #[cfg_attr(feature = "python", pyo3(get, set))]
+    pub element_count: i64,
+}
+
+impl UnitSummary {
+    pub fn new(unit: i64, first_saved_ms: u64, last_saved_ms: u64, element_count: i64) -> Self {
+        UnitSummary {
+            unit,
+            first_saved_ms,
+            last_saved_ms,
+            element_count,
+        }
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.last_saved_ms.saturating_sub(self.first_saved_ms)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl UnitSummary {
+    #[new]
+    fn py_new(unit: i64, first_saved_ms: u64, last_saved_ms: u64, element_count: i64) -> Self {
+        Self::new(unit, first_saved_ms, last_saved_ms, element_count)
+    }
+
+    #[pyo3(name = "duration_ms")]
+    fn py_duration_ms(&self) -> u64 {
+        self.duration_ms()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+impl UnitSummary {
+    pub fn to_avro_record(&self) -> Value {
+        Value::Record(vec![
+            ("unit".into(), Value::Long(self.unit)),
+            (
+                "first_saved_ms".into(),
+                Value::Long(self.first_saved_ms as i64),
+            ),
+            (
+                "last_saved_ms".into(),
+                Value::Long(self.last_saved_ms as i64),
+            ),
+            ("element_count".into(), Value::Long(self.element_count)),
         ])
     }
+
+    pub fn from_avro(value: &Value) -> Option<Self> {
+        match value {
+            Value::Record(fields) => match fields.as_slice() {
+                [(_, Value::Long(unit)), (_, Value::Long(first_saved_ms)), (_, Value::Long(last_saved_ms)), (_, Value::Long(element_count))] => {
+                    Some(UnitSummary {
+                        unit: *unit,
+                        first_saved_ms: *first_saved_ms as u64,
+                        last_saved_ms: *last_saved_ms as u64,
+                        element_count: *element_count,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub enum NotifyTypeImpl {
     Ready(ElementType),
     New,
@@ -189,27 +1215,41 @@ pub enum NotifyTypeImpl {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[pyclass]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "python", pyclass)]
 pub struct NotifyType {
     pub obj: NotifyTypeImpl,
 }
 
 #[allow(clippy::new_without_default)]
-#[pymethods]
 impl NotifyType {
-    #[staticmethod]
     pub fn ready(element: ElementType) -> Self {
         NotifyType {
             obj: NotifyTypeImpl::Ready(element),
         }
     }
 
-    #[staticmethod]
     pub fn new() -> Self {
         NotifyType {
             obj: NotifyTypeImpl::New,
         }
     }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NotifyType {
+    #[staticmethod]
+    #[pyo3(name = "ready")]
+    fn py_ready(element: ElementType) -> Self {
+        Self::ready(element)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "new")]
+    fn py_new() -> Self {
+        Self::new()
+    }
 
     fn __repr__(&self) -> String {
         format!("{:?}", self.obj)
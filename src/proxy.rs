@@ -0,0 +1,306 @@
+use crate::avro::{BuilderImpl, ProtocolError, RoutingHeader};
+use crate::hops::{push_hop, Hop};
+use crate::primitives::Labels;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Forward,
+    Drop,
+}
+
+/// Message passing through the proxy, interceptors rewrite its destination and
+/// envelope in place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forwarded {
+    pub topic: String,
+    pub header: RoutingHeader,
+}
+
+pub trait Interceptor: Send + Sync {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict;
+}
+
+impl<F> Interceptor for F
+where
+    F: Fn(&mut Forwarded) -> Verdict + Send + Sync,
+{
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        self(message)
+    }
+}
+
+/// Adds the labels, replacing the values of the existing keys.
+pub struct SetLabels(pub Labels);
+
+impl Interceptor for SetLabels {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        message
+            .header
+            .labels
+            .extend(self.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Verdict::Forward
+    }
+}
+
+pub struct RemoveLabels(pub Vec<String>);
+
+impl Interceptor for RemoveLabels {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        for key in &self.0 {
+            message.header.labels.remove(key);
+        }
+        Verdict::Forward
+    }
+}
+
+/// Sends the messages of the listed schemas to their own topics, the others keep
+/// the topic they came from.
+#[derive(Debug, Clone, Default)]
+pub struct RouteBySchema {
+    routes: HashMap<String, String>,
+}
+
+impl RouteBySchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route(mut self, schema: &str, topic: &str) -> Self {
+        self.routes
+            .insert(String::from(schema), String::from(topic));
+        self
+    }
+}
+
+impl Interceptor for RouteBySchema {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        if let Some(topic) = self.routes.get(&message.header.schema) {
+            message.topic = topic.clone();
+        }
+        Verdict::Forward
+    }
+}
+
+/// Moves the topics under the namespace of another site, e.g. `/site-a/streams`
+/// becomes `/site-b/streams`. Topics outside of `from` are dropped, so a gateway
+/// never leaks the traffic it isn't meant to carry.
+#[derive(Debug, Clone)]
+pub struct RewriteNamespace {
+    pub from: String,
+    pub to: String,
+}
+
+impl Interceptor for RewriteNamespace {
+    fn intercept(&self, message: &mut Forwarded) -> Verdict {
+        match message.topic.strip_prefix(self.from.as_str()) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') || self.from.ends_with('/') => {
+                message.topic = format!("{}{}", self.to, rest);
+                Verdict::Forward
+            }
+            _ => Verdict::Drop,
+        }
+    }
+}
+
+/// Transparent forwarder for gateways between brokers.
+///
+/// Only the envelope is decoded: the interceptors run in the order they are added
+/// and may rewrite the topic, the labels, the trace context and the priority, or
+/// drop the message. The payload is copied as it is, so signed, encrypted and
+/// checksummed messages pass through untouched.
+///
+/// A proxy with a node id appends a hop to the envelope of every message passed
+/// with [`Proxy::forward_timed`].
+pub struct Proxy {
+    builder: BuilderImpl,
+    interceptors: Vec<Box<dyn Interceptor>>,
+    node_id: Option<String>,
+}
+
+impl Proxy {
+    pub fn new(builder: BuilderImpl) -> Self {
+        Proxy {
+            builder,
+            interceptors: Vec::new(),
+            node_id: None,
+        }
+    }
+
+    pub fn with_node_id(mut self, node_id: &str) -> Self {
+        self.node_id = Some(String::from(node_id));
+        self
+    }
+
+    pub fn with(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.add(interceptor);
+        self
+    }
+
+    pub fn add(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptors.push(Box::new(interceptor));
+    }
+
+    /// Returns the destination topic and the message to send there, `None` when an
+    /// interceptor dropped the message.
+    pub fn forward(
+        &self,
+        topic: &str,
+        message: &[u8],
+    ) -> Result<Option<(String, Vec<u8>)>, ProtocolError> {
+        self.pass(topic, message, None)
+    }
+
+    /// Like [`Proxy::forward`], the hop is stamped with the time the message came
+    /// in and the time it is going to be sent.
+    pub fn forward_timed(
+        &self,
+        topic: &str,
+        message: &[u8],
+        received_ms: u64,
+        forwarded_ms: u64,
+    ) -> Result<Option<(String, Vec<u8>)>, ProtocolError> {
+        self.pass(topic, message, Some((received_ms, forwarded_ms)))
+    }
+
+    fn pass(
+        &self,
+        topic: &str,
+        message: &[u8],
+        times: Option<(u64, u64)>,
+    ) -> Result<Option<(String, Vec<u8>)>, ProtocolError> {
+        let header = self.builder.read_routing_header(message)?;
+        let mut forwarded = Forwarded {
+            topic: String::from(topic),
+            header: header.clone(),
+        };
+        for interceptor in &self.interceptors {
+            if interceptor.intercept(&mut forwarded) == Verdict::Drop {
+                return Ok(None);
+            }
+        }
+        if let (Some(node_id), Some((received_ms, forwarded_ms))) = (&self.node_id, times) {
+            push_hop(
+                &mut forwarded.header.hops,
+                Hop::new(node_id.clone(), received_ms, Some(forwarded_ms)),
+            );
+        }
+        let message = if forwarded.header == header {
+            message.to_vec()
+        } else {
+            self.builder
+                .rewrite_routing_header(message, &forwarded.header)?
+        };
+        Ok(Some((forwarded.topic, message)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl, PING_REQUEST_RESPONSE_SCHEMA};
+    use crate::hops::{hop_latencies, Hop};
+    use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::primitives::{AttributeValue, Labels, MessagePriority};
+    use crate::proxy::{
+        Forwarded, Proxy, RemoveLabels, RewriteNamespace, RouteBySchema, SetLabels, Verdict,
+    };
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_forward() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        mb.set_signing_key("k1", b"secret".to_vec());
+        mb.add_verification_key("k1", b"secret".to_vec());
+        let ping =
+            PingRequestResponse::new(1, String::from("/ab/c"), PingRequestResponseType::Request);
+        let message = mb
+            .save_from_avro_with_labels(
+                ping.save(&mb).unwrap(),
+                &Labels::from([(String::from("tenant"), AttributeValue::from("a"))]),
+            )
+            .unwrap();
+
+        let proxy = Proxy::new(BuilderImpl::new(get_avro_path().as_str()));
+        assert_eq!(
+            proxy.forward("/site-a/pings", &message),
+            Ok(Some((String::from("/site-a/pings"), message.clone())))
+        );
+
+        let proxy = Proxy::new(BuilderImpl::new(get_avro_path().as_str()))
+            .with(RewriteNamespace {
+                from: String::from("/site-a"),
+                to: String::from("/site-b"),
+            })
+            .with(RouteBySchema::new().route(PING_REQUEST_RESPONSE_SCHEMA, "/site-b/control"))
+            .with(SetLabels(Labels::from([(
+                String::from("gateway"),
+                AttributeValue::from("eu-1"),
+            )])))
+            .with(RemoveLabels(vec![String::from("tenant")]))
+            .with(|m: &mut Forwarded| {
+                m.header.priority = MessagePriority::High;
+                Verdict::Forward
+            });
+
+        assert_eq!(proxy.forward("/site-c/pings", &message), Ok(None));
+        assert_eq!(proxy.forward("/site-ab/pings", &message), Ok(None));
+
+        let (topic, forwarded) = proxy.forward("/site-a/pings", &message).unwrap().unwrap();
+        assert_eq!(topic, "/site-b/control");
+        assert_eq!(
+            mb.read_labels(forwarded.clone()),
+            Some(Labels::from([(
+                String::from("gateway"),
+                AttributeValue::from("eu-1")
+            )]))
+        );
+        assert_eq!(
+            mb.read_priority(forwarded.clone()),
+            Some(MessagePriority::High)
+        );
+        let envelope = mb.load_to_avro(forwarded).unwrap();
+        assert_eq!(PingRequestResponse::load(&envelope).unwrap(), ping);
+    }
+
+    #[test]
+    fn test_hops() {
+        let mut mb = Builder::new(get_avro_path().as_str());
+        mb.set_signing_key("k1", b"secret".to_vec());
+        mb.add_verification_key("k1", b"secret".to_vec());
+        let ping =
+            PingRequestResponse::new(1, String::from("/ab/c"), PingRequestResponseType::Request);
+        let message = mb.save_from_avro(ping.save(&mb).unwrap()).unwrap();
+        assert_eq!(mb.read_hops(message.clone()), Some(vec![]));
+
+        let first = Proxy::new(BuilderImpl::new(get_avro_path().as_str())).with_node_id("gw-a");
+        let second = Proxy::new(BuilderImpl::new(get_avro_path().as_str())).with_node_id("gw-b");
+        let (_, message) = first
+            .forward_timed("/pings", &message, 100, 102)
+            .unwrap()
+            .unwrap();
+        let (_, message) = second
+            .forward_timed("/pings", &message, 150, 151)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first.forward("/pings", &message).unwrap().unwrap().1,
+            message
+        );
+
+        let hops = mb.read_hops(message.clone()).unwrap();
+        assert_eq!(
+            hops,
+            vec![
+                Hop::new(String::from("gw-a"), 100, Some(102)),
+                Hop::new(String::from("gw-b"), 150, Some(151)),
+            ]
+        );
+        let (latencies, last_link_ms) = hop_latencies(&hops, 160);
+        assert_eq!(latencies[1].link_ms, Some(48));
+        assert_eq!(last_link_ms, Some(9));
+
+        let envelope = mb.load_to_avro(message).unwrap();
+        assert_eq!(PingRequestResponse::load(&envelope).unwrap(), ping);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    auth::AuthToken,
+    avro::{Builder, ProtocolMessage},
+    bitmap::UnitBitmap,
+    checksum::ChecksumPolicy,
+    hops::{Hop, HopLatency},
+    objects::services::auth::{AuthChallenge, AuthResult},
+    objects::services::error_response::{ErrorCode, ErrorResponse},
+    objects::services::ffprobe::{
+        FFProbeFormatInfo, FFProbeStreamInfo, ServicesFFProbeRequest, ServicesFFProbeResponse,
+        ServicesFFProbeResponseType,
+    },
+    objects::services::filmstrip::{ServicesFilmstripRequest, ServicesFilmstripResponse},
+    objects::services::heartbeat::HeartbeatMessage,
+    objects::services::inference::{BoundingBox, Detection, InferenceResultMessage},
+    objects::services::keep_alive::KeepAliveMessage,
+    objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity},
+    objects::services::ping::{PingRequestResponse, PingRequestResponseType},
+    objects::services::slo_report::SloReport,
+    objects::services::snapshot::{SnapshotFormat, SnapshotRequest, SnapshotResponse},
+    objects::services::storage::backfill::{BackfillRequest, BackfillResponse, BackfillStatus},
+    objects::services::storage::encoding_profile::{
+        EncodingAckStatus, EncodingProfile, EncodingProfileAck, EncodingProfileOffer,
+    },
+    objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest},
+    objects::services::storage::notify_cadence::NotifyCadenceControl,
+    objects::services::storage::notify_digest::{
+        NotifyDigestRequest, NotifyDigestResponse, TrackDigest,
+    },
+    objects::services::storage::notify_message::NotifyMessage,
+    objects::services::storage::retention::{
+        RetentionPolicy, StorageControlStatus, StoragePurgeRequest, StoragePurgeResponse,
+        StorageRetentionSetRequest, StorageRetentionSetResponse,
+    },
+    objects::services::storage::storage_stats::{
+        StorageStatsRequest, StorageStatsResponse, StreamStats, TrackStats,
+    },
+    objects::services::storage::stream_alias::{
+        ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+    },
+    objects::services::storage::stream_deletion::{RestoreStreamRequest, SoftDeleteStreamRequest},
+    objects::services::storage::stream_end_message::{StreamEndMessage, StreamEndReason},
+    objects::services::storage::stream_lifecycle_message::{
+        StreamLifecycleEvent, StreamLifecycleMessage,
+    },
+    objects::services::storage::stream_multi_track_unit_elements::{
+        StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse, TrackElements,
+    },
+    objects::services::storage::stream_replay::{
+        StreamReplayRequest, StreamReplayState, StreamReplayStatus, StreamReplayStop,
+    },
+    objects::services::storage::stream_track_keyframes::{
+        StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+    },
+    objects::services::storage::stream_track_unit_elements::{
+        StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+    },
+    objects::services::storage::stream_track_units::{
+        StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+    },
+    objects::services::storage::stream_track_units_by_time::{
+        StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+    },
+    objects::services::storage::stream_tracks::{
+        StreamTracksRequest, StreamTracksResponse, TrackDescriptor,
+    },
+    objects::services::storage::streams_discovery::{
+        StreamDescriptor, StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+    },
+    objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse},
+    objects::services::storage::unit_element_ack_message::{AckStatus, UnitElementAckMessage},
+    objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage,
+    objects::services::storage::unit_element_message::UnitElementMessage,
+    objects::services::telemetry::NodeTelemetry,
+    objects::services::transcode::{
+        TranscodeJobCancel, TranscodeJobRequest, TranscodeJobState, TranscodeJobStatus,
+    },
+    primitives::{
+        ArtifactReference, Checksum, ChecksumAlgorithm, Compression, EncryptionAlgorithm,
+        EncryptionInfo, MessagePriority, NotifyType, Payload, TrackInfo, TrackType, Unit,
+        UnitRange, UnitSummary, UnitTimestamp,
+    },
+    trace::TraceContext,
+};
+use pyo3::prelude::*;
+
+#[pymodule]
+fn insight_protocol(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Builder>()?;
+    m.add_class::<ProtocolMessage>()?;
+    m.add_class::<UnitElementMessage>()?;
+    m.add_class::<NotifyMessage>()?;
+    m.add_class::<PingRequestResponse>()?;
+    m.add_class::<ServicesFFProbeRequest>()?;
+    m.add_class::<ServicesFFProbeResponse>()?;
+    m.add_class::<FFProbeStreamInfo>()?;
+    m.add_class::<FFProbeFormatInfo>()?;
+    m.add_class::<StreamTrackUnitElementsRequest>()?;
+    m.add_class::<StreamTrackUnitElementsResponse>()?;
+    m.add_class::<StreamTracksRequest>()?;
+    m.add_class::<StreamTracksResponse>()?;
+    m.add_class::<StreamTrackUnitsRequest>()?;
+    m.add_class::<StreamTrackUnitsResponse>()?;
+    m.add_class::<PingRequestResponseType>()?;
+    m.add_class::<ServicesFFProbeResponseType>()?;
+    m.add_class::<Unit>()?;
+    m.add_class::<TrackInfo>()?;
+    m.add_class::<Payload>()?;
+    m.add_class::<TrackType>()?;
+    m.add_class::<NotifyType>()?;
+    m.add_class::<Compression>()?;
+    m.add_class::<MessagePriority>()?;
+    m.add_class::<EncryptionAlgorithm>()?;
+    m.add_class::<EncryptionInfo>()?;
+    m.add_class::<ChecksumAlgorithm>()?;
+    m.add_class::<Checksum>()?;
+    m.add_class::<ChecksumPolicy>()?;
+    m.add_class::<KeepAliveMessage>()?;
+    m.add_class::<ServicesFilmstripRequest>()?;
+    m.add_class::<ServicesFilmstripResponse>()?;
+    m.add_class::<StreamEndMessage>()?;
+    m.add_class::<StreamEndReason>()?;
+    m.add_class::<ArtifactReference>()?;
+    m.add_class::<UnitRange>()?;
+    m.add_class::<StreamLifecycleMessage>()?;
+    m.add_class::<StreamLifecycleEvent>()?;
+    m.add_class::<SloReport>()?;
+    m.add_class::<StreamsDiscoveryRequest>()?;
+    m.add_class::<StreamsDiscoveryResponse>()?;
+    m.add_class::<StreamDescriptor>()?;
+    m.add_class::<TrackDescriptor>()?;
+    m.add_class::<StreamTrackUnitsByTimeRequest>()?;
+    m.add_class::<StreamTrackUnitsByTimeResponse>()?;
+    m.add_class::<UnitTimestamp>()?;
+    m.add_class::<UnitSummary>()?;
+    m.add_class::<StreamTrackKeyframesRequest>()?;
+    m.add_class::<StreamTrackKeyframesResponse>()?;
+    m.add_class::<UnitElementChunkMessage>()?;
+    m.add_class::<UnitElementAckMessage>()?;
+    m.add_class::<AckStatus>()?;
+    m.add_class::<NotifyDigestRequest>()?;
+    m.add_class::<NotifyDigestResponse>()?;
+    m.add_class::<TrackDigest>()?;
+    m.add_class::<FlowControlGrant>()?;
+    m.add_class::<FlowControlRequest>()?;
+    m.add_class::<SetStreamAlias>()?;
+    m.add_class::<ResolveAliasRequest>()?;
+    m.add_class::<ResolveAliasResponse>()?;
+    m.add_class::<ErrorResponse>()?;
+    m.add_class::<ErrorCode>()?;
+    m.add_class::<SoftDeleteStreamRequest>()?;
+    m.add_class::<RestoreStreamRequest>()?;
+    m.add_class::<TraceContext>()?;
+    m.add_class::<Hop>()?;
+    m.add_class::<HopLatency>()?;
+    m.add_class::<MaintenanceNotice>()?;
+    m.add_class::<MaintenanceSeverity>()?;
+    m.add_class::<UnitBitmapRequest>()?;
+    m.add_class::<UnitBitmapResponse>()?;
+    m.add_class::<UnitBitmap>()?;
+    m.add_class::<BackfillRequest>()?;
+    m.add_class::<BackfillResponse>()?;
+    m.add_class::<BackfillStatus>()?;
+    m.add_class::<TranscodeJobRequest>()?;
+    m.add_class::<TranscodeJobStatus>()?;
+    m.add_class::<TranscodeJobCancel>()?;
+    m.add_class::<TranscodeJobState>()?;
+    m.add_class::<SnapshotRequest>()?;
+    m.add_class::<SnapshotResponse>()?;
+    m.add_class::<SnapshotFormat>()?;
+    m.add_class::<EncodingProfileOffer>()?;
+    m.add_class::<EncodingProfileAck>()?;
+    m.add_class::<EncodingProfile>()?;
+    m.add_class::<EncodingAckStatus>()?;
+    m.add_class::<StorageRetentionSetRequest>()?;
+    m.add_class::<StorageRetentionSetResponse>()?;
+    m.add_class::<StoragePurgeRequest>()?;
+    m.add_class::<StoragePurgeResponse>()?;
+    m.add_class::<RetentionPolicy>()?;
+    m.add_class::<StorageControlStatus>()?;
+    m.add_class::<NotifyCadenceControl>()?;
+    m.add_class::<StorageStatsRequest>()?;
+    m.add_class::<StorageStatsResponse>()?;
+    m.add_class::<StreamStats>()?;
+    m.add_class::<TrackStats>()?;
+    m.add_class::<StreamReplayRequest>()?;
+    m.add_class::<StreamReplayStatus>()?;
+    m.add_class::<StreamReplayStop>()?;
+    m.add_class::<StreamReplayState>()?;
+    m.add_class::<StreamMultiTrackUnitElementsRequest>()?;
+    m.add_class::<StreamMultiTrackUnitElementsResponse>()?;
+    m.add_class::<TrackElements>()?;
+    m.add_class::<InferenceResultMessage>()?;
+    m.add_class::<BoundingBox>()?;
+    m.add_class::<Detection>()?;
+    m.add_class::<NodeTelemetry>()?;
+    m.add_class::<HeartbeatMessage>()?;
+    m.add_class::<AuthChallenge>()?;
+    m.add_class::<AuthResult>()?;
+    m.add_class::<AuthToken>()?;
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsRequest;
+use crate::primitives::{ElementType, Unit};
+
+/// Speeds above the threshold (or rewinding) switch the planner to keyframes only.
+pub const TRICK_PLAY_SPEED: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Keyframe {
+    pub unit: i64,
+    pub element: ElementType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RequestPriority {
+    Immediate,
+    High,
+    Normal,
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedRequest {
+    pub unit: i64,
+    pub max_element: ElementType,
+    pub priority: RequestPriority,
+}
+
+impl PlannedRequest {
+    pub fn to_request(
+        &self,
+        request_id: i64,
+        topic: &str,
+        stream_unit: &Unit,
+    ) -> StreamTrackUnitElementsRequest {
+        let mut stream_unit = stream_unit.clone();
+        stream_unit.unit = self.unit;
+        StreamTrackUnitElementsRequest::new(
+            request_id,
+            String::from(topic),
+            stream_unit,
+            self.max_element,
+        )
+    }
+}
+
+/// Plans unit element requests which keep a player buffer filled.
+///
+/// In normal playback the planner starts from the GOP the position belongs to and
+/// requests whole units ahead of it, in trick play it requests keyframes only.
+#[derive(Debug, Clone)]
+pub struct ReadaheadPlanner {
+    keyframes: Vec<Keyframe>,
+    max_elements_per_unit: ElementType,
+    buffer_units: usize,
+}
+
+impl ReadaheadPlanner {
+    pub fn new(
+        mut keyframes: Vec<Keyframe>,
+        max_elements_per_unit: ElementType,
+        buffer_units: usize,
+    ) -> Self {
+        keyframes.sort();
+        keyframes.dedup();
+        ReadaheadPlanner {
+            keyframes,
+            max_elements_per_unit,
+            buffer_units,
+        }
+    }
+
+    pub fn add_keyframe(&mut self, keyframe: Keyframe) {
+        if let Err(pos) = self.keyframes.binary_search(&keyframe) {
+            self.keyframes.insert(pos, keyframe);
+        }
+    }
+
+    /// The keyframe playback must start from to show the position.
+    pub fn gop_start(&self, position: Keyframe) -> Option<Keyframe> {
+        match self.keyframes.binary_search(&position) {
+            Ok(pos) => Some(self.keyframes[pos]),
+            Err(0) => None,
+            Err(pos) => Some(self.keyframes[pos - 1]),
+        }
+    }
+
+    pub fn plan(&self, position: Keyframe, speed: f64) -> Vec<PlannedRequest> {
+        if speed == 0.0 {
+            return self
+                .gop_start(position)
+                .map(|k| {
+                    vec![PlannedRequest {
+                        unit: k.unit,
+                        max_element: self.max_elements_per_unit,
+                        priority: RequestPriority::Immediate,
+                    }]
+                })
+                .unwrap_or_default();
+        }
+
+        if !(0.0..=TRICK_PLAY_SPEED).contains(&speed) {
+            return self.plan_keyframes(position, speed);
+        }
+
+        let start = self
+            .gop_start(position)
+            .map(|k| k.unit)
+            .unwrap_or(position.unit);
+        let units = (self.buffer_units as f64 * speed.max(1.0)).ceil() as i64;
+        (0..=(position.unit - start) + units)
+            .map(|i| PlannedRequest {
+                unit: start + i,
+                max_element: self.max_elements_per_unit,
+                priority: Self::priority(start + i - position.unit, units),
+            })
+            .collect()
+    }
+
+    fn plan_keyframes(&self, position: Keyframe, speed: f64) -> Vec<PlannedRequest> {
+        let count = (self.buffer_units as f64 * speed.abs().max(1.0)).ceil() as usize;
+        let selected: Vec<&Keyframe> = if speed < 0.0 {
+            self.keyframes
+                .iter()
+                .rev()
+                .filter(|k| **k <= position)
+                .take(count)
+                .collect()
+        } else {
+            let start = self.gop_start(position).unwrap_or(position);
+            self.keyframes
+                .iter()
+                .filter(|k| **k >= start)
+                .take(count)
+                .collect()
+        };
+
+        selected
+            .iter()
+            .enumerate()
+            .map(|(i, k)| PlannedRequest {
+                unit: k.unit,
+                max_element: k.element,
+                priority: Self::priority(i as i64, count as i64),
+            })
+            .collect()
+    }
+
+    fn priority(distance: i64, horizon: i64) -> RequestPriority {
+        if distance <= 0 {
+            RequestPriority::Immediate
+        } else if distance * 3 <= horizon {
+            RequestPriority::High
+        } else if distance * 3 <= horizon * 2 {
+            RequestPriority::Normal
+        } else {
+            RequestPriority::Low
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::readahead::{Keyframe, ReadaheadPlanner, RequestPriority};
+
+    fn keyframes() -> Vec<Keyframe> {
+        vec![
+            Keyframe {
+                unit: 0,
+                element: 0,
+            },
+            Keyframe {
+                unit: 2,
+                element: 5,
+            },
+            Keyframe {
+                unit: 5,
+                element: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_plan_normal_playback() {
+        let planner = ReadaheadPlanner::new(keyframes(), 100, 3);
+        let plan = planner.plan(
+            Keyframe {
+                unit: 3,
+                element: 10,
+            },
+            1.0,
+        );
+        assert_eq!(plan.first().unwrap().unit, 2);
+        assert_eq!(plan.first().unwrap().priority, RequestPriority::Immediate);
+        assert_eq!(plan.last().unwrap().unit, 6);
+    }
+
+    #[test]
+    fn test_plan_ahead_of_position() {
+        let planner = ReadaheadPlanner::new(keyframes(), 100, 2);
+        let plan = planner.plan(
+            Keyframe {
+                unit: 10,
+                element: 0,
+            },
+            1.0,
+        );
+        let units: Vec<_> = plan.iter().map(|r| r.unit).collect();
+        assert_eq!(units, (5..=12).collect::<Vec<_>>());
+        assert_eq!(plan[5].priority, RequestPriority::Immediate);
+        assert_eq!(plan[6].priority, RequestPriority::Normal);
+        assert_eq!(plan[7].priority, RequestPriority::Low);
+    }
+
+    #[test]
+    fn test_plan_trick_play() {
+        let planner = ReadaheadPlanner::new(keyframes(), 100, 1);
+        let plan = planner.plan(
+            Keyframe {
+                unit: 5,
+                element: 3,
+            },
+            -4.0,
+        );
+        let units: Vec<_> = plan.iter().map(|r| (r.unit, r.max_element)).collect();
+        assert_eq!(units, vec![(5, 1), (2, 5), (0, 0)]);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::avro::{DecodeLimits, ProtocolError};
+use crate::checksum::crc32;
+use crate::compression::decompress;
+use crate::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::primitives::{Compression, ElementType, Unit};
+use log::warn;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+struct PendingElement {
+    chunks: Vec<Option<UnitElementChunkMessage>>,
+    received: usize,
+    bytes: usize,
+    started_ms: u64,
+}
+
+/// Bounds of what a [`Reassembler`] buffers, the chunks come from the network and
+/// their counts can't be trusted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReassemblyLimits {
+    /// Chunks of a single element.
+    pub max_chunks: i32,
+    /// Elements being reassembled at once, the oldest is evicted for a new one.
+    pub max_pending: usize,
+    /// Bytes buffered over all the elements, the oldest are evicted past it.
+    pub max_pending_bytes: usize,
+    /// Bytes of an element value once decompressed.
+    pub max_payload_size: usize,
+}
+
+impl Default for ReassemblyLimits {
+    fn default() -> Self {
+        ReassemblyLimits {
+            max_chunks: 4096,
+            max_pending: 1024,
+            max_pending_bytes: 256 * 1024 * 1024,
+            max_payload_size: DecodeLimits::default().max_payload_size,
+        }
+    }
+}
+
+/// Buffers unit element chunks and yields the element once all its chunks are received.
+///
+/// Elements which aren't completed within `timeout_ms` since the first chunk are
+/// dropped by [`Reassembler::evict_expired`].
+#[derive(Debug, Clone)]
+pub struct Reassembler {
+    timeout_ms: u64,
+    limits: ReassemblyLimits,
+    pending: HashMap<(Unit, ElementType), PendingElement>,
+    pending_bytes: usize,
+}
+
+impl Reassembler {
+    pub fn new(timeout_ms: u64) -> Self {
+        Self::with_limits(timeout_ms, ReassemblyLimits::default())
+    }
+
+    pub fn with_limits(timeout_ms: u64, limits: ReassemblyLimits) -> Self {
+        Reassembler {
+            timeout_ms,
+            limits,
+            pending: HashMap::default(),
+            pending_bytes: 0,
+        }
+    }
+
+    /// Adds the chunk, the complete element is returned with its last chunk. Elements
+    /// with inconsistent chunks or a checksum mismatch are dropped with an error.
+    pub fn push(
+        &mut self,
+        chunk: UnitElementChunkMessage,
+        now_ms: u64,
+    ) -> Result<Option<UnitElementMessage>, String> {
+        if chunk.total_chunks <= 0
+            || chunk.total_chunks > self.limits.max_chunks
+            || chunk.chunk_index < 0
+            || chunk.chunk_index >= chunk.total_chunks
+        {
+            return Err(format!(
+                "Chunk {} of {} is out of range",
+                chunk.chunk_index, chunk.total_chunks
+            ));
+        }
+        if chunk.encryption.is_some() {
+            return Err(String::from(
+                "Encrypted chunks can't be reassembled, no decryption key is available",
+            ));
+        }
+
+        let key = (chunk.stream_unit.clone(), chunk.element);
+        if !self.pending.contains_key(&key) {
+            while self.pending.len() >= self.limits.max_pending.max(1) {
+                self.evict_oldest();
+            }
+            self.pending.insert(
+                key.clone(),
+                PendingElement {
+                    chunks: vec![None; chunk.total_chunks as usize],
+                    received: 0,
+                    bytes: 0,
+                    started_ms: now_ms,
+                },
+            );
+        }
+        let pending = self.pending.get_mut(&key).unwrap();
+        if pending.chunks.len() != chunk.total_chunks as usize {
+            self.remove(&key);
+            return Err(format!(
+                "Element {} of unit {} changed the number of chunks",
+                chunk.element, chunk.stream_unit.unit
+            ));
+        }
+        let index = chunk.chunk_index as usize;
+        let added = chunk.value.len();
+        let replaced = match pending.chunks[index].replace(chunk) {
+            Some(previous) => previous.value.len(),
+            None => {
+                pending.received += 1;
+                0
+            }
+        };
+        pending.bytes = pending.bytes - replaced + added;
+        self.pending_bytes = self.pending_bytes - replaced + added;
+        let complete = pending.received == pending.chunks.len();
+
+        while self.pending_bytes > self.limits.max_pending_bytes {
+            if self.evict_oldest().as_ref() == Some(&key) {
+                return Err(format!(
+                    "Element {} of unit {} is evicted, the chunks take too many bytes",
+                    key.1, key.0.unit
+                ));
+            }
+        }
+        if !complete {
+            return Ok(None);
+        }
+
+        let chunks: Vec<_> = self
+            .remove(&key)
+            .unwrap()
+            .chunks
+            .into_iter()
+            .flatten()
+            .collect();
+        self.assemble(chunks).map(Some)
+    }
+
+    /// Drops the elements which weren't completed in time and returns their keys.
+    pub fn evict_expired(&mut self, now_ms: u64) -> Vec<(Unit, ElementType)> {
+        let expired: Vec<_> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| now_ms.saturating_sub(p.started_ms) > self.timeout_ms)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in &expired {
+            warn!(
+                "Element {} of unit {} is evicted, not all the chunks were received",
+                key.1, key.0.unit
+            );
+            self.remove(key);
+        }
+        expired
+    }
+
+    fn remove(&mut self, key: &(Unit, ElementType)) -> Option<PendingElement> {
+        let pending = self.pending.remove(key)?;
+        self.pending_bytes -= pending.bytes;
+        Some(pending)
+    }
+
+    fn evict_oldest(&mut self) -> Option<(Unit, ElementType)> {
+        let key = self
+            .pending
+            .iter()
+            .min_by_key(|(_, p)| p.started_ms)
+            .map(|(k, _)| k.clone())?;
+        warn!(
+            "Element {} of unit {} is evicted, too many elements are pending",
+            key.1, key.0.unit
+        );
+        self.remove(&key);
+        Some(key)
+    }
+
+    /// Bytes of the chunks buffered over all the pending elements.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    pub fn pending_elements(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn assemble(&self, chunks: Vec<UnitElementChunkMessage>) -> Result<UnitElementMessage, String> {
+        let first = &chunks[0];
+        let value: Vec<u8> = chunks
+            .iter()
+            .flat_map(|c| c.value.iter().copied())
+            .collect();
+        if chunks.iter().any(|c| c.checksum != first.checksum) || crc32(&value) != first.checksum {
+            return Err(format!(
+                "Checksum mismatch for element {} of unit {}",
+                first.element, first.stream_unit.unit
+            ));
+        }
+        let (value, compression) =
+            match decompress(&first.compression, &value, self.limits.max_payload_size) {
+                Ok(value) => (value, Compression::Uncompressed),
+                Err(e @ ProtocolError::LimitExceeded(_)) => return Err(e.to_string()),
+                Err(e) => {
+                    warn!("Payload is kept compressed. Error is {}", e);
+                    (value, first.compression)
+                }
+            };
+        Ok(UnitElementMessage {
+            value,
+            compression,
+            is_keyframe: first.is_keyframe,
+            ..UnitElementMessage::new(
+                first.stream_unit.clone(),
+                first.element,
+                vec![],
+                first.attributes.clone(),
+                first.last,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::services::storage::unit_element_chunk_message::{
+        fragment_unit_element, UnitElementChunkMessage,
+    };
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    #[cfg(feature = "compression")]
+    use crate::primitives::Compression;
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::reassembler::{Reassembler, ReassemblyLimits};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn message() -> UnitElementMessage {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        UnitElementMessage::new(
+            Unit::new(
+                stream_name.to_vec(),
+                track_name.to_vec(),
+                String::from("VIDEO"),
+                3,
+            ),
+            0,
+            (0..100u8).collect(),
+            HashMap::from([("a".into(), "b".into())]),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_reassemble_out_of_order() {
+        let mut reassembler = Reassembler::new(1000);
+        let mut chunks = fragment_unit_element(&message(), 30).unwrap();
+        chunks.reverse();
+        let last = chunks.pop().unwrap();
+        for c in chunks {
+            assert_eq!(reassembler.push(c, 10), Ok(None));
+        }
+        assert_eq!(reassembler.push(last, 20), Ok(Some(message())));
+        assert_eq!(reassembler.pending_elements(), 0);
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut reassembler = Reassembler::new(1000);
+        let mut chunks = fragment_unit_element(&message(), 60).unwrap();
+        chunks[1].value[0] ^= 0xff;
+        assert_eq!(reassembler.push(chunks.remove(0), 10), Ok(None));
+        assert!(reassembler.push(chunks.remove(0), 10).is_err());
+        assert_eq!(reassembler.pending_elements(), 0);
+    }
+
+    #[test]
+    fn test_evict_expired() {
+        let mut reassembler = Reassembler::new(1000);
+        let chunk = fragment_unit_element(&message(), 60).unwrap().remove(0);
+        assert_eq!(reassembler.push(chunk, 10), Ok(None));
+        assert!(reassembler.evict_expired(500).is_empty());
+        assert_eq!(reassembler.evict_expired(1500).len(), 1);
+        assert_eq!(reassembler.pending_elements(), 0);
+    }
+
+    #[test]
+    fn test_chunk_counts() {
+        let mut reassembler = Reassembler::new(1000);
+        let chunk = fragment_unit_element(&message(), 60).unwrap().remove(0);
+        for (chunk_index, total_chunks) in [(0, i32::MAX), (0, 0), (0, -1), (-1, 2), (2, 2)] {
+            let chunk = UnitElementChunkMessage {
+                chunk_index,
+                total_chunks,
+                ..chunk.clone()
+            };
+            assert!(reassembler.push(chunk, 10).is_err());
+        }
+        assert_eq!(reassembler.pending_elements(), 0);
+    }
+
+    #[test]
+    fn test_pending_limits() {
+        let limits = ReassemblyLimits {
+            max_pending: 2,
+            max_pending_bytes: 100,
+            ..ReassemblyLimits::default()
+        };
+        let mut reassembler = Reassembler::with_limits(1000, limits);
+        let element_message = |element| UnitElementMessage {
+            element,
+            ..message()
+        };
+        for (element, now_ms) in [(0, 10), (1, 20), (2, 30)] {
+            let chunk = fragment_unit_element(&element_message(element), 40)
+                .unwrap()
+                .remove(0);
+            assert_eq!(reassembler.push(chunk, now_ms), Ok(None));
+        }
+        // the oldest element made room for the third one
+        assert_eq!(reassembler.pending_elements(), 2);
+        assert_eq!(reassembler.pending_bytes(), 80);
+
+        let chunk = fragment_unit_element(&element_message(2), 40)
+            .unwrap()
+            .remove(1);
+        assert_eq!(reassembler.push(chunk, 40), Ok(None));
+        assert_eq!(reassembler.pending_elements(), 1);
+        assert_eq!(reassembler.pending_bytes(), 80);
+
+        let chunk = fragment_unit_element(&element_message(3), 200)
+            .unwrap()
+            .remove(0);
+        let chunk = UnitElementChunkMessage {
+            total_chunks: 2,
+            value: vec![0; 150],
+            ..chunk
+        };
+        assert!(reassembler.push(chunk, 50).is_err());
+        assert_eq!(reassembler.pending_elements(), 0);
+        assert_eq!(reassembler.pending_bytes(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_decompression_limit() {
+        let limits = ReassemblyLimits {
+            max_payload_size: 1024,
+            ..ReassemblyLimits::default()
+        };
+        let mut reassembler = Reassembler::with_limits(1000, limits);
+        let plain = message();
+        let bomb = UnitElementMessage::compressed(
+            plain.stream_unit,
+            plain.element,
+            vec![0; 1024 * 1024],
+            HashMap::default(),
+            false,
+            Compression::Zstd,
+        )
+        .unwrap();
+        let mut chunks = fragment_unit_element(&bomb, 1024).unwrap();
+        assert_eq!(chunks.len(), 1);
+        let error = reassembler.push(chunks.remove(0), 10).unwrap_err();
+        assert!(error.contains("decode limit"), "{}", error);
+    }
+}
@@ -0,0 +1,403 @@
+use log::warn;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const ENTRY_EXTENSION: &str = "msg";
+const TEMP_EXTENSION: &str = "tmp";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayError {
+    QueueFull { size: u64, limit: u64 },
+    Io(String),
+}
+
+impl fmt::Display for RelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayError::QueueFull { size, limit } => write!(
+                f,
+                "Relay queue would take {} bytes, at most {} are allowed",
+                size, limit
+            ),
+            RelayError::Io(m) => write!(f, "Relay queue I/O failed: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for RelayError {}
+
+impl From<std::io::Error> for RelayError {
+    fn from(e: std::io::Error) -> Self {
+        RelayError::Io(e.to_string())
+    }
+}
+
+/// Where the relay forwards the messages to, implemented on top of the broker
+/// client of the deployment.
+pub trait Upstream {
+    fn send(&mut self, topic: &str, message: &[u8]) -> Result<(), String>;
+}
+
+/// Exponential backoff between the attempts to forward the head of the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff after `failures` failed attempts in a row.
+    pub fn backoff_ms(&self, failures: u32) -> u64 {
+        let shift = failures.saturating_sub(1).min(63);
+        self.initial_backoff_ms
+            .saturating_mul(1 << shift)
+            .min(self.max_backoff_ms)
+    }
+}
+
+/// Persistent FIFO of the messages waiting to be forwarded, one file per message.
+///
+/// Files are written under a temporary name and renamed, so a crash leaves either
+/// the whole message or nothing. The queue refuses new messages once they would
+/// take more than `max_bytes` on disk.
+#[derive(Debug)]
+pub struct DiskQueue {
+    dir: PathBuf,
+    max_bytes: u64,
+    size: u64,
+    entries: VecDeque<(u64, u64)>,
+    next_seq: u64,
+}
+
+impl DiskQueue {
+    /// Opens the queue in `dir`, picking up the messages left by a previous run.
+    pub fn open(dir: &Path, max_bytes: u64) -> Result<Self, RelayError> {
+        fs::create_dir_all(dir)?;
+        let mut entries = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some(TEMP_EXTENSION) => fs::remove_file(&path)?,
+                Some(ENTRY_EXTENSION) => {
+                    match path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .and_then(|s| s.parse::<u64>().ok())
+                    {
+                        Some(seq) => entries.push((seq, fs::metadata(&path)?.len())),
+                        None => warn!("Unexpected file {:?} in the relay queue", path),
+                    }
+                }
+                _ => {}
+            }
+        }
+        entries.sort_unstable();
+        Ok(DiskQueue {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            size: entries.iter().map(|(_, size)| size).sum(),
+            next_seq: entries.last().map(|(seq, _)| seq + 1).unwrap_or_default(),
+            entries: entries.into(),
+        })
+    }
+
+    fn path(&self, seq: u64, extension: &str) -> PathBuf {
+        self.dir.join(format!("{:020}.{}", seq, extension))
+    }
+
+    pub fn push(
+        &mut self,
+        topic: &str,
+        message: &[u8],
+        accepted_ms: u64,
+    ) -> Result<(), RelayError> {
+        let mut record = Vec::with_capacity(12 + topic.len() + message.len());
+        record.extend_from_slice(&accepted_ms.to_le_bytes());
+        record.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+        record.extend_from_slice(topic.as_bytes());
+        record.extend_from_slice(message);
+
+        let size = self.size + record.len() as u64;
+        if size > self.max_bytes {
+            return Err(RelayError::QueueFull {
+                size,
+                limit: self.max_bytes,
+            });
+        }
+
+        let seq = self.next_seq;
+        let temp = self.path(seq, TEMP_EXTENSION);
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(&record)?;
+        file.sync_all()?;
+        fs::rename(&temp, self.path(seq, ENTRY_EXTENSION))?;
+
+        self.next_seq += 1;
+        self.size = size;
+        self.entries.push_back((seq, record.len() as u64));
+        Ok(())
+    }
+
+    /// The oldest message, a corrupted one is discarded with a warning.
+    pub fn peek(&mut self) -> Result<Option<(String, Vec<u8>)>, RelayError> {
+        while let Some((seq, _)) = self.entries.front().copied() {
+            let record = fs::read(self.path(seq, ENTRY_EXTENSION))?;
+            match decode_record(seq, &record) {
+                Some(entry) => return Ok(Some((entry.topic, entry.message))),
+                None => {
+                    warn!("Relay queue entry {} is corrupted and dropped", seq);
+                    self.pop()?;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn pop(&mut self) -> Result<(), RelayError> {
+        if let Some((seq, size)) = self.entries.pop_front() {
+            fs::remove_file(self.path(seq, ENTRY_EXTENSION))?;
+            self.size -= size;
+        }
+        Ok(())
+    }
+
+    /// Keeps the messages `keep` returns `true` for, in order; corrupted ones are
+    /// dropped. Returns how many messages and bytes were dropped.
+    pub fn retain<F>(&mut self, mut keep: F) -> Result<(usize, u64), RelayError>
+    where
+        F: FnMut(&QueuedMessage) -> bool,
+    {
+        let mut kept = VecDeque::with_capacity(self.entries.len());
+        let mut dropped = (0, 0);
+        while let Some((seq, size)) = self.entries.pop_front() {
+            let path = self.path(seq, ENTRY_EXTENSION);
+            let keep = match decode_record(seq, &fs::read(&path)?) {
+                Some(entry) => keep(&entry),
+                None => {
+                    warn!("Relay queue entry {} is corrupted and dropped", seq);
+                    false
+                }
+            };
+            if keep {
+                kept.push_back((seq, size));
+            } else {
+                fs::remove_file(&path)?;
+                self.size -= size;
+                dropped = (dropped.0 + 1, dropped.1 + size);
+            }
+        }
+        self.entries = kept;
+        Ok(dropped)
+    }
+
+    /// Visits the messages in order without changing the queue.
+    pub fn for_each<F>(&self, mut visit: F) -> Result<(), RelayError>
+    where
+        F: FnMut(&QueuedMessage),
+    {
+        for (seq, _) in &self.entries {
+            if let Some(entry) = decode_record(*seq, &fs::read(self.path(*seq, ENTRY_EXTENSION))?) {
+                visit(&entry);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedMessage {
+    pub seq: u64,
+    pub accepted_ms: u64,
+    pub topic: String,
+    pub message: Vec<u8>,
+}
+
+fn decode_record(seq: u64, record: &[u8]) -> Option<QueuedMessage> {
+    let accepted_ms = u64::from_le_bytes(record.get(..8)?.try_into().ok()?);
+    let topic_len = u32::from_le_bytes(record.get(8..12)?.try_into().ok()?) as usize;
+    let topic = record.get(12..12 + topic_len)?;
+    Some(QueuedMessage {
+        seq,
+        accepted_ms,
+        topic: String::from_utf8(topic.to_vec()).ok()?,
+        message: record[12 + topic_len..].to_vec(),
+    })
+}
+
+/// Store-and-forward relay for the sites with an unreliable uplink.
+///
+/// Accepted messages are persisted first and forwarded to the upstream in order by
+/// `pump`; a failed send keeps the message at the head of the queue and is retried
+/// after the backoff of the retry policy. The disk queue bounds what an outage may
+/// take, producers get [`RelayError::QueueFull`] once it's reached.
+pub struct Relay<U: Upstream> {
+    queue: DiskQueue,
+    upstream: U,
+    retry: RetryPolicy,
+    failures: u32,
+    next_attempt_ms: u64,
+}
+
+impl<U: Upstream> Relay<U> {
+    pub fn new(queue: DiskQueue, upstream: U, retry: RetryPolicy) -> Self {
+        Relay {
+            queue,
+            upstream,
+            retry,
+            failures: 0,
+            next_attempt_ms: 0,
+        }
+    }
+
+    pub fn accept(&mut self, topic: &str, message: &[u8], now_ms: u64) -> Result<(), RelayError> {
+        self.queue.push(topic, message, now_ms)
+    }
+
+    /// Forwards the queued messages until the queue is empty or the upstream fails,
+    /// returns how many were forwarded. Does nothing while backing off.
+    pub fn pump(&mut self, now_ms: u64) -> Result<usize, RelayError> {
+        if now_ms < self.next_attempt_ms {
+            return Ok(0);
+        }
+        let mut forwarded = 0;
+        while let Some((topic, message)) = self.queue.peek()? {
+            match self.upstream.send(&topic, &message) {
+                Ok(()) => {
+                    self.queue.pop()?;
+                    self.failures = 0;
+                    forwarded += 1;
+                }
+                Err(e) => {
+                    self.failures = self.failures.saturating_add(1);
+                    self.next_attempt_ms = now_ms + self.retry.backoff_ms(self.failures);
+                    warn!(
+                        "Unable to forward the message to {}, attempt {}. Error is {}",
+                        topic, self.failures, e
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(forwarded)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn next_attempt_ms(&self) -> u64 {
+        self.next_attempt_ms
+    }
+
+    pub fn queue_mut(&mut self) -> &mut DiskQueue {
+        &mut self.queue
+    }
+
+    pub fn upstream(&self) -> &U {
+        &self.upstream
+    }
+
+    pub fn into_parts(self) -> (DiskQueue, U) {
+        (self.queue, self.upstream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::relay::{DiskQueue, Relay, RelayError, RetryPolicy, Upstream};
+    use std::fs;
+
+    #[derive(Default)]
+    struct FlakyUpstream {
+        failures_left: usize,
+        sent: Vec<(String, Vec<u8>)>,
+    }
+
+    impl Upstream for FlakyUpstream {
+        fn send(&mut self, topic: &str, message: &[u8]) -> Result<(), String> {
+            if self.failures_left > 0 {
+                self.failures_left -= 1;
+                return Err(String::from("connection refused"));
+            }
+            self.sent.push((String::from(topic), message.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = RetryPolicy::default();
+        assert_eq!(retry.backoff_ms(1), 100);
+        assert_eq!(retry.backoff_ms(3), 400);
+        assert_eq!(retry.backoff_ms(100), 30_000);
+    }
+
+    #[test]
+    fn test_relay() {
+        let dir = std::env::temp_dir().join(format!("protocol-relay-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let queue = DiskQueue::open(&dir, 64).unwrap();
+        let upstream = FlakyUpstream {
+            failures_left: 2,
+            ..Default::default()
+        };
+        let mut relay = Relay::new(queue, upstream, RetryPolicy::default());
+        relay.accept("/a", &[1; 10], 0).unwrap();
+        relay.accept("/b", &[2; 10], 0).unwrap();
+        assert_eq!(
+            relay.accept("/c", &[3; 30], 0),
+            Err(RelayError::QueueFull {
+                size: 92,
+                limit: 64
+            })
+        );
+
+        assert_eq!(relay.pump(0), Ok(0));
+        assert_eq!(relay.next_attempt_ms(), 100);
+        assert_eq!(relay.pump(50), Ok(0));
+        assert_eq!(relay.pump(100), Ok(0));
+        assert_eq!(relay.next_attempt_ms(), 300);
+
+        // the queued messages survive a restart
+        let (_, upstream) = relay.into_parts();
+        let queue = DiskQueue::open(&dir, 64).unwrap();
+        assert_eq!((queue.len(), queue.size()), (2, 48));
+        let mut relay = Relay::new(queue, upstream, RetryPolicy::default());
+        assert_eq!(relay.pump(300), Ok(2));
+        assert_eq!(relay.pending(), 0);
+        assert_eq!(
+            relay.upstream().sent,
+            vec![
+                (String::from("/a"), vec![1; 10]),
+                (String::from("/b"), vec![2; 10])
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
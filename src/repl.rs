@@ -0,0 +1,564 @@
+use crate::avro::BuilderImpl;
+use crate::primitives::Labels;
+use avro_rs::types::Value;
+use avro_rs::Schema;
+use std::fmt;
+use std::io::{self, Read, Write};
+use uuid::Uuid;
+
+pub const COMMANDS: [&str; 8] = [
+    "kinds", "new", "set", "show", "send", "decode", "help", "quit",
+];
+
+const HELP: &str = "\
+kinds [prefix]        message kinds the schemas define
+new <kind>            starts a message, the fields get zero values
+set <field> <value>   sets a field, nested ones as `stream_unit.unit`
+show                  prints the message
+send <topic>          sends the message and prints the responses
+decode <hex>          prints an encoded message
+quit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplError {
+    UnknownCommand(String),
+    UnknownKind(String),
+    UnknownField(String),
+    InvalidValue { field: String, reason: String },
+    NoMessage,
+    Usage(&'static str),
+    Protocol(String),
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplError::UnknownCommand(command) => {
+                write!(f, "Unknown command `{}`, try `help`", command)
+            }
+            ReplError::UnknownKind(kind) => write!(f, "Unknown message kind `{}`", kind),
+            ReplError::UnknownField(field) => write!(f, "Unknown field `{}`", field),
+            ReplError::InvalidValue { field, reason } => {
+                write!(f, "Invalid value of `{}`: {}", field, reason)
+            }
+            ReplError::NoMessage => write!(f, "No message, start one with `new <kind>`"),
+            ReplError::Usage(usage) => write!(f, "Usage: {}", usage),
+            ReplError::Protocol(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplError {}
+
+/// What the front end has to do after a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    Text(String),
+    Send { topic: String, message: Vec<u8> },
+    Quit,
+}
+
+/// Message being composed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub schema: String,
+    pub value: Value,
+}
+
+/// Keeps the message an operator composes between the lines they type; the
+/// front end owns the terminal and the connection.
+pub struct Session {
+    builder: BuilderImpl,
+    draft: Option<Draft>,
+}
+
+impl Session {
+    pub fn new(builder: BuilderImpl) -> Self {
+        Session {
+            builder,
+            draft: None,
+        }
+    }
+
+    pub fn draft(&self) -> Option<&Draft> {
+        self.draft.as_ref()
+    }
+
+    /// Short names of the record schemas, e.g. `StreamTracksRequest`.
+    pub fn kinds(&self) -> Vec<String> {
+        let mut kinds: Vec<String> = self
+            .builder
+            .schema_names()
+            .iter()
+            .filter(|name| {
+                matches!(
+                    self.builder.get_schema(name).as_deref(),
+                    Some(Schema::Record { .. })
+                )
+            })
+            .map(|name| short_name(name).to_string())
+            .collect();
+        kinds.sort();
+        kinds
+    }
+
+    fn resolve_kind(&self, kind: &str) -> Option<String> {
+        self.builder
+            .schema_names()
+            .into_iter()
+            .find(|name| name == kind || short_name(name) == kind)
+    }
+
+    /// Dotted paths of the fields of the draft.
+    pub fn field_paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        if let Some(schema) = self
+            .draft
+            .as_ref()
+            .and_then(|d| self.builder.get_schema(&d.schema))
+        {
+            collect_paths(&schema, "", &mut paths);
+        }
+        paths
+    }
+
+    pub fn execute(&mut self, line: &str) -> Result<Output, ReplError> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match command {
+            "" => Ok(Output::Text(String::new())),
+            "help" => Ok(Output::Text(String::from(HELP))),
+            "quit" | "exit" => Ok(Output::Quit),
+            "kinds" => Ok(Output::Text(
+                self.kinds()
+                    .into_iter()
+                    .filter(|k| k.starts_with(args))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            "new" => {
+                let schema_name = self
+                    .resolve_kind(args)
+                    .ok_or_else(|| ReplError::UnknownKind(String::from(args)))?;
+                let schema = self.builder.get_schema(&schema_name).unwrap();
+                let draft = Draft {
+                    value: zero_value(&schema),
+                    schema: schema_name,
+                };
+                let text = pretty(&draft.value);
+                self.draft = Some(draft);
+                Ok(Output::Text(text))
+            }
+            "set" => {
+                let (path, text) = args
+                    .split_once(' ')
+                    .ok_or(ReplError::Usage("set <field> <value>"))?;
+                let draft = self.draft.as_mut().ok_or(ReplError::NoMessage)?;
+                let schema = self.builder.get_schema(&draft.schema).unwrap();
+                set_path(&schema, &mut draft.value, path, text.trim())?;
+                Ok(Output::Text(String::new()))
+            }
+            "show" => {
+                let draft = self.draft.as_ref().ok_or(ReplError::NoMessage)?;
+                Ok(Output::Text(format!(
+                    "{}\n{}",
+                    short_name(&draft.schema),
+                    pretty(&draft.value)
+                )))
+            }
+            "send" => {
+                if args.is_empty() {
+                    return Err(ReplError::Usage("send <topic>"));
+                }
+                let draft = self.draft.as_ref().ok_or(ReplError::NoMessage)?;
+                let message = self
+                    .builder
+                    .pack_message_with_labels(
+                        &draft.schema,
+                        draft.value.clone(),
+                        &Labels::default(),
+                    )
+                    .map_err(|e| ReplError::Protocol(e.to_string()))?;
+                Ok(Output::Send {
+                    topic: String::from(args),
+                    message,
+                })
+            }
+            "decode" => {
+                let message = parse_hex(args).ok_or(ReplError::Usage("decode <hex>"))?;
+                self.describe(&message).map(Output::Text)
+            }
+            _ => Err(ReplError::UnknownCommand(String::from(command))),
+        }
+    }
+
+    /// Pretty prints an encoded message, e.g. a response.
+    pub fn describe(&self, message: &[u8]) -> Result<String, ReplError> {
+        let (schema, value) = self
+            .builder
+            .read_protocol_message(message)
+            .map_err(|e| ReplError::Protocol(e.to_string()))?;
+        Ok(format!("{}\n{}", short_name(&schema), pretty(&value)))
+    }
+
+    /// Candidates for the word the cursor is at and where that word starts.
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let line = &line[..pos];
+        let start = line.rfind(' ').map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let candidates = match line[..start]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [] => COMMANDS.iter().map(|c| c.to_string()).collect(),
+            ["new"] | ["kinds"] => self.kinds(),
+            ["set"] => self.field_paths(),
+            _ => vec![],
+        };
+        (
+            start,
+            candidates
+                .into_iter()
+                .filter(|c| c.starts_with(word))
+                .collect(),
+        )
+    }
+}
+
+fn short_name(schema_name: &str) -> &str {
+    let name = schema_name.trim_end_matches(".avsc");
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+fn record_variant(schema: &Schema) -> Option<&Schema> {
+    match schema {
+        Schema::Record { .. } => Some(schema),
+        Schema::Union(union) => union
+            .variants()
+            .iter()
+            .find(|s| matches!(s, Schema::Record { .. })),
+        _ => None,
+    }
+}
+
+fn collect_paths(schema: &Schema, prefix: &str, paths: &mut Vec<String>) {
+    if let Some(Schema::Record { fields, .. }) = record_variant(schema) {
+        for field in fields {
+            let path = format!("{}{}", prefix, field.name);
+            collect_paths(&field.schema, &format!("{}.", path), paths);
+            paths.push(path);
+        }
+    }
+}
+
+/// The value a new message starts with: zeros, empty collections, first enum
+/// symbols and the first variants of the unions, which are `null` for optionals.
+pub fn zero_value(schema: &Schema) -> Value {
+    match schema {
+        Schema::Boolean => Value::Boolean(false),
+        Schema::Int => Value::Int(0),
+        Schema::Long => Value::Long(0),
+        Schema::Float => Value::Float(0.0),
+        Schema::Double => Value::Double(0.0),
+        Schema::Bytes => Value::Bytes(vec![]),
+        Schema::String => Value::String(String::new()),
+        Schema::Array(_) => Value::Array(vec![]),
+        Schema::Map(_) => Value::Map(Default::default()),
+        Schema::Fixed { size, .. } => Value::Fixed(*size, vec![0; *size]),
+        Schema::Enum { symbols, .. } => Value::Enum(0, symbols[0].clone()),
+        Schema::Union(union) => Value::Union(Box::new(
+            union.variants().first().map_or(Value::Null, zero_value),
+        )),
+        Schema::Record { fields, .. } => Value::Record(
+            fields
+                .iter()
+                .map(|f| (f.name.clone(), zero_value(&f.schema)))
+                .collect(),
+        ),
+        _ => Value::Null,
+    }
+}
+
+fn set_path(schema: &Schema, value: &mut Value, path: &str, text: &str) -> Result<(), ReplError> {
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+    let unknown = || ReplError::UnknownField(String::from(path));
+    let fields_schema = match record_variant(schema) {
+        Some(Schema::Record { fields, .. }) => fields,
+        _ => return Err(unknown()),
+    };
+    let field_schema = &fields_schema
+        .iter()
+        .find(|f| f.name == name)
+        .ok_or_else(unknown)?
+        .schema;
+    // an optional record is filled with zeros when one of its fields is set first
+    if let (Value::Union(inner), Some(_)) = (&mut *value, rest) {
+        if **inner == Value::Null {
+            **inner = zero_value(record_variant(schema).unwrap());
+        }
+    }
+    let fields = match value {
+        Value::Record(fields) => fields,
+        Value::Union(inner) => match inner.as_mut() {
+            Value::Record(fields) => fields,
+            _ => return Err(unknown()),
+        },
+        _ => return Err(unknown()),
+    };
+    let field = fields
+        .iter_mut()
+        .find(|(n, _)| n == name)
+        .ok_or_else(unknown)?;
+    match rest {
+        Some(rest) => set_path(field_schema, &mut field.1, rest, text),
+        None => {
+            field.1 =
+                parse_value(field_schema, text).map_err(|reason| ReplError::InvalidValue {
+                    field: String::from(path),
+                    reason,
+                })?;
+            Ok(())
+        }
+    }
+}
+
+/// Parses what an operator typed as a value of the schema. Bytes are taken as
+/// `0x` prefixed hex, a UUID for the stream names, or text.
+pub fn parse_value(schema: &Schema, text: &str) -> Result<Value, String> {
+    let invalid = |e: &dyn fmt::Display| e.to_string();
+    match schema {
+        Schema::Null if text == "null" => Ok(Value::Null),
+        Schema::Boolean => text.parse().map(Value::Boolean).map_err(|e| invalid(&e)),
+        Schema::Int => text.parse().map(Value::Int).map_err(|e| invalid(&e)),
+        Schema::Long => text.parse().map(Value::Long).map_err(|e| invalid(&e)),
+        Schema::Float => text.parse().map(Value::Float).map_err(|e| invalid(&e)),
+        Schema::Double => text.parse().map(Value::Double).map_err(|e| invalid(&e)),
+        Schema::String => Ok(Value::String(String::from(text))),
+        Schema::Bytes => Ok(Value::Bytes(parse_bytes(text))),
+        Schema::Enum { symbols, .. } => symbols
+            .iter()
+            .position(|s| s == text)
+            .map(|i| Value::Enum(i as i32, String::from(text)))
+            .ok_or_else(|| format!("expected one of {}", symbols.join(", "))),
+        Schema::Array(items) => text
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|x| parse_value(items, x))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Schema::Union(union) => union
+            .variants()
+            .iter()
+            .find_map(|variant| parse_value(variant, text).ok())
+            .map(|value| Value::Union(Box::new(value)))
+            .ok_or_else(|| format!("`{}` matches no variant", text)),
+        _ => Err(String::from("the field can't be typed in")),
+    }
+}
+
+fn parse_bytes(text: &str) -> Vec<u8> {
+    if let Some(bytes) = text.strip_prefix("0x").and_then(parse_hex) {
+        return bytes;
+    }
+    match Uuid::parse_str(text) {
+        Ok(uuid) => uuid.as_bytes().to_vec(),
+        Err(_) => text.as_bytes().to_vec(),
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Indented dump of a value, the bytes which hold zero-padded text are printed
+/// as text, the rest as hex.
+pub fn pretty(value: &Value) -> String {
+    let mut out = String::new();
+    write_pretty(value, 0, &mut out);
+    out
+}
+
+fn write_pretty(value: &Value, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Record(fields) => {
+            for (name, value) in fields {
+                match value {
+                    Value::Record(_) | Value::Array(_) | Value::Map(_) => {
+                        out.push_str(&format!("{}{}:\n", pad, name));
+                        write_pretty(value, indent + 1, out);
+                    }
+                    Value::Union(inner) if matches!(**inner, Value::Record(_)) => {
+                        out.push_str(&format!("{}{}:\n", pad, name));
+                        write_pretty(inner, indent + 1, out);
+                    }
+                    _ => out.push_str(&format!("{}{}: {}\n", pad, name, scalar(value))),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Record(_) => {
+                        out.push_str(&format!("{}-\n", pad));
+                        write_pretty(item, indent + 1, out);
+                    }
+                    _ => out.push_str(&format!("{}- {}\n", pad, scalar(item))),
+                }
+            }
+        }
+        Value::Map(entries) => {
+            let mut keys: Vec<&String> = entries.keys().collect();
+            keys.sort();
+            for key in keys {
+                out.push_str(&format!("{}{}: {}\n", pad, key, scalar(&entries[key])));
+            }
+        }
+        _ => out.push_str(&format!("{}{}\n", pad, scalar(value))),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Boolean(x) => x.to_string(),
+        Value::Int(x) => x.to_string(),
+        Value::Long(x) => x.to_string(),
+        Value::Float(x) => x.to_string(),
+        Value::Double(x) => x.to_string(),
+        Value::String(x) => format!("{:?}", x),
+        Value::Enum(_, symbol) => symbol.clone(),
+        Value::Union(inner) => scalar(inner),
+        Value::Bytes(bytes) | Value::Fixed(_, bytes) => {
+            let text = bytes
+                .iter()
+                .rposition(|c| *c != 0)
+                .map_or(&bytes[..0], |i| &bytes[..=i]);
+            match std::str::from_utf8(text) {
+                Ok(s) if !s.is_empty() && s.chars().all(|c| !c.is_control()) => {
+                    format!("{:?}", s)
+                }
+                _ => format!(
+                    "0x{}",
+                    bytes
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>()
+                ),
+            }
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Frame of the REPL transport: the topic and the message, both prefixed with
+/// their big-endian `u32` lengths.
+pub fn write_frame(to: &mut impl Write, topic: &str, message: &[u8]) -> io::Result<()> {
+    to.write_all(&(topic.len() as u32).to_be_bytes())?;
+    to.write_all(topic.as_bytes())?;
+    to.write_all(&(message.len() as u32).to_be_bytes())?;
+    to.write_all(message)?;
+    to.flush()
+}
+
+pub fn read_frame(from: &mut impl Read) -> io::Result<(String, Vec<u8>)> {
+    let mut read_part = || -> io::Result<Vec<u8>> {
+        let mut len = [0u8; 4];
+        from.read_exact(&mut len)?;
+        let mut part = vec![0u8; u32::from_be_bytes(len) as usize];
+        from.read_exact(&mut part)?;
+        Ok(part)
+    };
+    let topic = String::from_utf8(read_part()?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((topic, read_part()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl};
+    use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+    use crate::objects::FromProtocolMessage;
+    use crate::primitives::pack_stream_name;
+    use crate::repl::{read_frame, write_frame, Output, ReplError, Session};
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_compose() {
+        let mut session = Session::new(BuilderImpl::new(get_avro_path().as_str()));
+        assert_eq!(session.execute("send /storage"), Err(ReplError::NoMessage));
+
+        session.execute("new StreamTracksRequest").unwrap();
+        session.execute("set request_id 7").unwrap();
+        session.execute("set topic response").unwrap();
+        session
+            .execute("set stream_name fa807469-fbb3-4f63-b1a9-f63fbbf90f41")
+            .unwrap();
+        session.execute("set deadline_ms 1000").unwrap();
+        assert!(matches!(
+            session.execute("set request_id x"),
+            Err(ReplError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            session.execute("set nothing 1"),
+            Err(ReplError::UnknownField(String::from("nothing")))
+        );
+
+        let message = match session.execute("send /storage").unwrap() {
+            Output::Send { topic, message } => {
+                assert_eq!(topic, "/storage");
+                message
+            }
+            other => panic!("Unexpected output {:?}", other),
+        };
+        let mb = Builder::new(get_avro_path().as_str());
+        let request =
+            StreamTracksRequest::load(&mb.load_to_avro(message.clone()).unwrap()).unwrap();
+        assert_eq!(request.request_id, 7);
+        assert_eq!(
+            request.stream_name,
+            pack_stream_name(&Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap())
+        );
+        assert_eq!(request.deadline_ms, Some(1000));
+        assert!(session
+            .describe(&message)
+            .unwrap()
+            .starts_with("StreamTracksRequest\nrequest_id: 7\n"));
+
+        let mut frame = vec![];
+        write_frame(&mut frame, "/storage", &message).unwrap();
+        assert_eq!(
+            read_frame(&mut frame.as_slice()).unwrap(),
+            (String::from("/storage"), message)
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut session = Session::new(BuilderImpl::new(get_avro_path().as_str()));
+        assert_eq!(
+            session.complete("se", 2),
+            (0, vec![String::from("set"), String::from("send")])
+        );
+        let (start, kinds) = session.complete("new StreamTracksReq", 19);
+        assert_eq!(start, 4);
+        assert_eq!(kinds, vec![String::from("StreamTracksRequest")]);
+
+        session.execute("new StreamTrackUnitsRequest").unwrap();
+        let (_, fields) = session.complete("set stream_unit.u", 17);
+        assert_eq!(fields, vec![String::from("stream_unit.unit")]);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::avro::ProtocolError;
+use crate::display::{format_stream_name, format_track_name};
+use crate::primitives::{pack_track_name, StreamName, TrackName, TrackType};
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Where a message is published: `/streams/<stream>` for the stream-wide messages,
+/// `/streams/<stream>/<track type>/<track>` for the ones of a track and
+/// `/services/<service>` for the requests to a service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Stream(StreamName),
+    Track {
+        stream: StreamName,
+        track_type: TrackType,
+        track: TrackName,
+    },
+    Service(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicError {
+    /// Neither a stream nor a service topic.
+    Malformed(String),
+    StreamName(String),
+    TrackType(String),
+    TrackName(String),
+    ServiceName(String),
+}
+
+impl fmt::Display for TopicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopicError::Malformed(topic) => write!(f, "Malformed topic {}", topic),
+            TopicError::StreamName(name) => write!(f, "Invalid stream name {} in topic", name),
+            TopicError::TrackType(name) => write!(f, "Invalid track type {} in topic", name),
+            TopicError::TrackName(name) => write!(f, "Invalid track name {} in topic", name),
+            TopicError::ServiceName(name) => write!(f, "Invalid service name {} in topic", name),
+        }
+    }
+}
+
+impl std::error::Error for TopicError {}
+
+fn track_type_segment(track_type: &TrackType) -> &'static str {
+    match track_type {
+        TrackType::Video => "video",
+        TrackType::Meta => "meta",
+        TrackType::Audio => "audio",
+        TrackType::Subtitle => "subtitle",
+        TrackType::NotImplemented => "unknown",
+    }
+}
+
+/// A segment is the part between two slashes, so it can't be empty or hold one.
+fn is_segment(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/')
+}
+
+impl Topic {
+    pub fn parse(topic: &str) -> Result<Topic, TopicError> {
+        let segments: Vec<&str> = match topic.strip_prefix('/') {
+            Some(rest) => rest.split('/').collect(),
+            None => return Err(TopicError::Malformed(String::from(topic))),
+        };
+        match segments.as_slice() {
+            ["services", service] if is_segment(service) => {
+                Ok(Topic::Service(String::from(*service)))
+            }
+            ["services", service] => Err(TopicError::ServiceName(String::from(*service))),
+            ["streams", stream] => parse_stream(stream).map(Topic::Stream),
+            ["streams", stream, track_type, track] => Ok(Topic::Track {
+                stream: parse_stream(stream)?,
+                track_type: parse_track_type(track_type)?,
+                track: parse_track(track)?,
+            }),
+            _ => Err(TopicError::Malformed(String::from(topic))),
+        }
+    }
+
+    pub fn stream(&self) -> Option<&StreamName> {
+        match self {
+            Topic::Stream(stream) | Topic::Track { stream, .. } => Some(stream),
+            Topic::Service(_) => None,
+        }
+    }
+}
+
+fn parse_stream(stream: &str) -> Result<StreamName, TopicError> {
+    Uuid::parse_str(stream)
+        .map(|uuid| *uuid.as_bytes())
+        .map_err(|_| TopicError::StreamName(String::from(stream)))
+}
+
+fn parse_track_type(track_type: &str) -> Result<TrackType, TopicError> {
+    match track_type {
+        "video" => Ok(TrackType::Video),
+        "meta" => Ok(TrackType::Meta),
+        "audio" => Ok(TrackType::Audio),
+        "subtitle" => Ok(TrackType::Subtitle),
+        "unknown" => Ok(TrackType::NotImplemented),
+        _ => Err(TopicError::TrackType(String::from(track_type))),
+    }
+}
+
+fn parse_track(track: &str) -> Result<TrackName, TopicError> {
+    match pack_track_name(track) {
+        Ok(name) if is_segment(track) && !track.contains('\0') => Ok(name),
+        _ => Err(TopicError::TrackName(String::from(track))),
+    }
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topic::Stream(stream) => write!(f, "/streams/{}", format_stream_name(stream)),
+            Topic::Track {
+                stream,
+                track_type,
+                track,
+            } => write!(
+                f,
+                "/streams/{}/{}/{}",
+                format_stream_name(stream),
+                track_type_segment(track_type),
+                format_track_name(track)
+            ),
+            Topic::Service(service) => write!(f, "/services/{}", service),
+        }
+    }
+}
+
+impl FromStr for Topic {
+    type Err = TopicError;
+
+    fn from_str(topic: &str) -> Result<Self, Self::Err> {
+        Topic::parse(topic)
+    }
+}
+
+pub fn topic_for_stream(stream: &StreamName) -> String {
+    Topic::Stream(*stream).to_string()
+}
+
+/// The track name is the one [`crate::display::format_track_name`] prints, without
+/// the zero padding.
+pub fn topic_for_track(stream: &StreamName, track_type: TrackType, track: &TrackName) -> String {
+    Topic::Track {
+        stream: *stream,
+        track_type,
+        track: *track,
+    }
+    .to_string()
+}
+
+/// Fails on a name which isn't a topic segment, e.g. one with a slash.
+pub fn topic_for_service(name: &str) -> Result<String, ProtocolError> {
+    if !is_segment(name) {
+        return Err(ProtocolError::Encode(
+            TopicError::ServiceName(String::from(name)).to_string(),
+        ));
+    }
+    Ok(Topic::Service(String::from(name)).to_string())
+}
+
+/// Key of the partition of the messages of a stream or of a track of it: the stream
+/// name followed by the track name without the zero padding, so the messages of a
+/// track keep their order.
+pub fn partition_key(stream: &StreamName, track: Option<&TrackName>) -> Vec<u8> {
+    let mut key = stream.to_vec();
+    if let Some(track) = track {
+        key.extend_from_slice(format_track_name(track).as_bytes());
+    }
+    key
+}
+
+/// The partition of the key the way the Kafka default partitioner picks it, the
+/// positive murmur2 hash modulo the partitions, so the producers of any language
+/// shard alike.
+pub fn partition_for(key: &[u8], n_partitions: NonZeroU32) -> u32 {
+    (murmur2(key) & 0x7fff_ffff) % n_partitions.get()
+}
+
+/// MurmurHash2 with the seed of Kafka.
+fn murmur2(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    let mut h: u32 = 0x9747_b28c ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+    let tail = chunks.remainder();
+    if tail.len() >= 3 {
+        h ^= u32::from(tail[2]) << 16;
+    }
+    if tail.len() >= 2 {
+        h ^= u32::from(tail[1]) << 8;
+    }
+    if !tail.is_empty() {
+        h ^= u32::from(tail[0]);
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::{pack_track_name, TrackType};
+    use crate::routing::{
+        murmur2, partition_for, partition_key, topic_for_service, topic_for_stream,
+        topic_for_track, Topic, TopicError,
+    };
+    use std::num::NonZeroU32;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_round_trip() {
+        let stream = *Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8")
+            .unwrap()
+            .as_bytes();
+        let track = pack_track_name("meta-faces").unwrap();
+
+        let topic = topic_for_stream(&stream);
+        assert_eq!(topic, "/streams/67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(Topic::parse(&topic), Ok(Topic::Stream(stream)));
+
+        let topic = topic_for_track(&stream, TrackType::Meta, &track);
+        assert_eq!(
+            topic,
+            "/streams/67e55044-10b1-426f-9247-bb680e5fe0c8/meta/meta-faces"
+        );
+        let parsed: Topic = topic.parse().unwrap();
+        assert_eq!(parsed.stream(), Some(&stream));
+        assert_eq!(parsed.to_string(), topic);
+
+        let topic = topic_for_service("storage").unwrap();
+        assert_eq!(topic, "/services/storage");
+        assert_eq!(
+            Topic::parse(&topic),
+            Ok(Topic::Service(String::from("storage")))
+        );
+    }
+
+    #[test]
+    fn test_invalid_topics() {
+        for topic in ["", "streams", "/ab/c", "/services", "/streams/a/b"] {
+            assert!(Topic::parse(topic).is_err(), "{}", topic);
+        }
+        assert_eq!(
+            Topic::parse("/streams/camera-1"),
+            Err(TopicError::StreamName(String::from("camera-1")))
+        );
+        assert_eq!(
+            Topic::parse("/streams/67e55044-10b1-426f-9247-bb680e5fe0c8/depth/main"),
+            Err(TopicError::TrackType(String::from("depth")))
+        );
+        assert_eq!(
+            Topic::parse("/streams/67e55044-10b1-426f-9247-bb680e5fe0c8/video/"),
+            Err(TopicError::TrackName(String::new()))
+        );
+        assert_eq!(
+            Topic::parse("/services/"),
+            Err(TopicError::ServiceName(String::new()))
+        );
+        assert!(topic_for_service("storage/v2").is_err());
+        assert!(topic_for_service("").is_err());
+    }
+
+    #[test]
+    fn test_partition_for() {
+        // the vectors of the Kafka client
+        for (key, hash) in [
+            ("21", -973_932_308),
+            ("foobar", -790_332_482),
+            ("a-little-bit-long-string", -985_981_536),
+            ("abc", 479_470_107),
+        ] {
+            assert_eq!(murmur2(key.as_bytes()) as i32, hash, "{}", key);
+        }
+        let partitions = |n| NonZeroU32::new(n).unwrap();
+        assert_eq!(partition_for(b"abc", partitions(7)), 479_470_107 % 7);
+
+        let stream = [7; 16];
+        let track = pack_track_name("main").unwrap();
+        let key = partition_key(&stream, Some(&track));
+        assert_eq!(key.len(), 20);
+        assert_eq!(&key[16..], b"main");
+        assert_eq!(partition_key(&stream, None), stream.to_vec());
+        assert!((0..64).all(|n| partition_for(&key, partitions(n + 1)) <= n));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::avro::{Builder, SERVICES_INFERENCE_RESULT_SCHEMA};
+use crate::objects::services::inference::{BoundingBox, Detection, InferenceResultMessage};
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+use crate::primitives::{AttributeValue, Attributes, ElementType, Unit};
+use std::fmt;
+use uuid::Uuid;
+
+/// Attribute of a metadata element naming the schema of its value.
+pub const CONTENT_SCHEMA_ATTRIBUTE: &str = "content-schema";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InteropError {
+    NotInferenceResult(Option<String>),
+    Decode,
+    Encode,
+}
+
+impl fmt::Display for InteropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InteropError::NotInferenceResult(Some(schema)) => {
+                write!(f, "Element carries `{}`, not an inference result", schema)
+            }
+            InteropError::NotInferenceResult(None) => {
+                write!(f, "Element doesn't name the schema of its value")
+            }
+            InteropError::Decode => write!(f, "Unable to decode the inference result"),
+            InteropError::Encode => write!(f, "Unable to encode the inference result"),
+        }
+    }
+}
+
+impl std::error::Error for InteropError {}
+
+/// Box of the frame metadata: the center, the size and the rotation in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RBBox {
+    pub xc: f64,
+    pub yc: f64,
+    pub width: f64,
+    pub height: f64,
+    pub angle: Option<f64>,
+}
+
+impl From<BoundingBox> for RBBox {
+    fn from(bbox: BoundingBox) -> Self {
+        RBBox {
+            xc: bbox.left + bbox.width / 2.0,
+            yc: bbox.top + bbox.height / 2.0,
+            width: bbox.width,
+            height: bbox.height,
+            angle: None,
+        }
+    }
+}
+
+impl From<RBBox> for BoundingBox {
+    /// A rotated box turns into the axis-aligned one enclosing it.
+    fn from(bbox: RBBox) -> Self {
+        let (width, height) = match bbox.angle {
+            Some(angle) if angle % 180.0 != 0.0 => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                (
+                    bbox.width * cos.abs() + bbox.height * sin.abs(),
+                    bbox.width * sin.abs() + bbox.height * cos.abs(),
+                )
+            }
+            _ => (bbox.width, bbox.height),
+        };
+        BoundingBox::new(bbox.xc - width / 2.0, bbox.yc - height / 2.0, width, height)
+    }
+}
+
+/// Object of the frame metadata; `creator` is the model which found it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMeta {
+    pub id: i64,
+    pub creator: String,
+    pub label: String,
+    pub confidence: Option<f64>,
+    pub bbox: RBBox,
+    pub track_id: Option<i64>,
+    pub attributes: Attributes,
+}
+
+/// Metadata of a frame as the inference pipeline keeps it: the source is the
+/// stream, the pts is the unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMeta {
+    pub source_id: String,
+    pub pts: i64,
+    pub objects: Vec<ObjectMeta>,
+}
+
+impl FrameMeta {
+    pub fn from_inference(message: &InferenceResultMessage) -> Self {
+        FrameMeta {
+            source_id: Uuid::from_bytes(message.stream_unit.stream_name).to_string(),
+            pts: message.stream_unit.unit,
+            objects: message
+                .detections
+                .iter()
+                .enumerate()
+                .map(|(i, d)| ObjectMeta {
+                    id: d.object_id.unwrap_or(i as i64),
+                    creator: message.model.clone(),
+                    label: d.label.clone(),
+                    confidence: Some(d.confidence),
+                    bbox: d.bbox.into(),
+                    track_id: d.track_id,
+                    attributes: d.attributes.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The objects of other creators than `model` are left out, an inference
+    /// result belongs to a single model. Objects without a confidence get `1.0`.
+    pub fn to_inference(
+        &self,
+        stream_unit: Unit,
+        element: ElementType,
+        model: &str,
+        produced_ms: u64,
+    ) -> InferenceResultMessage {
+        InferenceResultMessage::new(stream_unit, element, String::from(model), produced_ms)
+            .with_detections(self.objects.iter().filter(|o| o.creator == model).map(|o| {
+                Detection {
+                    object_id: Some(o.id),
+                    track_id: o.track_id,
+                    attributes: o.attributes.clone(),
+                    ..Detection::new(o.label.clone(), o.confidence.unwrap_or(1.0), o.bbox.into())
+                }
+            }))
+    }
+}
+
+/// Puts the inference result into an element of a metadata track; the element
+/// is marked with the schema of its value.
+pub fn to_unit_element(
+    mb: &Builder,
+    message: &InferenceResultMessage,
+    stream_unit: Unit,
+    element: ElementType,
+    last: bool,
+) -> Result<UnitElementMessage, InteropError> {
+    let value = message
+        .save(mb)
+        .and_then(|envelope| mb.save_from_avro(envelope).ok())
+        .ok_or(InteropError::Encode)?;
+    let attributes = Attributes::from([(
+        String::from(CONTENT_SCHEMA_ATTRIBUTE),
+        AttributeValue::from(SERVICES_INFERENCE_RESULT_SCHEMA),
+    )]);
+    Ok(UnitElementMessage::new(
+        stream_unit,
+        element,
+        value,
+        attributes,
+        last,
+    ))
+}
+
+pub fn from_unit_element(
+    mb: &Builder,
+    message: &UnitElementMessage,
+) -> Result<InferenceResultMessage, InteropError> {
+    match message.attributes.get(CONTENT_SCHEMA_ATTRIBUTE) {
+        Some(AttributeValue::String(schema)) if schema == SERVICES_INFERENCE_RESULT_SCHEMA => {}
+        Some(AttributeValue::String(schema)) => {
+            return Err(InteropError::NotInferenceResult(Some(schema.clone())))
+        }
+        _ => return Err(InteropError::NotInferenceResult(None)),
+    }
+    mb.load_to_avro(message.value.clone())
+        .and_then(|envelope| InferenceResultMessage::load(&envelope))
+        .ok_or(InteropError::Decode)
+}
+
+/// Frame metadata of a metadata track element.
+pub fn frame_meta_from_unit_element(
+    mb: &Builder,
+    message: &UnitElementMessage,
+) -> Result<FrameMeta, InteropError> {
+    from_unit_element(mb, message).map(|result| FrameMeta::from_inference(&result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::inference::{BoundingBox, Detection, InferenceResultMessage};
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::savant::{
+        frame_meta_from_unit_element, to_unit_element, FrameMeta, InteropError, RBBox,
+    };
+    use crate::utils::get_avro_path;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_round_trip() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let unit = Unit::new(
+            pack_stream_name(&stream_uuid).to_vec(),
+            pack_track_name("video").unwrap().to_vec(),
+            String::from("VIDEO"),
+            3,
+        );
+        let meta_unit = Unit {
+            track_name: pack_track_name("meta-faces").unwrap(),
+            ..unit.clone()
+        };
+        let result = InferenceResultMessage::new(unit.clone(), 0, String::from("faces:1"), 10)
+            .with_detections([Detection::tracked(
+                String::from("face"),
+                0.8,
+                BoundingBox::new(10.0, 20.0, 30.0, 40.0),
+                1,
+                5,
+            )]);
+
+        let element = to_unit_element(&mb, &result, meta_unit, 0, true).unwrap();
+        let frame = frame_meta_from_unit_element(&mb, &element).unwrap();
+        assert_eq!(frame.source_id, "fa807469-fbb3-4f63-b1a9-f63fbbf90f41");
+        assert_eq!(frame.pts, 3);
+        assert_eq!(frame.objects[0].bbox.xc, 25.0);
+        assert_eq!(frame.objects[0].creator, "faces:1");
+
+        let mut other = frame.objects[0].clone();
+        other.creator = String::from("plates:1");
+        let frame = FrameMeta {
+            objects: vec![frame.objects[0].clone(), other],
+            ..frame
+        };
+        assert_eq!(frame.to_inference(unit, 0, "faces:1", 10), result);
+
+        let mut element = element;
+        element.attributes.clear();
+        assert_eq!(
+            frame_meta_from_unit_element(&mb, &element),
+            Err(InteropError::NotInferenceResult(None))
+        );
+    }
+
+    #[test]
+    fn test_rotated_box() {
+        let bbox: BoundingBox = RBBox {
+            xc: 50.0,
+            yc: 50.0,
+            width: 20.0,
+            height: 10.0,
+            angle: Some(90.0),
+        }
+        .into();
+        assert!((bbox.width - 10.0).abs() < 1e-9);
+        assert!((bbox.height - 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,275 @@
+use avro_rs::Schema;
+use std::ops::Range;
+
+fn read_long(buf: &[u8], pos: &mut usize) -> Option<i64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+    }
+    None
+}
+
+fn read_len(buf: &[u8], pos: &mut usize) -> Option<usize> {
+    usize::try_from(read_long(buf, pos)?).ok()
+}
+
+fn advance(buf: &[u8], pos: &mut usize, len: usize) -> Option<()> {
+    let end = pos.checked_add(len)?;
+    if end > buf.len() {
+        return None;
+    }
+    *pos = end;
+    Some(())
+}
+
+/// Moves `pos` past the datum of the schema without materializing it.
+fn skip(schema: &Schema, buf: &[u8], pos: &mut usize) -> Option<()> {
+    match schema {
+        Schema::Null => Some(()),
+        Schema::Boolean => advance(buf, pos, 1),
+        Schema::Int
+        | Schema::Long
+        | Schema::Enum { .. }
+        | Schema::Date
+        | Schema::TimeMillis
+        | Schema::TimeMicros
+        | Schema::TimestampMillis
+        | Schema::TimestampMicros => read_long(buf, pos).map(|_| ()),
+        Schema::Float => advance(buf, pos, 4),
+        Schema::Double => advance(buf, pos, 8),
+        Schema::Bytes | Schema::String | Schema::Uuid => {
+            let len = read_len(buf, pos)?;
+            advance(buf, pos, len)
+        }
+        Schema::Fixed { size, .. } => advance(buf, pos, *size),
+        Schema::Array(items) => skip_blocks(buf, pos, |buf, pos| skip(items, buf, pos)),
+        Schema::Map(values) => skip_blocks(buf, pos, |buf, pos| {
+            skip(&Schema::String, buf, pos)?;
+            skip(values, buf, pos)
+        }),
+        Schema::Union(union) => {
+            let index = usize::try_from(read_long(buf, pos)?).ok()?;
+            skip(union.variants().get(index)?, buf, pos)
+        }
+        Schema::Record { fields, .. } => fields
+            .iter()
+            .try_for_each(|field| skip(&field.schema, buf, pos)),
+        Schema::Duration => advance(buf, pos, 12),
+        Schema::Decimal { inner, .. } => skip(inner, buf, pos),
+    }
+}
+
+fn skip_blocks<F>(buf: &[u8], pos: &mut usize, mut item: F) -> Option<()>
+where
+    F: FnMut(&[u8], &mut usize) -> Option<()>,
+{
+    loop {
+        match read_long(buf, pos)? {
+            0 => return Some(()),
+            count if count < 0 => {
+                // a negative count is followed by the size of the block in bytes
+                let size = read_len(buf, pos)?;
+                advance(buf, pos, size)?;
+            }
+            count => {
+                // every item takes a byte at least, more items than bytes is garbage
+                if count as u64 > (buf.len() - *pos) as u64 {
+                    return None;
+                }
+                (0..count).try_for_each(|_| item(buf, pos))?;
+            }
+        }
+    }
+}
+
+/// Whether every length the datum declares fits into the buffer; the decoder
+/// allocates by the declared lengths, a datum which fits can't make it allocate
+/// more than the buffer holds.
+pub(crate) fn fits(schema: &Schema, buf: &[u8]) -> bool {
+    skip(schema, buf, &mut 0).is_some()
+}
+
+/// Ranges of the bytes and string fields of the record at `pos`; the null branches
+/// of the optional ones are left out.
+#[cfg(feature = "zero-copy")]
+pub(crate) fn field_ranges<'s>(
+    schema: &'s Schema,
+    buf: &[u8],
+    pos: &mut usize,
+) -> Option<Vec<(&'s str, Range<usize>)>> {
+    let fields = match schema {
+        Schema::Record { fields, .. } => fields,
+        _ => return None,
+    };
+    let mut ranges = vec![];
+    for field in fields {
+        let branch = match &field.schema {
+            Schema::Union(union) => {
+                let index = usize::try_from(read_long(buf, pos)?).ok()?;
+                union.variants().get(index)?
+            }
+            other => other,
+        };
+        match branch {
+            Schema::Bytes | Schema::String => {
+                let len = read_len(buf, pos)?;
+                let start = *pos;
+                advance(buf, pos, len)?;
+                ranges.push((field.name.as_str(), start..*pos));
+            }
+            other => skip(other, buf, pos)?,
+        }
+    }
+    Some(ranges)
+}
+
+/// Serialized datum of every field of the record at `pos`, with the schema of the
+/// field, so single fields can be decoded without the rest of the record.
+pub(crate) fn field_spans<'s>(
+    schema: &'s Schema,
+    buf: &[u8],
+    pos: &mut usize,
+) -> Option<Vec<(&'s str, &'s Schema, Range<usize>)>> {
+    let fields = match schema {
+        Schema::Record { fields, .. } => fields,
+        _ => return None,
+    };
+    let mut spans = Vec::with_capacity(fields.len());
+    for field in fields {
+        let start = *pos;
+        skip(&field.schema, buf, pos)?;
+        spans.push((field.name.as_str(), &field.schema, start..*pos));
+    }
+    Some(spans)
+}
+
+/// Content of the bytes or string datum spanning `span`, without its length.
+pub(crate) fn bytes_content(buf: &[u8], span: Range<usize>) -> Option<Range<usize>> {
+    let mut pos = span.start;
+    let len = read_len(buf, &mut pos)?;
+    (pos.checked_add(len)? == span.end).then_some(pos..span.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scan::{bytes_content, field_spans, fits};
+    use avro_rs::types::Value;
+    use avro_rs::{to_avro_datum, Schema};
+    use std::collections::HashMap;
+
+    fn record_schema() -> Schema {
+        Schema::parse_str(
+            r#"{"type": "record", "name": "Scanned", "fields": [
+                {"name": "stream_id", "type": "string"},
+                {"name": "payload", "type": "bytes"},
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+                {"name": "counts", "type": {"type": "map", "values": "long"}},
+                {"name": "alias", "type": ["null", "string"]}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    fn record() -> Value {
+        Value::Record(vec![
+            (
+                String::from("stream_id"),
+                Value::String(String::from("cam")),
+            ),
+            (String::from("payload"), Value::Bytes(vec![1, 2, 3, 4])),
+            (
+                String::from("tags"),
+                Value::Array(vec![Value::String(String::from("a")); 3]),
+            ),
+            (
+                String::from("counts"),
+                Value::Map(HashMap::from([(String::from("units"), Value::Long(-7))])),
+            ),
+            (
+                String::from("alias"),
+                Value::Union(Box::new(Value::String(String::from("front")))),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_fits() {
+        let schema = record_schema();
+        let datum = to_avro_datum(&schema, record()).unwrap();
+        assert!(fits(&schema, &datum));
+        for len in 0..datum.len() {
+            assert!(!fits(&schema, &datum[..len]));
+        }
+    }
+
+    #[test]
+    fn test_truncated_varint() {
+        assert!(fits(&Schema::Long, &[0x80, 0x01]));
+        assert!(!fits(&Schema::Long, &[0x80, 0x80]));
+        assert!(!fits(&Schema::Long, &[]));
+        // the continuation bit set on all ten bytes of a long
+        assert!(!fits(&Schema::Long, &[0xff; 10]));
+    }
+
+    #[test]
+    fn test_length_past_buffer() {
+        // 10 bytes declared, 3 present
+        assert!(!fits(&Schema::Bytes, &[0x14, 1, 2, 3]));
+        assert!(fits(&Schema::Bytes, &[0x06, 1, 2, 3]));
+        // a negative length
+        assert!(!fits(&Schema::String, &[0x01, b'a']));
+        let fixed = Schema::parse_str(r#"{"type": "fixed", "name": "Id", "size": 16}"#).unwrap();
+        assert!(!fits(&fixed, &[0; 15]));
+        // more items than bytes left
+        let array = Schema::Array(Box::new(Schema::Null));
+        assert!(!fits(&array, &[0x7e, 0x00]));
+    }
+
+    #[test]
+    fn test_negative_block_count() {
+        let array = Schema::Array(Box::new(Schema::Long));
+        // -2 items in a block of 2 bytes, then the end of the array
+        assert!(fits(&array, &[0x03, 0x04, 0x02, 0x04, 0x00]));
+        // the block size runs past the buffer
+        assert!(!fits(&array, &[0x03, 0x10, 0x02, 0x04, 0x00]));
+        // a negative block size
+        assert!(!fits(&array, &[0x03, 0x01, 0x02, 0x04, 0x00]));
+        let map = Schema::Map(Box::new(Schema::Long));
+        assert!(fits(&map, &[0x03, 0x06, 0x02, b'k', 0x02, 0x00]));
+        assert!(!fits(&map, &[0x03, 0x06, 0x02, b'k', 0x02]));
+    }
+
+    #[test]
+    fn test_union_branch_out_of_range() {
+        let union = Schema::parse_str(r#"["null", "string"]"#).unwrap();
+        assert!(fits(&union, &[0x00]));
+        assert!(fits(&union, &[0x02, 0x02, b'a']));
+        assert!(!fits(&union, &[0x04, 0x02, b'a']));
+        assert!(!fits(&union, &[0x01]));
+    }
+
+    #[test]
+    fn test_field_spans() {
+        let schema = record_schema();
+        let datum = to_avro_datum(&schema, record()).unwrap();
+        let spans = field_spans(&schema, &datum, &mut 0).unwrap();
+        let names: Vec<&str> = spans.iter().map(|(name, _, _)| *name).collect();
+        assert_eq!(names, ["stream_id", "payload", "tags", "counts", "alias"]);
+        assert_eq!(spans.last().unwrap().2.end, datum.len());
+
+        let payload = spans[1].2.clone();
+        let content = bytes_content(&datum, payload.clone()).unwrap();
+        assert_eq!(&datum[content], &[1, 2, 3, 4]);
+        assert_eq!(bytes_content(&datum, payload.start..payload.end - 1), None);
+        assert_eq!(field_spans(&Schema::Long, &datum, &mut 0), None);
+        assert_eq!(
+            field_spans(&schema, &datum[..datum.len() - 1], &mut 0),
+            None
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::avro::{BuilderImpl, SchemaLoadError};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Reloads the schemas of a builder as its directory changes, see
+/// [`BuilderImpl::watch`]. Dropping it stops the watching.
+pub struct SchemaWatcher {
+    reloaded: Arc<AtomicBool>,
+    _watcher: RecommendedWatcher,
+}
+
+impl SchemaWatcher {
+    /// Whether new schemas were swapped in since the last call.
+    pub fn reloaded(&self) -> bool {
+        self.reloaded.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl BuilderImpl {
+    /// Swaps the reloaded schemas into the builder as soon as its directory changes,
+    /// see [`BuilderImpl::reload`]. Takes any handle of the builder, e.g. the one of
+    /// [`BuilderImpl::shared`] or [`BuilderImpl::global`]. A directory which fails to
+    /// load is passed to `on_error` and the current schemas stay, a schema patch
+    /// written in several steps gets through once the last step lands.
+    pub fn watch<B, F>(builder: B, on_error: F) -> notify::Result<SchemaWatcher>
+    where
+        B: Deref<Target = BuilderImpl> + Send + 'static,
+        F: Fn(SchemaLoadError) + Send + 'static,
+    {
+        let path_prefix = String::from(builder.path_prefix());
+        let reloaded = Arc::new(AtomicBool::new(false));
+        let flag = reloaded.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if !is_change(&event) {
+                return;
+            }
+            match builder.reload() {
+                Ok(()) => flag.store(true, Ordering::Release),
+                Err(e) => on_error(e),
+            }
+        })?;
+        watcher.watch(Path::new(&path_prefix), RecursiveMode::Recursive)?;
+        Ok(SchemaWatcher {
+            reloaded,
+            _watcher: watcher,
+        })
+    }
+}
+
+fn is_change(event: &notify::Result<Event>) -> bool {
+    matches!(event, Ok(event) if !matches!(event.kind, EventKind::Access(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{BuilderImpl, SchemaLoadError, KEEPALIVE_MESSAGE_SCHEMA, TRANSPORT_SCHEMAS};
+    use crate::utils::get_avro_path;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn eventually(condition: impl Fn() -> bool) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(10) {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_watch() {
+        let dir = std::env::temp_dir().join(format!("protocol-watch-{}", std::process::id()));
+        for (sub_dir, file) in BuilderImpl::schema_files() {
+            fs::create_dir_all(dir.join(sub_dir)).unwrap();
+            fs::copy(
+                Path::new(&get_avro_path()).join(sub_dir).join(file),
+                dir.join(sub_dir).join(file),
+            )
+            .unwrap();
+        }
+        let builder = Arc::new(BuilderImpl::new(dir.to_str().unwrap()));
+        let (errors_tx, errors) = mpsc::channel();
+        let watcher = BuilderImpl::watch(builder.clone(), move |e| {
+            let _ = errors_tx.send(e);
+        })
+        .unwrap();
+        let fingerprint = builder.schema_fingerprint(KEEPALIVE_MESSAGE_SCHEMA);
+        assert!(!watcher.reloaded());
+
+        // a field more changes the canonical form, so the fingerprint
+        let keep_alive = dir.join(TRANSPORT_SCHEMAS).join(KEEPALIVE_MESSAGE_SCHEMA);
+        let original = fs::read_to_string(&keep_alive).unwrap();
+        let fields = original.find("\"fields\"").unwrap();
+        let at = fields + original[fields..].find('[').unwrap() + 1;
+        let mut patched = original.clone();
+        patched.insert_str(at, r#"{"name": "watched", "type": "int", "default": 0},"#);
+        fs::write(&keep_alive, patched).unwrap();
+        assert!(eventually(|| {
+            builder.schema_fingerprint(KEEPALIVE_MESSAGE_SCHEMA) != fingerprint
+        }));
+        assert!(watcher.reloaded());
+
+        while errors.try_recv().is_ok() {}
+        fs::write(&keep_alive, "{").unwrap();
+        assert!(matches!(
+            errors.recv_timeout(Duration::from_secs(10)),
+            Ok(SchemaLoadError::Parse(_))
+        ));
+        assert!(builder.get_schema(KEEPALIVE_MESSAGE_SCHEMA).is_some());
+
+        drop(watcher);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
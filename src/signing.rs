@@ -0,0 +1,119 @@
+use crate::auth::AuthToken;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The envelope fields a signature covers.
+pub struct SignedEnvelope<'a> {
+    pub key_id: &'a str,
+    pub schema_name: &'a str,
+    pub namespace: &'a str,
+    pub auth: Option<&'a AuthToken>,
+    pub payload: &'a [u8],
+}
+
+/// Feeds a length-prefixed field, so the bytes of one field can't be moved into
+/// the next one.
+fn update_field(mac: &mut HmacSha256, field: &[u8]) {
+    mac.update(&(field.len() as u64).to_be_bytes());
+    mac.update(field);
+}
+
+fn envelope_mac(key: &[u8], envelope: &SignedEnvelope) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    update_field(&mut mac, envelope.key_id.as_bytes());
+    update_field(&mut mac, envelope.schema_name.as_bytes());
+    update_field(&mut mac, envelope.namespace.as_bytes());
+    match envelope.auth {
+        Some(auth) => {
+            mac.update(&[1]);
+            update_field(&mut mac, auth.scheme.as_bytes());
+            update_field(&mut mac, auth.token.as_bytes());
+        }
+        None => mac.update(&[0]),
+    }
+    update_field(&mut mac, envelope.payload);
+    mac
+}
+
+pub fn sign_envelope(key: &[u8], envelope: &SignedEnvelope) -> Vec<u8> {
+    envelope_mac(key, envelope).finalize().into_bytes().to_vec()
+}
+
+pub fn verify_envelope(key: &[u8], envelope: &SignedEnvelope, signature: &[u8]) -> bool {
+    envelope_mac(key, envelope).verify_slice(signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::AuthToken;
+    use crate::signing::{sign_envelope, verify_envelope, SignedEnvelope};
+
+    fn envelope<'a>(payload: &'a [u8], auth: Option<&'a AuthToken>) -> SignedEnvelope<'a> {
+        SignedEnvelope {
+            key_id: "k1",
+            schema_name: "schema",
+            namespace: "tenant-a",
+            auth,
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let signature = sign_envelope(b"secret", &envelope(&[1, 2, 3], None));
+        assert!(verify_envelope(
+            b"secret",
+            &envelope(&[1, 2, 3], None),
+            &signature
+        ));
+        assert!(!verify_envelope(
+            b"secret",
+            &envelope(&[1, 2, 4], None),
+            &signature
+        ));
+        assert!(!verify_envelope(
+            b"other",
+            &envelope(&[1, 2, 3], None),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_envelope_fields() {
+        let auth = AuthToken::new(String::from("Bearer"), String::from("token"));
+        let signature = sign_envelope(b"secret", &envelope(&[1, 2, 3], Some(&auth)));
+        assert!(verify_envelope(
+            b"secret",
+            &envelope(&[1, 2, 3], Some(&auth)),
+            &signature
+        ));
+
+        let moved = SignedEnvelope {
+            namespace: "tenant-b",
+            ..envelope(&[1, 2, 3], Some(&auth))
+        };
+        assert!(!verify_envelope(b"secret", &moved, &signature));
+
+        let stolen = AuthToken::new(String::from("Bearer"), String::from("other"));
+        assert!(!verify_envelope(
+            b"secret",
+            &envelope(&[1, 2, 3], Some(&stolen)),
+            &signature
+        ));
+        assert!(!verify_envelope(
+            b"secret",
+            &envelope(&[1, 2, 3], None),
+            &signature
+        ));
+
+        // the bytes of the namespace can't be shifted into the schema name
+        let shifted = SignedEnvelope {
+            schema_name: "schematenant-a",
+            namespace: "",
+            ..envelope(&[1, 2, 3], Some(&auth))
+        };
+        assert!(!verify_envelope(b"secret", &shifted, &signature));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::objects::services::keep_alive::KeepAliveMessage;
+use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+use crate::objects::services::slo_report::SloReport;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloTargets {
+    pub availability: f64,
+    pub latency_ms: f64,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ServiceWindow {
+    /// Whether the service ever sent a keep-alive, so a silent window counts as down.
+    keeps_alive: bool,
+    alive_slots: BTreeSet<u64>,
+    latencies_ms: Vec<f64>,
+}
+
+/// Derives per-service SLO compliance from the messages seen on the bus.
+///
+/// Availability is the share of keep-alive intervals of the window in which the
+/// service sent at least one `KeepAliveMessage`; services are keyed by module id.
+/// Latency is measured between ping requests and responses on the same topic and
+/// request id; such services are keyed by topic. A service once seen is reported
+/// in every later window, a silent one with the availability of 0; a ping left
+/// unanswered for a whole window is dropped.
+#[derive(Debug, Clone)]
+pub struct SloTracker {
+    targets: SloTargets,
+    keepalive_interval_ms: u64,
+    window_ms: u64,
+    window_start_ms: u64,
+    services: HashMap<String, ServiceWindow>,
+    pending_pings: HashMap<(String, i64), u64>,
+}
+
+impl SloTracker {
+    pub fn new(
+        targets: SloTargets,
+        keepalive_interval_ms: u64,
+        window_ms: u64,
+        now_ms: u64,
+    ) -> Self {
+        assert!(
+            keepalive_interval_ms > 0,
+            "Keep-alive interval must be positive"
+        );
+        SloTracker {
+            targets,
+            keepalive_interval_ms,
+            window_ms,
+            window_start_ms: now_ms,
+            services: HashMap::default(),
+            pending_pings: HashMap::default(),
+        }
+    }
+
+    pub fn on_keep_alive(&mut self, message: &KeepAliveMessage, now_ms: u64) {
+        let slot = now_ms.saturating_sub(self.window_start_ms) / self.keepalive_interval_ms;
+        let window = self.services.entry(message.module_id.clone()).or_default();
+        window.keeps_alive = true;
+        window.alive_slots.insert(slot);
+    }
+
+    pub fn on_ping(&mut self, message: &PingRequestResponse, now_ms: u64) {
+        let key = (message.topic.clone(), message.request_id);
+        match message.mtype {
+            PingRequestResponseType::Request => {
+                self.pending_pings.insert(key, now_ms);
+            }
+            PingRequestResponseType::Response => {
+                if let Some(sent_ms) = self.pending_pings.remove(&key) {
+                    self.services
+                        .entry(key.0)
+                        .or_default()
+                        .latencies_ms
+                        .push(now_ms.saturating_sub(sent_ms) as f64);
+                }
+            }
+        }
+    }
+
+    /// Emits the reports once the window has elapsed and starts the next one.
+    pub fn poll(&mut self, now_ms: u64) -> Vec<SloReport> {
+        if now_ms.saturating_sub(self.window_start_ms) < self.window_ms {
+            return vec![];
+        }
+        let window_end_ms = self.window_start_ms + self.window_ms;
+        let total_slots = self.window_ms.div_ceil(self.keepalive_interval_ms).max(1);
+
+        let mut reports: Vec<_> = self
+            .services
+            .iter_mut()
+            .map(|(service, window)| {
+                let alive_slots = std::mem::take(&mut window.alive_slots);
+                let latencies_ms = std::mem::take(&mut window.latencies_ms);
+                let availability = if window.keeps_alive {
+                    alive_slots.range(..total_slots).count() as f64 / total_slots as f64
+                } else {
+                    1.0
+                };
+                let latency_samples = latencies_ms.len() as i64;
+                let latency_p95_ms = percentile(latencies_ms, 0.95);
+                let compliant = availability >= self.targets.availability
+                    && latency_p95_ms <= self.targets.latency_ms;
+                SloReport::new(
+                    service.clone(),
+                    self.window_start_ms,
+                    window_end_ms,
+                    availability,
+                    self.targets.availability,
+                    latency_p95_ms,
+                    self.targets.latency_ms,
+                    latency_samples,
+                    compliant,
+                )
+            })
+            .collect();
+        reports.sort_by(|a, b| a.service.cmp(&b.service));
+
+        let window_start_ms = self.window_start_ms;
+        self.pending_pings
+            .retain(|_, sent_ms| *sent_ms >= window_start_ms);
+        self.window_start_ms = window_end_ms;
+        reports
+    }
+}
+
+fn percentile(mut samples: Vec<f64>, p: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.sort_by(f64::total_cmp);
+    let rank = ((samples.len() as f64) * p).ceil() as usize;
+    samples[rank.clamp(1, samples.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+    use crate::slo::{SloTargets, SloTracker};
+
+    #[test]
+    fn test_report() {
+        let targets = SloTargets {
+            availability: 0.9,
+            latency_ms: 20.0,
+        };
+        let mut tracker = SloTracker::new(targets, 1000, 10_000, 0);
+        let keepalive = KeepAliveMessage::new(String::from("storage"));
+        for ts in (0..8000).step_by(1000) {
+            tracker.on_keep_alive(&keepalive, ts);
+        }
+        for (id, rtt) in [(1, 5), (2, 10), (3, 30)] {
+            let req = PingRequestResponse::new(
+                id,
+                String::from("ping"),
+                PingRequestResponseType::Request,
+            );
+            let rep = PingRequestResponse::new(
+                id,
+                String::from("ping"),
+                PingRequestResponseType::Response,
+            );
+            tracker.on_ping(&req, 100);
+            tracker.on_ping(&rep, 100 + rtt);
+        }
+
+        assert!(tracker.poll(9999).is_empty());
+        let reports = tracker.poll(10_000);
+        assert_eq!(reports.len(), 2);
+
+        let ping = &reports[0];
+        assert_eq!(ping.service, "ping");
+        assert_eq!(ping.latency_samples, 3);
+        assert_eq!(ping.latency_p95_ms, 30.0);
+        assert!(!ping.compliant);
+
+        let storage = &reports[1];
+        assert_eq!(storage.service, "storage");
+        assert!((storage.availability - 0.8).abs() < 1e-9);
+        assert!(!storage.compliant);
+
+        assert!(tracker.poll(10_001).is_empty());
+
+        // the storage is silent in the next window, a ping is left unanswered
+        let req =
+            PingRequestResponse::new(4, String::from("ping"), PingRequestResponseType::Request);
+        tracker.on_ping(&req, 15_000);
+        tracker.on_keep_alive(&KeepAliveMessage::new(String::from("search")), 15_000);
+        let reports = tracker.poll(20_000);
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].service, "ping");
+        assert_eq!(reports[0].latency_samples, 0);
+        assert_eq!(reports[1].service, "search");
+        assert!((reports[1].availability - 0.1).abs() < 1e-9);
+        assert_eq!(reports[2].service, "storage");
+        assert_eq!(reports[2].availability, 0.0);
+        assert!(!reports[2].compliant);
+
+        // a ping unanswered for a whole window is dropped
+        assert_eq!(tracker.poll(30_000).len(), 3);
+        let rep =
+            PingRequestResponse::new(4, String::from("ping"), PingRequestResponseType::Response);
+        tracker.on_ping(&rep, 30_010);
+        assert_eq!(tracker.poll(40_000)[0].latency_samples, 0);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::services::storage::stream_end_message::{StreamEndMessage, StreamEndReason};
+use crate::objects::services::storage::stream_lifecycle_message::{
+    StreamLifecycleEvent, StreamLifecycleMessage,
+};
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::primitives::{ElementType, NotifyTypeImpl, StreamName, TrackInfo, Unit};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackState {
+    pub track_info: TrackInfo,
+    pub last_unit: i64,
+    pub last_element: Option<ElementType>,
+    pub last_saved_ms: Option<u64>,
+    pub ended: Option<StreamEndReason>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    TrackDiscovered(TrackInfo),
+    UnitStarted {
+        track_info: TrackInfo,
+        unit: i64,
+    },
+    UnitReady {
+        track_info: TrackInfo,
+        unit: i64,
+        last_element: ElementType,
+    },
+    UnitCompleted {
+        track_info: TrackInfo,
+        unit: i64,
+    },
+    TrackEnded {
+        track_info: TrackInfo,
+        last_unit: i64,
+        reason: StreamEndReason,
+    },
+    StreamLifecycle(StreamLifecycleEvent),
+    StreamAlive,
+    StreamStale,
+}
+
+/// Keeps the state of a single stream built from the messages a consumer receives.
+///
+/// Messages which belong to other streams are ignored, so a consumer may feed the
+/// machine with everything it reads from a topic.
+#[derive(Debug, Clone)]
+pub struct StreamStateMachine {
+    stream_name: StreamName,
+    liveness_timeout_ms: u64,
+    tracks: HashMap<TrackInfo, TrackState>,
+    last_activity_ms: Option<u64>,
+    alive: bool,
+    lifecycle: Option<StreamLifecycleEvent>,
+}
+
+impl StreamStateMachine {
+    pub fn new(stream_name: StreamName, liveness_timeout_ms: u64) -> Self {
+        StreamStateMachine {
+            stream_name,
+            liveness_timeout_ms,
+            tracks: HashMap::default(),
+            last_activity_ms: None,
+            alive: false,
+            lifecycle: None,
+        }
+    }
+
+    pub fn stream_name(&self) -> &StreamName {
+        &self.stream_name
+    }
+
+    pub fn tracks(&self) -> Vec<&TrackState> {
+        self.tracks.values().collect()
+    }
+
+    pub fn track(&self, track_info: &TrackInfo) -> Option<&TrackState> {
+        self.tracks.get(track_info)
+    }
+
+    pub fn last_position(&self, track_info: &TrackInfo) -> Option<(i64, Option<ElementType>)> {
+        self.tracks
+            .get(track_info)
+            .map(|t| (t.last_unit, t.last_element))
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn last_activity_ms(&self) -> Option<u64> {
+        self.last_activity_ms
+    }
+
+    pub fn lifecycle(&self) -> Option<StreamLifecycleEvent> {
+        self.lifecycle
+    }
+
+    pub fn on_notify(&mut self, message: &NotifyMessage, now_ms: u64) -> Vec<StreamEvent> {
+        if message.stream_unit.stream_name != self.stream_name {
+            return vec![];
+        }
+        let mut events = self.touch(now_ms);
+        let track_info = Self::track_info(&message.stream_unit);
+        let unit = message.stream_unit.unit;
+        self.update_unit(&track_info, unit, &mut events);
+
+        let state = self.tracks.get_mut(&track_info).unwrap();
+        state.last_saved_ms = Some(message.saved_ms);
+        if let NotifyTypeImpl::Ready(last_element) = message.notify_type.obj {
+            state.last_element = Some(last_element);
+            events.push(StreamEvent::UnitReady {
+                track_info,
+                unit,
+                last_element,
+            });
+        }
+        events
+    }
+
+    pub fn on_unit_element(
+        &mut self,
+        message: &UnitElementMessage,
+        now_ms: u64,
+    ) -> Vec<StreamEvent> {
+        if message.stream_unit.stream_name != self.stream_name {
+            return vec![];
+        }
+        let mut events = self.touch(now_ms);
+        let track_info = Self::track_info(&message.stream_unit);
+        let unit = message.stream_unit.unit;
+        self.update_unit(&track_info, unit, &mut events);
+
+        let state = self.tracks.get_mut(&track_info).unwrap();
+        state.last_element = Some(message.element);
+        if message.last {
+            events.push(StreamEvent::UnitCompleted { track_info, unit });
+        }
+        events
+    }
+
+    pub fn on_stream_end(&mut self, message: &StreamEndMessage, now_ms: u64) -> Vec<StreamEvent> {
+        if message.stream_name != self.stream_name {
+            return vec![];
+        }
+        let mut events = self.touch(now_ms);
+        let track_info = message.track_info;
+        self.update_unit(&track_info, message.last_unit, &mut events);
+
+        let state = self.tracks.get_mut(&track_info).unwrap();
+        state.ended = Some(message.reason);
+        events.push(StreamEvent::TrackEnded {
+            track_info,
+            last_unit: message.last_unit,
+            reason: message.reason,
+        });
+        events
+    }
+
+    /// Deleted streams drop the known tracks, so a stream re-created under the same
+    /// name is discovered from scratch.
+    pub fn on_lifecycle(
+        &mut self,
+        message: &StreamLifecycleMessage,
+        now_ms: u64,
+    ) -> Vec<StreamEvent> {
+        if message.stream_name != self.stream_name {
+            return vec![];
+        }
+        let mut events = self.touch(now_ms);
+        if message.event == StreamLifecycleEvent::Deleted {
+            self.tracks.clear();
+        }
+        self.lifecycle = Some(message.event);
+        events.push(StreamEvent::StreamLifecycle(message.event));
+        events
+    }
+
+    /// Reports the stream as stale when nothing was received for longer than
+    /// the liveness timeout.
+    pub fn check_liveness(&mut self, now_ms: u64) -> Vec<StreamEvent> {
+        match self.last_activity_ms {
+            Some(last) if self.alive && now_ms.saturating_sub(last) > self.liveness_timeout_ms => {
+                self.alive = false;
+                vec![StreamEvent::StreamStale]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn touch(&mut self, now_ms: u64) -> Vec<StreamEvent> {
+        self.last_activity_ms = Some(now_ms);
+        if self.alive {
+            vec![]
+        } else {
+            self.alive = true;
+            vec![StreamEvent::StreamAlive]
+        }
+    }
+
+    fn update_unit(&mut self, track_info: &TrackInfo, unit: i64, events: &mut Vec<StreamEvent>) {
+        match self.tracks.get_mut(track_info) {
+            None => {
+                self.tracks.insert(
+                    *track_info,
+                    TrackState {
+                        track_info: *track_info,
+                        last_unit: unit,
+                        last_element: None,
+                        last_saved_ms: None,
+                        ended: None,
+                    },
+                );
+                events.push(StreamEvent::TrackDiscovered(*track_info));
+                events.push(StreamEvent::UnitStarted {
+                    track_info: *track_info,
+                    unit,
+                });
+            }
+            Some(state) if unit > state.last_unit => {
+                state.last_unit = unit;
+                state.last_element = None;
+                events.push(StreamEvent::UnitStarted {
+                    track_info: *track_info,
+                    unit,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn track_info(unit: &Unit) -> TrackInfo {
+        TrackInfo::new(unit.track_type, unit.track_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::services::storage::notify_message::NotifyMessage;
+    use crate::objects::services::storage::stream_end_message::{
+        StreamEndMessage, StreamEndReason,
+    };
+    use crate::objects::services::storage::stream_lifecycle_message::{
+        StreamLifecycleEvent, StreamLifecycleMessage,
+    };
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::primitives::{pack_stream_name, pack_track_name, NotifyType, TrackInfo, Unit};
+    use crate::stream_state::{StreamEvent, StreamStateMachine};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_transitions() {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let unit = Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        );
+        let track_info = TrackInfo::new(unit.track_type, unit.track_name);
+
+        let mut sm = StreamStateMachine::new(stream_name, 1000);
+        let events = sm.on_notify(&NotifyMessage::new(unit.clone(), 10, NotifyType::new()), 10);
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::StreamAlive,
+                StreamEvent::TrackDiscovered(track_info),
+                StreamEvent::UnitStarted {
+                    track_info,
+                    unit: 3
+                }
+            ]
+        );
+
+        let events = sm.on_unit_element(
+            &UnitElementMessage::new(unit, 0, vec![], HashMap::default(), true),
+            20,
+        );
+        assert_eq!(
+            events,
+            vec![StreamEvent::UnitCompleted {
+                track_info,
+                unit: 3
+            }]
+        );
+        assert_eq!(sm.last_position(&track_info), Some((3, Some(0))));
+
+        let events = sm.on_stream_end(
+            &StreamEndMessage::new(stream_name, track_info, 3, StreamEndReason::Finished),
+            30,
+        );
+        assert_eq!(
+            events,
+            vec![StreamEvent::TrackEnded {
+                track_info,
+                last_unit: 3,
+                reason: StreamEndReason::Finished
+            }]
+        );
+
+        assert!(sm.check_liveness(500).is_empty());
+        assert_eq!(sm.check_liveness(1500), vec![StreamEvent::StreamStale]);
+        assert!(!sm.is_alive());
+
+        let events = sm.on_lifecycle(
+            &StreamLifecycleMessage::new(stream_name, StreamLifecycleEvent::Deleted, 1600),
+            1600,
+        );
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::StreamAlive,
+                StreamEvent::StreamLifecycle(StreamLifecycleEvent::Deleted)
+            ]
+        );
+        assert!(sm.tracks().is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::avro::Builder;
+use crate::testkit::corpus::golden_corpus;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Inputs up to this length are generated exhaustively.
+pub const EXHAUSTIVE_LENGTH: usize = 2;
+
+/// Only the bytes of this prefix of the golden messages are mutated: it holds the
+/// envelope and the head of the payload, where the lengths and the union indices are.
+pub const MUTATED_PREFIX: usize = 256;
+
+const SUBSTITUTIONS: [u8; 4] = [0x00, 0xff, 0x80, 0x7f];
+
+/// Calls `check` with every adversarial input: all the byte strings up to
+/// `EXHAUSTIVE_LENGTH`, then the truncations and the byte substitutions of the
+/// golden corpus messages.
+pub fn for_each_adversarial_input<F>(mb: &Builder, mut check: F)
+where
+    F: FnMut(&[u8]),
+{
+    let mut input = Vec::with_capacity(EXHAUSTIVE_LENGTH);
+    exhaustive(&mut input, &mut check);
+
+    for case in golden_corpus(mb) {
+        let message = case.message;
+        let prefix = message.len().min(MUTATED_PREFIX);
+        (0..prefix).for_each(|len| check(&message[..len]));
+        check(&message[..message.len().saturating_sub(1)]);
+
+        let mut mutated = message.clone();
+        for position in 0..prefix {
+            for substitution in SUBSTITUTIONS {
+                if message[position] != substitution {
+                    mutated[position] = substitution;
+                    check(&mutated);
+                }
+            }
+            mutated[position] = message[position];
+        }
+    }
+}
+
+fn exhaustive<F>(input: &mut Vec<u8>, check: &mut F)
+where
+    F: FnMut(&[u8]),
+{
+    check(input);
+    if input.len() == EXHAUSTIVE_LENGTH {
+        return;
+    }
+    for byte in 0..=u8::MAX {
+        input.push(byte);
+        exhaustive(input, check);
+        input.pop();
+    }
+}
+
+/// Whether decoding the input panics anywhere: in the envelope reads or in the
+/// decoding of the message itself.
+pub fn decode_panics(mb: &Builder, input: &[u8]) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let input = input.to_vec();
+        mb.read_labels(input.clone());
+        mb.read_priority(input.clone());
+        mb.read_trace_context(input.clone());
+        mb.read_namespace(input.clone());
+        mb.read_auth(input.clone());
+        mb.read_hops(input.clone());
+        mb.load_to_avro(input);
+    }))
+    .is_err()
+}
+
+/// The adversarial inputs which panic the decoder, empty when it's hardened.
+pub fn find_panics(mb: &Builder) -> Vec<Vec<u8>> {
+    let mut panicking = vec![];
+    for_each_adversarial_input(mb, |input| {
+        if decode_panics(mb, input) {
+            panicking.push(input.to_vec());
+        }
+    });
+    panicking
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::testkit::adversarial::{find_panics, for_each_adversarial_input};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_decoder_never_panics() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut count = 0;
+        for_each_adversarial_input(&mb, |_| count += 1);
+        assert!(count > 1 << 16);
+
+        assert_eq!(find_panics(&mb), Vec::<Vec<u8>>::new());
+    }
+}
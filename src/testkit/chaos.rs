@@ -0,0 +1,178 @@
+use crate::avro::Builder;
+use crate::objects::services::ffprobe::ServicesFFProbeRequest;
+use crate::objects::services::keep_alive::KeepAliveMessage;
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::services::storage::stream_tracks::StreamTracksRequest;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::ToProtocolMessage;
+use crate::primitives::{
+    get_empty_track_name, AttributeValue, Attributes, NotifyType, StreamName, Unit,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    pub max_payload_size: usize,
+    pub attribute_count: usize,
+    pub attribute_value_size: usize,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        ChaosConfig {
+            max_payload_size: 16 * 1024 * 1024,
+            attribute_count: 10_000,
+            attribute_value_size: 256,
+        }
+    }
+}
+
+/// Message which is valid against the schemas but pushes a consumer to its limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosCase {
+    pub name: &'static str,
+    pub message: Vec<u8>,
+}
+
+/// Produces pathological, schema-valid messages for resilience tests.
+///
+/// The injector doesn't know about transports: `inject` hands the serialized
+/// messages to a sink which publishes them the way the system under test expects.
+pub struct ChaosInjector {
+    config: ChaosConfig,
+}
+
+impl ChaosInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        ChaosInjector { config }
+    }
+
+    pub fn cases(&self, mb: &Builder) -> Vec<ChaosCase> {
+        let stream_unit = Unit::new(vec![0xff; 16], vec![b'c'; 16], String::from("VIDEO"), 0);
+        let empty_unit = Unit::new(
+            StreamName::default().to_vec(),
+            get_empty_track_name().to_vec(),
+            String::from("VIDEO"),
+            0,
+        );
+
+        let mut cases = vec![];
+        let mut push = |name: &'static str, message: &dyn ToProtocolMessage| {
+            if let Some(message) = message.save(mb).and_then(|e| mb.save_from_avro(e).ok()) {
+                cases.push(ChaosCase { name, message });
+            }
+        };
+
+        push(
+            "max_size_payload",
+            &UnitElementMessage::new(
+                stream_unit.clone(),
+                0,
+                vec![0xaa; self.config.max_payload_size],
+                Attributes::default(),
+                true,
+            ),
+        );
+        push(
+            "zero_length_names",
+            &UnitElementMessage::new(empty_unit.clone(), 0, vec![], Attributes::default(), true),
+        );
+        push(
+            "zero_length_stream_tracks",
+            &StreamTracksRequest::new(0, String::default(), StreamName::default()),
+        );
+        push("empty_module_id", &KeepAliveMessage::new(String::default()));
+
+        let mut max_unit = stream_unit.clone();
+        max_unit.unit = i64::MAX;
+        push(
+            "max_timestamp",
+            &NotifyMessage::new(max_unit, i64::MAX as u64, NotifyType::new()),
+        );
+        let mut min_unit = stream_unit.clone();
+        min_unit.unit = i64::MIN;
+        push(
+            "min_timestamp",
+            &NotifyMessage::new(min_unit, 0, NotifyType::ready(i16::MAX)),
+        );
+        push(
+            "negative_element",
+            &UnitElementMessage::new(
+                stream_unit.clone(),
+                i16::MIN,
+                vec![],
+                Attributes::default(),
+                false,
+            ),
+        );
+
+        let attributes = self.huge_attributes();
+        push(
+            "huge_attribute_map",
+            &UnitElementMessage::new(stream_unit, 0, vec![], attributes.clone(), true),
+        );
+        push(
+            "huge_service_attributes",
+            &ServicesFFProbeRequest::new(
+                i64::MIN,
+                "t".repeat(self.config.attribute_value_size),
+                String::default(),
+                attributes,
+            ),
+        );
+        cases
+    }
+
+    /// Sends every case to the sink and returns the number of messages injected.
+    pub fn inject<F>(&self, mb: &Builder, mut sink: F) -> usize
+    where
+        F: FnMut(&ChaosCase),
+    {
+        let cases = self.cases(mb);
+        cases.iter().for_each(&mut sink);
+        cases.len()
+    }
+
+    fn huge_attributes(&self) -> Attributes {
+        (0..self.config.attribute_count)
+            .map(|i| {
+                let value = if i % 2 == 0 {
+                    AttributeValue::String("v".repeat(self.config.attribute_value_size))
+                } else {
+                    AttributeValue::I64(i64::MIN + i as i64)
+                };
+                (format!("attribute-{}", i), value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::testkit::chaos::{ChaosConfig, ChaosInjector};
+    use crate::utils::get_avro_path;
+
+    #[test]
+    fn test_cases_are_readable() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let injector = ChaosInjector::new(ChaosConfig {
+            max_payload_size: 1024 * 1024,
+            attribute_count: 1000,
+            attribute_value_size: 64,
+        });
+
+        let mut names = vec![];
+        let count = injector.inject(&mb, |case| {
+            assert!(
+                mb.load_to_avro(case.message.clone()).is_some(),
+                "{}",
+                case.name
+            );
+            names.push(case.name);
+        });
+
+        assert_eq!(count, names.len());
+        assert!(names.contains(&"max_size_payload"));
+        assert!(names.contains(&"huge_attribute_map"));
+    }
+}
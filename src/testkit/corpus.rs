@@ -0,0 +1,1158 @@
+use crate::avro::Builder;
+use crate::bitmap::UnitBitmap;
+use crate::objects::services::auth::{AuthChallenge, AuthResult};
+use crate::objects::services::error_response::{ErrorCode, ErrorResponse};
+use crate::objects::services::ffprobe::{
+    FFProbeFormatInfo, FFProbeStreamInfo, ServicesFFProbeRequest, ServicesFFProbeResponse,
+    ServicesFFProbeResponseType,
+};
+use crate::objects::services::filmstrip::{ServicesFilmstripRequest, ServicesFilmstripResponse};
+use crate::objects::services::heartbeat::HeartbeatMessage;
+use crate::objects::services::inference::{BoundingBox, Detection, InferenceResultMessage};
+use crate::objects::services::keep_alive::KeepAliveMessage;
+use crate::objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity};
+use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+use crate::objects::services::slo_report::SloReport;
+use crate::objects::services::snapshot::{SnapshotFormat, SnapshotRequest, SnapshotResponse};
+use crate::objects::services::storage::backfill::{
+    BackfillRequest, BackfillResponse, BackfillStatus,
+};
+use crate::objects::services::storage::encoding_profile::{
+    EncodingAckStatus, EncodingProfile, EncodingProfileAck, EncodingProfileOffer,
+};
+use crate::objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest};
+use crate::objects::services::storage::notify_cadence::NotifyCadenceControl;
+use crate::objects::services::storage::notify_digest::{
+    NotifyDigestRequest, NotifyDigestResponse, TrackDigest,
+};
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::services::storage::retention::{
+    RetentionPolicy, StorageControlStatus, StoragePurgeRequest, StoragePurgeResponse,
+    StorageRetentionSetRequest, StorageRetentionSetResponse,
+};
+use crate::objects::services::storage::storage_stats::{
+    StorageStatsRequest, StorageStatsResponse, StreamStats, TrackStats,
+};
+use crate::objects::services::storage::stream_alias::{
+    ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+};
+use crate::objects::services::storage::stream_deletion::{
+    RestoreStreamRequest, SoftDeleteStreamRequest,
+};
+use crate::objects::services::storage::stream_end_message::{StreamEndMessage, StreamEndReason};
+use crate::objects::services::storage::stream_lifecycle_message::{
+    StreamLifecycleEvent, StreamLifecycleMessage,
+};
+use crate::objects::services::storage::stream_multi_track_unit_elements::{
+    StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse, TrackElements,
+};
+use crate::objects::services::storage::stream_replay::{
+    StreamReplayRequest, StreamReplayState, StreamReplayStatus, StreamReplayStop,
+};
+use crate::objects::services::storage::stream_track_keyframes::{
+    StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+};
+use crate::objects::services::storage::stream_track_unit_elements::{
+    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_track_units::{
+    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+};
+use crate::objects::services::storage::stream_track_units_by_time::{
+    StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+};
+use crate::objects::services::storage::stream_tracks::{
+    StreamTracksRequest, StreamTracksResponse, TrackDescriptor,
+};
+use crate::objects::services::storage::streams_discovery::{
+    StreamDescriptor, StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+};
+use crate::objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse};
+use crate::objects::services::storage::unit_element_ack_message::{
+    AckStatus, UnitElementAckMessage,
+};
+use crate::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::services::telemetry::NodeTelemetry;
+use crate::objects::services::transcode::{
+    TranscodeJobCancel, TranscodeJobRequest, TranscodeJobState, TranscodeJobStatus,
+};
+use crate::objects::ToProtocolMessage;
+use crate::primitives::{
+    ArtifactReference, AttributeValue, Attributes, Checksum, ChecksumAlgorithm, Compression,
+    ElementType, NotifyType, Payload, StreamName, TrackInfo, TrackType, Unit, UnitRange,
+    UnitSummary, UnitTimestamp, STREAM_NAME_MAX_LENGTH, TRACK_NAME_MAX_LENGTH,
+};
+
+/// Boundary case of a message kind, serialized deterministically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenCase {
+    pub name: String,
+    pub schema: String,
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    Min,
+    Max,
+}
+
+impl Boundary {
+    fn name(&self) -> &'static str {
+        match self {
+            Boundary::Min => "min",
+            Boundary::Max => "max",
+        }
+    }
+
+    fn long(&self) -> i64 {
+        match self {
+            Boundary::Min => 0,
+            Boundary::Max => i64::MAX,
+        }
+    }
+
+    fn int(&self) -> i32 {
+        match self {
+            Boundary::Min => 0,
+            Boundary::Max => i32::MAX,
+        }
+    }
+
+    fn element(&self) -> ElementType {
+        match self {
+            Boundary::Min => 0,
+            Boundary::Max => ElementType::MAX,
+        }
+    }
+
+    fn string(&self) -> String {
+        match self {
+            Boundary::Min => String::default(),
+            Boundary::Max => "s".repeat(1024),
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Boundary::Min => vec![],
+            Boundary::Max => vec![0xff; 64 * 1024],
+        }
+    }
+
+    fn unit(&self) -> Unit {
+        match self {
+            Boundary::Min => Unit::new(vec![], vec![], String::from("VIDEO"), 0),
+            Boundary::Max => Unit::new(
+                vec![0xff; STREAM_NAME_MAX_LENGTH],
+                vec![b'z'; TRACK_NAME_MAX_LENGTH],
+                String::from("SUBTITLE"),
+                i64::MAX,
+            ),
+        }
+    }
+
+    fn stream_name(&self) -> StreamName {
+        let mut stream_name = StreamName::default();
+        stream_name.copy_from_slice(&self.unit().stream_name);
+        stream_name
+    }
+
+    fn track_info(&self) -> TrackInfo {
+        let unit = self.unit();
+        TrackInfo::new(unit.track_type, unit.track_name)
+    }
+
+    /// Avro maps are encoded in the hash map iteration order, so every map holds
+    /// a single attribute to keep the output deterministic.
+    fn attribute_maps(&self) -> Vec<Attributes> {
+        match self {
+            Boundary::Min => vec![],
+            Boundary::Max => vec![
+                self.string().into(),
+                AttributeValue::I64(i64::MIN),
+                AttributeValue::F64(f64::MAX),
+                AttributeValue::Bool(true),
+                AttributeValue::Bytes(self.bytes()),
+                AttributeValue::StringList(vec![self.string(); 16]),
+            ]
+            .into_iter()
+            .map(|value| Attributes::from([(self.string(), value)]))
+            .collect(),
+        }
+    }
+
+    fn attributes(&self) -> Attributes {
+        self.attribute_maps().into_iter().next().unwrap_or_default()
+    }
+
+    fn payloads(&self) -> Vec<Payload> {
+        self.attribute_maps()
+            .into_iter()
+            .map(|attributes| Payload {
+                checksum: self.checksum(),
+                ..Payload::new(self.bytes(), attributes)
+            })
+            .collect()
+    }
+
+    fn checksum(&self) -> Option<Checksum> {
+        self.optional(Checksum::new(ChecksumAlgorithm::XxHash64, u64::MAX))
+    }
+
+    fn optional<T>(&self, value: T) -> Option<T> {
+        match self {
+            Boundary::Min => None,
+            Boundary::Max => Some(value),
+        }
+    }
+
+    fn items<T: Clone>(&self, value: T) -> Vec<T> {
+        match self {
+            Boundary::Min => vec![],
+            Boundary::Max => vec![value; 16],
+        }
+    }
+}
+
+fn case<T: ToProtocolMessage + 'static>(
+    name: &'static str,
+    message: T,
+) -> (&'static str, Box<dyn ToProtocolMessage>) {
+    (name, Box::new(message))
+}
+
+fn messages(b: Boundary) -> Vec<(&'static str, Box<dyn ToProtocolMessage>)> {
+    let unit = b.unit();
+    let track_info = b.track_info();
+    vec![
+        case(
+            "notify_message",
+            NotifyMessage {
+                attributes: b.attributes(),
+                ..NotifyMessage::new(
+                    unit.clone(),
+                    b.long() as u64,
+                    match b {
+                        Boundary::Min => NotifyType::new(),
+                        Boundary::Max => NotifyType::ready(i16::MAX),
+                    },
+                )
+            },
+        ),
+        case(
+            "unit_element_message",
+            UnitElementMessage {
+                is_keyframe: b == Boundary::Max,
+                checksum: b.checksum(),
+                ..UnitElementMessage::new(
+                    unit.clone(),
+                    b.element(),
+                    b.bytes(),
+                    b.attributes(),
+                    b == Boundary::Max,
+                )
+            },
+        ),
+        case(
+            "unit_element_chunk_message",
+            UnitElementChunkMessage {
+                stream_unit: unit.clone(),
+                element: b.element(),
+                chunk_index: b.int(),
+                total_chunks: b.int(),
+                checksum: b.long() as u32,
+                value: b.bytes(),
+                attributes: b.attributes(),
+                last: b == Boundary::Max,
+                compression: Compression::Uncompressed,
+                encryption: None,
+                is_keyframe: b == Boundary::Max,
+            },
+        ),
+        case(
+            "stream_tracks_request",
+            StreamTracksRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamTracksRequest::new(b.long(), b.string(), b.stream_name())
+            },
+        ),
+        case(
+            "stream_tracks_response",
+            match b {
+                Boundary::Min => StreamTracksResponse::new(b.long(), b.stream_name(), vec![]),
+                Boundary::Max => StreamTracksResponse::with_descriptors(
+                    b.long(),
+                    b.stream_name(),
+                    b.items(TrackDescriptor::new(
+                        track_info,
+                        b.string(),
+                        b.int(),
+                        b.int(),
+                        f64::MAX,
+                        i64::MIN,
+                        b.long(),
+                        b.long(),
+                    )),
+                ),
+            },
+        ),
+        case(
+            "stream_track_unit_elements_request",
+            StreamTrackUnitElementsRequest {
+                continuation_cursor: b.optional(b.bytes()),
+                snapshot_token: b.optional(b.bytes()),
+                track_pattern: b.optional(b.string()),
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamTrackUnitElementsRequest::paged(
+                    b.long(),
+                    b.string(),
+                    unit.clone(),
+                    b.element(),
+                    b.int(),
+                )
+            },
+        ),
+        case(
+            "stream_track_unit_elements_response",
+            StreamTrackUnitElementsResponse {
+                continuation_cursor: b.optional(b.bytes()),
+                snapshot_token: b.optional(b.bytes()),
+                has_more: b == Boundary::Max,
+                track_names: b.items(unit.track_name),
+                ..StreamTrackUnitElementsResponse::new(b.long(), unit.clone(), b.payloads())
+            },
+        ),
+        case(
+            "stream_track_units_request",
+            StreamTrackUnitsRequest {
+                continuation_cursor: b.optional(b.bytes()),
+                snapshot_token: b.optional(b.bytes()),
+                track_pattern: b.optional(b.string()),
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamTrackUnitsRequest::paged(
+                    b.long(),
+                    b.string(),
+                    unit.clone(),
+                    b.long() as u128,
+                    b.long() as u128,
+                    b.int(),
+                )
+            },
+        ),
+        case(
+            "stream_track_units_response",
+            StreamTrackUnitsResponse {
+                continuation_cursor: b.optional(b.bytes()),
+                snapshot_token: b.optional(b.bytes()),
+                has_more: b == Boundary::Max,
+                track_names: b.items(unit.track_name),
+                unit_summaries: b.items(UnitSummary::new(
+                    b.long(),
+                    b.long() as u64,
+                    b.long() as u64,
+                    b.long(),
+                )),
+                ..StreamTrackUnitsResponse::new(
+                    b.long(),
+                    unit.clone(),
+                    b.long() as u128,
+                    b.long() as u128,
+                    b.items(b.long()),
+                )
+            },
+        ),
+        case(
+            "stream_track_units_by_time_request",
+            StreamTrackUnitsByTimeRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamTrackUnitsByTimeRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    track_info,
+                    b.long() as u64,
+                    b.long() as u64,
+                )
+            },
+        ),
+        case(
+            "stream_track_units_by_time_response",
+            StreamTrackUnitsByTimeResponse::new(
+                b.long(),
+                b.stream_name(),
+                track_info,
+                b.long() as u64,
+                b.long() as u64,
+                b.items(UnitTimestamp::new(b.long(), b.long() as u64)),
+            ),
+        ),
+        case(
+            "stream_track_keyframes_request",
+            StreamTrackKeyframesRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamTrackKeyframesRequest::new(
+                    b.long(),
+                    b.string(),
+                    UnitRange::new(
+                        unit.stream_name.to_vec(),
+                        unit.track_name.to_vec(),
+                        String::from("VIDEO"),
+                        b.long(),
+                        b.long(),
+                    ),
+                )
+            },
+        ),
+        case(
+            "stream_track_keyframes_response",
+            StreamTrackKeyframesResponse::new(
+                b.long(),
+                UnitRange::new(
+                    unit.stream_name.to_vec(),
+                    unit.track_name.to_vec(),
+                    String::from("VIDEO"),
+                    b.long(),
+                    b.long(),
+                ),
+                b.items((b.long(), b.element())),
+            ),
+        ),
+        case(
+            "ping_request_response",
+            PingRequestResponse::new(
+                b.long(),
+                b.string(),
+                match b {
+                    Boundary::Min => PingRequestResponseType::Request,
+                    Boundary::Max => PingRequestResponseType::Response,
+                },
+            ),
+        ),
+        case("keep_alive_message", KeepAliveMessage::new(b.string())),
+        case(
+            "ffprobe_request",
+            ServicesFFProbeRequest {
+                source_range: b.optional(UnitRange::new(
+                    unit.stream_name.to_vec(),
+                    unit.track_name.to_vec(),
+                    String::from("AUDIO"),
+                    i64::MIN,
+                    b.long(),
+                )),
+                deadline_ms: b.optional(b.long() as u64),
+                ..ServicesFFProbeRequest::new(b.long(), b.string(), b.string(), b.attributes())
+            },
+        ),
+        case(
+            "ffprobe_response",
+            ServicesFFProbeResponse {
+                error_detail: b.optional(b.string()),
+                ..ServicesFFProbeResponse::typed(
+                    b.long(),
+                    match b {
+                        Boundary::Min => ServicesFFProbeResponseType::Accepted,
+                        Boundary::Max => ServicesFFProbeResponseType::AuthFailed,
+                    },
+                    b.long(),
+                    b.items(FFProbeStreamInfo {
+                        index: b.optional(b.long()),
+                        codec_type: b.optional(b.string()),
+                        codec_name: b.optional(b.string()),
+                        width: b.optional(b.long()),
+                        height: b.optional(b.long()),
+                        fps: b.optional(f64::MAX),
+                        bit_rate: b.optional(b.long()),
+                        duration_s: b.optional(f64::MAX),
+                        extra: b.attributes(),
+                    }),
+                    b.optional(FFProbeFormatInfo {
+                        format_name: b.optional(b.string()),
+                        duration_s: b.optional(f64::MAX),
+                        size_bytes: b.optional(b.long()),
+                        bit_rate: b.optional(b.long()),
+                        nb_streams: b.optional(b.long()),
+                        extra: b.attributes(),
+                    }),
+                )
+            },
+        ),
+        case(
+            "filmstrip_request",
+            ServicesFilmstripRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..ServicesFilmstripRequest::new(
+                    b.long(),
+                    b.string(),
+                    unit.clone(),
+                    b.long() as u64,
+                    b.long() as u64,
+                    b.int(),
+                    b.int(),
+                    b.int(),
+                    b == Boundary::Max,
+                )
+            },
+        ),
+        case(
+            "filmstrip_response",
+            ServicesFilmstripResponse::new(
+                b.long(),
+                unit.clone(),
+                b == Boundary::Max,
+                b.payloads(),
+                b.items(ArtifactReference::new(
+                    b.string(),
+                    b.long() as u64,
+                    b.string(),
+                    b.long(),
+                )),
+            ),
+        ),
+        case(
+            "transcode_job_request",
+            TranscodeJobRequest {
+                source_range: b.optional(UnitRange::new(
+                    unit.stream_name.to_vec(),
+                    unit.track_name.to_vec(),
+                    String::from("VIDEO"),
+                    b.long(),
+                    b.long(),
+                )),
+                deadline_ms: b.optional(b.long() as u64),
+                ..TranscodeJobRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.string(),
+                    b.string(),
+                    b.string(),
+                    b.attributes(),
+                )
+            },
+        ),
+        case(
+            "transcode_job_status",
+            TranscodeJobStatus {
+                error: b.optional(b.string()),
+                ..TranscodeJobStatus::new(
+                    b.long(),
+                    b.string(),
+                    match b {
+                        Boundary::Min => TranscodeJobState::Queued,
+                        Boundary::Max => TranscodeJobState::Cancelled,
+                    },
+                    match b {
+                        Boundary::Min => 0.0,
+                        Boundary::Max => f64::MAX,
+                    },
+                )
+            },
+        ),
+        case(
+            "transcode_job_cancel",
+            TranscodeJobCancel {
+                deadline_ms: b.optional(b.long() as u64),
+                ..TranscodeJobCancel::new(b.long(), b.string(), b.string())
+            },
+        ),
+        case(
+            "snapshot_request",
+            SnapshotRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..SnapshotRequest::new(
+                    b.long(),
+                    b.string(),
+                    unit.clone(),
+                    b.element(),
+                    match b {
+                        Boundary::Min => SnapshotFormat::Jpeg,
+                        Boundary::Max => SnapshotFormat::Webp,
+                    },
+                    b.int(),
+                )
+            },
+        ),
+        case(
+            "snapshot_response",
+            SnapshotResponse::new(
+                b.long(),
+                b.payloads().into_iter().next().unwrap_or_default(),
+                b.long() as u64,
+            ),
+        ),
+        case(
+            "unit_element_ack_message",
+            UnitElementAckMessage::new(
+                unit.clone(),
+                b.element(),
+                match b {
+                    Boundary::Min => AckStatus::Persisted,
+                    Boundary::Max => AckStatus::RetryLater,
+                },
+                b.optional(b.string()),
+            ),
+        ),
+        case(
+            "notify_digest_request",
+            NotifyDigestRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..NotifyDigestRequest::new(b.long(), b.string(), b.stream_name(), b.long() as u64)
+            },
+        ),
+        case(
+            "notify_digest_response",
+            NotifyDigestResponse::new(
+                b.long(),
+                b.stream_name(),
+                b.items(TrackDigest::new(
+                    track_info,
+                    b.long(),
+                    b.long(),
+                    b.long(),
+                    b.long(),
+                    b.long() as u64,
+                    b.long() as u64,
+                )),
+            ),
+        ),
+        case(
+            "flow_control_grant",
+            FlowControlGrant::new(b.stream_name(), track_info, b.long()),
+        ),
+        case(
+            "flow_control_request",
+            FlowControlRequest::new(b.stream_name(), track_info, b.long()),
+        ),
+        case(
+            "notify_cadence_control",
+            NotifyCadenceControl::new(
+                b.stream_name(),
+                b.optional(track_info),
+                b.optional(b.long() as u64),
+                b.optional(b.long() as u64),
+            ),
+        ),
+        case(
+            "set_stream_alias",
+            SetStreamAlias::new(b.string(), b.stream_name(), b == Boundary::Max),
+        ),
+        case(
+            "resolve_alias_request",
+            ResolveAliasRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..ResolveAliasRequest::new(b.long(), b.string(), b.string())
+            },
+        ),
+        case(
+            "resolve_alias_response",
+            ResolveAliasResponse::new(b.long(), b.string(), b.optional(b.stream_name())),
+        ),
+        case(
+            "error_response",
+            ErrorResponse::new(
+                b.long(),
+                match b {
+                    Boundary::Min => ErrorCode::NotFound,
+                    Boundary::Max => ErrorCode::Internal,
+                },
+                b.string(),
+                b.attributes(),
+            ),
+        ),
+        case(
+            "stream_end_message",
+            StreamEndMessage::new(
+                b.stream_name(),
+                track_info,
+                b.long(),
+                match b {
+                    Boundary::Min => StreamEndReason::Finished,
+                    Boundary::Max => StreamEndReason::Error,
+                },
+            ),
+        ),
+        case(
+            "stream_lifecycle_message",
+            StreamLifecycleMessage::new(
+                b.stream_name(),
+                match b {
+                    Boundary::Min => StreamLifecycleEvent::Created,
+                    Boundary::Max => StreamLifecycleEvent::Restored,
+                },
+                b.long() as u64,
+            ),
+        ),
+        case(
+            "soft_delete_stream_request",
+            SoftDeleteStreamRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..SoftDeleteStreamRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    b.long() as u64,
+                )
+            },
+        ),
+        case(
+            "restore_stream_request",
+            RestoreStreamRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..RestoreStreamRequest::new(b.long(), b.string(), b.stream_name())
+            },
+        ),
+        case(
+            "maintenance_notice",
+            MaintenanceNotice::new(
+                b.string(),
+                b.items(b.stream_name()),
+                b.long() as u64,
+                b.long() as u64,
+                match b {
+                    Boundary::Min => MaintenanceSeverity::Info,
+                    Boundary::Max => MaintenanceSeverity::Outage,
+                },
+            ),
+        ),
+        case(
+            "unit_bitmap_request",
+            UnitBitmapRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..UnitBitmapRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    track_info,
+                    b.long(),
+                    b.long(),
+                )
+            },
+        ),
+        case(
+            "unit_bitmap_response",
+            UnitBitmapResponse::new(
+                b.long(),
+                b.stream_name(),
+                track_info,
+                b.long(),
+                b.long(),
+                match b {
+                    Boundary::Min => UnitBitmap::new(),
+                    Boundary::Max => (0..65_536u32)
+                        .filter(|u| u % 3 != 0)
+                        .chain(1 << 20..1 << 21)
+                        .chain((1 << 24..(1 << 24) + 100).map(|u| u * 7))
+                        .collect(),
+                },
+            ),
+        ),
+        case(
+            "backfill_request",
+            BackfillRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..BackfillRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.items(UnitRange::new(
+                        unit.stream_name.to_vec(),
+                        unit.track_name.to_vec(),
+                        String::from("META"),
+                        b.long(),
+                        b.long(),
+                    )),
+                )
+            },
+        ),
+        case(
+            "backfill_response",
+            BackfillResponse::new(
+                b.long(),
+                match b {
+                    Boundary::Min => BackfillStatus::Unavailable,
+                    Boundary::Max => BackfillStatus::Partial,
+                },
+                b.items(UnitRange::new(
+                    unit.stream_name.to_vec(),
+                    unit.track_name.to_vec(),
+                    String::from("VIDEO"),
+                    b.long(),
+                    b.long(),
+                )),
+            ),
+        ),
+        case(
+            "encoding_profile_offer",
+            EncodingProfileOffer {
+                deadline_ms: b.optional(b.long() as u64),
+                ..EncodingProfileOffer::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    b.items(EncodingProfile::new(
+                        TrackType::Subtitle,
+                        b.string(),
+                        b.items(b.string()),
+                    )),
+                )
+            },
+        ),
+        case(
+            "encoding_profile_ack",
+            EncodingProfileAck::new(
+                b.long(),
+                b.stream_name(),
+                match b {
+                    Boundary::Min => EncodingAckStatus::Accepted,
+                    Boundary::Max => EncodingAckStatus::Rejected,
+                },
+                b.items(track_info),
+            ),
+        ),
+        case(
+            "storage_retention_set_request",
+            StorageRetentionSetRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StorageRetentionSetRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    RetentionPolicy::new(
+                        b.optional(b.long() as u64),
+                        b.optional(b.long() as u64),
+                        b.optional(b.long() as u64),
+                    ),
+                )
+            },
+        ),
+        case(
+            "storage_retention_set_response",
+            StorageRetentionSetResponse::new(
+                b.long(),
+                b.stream_name(),
+                match b {
+                    Boundary::Min => StorageControlStatus::Applied,
+                    Boundary::Max => StorageControlStatus::NotFound,
+                },
+                b.optional(RetentionPolicy::new(
+                    b.optional(b.long() as u64),
+                    b.optional(b.long() as u64),
+                    b.optional(b.long() as u64),
+                )),
+                b.optional(b.string()),
+            ),
+        ),
+        case(
+            "storage_purge_request",
+            StoragePurgeRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StoragePurgeRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    b.optional(track_info),
+                    b.optional(b.long()),
+                    b.optional(b.long() as u64),
+                )
+            },
+        ),
+        case(
+            "storage_purge_response",
+            StoragePurgeResponse::new(
+                b.long(),
+                b.stream_name(),
+                match b {
+                    Boundary::Min => StorageControlStatus::Applied,
+                    Boundary::Max => StorageControlStatus::Rejected,
+                },
+                b.long() as u64,
+                b.long() as u64,
+                b.optional(b.string()),
+            ),
+        ),
+        case(
+            "storage_stats_request",
+            StorageStatsRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StorageStatsRequest::new(b.long(), b.string(), b.optional(b.stream_name()))
+            },
+        ),
+        case(
+            "storage_stats_response",
+            StorageStatsResponse::new(
+                b.long(),
+                b.items(StreamStats::new(
+                    b.stream_name(),
+                    b.items(TrackStats::new(
+                        track_info,
+                        b.long() as u64,
+                        b.long() as u64,
+                        b.long(),
+                        b.long(),
+                        b.long() as u64,
+                    )),
+                )),
+            ),
+        ),
+        case(
+            "stream_replay_request",
+            StreamReplayRequest {
+                from_unit: b.optional(b.long()),
+                from_ms: b.optional(b.long() as u64),
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamReplayRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    b.items(track_info),
+                    b.long(),
+                    match b {
+                        Boundary::Min => 0.0,
+                        Boundary::Max => f64::MAX,
+                    },
+                    b.string(),
+                )
+            },
+        ),
+        case(
+            "stream_replay_status",
+            StreamReplayStatus {
+                error: b.optional(b.string()),
+                ..StreamReplayStatus::new(
+                    b.long(),
+                    b.string(),
+                    match b {
+                        Boundary::Min => StreamReplayState::Started,
+                        Boundary::Max => StreamReplayState::Failed,
+                    },
+                    b.optional(b.long()),
+                )
+            },
+        ),
+        case(
+            "stream_replay_stop",
+            StreamReplayStop {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamReplayStop::new(b.long(), b.string(), b.string())
+            },
+        ),
+        case(
+            "stream_multi_track_unit_elements_request",
+            StreamMultiTrackUnitElementsRequest {
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamMultiTrackUnitElementsRequest::new(
+                    b.long(),
+                    b.string(),
+                    b.stream_name(),
+                    b.items(track_info),
+                    b.long(),
+                    b.element(),
+                )
+            },
+        ),
+        case(
+            "stream_multi_track_unit_elements_response",
+            StreamMultiTrackUnitElementsResponse::new(
+                b.long(),
+                b.stream_name(),
+                b.long(),
+                b.items(TrackElements::new(track_info, b.payloads())),
+            ),
+        ),
+        case(
+            "inference_result_message",
+            InferenceResultMessage::new(unit.clone(), b.element(), b.string(), b.long() as u64)
+                .with_detections(b.items(Detection {
+                    object_id: b.optional(b.long()),
+                    track_id: b.optional(b.long()),
+                    attributes: b.attributes(),
+                    ..Detection::new(
+                        b.string(),
+                        match b {
+                            Boundary::Min => 0.0,
+                            Boundary::Max => 1.0,
+                        },
+                        BoundingBox::new(
+                            b.long() as f64,
+                            b.long() as f64,
+                            b.long() as f64,
+                            b.long() as f64,
+                        ),
+                    )
+                })),
+        ),
+        case(
+            "node_telemetry",
+            NodeTelemetry {
+                counters: b
+                    .attributes()
+                    .into_keys()
+                    .map(|name| (name, b.long()))
+                    .collect(),
+                gauges: b
+                    .attributes()
+                    .into_keys()
+                    .map(|name| (name, b.long() as f64))
+                    .collect(),
+                ..NodeTelemetry::new(b.string(), b.string(), b.long() as u64)
+            },
+        ),
+        case(
+            "heartbeat",
+            HeartbeatMessage::new(b.string(), b.string(), b.long() as u64, b.long() as f64),
+        ),
+        case(
+            "auth_challenge",
+            AuthChallenge {
+                reason: b.optional(b.string()),
+                ..AuthChallenge::new(b.long(), vec![b.string(), b.string()], b.bytes())
+            },
+        ),
+        case(
+            "auth_result",
+            AuthResult {
+                principal: b.optional(b.string()),
+                expires_ms: b.optional(b.long() as u64),
+                reason: b.optional(b.string()),
+                ..AuthResult::new(b.long(), true)
+            },
+        ),
+        case(
+            "slo_report",
+            SloReport::new(
+                b.string(),
+                b.long() as u64,
+                b.long() as u64,
+                b.long() as f64,
+                f64::MIN_POSITIVE,
+                f64::MAX,
+                f64::MAX,
+                b.long(),
+                b == Boundary::Max,
+            ),
+        ),
+        case(
+            "streams_discovery_request",
+            StreamsDiscoveryRequest {
+                include_deleted: b == Boundary::Max,
+                deadline_ms: b.optional(b.long() as u64),
+                ..StreamsDiscoveryRequest::new(b.long(), b.string(), b.stream_name().to_vec())
+            },
+        ),
+        case(
+            "streams_discovery_response",
+            StreamsDiscoveryResponse::new(
+                b.long(),
+                b.items(StreamDescriptor {
+                    deleted: b == Boundary::Max,
+                    ..StreamDescriptor::new(b.stream_name(), b.int(), b.long() as u64)
+                }),
+            ),
+        ),
+    ]
+}
+
+/// The boundary cases before serialization, named like the golden cases.
+#[cfg(feature = "bench")]
+pub(crate) fn boundary_messages() -> Vec<(String, Box<dyn ToProtocolMessage>)> {
+    [Boundary::Min, Boundary::Max]
+        .iter()
+        .flat_map(|b| {
+            messages(*b)
+                .into_iter()
+                .map(move |(name, message)| (format!("{}_{}", name, b.name()), message))
+        })
+        .collect()
+}
+
+/// Enumerates the boundary cases of every message kind: empty collections and
+/// strings, maximal lengths and values, optional fields unset and set.
+///
+/// The builder must not sign or encrypt messages, otherwise the output isn't
+/// deterministic.
+pub fn golden_corpus(mb: &Builder) -> Vec<GoldenCase> {
+    [Boundary::Min, Boundary::Max]
+        .iter()
+        .flat_map(|b| {
+            messages(*b).into_iter().filter_map(move |(name, message)| {
+                let envelope = message.save(mb)?;
+                Some(GoldenCase {
+                    name: format!("{}_{}", name, b.name()),
+                    schema: envelope.schema.clone(),
+                    message: mb.save_from_avro(envelope).ok()?,
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{
+        Builder, BuilderImpl, ARTIFACT_REFERENCE_SCHEMA, AUTH_TOKEN_SCHEMA,
+        CHECKSUM_ALGORITHM_SCHEMA, CHECKSUM_SCHEMA, COMPRESSION_SCHEMA, ENCODING_PROFILE_SCHEMA,
+        ENCRYPTION_ALGORITHM_SCHEMA, ENCRYPTION_INFO_SCHEMA, MESSAGE_ENVELOPE_SCHEMA,
+        MESSAGE_PRIORITY_SCHEMA, RETENTION_POLICY_SCHEMA, SERVICES_FFPROBE_FORMAT_INFO_SCHEMA,
+        SERVICES_FFPROBE_STREAM_INFO_SCHEMA, SERVICES_INFERENCE_BOUNDING_BOX_SCHEMA,
+        SERVICES_INFERENCE_DETECTION_SCHEMA, STREAM_DESCRIPTOR_SCHEMA, STREAM_STATS_SCHEMA,
+        TRACK_DESCRIPTOR_SCHEMA, TRACK_DIGEST_SCHEMA, TRACK_ELEMENTS_SCHEMA, TRACK_INFO_SCHEMA,
+        TRACK_STATS_SCHEMA, TRACK_TYPE_SCHEMA, UNIT_ELEMENT_VALUE_SCHEMA, UNIT_RANGE_SCHEMA,
+        UNIT_SCHEMA, UNIT_SUMMARY_SCHEMA, UNIT_TIMESTAMP_SCHEMA,
+    };
+    use crate::testkit::corpus::golden_corpus;
+    use crate::utils::get_avro_path;
+    use std::collections::HashSet;
+
+    /// Schemas which are parts of other messages and have no message kind.
+    const COMPONENT_SCHEMAS: [&str; 28] = [
+        TRACK_TYPE_SCHEMA,
+        TRACK_INFO_SCHEMA,
+        TRACK_DESCRIPTOR_SCHEMA,
+        UNIT_SCHEMA,
+        UNIT_RANGE_SCHEMA,
+        UNIT_TIMESTAMP_SCHEMA,
+        UNIT_ELEMENT_VALUE_SCHEMA,
+        COMPRESSION_SCHEMA,
+        ENCRYPTION_ALGORITHM_SCHEMA,
+        ENCRYPTION_INFO_SCHEMA,
+        ARTIFACT_REFERENCE_SCHEMA,
+        STREAM_DESCRIPTOR_SCHEMA,
+        MESSAGE_ENVELOPE_SCHEMA,
+        CHECKSUM_ALGORITHM_SCHEMA,
+        CHECKSUM_SCHEMA,
+        TRACK_DIGEST_SCHEMA,
+        MESSAGE_PRIORITY_SCHEMA,
+        SERVICES_FFPROBE_STREAM_INFO_SCHEMA,
+        SERVICES_FFPROBE_FORMAT_INFO_SCHEMA,
+        ENCODING_PROFILE_SCHEMA,
+        RETENTION_POLICY_SCHEMA,
+        TRACK_STATS_SCHEMA,
+        STREAM_STATS_SCHEMA,
+        TRACK_ELEMENTS_SCHEMA,
+        UNIT_SUMMARY_SCHEMA,
+        SERVICES_INFERENCE_BOUNDING_BOX_SCHEMA,
+        SERVICES_INFERENCE_DETECTION_SCHEMA,
+        AUTH_TOKEN_SCHEMA,
+    ];
+
+    #[test]
+    fn test_corpus_covers_all_messages() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let corpus = golden_corpus(&mb);
+
+        let covered: HashSet<_> = corpus.iter().map(|c| c.schema.as_str()).collect();
+        for (_, schema) in BuilderImpl::schema_files() {
+            assert!(
+                covered.contains(schema) || COMPONENT_SCHEMAS.contains(&schema),
+                "No golden cases for {}",
+                schema
+            );
+        }
+
+        for case in &corpus {
+            let envelope = mb.load_to_avro(case.message.clone()).unwrap();
+            assert_eq!(envelope.schema, case.schema, "{}", case.name);
+        }
+        assert_eq!(golden_corpus(&mb), corpus);
+    }
+}
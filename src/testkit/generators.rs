@@ -0,0 +1,1766 @@
+use crate::bitmap::UnitBitmap;
+use crate::checksum::{compute, crc32};
+use crate::message::Message;
+use crate::message_kind::MessageKind;
+use crate::objects::services::auth::{AuthChallenge, AuthResult};
+use crate::objects::services::error_response::{ErrorCode, ErrorResponse};
+use crate::objects::services::ffprobe::{
+    FFProbeFormatInfo, FFProbeStreamInfo, ServicesFFProbeRequest, ServicesFFProbeResponse,
+    ServicesFFProbeResponseType,
+};
+use crate::objects::services::filmstrip::{ServicesFilmstripRequest, ServicesFilmstripResponse};
+use crate::objects::services::heartbeat::HeartbeatMessage;
+use crate::objects::services::inference::{BoundingBox, Detection, InferenceResultMessage};
+use crate::objects::services::keep_alive::KeepAliveMessage;
+use crate::objects::services::maintenance::{MaintenanceNotice, MaintenanceSeverity};
+use crate::objects::services::ping::{PingRequestResponse, PingRequestResponseType};
+use crate::objects::services::slo_report::SloReport;
+use crate::objects::services::snapshot::{SnapshotFormat, SnapshotRequest, SnapshotResponse};
+use crate::objects::services::storage::backfill::{
+    BackfillRequest, BackfillResponse, BackfillStatus,
+};
+use crate::objects::services::storage::encoding_profile::{
+    EncodingAckStatus, EncodingProfile, EncodingProfileAck, EncodingProfileOffer,
+};
+use crate::objects::services::storage::flow_control::{FlowControlGrant, FlowControlRequest};
+use crate::objects::services::storage::notify_cadence::NotifyCadenceControl;
+use crate::objects::services::storage::notify_digest::{
+    NotifyDigestRequest, NotifyDigestResponse, TrackDigest,
+};
+use crate::objects::services::storage::notify_message::NotifyMessage;
+use crate::objects::services::storage::retention::{
+    RetentionPolicy, StorageControlStatus, StoragePurgeRequest, StoragePurgeResponse,
+    StorageRetentionSetRequest, StorageRetentionSetResponse,
+};
+use crate::objects::services::storage::storage_stats::{
+    StorageStatsRequest, StorageStatsResponse, StreamStats, TrackStats,
+};
+use crate::objects::services::storage::stream_alias::{
+    ResolveAliasRequest, ResolveAliasResponse, SetStreamAlias,
+};
+use crate::objects::services::storage::stream_deletion::{
+    RestoreStreamRequest, SoftDeleteStreamRequest,
+};
+use crate::objects::services::storage::stream_end_message::{StreamEndMessage, StreamEndReason};
+use crate::objects::services::storage::stream_lifecycle_message::{
+    StreamLifecycleEvent, StreamLifecycleMessage,
+};
+use crate::objects::services::storage::stream_multi_track_unit_elements::{
+    StreamMultiTrackUnitElementsRequest, StreamMultiTrackUnitElementsResponse, TrackElements,
+};
+use crate::objects::services::storage::stream_replay::{
+    StreamReplayRequest, StreamReplayState, StreamReplayStatus, StreamReplayStop,
+};
+use crate::objects::services::storage::stream_track_keyframes::{
+    StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+};
+use crate::objects::services::storage::stream_track_unit_elements::{
+    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_track_units::{
+    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+};
+use crate::objects::services::storage::stream_track_units_by_time::{
+    StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+};
+use crate::objects::services::storage::stream_tracks::{
+    StreamTracksRequest, StreamTracksResponse, TrackDescriptor,
+};
+use crate::objects::services::storage::streams_discovery::{
+    StreamDescriptor, StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+};
+use crate::objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse};
+use crate::objects::services::storage::unit_element_ack_message::{
+    AckStatus, UnitElementAckMessage,
+};
+use crate::objects::services::storage::unit_element_chunk_message::UnitElementChunkMessage;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::services::telemetry::NodeTelemetry;
+use crate::objects::services::transcode::{
+    TranscodeJobCancel, TranscodeJobRequest, TranscodeJobState, TranscodeJobStatus,
+};
+use crate::primitives::{
+    pack_track_name, ArtifactReference, AttributeValue, Attributes, Checksum, ChecksumAlgorithm,
+    Compression, ElementType, EncryptionAlgorithm, EncryptionInfo, NotifyType, Payload, StreamName,
+    TrackInfo, TrackName, TrackType, Unit, UnitRange, UnitSummary, UnitTimestamp,
+    TRACK_NAME_MAX_LENGTH,
+};
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::string::string_regex;
+use std::fmt::Debug;
+
+// Avro has no unsigned types, the u64 and u128 fields are sent as longs
+fn millis() -> impl Strategy<Value = u64> + Clone {
+    0..=i64::MAX as u64
+}
+
+fn deadline() -> impl Strategy<Value = Option<u64>> {
+    option::of(millis())
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,24}"
+}
+
+fn real() -> impl Strategy<Value = f64> {
+    -1.0e9..1.0e9
+}
+
+fn bytes(max: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..=max)
+}
+
+/// The bounds of a range, the start isn't after the end.
+fn ordered<T: Ord + Copy + Debug>(
+    bound: impl Strategy<Value = T> + Clone,
+) -> impl Strategy<Value = (T, T)> {
+    (bound.clone(), bound).prop_map(|(a, b)| if a <= b { (a, b) } else { (b, a) })
+}
+
+/// Stream names which aren't all zeroes, an all zeroes name is empty.
+pub fn stream_name() -> impl Strategy<Value = StreamName> {
+    any::<StreamName>().prop_map(|mut stream_name| {
+        stream_name[0] |= 1;
+        stream_name
+    })
+}
+
+/// UTF-8 names of up to [`TRACK_NAME_MAX_LENGTH`] bytes.
+pub fn track_name() -> impl Strategy<Value = TrackName> {
+    string_regex(&format!("[a-z0-9_-]{{1,{}}}", TRACK_NAME_MAX_LENGTH))
+        .unwrap()
+        .prop_map(|name| pack_track_name(&name).unwrap())
+}
+
+pub fn track_type() -> impl Strategy<Value = TrackType> {
+    prop_oneof![
+        Just(TrackType::Video),
+        Just(TrackType::Meta),
+        Just(TrackType::Audio),
+        Just(TrackType::Subtitle),
+    ]
+}
+
+pub fn track_info() -> impl Strategy<Value = TrackInfo> {
+    (track_type(), track_name()).prop_map(|(track_type, track_name)| TrackInfo {
+        track_type,
+        track_name,
+    })
+}
+
+pub fn element() -> impl Strategy<Value = ElementType> {
+    0..=ElementType::MAX
+}
+
+pub fn unit() -> impl Strategy<Value = Unit> {
+    (stream_name(), track_info(), 0..=i64::MAX).prop_map(|(stream_name, track_info, unit)| Unit {
+        stream_name,
+        track_name: track_info.track_name,
+        track_type: track_info.track_type,
+        unit,
+    })
+}
+
+pub fn unit_range() -> impl Strategy<Value = UnitRange> {
+    (stream_name(), track_info(), ordered(0..=i64::MAX)).prop_map(
+        |(stream_name, track_info, (from_unit, to_unit))| UnitRange {
+            stream_name,
+            track_name: track_info.track_name,
+            track_type: track_info.track_type,
+            from_unit,
+            to_unit,
+        },
+    )
+}
+
+pub fn attribute_value() -> impl Strategy<Value = AttributeValue> {
+    prop_oneof![
+        text().prop_map(AttributeValue::String),
+        any::<i64>().prop_map(AttributeValue::I64),
+        real().prop_map(AttributeValue::F64),
+        any::<bool>().prop_map(AttributeValue::Bool),
+        bytes(64).prop_map(AttributeValue::Bytes),
+        vec(text(), 0..4).prop_map(AttributeValue::StringList),
+    ]
+}
+
+/// A few attributes, well within the limits of [`crate::validation::MessageLimits`].
+pub fn attributes() -> impl Strategy<Value = Attributes> {
+    hash_map(text(), attribute_value(), 0..4)
+}
+
+/// Checksum of the data by any of the supported algorithms.
+pub fn checksum(data: &[u8]) -> impl Strategy<Value = Checksum> {
+    let (crc32c, xxhash64) = (
+        compute(ChecksumAlgorithm::Crc32c, data).unwrap(),
+        compute(ChecksumAlgorithm::XxHash64, data).unwrap(),
+    );
+    prop_oneof![Just(crc32c), Just(xxhash64)]
+}
+
+pub fn payload() -> impl Strategy<Value = Payload> {
+    (bytes(256), attributes(), any::<bool>()).prop_flat_map(|(data, attributes, checked)| {
+        let checksum = if checked {
+            checksum(&data).prop_map(Some).boxed()
+        } else {
+            Just(None).boxed()
+        };
+        (Just(data), Just(attributes), checksum).prop_map(|(data, attributes, checksum)| Payload {
+            data,
+            attributes,
+            checksum,
+        })
+    })
+}
+
+pub fn encryption_info() -> impl Strategy<Value = EncryptionInfo> {
+    ("[a-z0-9-]{1,16}", any::<[u8; 12]>()).prop_map(|(key_id, nonce)| EncryptionInfo {
+        key_id,
+        nonce: nonce.to_vec(),
+        algo: EncryptionAlgorithm::Aes256Gcm,
+    })
+}
+
+pub fn artifact_reference() -> impl Strategy<Value = ArtifactReference> {
+    (text(), millis(), text(), 0..=i64::MAX).prop_map(
+        |(url, expires_ms, content_type, size_bytes)| ArtifactReference {
+            url,
+            expires_ms,
+            content_type,
+            size_bytes,
+        },
+    )
+}
+
+pub fn unit_timestamp() -> impl Strategy<Value = UnitTimestamp> {
+    (0..=i64::MAX, millis()).prop_map(|(unit, saved_ms)| UnitTimestamp { unit, saved_ms })
+}
+
+pub fn unit_summary() -> impl Strategy<Value = UnitSummary> {
+    (0..=i64::MAX, ordered(millis()), 0..=i64::MAX).prop_map(
+        |(unit, (first_saved_ms, last_saved_ms), element_count)| UnitSummary {
+            unit,
+            first_saved_ms,
+            last_saved_ms,
+            element_count,
+        },
+    )
+}
+
+/// Bitmap of the units of `from_unit..=to_unit`, as the offsets from `from_unit`.
+pub fn unit_bitmap(from_unit: i64, to_unit: i64) -> impl Strategy<Value = UnitBitmap> {
+    let span = (to_unit - from_unit).min(1 << 20) as u32;
+    vec(0..=span, 0..64).prop_map(|offsets| {
+        let mut bitmap = UnitBitmap::new();
+        for offset in offsets {
+            bitmap.insert(offset);
+        }
+        bitmap
+    })
+}
+
+pub fn auth_challenge() -> impl Strategy<Value = AuthChallenge> {
+    (
+        any::<i64>(),
+        vec(text(), 0..4),
+        bytes(32),
+        option::of(text()),
+    )
+        .prop_map(|(request_id, schemes, nonce, reason)| AuthChallenge {
+            request_id,
+            schemes,
+            nonce,
+            reason,
+        })
+}
+
+pub fn auth_result() -> impl Strategy<Value = AuthResult> {
+    (
+        any::<i64>(),
+        any::<bool>(),
+        option::of(text()),
+        option::of(millis()),
+        option::of(text()),
+    )
+        .prop_map(
+            |(request_id, accepted, principal, expires_ms, reason)| AuthResult {
+                request_id,
+                accepted,
+                principal,
+                expires_ms,
+                reason,
+            },
+        )
+}
+
+pub fn backfill_request() -> impl Strategy<Value = BackfillRequest> {
+    (any::<i64>(), text(), vec(unit_range(), 0..4), deadline()).prop_map(
+        |(request_id, topic, ranges, deadline_ms)| BackfillRequest {
+            request_id,
+            topic,
+            ranges,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn backfill_response() -> impl Strategy<Value = BackfillResponse> {
+    let status = prop_oneof![
+        Just(BackfillStatus::Accepted),
+        Just(BackfillStatus::Partial),
+        Just(BackfillStatus::Unavailable),
+    ];
+    (any::<i64>(), status, vec(unit_range(), 0..4)).prop_map(|(request_id, status, ranges)| {
+        BackfillResponse {
+            request_id,
+            status,
+            ranges,
+        }
+    })
+}
+
+pub fn encoding_profile_ack() -> impl Strategy<Value = EncodingProfileAck> {
+    let status = prop_oneof![
+        Just(EncodingAckStatus::Accepted),
+        Just(EncodingAckStatus::Partial),
+        Just(EncodingAckStatus::Rejected),
+    ];
+    (any::<i64>(), stream_name(), status, vec(track_info(), 0..4)).prop_map(
+        |(request_id, stream_name, status, unsupported_tracks)| EncodingProfileAck {
+            request_id,
+            stream_name,
+            status,
+            unsupported_tracks,
+        },
+    )
+}
+
+pub fn encoding_profile_offer() -> impl Strategy<Value = EncodingProfileOffer> {
+    let profile =
+        (track_type(), text(), vec(text(), 0..4)).prop_map(|(track_type, codec, profiles)| {
+            EncodingProfile {
+                track_type,
+                codec,
+                profiles,
+            }
+        });
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        vec(profile, 0..4),
+        deadline(),
+    )
+        .prop_map(|(request_id, topic, stream_name, profiles, deadline_ms)| {
+            EncodingProfileOffer {
+                request_id,
+                topic,
+                stream_name,
+                profiles,
+                deadline_ms,
+            }
+        })
+}
+
+pub fn error_response() -> impl Strategy<Value = ErrorResponse> {
+    let code = prop_oneof![
+        Just(ErrorCode::NotFound),
+        Just(ErrorCode::InvalidRange),
+        Just(ErrorCode::Unauthorized),
+        Just(ErrorCode::Overloaded),
+        Just(ErrorCode::Internal),
+    ];
+    (any::<i64>(), code, text(), attributes()).prop_map(|(request_id, code, message, details)| {
+        ErrorResponse {
+            request_id,
+            code,
+            message,
+            details,
+        }
+    })
+}
+
+pub fn flow_control_grant() -> impl Strategy<Value = FlowControlGrant> {
+    (stream_name(), track_info(), 0..=i64::MAX).prop_map(|(stream_name, track_info, credits)| {
+        FlowControlGrant {
+            stream_name,
+            track_info,
+            credits,
+        }
+    })
+}
+
+pub fn flow_control_request() -> impl Strategy<Value = FlowControlRequest> {
+    (stream_name(), track_info(), 0..=i64::MAX).prop_map(|(stream_name, track_info, pending)| {
+        FlowControlRequest {
+            stream_name,
+            track_info,
+            pending,
+        }
+    })
+}
+
+pub fn heartbeat_message() -> impl Strategy<Value = HeartbeatMessage> {
+    (text(), text(), millis(), real()).prop_map(|(node_id, role, uptime_ms, load)| {
+        HeartbeatMessage {
+            node_id,
+            role,
+            uptime_ms,
+            load,
+        }
+    })
+}
+
+pub fn inference_result_message() -> impl Strategy<Value = InferenceResultMessage> {
+    let bbox = (0.0..1.0, 0.0..1.0, 0.0..1.0, 0.0..1.0).prop_map(|(left, top, width, height)| {
+        BoundingBox {
+            left,
+            top,
+            width,
+            height,
+        }
+    });
+    let detection = (
+        text(),
+        0.0..=1.0,
+        bbox,
+        option::of(any::<i64>()),
+        option::of(any::<i64>()),
+        attributes(),
+    )
+        .prop_map(
+            |(label, confidence, bbox, object_id, track_id, attributes)| Detection {
+                label,
+                confidence,
+                bbox,
+                object_id,
+                track_id,
+                attributes,
+            },
+        );
+    (unit(), element(), text(), vec(detection, 0..4), millis()).prop_map(
+        |(stream_unit, element, model, detections, produced_ms)| InferenceResultMessage {
+            stream_unit,
+            element,
+            model,
+            detections,
+            produced_ms,
+        },
+    )
+}
+
+pub fn keep_alive_message() -> impl Strategy<Value = KeepAliveMessage> {
+    text().prop_map(|module_id| KeepAliveMessage { module_id })
+}
+
+pub fn maintenance_notice() -> impl Strategy<Value = MaintenanceNotice> {
+    let severity = prop_oneof![
+        Just(MaintenanceSeverity::Info),
+        Just(MaintenanceSeverity::Degraded),
+        Just(MaintenanceSeverity::Outage),
+    ];
+    (
+        text(),
+        vec(stream_name(), 0..4),
+        millis(),
+        millis(),
+        severity,
+    )
+        .prop_map(
+            |(node, streams, start_ms, duration_ms, severity)| MaintenanceNotice {
+                node,
+                streams,
+                start_ms,
+                duration_ms,
+                severity,
+            },
+        )
+}
+
+pub fn node_telemetry() -> impl Strategy<Value = NodeTelemetry> {
+    (
+        text(),
+        text(),
+        hash_map(text(), any::<i64>(), 0..4),
+        hash_map(text(), real(), 0..4),
+        millis(),
+    )
+        .prop_map(
+            |(node_id, role, counters, gauges, collected_ms)| NodeTelemetry {
+                node_id,
+                role,
+                counters,
+                gauges,
+                collected_ms,
+            },
+        )
+}
+
+pub fn notify_cadence_control() -> impl Strategy<Value = NotifyCadenceControl> {
+    (
+        stream_name(),
+        option::of(track_info()),
+        option::of(millis()),
+        option::of(millis()),
+    )
+        .prop_map(
+            |(stream_name, track_info, notify_interval_ms, unit_duration_ms)| {
+                NotifyCadenceControl {
+                    stream_name,
+                    track_info,
+                    notify_interval_ms,
+                    unit_duration_ms,
+                }
+            },
+        )
+}
+
+pub fn notify_digest_request() -> impl Strategy<Value = NotifyDigestRequest> {
+    (any::<i64>(), text(), stream_name(), millis(), deadline()).prop_map(
+        |(request_id, topic, stream_name, since_ms, deadline_ms)| NotifyDigestRequest {
+            request_id,
+            topic,
+            stream_name,
+            since_ms,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn notify_digest_response() -> impl Strategy<Value = NotifyDigestResponse> {
+    let digest = (
+        track_info(),
+        ordered(0..=i64::MAX),
+        0..=i64::MAX,
+        0..=i64::MAX,
+        ordered(millis()),
+    )
+        .prop_map(
+            |(
+                track_info,
+                (first_unit, last_unit),
+                units_added,
+                notifications,
+                (first_saved_ms, last_saved_ms),
+            )| TrackDigest {
+                track_info,
+                first_unit,
+                last_unit,
+                units_added,
+                notifications,
+                first_saved_ms,
+                last_saved_ms,
+            },
+        );
+    (any::<i64>(), stream_name(), vec(digest, 0..4)).prop_map(
+        |(request_id, stream_name, tracks)| NotifyDigestResponse {
+            request_id,
+            stream_name,
+            tracks,
+        },
+    )
+}
+
+pub fn notify_message() -> impl Strategy<Value = NotifyMessage> {
+    let notify_type = prop_oneof![
+        Just(NotifyType::new()),
+        element().prop_map(NotifyType::ready),
+    ];
+    (unit(), millis(), notify_type, attributes()).prop_map(
+        |(stream_unit, saved_ms, notify_type, attributes)| NotifyMessage {
+            stream_unit,
+            saved_ms,
+            notify_type,
+            attributes,
+        },
+    )
+}
+
+pub fn ping_request_response() -> impl Strategy<Value = PingRequestResponse> {
+    let mtype = prop_oneof![
+        Just(PingRequestResponseType::Request),
+        Just(PingRequestResponseType::Response),
+    ];
+    (any::<i64>(), text(), mtype).prop_map(|(request_id, topic, mtype)| PingRequestResponse {
+        request_id,
+        topic,
+        mtype,
+    })
+}
+
+pub fn resolve_alias_request() -> impl Strategy<Value = ResolveAliasRequest> {
+    (any::<i64>(), text(), text(), deadline()).prop_map(
+        |(request_id, topic, alias, deadline_ms)| ResolveAliasRequest {
+            request_id,
+            topic,
+            alias,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn resolve_alias_response() -> impl Strategy<Value = ResolveAliasResponse> {
+    (any::<i64>(), text(), option::of(stream_name())).prop_map(
+        |(request_id, alias, stream_name)| ResolveAliasResponse {
+            request_id,
+            alias,
+            stream_name,
+        },
+    )
+}
+
+pub fn restore_stream_request() -> impl Strategy<Value = RestoreStreamRequest> {
+    (any::<i64>(), text(), stream_name(), deadline()).prop_map(
+        |(request_id, topic, stream_name, deadline_ms)| RestoreStreamRequest {
+            request_id,
+            topic,
+            stream_name,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn services_ffprobe_request() -> impl Strategy<Value = ServicesFFProbeRequest> {
+    (
+        any::<i64>(),
+        text(),
+        text(),
+        option::of(unit_range()),
+        attributes(),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, url, source_range, attributes, deadline_ms)| {
+                ServicesFFProbeRequest {
+                    request_id,
+                    topic,
+                    url,
+                    source_range,
+                    attributes,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn services_ffprobe_response() -> impl Strategy<Value = ServicesFFProbeResponse> {
+    let response_type = prop_oneof![
+        Just(ServicesFFProbeResponseType::Accepted),
+        Just(ServicesFFProbeResponseType::Complete),
+        Just(ServicesFFProbeResponseType::Error),
+        Just(ServicesFFProbeResponseType::Timeout),
+        Just(ServicesFFProbeResponseType::SourceUnreachable),
+        Just(ServicesFFProbeResponseType::UnsupportedProtocol),
+        Just(ServicesFFProbeResponseType::AuthFailed),
+    ];
+    let stream = (
+        option::of(0..64i64),
+        option::of(text()),
+        option::of(text()),
+        option::of((0..=8192i64, 0..=8192i64)),
+        option::of(0.0..240.0),
+        option::of(0..=i64::MAX),
+        option::of(0.0..1.0e6),
+        attributes(),
+    )
+        .prop_map(
+            |(index, codec_type, codec_name, dimensions, fps, bit_rate, duration_s, extra)| {
+                FFProbeStreamInfo {
+                    index,
+                    codec_type,
+                    codec_name,
+                    width: dimensions.map(|(width, _)| width),
+                    height: dimensions.map(|(_, height)| height),
+                    fps,
+                    bit_rate,
+                    duration_s,
+                    extra,
+                }
+            },
+        );
+    let format = (
+        option::of(text()),
+        option::of(0.0..1.0e6),
+        option::of(0..=i64::MAX),
+        option::of(0..=i64::MAX),
+        option::of(0..64i64),
+        attributes(),
+    )
+        .prop_map(
+            |(format_name, duration_s, size_bytes, bit_rate, nb_streams, extra)| {
+                FFProbeFormatInfo {
+                    format_name,
+                    duration_s,
+                    size_bytes,
+                    bit_rate,
+                    nb_streams,
+                    extra,
+                }
+            },
+        );
+    (
+        any::<i64>(),
+        response_type,
+        0..=i64::MAX,
+        vec(stream, 0..4),
+        option::of(format),
+        option::of(text()),
+    )
+        .prop_map(
+            |(request_id, response_type, time_spent, streams, format, error_detail)| {
+                ServicesFFProbeResponse {
+                    request_id,
+                    response_type,
+                    time_spent,
+                    streams,
+                    format,
+                    error_detail,
+                }
+            },
+        )
+}
+
+pub fn services_filmstrip_request() -> impl Strategy<Value = ServicesFilmstripRequest> {
+    (
+        any::<i64>(),
+        text(),
+        unit(),
+        ordered(millis()),
+        (1..=64i32, 1..=4096i32, 1..=4096i32),
+        any::<bool>(),
+        deadline(),
+    )
+        .prop_map(
+            |(
+                request_id,
+                topic,
+                stream_unit,
+                (from_ms, to_ms),
+                (tile_count, tile_width, tile_height),
+                composite,
+                deadline_ms,
+            )| ServicesFilmstripRequest {
+                request_id,
+                topic,
+                stream_unit,
+                from_ms,
+                to_ms,
+                tile_count,
+                tile_width,
+                tile_height,
+                composite,
+                deadline_ms,
+            },
+        )
+}
+
+pub fn services_filmstrip_response() -> impl Strategy<Value = ServicesFilmstripResponse> {
+    (
+        any::<i64>(),
+        unit(),
+        any::<bool>(),
+        vec(payload(), 0..4),
+        vec(artifact_reference(), 0..4),
+    )
+        .prop_map(|(request_id, stream_unit, composite, tiles, artifacts)| {
+            ServicesFilmstripResponse {
+                request_id,
+                stream_unit,
+                composite,
+                tiles,
+                artifacts,
+            }
+        })
+}
+
+pub fn set_stream_alias() -> impl Strategy<Value = SetStreamAlias> {
+    (text(), stream_name(), any::<bool>()).prop_map(|(alias, stream_name, replace)| {
+        SetStreamAlias {
+            alias,
+            stream_name,
+            replace,
+        }
+    })
+}
+
+pub fn slo_report() -> impl Strategy<Value = SloReport> {
+    (
+        text(),
+        ordered(millis()),
+        (0.0..=1.0, 0.0..=1.0),
+        (0.0..1.0e6, 0.0..1.0e6),
+        0..=i64::MAX,
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                service,
+                (window_start_ms, window_end_ms),
+                (availability, availability_target),
+                (latency_p95_ms, latency_target_ms),
+                latency_samples,
+                compliant,
+            )| SloReport {
+                service,
+                window_start_ms,
+                window_end_ms,
+                availability,
+                availability_target,
+                latency_p95_ms,
+                latency_target_ms,
+                latency_samples,
+                compliant,
+            },
+        )
+}
+
+pub fn snapshot_request() -> impl Strategy<Value = SnapshotRequest> {
+    let format = prop_oneof![
+        Just(SnapshotFormat::Jpeg),
+        Just(SnapshotFormat::Png),
+        Just(SnapshotFormat::Webp),
+    ];
+    (
+        any::<i64>(),
+        text(),
+        unit(),
+        element(),
+        format,
+        0..=8192i32,
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, stream_unit, element, format, max_dimension, deadline_ms)| {
+                SnapshotRequest {
+                    request_id,
+                    topic,
+                    stream_unit,
+                    element,
+                    format,
+                    max_dimension,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn snapshot_response() -> impl Strategy<Value = SnapshotResponse> {
+    (any::<i64>(), payload(), millis()).prop_map(|(request_id, image, produced_ms)| {
+        SnapshotResponse {
+            request_id,
+            image,
+            produced_ms,
+        }
+    })
+}
+
+pub fn soft_delete_stream_request() -> impl Strategy<Value = SoftDeleteStreamRequest> {
+    (any::<i64>(), text(), stream_name(), millis(), deadline()).prop_map(
+        |(request_id, topic, stream_name, grace_period_ms, deadline_ms)| SoftDeleteStreamRequest {
+            request_id,
+            topic,
+            stream_name,
+            grace_period_ms,
+            deadline_ms,
+        },
+    )
+}
+
+fn storage_control_status() -> impl Strategy<Value = StorageControlStatus> {
+    prop_oneof![
+        Just(StorageControlStatus::Applied),
+        Just(StorageControlStatus::Rejected),
+        Just(StorageControlStatus::NotFound),
+    ]
+}
+
+fn retention_policy() -> impl Strategy<Value = RetentionPolicy> {
+    (
+        option::of(millis()),
+        option::of(millis()),
+        option::of(millis()),
+    )
+        .prop_map(|(ttl_ms, max_bytes, max_units)| RetentionPolicy {
+            ttl_ms,
+            max_bytes,
+            max_units,
+        })
+}
+
+pub fn storage_purge_request() -> impl Strategy<Value = StoragePurgeRequest> {
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        option::of(track_info()),
+        option::of(0..=i64::MAX),
+        option::of(millis()),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, stream_name, track_info, before_unit, before_ms, deadline_ms)| {
+                StoragePurgeRequest {
+                    request_id,
+                    topic,
+                    stream_name,
+                    track_info,
+                    before_unit,
+                    before_ms,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn storage_purge_response() -> impl Strategy<Value = StoragePurgeResponse> {
+    (
+        any::<i64>(),
+        stream_name(),
+        storage_control_status(),
+        millis(),
+        millis(),
+        option::of(text()),
+    )
+        .prop_map(
+            |(request_id, stream_name, status, purged_units, freed_bytes, error)| {
+                StoragePurgeResponse {
+                    request_id,
+                    stream_name,
+                    status,
+                    purged_units,
+                    freed_bytes,
+                    error,
+                }
+            },
+        )
+}
+
+pub fn storage_retention_set_request() -> impl Strategy<Value = StorageRetentionSetRequest> {
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        retention_policy(),
+        deadline(),
+    )
+        .prop_map(|(request_id, topic, stream_name, policy, deadline_ms)| {
+            StorageRetentionSetRequest {
+                request_id,
+                topic,
+                stream_name,
+                policy,
+                deadline_ms,
+            }
+        })
+}
+
+pub fn storage_retention_set_response() -> impl Strategy<Value = StorageRetentionSetResponse> {
+    (
+        any::<i64>(),
+        stream_name(),
+        storage_control_status(),
+        option::of(retention_policy()),
+        option::of(text()),
+    )
+        .prop_map(|(request_id, stream_name, status, previous, error)| {
+            StorageRetentionSetResponse {
+                request_id,
+                stream_name,
+                status,
+                previous,
+                error,
+            }
+        })
+}
+
+pub fn storage_stats_request() -> impl Strategy<Value = StorageStatsRequest> {
+    (any::<i64>(), text(), option::of(stream_name()), deadline()).prop_map(
+        |(request_id, topic, stream_name, deadline_ms)| StorageStatsRequest {
+            request_id,
+            topic,
+            stream_name,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn storage_stats_response() -> impl Strategy<Value = StorageStatsResponse> {
+    let track = (
+        track_info(),
+        millis(),
+        millis(),
+        ordered(0..=i64::MAX),
+        millis(),
+    )
+        .prop_map(
+            |(track_info, unit_count, byte_size, (first_unit, last_unit), last_write_ms)| {
+                TrackStats {
+                    track_info,
+                    unit_count,
+                    byte_size,
+                    first_unit,
+                    last_unit,
+                    last_write_ms,
+                }
+            },
+        );
+    let stream = (stream_name(), vec(track, 0..4)).prop_map(|(stream_name, tracks)| StreamStats {
+        stream_name,
+        tracks,
+    });
+    (any::<i64>(), vec(stream, 0..4)).prop_map(|(request_id, streams)| StorageStatsResponse {
+        request_id,
+        streams,
+    })
+}
+
+pub fn stream_end_message() -> impl Strategy<Value = StreamEndMessage> {
+    let reason = prop_oneof![
+        Just(StreamEndReason::Finished),
+        Just(StreamEndReason::SourceLost),
+        Just(StreamEndReason::Deleted),
+        Just(StreamEndReason::Error),
+    ];
+    (stream_name(), track_info(), 0..=i64::MAX, reason).prop_map(
+        |(stream_name, track_info, last_unit, reason)| StreamEndMessage {
+            stream_name,
+            track_info,
+            last_unit,
+            reason,
+        },
+    )
+}
+
+pub fn stream_lifecycle_message() -> impl Strategy<Value = StreamLifecycleMessage> {
+    let event = prop_oneof![
+        Just(StreamLifecycleEvent::Created),
+        Just(StreamLifecycleEvent::Paused),
+        Just(StreamLifecycleEvent::Resumed),
+        Just(StreamLifecycleEvent::Deleted),
+        Just(StreamLifecycleEvent::SoftDeleted),
+        Just(StreamLifecycleEvent::Restored),
+    ];
+    (stream_name(), event, millis()).prop_map(|(stream_name, event, timestamp_ms)| {
+        StreamLifecycleMessage {
+            stream_name,
+            event,
+            timestamp_ms,
+        }
+    })
+}
+
+pub fn stream_multi_track_unit_elements_request(
+) -> impl Strategy<Value = StreamMultiTrackUnitElementsRequest> {
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        vec(track_info(), 0..4),
+        0..=i64::MAX,
+        element(),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, stream_name, tracks, unit, max_element, deadline_ms)| {
+                StreamMultiTrackUnitElementsRequest {
+                    request_id,
+                    topic,
+                    stream_name,
+                    tracks,
+                    unit,
+                    max_element,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn stream_multi_track_unit_elements_response(
+) -> impl Strategy<Value = StreamMultiTrackUnitElementsResponse> {
+    let track = (track_info(), vec(payload(), 0..4))
+        .prop_map(|(track_info, values)| TrackElements { track_info, values });
+    (any::<i64>(), stream_name(), 0..=i64::MAX, vec(track, 0..4)).prop_map(
+        |(request_id, stream_name, unit, tracks)| StreamMultiTrackUnitElementsResponse {
+            request_id,
+            stream_name,
+            unit,
+            tracks,
+        },
+    )
+}
+
+pub fn stream_replay_request() -> impl Strategy<Value = StreamReplayRequest> {
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        vec(track_info(), 0..4),
+        option::of(0..=i64::MAX),
+        option::of(millis()),
+        0.1..16.0,
+        text(),
+        deadline(),
+    )
+        .prop_map(
+            |(
+                request_id,
+                topic,
+                stream_name,
+                tracks,
+                from_unit,
+                from_ms,
+                speed,
+                destination_topic,
+                deadline_ms,
+            )| StreamReplayRequest {
+                request_id,
+                topic,
+                stream_name,
+                tracks,
+                from_unit,
+                from_ms,
+                speed,
+                destination_topic,
+                deadline_ms,
+            },
+        )
+}
+
+pub fn stream_replay_status() -> impl Strategy<Value = StreamReplayStatus> {
+    let state = prop_oneof![
+        Just(StreamReplayState::Started),
+        Just(StreamReplayState::Running),
+        Just(StreamReplayState::Completed),
+        Just(StreamReplayState::Stopped),
+        Just(StreamReplayState::Failed),
+    ];
+    (
+        any::<i64>(),
+        text(),
+        state,
+        option::of(0..=i64::MAX),
+        option::of(text()),
+    )
+        .prop_map(
+            |(request_id, replay_id, state, last_unit, error)| StreamReplayStatus {
+                request_id,
+                replay_id,
+                state,
+                last_unit,
+                error,
+            },
+        )
+}
+
+pub fn stream_replay_stop() -> impl Strategy<Value = StreamReplayStop> {
+    (any::<i64>(), text(), text(), deadline()).prop_map(
+        |(request_id, topic, replay_id, deadline_ms)| StreamReplayStop {
+            request_id,
+            topic,
+            replay_id,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn stream_track_keyframes_request() -> impl Strategy<Value = StreamTrackKeyframesRequest> {
+    (any::<i64>(), text(), unit_range(), deadline()).prop_map(
+        |(request_id, topic, range, deadline_ms)| StreamTrackKeyframesRequest {
+            request_id,
+            topic,
+            range,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn stream_track_keyframes_response() -> impl Strategy<Value = StreamTrackKeyframesResponse> {
+    (
+        any::<i64>(),
+        unit_range(),
+        vec((0..=i64::MAX, element()), 0..8),
+    )
+        .prop_map(
+            |(request_id, range, keyframes)| StreamTrackKeyframesResponse {
+                request_id,
+                range,
+                keyframes,
+            },
+        )
+}
+
+pub fn stream_track_unit_elements_request() -> impl Strategy<Value = StreamTrackUnitElementsRequest>
+{
+    (
+        any::<i64>(),
+        text(),
+        unit(),
+        element(),
+        0..=i32::MAX,
+        option::of(bytes(32)),
+        option::of(bytes(32)),
+        option::of("[a-z0-9_*-]{1,16}"),
+        deadline(),
+    )
+        .prop_map(
+            |(
+                request_id,
+                topic,
+                stream_unit,
+                max_element,
+                max_results,
+                continuation_cursor,
+                snapshot_token,
+                track_pattern,
+                deadline_ms,
+            )| StreamTrackUnitElementsRequest {
+                request_id,
+                topic,
+                stream_unit,
+                max_element,
+                max_results,
+                continuation_cursor,
+                snapshot_token,
+                track_pattern,
+                deadline_ms,
+            },
+        )
+}
+
+pub fn stream_track_unit_elements_response(
+) -> impl Strategy<Value = StreamTrackUnitElementsResponse> {
+    (
+        any::<i64>(),
+        unit(),
+        vec(payload(), 0..4),
+        option::of(bytes(32)),
+        any::<bool>(),
+        option::of(bytes(32)),
+        vec(track_name(), 0..4),
+    )
+        .prop_map(
+            |(
+                request_id,
+                stream_unit,
+                values,
+                continuation_cursor,
+                has_more,
+                snapshot_token,
+                track_names,
+            )| StreamTrackUnitElementsResponse {
+                request_id,
+                stream_unit,
+                values,
+                continuation_cursor,
+                has_more,
+                snapshot_token,
+                track_names,
+            },
+        )
+}
+
+pub fn stream_track_units_request() -> impl Strategy<Value = StreamTrackUnitsRequest> {
+    (
+        any::<i64>(),
+        text(),
+        unit(),
+        ordered(millis()),
+        0..=i32::MAX,
+        option::of(bytes(32)),
+        option::of(bytes(32)),
+        option::of("[a-z0-9_*-]{1,16}"),
+        deadline(),
+    )
+        .prop_map(
+            |(
+                request_id,
+                topic,
+                stream_unit,
+                (from_ms, to_ms),
+                max_results,
+                continuation_cursor,
+                snapshot_token,
+                track_pattern,
+                deadline_ms,
+            )| StreamTrackUnitsRequest {
+                request_id,
+                topic,
+                stream_unit,
+                from_ms: from_ms.into(),
+                to_ms: to_ms.into(),
+                max_results,
+                continuation_cursor,
+                snapshot_token,
+                track_pattern,
+                deadline_ms,
+            },
+        )
+}
+
+pub fn stream_track_units_response() -> impl Strategy<Value = StreamTrackUnitsResponse> {
+    (
+        any::<i64>(),
+        unit(),
+        ordered(millis()),
+        vec(0..=i64::MAX, 0..8),
+        option::of(bytes(32)),
+        any::<bool>(),
+        option::of(bytes(32)),
+        vec(track_name(), 0..4),
+        vec(unit_summary(), 0..4),
+    )
+        .prop_map(
+            |(
+                request_id,
+                stream_unit,
+                (from_ms, to_ms),
+                units,
+                continuation_cursor,
+                has_more,
+                snapshot_token,
+                track_names,
+                unit_summaries,
+            )| StreamTrackUnitsResponse {
+                request_id,
+                stream_unit,
+                from_ms: from_ms.into(),
+                to_ms: to_ms.into(),
+                units,
+                continuation_cursor,
+                has_more,
+                snapshot_token,
+                track_names,
+                unit_summaries,
+            },
+        )
+}
+
+pub fn stream_track_units_by_time_request() -> impl Strategy<Value = StreamTrackUnitsByTimeRequest>
+{
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        track_info(),
+        ordered(millis()),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, stream_name, track_info, (from_ms, to_ms), deadline_ms)| {
+                StreamTrackUnitsByTimeRequest {
+                    request_id,
+                    topic,
+                    stream_name,
+                    track_info,
+                    from_ms,
+                    to_ms,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn stream_track_units_by_time_response() -> impl Strategy<Value = StreamTrackUnitsByTimeResponse>
+{
+    (
+        any::<i64>(),
+        stream_name(),
+        track_info(),
+        ordered(millis()),
+        vec(unit_timestamp(), 0..8),
+    )
+        .prop_map(
+            |(request_id, stream_name, track_info, (from_ms, to_ms), units)| {
+                StreamTrackUnitsByTimeResponse {
+                    request_id,
+                    stream_name,
+                    track_info,
+                    from_ms,
+                    to_ms,
+                    units,
+                }
+            },
+        )
+}
+
+pub fn stream_tracks_request() -> impl Strategy<Value = StreamTracksRequest> {
+    (any::<i64>(), text(), stream_name(), deadline()).prop_map(
+        |(request_id, topic, stream_name, deadline_ms)| StreamTracksRequest {
+            request_id,
+            topic,
+            stream_name,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn stream_tracks_response() -> impl Strategy<Value = StreamTracksResponse> {
+    let descriptor = (
+        track_info(),
+        text(),
+        (0..=8192i32, 0..=8192i32),
+        0.0..240.0,
+        ordered(0..=i64::MAX),
+        0..=i64::MAX,
+    )
+        .prop_map(
+            |(track_info, codec, (width, height), fps, (first_unit, last_unit), byte_size)| {
+                TrackDescriptor {
+                    track_info,
+                    codec,
+                    width,
+                    height,
+                    fps,
+                    first_unit,
+                    last_unit,
+                    byte_size,
+                }
+            },
+        );
+    (any::<i64>(), stream_name(), vec(descriptor, 0..4)).prop_map(
+        |(request_id, stream_name, descriptors)| StreamTracksResponse {
+            request_id,
+            stream_name,
+            tracks: descriptors.iter().map(|d| d.track_info.clone()).collect(),
+            descriptors,
+        },
+    )
+}
+
+pub fn streams_discovery_request() -> impl Strategy<Value = StreamsDiscoveryRequest> {
+    (any::<i64>(), text(), bytes(16), any::<bool>(), deadline()).prop_map(
+        |(request_id, topic, prefix_filter, include_deleted, deadline_ms)| {
+            StreamsDiscoveryRequest {
+                request_id,
+                topic,
+                prefix_filter,
+                include_deleted,
+                deadline_ms,
+            }
+        },
+    )
+}
+
+pub fn streams_discovery_response() -> impl Strategy<Value = StreamsDiscoveryResponse> {
+    let descriptor = (stream_name(), 0..64i32, millis(), any::<bool>()).prop_map(
+        |(stream_name, track_count, last_activity_ms, deleted)| StreamDescriptor {
+            stream_name,
+            track_count,
+            last_activity_ms,
+            deleted,
+        },
+    );
+    (any::<i64>(), vec(descriptor, 0..4)).prop_map(|(request_id, streams)| {
+        StreamsDiscoveryResponse {
+            request_id,
+            streams,
+        }
+    })
+}
+
+pub fn transcode_job_cancel() -> impl Strategy<Value = TranscodeJobCancel> {
+    (any::<i64>(), text(), text(), deadline()).prop_map(
+        |(request_id, topic, job_id, deadline_ms)| TranscodeJobCancel {
+            request_id,
+            topic,
+            job_id,
+            deadline_ms,
+        },
+    )
+}
+
+pub fn transcode_job_request() -> impl Strategy<Value = TranscodeJobRequest> {
+    (
+        any::<i64>(),
+        text(),
+        text(),
+        option::of(unit_range()),
+        text(),
+        text(),
+        attributes(),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, source, source_range, profile, output, options, deadline_ms)| {
+                TranscodeJobRequest {
+                    request_id,
+                    topic,
+                    source,
+                    source_range,
+                    profile,
+                    output,
+                    options,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn transcode_job_status() -> impl Strategy<Value = TranscodeJobStatus> {
+    let state = prop_oneof![
+        Just(TranscodeJobState::Queued),
+        Just(TranscodeJobState::Running),
+        Just(TranscodeJobState::Completed),
+        Just(TranscodeJobState::Failed),
+        Just(TranscodeJobState::Cancelled),
+    ];
+    (any::<i64>(), text(), state, 0.0..=1.0, option::of(text())).prop_map(
+        |(request_id, job_id, state, progress, error)| TranscodeJobStatus {
+            request_id,
+            job_id,
+            state,
+            progress,
+            error,
+        },
+    )
+}
+
+pub fn unit_bitmap_request() -> impl Strategy<Value = UnitBitmapRequest> {
+    (
+        any::<i64>(),
+        text(),
+        stream_name(),
+        track_info(),
+        ordered(0..=i64::MAX),
+        deadline(),
+    )
+        .prop_map(
+            |(request_id, topic, stream_name, track_info, (from_unit, to_unit), deadline_ms)| {
+                UnitBitmapRequest {
+                    request_id,
+                    topic,
+                    stream_name,
+                    track_info,
+                    from_unit,
+                    to_unit,
+                    deadline_ms,
+                }
+            },
+        )
+}
+
+pub fn unit_bitmap_response() -> impl Strategy<Value = UnitBitmapResponse> {
+    (
+        any::<i64>(),
+        stream_name(),
+        track_info(),
+        ordered(0..=i64::MAX),
+    )
+        .prop_flat_map(
+            |(request_id, stream_name, track_info, (from_unit, to_unit))| {
+                unit_bitmap(from_unit, to_unit).prop_map(move |bitmap| UnitBitmapResponse {
+                    request_id,
+                    stream_name,
+                    track_info: track_info.clone(),
+                    from_unit,
+                    to_unit,
+                    bitmap,
+                })
+            },
+        )
+}
+
+pub fn unit_element_ack_message() -> impl Strategy<Value = UnitElementAckMessage> {
+    let status = prop_oneof![
+        Just(AckStatus::Persisted),
+        Just(AckStatus::Rejected),
+        Just(AckStatus::RetryLater),
+    ];
+    (unit(), element(), status, option::of(text())).prop_map(
+        |(stream_unit, element, status, error)| UnitElementAckMessage {
+            stream_unit,
+            element,
+            status,
+            error,
+        },
+    )
+}
+
+/// A chunk of an element, the checksum is the CRC-32 of its value.
+pub fn unit_element_chunk_message() -> impl Strategy<Value = UnitElementChunkMessage> {
+    let compression = prop_oneof![
+        Just(Compression::Uncompressed),
+        Just(Compression::Zstd),
+        Just(Compression::Lz4),
+    ];
+    (
+        unit(),
+        element(),
+        (1..=16i32).prop_flat_map(|total_chunks| (0..total_chunks, Just(total_chunks))),
+        bytes(256),
+        attributes(),
+        compression,
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                stream_unit,
+                element,
+                (chunk_index, total_chunks),
+                value,
+                attributes,
+                compression,
+                is_keyframe,
+            )| UnitElementChunkMessage {
+                stream_unit,
+                element,
+                chunk_index,
+                total_chunks,
+                checksum: crc32(&value),
+                value,
+                attributes,
+                last: chunk_index == total_chunks - 1,
+                compression,
+                encryption: None,
+                is_keyframe,
+            },
+        )
+}
+
+/// An uncompressed element, the decoder decompresses the values, so a compressed
+/// one wouldn't read back as it was written.
+pub fn unit_element_message() -> impl Strategy<Value = UnitElementMessage> {
+    (
+        unit(),
+        element(),
+        payload(),
+        any::<bool>(),
+        option::of(encryption_info()),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(stream_unit, element, payload, last, encryption, is_keyframe)| UnitElementMessage {
+                stream_unit,
+                element,
+                value: payload.data,
+                attributes: payload.attributes,
+                last,
+                compression: Compression::Uncompressed,
+                encryption,
+                is_keyframe,
+                checksum: payload.checksum,
+            },
+        )
+}
+
+macro_rules! message_strategies {
+    ($($kind:ident => $strategy:ident,)*) => {
+        /// Valid messages of the kind: they save, pass [`ProtocolMessage::validate`] and
+        /// load back equal.
+        ///
+        /// [`ProtocolMessage::validate`]: crate::avro::ProtocolMessage::validate
+        pub fn message(kind: MessageKind) -> BoxedStrategy<Message> {
+            match kind {
+                $(MessageKind::$kind => $strategy().prop_map(Message::$kind).boxed(),)*
+            }
+        }
+    };
+}
+
+message_strategies! {
+    AuthChallenge => auth_challenge,
+    AuthResult => auth_result,
+    BackfillRequest => backfill_request,
+    BackfillResponse => backfill_response,
+    EncodingProfileAck => encoding_profile_ack,
+    EncodingProfileOffer => encoding_profile_offer,
+    ErrorResponse => error_response,
+    FlowControlGrant => flow_control_grant,
+    FlowControlRequest => flow_control_request,
+    HeartbeatMessage => heartbeat_message,
+    InferenceResultMessage => inference_result_message,
+    KeepAliveMessage => keep_alive_message,
+    MaintenanceNotice => maintenance_notice,
+    NodeTelemetry => node_telemetry,
+    NotifyCadenceControl => notify_cadence_control,
+    NotifyDigestRequest => notify_digest_request,
+    NotifyDigestResponse => notify_digest_response,
+    NotifyMessage => notify_message,
+    PingRequestResponse => ping_request_response,
+    ResolveAliasRequest => resolve_alias_request,
+    ResolveAliasResponse => resolve_alias_response,
+    RestoreStreamRequest => restore_stream_request,
+    ServicesFFProbeRequest => services_ffprobe_request,
+    ServicesFFProbeResponse => services_ffprobe_response,
+    ServicesFilmstripRequest => services_filmstrip_request,
+    ServicesFilmstripResponse => services_filmstrip_response,
+    SetStreamAlias => set_stream_alias,
+    SloReport => slo_report,
+    SnapshotRequest => snapshot_request,
+    SnapshotResponse => snapshot_response,
+    SoftDeleteStreamRequest => soft_delete_stream_request,
+    StoragePurgeRequest => storage_purge_request,
+    StoragePurgeResponse => storage_purge_response,
+    StorageRetentionSetRequest => storage_retention_set_request,
+    StorageRetentionSetResponse => storage_retention_set_response,
+    StorageStatsRequest => storage_stats_request,
+    StorageStatsResponse => storage_stats_response,
+    StreamEndMessage => stream_end_message,
+    StreamLifecycleMessage => stream_lifecycle_message,
+    StreamMultiTrackUnitElementsRequest => stream_multi_track_unit_elements_request,
+    StreamMultiTrackUnitElementsResponse => stream_multi_track_unit_elements_response,
+    StreamReplayRequest => stream_replay_request,
+    StreamReplayStatus => stream_replay_status,
+    StreamReplayStop => stream_replay_stop,
+    StreamTrackKeyframesRequest => stream_track_keyframes_request,
+    StreamTrackKeyframesResponse => stream_track_keyframes_response,
+    StreamTrackUnitElementsRequest => stream_track_unit_elements_request,
+    StreamTrackUnitElementsResponse => stream_track_unit_elements_response,
+    StreamTrackUnitsByTimeRequest => stream_track_units_by_time_request,
+    StreamTrackUnitsByTimeResponse => stream_track_units_by_time_response,
+    StreamTrackUnitsRequest => stream_track_units_request,
+    StreamTrackUnitsResponse => stream_track_units_response,
+    StreamTracksRequest => stream_tracks_request,
+    StreamTracksResponse => stream_tracks_response,
+    StreamsDiscoveryRequest => streams_discovery_request,
+    StreamsDiscoveryResponse => streams_discovery_response,
+    TranscodeJobCancel => transcode_job_cancel,
+    TranscodeJobRequest => transcode_job_request,
+    TranscodeJobStatus => transcode_job_status,
+    UnitBitmapRequest => unit_bitmap_request,
+    UnitBitmapResponse => unit_bitmap_response,
+    UnitElementAckMessage => unit_element_ack_message,
+    UnitElementChunkMessage => unit_element_chunk_message,
+    UnitElementMessage => unit_element_message,
+}
+
+/// Valid messages of any kind.
+pub fn any_message() -> BoxedStrategy<Message> {
+    proptest::sample::select(MessageKind::ALL)
+        .prop_flat_map(message)
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, ProtocolMessage};
+    use crate::message::Message;
+    use crate::message_kind::MessageKind;
+    use crate::objects::{FromProtocolMessage, ToProtocolMessage};
+    use crate::testkit::generators::message;
+    use crate::utils::get_avro_path;
+    use proptest::test_runner::{Config, TestRunner};
+
+    #[test]
+    fn test_round_trip() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let mut runner = TestRunner::new(Config::with_cases(16));
+        for kind in MessageKind::ALL {
+            runner
+                .run(&message(*kind), |generated| {
+                    assert_eq!(generated.kind(), Some(*kind));
+                    let saved = generated.save(&mb).unwrap();
+                    assert_eq!(saved.validate(), Ok(()));
+                    let packed = mb.save_from_avro(saved).unwrap();
+                    let (schema, object) =
+                        mb.builder_impl().read_protocol_message(&packed).unwrap();
+                    let read = Message::load(&ProtocolMessage { schema, object });
+                    assert_eq!(read, Some(generated));
+                    Ok(())
+                })
+                .unwrap_or_else(|e| panic!("{:?}: {}", kind, e));
+        }
+    }
+}
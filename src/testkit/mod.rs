@@ -0,0 +1,4 @@
+pub mod adversarial;
+pub mod chaos;
+pub mod corpus;
+pub mod generators;
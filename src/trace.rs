@@ -0,0 +1,165 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+pub const MAX_TRACESTATE_LENGTH: usize = 512;
+
+/// W3C trace context carried by the envelope, so a trace follows a message across
+/// the protocol hops.
+///
+/// `traceparent` is kept in the `00-<trace-id>-<parent-id>-<flags>` form,
+/// `tracestate` is vendor data passed through as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "python", cfg_eval::cfg_eval, pyclass)]
+pub struct TraceContext {
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub traceparent: String,
+    #[cfg_attr(feature = "python", pyo3(get))]
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    pub fn trace_id(&self) -> String {
+        self.traceparent[3..35].to_string()
+    }
+
+    pub fn parent_id(&self) -> String {
+        self.traceparent[36..52].to_string()
+    }
+
+    pub fn is_sampled(&self) -> bool {
+        u8::from_str_radix(&self.traceparent[53..55], 16).unwrap_or_default() & 0x01 != 0
+    }
+
+    /// Context of the next hop: the same trace with `parent_id` of the span the
+    /// hop is processed in.
+    pub fn child(&self, parent_id: &str) -> Result<Self, String> {
+        let traceparent = format!(
+            "00-{}-{}-{}",
+            self.trace_id(),
+            parent_id,
+            &self.traceparent[53..55]
+        );
+        TraceContext::parse(&traceparent, self.tracestate.as_deref())
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TraceContext {
+    #[new]
+    fn py_new(traceparent: String, tracestate: Option<String>) -> PyResult<Self> {
+        TraceContext::parse(&traceparent, tracestate.as_deref()).map_err(PyValueError::new_err)
+    }
+
+    #[pyo3(name = "trace_id")]
+    fn py_trace_id(&self) -> String {
+        self.trace_id()
+    }
+
+    #[pyo3(name = "parent_id")]
+    fn py_parent_id(&self) -> String {
+        self.parent_id()
+    }
+
+    #[pyo3(name = "is_sampled")]
+    fn py_is_sampled(&self) -> bool {
+        self.is_sampled()
+    }
+
+    #[pyo3(name = "child")]
+    fn py_child(&self, parent_id: &str) -> PyResult<Self> {
+        self.child(parent_id).map_err(PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+}
+
+fn is_lower_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+}
+
+impl TraceContext {
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Result<Self, String> {
+        let parts: Vec<_> = traceparent.split('-').collect();
+        match parts.as_slice() {
+            [version, trace_id, parent_id, flags]
+                if *version == "00"
+                    && is_lower_hex(trace_id, 32)
+                    && is_lower_hex(parent_id, 16)
+                    && is_lower_hex(flags, 2) =>
+            {
+                if trace_id.chars().all(|c| c == '0') || parent_id.chars().all(|c| c == '0') {
+                    return Err(format!(
+                        "Trace or parent id of `{}` is all zeroes",
+                        traceparent
+                    ));
+                }
+            }
+            _ => return Err(format!("`{}` is not a valid traceparent", traceparent)),
+        }
+        if let Some(tracestate) = tracestate {
+            if tracestate.len() > MAX_TRACESTATE_LENGTH {
+                return Err(format!(
+                    "tracestate is {} bytes long, at most {} are allowed",
+                    tracestate.len(),
+                    MAX_TRACESTATE_LENGTH
+                ));
+            }
+        }
+        Ok(TraceContext {
+            traceparent: traceparent.to_string(),
+            tracestate: tracestate.map(String::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::TraceContext;
+
+    #[test]
+    fn test_parse() {
+        let ctx = TraceContext::parse(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            Some("vendor=value"),
+        )
+        .unwrap();
+        assert_eq!(ctx.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(ctx.parent_id(), "00f067aa0ba902b7");
+        assert!(ctx.is_sampled());
+
+        let child = ctx.child("b7ad6b7169203331").unwrap();
+        assert_eq!(child.trace_id(), ctx.trace_id());
+        assert_eq!(child.parent_id(), "b7ad6b7169203331");
+        assert_eq!(child.tracestate, ctx.tracestate);
+
+        assert!(TraceContext::parse(
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            None
+        )
+        .is_err());
+        assert!(TraceContext::parse(
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            None
+        )
+        .is_err());
+        assert!(TraceContext::parse(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            None
+        )
+        .is_err());
+        assert!(TraceContext::parse("garbage", None).is_err());
+    }
+}
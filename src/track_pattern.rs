@@ -0,0 +1,79 @@
+use crate::primitives::{TrackName, TRACK_NAME_MAX_LENGTH};
+
+/// Patterns are glob-like: `*` matches any run of characters and `?` a single one,
+/// e.g. `meta-*` selects the tracks a pipeline creates as `meta-0`, `meta-faces`.
+pub fn validate_track_pattern(pattern: &str) -> Result<(), String> {
+    if pattern.is_empty() {
+        return Err(String::from("Track pattern is empty"));
+    }
+    let literal_len = pattern.bytes().filter(|c| *c != b'*').count();
+    if literal_len > TRACK_NAME_MAX_LENGTH {
+        return Err(format!(
+            "Track pattern `{}` can't match names of at most {} characters",
+            pattern, TRACK_NAME_MAX_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Track names are zero-padded, the padding isn't part of the name.
+pub fn track_name_matches(pattern: &str, track_name: &TrackName) -> bool {
+    let len = track_name
+        .iter()
+        .rposition(|c| *c != 0)
+        .map(|i| i + 1)
+        .unwrap_or_default();
+    glob_matches(pattern.as_bytes(), &track_name[..len])
+}
+
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` and of the name when it was met, to backtrack to
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == b'?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::pack_track_name;
+    use crate::track_pattern::{track_name_matches, validate_track_pattern};
+
+    #[test]
+    fn test_matches() {
+        let name = |n: &str| pack_track_name(n).unwrap();
+
+        assert!(track_name_matches("meta-*", &name("meta-faces")));
+        assert!(track_name_matches("meta-*", &name("meta-")));
+        assert!(!track_name_matches("meta-*", &name("video")));
+        assert!(track_name_matches("*-faces", &name("meta-faces")));
+        assert!(track_name_matches("m?ta-*s", &name("meta-faces")));
+        assert!(!track_name_matches("m?ta-*s", &name("meta-face")));
+        assert!(track_name_matches("*", &name("")));
+        assert!(track_name_matches("main", &name("main")));
+        assert!(!track_name_matches("main", &name("main2")));
+
+        assert!(validate_track_pattern("meta-*").is_ok());
+        assert!(validate_track_pattern("").is_err());
+        assert!(validate_track_pattern("a-very-long-track-name-*").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::primitives::{ElementType, Payload, Unit};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssembledUnit {
+    pub stream_unit: Unit,
+    pub payloads: Vec<Payload>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct PendingUnit {
+    elements: BTreeMap<ElementType, Payload>,
+    last_element: Option<ElementType>,
+}
+
+impl PendingUnit {
+    fn is_complete(&self) -> bool {
+        match self.last_element {
+            Some(last) => {
+                self.elements.len() == last as usize + 1
+                    && self.elements.keys().next() == Some(&0)
+                    && self.elements.keys().next_back() == Some(&last)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Collects unit elements until the element flagged as `last` and all the elements
+/// before it are received, then yields the unit with the payloads ordered by element.
+#[derive(Debug, Default, Clone)]
+pub struct UnitAssembler {
+    pending: HashMap<Unit, PendingUnit>,
+}
+
+impl UnitAssembler {
+    pub fn new() -> Self {
+        UnitAssembler::default()
+    }
+
+    pub fn push(&mut self, message: UnitElementMessage) -> Option<AssembledUnit> {
+        let pending = self.pending.entry(message.stream_unit.clone()).or_default();
+        if message.last {
+            pending.last_element = Some(message.element);
+        }
+        pending.elements.insert(
+            message.element,
+            Payload::new(message.value, message.attributes),
+        );
+
+        if !pending.is_complete() {
+            return None;
+        }
+
+        self.pending
+            .remove(&message.stream_unit)
+            .map(|pending| AssembledUnit {
+                stream_unit: message.stream_unit,
+                payloads: pending.elements.into_values().collect(),
+            })
+    }
+
+    pub fn pending_units(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn discard(&mut self, stream_unit: &Unit) -> bool {
+        self.pending.remove(stream_unit).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::primitives::{pack_stream_name, pack_track_name, Unit};
+    use crate::unit_assembler::UnitAssembler;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_assemble_out_of_order() {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        let unit = Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        );
+        let element = |element: i16, last: bool| {
+            UnitElementMessage::new(
+                unit.clone(),
+                element,
+                vec![element as u8],
+                HashMap::default(),
+                last,
+            )
+        };
+
+        let mut assembler = UnitAssembler::new();
+        assert!(assembler.push(element(2, true)).is_none());
+        assert!(assembler.push(element(0, false)).is_none());
+        assert_eq!(assembler.pending_units(), 1);
+
+        let assembled = assembler.push(element(1, false)).unwrap();
+        assert_eq!(assembled.stream_unit, unit);
+        assert_eq!(
+            assembled
+                .payloads
+                .iter()
+                .map(|p| p.data.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![0], vec![1], vec![2]]
+        );
+        assert_eq!(assembler.pending_units(), 0);
+    }
+}
@@ -1,25 +1,40 @@
+use crate::primitives::AttributeValue;
 use avro_rs::types::Value;
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs;
 use std::path::{Path, PathBuf};
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn load_file(prefix: &Path, schema_name: &str) -> String {
+    try_load_file(prefix, schema_name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn try_load_file(prefix: &Path, schema_name: &str) -> Result<String, String> {
     let path = prefix.join(schema_name);
-    fs::read_to_string(&path).unwrap_or_else(|e| {
-        panic!(
+    fs::read_to_string(&path).map_err(|e| {
+        format!(
             "File {} cannot be loaded. Error is {:?}",
-            &path.to_str().unwrap(),
+            path.to_string_lossy(),
             e
         )
     })
 }
 
-pub fn gen_hash_map(s: &HashMap<String, String>) -> Value {
-    Value::Map(
-        s.iter()
-            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
-            .collect(),
-    )
+pub fn gen_hash_map(s: &HashMap<String, AttributeValue>) -> Value {
+    Value::Map(s.iter().map(|(k, v)| (k.clone(), v.to_avro())).collect())
+}
+
+pub fn parse_hash_map(m: &HashMap<String, Value>) -> HashMap<String, AttributeValue> {
+    m.iter()
+        .map(|(k, v)| {
+            (
+                k.clone(),
+                AttributeValue::from_avro(v).unwrap_or_else(|| AttributeValue::from("")),
+            )
+        })
+        .collect()
 }
 
 pub fn value_to_string(v: &Value) -> Option<String> {
@@ -0,0 +1,999 @@
+use crate::attributes::attributes_size;
+use crate::avro::ProtocolMessage;
+use crate::message::Message;
+use crate::objects::services::storage::notify_digest::{NotifyDigestRequest, NotifyDigestResponse};
+use crate::objects::services::storage::stream_track_keyframes::{
+    StreamTrackKeyframesRequest, StreamTrackKeyframesResponse,
+};
+use crate::objects::services::storage::stream_track_unit_elements::{
+    StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+};
+use crate::objects::services::storage::stream_track_units::{
+    StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+};
+use crate::objects::services::storage::stream_track_units_by_time::{
+    StreamTrackUnitsByTimeRequest, StreamTrackUnitsByTimeResponse,
+};
+use crate::objects::services::storage::stream_tracks::{StreamTracksRequest, StreamTracksResponse};
+use crate::objects::services::storage::streams_discovery::{
+    StreamsDiscoveryRequest, StreamsDiscoveryResponse,
+};
+use crate::objects::services::storage::unit_bitmap::{UnitBitmapRequest, UnitBitmapResponse};
+use crate::objects::FromProtocolMessage;
+use crate::primitives::{
+    get_empty_track_name, Attributes, Payload, StreamName, TrackInfo, TrackName, Unit, UnitRange,
+};
+use crate::track_pattern::track_name_matches;
+use std::fmt;
+use std::str;
+
+/// Invariant of a decoded response the store failed to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    RequestIdMismatch {
+        expected: i64,
+        actual: i64,
+    },
+    StreamMismatch,
+    EmptyTrackName,
+    UnitsNotMonotonic {
+        previous: i64,
+        unit: i64,
+    },
+    UnitOutOfRange {
+        unit: i64,
+        from_unit: i64,
+        to_unit: i64,
+    },
+    CountMismatch {
+        field: &'static str,
+        declared: usize,
+        actual: usize,
+    },
+    NegativeCount {
+        field: &'static str,
+    },
+    EmptyStreamName,
+    InvalidTrackName,
+    InvertedRange {
+        field: &'static str,
+        from: i64,
+        to: i64,
+    },
+    TooManyAttributes {
+        count: usize,
+        limit: usize,
+    },
+    AttributesTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    PayloadTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    Malformed,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::RequestIdMismatch { expected, actual } => write!(
+                f,
+                "Response belongs to request {}, expected {}",
+                actual, expected
+            ),
+            ValidationError::StreamMismatch => {
+                write!(f, "Response is about another stream or track")
+            }
+            ValidationError::EmptyTrackName => write!(f, "Track name is empty"),
+            ValidationError::UnitsNotMonotonic { previous, unit } => write!(
+                f,
+                "Unit {} follows unit {}, units must increase",
+                unit, previous
+            ),
+            ValidationError::UnitOutOfRange {
+                unit,
+                from_unit,
+                to_unit,
+            } => write!(
+                f,
+                "Unit {} is out of the requested range [{}, {}]",
+                unit, from_unit, to_unit
+            ),
+            ValidationError::CountMismatch {
+                field,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "Field `{}` has {} items, at most {} are declared",
+                field, actual, declared
+            ),
+            ValidationError::NegativeCount { field } => {
+                write!(f, "Field `{}` is negative", field)
+            }
+            ValidationError::EmptyStreamName => write!(f, "Stream name is empty"),
+            ValidationError::InvalidTrackName => write!(f, "Track name is not a valid UTF-8"),
+            ValidationError::InvertedRange { field, from, to } => write!(
+                f,
+                "Range `{}` starts at {} after its end {}",
+                field, from, to
+            ),
+            ValidationError::TooManyAttributes { count, limit } => write!(
+                f,
+                "Message has {} attributes, at most {} are allowed",
+                count, limit
+            ),
+            ValidationError::AttributesTooLarge { size, limit } => write!(
+                f,
+                "Attributes take {} bytes, at most {} are allowed",
+                size, limit
+            ),
+            ValidationError::PayloadTooLarge { size, limit } => write!(
+                f,
+                "Payload takes {} bytes, at most {} are allowed",
+                size, limit
+            ),
+            ValidationError::Malformed => {
+                write!(f, "Message doesn't match the record of its schema")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks a decoded response against the request it answers.
+///
+/// Validation is optional, the consumers which trust the store may skip it.
+pub trait ValidateResponse {
+    type Request;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError>;
+}
+
+fn check_request_id(expected: i64, actual: i64) -> Result<(), ValidationError> {
+    if expected != actual {
+        return Err(ValidationError::RequestIdMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+fn check_track_name(track_name: &TrackName) -> Result<(), ValidationError> {
+    if *track_name == get_empty_track_name() {
+        return Err(ValidationError::EmptyTrackName);
+    }
+    Ok(())
+}
+
+fn check_unit_summaries(response: &StreamTrackUnitsResponse) -> Result<(), ValidationError> {
+    if response.unit_summaries.is_empty() {
+        return Ok(());
+    }
+    if response.unit_summaries.len() != response.units.len() {
+        return Err(ValidationError::CountMismatch {
+            field: "unit_summaries",
+            declared: response.units.len(),
+            actual: response.unit_summaries.len(),
+        });
+    }
+    if response
+        .unit_summaries
+        .iter()
+        .zip(&response.units)
+        .any(|(summary, unit)| summary.unit != *unit)
+    {
+        return Err(ValidationError::StreamMismatch);
+    }
+    Ok(())
+}
+
+fn check_resolved_tracks(
+    pattern: &str,
+    track_names: &[TrackName],
+    entries: usize,
+) -> Result<(), ValidationError> {
+    if track_names.len() != entries {
+        return Err(ValidationError::CountMismatch {
+            field: "track_names",
+            declared: entries,
+            actual: track_names.len(),
+        });
+    }
+    for track_name in track_names {
+        check_track_name(track_name)?;
+        if !track_name_matches(pattern, track_name) {
+            return Err(ValidationError::StreamMismatch);
+        }
+    }
+    Ok(())
+}
+
+fn check_monotonic(units: impl Iterator<Item = i64>) -> Result<(), ValidationError> {
+    let mut previous: Option<i64> = None;
+    for unit in units {
+        match previous {
+            Some(previous) if unit <= previous => {
+                return Err(ValidationError::UnitsNotMonotonic { previous, unit })
+            }
+            _ => previous = Some(unit),
+        }
+    }
+    Ok(())
+}
+
+/// `max` of zero or less means no limit was declared.
+fn check_count(field: &'static str, max: i64, actual: usize) -> Result<(), ValidationError> {
+    if max > 0 && actual as i64 > max {
+        return Err(ValidationError::CountMismatch {
+            field,
+            declared: max as usize,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+impl ValidateResponse for StreamTracksResponse {
+    type Request = StreamTracksRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        if self.stream_name != request.stream_name {
+            return Err(ValidationError::StreamMismatch);
+        }
+        for track in &self.tracks {
+            check_track_name(&track.track_name)?;
+        }
+        if !self.descriptors.is_empty() && self.descriptors.len() != self.tracks.len() {
+            return Err(ValidationError::CountMismatch {
+                field: "descriptors",
+                declared: self.tracks.len(),
+                actual: self.descriptors.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ValidateResponse for StreamTrackUnitsResponse {
+    type Request = StreamTrackUnitsRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        if let Some(pattern) = &request.track_pattern {
+            if self.stream_unit.stream_name != request.stream_unit.stream_name {
+                return Err(ValidationError::StreamMismatch);
+            }
+            check_count("units", request.max_results.into(), self.units.len())?;
+            check_resolved_tracks(pattern, &self.track_names, self.units.len())?;
+            // units of different tracks interleave, every track must still increase
+            for track_name in &self.track_names {
+                check_monotonic(
+                    self.resolved_units()
+                        .into_iter()
+                        .filter(|(name, _)| name == track_name)
+                        .map(|(_, unit)| unit),
+                )?;
+            }
+            return check_unit_summaries(self);
+        }
+        check_track_name(&self.stream_unit.track_name)?;
+        if self.stream_unit.stream_name != request.stream_unit.stream_name
+            || self.stream_unit.track_name != request.stream_unit.track_name
+        {
+            return Err(ValidationError::StreamMismatch);
+        }
+        check_count("units", request.max_results.into(), self.units.len())?;
+        check_monotonic(self.units.iter().copied())?;
+        check_unit_summaries(self)
+    }
+}
+
+impl ValidateResponse for StreamTrackUnitElementsResponse {
+    type Request = StreamTrackUnitElementsRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        match &request.track_pattern {
+            Some(pattern) => {
+                if self.stream_unit.stream_name != request.stream_unit.stream_name
+                    || self.stream_unit.track_type != request.stream_unit.track_type
+                    || self.stream_unit.unit != request.stream_unit.unit
+                {
+                    return Err(ValidationError::StreamMismatch);
+                }
+                check_resolved_tracks(pattern, &self.track_names, self.values.len())?;
+            }
+            None => {
+                check_track_name(&self.stream_unit.track_name)?;
+                if self.stream_unit != request.stream_unit {
+                    return Err(ValidationError::StreamMismatch);
+                }
+            }
+        }
+        if request.max_element < 0 {
+            return Err(ValidationError::NegativeCount {
+                field: "max_element",
+            });
+        }
+        check_count(
+            "values",
+            i64::from(request.max_element) + 1,
+            self.values.len(),
+        )?;
+        check_count("values", request.max_results.into(), self.values.len())
+    }
+}
+
+impl ValidateResponse for StreamTrackUnitsByTimeResponse {
+    type Request = StreamTrackUnitsByTimeRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        check_track_name(&self.track_info.track_name)?;
+        if self.stream_name != request.stream_name || self.track_info != request.track_info {
+            return Err(ValidationError::StreamMismatch);
+        }
+        check_monotonic(self.units.iter().map(|u| u.unit))
+    }
+}
+
+impl ValidateResponse for StreamTrackKeyframesResponse {
+    type Request = StreamTrackKeyframesRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        check_track_name(&self.range.track_name)?;
+        if self.range != request.range {
+            return Err(ValidationError::StreamMismatch);
+        }
+        for (unit, _) in &self.keyframes {
+            if *unit < self.range.from_unit || *unit > self.range.to_unit {
+                return Err(ValidationError::UnitOutOfRange {
+                    unit: *unit,
+                    from_unit: self.range.from_unit,
+                    to_unit: self.range.to_unit,
+                });
+            }
+        }
+        // several keyframes may share a unit, but the list must not go backwards
+        match self.keyframes.windows(2).find(|w| w[1] < w[0]) {
+            Some(w) => Err(ValidationError::UnitsNotMonotonic {
+                previous: w[0].0,
+                unit: w[1].0,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ValidateResponse for StreamsDiscoveryResponse {
+    type Request = StreamsDiscoveryRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        for stream in &self.streams {
+            if !request.matches(stream.stream_name) || (stream.deleted && !request.include_deleted)
+            {
+                return Err(ValidationError::StreamMismatch);
+            }
+            if stream.track_count < 0 {
+                return Err(ValidationError::NegativeCount {
+                    field: "track_count",
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ValidateResponse for NotifyDigestResponse {
+    type Request = NotifyDigestRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        if self.stream_name != request.stream_name {
+            return Err(ValidationError::StreamMismatch);
+        }
+        for track in &self.tracks {
+            check_track_name(&track.track_info.track_name)?;
+            if track.first_unit > track.last_unit {
+                return Err(ValidationError::UnitsNotMonotonic {
+                    previous: track.first_unit,
+                    unit: track.last_unit,
+                });
+            }
+            if track.units_added < 0 {
+                return Err(ValidationError::NegativeCount {
+                    field: "units_added",
+                });
+            }
+            if track.notifications < 0 {
+                return Err(ValidationError::NegativeCount {
+                    field: "notifications",
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ValidateResponse for UnitBitmapResponse {
+    type Request = UnitBitmapRequest;
+
+    fn validate(&self, request: &Self::Request) -> Result<(), ValidationError> {
+        check_request_id(request.request_id, self.request_id)?;
+        check_track_name(&self.track_info.track_name)?;
+        if self.stream_name != request.stream_name
+            || self.track_info != request.track_info
+            || self.from_unit != request.from_unit
+        {
+            return Err(ValidationError::StreamMismatch);
+        }
+        match self.bitmap.runs().last() {
+            Some((_, last))
+                if request.from_unit.saturating_add((*last).into()) > request.to_unit =>
+            {
+                Err(ValidationError::UnitOutOfRange {
+                    unit: request.from_unit.saturating_add((*last).into()),
+                    from_unit: request.from_unit,
+                    to_unit: request.to_unit,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Limits a message is checked against before it's sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageLimits {
+    pub max_attributes: usize,
+    pub max_attributes_size: usize,
+    pub max_payload_size: usize,
+}
+
+impl Default for MessageLimits {
+    fn default() -> Self {
+        MessageLimits {
+            max_attributes: 256,
+            max_attributes_size: 64 * 1024,
+            max_payload_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+fn clamp<T: TryInto<i64>>(value: T) -> i64 {
+    value.try_into().unwrap_or(i64::MAX)
+}
+
+/// Errors of the parts of a message, in the order they are checked.
+struct Checks<'a> {
+    limits: &'a MessageLimits,
+    errors: Vec<ValidationError>,
+}
+
+impl Checks<'_> {
+    fn stream_name(&mut self, stream_name: &StreamName) {
+        if stream_name.iter().all(|b| *b == 0) {
+            self.errors.push(ValidationError::EmptyStreamName);
+        }
+    }
+
+    fn track_name(&mut self, track_name: &TrackName) {
+        let len = track_name
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |last| last + 1);
+        if str::from_utf8(&track_name[..len]).is_err() {
+            self.errors.push(ValidationError::InvalidTrackName);
+        }
+    }
+
+    fn track_info(&mut self, track_info: &TrackInfo) {
+        self.track_name(&track_info.track_name);
+    }
+
+    fn unit(&mut self, unit: &Unit) {
+        self.stream_name(&unit.stream_name);
+        self.track_name(&unit.track_name);
+    }
+
+    fn unit_range(&mut self, range: &UnitRange) {
+        self.range("units", range.from_unit, range.to_unit);
+        self.stream_name(&range.stream_name);
+        self.track_name(&range.track_name);
+    }
+
+    fn range<T: PartialOrd + TryInto<i64>>(&mut self, field: &'static str, from: T, to: T) {
+        if from > to {
+            self.errors.push(ValidationError::InvertedRange {
+                field,
+                from: clamp(from),
+                to: clamp(to),
+            });
+        }
+    }
+
+    fn attributes(&mut self, attributes: &Attributes) {
+        if attributes.len() > self.limits.max_attributes {
+            self.errors.push(ValidationError::TooManyAttributes {
+                count: attributes.len(),
+                limit: self.limits.max_attributes,
+            });
+        }
+        let size = attributes_size(attributes);
+        if size > self.limits.max_attributes_size {
+            self.errors.push(ValidationError::AttributesTooLarge {
+                size,
+                limit: self.limits.max_attributes_size,
+            });
+        }
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        if data.len() > self.limits.max_payload_size {
+            self.errors.push(ValidationError::PayloadTooLarge {
+                size: data.len(),
+                limit: self.limits.max_payload_size,
+            });
+        }
+    }
+
+    fn payload(&mut self, payload: &Payload) {
+        self.data(&payload.data);
+        self.attributes(&payload.attributes);
+    }
+
+    fn message(&mut self, message: &Message) {
+        match message {
+            Message::BackfillRequest(m) => m.ranges.iter().for_each(|r| self.unit_range(r)),
+            Message::BackfillResponse(m) => m.ranges.iter().for_each(|r| self.unit_range(r)),
+            Message::EncodingProfileAck(m) => {
+                self.stream_name(&m.stream_name);
+                m.unsupported_tracks.iter().for_each(|t| self.track_info(t));
+            }
+            Message::EncodingProfileOffer(m) => self.stream_name(&m.stream_name),
+            Message::ErrorResponse(m) => self.attributes(&m.details),
+            Message::FlowControlGrant(m) => {
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::FlowControlRequest(m) => {
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::InferenceResultMessage(m) => {
+                self.unit(&m.stream_unit);
+                m.detections
+                    .iter()
+                    .for_each(|d| self.attributes(&d.attributes));
+            }
+            Message::MaintenanceNotice(m) => m.streams.iter().for_each(|s| self.stream_name(s)),
+            Message::NotifyCadenceControl(m) => {
+                self.stream_name(&m.stream_name);
+                m.track_info.iter().for_each(|t| self.track_info(t));
+            }
+            Message::NotifyDigestRequest(m) => self.stream_name(&m.stream_name),
+            Message::NotifyDigestResponse(m) => {
+                self.stream_name(&m.stream_name);
+                m.tracks.iter().for_each(|t| self.track_info(&t.track_info));
+            }
+            Message::NotifyMessage(m) => {
+                self.unit(&m.stream_unit);
+                self.attributes(&m.attributes);
+            }
+            Message::ResolveAliasResponse(m) => {
+                m.stream_name.iter().for_each(|s| self.stream_name(s))
+            }
+            Message::RestoreStreamRequest(m) => self.stream_name(&m.stream_name),
+            Message::ServicesFFProbeRequest(m) => {
+                m.source_range.iter().for_each(|r| self.unit_range(r));
+                self.attributes(&m.attributes);
+            }
+            Message::ServicesFFProbeResponse(m) => {
+                m.streams.iter().for_each(|s| self.attributes(&s.extra));
+                m.format.iter().for_each(|f| self.attributes(&f.extra));
+            }
+            Message::ServicesFilmstripRequest(m) => {
+                self.range("time", m.from_ms, m.to_ms);
+                self.unit(&m.stream_unit);
+            }
+            Message::ServicesFilmstripResponse(m) => {
+                self.unit(&m.stream_unit);
+                m.tiles.iter().for_each(|p| self.payload(p));
+            }
+            Message::SetStreamAlias(m) => self.stream_name(&m.stream_name),
+            Message::SnapshotRequest(m) => self.unit(&m.stream_unit),
+            Message::SnapshotResponse(m) => self.payload(&m.image),
+            Message::SoftDeleteStreamRequest(m) => self.stream_name(&m.stream_name),
+            Message::StoragePurgeRequest(m) => {
+                self.stream_name(&m.stream_name);
+                m.track_info.iter().for_each(|t| self.track_info(t));
+            }
+            Message::StoragePurgeResponse(m) => self.stream_name(&m.stream_name),
+            Message::StorageRetentionSetRequest(m) => self.stream_name(&m.stream_name),
+            Message::StorageRetentionSetResponse(m) => self.stream_name(&m.stream_name),
+            Message::StorageStatsRequest(m) => {
+                m.stream_name.iter().for_each(|s| self.stream_name(s))
+            }
+            Message::StorageStatsResponse(m) => {
+                for stream in &m.streams {
+                    self.stream_name(&stream.stream_name);
+                    stream
+                        .tracks
+                        .iter()
+                        .for_each(|t| self.track_info(&t.track_info));
+                }
+            }
+            Message::StreamEndMessage(m) => {
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::StreamLifecycleMessage(m) => self.stream_name(&m.stream_name),
+            Message::StreamMultiTrackUnitElementsRequest(m) => {
+                self.stream_name(&m.stream_name);
+                m.tracks.iter().for_each(|t| self.track_info(t));
+            }
+            Message::StreamMultiTrackUnitElementsResponse(m) => {
+                self.stream_name(&m.stream_name);
+                for track in &m.tracks {
+                    self.track_info(&track.track_info);
+                    track.values.iter().for_each(|p| self.payload(p));
+                }
+            }
+            Message::StreamReplayRequest(m) => {
+                self.stream_name(&m.stream_name);
+                m.tracks.iter().for_each(|t| self.track_info(t));
+            }
+            Message::StreamTrackKeyframesRequest(m) => self.unit_range(&m.range),
+            Message::StreamTrackKeyframesResponse(m) => self.unit_range(&m.range),
+            Message::StreamTrackUnitElementsRequest(m) => self.unit(&m.stream_unit),
+            Message::StreamTrackUnitElementsResponse(m) => {
+                self.unit(&m.stream_unit);
+                m.values.iter().for_each(|p| self.payload(p));
+                m.track_names.iter().for_each(|t| self.track_name(t));
+            }
+            Message::StreamTrackUnitsByTimeRequest(m) => {
+                self.range("time", m.from_ms, m.to_ms);
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::StreamTrackUnitsByTimeResponse(m) => {
+                self.range("time", m.from_ms, m.to_ms);
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::StreamTrackUnitsRequest(m) => {
+                self.range("time", m.from_ms, m.to_ms);
+                self.unit(&m.stream_unit);
+            }
+            Message::StreamTrackUnitsResponse(m) => {
+                self.range("time", m.from_ms, m.to_ms);
+                self.unit(&m.stream_unit);
+                m.track_names.iter().for_each(|t| self.track_name(t));
+            }
+            Message::StreamTracksRequest(m) => self.stream_name(&m.stream_name),
+            Message::StreamTracksResponse(m) => {
+                self.stream_name(&m.stream_name);
+                m.tracks.iter().for_each(|t| self.track_info(t));
+                m.descriptors
+                    .iter()
+                    .for_each(|d| self.track_info(&d.track_info));
+            }
+            Message::StreamsDiscoveryResponse(m) => m
+                .streams
+                .iter()
+                .for_each(|s| self.stream_name(&s.stream_name)),
+            Message::TranscodeJobRequest(m) => {
+                m.source_range.iter().for_each(|r| self.unit_range(r));
+                self.attributes(&m.options);
+            }
+            Message::UnitBitmapRequest(m) => {
+                self.range("units", m.from_unit, m.to_unit);
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::UnitBitmapResponse(m) => {
+                self.range("units", m.from_unit, m.to_unit);
+                self.stream_name(&m.stream_name);
+                self.track_info(&m.track_info);
+            }
+            Message::UnitElementAckMessage(m) => self.unit(&m.stream_unit),
+            Message::UnitElementChunkMessage(m) => {
+                self.unit(&m.stream_unit);
+                self.data(&m.value);
+                self.attributes(&m.attributes);
+            }
+            Message::UnitElementMessage(m) => {
+                self.unit(&m.stream_unit);
+                self.data(&m.value);
+                self.attributes(&m.attributes);
+            }
+            // nothing to check, the extensions have no invariants known to the protocol
+            Message::AuthChallenge(_)
+            | Message::AuthResult(_)
+            | Message::HeartbeatMessage(_)
+            | Message::KeepAliveMessage(_)
+            | Message::NodeTelemetry(_)
+            | Message::PingRequestResponse(_)
+            | Message::ResolveAliasRequest(_)
+            | Message::SloReport(_)
+            | Message::StreamReplayStatus(_)
+            | Message::StreamReplayStop(_)
+            | Message::StreamsDiscoveryRequest(_)
+            | Message::TranscodeJobCancel(_)
+            | Message::TranscodeJobStatus(_)
+            | Message::Extension { .. } => {}
+        }
+    }
+}
+
+/// Checks the invariants the receiving end relies on, so an invalid message is
+/// caught by its producer: the names, the ranges and the sizes.
+impl Message {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        self.validate_with(&MessageLimits::default())
+    }
+
+    pub fn validate_with(&self, limits: &MessageLimits) -> Result<(), Vec<ValidationError>> {
+        let mut checks = Checks {
+            limits,
+            errors: vec![],
+        };
+        checks.message(self);
+        if checks.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(checks.errors)
+        }
+    }
+}
+
+/// See [`Message::validate`], a message which doesn't load into its kind is
+/// [`ValidationError::Malformed`].
+impl ProtocolMessage {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        self.validate_with(&MessageLimits::default())
+    }
+
+    pub fn validate_with(&self, limits: &MessageLimits) -> Result<(), Vec<ValidationError>> {
+        Message::load(self)
+            .ok_or_else(|| vec![ValidationError::Malformed])?
+            .validate_with(limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, ProtocolMessage, UNIT_BITMAP_REQUEST_SCHEMA};
+    use crate::message::Message;
+    use crate::objects::services::storage::stream_track_unit_elements::{
+        StreamTrackUnitElementsRequest, StreamTrackUnitElementsResponse,
+    };
+    use crate::objects::services::storage::stream_track_units::{
+        StreamTrackUnitsRequest, StreamTrackUnitsResponse,
+    };
+    use crate::objects::services::storage::unit_bitmap::UnitBitmapRequest;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{
+        pack_stream_name, pack_track_name, Payload, TrackInfo, TrackType, Unit,
+        TRACK_NAME_MAX_LENGTH,
+    };
+    use crate::utils::get_avro_path;
+    use crate::validation::{MessageLimits, ValidateResponse, ValidationError};
+    use avro_rs::types::Value;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn unit() -> Unit {
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let stream_name = pack_stream_name(&stream_uuid);
+        Unit::new(
+            stream_name.to_vec(),
+            track_name.to_vec(),
+            String::from("VIDEO"),
+            3,
+        )
+    }
+
+    #[test]
+    fn test_validate_message() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let message = StreamTrackUnitsRequest::new(1, String::from("topic"), unit(), 10, 20)
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.validate(), Ok(()));
+
+        let mut broken = Unit::new(vec![], vec![0xff, 0xfe], String::from("VIDEO"), 3);
+        broken.track_name[2] = b'a';
+        let message = StreamTrackUnitsRequest::new(1, String::from("topic"), broken, 20, 10)
+            .save(&mb)
+            .unwrap();
+        assert_eq!(
+            message.validate(),
+            Err(vec![
+                ValidationError::InvertedRange {
+                    field: "time",
+                    from: 20,
+                    to: 10
+                },
+                ValidationError::EmptyStreamName,
+                ValidationError::InvalidTrackName,
+            ])
+        );
+
+        let attributes = HashMap::from([("a".into(), "b".repeat(100).into())]);
+        let message = UnitElementMessage::new(unit(), 0, vec![0; 1024], attributes, true)
+            .save(&mb)
+            .unwrap();
+        assert_eq!(message.validate(), Ok(()));
+        let limits = MessageLimits {
+            max_attributes: 0,
+            max_attributes_size: 100,
+            max_payload_size: 1000,
+        };
+        assert_eq!(
+            message.validate_with(&limits),
+            Err(vec![
+                ValidationError::PayloadTooLarge {
+                    size: 1024,
+                    limit: 1000
+                },
+                ValidationError::TooManyAttributes { count: 1, limit: 0 },
+                ValidationError::AttributesTooLarge {
+                    size: 101,
+                    limit: 100
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_kind() {
+        let unit = unit();
+        let track_info = TrackInfo::new(TrackType::Video, unit.track_name);
+        let request = UnitBitmapRequest::new(
+            1,
+            String::from("topic"),
+            unit.stream_name,
+            track_info,
+            10,
+            20,
+        );
+        assert_eq!(
+            Message::UnitBitmapRequest(request.clone()).validate(),
+            Ok(())
+        );
+
+        let mut broken = TrackInfo::new(TrackType::Video, [0xff; TRACK_NAME_MAX_LENGTH]);
+        broken.track_name[0] = b'a';
+        let message = Message::UnitBitmapRequest(UnitBitmapRequest {
+            stream_name: Default::default(),
+            track_info: broken,
+            from_unit: 20,
+            to_unit: 10,
+            ..request
+        });
+        assert_eq!(
+            message.validate(),
+            Err(vec![
+                ValidationError::InvertedRange {
+                    field: "units",
+                    from: 20,
+                    to: 10
+                },
+                ValidationError::EmptyStreamName,
+                ValidationError::InvalidTrackName,
+            ])
+        );
+
+        // the fields of the extensions mean nothing to the protocol
+        let extension = Message::Extension {
+            name: String::from("acme.Clip"),
+            value: Value::Record(vec![(
+                String::from("stream_name"),
+                Value::Bytes(vec![0; 16]),
+            )]),
+        };
+        assert_eq!(extension.validate(), Ok(()));
+        let malformed = ProtocolMessage {
+            schema: String::from(UNIT_BITMAP_REQUEST_SCHEMA),
+            object: Value::Record(vec![]),
+        };
+        assert_eq!(malformed.validate(), Err(vec![ValidationError::Malformed]));
+    }
+
+    #[test]
+    fn test_units() {
+        let req = StreamTrackUnitsRequest::new(1, String::from("response"), unit(), 100, 500);
+
+        let rep = StreamTrackUnitsResponse::new(1, unit(), 100, 500, vec![1, 2, 3]);
+        assert_eq!(rep.validate(&req), Ok(()));
+
+        let rep = StreamTrackUnitsResponse::new(1, unit(), 100, 500, vec![1, 3, 2]);
+        assert_eq!(
+            rep.validate(&req),
+            Err(ValidationError::UnitsNotMonotonic {
+                previous: 3,
+                unit: 2
+            })
+        );
+
+        let rep = StreamTrackUnitsResponse::new(2, unit(), 100, 500, vec![]);
+        assert_eq!(
+            rep.validate(&req),
+            Err(ValidationError::RequestIdMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+
+        let mut unnamed = unit();
+        unnamed.track_name = Default::default();
+        let rep = StreamTrackUnitsResponse::new(1, unnamed, 100, 500, vec![]);
+        assert_eq!(rep.validate(&req), Err(ValidationError::EmptyTrackName));
+    }
+
+    #[test]
+    fn test_units_by_pattern() {
+        let req = StreamTrackUnitsRequest {
+            track_pattern: Some(String::from("meta-*")),
+            ..StreamTrackUnitsRequest::new(1, String::from("response"), unit(), 100, 500)
+        };
+        let faces = pack_track_name("meta-faces").unwrap();
+        let plates = pack_track_name("meta-plates").unwrap();
+
+        let rep = StreamTrackUnitsResponse {
+            track_names: vec![faces, plates, faces, plates],
+            ..StreamTrackUnitsResponse::new(1, unit(), 100, 500, vec![1, 1, 2, 2])
+        };
+        assert_eq!(rep.validate(&req), Ok(()));
+        assert_eq!(
+            rep.resolved_units(),
+            vec![(faces, 1), (plates, 1), (faces, 2), (plates, 2)]
+        );
+
+        let rep = StreamTrackUnitsResponse {
+            track_names: vec![faces, plates],
+            ..StreamTrackUnitsResponse::new(1, unit(), 100, 500, vec![1, 1, 2])
+        };
+        assert_eq!(
+            rep.validate(&req),
+            Err(ValidationError::CountMismatch {
+                field: "track_names",
+                declared: 3,
+                actual: 2
+            })
+        );
+
+        let rep = StreamTrackUnitsResponse {
+            track_names: vec![faces, pack_track_name("test").unwrap()],
+            ..StreamTrackUnitsResponse::new(1, unit(), 100, 500, vec![1, 1])
+        };
+        assert_eq!(rep.validate(&req), Err(ValidationError::StreamMismatch));
+    }
+
+    #[test]
+    fn test_unit_elements() {
+        let req = StreamTrackUnitElementsRequest::new(1, String::from("response"), unit(), 1);
+        let payload = Payload::new(vec![0], HashMap::default());
+
+        let rep = StreamTrackUnitElementsResponse::new(1, unit(), vec![payload.clone(); 2]);
+        assert_eq!(rep.validate(&req), Ok(()));
+
+        let rep = StreamTrackUnitElementsResponse::new(1, unit(), vec![payload; 3]);
+        assert_eq!(
+            rep.validate(&req),
+            Err(ValidationError::CountMismatch {
+                field: "values",
+                declared: 2,
+                actual: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use crate::avro::{
+    Builder, BuilderImpl, ProtocolMessage, STREAM_TRACKS_REQUEST_SCHEMA,
+    STREAM_TRACKS_RESPONSE_SCHEMA, STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA, STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_RESPONSE_SCHEMA, STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA, UNIT_ELEMENT_MESSAGE_SCHEMA,
+};
+use crate::json::{from_json, to_json};
+use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsResponse;
+use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+use crate::objects::FromProtocolMessage;
+use js_sys::{Array, Uint8Array, JSON};
+use serde_json::Value as Json;
+use wasm_bindgen::prelude::*;
+
+/// The messages a playback client sends and receives, the others are refused.
+pub const PLAYBACK_SCHEMAS: [&str; 11] = [
+    STREAM_TRACKS_REQUEST_SCHEMA,
+    STREAM_TRACKS_RESPONSE_SCHEMA,
+    STREAM_TRACK_UNIT_ELEMENTS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNIT_ELEMENTS_RESPONSE_SCHEMA,
+    STREAM_TRACK_UNITS_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_RESPONSE_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_REQUEST_SCHEMA,
+    STREAM_TRACK_UNITS_BY_TIME_RESPONSE_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_REQUEST_SCHEMA,
+    STREAM_TRACK_KEYFRAMES_RESPONSE_SCHEMA,
+    UNIT_ELEMENT_MESSAGE_SCHEMA,
+];
+
+fn check_playback(schema: &str) -> Result<(), String> {
+    if PLAYBACK_SCHEMAS.contains(&schema) {
+        Ok(())
+    } else {
+        Err(format!("{} isn't a playback message", schema))
+    }
+}
+
+/// Encodes the message given in the `{"schema": ..., "object": ...}` form of
+/// [`crate::json`].
+pub fn encode_playback(builder: &Builder, message: &Json) -> Result<Vec<u8>, String> {
+    let schema = message
+        .get("schema")
+        .and_then(Json::as_str)
+        .ok_or_else(|| String::from("No schema in the message"))?;
+    check_playback(schema)?;
+    let object = builder
+        .get_schema(schema)
+        .zip(message.get("object"))
+        .and_then(|(avro_schema, object)| from_json(&avro_schema, object))
+        .ok_or_else(|| format!("The object doesn't match {}", schema))?;
+    builder
+        .save_from_avro(ProtocolMessage {
+            schema: String::from(schema),
+            object,
+        })
+        .map_err(|e| e.to_string())
+}
+
+pub fn decode_playback(builder: &Builder, message: &[u8]) -> Result<ProtocolMessage, String> {
+    let (schema, object) = builder
+        .builder_impl()
+        .read_protocol_message(message)
+        .map_err(|e| e.to_string())?;
+    check_playback(&schema)?;
+    Ok(ProtocolMessage { schema, object })
+}
+
+/// The element values of the message in the order they were sent: the value of a
+/// unit element or the payloads of a unit elements response.
+pub fn element_values(message: &ProtocolMessage) -> Option<Vec<Vec<u8>>> {
+    if let Some(element) = UnitElementMessage::load(message) {
+        return Some(vec![element.value]);
+    }
+    StreamTrackUnitElementsResponse::load(message)
+        .map(|response| response.values.into_iter().map(|v| v.data).collect())
+}
+
+/// Schema registry and codec of the playback messages for the browser. The schemas
+/// are fetched by the page, there's no filesystem to read them from.
+#[wasm_bindgen(js_name = PlaybackBuilder)]
+pub struct WasmBuilder {
+    builder: Builder,
+}
+
+#[wasm_bindgen(js_class = PlaybackBuilder)]
+impl WasmBuilder {
+    /// Takes the sources of the files listed by `schemaFiles`, in any order.
+    #[wasm_bindgen(constructor)]
+    pub fn new(schemas: Array) -> Result<WasmBuilder, JsError> {
+        let sources: Vec<String> = schemas.iter().filter_map(|s| s.as_string()).collect();
+        if sources.len() < schemas.length() as usize {
+            return Err(JsError::new("Schemas must be strings"));
+        }
+        let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+        let builder =
+            BuilderImpl::from_schemas(&sources).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(WasmBuilder {
+            builder: Builder::from(builder),
+        })
+    }
+
+    /// Paths of the schema files relative to `API/avro/protocol`.
+    #[wasm_bindgen(js_name = schemaFiles)]
+    pub fn schema_files() -> Array {
+        BuilderImpl::schema_files()
+            .into_iter()
+            .map(|(dir, file)| JsValue::from(format!("{}/{}", dir, file)))
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = addVerificationKey)]
+    pub fn add_verification_key(&mut self, key_id: &str, key: Vec<u8>) {
+        self.builder.add_verification_key(key_id, key);
+    }
+
+    /// Encodes `{schema, object}`; bytes are base64 strings and `long` fields are
+    /// numbers, exact up to 2^53.
+    pub fn encode(&self, message: JsValue) -> Result<Vec<u8>, JsError> {
+        let json = JSON::stringify(&message)
+            .map_err(|_| JsError::new("The message has no JSON form"))?
+            .as_string()
+            .ok_or_else(|| JsError::new("The message has no JSON form"))?;
+        let message: Json =
+            serde_json::from_str(&json).map_err(|e| JsError::new(&e.to_string()))?;
+        encode_playback(&self.builder, &message).map_err(|e| JsError::new(&e))
+    }
+
+    pub fn decode(&self, message: &[u8]) -> Result<DecodedMessage, JsError> {
+        decode_playback(&self.builder, message)
+            .map(|message| DecodedMessage { message })
+            .map_err(|e| JsError::new(&e))
+    }
+}
+
+#[wasm_bindgen]
+pub struct DecodedMessage {
+    message: ProtocolMessage,
+}
+
+#[wasm_bindgen]
+impl DecodedMessage {
+    #[wasm_bindgen(getter)]
+    pub fn schema(&self) -> String {
+        self.message.schema.clone()
+    }
+
+    /// The object in the form [`WasmBuilder::encode`] takes.
+    pub fn object(&self) -> Result<JsValue, JsError> {
+        let json = to_json(&self.message.object).map_err(|e| JsError::new(&e))?;
+        JSON::parse(&json.to_string()).map_err(|_| JsError::new("Failed to parse the JSON"))
+    }
+
+    /// The element values without the base64 detour, `undefined` for the messages
+    /// which carry none.
+    #[wasm_bindgen(js_name = elementValues)]
+    pub fn element_values(&self) -> Option<Array> {
+        element_values(&self.message).map(|values| {
+            values
+                .iter()
+                .map(|v| JsValue::from(Uint8Array::from(v.as_slice())))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::Builder;
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::stream_track_unit_elements::StreamTrackUnitElementsResponse;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{Attributes, Payload, Unit};
+    use crate::utils::get_avro_path;
+    use crate::wasm::{decode_playback, element_values, encode_playback};
+
+    #[test]
+    fn test_playback_round_trip() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let unit = Unit::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), 7);
+        let values = vec![
+            Payload::new(b"foo".to_vec(), Attributes::default()),
+            Payload::new(b"bar".to_vec(), Attributes::default()),
+        ];
+        let response = StreamTrackUnitElementsResponse::new(1, unit, values)
+            .save(&mb)
+            .unwrap();
+
+        let json = serde_json::to_value(&response).unwrap();
+        let packed = encode_playback(&mb, &json).unwrap();
+        let read = decode_playback(&mb, &packed).unwrap();
+        assert_eq!(read, response);
+        assert_eq!(
+            element_values(&read).unwrap(),
+            vec![b"foo".to_vec(), b"bar".to_vec()]
+        );
+
+        let keep_alive = KeepAliveMessage::new(String::from("camera-1"))
+            .save(&mb)
+            .unwrap();
+        assert!(element_values(&keep_alive).is_none());
+        let json = serde_json::to_value(&keep_alive).unwrap();
+        assert!(encode_playback(&mb, &json).is_err());
+        assert!(decode_playback(&mb, &mb.save_from_avro(keep_alive).unwrap()).is_err());
+    }
+}
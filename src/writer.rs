@@ -0,0 +1,254 @@
+use avro_rs::types::Value;
+use avro_rs::{to_avro_datum, Schema};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Encoding plan of a schema, resolved once when the schema directory is loaded.
+#[derive(Debug)]
+enum Node {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Fixed(usize),
+    Enum(usize),
+    Array(Box<Node>),
+    Map(Box<Node>),
+    Union(Vec<Node>),
+    Record(Vec<(String, Node)>),
+    /// Logical types and the rest are left to the AVRO library.
+    Other(Schema),
+}
+
+impl Node {
+    fn compile(schema: &Schema) -> Node {
+        match schema {
+            Schema::Null => Node::Null,
+            Schema::Boolean => Node::Boolean,
+            Schema::Int => Node::Int,
+            Schema::Long => Node::Long,
+            Schema::Float => Node::Float,
+            Schema::Double => Node::Double,
+            Schema::Bytes => Node::Bytes,
+            Schema::String => Node::String,
+            Schema::Fixed { size, .. } => Node::Fixed(*size),
+            Schema::Enum { symbols, .. } => Node::Enum(symbols.len()),
+            Schema::Array(items) => Node::Array(Box::new(Node::compile(items))),
+            Schema::Map(values) => Node::Map(Box::new(Node::compile(values))),
+            Schema::Union(union) => {
+                Node::Union(union.variants().iter().map(Node::compile).collect())
+            }
+            Schema::Record { fields, .. } => Node::Record(
+                fields
+                    .iter()
+                    .map(|field| (field.name.clone(), Node::compile(&field.schema)))
+                    .collect(),
+            ),
+            other => Node::Other(other.clone()),
+        }
+    }
+
+    /// Whether the value is of this node, used to pick the branch of a union.
+    fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Node::Null, Value::Null)
+            | (Node::Boolean, Value::Boolean(_))
+            | (Node::Int, Value::Int(_))
+            | (Node::Long, Value::Long(_) | Value::Int(_))
+            | (Node::Float, Value::Float(_))
+            | (Node::Double, Value::Double(_))
+            | (Node::Bytes, Value::Bytes(_))
+            | (Node::String, Value::String(_))
+            | (Node::Enum(_), Value::Enum(_, _))
+            | (Node::Array(_), Value::Array(_))
+            | (Node::Map(_), Value::Map(_)) => true,
+            (Node::Fixed(size), Value::Fixed(len, _)) => size == len,
+            (Node::Record(fields), Value::Record(values)) => {
+                fields.len() == values.len()
+                    && fields
+                        .iter()
+                        .zip(values)
+                        .all(|((name, _), (value_name, _))| name == value_name)
+            }
+            (Node::Other(schema), value) => value.validate(schema),
+            _ => false,
+        }
+    }
+
+    fn write(&self, value: &Value, buffer: &mut Vec<u8>) -> Result<(), String> {
+        match (self, value) {
+            (Node::Null, Value::Null) => {}
+            (Node::Boolean, Value::Boolean(b)) => buffer.push(u8::from(*b)),
+            (Node::Int, Value::Int(i)) => write_long(i64::from(*i), buffer),
+            (Node::Long, Value::Int(i)) => write_long(i64::from(*i), buffer),
+            (Node::Long, Value::Long(l)) => write_long(*l, buffer),
+            (Node::Float, Value::Float(f)) => buffer.extend_from_slice(&f.to_le_bytes()),
+            (Node::Double, Value::Double(d)) => buffer.extend_from_slice(&d.to_le_bytes()),
+            (Node::Bytes, Value::Bytes(bytes)) => write_bytes(bytes, buffer),
+            (Node::String, Value::String(s)) => write_bytes(s.as_bytes(), buffer),
+            (Node::Fixed(size), Value::Fixed(len, bytes)) if size == len && bytes.len() == *len => {
+                buffer.extend_from_slice(bytes)
+            }
+            (Node::Enum(symbols), Value::Enum(index, _))
+                if *index >= 0 && (*index as usize) < *symbols =>
+            {
+                write_long(i64::from(*index), buffer)
+            }
+            (Node::Array(items), Value::Array(values)) => {
+                if !values.is_empty() {
+                    write_long(values.len() as i64, buffer);
+                    for value in values {
+                        items.write(value, buffer)?;
+                    }
+                }
+                buffer.push(0);
+            }
+            (Node::Map(node), Value::Map(values)) => {
+                if !values.is_empty() {
+                    write_long(values.len() as i64, buffer);
+                    for (key, value) in values {
+                        write_bytes(key.as_bytes(), buffer);
+                        node.write(value, buffer)?;
+                    }
+                }
+                buffer.push(0);
+            }
+            (Node::Union(variants), value) => {
+                let inner = match value {
+                    Value::Union(inner) => inner.as_ref(),
+                    other => other,
+                };
+                let index = variants
+                    .iter()
+                    .position(|variant| variant.accepts(inner))
+                    .ok_or_else(|| String::from("No union branch matches the value"))?;
+                write_long(index as i64, buffer);
+                variants[index].write(inner, buffer)?;
+            }
+            (Node::Record(fields), Value::Record(values)) if fields.len() == values.len() => {
+                for ((name, node), (value_name, value)) in fields.iter().zip(values) {
+                    if name != value_name {
+                        return Err(format!("Field {} is out of place", value_name));
+                    }
+                    node.write(value, buffer)
+                        .map_err(|e| format!("{}: {}", name, e))?;
+                }
+            }
+            (Node::Other(schema), value) => {
+                buffer.extend(to_avro_datum(schema, value.clone()).map_err(|e| e.to_string())?)
+            }
+            _ => return Err(String::from("Value doesn't match the schema")),
+        }
+        Ok(())
+    }
+}
+
+fn write_long(n: i64, buffer: &mut Vec<u8>) {
+    let mut z = ((n << 1) ^ (n >> 63)) as u64;
+    while z >= 0x80 {
+        buffer.push((z as u8) | 0x80);
+        z >>= 7;
+    }
+    buffer.push(z as u8);
+}
+
+fn write_bytes(bytes: &[u8], buffer: &mut Vec<u8>) {
+    write_long(bytes.len() as i64, buffer);
+    buffer.extend_from_slice(bytes);
+}
+
+/// Writer of a message kind. The schema is resolved when the writer is built, so
+/// encoding a message only walks the value; the buffer is sized after the
+/// previous message of the kind.
+#[derive(Debug)]
+pub struct DatumWriter {
+    root: Node,
+    size_hint: AtomicUsize,
+}
+
+impl DatumWriter {
+    pub fn new(schema: &Schema) -> Self {
+        DatumWriter {
+            root: Node::compile(schema),
+            size_hint: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn write(&self, value: &Value) -> Result<Vec<u8>, String> {
+        let mut buffer = Vec::with_capacity(self.size_hint.load(Ordering::Relaxed));
+        self.write_into(value, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Appends the encoded value to the buffer; a value which fails to encode leaves
+    /// the buffer as it was.
+    pub fn write_into(&self, value: &Value, buffer: &mut Vec<u8>) -> Result<(), String> {
+        let start = buffer.len();
+        buffer.reserve(self.size_hint.load(Ordering::Relaxed));
+        match self.root.write(value, buffer) {
+            Ok(()) => {
+                self.size_hint
+                    .store(buffer.len() - start, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                buffer.truncate(start);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl, MESSAGE_ENVELOPE_SCHEMA};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::stream_tracks::StreamTracksResponse;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{
+        pack_stream_name, pack_track_name, AttributeValue, Attributes, TrackInfo, TrackType, Unit,
+    };
+    use crate::utils::get_avro_path;
+    use crate::writer::DatumWriter;
+    use avro_rs::to_avro_datum;
+    use avro_rs::types::Value;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_matches_avro_encoding() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str());
+        let stream_uuid = Uuid::parse_str("fa807469-fbb3-4f63-b1a9-f63fbbf90f41").unwrap();
+        let track_name = pack_track_name(&String::from("test")).unwrap();
+        let unit = Unit::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), -5);
+        let attributes = Attributes::from([
+            (String::from("a"), AttributeValue::I64(i64::MIN)),
+            (String::from("b"), AttributeValue::String(String::from("c"))),
+        ]);
+        let messages = [
+            KeepAliveMessage::new(String::from("camera-1")).save(&mb),
+            UnitElementMessage::new(unit, 3, vec![0xaa; 300], attributes, true).save(&mb),
+            StreamTracksResponse::new(
+                1,
+                pack_stream_name(&stream_uuid),
+                vec![TrackInfo::new(TrackType::Video, track_name)],
+            )
+            .save(&mb),
+        ];
+
+        for message in messages.into_iter().map(Option::unwrap) {
+            let schema = builder.get_schema(&message.schema).unwrap();
+            let writer = DatumWriter::new(&schema);
+            let expected = to_avro_datum(&schema, message.object.clone()).unwrap();
+            assert_eq!(writer.write(&message.object).unwrap(), expected);
+            assert_eq!(writer.write(&message.object).unwrap(), expected);
+        }
+
+        let envelope = builder.get_schema(MESSAGE_ENVELOPE_SCHEMA).unwrap();
+        assert!(DatumWriter::new(&envelope).write(&Value::Null).is_err());
+    }
+}
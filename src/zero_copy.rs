@@ -0,0 +1,113 @@
+use crate::avro::{BuilderImpl, ProtocolError, UNIT_ELEMENT_MESSAGE_SCHEMA};
+use crate::primitives::{Attributes, Payload};
+use crate::scan::field_ranges;
+use avro_rs::Schema;
+use bytes::Bytes;
+use std::ops::Range;
+use std::sync::Arc;
+
+fn field<'r>(ranges: &'r [(&str, Range<usize>)], name: &str) -> Option<&'r Range<usize>> {
+    ranges
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, range)| range)
+}
+
+fn schema(builder: &BuilderImpl, schema_name: &str) -> Result<Arc<Schema>, ProtocolError> {
+    builder
+        .get_schema(schema_name)
+        .ok_or_else(|| ProtocolError::Decode(format!("No {} schema in the catalog", schema_name)))
+}
+
+/// The schema name and the serialized payload of the envelope; the payload is a
+/// slice of the message, nothing is copied. The signature is verified, the other
+/// checks of [`BuilderImpl::read_protocol_message`] are up to the consumer.
+pub fn envelope_payload(
+    builder: &BuilderImpl,
+    message: &Bytes,
+) -> Result<(String, Bytes), ProtocolError> {
+    let header = builder.read_header(message)?;
+    let payload = header.verified_payload()?;
+    Ok((String::from(header.schema()), message.slice_ref(payload)))
+}
+
+/// The value of a unit element, sliced out of the received message to be forwarded
+/// without copying. The value is as it was sent, compressed or encrypted ones stay so.
+pub fn unit_element_value(builder: &BuilderImpl, message: &Bytes) -> Result<Bytes, ProtocolError> {
+    let (schema_name, payload) = envelope_payload(builder, message)?;
+    if schema_name != UNIT_ELEMENT_MESSAGE_SCHEMA {
+        return Err(ProtocolError::Decode(format!(
+            "{} isn't a unit element",
+            schema_name
+        )));
+    }
+    let unit_element_schema = schema(builder, UNIT_ELEMENT_MESSAGE_SCHEMA)?;
+    let ranges = field_ranges(&unit_element_schema, &payload, &mut 0)
+        .ok_or_else(|| ProtocolError::Decode(String::from("Failed to scan the unit element")))?;
+    field(&ranges, "value")
+        .map(|range| payload.slice(range.clone()))
+        .ok_or_else(|| ProtocolError::Decode(String::from("No value in the unit element")))
+}
+
+impl Payload {
+    /// Takes over the buffer of `data` when nothing else refers to it.
+    pub fn from_bytes(data: Bytes, attributes: Attributes) -> Self {
+        Payload::new(Vec::from(data), attributes)
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        Bytes::from(self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{Builder, BuilderImpl, ProtocolError, UNIT_ELEMENT_MESSAGE_SCHEMA};
+    use crate::objects::services::keep_alive::KeepAliveMessage;
+    use crate::objects::services::storage::unit_element_message::UnitElementMessage;
+    use crate::objects::ToProtocolMessage;
+    use crate::primitives::{Attributes, Payload, Unit};
+    use crate::utils::get_avro_path;
+    use crate::zero_copy::{envelope_payload, unit_element_value};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_value_is_sliced() {
+        let mb = Builder::new(get_avro_path().as_str());
+        let builder = BuilderImpl::new(get_avro_path().as_str()).with_signing_key("k1", b"secret");
+        let unit = Unit::new(vec![1; 16], vec![2; 16], String::from("VIDEO"), 7);
+        let value = vec![0xab; 4096];
+        let message = UnitElementMessage::new(unit, 0, value.clone(), Attributes::default(), true)
+            .save(&mb)
+            .unwrap();
+        let packed = Bytes::from(
+            builder
+                .pack_message_with_labels(&message.schema, message.object, &Default::default())
+                .unwrap(),
+        );
+
+        let (schema_name, _) = envelope_payload(&builder, &packed).unwrap();
+        assert_eq!(schema_name, UNIT_ELEMENT_MESSAGE_SCHEMA);
+        let sliced = unit_element_value(&builder, &packed).unwrap();
+        assert_eq!(sliced, value);
+        let start = packed.as_ptr() as usize;
+        assert!((start..start + packed.len()).contains(&(sliced.as_ptr() as usize)));
+
+        let mut tampered = packed.to_vec();
+        tampered[sliced.as_ptr() as usize - start] ^= 1;
+        assert_eq!(
+            unit_element_value(&builder, &Bytes::from(tampered)),
+            Err(ProtocolError::SignatureMismatch)
+        );
+
+        let keep_alive = KeepAliveMessage::new(String::from("camera-1"))
+            .save(&mb)
+            .unwrap();
+        let packed = Bytes::from(mb.save_from_avro(keep_alive).unwrap());
+        assert!(unit_element_value(&builder, &packed).is_err());
+        assert!(envelope_payload(&builder, &packed.slice(..3)).is_err());
+
+        let payload = Payload::from_bytes(Bytes::from(value.clone()), Attributes::default());
+        assert_eq!(payload.into_bytes(), value);
+    }
+}